@@ -0,0 +1,318 @@
+//! Spoken recording state for screen-reader users (`a11y_announcements`): recording started
+//! and stopped, transcript ready, pasted and failures. macOS asks VoiceOver to read the text
+//! out; other systems show a notification, which Narrator and Orca read. Announcements are
+//! rate limited so a burst of state changes does not talk over the user.
+
+use crate::locks::lock_or_recover;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The same announcement again within this long is dropped.
+pub const REPEAT_INTERVAL: Duration = Duration::from_secs(2);
+/// At most [`BURST_LIMIT`] announcements are read within this long.
+pub const BURST_WINDOW: Duration = Duration::from_secs(10);
+pub const BURST_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Announcement {
+    RecordingStarted,
+    RecordingStopped,
+    TranscriptReady { words: usize },
+    Pasted,
+    MicrophoneLost,
+    TranscriptionFailed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    RecordingStarted,
+    RecordingStopped,
+    TranscriptReady,
+    Pasted,
+    MicrophoneLost,
+    TranscriptionFailed,
+}
+
+impl Announcement {
+    fn kind(self) -> Kind {
+        match self {
+            Announcement::RecordingStarted => Kind::RecordingStarted,
+            Announcement::RecordingStopped => Kind::RecordingStopped,
+            Announcement::TranscriptReady { .. } => Kind::TranscriptReady,
+            Announcement::Pasted => Kind::Pasted,
+            Announcement::MicrophoneLost => Kind::MicrophoneLost,
+            Announcement::TranscriptionFailed => Kind::TranscriptionFailed,
+        }
+    }
+
+    fn is_error(self) -> bool {
+        matches!(
+            self,
+            Announcement::MicrophoneLost | Announcement::TranscriptionFailed
+        )
+    }
+
+    /// What is read out, in `language`.
+    pub fn text(self, language: Language) -> String {
+        match (self, language) {
+            (Announcement::RecordingStarted, Language::English) => "Recording".to_string(),
+            (Announcement::RecordingStarted, Language::Portuguese) => "Gravando".to_string(),
+            (Announcement::RecordingStopped, Language::English) => {
+                "Recording stopped, transcribing".to_string()
+            }
+            (Announcement::RecordingStopped, Language::Portuguese) => {
+                "Gravação parada, transcrevendo".to_string()
+            }
+            (Announcement::TranscriptReady { words: 1 }, Language::English) => {
+                "Transcript ready, 1 word".to_string()
+            }
+            (Announcement::TranscriptReady { words }, Language::English) => {
+                format!("Transcript ready, {} words", words)
+            }
+            (Announcement::TranscriptReady { words: 1 }, Language::Portuguese) => {
+                "Transcrição pronta, 1 palavra".to_string()
+            }
+            (Announcement::TranscriptReady { words }, Language::Portuguese) => {
+                format!("Transcrição pronta, {} palavras", words)
+            }
+            (Announcement::Pasted, Language::English) => "Pasted".to_string(),
+            (Announcement::Pasted, Language::Portuguese) => "Texto colado".to_string(),
+            (Announcement::MicrophoneLost, Language::English) => {
+                "Recording stopped, the microphone was disconnected".to_string()
+            }
+            (Announcement::MicrophoneLost, Language::Portuguese) => {
+                "Gravação interrompida, o microfone foi desconectado".to_string()
+            }
+            (Announcement::TranscriptionFailed, Language::English) => {
+                "Transcription failed".to_string()
+            }
+            (Announcement::TranscriptionFailed, Language::Portuguese) => {
+                "A transcrição falhou".to_string()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    Portuguese,
+}
+
+impl Language {
+    /// From the `language` setting; `auto` and anything unknown read English.
+    pub fn from_setting(language: &str) -> Self {
+        if language.trim().eq_ignore_ascii_case("pt") {
+            Language::Portuguese
+        } else {
+            Language::English
+        }
+    }
+}
+
+/// Drops an announcement that repeats the previous one of its kind within
+/// [`REPEAT_INTERVAL`], and any past [`BURST_LIMIT`] in [`BURST_WINDOW`]. Errors only obey
+/// the first rule; a failure is never swallowed because the recording was chatty.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    last_by_kind: HashMap<Kind, Instant>,
+    recent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// Whether `announcement` may be read at `now`; counts it when it may.
+    pub fn allow(&mut self, announcement: Announcement, now: Instant) -> bool {
+        let kind = announcement.kind();
+        if let Some(last) = self.last_by_kind.get(&kind) {
+            if now.saturating_duration_since(*last) < REPEAT_INTERVAL {
+                return false;
+            }
+        }
+        while self
+            .recent
+            .front()
+            .is_some_and(|sent| now.saturating_duration_since(*sent) >= BURST_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= BURST_LIMIT && !announcement.is_error() {
+            return false;
+        }
+        self.last_by_kind.insert(kind, now);
+        self.recent.push_back(now);
+        true
+    }
+}
+
+/// The OS side: reads `text` to the user.
+pub trait Announcer: Send + Sync {
+    fn announce(&self, text: &str);
+}
+
+/// Announcement state shared by the app; silent until enabled and an [`Announcer`] is
+/// attached.
+#[derive(Default)]
+pub struct Announcements {
+    enabled: AtomicBool,
+    language: Mutex<Language>,
+    limiter: Mutex<RateLimiter>,
+    announcer: Mutex<Option<Arc<dyn Announcer>>>,
+}
+
+impl Announcements {
+    pub fn attach(&self, announcer: Arc<dyn Announcer>) {
+        *lock_or_recover(&self.announcer, "announcer") = Some(announcer);
+    }
+
+    pub fn configure(&self, enabled: bool, language: Language) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        *lock_or_recover(&self.language, "announcement language") = language;
+    }
+
+    pub fn announce(&self, announcement: Announcement) {
+        self.announce_at(announcement, Instant::now());
+    }
+
+    /// Returns whether the announcement was read.
+    fn announce_at(&self, announcement: Announcement, now: Instant) -> bool {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return false;
+        }
+        let Some(announcer) = lock_or_recover(&self.announcer, "announcer").clone() else {
+            return false;
+        };
+        if !lock_or_recover(&self.limiter, "announcement limiter").allow(announcement, now) {
+            tracing::debug!("Announcement dropped by the rate limit: {:?}", announcement);
+            return false;
+        }
+        let language = *lock_or_recover(&self.language, "announcement language");
+        announcer.announce(&announcement.text(language));
+        true
+    }
+}
+
+/// Ask VoiceOver to read `text` out. Call on the main thread.
+#[cfg(target_os = "macos")]
+pub fn post_voiceover(text: &str) {
+    use core_foundation::base::TCFType;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::c_void;
+
+    /// `NSAccessibilityPriorityHigh`: interrupts what VoiceOver is reading
+    const PRIORITY_HIGH: i64 = 90;
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSApp: *mut c_void;
+        static NSAccessibilityAnnouncementRequestedNotification: CFStringRef;
+        static NSAccessibilityAnnouncementKey: CFStringRef;
+        static NSAccessibilityPriorityKey: CFStringRef;
+        fn NSAccessibilityPostNotificationWithUserInfo(
+            element: *mut c_void,
+            notification: CFStringRef,
+            user_info: CFDictionaryRef,
+        );
+    }
+
+    // SAFETY: the statics are AppKit constants, valid once AppKit is loaded, and `NSApp`
+    // is set before Tauri runs any main-thread task. The strings are toll-free bridged to
+    // NSString and the dictionary to NSDictionary.
+    unsafe {
+        if NSApp.is_null() {
+            return;
+        }
+        let info = CFDictionary::from_CFType_pairs(&[
+            (
+                CFString::wrap_under_get_rule(NSAccessibilityAnnouncementKey).as_CFType(),
+                CFString::new(text).as_CFType(),
+            ),
+            (
+                CFString::wrap_under_get_rule(NSAccessibilityPriorityKey).as_CFType(),
+                CFNumber::from(PRIORITY_HIGH).as_CFType(),
+            ),
+        ]);
+        NSAccessibilityPostNotificationWithUserInfo(
+            NSApp,
+            NSAccessibilityAnnouncementRequestedNotification,
+            info.as_concrete_TypeRef(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Announcer for Recorder {
+        fn announce(&self, text: &str) {
+            self.0.lock().unwrap().push(text.to_string());
+        }
+    }
+
+    #[test]
+    fn test_text_follows_the_language_setting() {
+        let ready = Announcement::TranscriptReady { words: 12 };
+        assert_eq!(
+            ready.text(Language::from_setting("pt")),
+            "Transcrição pronta, 12 palavras"
+        );
+        assert_eq!(
+            ready.text(Language::from_setting(" EN ")),
+            "Transcript ready, 12 words"
+        );
+        assert_eq!(
+            Announcement::TranscriptReady { words: 1 }.text(Language::Portuguese),
+            "Transcrição pronta, 1 palavra"
+        );
+        assert_eq!(Language::from_setting("auto"), Language::English);
+        assert_eq!(
+            Announcement::RecordingStarted.text(Language::Portuguese),
+            "Gravando"
+        );
+    }
+
+    #[test]
+    fn test_repeats_and_bursts_are_dropped_but_errors_get_through() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut limiter = RateLimiter::default();
+
+        assert!(limiter.allow(Announcement::RecordingStarted, at(0)));
+        assert!(!limiter.allow(Announcement::RecordingStarted, at(500)));
+        assert!(limiter.allow(Announcement::RecordingStarted, at(2_500)));
+        assert!(limiter.allow(Announcement::RecordingStopped, at(2_600)));
+        assert!(limiter.allow(Announcement::TranscriptReady { words: 3 }, at(2_700)));
+        // A different word count is still the same announcement.
+        assert!(!limiter.allow(Announcement::TranscriptReady { words: 4 }, at(2_800)));
+        assert!(limiter.allow(Announcement::Pasted, at(2_900)));
+        // Five in the last ten seconds: the burst cap holds the next one back...
+        assert!(!limiter.allow(Announcement::RecordingStarted, at(5_000)));
+        // ...but not a failure.
+        assert!(limiter.allow(Announcement::TranscriptionFailed, at(5_100)));
+        // Once the first ones age out there is room again.
+        assert!(limiter.allow(Announcement::RecordingStarted, at(12_600)));
+    }
+
+    #[test]
+    fn test_nothing_is_read_while_disabled() {
+        let recorder = Arc::new(Recorder::default());
+        let announcements = Announcements::default();
+        announcements.attach(recorder.clone());
+        let now = Instant::now();
+
+        assert!(!announcements.announce_at(Announcement::RecordingStarted, now));
+        announcements.configure(true, Language::Portuguese);
+        assert!(announcements.announce_at(Announcement::Pasted, now));
+        announcements.configure(false, Language::Portuguese);
+        assert!(!announcements.announce_at(Announcement::RecordingStopped, now));
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["Texto colado"]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Versioned payloads for consumers outside the app, so far the history items mirrored to a
+//! folder. Every field is spelled out here instead of serializing internal types, so a
+//! refactor of `HistoryItem` cannot change what an integrator receives. A breaking change
+//! means adding `*V2` types next to these, never editing V1; the snapshot tests pin the shapes.
+
+use crate::config::{HistoryItem, HistoryStatus, HistoryVersion};
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Sent as `schema_version` in every outbound payload.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryStatusV1 {
+    Transcribed,
+    /// Recorded offline; `text` is a placeholder until the audio is transcribed
+    PendingTranscription,
+}
+
+impl From<HistoryStatus> for HistoryStatusV1 {
+    fn from(status: HistoryStatus) -> Self {
+        match status {
+            HistoryStatus::Transcribed => Self::Transcribed,
+            HistoryStatus::PendingTranscription => Self::PendingTranscription,
+        }
+    }
+}
+
+/// An earlier transcription of the same audio.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HistoryVersionV1 {
+    pub text: String,
+    pub provider: String,
+    /// RFC 3339
+    pub created_at: String,
+}
+
+impl From<&HistoryVersion> for HistoryVersionV1 {
+    fn from(version: &HistoryVersion) -> Self {
+        Self {
+            text: version.text.clone(),
+            provider: version.provider.clone(),
+            created_at: version.created_at.clone(),
+        }
+    }
+}
+
+/// A history entry as integrators see it. The archived audio path stays local.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HistoryItemV1 {
+    pub schema_version: u32,
+    pub id: String,
+    pub request_id: Option<String>,
+    pub text: String,
+    /// RFC 3339
+    pub recorded_at: String,
+    pub duration_seconds: f32,
+    pub word_count: u32,
+    pub status: HistoryStatusV1,
+    /// Earlier transcriptions, newest first
+    pub versions: Vec<HistoryVersionV1>,
+}
+
+impl From<&HistoryItem> for HistoryItemV1 {
+    fn from(item: &HistoryItem) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id: item.id.clone(),
+            request_id: item.request_id.clone(),
+            text: item.text.clone(),
+            recorded_at: item.timestamp.clone(),
+            duration_seconds: item.duration_seconds,
+            word_count: item.word_count,
+            status: item.status.into(),
+            versions: item.versions.iter().map(HistoryVersionV1::from).collect(),
+        }
+    }
+}
+
+/// JSON Schema of every outbound payload, keyed by type name.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiSchema {
+    pub schema_version: u32,
+    pub schemas: BTreeMap<&'static str, RootSchema>,
+}
+
+pub fn api_schema() -> ApiSchema {
+    ApiSchema {
+        schema_version: SCHEMA_VERSION,
+        schemas: BTreeMap::from([("HistoryItemV1", schema_for!(HistoryItemV1))]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn history_item() -> HistoryItem {
+        HistoryItem {
+            id: "h1".to_string(),
+            text: "Reunião às dez".to_string(),
+            timestamp: "2026-03-01T10:00:00Z".to_string(),
+            duration_seconds: 3.5,
+            word_count: 3,
+            provider: Some("groq".to_string()),
+            audio_path: Some("/home/ana/.zentra/audio/h1.wav".to_string()),
+            versions: vec![HistoryVersion {
+                text: "Reunião as dez".to_string(),
+                provider: "groq".to_string(),
+                created_at: "2026-03-01T10:00:02Z".to_string(),
+            }],
+            status: HistoryStatus::Transcribed,
+            request_id: Some("r1".to_string()),
+            session_stats: None,
+        }
+    }
+
+    fn snapshot<T: Serialize>(value: &T) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_v1_payloads_keep_their_shape() {
+        let item = history_item();
+        let history_v1 = json!({
+            "schema_version": 1,
+            "id": "h1",
+            "request_id": "r1",
+            "text": "Reunião às dez",
+            "recorded_at": "2026-03-01T10:00:00Z",
+            "duration_seconds": 3.5,
+            "word_count": 3,
+            "status": "transcribed",
+            "versions": [{
+                "text": "Reunião as dez",
+                "provider": "groq",
+                "created_at": "2026-03-01T10:00:02Z"
+            }]
+        });
+        assert_eq!(snapshot(&HistoryItemV1::from(&item)), history_v1);
+
+        let pending = HistoryItem {
+            status: HistoryStatus::PendingTranscription,
+            ..item
+        };
+        assert_eq!(
+            snapshot(&HistoryItemV1::from(&pending))["status"],
+            "pending_transcription"
+        );
+    }
+
+    #[test]
+    fn test_schema_lists_every_payload_and_requires_its_version() {
+        let schema = snapshot(&api_schema());
+        assert_eq!(schema["schema_version"], 1);
+        let schemas = schema["schemas"].as_object().unwrap();
+        assert_eq!(schemas.keys().collect::<Vec<_>>(), vec!["HistoryItemV1"]);
+        for (name, schema) in schemas {
+            let required = schema["required"].as_array().unwrap();
+            assert!(
+                required.contains(&json!("schema_version")),
+                "{} does not require schema_version",
+                name
+            );
+        }
+    }
+}
@@ -1,153 +1,646 @@
+use crate::audio::collector::{
+    self, CaptureQuality, Collector, OverrunCounter, Retention, SampleWriter, KEEP_ALL,
+};
+use crate::audio::devices::{CpalHosts, HostedDevice, InputCatalog};
 use crate::audio::AudioBuffer;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use crate::locks::lock_or_recover;
+use cpal::traits::{DeviceTrait, StreamTrait};
+use serde::Serialize;
+use std::fmt;
 use std::sync::{
-    atomic::{AtomicU32, Ordering},
+    atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering},
     Arc, Mutex,
 };
 use tracing::{error, info, warn};
+use zentra_core::error_codes::ErrorCode;
+
+/// Level meter gain unless `AppConfig.input_gain` sets one; speech RMS is far below 1.0.
+pub const DEFAULT_INPUT_GAIN: f32 = 2.5;
+/// Longest pre-roll a standby stream keeps.
+pub const MAX_PRE_ROLL_MS: u32 = 1_000;
+/// Share of each new callback's RMS in the smoothed level.
+const RMS_SMOOTHING: f32 = 0.5;
+/// Peak, full scale being 1.0, at which a window counts as clipped.
+pub const CLIP_PEAK: f32 = 0.99;
+/// Clipped windows in a row before the meter reports clipping; one loud click is not.
+pub const CLIP_WINDOWS: u32 = 3;
+/// A real microphone never delivers this long a run of exact zeros; a device held
+/// exclusively by another app often does.
+pub const EXCLUSIVE_PROBE_SECS: f32 = 1.5;
+/// Backend messages that mean another process owns the device (WASAPI
+/// `AUDCLNT_E_DEVICE_IN_USE`, ALSA `EBUSY`, CoreAudio hog mode).
+const BUSY_MARKERS: [&str; 6] = [
+    "0x8889000a",
+    "device_in_use",
+    "in use",
+    "busy",
+    "exclusive",
+    "hog mode",
+];
+
+/// Why capture failed. `code()` is stable and safe to match on in the frontend.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureError {
+    AlreadyRecording,
+    NoInputDevice,
+    /// Another application holds the device (often in exclusive mode).
+    DeviceBusy {
+        device: String,
+    },
+    UnsupportedFormat {
+        format: String,
+    },
+    Backend {
+        message: String,
+    },
+}
+
+impl CaptureError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            CaptureError::AlreadyRecording => "already_recording",
+            CaptureError::NoInputDevice => "no_input_device",
+            CaptureError::DeviceBusy { .. } => "device_busy",
+            CaptureError::UnsupportedFormat { .. } => "unsupported_format",
+            CaptureError::Backend { .. } => "capture_failed",
+        }
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            CaptureError::AlreadyRecording => ErrorCode::RecAlreadyRecording,
+            CaptureError::NoInputDevice => ErrorCode::RecNoDevice,
+            CaptureError::DeviceBusy { .. } => ErrorCode::RecDeviceBusy,
+            CaptureError::UnsupportedFormat { .. } => ErrorCode::RecUnsupportedFormat,
+            CaptureError::Backend { .. } => ErrorCode::RecCaptureFailed,
+        }
+    }
+
+    pub fn device(&self) -> Option<&str> {
+        match self {
+            CaptureError::DeviceBusy { device } => Some(device),
+            _ => None,
+        }
+    }
+
+    fn from_backend(device: &str, description: &str) -> Self {
+        let lower = description.to_ascii_lowercase();
+        if BUSY_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            CaptureError::DeviceBusy {
+                device: device.to_string(),
+            }
+        } else {
+            CaptureError::Backend {
+                message: description.to_string(),
+            }
+        }
+    }
+
+    // `DeviceNotAvailable` right after the device was enumerated means it could not be
+    // opened, which both WASAPI and ALSA report when another process holds it.
+    fn from_config_error(device: &str, err: cpal::DefaultStreamConfigError) -> Self {
+        match err {
+            cpal::DefaultStreamConfigError::DeviceNotAvailable => CaptureError::DeviceBusy {
+                device: device.to_string(),
+            },
+            cpal::DefaultStreamConfigError::BackendSpecific { err } => {
+                Self::from_backend(device, &err.description)
+            }
+            other => CaptureError::Backend {
+                message: other.to_string(),
+            },
+        }
+    }
+
+    fn from_build_error(device: &str, format: &str, err: cpal::BuildStreamError) -> Self {
+        match err {
+            cpal::BuildStreamError::DeviceNotAvailable => CaptureError::DeviceBusy {
+                device: device.to_string(),
+            },
+            cpal::BuildStreamError::StreamConfigNotSupported => CaptureError::UnsupportedFormat {
+                format: format.to_string(),
+            },
+            cpal::BuildStreamError::BackendSpecific { err } => {
+                Self::from_backend(device, &err.description)
+            }
+            other => CaptureError::Backend {
+                message: other.to_string(),
+            },
+        }
+    }
+
+    fn from_play_error(device: &str, err: cpal::PlayStreamError) -> Self {
+        match err {
+            cpal::PlayStreamError::DeviceNotAvailable => CaptureError::DeviceBusy {
+                device: device.to_string(),
+            },
+            cpal::PlayStreamError::BackendSpecific { err } => {
+                Self::from_backend(device, &err.description)
+            }
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::AlreadyRecording => write!(f, "Already recording"),
+            CaptureError::NoInputDevice => write!(f, "No input device available"),
+            CaptureError::DeviceBusy { device } => write!(
+                f,
+                "'{}' is in use by another application. Close apps that may hold it \
+                 exclusively (Discord, OBS, ...) or pick a different input device.",
+                device
+            ),
+            CaptureError::UnsupportedFormat { format } => {
+                write!(f, "Unsupported sample format: {}", format)
+            }
+            CaptureError::Backend { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// True when at least `window_secs` of audio arrived and all of it is exact zeros.
+pub fn is_digital_silence(buffer: &AudioBuffer, window_secs: f32) -> bool {
+    let window =
+        (window_secs * buffer.sample_rate as f32) as usize * buffer.channels.max(1) as usize;
+    window > 0
+        && buffer.samples.len() >= window
+        && buffer.samples[..window].iter().all(|&sample| sample == 0)
+}
+
+/// Capture format negotiated with the input device for the current recording.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureInfo {
+    pub device_name: String,
+    /// Audio host the device was opened on (`WASAPI`, `ALSA`, ...)
+    pub host: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: String,
+    /// Audio from before the start that the recording begins with; in standby, how much
+    /// the stream keeps
+    pub pre_roll_ms: u32,
+}
+
+impl CaptureInfo {
+    /// Same device opened in the same format.
+    fn same_input(&self, other: &CaptureInfo) -> bool {
+        self.device_name == other.device_name
+            && self.host == other.host
+            && self.sample_rate == other.sample_rate
+            && self.channels == other.channels
+            && self.sample_format == other.sample_format
+    }
+}
 
-const RMS_BOOST: f32 = 2.5;
+/// Samples `ms` of audio take in this format, in whole frames.
+fn pre_roll_samples(ms: u32, sample_rate: u32, channels: u16) -> usize {
+    (u64::from(ms) * u64::from(sample_rate) / 1_000) as usize * usize::from(channels)
+}
+
+/// Set by the stream's error callback when the device stops delivering audio, usually
+/// because it was unplugged. Holds the backend's message.
+pub type CaptureFault = Arc<Mutex<Option<String>>>;
+
+/// Underruns cost a few samples and the stream keeps running; anything else ends it.
+fn is_fatal_stream_error(err: &cpal::StreamError) -> bool {
+    !matches!(err, cpal::StreamError::BufferUnderrun)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelReading {
+    /// Smoothed RMS scaled by the input gain, 0.0 to 1.0
+    pub rms: f32,
+    /// Highest sample since the previous reading, 0.0 to 1.0
+    pub peak: f32,
+}
+
+/// Input level written by the capture callback and read by the level loop. The smoothed
+/// RMS and the peak share one atomic, so a reading never mixes two windows.
+pub struct LevelMeter {
+    /// RMS bits in the high half, peak bits in the low half
+    packed: AtomicU64,
+    gain: AtomicU32,
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self {
+            packed: AtomicU64::new(0),
+            gain: AtomicU32::new(DEFAULT_INPUT_GAIN.to_bits()),
+        }
+    }
+}
+
+impl LevelMeter {
+    pub fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    fn record(&self, rms: f32, peak: f32) {
+        let scaled = (rms * f32::from_bits(self.gain.load(Ordering::Relaxed))).clamp(0.0, 1.0);
+        let _ = self
+            .packed
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                let previous = unpack(bits);
+                Some(pack(LevelReading {
+                    rms: previous.rms + (scaled - previous.rms) * RMS_SMOOTHING,
+                    peak: previous.peak.max(peak.clamp(0.0, 1.0)),
+                }))
+            });
+    }
+
+    /// The current level; the peak starts over for the next reading.
+    pub fn take(&self) -> LevelReading {
+        let bits = self
+            .packed
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some(pack(LevelReading {
+                    peak: 0.0,
+                    ..unpack(bits)
+                }))
+            })
+            .unwrap_or_else(|bits| bits);
+        unpack(bits)
+    }
+
+    fn reset(&self) {
+        self.packed.store(0, Ordering::Relaxed);
+    }
+}
 
+fn pack(reading: LevelReading) -> u64 {
+    ((reading.rms.to_bits() as u64) << 32) | reading.peak.to_bits() as u64
+}
+
+fn unpack(bits: u64) -> LevelReading {
+    LevelReading {
+        rms: f32::from_bits((bits >> 32) as u32),
+        peak: f32::from_bits(bits as u32),
+    }
+}
+
+/// Turns per-reading peaks into a clipping flag: [`CLIP_WINDOWS`] clipped readings in a
+/// row start it, the first clean one ends it.
+#[derive(Debug, Default)]
+pub struct ClipDetector {
+    clipped_in_a_row: u32,
+}
+
+impl ClipDetector {
+    pub fn update(&mut self, peak: f32) -> bool {
+        self.clipped_in_a_row = if peak >= CLIP_PEAK {
+            self.clipped_in_a_row.saturating_add(1)
+        } else {
+            0
+        };
+        self.clipped_in_a_row >= CLIP_WINDOWS
+    }
+}
+
+/// The device picked for a recording, its default input format and what capture reports
+/// about them.
+type Negotiated<'a> = (
+    &'a HostedDevice<cpal::Device>,
+    cpal::SupportedStreamConfig,
+    CaptureInfo,
+);
+
+/// Opens the input and collects what it delivers. Besides recording, the stream can run
+/// in standby: open between recordings, metering the level but keeping only the last
+/// `pre_roll_ms` of audio. A recording started from standby carries on with the same
+/// stream, so it begins with that audio instead of after the device has warmed up.
 pub struct AudioCapture {
     stream: Option<cpal::Stream>,
+    /// Drains the stream's ring into `buffer` while recording
+    collector: Option<Collector>,
     is_recording: bool,
     buffer: Arc<Mutex<AudioBuffer>>,
-    level: Arc<AtomicU32>,
-    selected_input_device: Option<String>,
+    level: Arc<LevelMeter>,
+    fault: CaptureFault,
+    overruns: Arc<OverrunCounter>,
+    /// What the collector keeps of `buffer`: everything while recording, the pre-roll in
+    /// standby
+    retention: Retention,
+    /// The input the standby stream was opened on; `None` when there is no standby
+    standby: Option<CaptureInfo>,
 }
 
 impl AudioCapture {
     pub fn new() -> Self {
         Self {
             stream: None,
+            collector: None,
             is_recording: false,
             buffer: Arc::new(Mutex::new(AudioBuffer::new(16000, 1))),
-            level: Arc::new(AtomicU32::new(0.0f32.to_bits())),
-            selected_input_device: None,
+            level: Arc::new(LevelMeter::default()),
+            fault: Arc::new(Mutex::new(None)),
+            overruns: Arc::new(OverrunCounter::default()),
+            retention: Arc::new(AtomicUsize::new(KEEP_ALL)),
+            standby: None,
         }
     }
 
-    pub fn start(&mut self) -> Result<(), String> {
+    /// `preferred_device` falls back to the system default when missing or loopback-like.
+    /// A standby stream on that device is taken over with its pre-roll; one that failed
+    /// or was opened on another device or format is replaced.
+    pub fn start(&mut self, preferred_device: Option<&str>) -> Result<CaptureInfo, CaptureError> {
         if self.is_recording {
-            return Err("Already recording".into());
+            return Err(CaptureError::AlreadyRecording);
         }
 
-        let host = cpal::default_host();
-        let preferred_name = self.selected_input_device.clone();
-        let device = Self::pick_input_device(&host, self.selected_input_device.as_deref())
-            .ok_or("No input device available")?;
-
-        let device_name = Self::device_display_name(&device);
-        if let Some(selected) = preferred_name {
+        let catalog = InputCatalog::discover(&CpalHosts);
+        let (picked, config, mut info) = Self::negotiate(&catalog, preferred_device)?;
+        if let Some(selected) = preferred_device {
             info!("Selected input device preference: {}", selected);
         }
-        info!("Input device in use: {}", device_name);
+        if let Some(standby) = self.standby.take() {
+            if standby.same_input(&info) && !self.has_fault() {
+                self.retention.store(KEEP_ALL, Ordering::Release);
+                self.overruns.reset();
+                self.is_recording = true;
+                info.pre_roll_ms = self.buffered_ms();
+                info!(
+                    "Recording from standby on {} with {} ms of pre-roll",
+                    info.device_name, info.pre_roll_ms
+                );
+                return Ok(info);
+            }
+            info!("Standby stream no longer matches the input; reopening");
+            self.close();
+        }
+
+        self.open(&picked.device, config, &info, KEEP_ALL)?;
+        info!(
+            "Input device in use: {} (host {})",
+            info.device_name, picked.host
+        );
+        info!(
+            "Capture format: {} Hz, {} ch, {}",
+            info.sample_rate, info.channels, info.sample_format
+        );
+        self.is_recording = true;
+        Ok(info)
+    }
+
+    /// Keep a stream open that holds the last `pre_roll_ms` of audio for the next
+    /// recording. A no-op when the standby stream already fits.
+    pub fn enter_standby(
+        &mut self,
+        preferred_device: Option<&str>,
+        pre_roll_ms: u32,
+    ) -> Result<CaptureInfo, CaptureError> {
+        if self.is_recording {
+            return Err(CaptureError::AlreadyRecording);
+        }
+
+        let catalog = InputCatalog::discover(&CpalHosts);
+        let (picked, config, mut info) = Self::negotiate(&catalog, preferred_device)?;
+        info.pre_roll_ms = pre_roll_ms.min(MAX_PRE_ROLL_MS);
+        if self.standby.as_ref() == Some(&info) && !self.has_fault() {
+            return Ok(info);
+        }
+
+        self.leave_standby();
+        let keep = pre_roll_samples(info.pre_roll_ms, info.sample_rate, info.channels);
+        self.open(&picked.device, config, &info, keep)?;
+        info!(
+            "Standby on {} keeping {} ms of pre-roll",
+            info.device_name, info.pre_roll_ms
+        );
+        self.standby = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Close the standby stream, if there is one.
+    pub fn leave_standby(&mut self) {
+        if self.standby.take().is_some() {
+            self.close();
+        }
+    }
 
-        let config = device.default_input_config().map_err(|e| e.to_string())?;
-        if let Ok(mut guard) = self.buffer.lock() {
+    fn open(
+        &mut self,
+        device: &cpal::Device,
+        config: cpal::SupportedStreamConfig,
+        info: &CaptureInfo,
+        keep: usize,
+    ) -> Result<(), CaptureError> {
+        let device_name = &info.device_name;
+        {
+            let mut guard = lock_or_recover(&self.buffer, "capture buffer");
             guard.sample_rate = config.sample_rate();
             guard.channels = config.channels();
             guard.clear();
         }
-
-        let buffer_clone = self.buffer.clone();
+        *lock_or_recover(&self.fault, "capture fault") = None;
+        self.overruns.reset();
+        self.retention.store(keep, Ordering::Release);
+
+        let ring_capacity = config.sample_rate() as usize
+            * config.channels() as usize
+            * collector::RING_SECS as usize;
+        let (mut writer, collector) = collector::start(
+            ring_capacity,
+            self.buffer.clone(),
+            self.overruns.clone(),
+            self.retention.clone(),
+        )
+        .map_err(|e| CaptureError::Backend {
+            message: format!("Failed to start the audio collector: {}", e),
+        })?;
         let level_clone = self.level.clone();
-        let err_fn = |err| error!("an error occurred on stream: {}", err);
+        let fault = self.fault.clone();
+        let err_fn = move |err: cpal::StreamError| {
+            error!("an error occurred on stream: {}", err);
+            if is_fatal_stream_error(&err) {
+                lock_or_recover(&fault, "capture fault").get_or_insert_with(|| err.to_string());
+            }
+        };
 
         let stream = match config.sample_format() {
             cpal::SampleFormat::I16 => device.build_input_stream(
                 &config.into(),
-                move |data: &[i16], _: &_| write_input_data(data, &buffer_clone, &level_clone),
+                move |data: &[i16], _: &_| write_input_data(data, &mut writer, &level_clone),
                 err_fn,
                 None,
             ),
             cpal::SampleFormat::F32 => device.build_input_stream(
                 &config.into(),
-                move |data: &[f32], _: &_| write_input_data_f32(data, &buffer_clone, &level_clone),
+                move |data: &[f32], _: &_| write_input_data_f32(data, &mut writer, &level_clone),
                 err_fn,
                 None,
             ),
-            _ => return Err("Unsupported sample format".into()),
+            format => {
+                return Err(CaptureError::UnsupportedFormat {
+                    format: format.to_string(),
+                })
+            }
         }
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CaptureError::from_build_error(device_name, &info.sample_format, e))?;
 
-        stream.play().map_err(|e| e.to_string())?;
+        stream
+            .play()
+            .map_err(|e| CaptureError::from_play_error(device_name, e))?;
         self.stream = Some(stream);
-        self.is_recording = true;
-
+        self.collector = Some(collector);
         Ok(())
     }
 
+    /// Stop the stream and drop whatever it collected.
+    fn close(&mut self) {
+        self.stream.take();
+        self.finish_collector();
+        self.level.reset();
+        lock_or_recover(&self.buffer, "capture buffer").clear();
+        lock_or_recover(&self.fault, "capture fault").take();
+    }
+
+    fn has_fault(&self) -> bool {
+        lock_or_recover(&self.fault, "capture fault").is_some()
+    }
+
+    /// Length of the audio collected so far.
+    fn buffered_ms(&self) -> u32 {
+        let guard = lock_or_recover(&self.buffer, "capture buffer");
+        let frames = guard.samples.len() / usize::from(guard.channels.max(1));
+        (frames as u64 * 1_000 / u64::from(guard.sample_rate.max(1))) as u32
+    }
+
     pub fn stop(&mut self) -> Result<AudioBuffer, String> {
         if !self.is_recording {
             return Err("Not recording".into());
         }
 
         self.stream.take();
+        self.finish_collector();
         self.is_recording = false;
-        self.level.store(0.0f32.to_bits(), Ordering::Relaxed);
+        self.level.reset();
 
-        let mut guard = self.buffer.lock().map_err(|e| e.to_string())?;
+        let quality = self.overruns.quality();
+        if quality.overruns > 0 {
+            warn!(
+                "Capture fell behind {} times and dropped {} samples",
+                quality.overruns, quality.dropped_samples
+            );
+        }
+        let mut guard = lock_or_recover(&self.buffer, "capture buffer");
         let out = guard.clone();
         guard.clear();
+        // Whatever arrived before the device went away is cut mid-word; don't pass it on
+        // as if the recording ended normally.
+        if let Some(message) = lock_or_recover(&self.fault, "capture fault").take() {
+            return Err(format!(
+                "Input device stopped during recording: {}",
+                message
+            ));
+        }
         Ok(out)
     }
 
-    pub fn audio_level_handle(&self) -> Arc<AtomicU32> {
+    /// Stop the stream and throw the captured samples away.
+    pub fn cancel(&mut self) -> Result<(), String> {
+        self.close();
+        self.is_recording = false;
+        self.standby = None;
+        Ok(())
+    }
+
+    /// Drain what the stream left in the ring; the stream must be gone already.
+    fn finish_collector(&mut self) {
+        if let Some(collector) = self.collector.take() {
+            collector.finish();
+        }
+    }
+
+    pub fn audio_level_handle(&self) -> Arc<LevelMeter> {
         self.level.clone()
     }
 
-    pub fn list_input_devices(&self) -> Result<Vec<String>, String> {
-        let host = cpal::default_host();
-        let devices = host
-            .input_devices()
-            .map_err(|e| e.to_string())?
-            .map(|device| Self::device_display_name(&device))
-            .collect::<Vec<_>>();
-        Ok(devices)
+    /// Overruns of the current recording, or of the last one once it stopped.
+    pub fn quality(&self) -> CaptureQuality {
+        self.overruns.quality()
     }
 
-    pub fn default_input_device_name(&self) -> Option<String> {
-        cpal::default_host()
-            .default_input_device()
-            .map(|device| Self::device_display_name(&device))
+    /// Read by the level loop to notice a failed stream while recording.
+    pub fn fault_handle(&self) -> CaptureFault {
+        self.fault.clone()
     }
 
-    pub fn selected_input_device(&self) -> Option<String> {
-        self.selected_input_device.clone()
+    /// The buffer the stream appends to; the next recording starts it over, from its
+    /// pre-roll when it starts from standby.
+    pub fn live_buffer(&self) -> Arc<Mutex<AudioBuffer>> {
+        self.buffer.clone()
     }
 
-    pub fn set_selected_input_device(&mut self, name: Option<String>) {
-        self.selected_input_device = name
-            .map(|n| n.trim().to_string())
-            .filter(|n| !n.is_empty());
+    /// Hand over the first `len` samples of the recording and keep recording into what is
+    /// left, without touching the stream.
+    pub fn take_snapshot_and_reset(&self, len: usize) -> AudioBuffer {
+        let mut guard = lock_or_recover(&self.buffer, "capture buffer");
+        let len = len.min(guard.samples.len());
+        let mut snapshot = AudioBuffer::new(guard.sample_rate, guard.channels);
+        snapshot.append(&guard.samples[..len]);
+        guard.samples.drain(..len);
+        guard.update_duration();
+        snapshot
     }
 
-    pub fn has_selected_device_available(&self) -> bool {
-        let Some(selected) = self.selected_input_device.as_ref() else {
-            return false;
-        };
+    /// The first [`EXCLUSIVE_PROBE_SECS`] of the current recording are exact zeros even
+    /// though the stream is running.
+    pub fn leading_digital_silence(&self) -> bool {
+        self.is_recording
+            && is_digital_silence(
+                &lock_or_recover(&self.buffer, "capture buffer"),
+                EXCLUSIVE_PROBE_SECS,
+            )
+    }
 
-        let host = cpal::default_host();
-        let Ok(devices) = host.input_devices() else {
-            return false;
-        };
+    /// `preferred_name` is a catalog id; loopback-like devices are skipped.
+    /// The device and format a recording started now with `preferred_device` would use,
+    /// without opening a stream.
+    pub fn probe(preferred_device: Option<&str>) -> Result<CaptureInfo, CaptureError> {
+        let catalog = InputCatalog::discover(&CpalHosts);
+        Self::negotiate(&catalog, preferred_device).map(|(_, _, info)| info)
+    }
 
-        devices.into_iter().any(|device| Self::device_display_name(&device) == *selected)
+    fn negotiate<'a>(
+        catalog: &'a InputCatalog<cpal::Device>,
+        preferred_device: Option<&str>,
+    ) -> Result<Negotiated<'a>, CaptureError> {
+        let picked = Self::pick_input_device(catalog, preferred_device)
+            .ok_or(CaptureError::NoInputDevice)?;
+        let config = picked
+            .device
+            .default_input_config()
+            .map_err(|e| CaptureError::from_config_error(&picked.id, e))?;
+        let info = CaptureInfo {
+            device_name: picked.id.clone(),
+            host: picked.host.clone(),
+            sample_rate: config.sample_rate(),
+            channels: config.channels(),
+            sample_format: config.sample_format().to_string(),
+            pre_roll_ms: 0,
+        };
+        Ok((picked, config, info))
     }
 
-    fn pick_input_device(host: &cpal::Host, preferred_name: Option<&str>) -> Option<cpal::Device> {
+    fn pick_input_device<'a, D>(
+        catalog: &'a InputCatalog<D>,
+        preferred_name: Option<&str>,
+    ) -> Option<&'a HostedDevice<D>> {
         if let Some(name) = preferred_name {
-            if let Ok(mut devices) = host.input_devices() {
-                if let Some(device) = devices.find(|d| Self::device_display_name(d) == name) {
-                    if Self::looks_like_loopback(name) {
-                        warn!(
-                            "Preferred input device '{}' looks like loopback, ignoring preference",
-                            name
-                        );
-                    } else {
-                        return Some(device);
-                    }
+            if let Some(device) = catalog.find(name) {
+                if Self::looks_like_loopback(&device.name) {
+                    warn!(
+                        "Preferred input device '{}' looks like loopback, ignoring preference",
+                        name
+                    );
+                } else {
+                    return Some(device);
                 }
             }
             warn!(
@@ -156,45 +649,29 @@ impl AudioCapture {
             );
         }
 
-        if let Some(default_device) = host.default_input_device() {
-            let default_name = Self::device_display_name(&default_device);
-            if !Self::looks_like_loopback(&default_name) {
+        if let Some(default_device) = catalog.default_device() {
+            if !Self::looks_like_loopback(&default_device.name) {
                 return Some(default_device);
             }
             warn!(
                 "System default input '{}' looks like loopback, trying safer fallback",
-                default_name
+                default_device.id
             );
         }
 
-        let mut mic_like: Option<cpal::Device> = None;
-        let mut non_loopback: Option<cpal::Device> = None;
-        let mut fallback: Option<cpal::Device> = None;
-        if let Ok(devices) = host.input_devices() {
-            for device in devices {
-                let name = Self::device_display_name(&device);
-                if fallback.is_none() {
-                    fallback = Some(device.clone());
-                }
-                if Self::looks_like_loopback(&name) {
-                    continue;
-                }
-                if non_loopback.is_none() {
-                    non_loopback = Some(device.clone());
-                }
-                if mic_like.is_none() && Self::looks_like_microphone(&name) {
-                    mic_like = Some(device);
-                }
-            }
-        }
-
-        mic_like
-            .or(non_loopback)
-            .or(fallback)
-            .or_else(|| host.default_input_device())
+        let devices = catalog.devices();
+        let non_loopback = || {
+            devices
+                .iter()
+                .filter(|device| !Self::looks_like_loopback(&device.name))
+        };
+        non_loopback()
+            .find(|device| Self::looks_like_microphone(&device.name))
+            .or_else(|| non_loopback().next())
+            .or_else(|| devices.first())
     }
 
-    fn device_display_name(device: &cpal::Device) -> String {
+    pub(crate) fn device_display_name(device: &cpal::Device) -> String {
         device
             .name()
             .or_else(|_| device.description().map(|d| d.name().to_string()))
@@ -243,31 +720,22 @@ impl AudioCapture {
     }
 }
 
-fn write_input_data(input: &[i16], buffer: &Arc<Mutex<AudioBuffer>>, level: &Arc<AtomicU32>) {
-    if let Ok(mut guard) = buffer.lock() {
-        guard.append(input);
-    }
+// Both run on the audio thread: nothing here may lock or allocate.
+fn write_input_data(input: &[i16], writer: &mut SampleWriter, level: &LevelMeter) {
+    writer.write(input.iter().copied());
 
-    let rms = rms_i16(input);
-    let normalized = (rms * RMS_BOOST).clamp(0.0, 1.0);
-    level.store(normalized.to_bits(), Ordering::Relaxed);
+    let peak = input.iter().map(|&s| s.unsigned_abs()).max().unwrap_or(0) as f32 / i16::MAX as f32;
+    level.record(rms_i16(input), peak);
 }
 
-fn write_input_data_f32(input: &[f32], buffer: &Arc<Mutex<AudioBuffer>>, level: &Arc<AtomicU32>) {
-    let rms = rms_f32(input);
-    let normalized = (rms * RMS_BOOST).clamp(0.0, 1.0);
-    level.store(normalized.to_bits(), Ordering::Relaxed);
+fn write_input_data_f32(input: &[f32], writer: &mut SampleWriter, level: &LevelMeter) {
+    let peak = input.iter().fold(0.0f32, |peak, &s| peak.max(s.abs()));
+    level.record(rms_f32(input), peak);
 
-    let samples: Vec<i16> = input
-        .iter()
-        .map(|&x| {
-            let clamped = x.clamp(-1.0, 1.0);
-            (clamped * i16::MAX as f32) as i16
-        })
-        .collect();
-    if let Ok(mut guard) = buffer.lock() {
-        guard.append(&samples);
-    }
+    writer.write(input.iter().map(|&x| {
+        let clamped = x.clamp(-1.0, 1.0);
+        (clamped * i16::MAX as f32) as i16
+    }));
 }
 
 fn rms_i16(input: &[i16]) -> f32 {
@@ -291,3 +759,262 @@ fn rms_f32(input: &[f32]) -> f32 {
     let sum: f32 = input.iter().map(|&s| s * s).sum();
     (sum / input.len() as f32).sqrt()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::devices::HostInputs;
+
+    #[test]
+    fn test_meter_reports_smoothed_rms_and_the_peak_since_the_last_reading() {
+        let buffer = Arc::new(Mutex::new(AudioBuffer::new(16_000, 1)));
+        let (mut writer, _collector) = collector::start(
+            64,
+            buffer,
+            Arc::new(OverrunCounter::default()),
+            Arc::new(AtomicUsize::new(KEEP_ALL)),
+        )
+        .unwrap();
+        let meter = LevelMeter::default();
+        meter.set_gain(2.0);
+        write_input_data(&[i16::MIN, 0, 0, 0], &mut writer, &meter);
+        write_input_data(&[1_000, -1_000], &mut writer, &meter);
+
+        let reading = meter.take();
+        assert!((reading.peak - 1.0).abs() < 1e-6);
+        // 0.5 * 1.0 (the clamped first window), then halfway to 2 * 0.0305.
+        assert!((reading.rms - 0.28).abs() < 0.01, "{:?}", reading);
+        // The peak starts over, the RMS carries on.
+        let next = meter.take();
+        assert_eq!(next.peak, 0.0);
+        assert_eq!(next.rms, reading.rms);
+
+        meter.reset();
+        assert_eq!(meter.take(), LevelReading::default());
+    }
+
+    #[test]
+    fn test_clipping_needs_several_clipped_windows_in_a_row() {
+        let mut clip = ClipDetector::default();
+        let flags: Vec<bool> = [1.0, 1.0, 0.5, 0.99, 1.0, 1.0, 1.0, 0.2]
+            .into_iter()
+            .map(|peak| clip.update(peak))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![false, false, false, false, false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_capture_info_serializes_camel_case() {
+        let info = CaptureInfo {
+            device_name: "Headset Microphone".to_string(),
+            host: "WASAPI".to_string(),
+            sample_rate: 8000,
+            channels: 1,
+            sample_format: "i16".to_string(),
+            pre_roll_ms: 0,
+        };
+
+        let value = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "deviceName": "Headset Microphone",
+                "host": "WASAPI",
+                "sampleRate": 8000,
+                "channels": 1,
+                "sampleFormat": "i16",
+                "preRollMs": 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_standby_is_reused_only_for_the_same_input() {
+        let standby = CaptureInfo {
+            device_name: "USB Mic".to_string(),
+            host: "WASAPI".to_string(),
+            sample_rate: 48_000,
+            channels: 2,
+            sample_format: "f32".to_string(),
+            pre_roll_ms: 500,
+        };
+        let negotiated = CaptureInfo {
+            pre_roll_ms: 0,
+            ..standby.clone()
+        };
+        assert!(standby.same_input(&negotiated));
+        for changed in [
+            CaptureInfo {
+                device_name: "Headset".to_string(),
+                ..negotiated.clone()
+            },
+            CaptureInfo {
+                sample_rate: 44_100,
+                ..negotiated.clone()
+            },
+            CaptureInfo {
+                channels: 1,
+                ..negotiated.clone()
+            },
+        ] {
+            assert!(!standby.same_input(&changed), "{:?}", changed);
+        }
+
+        // Whole frames, so the channels of the kept audio don't shift.
+        assert_eq!(pre_roll_samples(500, 48_000, 2), 48_000);
+        assert_eq!(pre_roll_samples(333, 44_100, 2), 29_370);
+        assert_eq!(pre_roll_samples(0, 16_000, 1), 0);
+    }
+
+    fn backend(description: &str) -> cpal::BackendSpecificError {
+        cpal::BackendSpecificError {
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cpal_errors_map_to_capture_errors() {
+        let busy = CaptureError::DeviceBusy {
+            device: "USB Mic".to_string(),
+        };
+        let cases = [
+            (
+                CaptureError::from_build_error(
+                    "USB Mic",
+                    "f32",
+                    cpal::BuildStreamError::DeviceNotAvailable,
+                ),
+                busy.clone(),
+            ),
+            (
+                CaptureError::from_build_error(
+                    "USB Mic",
+                    "f32",
+                    cpal::BuildStreamError::BackendSpecific {
+                        err: backend(
+                            "ALSA function 'snd_pcm_open' failed: Device or resource busy",
+                        ),
+                    },
+                ),
+                busy.clone(),
+            ),
+            (
+                CaptureError::from_build_error(
+                    "USB Mic",
+                    "f32",
+                    cpal::BuildStreamError::StreamConfigNotSupported,
+                ),
+                CaptureError::UnsupportedFormat {
+                    format: "f32".to_string(),
+                },
+            ),
+            (
+                CaptureError::from_config_error(
+                    "USB Mic",
+                    cpal::DefaultStreamConfigError::DeviceNotAvailable,
+                ),
+                busy.clone(),
+            ),
+            (
+                CaptureError::from_config_error(
+                    "USB Mic",
+                    cpal::DefaultStreamConfigError::BackendSpecific {
+                        err: backend("0x8889000A: device in use"),
+                    },
+                ),
+                busy.clone(),
+            ),
+            (
+                CaptureError::from_play_error("USB Mic", cpal::PlayStreamError::DeviceNotAvailable),
+                busy.clone(),
+            ),
+            (
+                CaptureError::from_play_error(
+                    "USB Mic",
+                    cpal::PlayStreamError::BackendSpecific {
+                        err: backend("timeout waiting for buffer"),
+                    },
+                ),
+                CaptureError::Backend {
+                    message: "timeout waiting for buffer".to_string(),
+                },
+            ),
+        ];
+
+        for (actual, expected) in cases {
+            assert_eq!(actual, expected);
+        }
+        assert_eq!(busy.code(), "device_busy");
+        assert_eq!(busy.error_code(), ErrorCode::RecDeviceBusy);
+        assert_eq!(busy.device(), Some("USB Mic"));
+        assert!(busy.to_string().contains("USB Mic"));
+    }
+
+    #[test]
+    fn test_only_underruns_leave_the_stream_running() {
+        assert!(!is_fatal_stream_error(&cpal::StreamError::BufferUnderrun));
+        assert!(is_fatal_stream_error(
+            &cpal::StreamError::DeviceNotAvailable
+        ));
+        assert!(is_fatal_stream_error(&cpal::StreamError::StreamInvalidated));
+        assert!(is_fatal_stream_error(&cpal::StreamError::BackendSpecific {
+            err: backend("snd_pcm_poll_descriptors_revents: No such device"),
+        }));
+    }
+
+    fn catalog(hosts: Vec<(&str, bool, Vec<&'static str>)>) -> InputCatalog<&'static str> {
+        InputCatalog::from_hosts(
+            hosts
+                .into_iter()
+                .map(|(host, is_default, devices)| HostInputs {
+                    host: host.to_string(),
+                    is_default,
+                    devices: devices
+                        .iter()
+                        .map(|name| (name.to_string(), *name))
+                        .collect(),
+                    default_device: None,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_devices_of_other_hosts_can_be_picked() {
+        let hosts = catalog(vec![
+            ("WASAPI", true, vec![]),
+            ("ASIO", false, vec!["Stereo Mix", "Focusrite Mic"]),
+        ]);
+        let picked = AudioCapture::pick_input_device(&hosts, Some("Focusrite Mic (ASIO)"));
+        assert_eq!(picked.map(|device| device.host.as_str()), Some("ASIO"));
+
+        // Nothing preferred and no default: a microphone-like device on the fallback host.
+        let picked = AudioCapture::pick_input_device(&hosts, None).unwrap();
+        assert_eq!(picked.id, "Focusrite Mic (ASIO)");
+
+        let empty = catalog(vec![("WASAPI", true, vec![])]);
+        assert!(AudioCapture::pick_input_device(&empty, Some("USB Mic")).is_none());
+    }
+
+    #[test]
+    fn test_digital_silence_needs_a_full_window_of_zeros() {
+        let mut buffer = AudioBuffer::new(16_000, 1);
+        buffer.append(&vec![0i16; 16_000]);
+        assert!(
+            !is_digital_silence(&buffer, EXCLUSIVE_PROBE_SECS),
+            "only 1 s so far"
+        );
+
+        buffer.append(&vec![0i16; 8_000]);
+        assert!(is_digital_silence(&buffer, EXCLUSIVE_PROBE_SECS));
+
+        let mut quiet_room = AudioBuffer::new(16_000, 1);
+        let mut samples = vec![0i16; 24_000];
+        samples[12_000] = 1;
+        quiet_room.append(&samples);
+        assert!(!is_digital_silence(&quiet_room, EXCLUSIVE_PROBE_SECS));
+    }
+}
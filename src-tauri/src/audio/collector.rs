@@ -0,0 +1,282 @@
+//! Moves captured samples off the real-time audio thread. The cpal callback writes into a
+//! lock-free ring and a collector thread drains it into the growable [`AudioBuffer`], so
+//! the callback never waits on a lock or reallocates. In standby the collector keeps only
+//! the latest samples (see [`Retention`]), so the buffer stays a small pre-roll ring.
+
+use crate::audio::AudioBuffer;
+use crate::locks::lock_or_recover;
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+use serde::Serialize;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Audio the ring holds before the callback starts dropping samples.
+pub const RING_SECS: u32 = 2;
+/// How often the collector empties the ring; far below [`RING_SECS`].
+const DRAIN_INTERVAL: Duration = Duration::from_millis(10);
+/// [`Retention`] while recording.
+pub const KEEP_ALL: usize = usize::MAX;
+
+/// How many of the latest samples the collector keeps in the buffer; older ones are
+/// dropped as new ones arrive. A whole number of frames, so channels never shift.
+pub type Retention = Arc<AtomicUsize>;
+
+/// Samples lost because the collector fell behind during the current recording.
+#[derive(Debug, Default)]
+pub struct OverrunCounter {
+    overruns: AtomicU64,
+    dropped_samples: AtomicU64,
+}
+
+impl OverrunCounter {
+    pub fn quality(&self) -> CaptureQuality {
+        CaptureQuality {
+            overruns: self.overruns.load(Ordering::Relaxed),
+            dropped_samples: self.dropped_samples.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, dropped: usize) {
+        self.overruns.fetch_add(1, Ordering::Relaxed);
+        self.dropped_samples
+            .fetch_add(dropped as u64, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.overruns.store(0, Ordering::Relaxed);
+        self.dropped_samples.store(0, Ordering::Relaxed);
+    }
+}
+
+/// How cleanly the current or last recording was captured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureQuality {
+    /// Callbacks that found the ring full
+    pub overruns: u64,
+    /// Samples those callbacks had to drop
+    pub dropped_samples: u64,
+}
+
+/// The callback's end of the ring.
+pub struct SampleWriter {
+    producer: HeapProd<i16>,
+    counter: Arc<OverrunCounter>,
+}
+
+impl SampleWriter {
+    /// Never blocks or allocates. Samples that don't fit are dropped and counted.
+    pub fn write(&mut self, samples: impl ExactSizeIterator<Item = i16>) {
+        let len = samples.len();
+        let written = self.producer.push_iter(samples);
+        if written < len {
+            self.counter.record(len - written);
+        }
+    }
+}
+
+/// The thread draining the ring into the recording buffer. Finishing (or dropping) it
+/// drains whatever is left, so stop the stream first.
+pub struct Collector {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Collector {
+    pub fn finish(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                tracing::error!("Audio collector panicked");
+            }
+        }
+    }
+}
+
+impl Drop for Collector {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+/// A ring of `capacity` samples; the collector starts right away.
+pub fn start(
+    capacity: usize,
+    buffer: Arc<Mutex<AudioBuffer>>,
+    counter: Arc<OverrunCounter>,
+    retention: Retention,
+) -> std::io::Result<(SampleWriter, Collector)> {
+    let (producer, consumer) = HeapRb::<i16>::new(capacity.max(1)).split();
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = thread::Builder::new()
+        .name("zentra-audio-collector".to_string())
+        .spawn({
+            let stop = stop.clone();
+            move || collect(consumer, &buffer, &stop, &retention)
+        })?;
+    Ok((
+        SampleWriter { producer, counter },
+        Collector {
+            stop,
+            handle: Some(handle),
+        },
+    ))
+}
+
+fn collect(
+    mut consumer: HeapCons<i16>,
+    buffer: &Mutex<AudioBuffer>,
+    stop: &AtomicBool,
+    retention: &AtomicUsize,
+) {
+    loop {
+        // Read the flag first: samples written before it was set are drained below.
+        let stopping = stop.load(Ordering::Acquire);
+        drain(&mut consumer, buffer, retention.load(Ordering::Acquire));
+        if stopping {
+            return;
+        }
+        thread::sleep(DRAIN_INTERVAL);
+    }
+}
+
+fn drain(consumer: &mut HeapCons<i16>, buffer: &Mutex<AudioBuffer>, keep: usize) {
+    if consumer.is_empty() {
+        return;
+    }
+    let (head, tail) = consumer.as_slices();
+    let count = head.len() + tail.len();
+    {
+        let mut buffer = lock_or_recover(buffer, "capture buffer");
+        buffer.append(head);
+        buffer.append(tail);
+        if buffer.samples.len() > keep {
+            let excess = buffer.samples.len() - keep;
+            buffer.samples.drain(..excess);
+            buffer.update_duration();
+        }
+    }
+    consumer.skip(count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn recording() -> Arc<Mutex<AudioBuffer>> {
+        Arc::new(Mutex::new(AudioBuffer::new(16_000, 1)))
+    }
+
+    fn keep_all() -> Retention {
+        Arc::new(AtomicUsize::new(KEEP_ALL))
+    }
+
+    #[test]
+    fn test_samples_arrive_complete_and_in_order() {
+        let buffer = recording();
+        let counter = Arc::new(OverrunCounter::default());
+        let (mut writer, collector) =
+            start(48_000, buffer.clone(), counter.clone(), keep_all()).unwrap();
+
+        // 480-sample callbacks, what a 10 ms period at 48 kHz delivers, ten times as fast.
+        let producer = thread::spawn(move || {
+            for chunk in 0..200u32 {
+                let start = chunk * 480;
+                writer.write((start..start + 480).map(|n| n as i16));
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        producer.join().unwrap();
+        collector.finish();
+
+        let samples = lock_or_recover(&buffer, "capture buffer").samples.clone();
+        let expected: Vec<i16> = (0..96_000u32).map(|n| n as i16).collect();
+        assert_eq!(samples.len(), expected.len());
+        assert!(samples == expected, "samples were reordered");
+        assert_eq!(counter.quality(), CaptureQuality::default());
+    }
+
+    #[test]
+    fn test_a_stalled_collector_counts_overruns_instead_of_blocking_the_callback() {
+        let buffer = recording();
+        let counter = Arc::new(OverrunCounter::default());
+        let (mut writer, collector) =
+            start(1_000, buffer.clone(), counter.clone(), keep_all()).unwrap();
+
+        // Hold the buffer lock so the collector can't drain anything.
+        let held = lock_or_recover(&buffer, "capture buffer");
+        let (done, finished) = mpsc::channel();
+        let producer = thread::spawn(move || {
+            for chunk in 0..100i16 {
+                writer.write([chunk; 100].into_iter());
+            }
+            done.send(()).unwrap();
+        });
+        finished
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the callback blocked on a full ring");
+        producer.join().unwrap();
+        drop(held);
+        collector.finish();
+
+        // The first ring's worth is kept; every callback after it found the ring full.
+        let kept = lock_or_recover(&buffer, "capture buffer").samples.clone();
+        let expected: Vec<i16> = (0..10i16).flat_map(|chunk| [chunk; 100]).collect();
+        assert_eq!(kept, expected);
+        assert_eq!(
+            counter.quality(),
+            CaptureQuality {
+                overruns: 90,
+                dropped_samples: 9_000,
+            }
+        );
+
+        counter.reset();
+        assert_eq!(counter.quality(), CaptureQuality::default());
+    }
+
+    #[test]
+    fn test_standby_keeps_only_the_latest_samples_until_recording_starts() {
+        let buffer = recording();
+        let retention: Retention = Arc::new(AtomicUsize::new(100));
+        let (mut writer, collector) = start(
+            4_000,
+            buffer.clone(),
+            Arc::new(OverrunCounter::default()),
+            retention.clone(),
+        )
+        .unwrap();
+
+        writer.write(0..1_000i16);
+        let drained = || lock_or_recover(&buffer, "capture buffer").samples.last() == Some(&999);
+        for _ in 0..500 {
+            if drained() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        assert!(drained(), "the collector never drained the ring");
+        assert_eq!(
+            lock_or_recover(&buffer, "capture buffer").samples.len(),
+            100
+        );
+
+        // Recording starts: the pre-roll stays and nothing is dropped from here on.
+        retention.store(KEEP_ALL, Ordering::Release);
+        writer.write(1_000..1_500i16);
+        collector.finish();
+
+        let samples = lock_or_recover(&buffer, "capture buffer").samples.clone();
+        assert_eq!(samples, (900..1_500i16).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,460 @@
+//! Input device enumeration and the selected-device preference, kept apart from the
+//! capture stream so device queries never wait on (or block) recording start/stop.
+//!
+//! Devices come from every cpal host, not just the default one: some Linux setups and
+//! Windows machines with broken WASAPI endpoints report no inputs on the default host
+//! while another host (ALSA, ASIO, JACK) has them.
+
+use super::capture::AudioCapture;
+use arc_swap::ArcSwapOption;
+use cpal::traits::HostTrait;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+
+/// How often the device watcher re-enumerates inputs.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Input devices of one audio host.
+pub struct HostInputs<D> {
+    /// cpal's host name: `WASAPI`, `ALSA`, `CoreAudio`, ...
+    pub host: String,
+    pub is_default: bool,
+    /// Display name and device, in enumeration order
+    pub devices: Vec<(String, D)>,
+    /// Display name of the host's default input
+    pub default_device: Option<String>,
+}
+
+/// Where device discovery gets its hosts; cpal in the app, fakes in tests.
+pub trait HostProvider {
+    type Device;
+
+    fn hosts(&self) -> Vec<HostInputs<Self::Device>>;
+}
+
+/// Every host cpal was built with.
+pub struct CpalHosts;
+
+impl HostProvider for CpalHosts {
+    type Device = cpal::Device;
+
+    fn hosts(&self) -> Vec<HostInputs<cpal::Device>> {
+        let default_id = cpal::default_host().id();
+        cpal::available_hosts()
+            .into_iter()
+            .filter_map(|id| {
+                let host = cpal::host_from_id(id)
+                    .map_err(|e| tracing::debug!("Audio host {} unavailable: {}", id.name(), e))
+                    .ok()?;
+                let devices = match host.input_devices() {
+                    Ok(devices) => devices
+                        .map(|device| (AudioCapture::device_display_name(&device), device))
+                        .collect(),
+                    Err(e) => {
+                        tracing::debug!("Audio host {} has no input list: {}", id.name(), e);
+                        Vec::new()
+                    }
+                };
+                Some(HostInputs {
+                    host: id.name().to_string(),
+                    is_default: id == default_id,
+                    devices,
+                    default_device: host
+                        .default_input_device()
+                        .map(|device| AudioCapture::device_display_name(&device)),
+                })
+            })
+            .collect()
+    }
+}
+
+/// An input device and the host that exposes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostedDevice<D> {
+    /// What settings store and the device list shows
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub device: D,
+}
+
+/// The input devices of all hosts under one list of ids. Devices of the default host keep
+/// their plain name as id, so settings saved before multi-host discovery still match;
+/// other hosts tag theirs with the host name: `"USB Mic (ALSA)"`.
+pub struct InputCatalog<D> {
+    devices: Vec<HostedDevice<D>>,
+    default_device: Option<usize>,
+}
+
+impl<D> InputCatalog<D> {
+    pub fn discover(provider: &impl HostProvider<Device = D>) -> Self {
+        Self::from_hosts(provider.hosts())
+    }
+
+    /// The default is the default host's default input; when the default host has no
+    /// inputs at all, the first host that does decides.
+    pub fn from_hosts(mut hosts: Vec<HostInputs<D>>) -> Self {
+        hosts.sort_by_key(|host| !host.is_default);
+
+        let mut devices: Vec<HostedDevice<D>> = Vec::new();
+        let mut default_device = None;
+        let mut default_decided = false;
+        for host in hosts {
+            let first = devices.len();
+            for (name, device) in host.devices {
+                let id = if host.is_default {
+                    name.clone()
+                } else {
+                    format!("{} ({})", name, host.host)
+                };
+                if devices.iter().any(|known| known.id == id) {
+                    continue;
+                }
+                devices.push(HostedDevice {
+                    id,
+                    name,
+                    host: host.host.clone(),
+                    device,
+                });
+            }
+
+            if default_decided || devices.len() == first {
+                continue;
+            }
+            default_decided = true;
+            let named = host.default_device.as_ref().and_then(|name| {
+                devices[first..]
+                    .iter()
+                    .position(|device| &device.name == name)
+                    .map(|index| first + index)
+            });
+            default_device = match named {
+                Some(index) => Some(index),
+                // A fallback host without a default still beats having no device.
+                None if !host.is_default => Some(first),
+                None => None,
+            };
+        }
+
+        Self {
+            devices,
+            default_device,
+        }
+    }
+
+    pub fn devices(&self) -> &[HostedDevice<D>] {
+        &self.devices
+    }
+
+    /// Ids of every device, sorted and deduplicated.
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids = self
+            .devices
+            .iter()
+            .map(|device| device.id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    pub fn find(&self, id: &str) -> Option<&HostedDevice<D>> {
+        self.devices.iter().find(|device| device.id == id)
+    }
+
+    pub fn default_device(&self) -> Option<&HostedDevice<D>> {
+        self.default_device.map(|index| &self.devices[index])
+    }
+}
+
+/// The input list changed between two watcher polls.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceChange {
+    pub devices: Vec<String>,
+    pub selected: Option<String>,
+    /// The selected device is no longer plugged in; capture falls back to the default.
+    pub selected_missing: bool,
+}
+
+#[derive(Default)]
+pub struct DeviceManager {
+    /// Result of the last enumeration, for readers that can live with a stale list.
+    devices: RwLock<Vec<String>>,
+    /// Read on every recording start; swapped without locking.
+    selected: ArcSwapOption<String>,
+    /// The device saved in settings, kept while it is unplugged so it can be selected
+    /// again when it comes back.
+    configured: ArcSwapOption<String>,
+    /// A selection made mid-recording, applied once it stops; the inner `None` switches
+    /// back to the system default.
+    queued: Mutex<Option<Option<String>>>,
+}
+
+impl DeviceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enumerate input devices of every host (sorted ids) and refresh the cached list.
+    /// The enumeration itself runs without holding any lock.
+    pub fn refresh(&self) -> Result<Vec<String>, String> {
+        let hosts = CpalHosts.hosts();
+        if hosts.is_empty() {
+            return Err("No audio host available".to_string());
+        }
+        let devices = InputCatalog::from_hosts(hosts).ids();
+
+        self.devices
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone_from(&devices);
+        Ok(devices)
+    }
+
+    /// Devices seen by the last [`Self::refresh`].
+    pub fn cached_devices(&self) -> Vec<String> {
+        self.devices
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn default_input_device_name(&self) -> Option<String> {
+        InputCatalog::discover(&CpalHosts)
+            .default_device()
+            .map(|device| device.id.clone())
+    }
+
+    pub fn selected(&self) -> Option<String> {
+        self.selected.load_full().map(|name| name.as_ref().clone())
+    }
+
+    /// Blank names clear the preference (capture then uses the system default).
+    pub fn select(&self, name: Option<String>) {
+        let name = name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        self.selected.store(name.map(Arc::new));
+    }
+
+    /// Select `name` now, or queue it until the current capture stops when `capturing`,
+    /// so a recording never changes format halfway. Returns whether it took effect.
+    pub fn request(&self, name: Option<String>, capturing: bool) -> bool {
+        let mut queued = self.queued.lock().unwrap_or_else(PoisonError::into_inner);
+        if capturing {
+            *queued = Some(name);
+            return false;
+        }
+        queued.take();
+        self.select(name);
+        true
+    }
+
+    /// Apply the selection queued during the capture that just stopped. Returns whether
+    /// there was one.
+    pub fn apply_queued(&self) -> bool {
+        let queued = self
+            .queued
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        match queued {
+            Some(name) => {
+                self.select(name);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remember the device from settings and select it.
+    pub fn configure(&self, name: Option<String>) {
+        self.select(name);
+        self.configured.store(self.selected.load_full());
+    }
+
+    /// Re-enumerate and compare with `previous`, the watcher's last snapshot. `None` when
+    /// the list did not change.
+    pub fn poll(&self, previous: &[String]) -> Result<Option<DeviceChange>, String> {
+        let devices = self.refresh()?;
+        Ok(self.compare(previous, devices))
+    }
+
+    fn compare(&self, previous: &[String], devices: Vec<String>) -> Option<DeviceChange> {
+        if previous == devices.as_slice() {
+            return None;
+        }
+        if let Some(configured) = self.configured.load_full() {
+            let returned = !previous.contains(&configured) && devices.contains(&configured);
+            if returned && self.selected().as_ref() != Some(configured.as_ref()) {
+                tracing::info!("Input device '{}' is back; selecting it", configured);
+                self.selected.store(Some(configured));
+            }
+        }
+        let selected = self.selected();
+        let selected_missing = selected
+            .as_ref()
+            .is_some_and(|name| !devices.contains(name));
+        Some(DeviceChange {
+            devices,
+            selected,
+            selected_missing,
+        })
+    }
+
+    /// The selected device is currently plugged in. Re-enumerates.
+    pub fn selected_available(&self) -> bool {
+        let Some(selected) = self.selected() else {
+            return false;
+        };
+        self.refresh()
+            .map(|devices| devices.contains(&selected))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    struct FakeHosts(Vec<(&'static str, bool, Vec<&'static str>, Option<&'static str>)>);
+
+    impl HostProvider for FakeHosts {
+        type Device = &'static str;
+
+        fn hosts(&self) -> Vec<HostInputs<&'static str>> {
+            self.0
+                .iter()
+                .map(|(host, is_default, devices, default_device)| HostInputs {
+                    host: host.to_string(),
+                    is_default: *is_default,
+                    devices: devices
+                        .iter()
+                        .map(|name| (name.to_string(), *name))
+                        .collect(),
+                    default_device: default_device.map(str::to_string),
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_default_host_devices_keep_plain_ids_and_win_the_default() {
+        let catalog = InputCatalog::discover(&FakeHosts(vec![
+            ("JACK", false, vec!["system"], Some("system")),
+            ("ALSA", true, vec!["Built-in", "USB Mic"], Some("USB Mic")),
+        ]));
+        assert_eq!(
+            catalog.ids(),
+            names(&["Built-in", "USB Mic", "system (JACK)"])
+        );
+        let default = catalog.default_device().unwrap();
+        assert_eq!(
+            (default.id.as_str(), default.host.as_str()),
+            ("USB Mic", "ALSA")
+        );
+
+        let jack = catalog.find("system (JACK)").unwrap();
+        assert_eq!((jack.name.as_str(), jack.device), ("system", "system"));
+        assert!(catalog.find("system").is_none());
+    }
+
+    #[test]
+    fn test_empty_default_host_falls_back_to_the_next_host() {
+        let catalog = InputCatalog::discover(&FakeHosts(vec![
+            ("WASAPI", true, vec![], None),
+            (
+                "ASIO",
+                false,
+                vec!["Focusrite In 1", "Focusrite In 2"],
+                None,
+            ),
+        ]));
+        let default = catalog.default_device().unwrap();
+        assert_eq!(default.id, "Focusrite In 1 (ASIO)");
+        assert_eq!(default.host, "ASIO");
+
+        // A default host with inputs but no default leaves the choice to the capture
+        // heuristics instead of jumping hosts.
+        let catalog = InputCatalog::discover(&FakeHosts(vec![
+            ("ALSA", true, vec!["hw:0"], None),
+            ("JACK", false, vec!["system"], Some("system")),
+        ]));
+        assert!(catalog.default_device().is_none());
+        assert_eq!(catalog.devices().len(), 2);
+
+        assert!(InputCatalog::discover(&FakeHosts(vec![]))
+            .default_device()
+            .is_none());
+    }
+
+    #[test]
+    fn test_unplugged_device_is_flagged_and_reselected_when_it_returns() {
+        let manager = DeviceManager::new();
+        manager.configure(Some("USB Mic".to_string()));
+        let both = names(&["Built-in", "USB Mic"]);
+
+        assert_eq!(manager.compare(&both, both.clone()), None);
+
+        // Unplugged: flagged, and startup-style fallback to the default.
+        let change = manager.compare(&both, names(&["Built-in"])).unwrap();
+        assert!(change.selected_missing);
+        manager.select(Some("Built-in".to_string()));
+
+        // A different device showing up changes nothing about the selection.
+        let change = manager
+            .compare(&names(&["Built-in"]), names(&["Built-in", "Webcam"]))
+            .unwrap();
+        assert_eq!(change.selected.as_deref(), Some("Built-in"));
+        assert!(!change.selected_missing);
+
+        let change = manager
+            .compare(&names(&["Built-in", "Webcam"]), both.clone())
+            .unwrap();
+        assert_eq!(change.selected.as_deref(), Some("USB Mic"));
+        assert!(!change.selected_missing);
+        assert_eq!(change.devices, both);
+    }
+
+    #[test]
+    fn test_manual_selection_is_kept_while_the_configured_device_stays() {
+        let manager = DeviceManager::new();
+        manager.configure(Some("USB Mic".to_string()));
+        manager.select(Some("Webcam".to_string()));
+
+        let change = manager
+            .compare(
+                &names(&["USB Mic", "Webcam"]),
+                names(&["Built-in", "USB Mic", "Webcam"]),
+            )
+            .unwrap();
+        assert_eq!(change.selected.as_deref(), Some("Webcam"));
+    }
+
+    #[test]
+    fn test_selection_waits_for_the_capture_to_stop() {
+        let manager = DeviceManager::new();
+        assert!(manager.request(Some("USB Mic".to_string()), false));
+        assert_eq!(manager.selected().as_deref(), Some("USB Mic"));
+
+        assert!(!manager.request(Some("Webcam".to_string()), true));
+        assert!(!manager.request(None, true));
+        assert_eq!(manager.selected().as_deref(), Some("USB Mic"));
+        // The last request while capturing wins: back to the default device.
+        assert!(manager.apply_queued());
+        assert_eq!(manager.selected(), None);
+        assert!(!manager.apply_queued());
+
+        // Selecting while idle drops anything still queued.
+        manager.request(Some("Webcam".to_string()), true);
+        manager.request(Some("USB Mic".to_string()), false);
+        assert!(!manager.apply_queued());
+        assert_eq!(manager.selected().as_deref(), Some("USB Mic"));
+    }
+}
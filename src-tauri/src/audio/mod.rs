@@ -1,26 +1,36 @@
-﻿pub mod buffer;
-pub mod capture;
-pub mod vad;
+﻿pub mod capture;
+pub mod collector;
+pub mod devices;
 
-pub use buffer::AudioBuffer;
+pub use capture::{CaptureError, CaptureFault, CaptureInfo, ClipDetector, LevelMeter};
+pub use collector::CaptureQuality;
+pub use devices::{DeviceChange, DeviceManager};
+pub use zentra_core::audio::{buffer, denoise, vad, wav, AudioBuffer};
 
-#[cfg(feature = "onnx")]
-use std::path::PathBuf;
 #[cfg(feature = "onnx")]
 use std::path::Path;
-use std::sync::{Arc, atomic::AtomicU32};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use capture::AudioCapture;
 #[cfg(feature = "onnx")]
-use vad::Vad;
+use zentra_core::audio::vad::Vad;
+
+/// Silero VAD model, in the models dir or the bundled `resources/`
+#[cfg(feature = "onnx")]
+const VAD_MODEL_FILE: &str = "silero_vad.onnx";
 
 pub struct AudioRecorder {
     capture: AudioCapture,
     #[cfg(feature = "onnx")]
     vad: Option<Vad>,
+    /// Searched for models before the ones bundled with the app
+    models_dir: Option<PathBuf>,
     is_recording: bool,
-    // Store accumulated chunks here? Or inside capture?
-    // Start simple: direct passthrough or basic logic
+    /// Run [`denoise::denoise`] over each finished recording
+    noise_suppression: bool,
+    /// Audio kept from before each start; 0 keeps no standby stream open
+    pre_roll_ms: u32,
 }
 
 impl AudioRecorder {
@@ -29,21 +39,16 @@ impl AudioRecorder {
         let is_recording = false;
 
         #[cfg(feature = "onnx")]
-        let vad = {
-            let model_path = PathBuf::from("resources/silero_vad.onnx");
-            if model_path.exists() {
-                Some(Vad::new(&model_path)?)
-            } else {
-                eprintln!("VAD model not found at {:?}. Running without VAD.", model_path);
-                None
-            }
-        };
+        let vad = load_vad(None)?;
 
         Ok(Self {
             capture,
             #[cfg(feature = "onnx")]
             vad,
+            models_dir: None,
             is_recording,
+            noise_suppression: false,
+            pre_roll_ms: 0,
         })
     }
 
@@ -52,17 +57,34 @@ impl AudioRecorder {
             capture: AudioCapture::new(),
             #[cfg(feature = "onnx")]
             vad: None,
+            models_dir: None,
             is_recording: false,
+            noise_suppression: false,
+            pre_roll_ms: 0,
         }
     }
 
-    pub fn start_recording(&mut self) -> Result<(), String> {
+    pub fn start_recording(&mut self, device: Option<&str>) -> Result<CaptureInfo, CaptureError> {
         if self.is_recording {
-            return Err("Already recording".into());
+            return Err(CaptureError::AlreadyRecording);
         }
-        self.capture.start()?;
+        let info = self.capture.start(device)?;
         self.is_recording = true;
-        Ok(())
+        Ok(info)
+    }
+
+    /// Look for models in `dir` first, e.g. the portable install's `data/models`.
+    pub fn set_models_dir(&mut self, dir: PathBuf) {
+        #[cfg(feature = "onnx")]
+        match load_vad(Some(&dir)) {
+            Ok(vad) => self.vad = vad,
+            Err(e) => tracing::warn!("Keeping the previous VAD: {}", e),
+        }
+        self.models_dir = Some(dir);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.is_recording
     }
 
     pub fn stop_recording(&mut self) -> Result<AudioBuffer, String> {
@@ -71,30 +93,268 @@ impl AudioRecorder {
         }
         let buffer = self.capture.stop()?;
         self.is_recording = false;
-        Ok(buffer)
+        Ok(self.clean_up(buffer))
+    }
+
+    /// Cut the first `len` samples off the running recording as a segment of its own;
+    /// capture carries on. `None` when nothing is being recorded.
+    pub fn take_snapshot_and_reset(&mut self, len: usize) -> Option<AudioBuffer> {
+        if !self.is_recording {
+            return None;
+        }
+        let snapshot = self.capture.take_snapshot_and_reset(len);
+        Some(self.clean_up(snapshot))
     }
 
-    pub fn audio_level_handle(&self) -> Arc<AtomicU32> {
+    /// Denoise and trim captured audio before it goes to a provider.
+    fn clean_up(&mut self, mut buffer: AudioBuffer) -> AudioBuffer {
+        if self.noise_suppression {
+            let started = std::time::Instant::now();
+            buffer = denoised(buffer);
+            tracing::debug!(
+                "Denoised {:.2}s of audio in {:?}",
+                buffer.duration_secs,
+                started.elapsed()
+            );
+        }
+
+        // Dead air at either end makes Whisper models invent text; cut it before upload.
+        #[cfg(feature = "onnx")]
+        if let Some(vad) = self.vad.as_mut() {
+            match vad.trim_silence(&buffer) {
+                Ok(trimmed) => {
+                    tracing::debug!(
+                        "VAD trimmed {:.2}s to {:.2}s",
+                        buffer.duration_secs,
+                        trimmed.duration_secs
+                    );
+                    return trimmed;
+                }
+                Err(e) => tracing::warn!("VAD trim failed, keeping full recording: {}", e),
+            }
+        }
+
+        buffer
+    }
+
+    pub fn set_noise_suppression(&mut self, enabled: bool) {
+        self.noise_suppression = enabled;
+    }
+
+    pub fn noise_suppression(&self) -> bool {
+        self.noise_suppression
+    }
+
+    /// Takes effect with the next [`Self::resume_standby`].
+    pub fn set_pre_roll(&mut self, pre_roll_ms: u32) {
+        self.pre_roll_ms = pre_roll_ms;
+    }
+
+    /// Between recordings, keep the pre-roll stream open on `device`, or close it when
+    /// pre-roll is off. Recording keeps its stream; the call is a no-op then.
+    pub fn resume_standby(&mut self, device: Option<&str>) -> Result<(), CaptureError> {
+        if self.is_recording {
+            return Ok(());
+        }
+        if self.pre_roll_ms == 0 {
+            self.capture.leave_standby();
+            return Ok(());
+        }
+        self.capture
+            .enter_standby(device, self.pre_roll_ms)
+            .map(|_| ())
+    }
+
+    /// Scale of the level meter's RMS; the recorded audio is not changed.
+    pub fn set_input_gain(&self, gain: f32) {
+        self.capture.audio_level_handle().set_gain(gain);
+    }
+
+    /// Stop capturing without returning audio. A no-op when not recording.
+    pub fn cancel_recording(&mut self) -> Result<(), String> {
+        if !self.is_recording {
+            return Ok(());
+        }
+        self.capture.cancel()?;
+        self.is_recording = false;
+        Ok(())
+    }
+
+    pub fn audio_level_handle(&self) -> Arc<LevelMeter> {
         self.capture.audio_level_handle()
     }
 
-    pub fn list_input_devices(&self) -> Result<Vec<String>, String> {
-        self.capture.list_input_devices()
+    pub fn live_buffer(&self) -> Arc<Mutex<AudioBuffer>> {
+        self.capture.live_buffer()
+    }
+
+    pub fn fault_handle(&self) -> CaptureFault {
+        self.capture.fault_handle()
+    }
+
+    /// Samples the current recording, or the last one, lost to a full capture ring.
+    pub fn capture_quality(&self) -> CaptureQuality {
+        self.capture.quality()
+    }
+
+    /// The recording started with exact digital silence; see [`capture::EXCLUSIVE_PROBE_SECS`].
+    pub fn possibly_exclusive(&self) -> bool {
+        self.is_recording && self.capture.leading_digital_silence()
+    }
+}
+
+/// Capture control shared by commands, the shortcut path and background probes. Only
+/// start/stop/cancel, the exclusive-mode probe and settings changes take this lock; device
+/// queries go through [`DeviceManager`].
+pub struct SharedRecorder {
+    inner: Mutex<AudioRecorder>,
+}
+
+impl SharedRecorder {
+    pub fn new(recorder: AudioRecorder) -> Self {
+        Self {
+            inner: Mutex::new(recorder),
+        }
+    }
+
+    /// A panic while the recorder was held leaves the stream in an unknown state, so a
+    /// poisoned lock rebuilds the recorder instead of failing every later command.
+    pub fn lock(&self) -> MutexGuard<'_, AudioRecorder> {
+        self.inner.lock().unwrap_or_else(|poisoned| {
+            tracing::error!("Recorder lock poisoned; reinitializing audio capture");
+            let mut guard = poisoned.into_inner();
+            let models_dir = guard.models_dir.take();
+            *guard = AudioRecorder::new().unwrap_or_else(|e| {
+                tracing::warn!("Recorder reinitialization failed: {}", e);
+                AudioRecorder::new_dummy()
+            });
+            if let Some(dir) = models_dir {
+                guard.set_models_dir(dir);
+            }
+            self.inner.clear_poison();
+            guard
+        })
     }
+}
 
-    pub fn selected_input_device(&self) -> Option<String> {
-        self.capture.selected_input_device()
+/// The VAD from the first model found in `models_dir`, then in `resources/`.
+#[cfg(feature = "onnx")]
+fn load_vad(models_dir: Option<&Path>) -> Result<Option<Vad>, String> {
+    let bundled = Path::new("resources").join(VAD_MODEL_FILE);
+    let found = models_dir
+        .map(|dir| dir.join(VAD_MODEL_FILE))
+        .into_iter()
+        .chain([bundled])
+        .find(|path| path.exists());
+    match found {
+        Some(model_path) => Vad::new(&model_path).map(Some),
+        None => {
+            tracing::warn!("VAD model not found. Running without VAD.");
+            Ok(None)
+        }
     }
+}
+
+/// The denoiser works on one channel, so a multichannel recording is downmixed first; the
+/// providers get mono anyway.
+fn denoised(buffer: AudioBuffer) -> AudioBuffer {
+    let mut mono = AudioBuffer::new(buffer.sample_rate, 1);
+    let samples = if buffer.channels > 1 {
+        buffer.to_mono(buffer.sample_rate)
+    } else {
+        buffer.samples
+    };
+    mono.append(&denoise::denoise(&samples, mono.sample_rate));
+    mono
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
 
-    pub fn set_selected_input_device(&mut self, name: Option<String>) {
-        self.capture.set_selected_input_device(name);
+    #[test]
+    fn test_device_queries_do_not_contend_with_recording() {
+        let recorder = Arc::new(SharedRecorder::new(AudioRecorder::new_dummy()));
+        let devices = Arc::new(DeviceManager::new());
+        let (done, finished) = mpsc::channel();
+
+        let mut workers = Vec::new();
+        for worker in 0..4 {
+            let devices = devices.clone();
+            let done = done.clone();
+            workers.push(thread::spawn(move || {
+                for round in 0..200 {
+                    let _ = devices.refresh();
+                    let _ = devices.cached_devices();
+                    let _ = devices.selected_available();
+                    devices.select(Some(format!("Mic {}-{}", worker, round % 3)));
+                    let selected = devices.selected().unwrap_or_default();
+                    assert!(selected.starts_with("Mic "));
+                }
+                done.send(()).unwrap();
+            }));
+        }
+        for _ in 0..2 {
+            let recorder = recorder.clone();
+            let devices = devices.clone();
+            let done = done.clone();
+            workers.push(thread::spawn(move || {
+                for _ in 0..200 {
+                    let device = devices.selected();
+                    let mut recorder = recorder.lock();
+                    if recorder.start_recording(device.as_deref()).is_ok() {
+                        recorder.stop_recording().unwrap();
+                    }
+                    recorder.cancel_recording().unwrap();
+                }
+                done.send(()).unwrap();
+            }));
+        }
+
+        for _ in 0..workers.len() {
+            finished
+                .recv_timeout(Duration::from_secs(30))
+                .expect("device queries and recording start/stop deadlocked");
+        }
+        for worker in workers {
+            worker.join().unwrap();
+        }
     }
 
-    pub fn selected_device_available(&self) -> bool {
-        self.capture.has_selected_device_available()
+    #[test]
+    fn test_stereo_recordings_are_denoised_as_mono() {
+        let mut stereo = AudioBuffer::new(48_000, 2);
+        let frames: Vec<i16> = (0..48_000)
+            .flat_map(|idx| {
+                let sample = ((idx as f32 * 0.05).sin() * 8_000.0) as i16;
+                [sample, sample]
+            })
+            .collect();
+        stereo.append(&frames);
+
+        let cleaned = denoised(stereo);
+        assert_eq!(cleaned.channels, 1);
+        assert_eq!(cleaned.samples.len(), 48_000);
+        assert!((cleaned.duration_secs - 1.0).abs() < 0.01);
     }
 
-    pub fn default_input_device_name(&self) -> Option<String> {
-        self.capture.default_input_device_name()
+    #[test]
+    fn test_poisoned_recorder_is_reinitialized() {
+        let recorder = Arc::new(SharedRecorder::new(AudioRecorder::new_dummy()));
+        let poisoner = recorder.clone();
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.lock();
+            panic!("panicked while holding the recorder");
+        })
+        .join();
+
+        let mut guard = recorder.lock();
+        assert!(!guard.possibly_exclusive());
+        assert!(guard.cancel_recording().is_ok());
+        drop(guard);
+        assert!(!recorder.inner.is_poisoned());
     }
 }
@@ -0,0 +1,242 @@
+//! Audio of recent dictations, kept as WAV next to their history items so a bad
+//! transcription can be run again later. Only the newest recordings are kept, bounded by
+//! count and total size; a file goes when its item is deleted or rotated out of history.
+//! The offline queue archives into the same folder, next to the chunk progress of the long
+//! recordings it is transcribing.
+
+use crate::config::{HistoryItem, HistoryStatus};
+use crate::history_store::HistoryStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zentra_core::audio::{wav, AudioBuffer};
+
+/// `<history item id>.progress.jsonl`: chunks of a pending item already transcribed
+pub const CHUNK_PROGRESS_SUFFIX: &str = ".progress.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AudioArchiveSettings {
+    /// Off by default; turning it off deletes the recordings already kept
+    pub enabled: bool,
+    pub keep_last: usize,
+    /// Total size of the kept audio
+    pub max_mb: u64,
+}
+
+impl Default for AudioArchiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep_last: 20,
+            max_mb: 200,
+        }
+    }
+}
+
+/// Write `audio` into `dir` as a new WAV file.
+pub fn archive(dir: &Path, audio: &AudioBuffer) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create audio archive dir: {}", e))?;
+    let path = dir.join(format!("{}.wav", uuid::Uuid::new_v4()));
+    wav::write_wav_file(&path, audio)?;
+    Ok(path)
+}
+
+/// Unlink the audio of transcribed items past the newest `keep_last` or the size cap.
+/// Pending items keep theirs; it has not been transcribed yet.
+pub fn enforce_limit(history: &mut [HistoryItem], settings: &AudioArchiveSettings) {
+    let max_bytes = settings.max_mb.saturating_mul(1024 * 1024);
+    let mut kept = 0;
+    let mut kept_bytes = 0u64;
+    for item in history
+        .iter_mut()
+        .filter(|item| item.status == HistoryStatus::Transcribed)
+    {
+        let Some(path) = item.audio_path.as_deref() else {
+            continue;
+        };
+        let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+        if settings.enabled && kept < settings.keep_last && kept_bytes + size <= max_bytes {
+            kept += 1;
+            kept_bytes += size;
+        } else {
+            item.audio_path = None;
+        }
+    }
+}
+
+/// Delete the files in `before` (from [`HistoryStore::audio_paths`]) that `store` no
+/// longer references. Call once the change is written, so a failed write never leaves an
+/// item pointing at a deleted file. Nothing is deleted when the store can't be read.
+pub fn remove_released(before: &HashSet<PathBuf>, store: &HistoryStore) {
+    let kept = match store.audio_paths() {
+        Ok(kept) => kept,
+        Err(e) => {
+            tracing::warn!("Archived audio left in place: {}", e);
+            return;
+        }
+    };
+    for path in before.difference(&kept) {
+        remove_file(path);
+    }
+}
+
+/// Delete WAV files in `dir` that nothing references, such as the audio of a purged trash,
+/// and chunk progress of items no longer pending. Returns how many WAV files were removed.
+pub fn remove_orphans(dir: &Path, store: &HistoryStore) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let (kept, pending) = match (store.audio_paths(), store.pending()) {
+        (Ok(kept), Ok(pending)) => (kept, pending),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::warn!("Archived audio left in place: {}", e);
+            return 0;
+        }
+    };
+    let pending: HashSet<&str> = pending.iter().map(|item| item.id.as_str()).collect();
+    let paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+
+    let stale_progress = paths.iter().filter(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(CHUNK_PROGRESS_SUFFIX))
+            .is_some_and(|id| !pending.contains(id))
+    });
+    for path in stale_progress {
+        remove_file(path);
+    }
+
+    let orphans: Vec<&PathBuf> = paths
+        .iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        })
+        .filter(|path| !kept.contains(*path))
+        .collect();
+    for path in &orphans {
+        remove_file(path);
+    }
+    orphans.len()
+}
+
+fn remove_file(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Failed to remove {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HistoryTrash;
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("zentra-archive-{}-{}", label, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn speech(secs: usize) -> AudioBuffer {
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![2_000i16; 16_000 * secs]);
+        audio
+    }
+
+    fn item(id: &str, audio_path: Option<&Path>, status: HistoryStatus) -> HistoryItem {
+        HistoryItem {
+            id: id.to_string(),
+            text: "texto".to_string(),
+            timestamp: "2026-01-01T10:00:00Z".to_string(),
+            duration_seconds: 1.0,
+            word_count: 1,
+            provider: None,
+            audio_path: audio_path.map(|path| path.display().to_string()),
+            versions: Vec::new(),
+            status,
+            request_id: None,
+            session_stats: None,
+        }
+    }
+
+    #[test]
+    fn test_only_the_newest_recordings_keep_their_audio() {
+        let dir = temp_dir("limit");
+        let paths: Vec<PathBuf> = (0..4).map(|_| archive(&dir, &speech(1)).unwrap()).collect();
+        let mut history = vec![
+            item(
+                "pending",
+                Some(&paths[0]),
+                HistoryStatus::PendingTranscription,
+            ),
+            item("newest", Some(&paths[1]), HistoryStatus::Transcribed),
+            item("typed", None, HistoryStatus::Transcribed),
+            item("older", Some(&paths[2]), HistoryStatus::Transcribed),
+            item("oldest", Some(&paths[3]), HistoryStatus::Transcribed),
+        ];
+        let mut settings = AudioArchiveSettings {
+            enabled: true,
+            keep_last: 2,
+            ..Default::default()
+        };
+
+        enforce_limit(&mut history, &settings);
+        let kept: Vec<bool> = history.iter().map(|i| i.audio_path.is_some()).collect();
+        assert_eq!(kept, vec![true, true, false, true, false]);
+
+        // One second of 16 kHz mono is about 31 KB; a 0 MB cap keeps nothing.
+        settings.max_mb = 0;
+        enforce_limit(&mut history, &settings);
+        assert!(history[0].audio_path.is_some());
+        assert!(history[1..].iter().all(|i| i.audio_path.is_none()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_released_and_orphaned_audio_is_deleted() {
+        let dir = temp_dir("cleanup");
+        let kept = archive(&dir, &speech(1)).unwrap();
+        let trashed = archive(&dir, &speech(1)).unwrap();
+        let deleted = archive(&dir, &speech(1)).unwrap();
+        let orphan = archive(&dir, &speech(1)).unwrap();
+        fs::write(dir.join("notes.txt"), "not audio").unwrap();
+        let finished = dir.join(format!("b{}", CHUNK_PROGRESS_SUFFIX));
+        fs::write(&finished, "{}").unwrap();
+        let pending = dir.join(format!("p{}", CHUNK_PROGRESS_SUFFIX));
+        fs::write(&pending, "{}").unwrap();
+
+        let store = HistoryStore::in_memory().unwrap();
+        let trash = HistoryTrash {
+            deleted_at: chrono::Utc::now().to_rfc3339(),
+            items: vec![item("c", Some(&trashed), HistoryStatus::Transcribed)],
+        };
+        let history = vec![
+            item("a", Some(&kept), HistoryStatus::Transcribed),
+            item("b", Some(&deleted), HistoryStatus::Transcribed),
+            item("p", None, HistoryStatus::PendingTranscription),
+        ];
+        store.import_legacy(&history, Some(&trash)).unwrap();
+        let before = store.audio_paths().unwrap();
+        store.delete("b").unwrap();
+        remove_released(&before, &store);
+        assert!(!deleted.exists());
+        assert!(kept.exists() && trashed.exists() && orphan.exists());
+
+        assert_eq!(remove_orphans(&dir, &store), 1);
+        assert!(!orphan.exists());
+        assert!(kept.exists() && trashed.exists());
+        assert!(dir.join("notes.txt").exists());
+        assert!(!finished.exists() && pending.exists());
+        assert_eq!(remove_orphans(&dir.join("missing"), &store), 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,250 @@
+//! Audio files referenced by the clipboard. The clipboard-manager plugin only reads text and
+//! images, so file lists (CF_HDROP on Windows, file URLs on macOS) are read here directly.
+//! Text that consists only of file paths is accepted too.
+
+use std::path::{Path, PathBuf};
+
+/// Extensions the file pipeline can decode.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipboardAudio {
+    /// Decodable files in clipboard order, plus any referenced files that were skipped.
+    Files {
+        files: Vec<PathBuf>,
+        skipped: Vec<PathBuf>,
+    },
+    /// Files were referenced but none has a supported extension.
+    Unsupported(Vec<PathBuf>),
+    /// Nothing that looks like a file.
+    NoFiles,
+}
+
+/// `files` is the native file list; `text` is only consulted when that list is empty.
+pub fn classify(files: Vec<PathBuf>, text: Option<&str>) -> ClipboardAudio {
+    let candidates = if files.is_empty() {
+        text.and_then(paths_from_text).unwrap_or_default()
+    } else {
+        files
+    };
+    if candidates.is_empty() {
+        return ClipboardAudio::NoFiles;
+    }
+
+    let (files, skipped): (Vec<_>, Vec<_>) =
+        candidates.into_iter().partition(|path| is_supported(path));
+    if files.is_empty() {
+        ClipboardAudio::Unsupported(skipped)
+    } else {
+        ClipboardAudio::Files { files, skipped }
+    }
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            SUPPORTED_EXTENSIONS
+                .iter()
+                .any(|supported| ext.eq_ignore_ascii_case(supported))
+        })
+}
+
+/// Text counts as a file list only when every non-empty line is an absolute path or a
+/// `file://` URL; anything else is ordinary copied text.
+pub fn paths_from_text(text: &str) -> Option<Vec<PathBuf>> {
+    let paths = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_path_line)
+        .collect::<Option<Vec<_>>>()?;
+    (!paths.is_empty()).then_some(paths)
+}
+
+fn parse_path_line(line: &str) -> Option<PathBuf> {
+    let line = line.trim_matches(|c| c == '"' || c == '\'');
+    let path = match line.strip_prefix("file://") {
+        Some(url) => {
+            let decoded = percent_decode(url.strip_prefix("localhost").unwrap_or(url))?;
+            // file:///C:/Users/... keeps a slash in front of the drive letter.
+            match decoded.strip_prefix('/') {
+                Some(rest) if has_drive_prefix(rest) => rest.to_string(),
+                _ => decoded,
+            }
+        }
+        None => line.to_string(),
+    };
+
+    let absolute = path.starts_with('/') || path.starts_with("\\\\") || has_drive_prefix(&path);
+    (absolute && !path.chars().any(char::is_control)).then(|| PathBuf::from(path))
+}
+
+fn has_drive_prefix(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/')
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            let hex = value.get(idx + 1..idx + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            idx += 3;
+        } else {
+            out.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Files on the clipboard as a native file list (Explorer/Finder copy, recorder apps).
+#[cfg(target_os = "windows")]
+pub fn read_file_list() -> Vec<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::um::shellapi::{DragQueryFileW, HDROP};
+    use winapi::um::winuser::{
+        CloseClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard, CF_HDROP,
+    };
+
+    let mut files = Vec::new();
+    unsafe {
+        if IsClipboardFormatAvailable(CF_HDROP) == 0 || OpenClipboard(std::ptr::null_mut()) == 0 {
+            return files;
+        }
+
+        let handle = GetClipboardData(CF_HDROP) as HDROP;
+        if !handle.is_null() {
+            let count = DragQueryFileW(handle, u32::MAX, std::ptr::null_mut(), 0);
+            for idx in 0..count {
+                let len = DragQueryFileW(handle, idx, std::ptr::null_mut(), 0);
+                let mut buffer = vec![0u16; len as usize + 1];
+                let written = DragQueryFileW(handle, idx, buffer.as_mut_ptr(), buffer.len() as u32);
+                if written > 0 {
+                    files.push(PathBuf::from(OsString::from_wide(
+                        &buffer[..written as usize],
+                    )));
+                }
+            }
+        }
+        CloseClipboard();
+    }
+    files
+}
+
+/// Files on the clipboard as a native file list (Explorer/Finder copy, recorder apps).
+#[cfg(target_os = "macos")]
+pub fn read_file_list() -> Vec<PathBuf> {
+    // AppleScript's `the clipboard as «class furl»` only sees the first file, so read every
+    // file URL from NSPasteboard through JXA.
+    const SCRIPT: &str = r#"
+ObjC.import('AppKit');
+var urls = $.NSPasteboard.generalPasteboard.readObjectsForClassesOptions($([$.NSURL]), $({}));
+var paths = [];
+for (var i = 0; urls && i < urls.count; i++) {
+  var url = urls.objectAtIndex(i);
+  if (url.isFileURL) { paths.push(url.path.js); }
+}
+paths.join('\n');
+"#;
+
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", SCRIPT])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        Ok(output) => {
+            tracing::warn!(
+                "Reading clipboard files failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Vec::new()
+        }
+        Err(e) => {
+            tracing::warn!("Failed to run osascript: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Other platforms only expose paths copied as text.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn read_file_list() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_is_only_a_path_list_when_every_line_is_a_path() {
+        assert_eq!(
+            paths_from_text("/home/ana/memo.wav\n\n  \"C:\\Users\\Ana\\nota.WAV\"  "),
+            Some(vec![
+                PathBuf::from("/home/ana/memo.wav"),
+                PathBuf::from("C:\\Users\\Ana\\nota.WAV"),
+            ])
+        );
+        assert_eq!(
+            paths_from_text("file:///Users/ana/Voice%20Memos/take%201.wav"),
+            Some(vec![PathBuf::from("/Users/ana/Voice Memos/take 1.wav")])
+        );
+        assert_eq!(
+            paths_from_text("file:///C:/Rec/a.wav"),
+            Some(vec![PathBuf::from("C:/Rec/a.wav")])
+        );
+
+        assert_eq!(paths_from_text("Reunião amanhã às 10h"), None);
+        assert_eq!(paths_from_text("see /tmp/a.wav for details"), None);
+        assert_eq!(paths_from_text("/tmp/a.wav\nand some notes"), None);
+        assert_eq!(paths_from_text("relative/take.wav"), None);
+        assert_eq!(paths_from_text("   \n"), None);
+    }
+
+    #[test]
+    fn test_files_are_filtered_by_extension() {
+        let files = vec![
+            PathBuf::from("/rec/one.wav"),
+            PathBuf::from("/rec/two.m4a"),
+            PathBuf::from("/rec/three.WAV"),
+            PathBuf::from("/rec/no-extension"),
+        ];
+        assert_eq!(
+            classify(files, Some("ignored when a file list exists")),
+            ClipboardAudio::Files {
+                files: vec![
+                    PathBuf::from("/rec/one.wav"),
+                    PathBuf::from("/rec/three.WAV")
+                ],
+                skipped: vec![
+                    PathBuf::from("/rec/two.m4a"),
+                    PathBuf::from("/rec/no-extension")
+                ],
+            }
+        );
+
+        assert_eq!(
+            classify(Vec::new(), Some("/rec/voice.m4a")),
+            ClipboardAudio::Unsupported(vec![PathBuf::from("/rec/voice.m4a")])
+        );
+        assert_eq!(
+            classify(Vec::new(), Some("just some dictated text")),
+            ClipboardAudio::NoFiles
+        );
+        assert_eq!(classify(Vec::new(), None), ClipboardAudio::NoFiles);
+    }
+}
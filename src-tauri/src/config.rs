@@ -1,20 +1,71 @@
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine as _;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
-use tauri::path::BaseDirectory;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
+use zentra_core::audio::{wav, AudioBuffer};
+use zentra_core::error_codes::ErrorCode;
+use zentra_core::key_health::KeyStatus;
+use zentra_core::orchestrator::{
+    usage, LocalWhisper, ProviderOverrides, ProviderSetup, TranscriptionSettings,
+};
+use zentra_core::privacy::PrivacyGuard;
+use zentra_core::prompt_engine::{LlmKeys, OptimizationMode};
+use zentra_core::session::{SessionStats, SilenceGate};
+use zentra_core::stt::WhisperOptions;
+use zentra_core::text::normalize::normalize_for_match;
+use zentra_core::text::postprocess::{PostProcessingOptions, ReplacementRule};
 
-const CONFIG_DIR: &str = "zentra";
-const CONFIG_FILE: &str = "config.json";
-const HISTORY_LIMIT: usize = 50;
+use crate::audio::capture::{DEFAULT_INPUT_GAIN, MAX_PRE_ROLL_MS};
+use crate::audio_archive::{self, AudioArchiveSettings};
+use crate::destructive::DestructiveScope;
+use crate::env_import::EnvImportRecord;
+use crate::history_store::{self, HistoryStore};
+use crate::history_updates::ChangeSummary;
+use crate::hotkeys::{self, HotkeyMap};
+use crate::integrations::IntegrationSettings;
+use crate::key_health::{self, KeyCheck};
+use crate::locks::lock_or_recover;
+use crate::offline_queue::{self, OfflineQueueSettings};
+use crate::paths::Resolver;
+use crate::post_actions::{self, PostAction};
+use crate::presets::{self, ActivePreset, Preset, SessionSettings};
+use crate::secrets::{self, ApiKeySlot};
+use crate::stats::{self, HistoryEntry};
+use crate::suggestions::{LanguageSuggestion, LanguageSuggestionState};
+
+pub const CONFIG_FILE: &str = "config.json";
+/// Items kept in history unless configured otherwise.
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+/// History items sent with the dashboard data and per search page by default.
+pub const HISTORY_PAGE_SIZE: usize = 20;
+const MAX_HISTORY_PAGE_SIZE: usize = 200;
+const HISTORY_VERSION_LIMIT: usize = 3;
+/// Text of a history item whose audio is waiting in the offline queue.
+pub const PENDING_TRANSCRIPTION_TEXT: &str = "Waiting for a connection to transcribe";
+/// Every masked key contains this; no real key does.
+const KEY_MASK: &str = "******";
 const API_KEY_XOR_KEY: &[u8] = b"zentra-local-key-v1";
+const WRITE_PROBE_FILE: &str = ".write-probe";
+
+/// Wait between the paste keystroke and putting the old clipboard back.
+pub const DEFAULT_CLIPBOARD_RESTORE_DELAY_MS: u64 = 500;
+const MAX_CLIPBOARD_RESTORE_DELAY_MS: u64 = 5_000;
+const MIN_INPUT_GAIN: f32 = 0.5;
+const MAX_INPUT_GAIN: f32 = 10.0;
+
+/// Recordings stop by themselves after this long unless configured otherwise.
+pub const DEFAULT_MAX_RECORDING_SECS: u64 = 300;
 
 pub const DEFAULT_HOTKEY: &str = "CommandOrControl+Shift+Space";
 pub const DEFAULT_LANGUAGE: &str = "pt";
 pub const DEFAULT_USE_CASE: &str = "general";
+pub const DEFAULT_PROMPT_PROFILE: &str = "clarity";
 pub const GITHUB_URL: &str = "https://github.com/DaviBonetto/zentra";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,11 +75,91 @@ pub struct AppConfig {
     pub user_name: String,
     pub use_case: String,
     pub groq_api_key_obfuscated: Option<String>,
+    pub elevenlabs_api_key_obfuscated: Option<String>,
+    pub openrouter_api_key_obfuscated: Option<String>,
+    /// Last conclusive health check of the Groq key
+    pub key_check: Option<KeyCheck>,
+    pub local_engines: LocalEngineSettings,
+    /// Per-provider overrides (enabled, priority, retries, timeout, confidence threshold)
+    pub providers: ProviderOverrides,
+    /// Racing, repetition collapse and upload format for every provider; config.json only
+    pub transcription: TranscriptionSettings,
     pub input_device_name: Option<String>,
+    /// Starts and stops recording
     pub hotkey: String,
+    /// Optional stop-only and paste-last-transcript shortcuts
+    pub hotkeys: HotkeyMap,
     pub language: String,
-    pub stats: Stats,
-    pub history: Vec<HistoryItem>,
+    pub privacy_mode: bool,
+    /// Turned on by hand; offline mode is also detected from network failures
+    pub offline_mode: bool,
+    /// Bounds on audio kept for transcription once back online
+    pub offline_queue: OfflineQueueSettings,
+    /// Recordings kept after transcription, for re-transcribing history items
+    pub audio_archive: AudioArchiveSettings,
+    pub pill_focusable: bool,
+    /// Move the pill to the monitor under the cursor when a recording starts
+    pub pill_follows_active_monitor: bool,
+    /// Put the previous clipboard text back after auto-paste
+    pub restore_clipboard: bool,
+    pub clipboard_restore_delay_ms: u64,
+    /// Partial transcripts while recording; costs extra provider requests
+    pub live_transcription: bool,
+    /// Subtract steady background noise (fans, hum) from a recording before upload
+    pub noise_suppression: bool,
+    /// Read recording state changes out through the screen reader or notifications
+    pub a11y_announcements: bool,
+    /// Send the first seconds of speech early as a throwaway request, to open the
+    /// connection and pin the session's language before the first segment
+    pub session_warmup: bool,
+    /// Scale of the level meter's RMS, for quiet or hot microphones. The recording itself
+    /// is not amplified
+    pub input_gain: f32,
+    /// Keep the microphone open between recordings and start each one with this much of
+    /// the audio before the shortcut, so the first word isn't clipped. 0 turns it off
+    pub pre_roll_ms: u32,
+    /// Linux: read the shortcuts from the keyboards in /dev/input when neither the
+    /// global-shortcut plugin nor the desktop portal can register them. Needs the user in
+    /// the `input` group, which lets their programs read every key typed; config.json only
+    pub evdev_hotkeys: bool,
+    /// Per-minute USD rates by provider id for the usage estimate, over the built-in ones;
+    /// config.json only
+    pub usage_rates: BTreeMap<String, f64>,
+    /// Cut session segments at a fixed length and start uploading each before it is fully
+    /// recorded. Set in config.json only while it is experimental
+    pub experimental_streaming_upload: bool,
+    /// A recording left running is stopped and transcribed after this long; 0 disables
+    pub max_recording_secs: u64,
+    /// Energy thresholds below which a recording is dropped instead of transcribed
+    pub silence_gate: SilenceGate,
+    /// Settings that presets (meeting mode) swap in and out
+    pub session: SessionSettings,
+    pub presets: Vec<Preset>,
+    pub active_preset: Option<ActivePreset>,
+    pub integrations: IntegrationSettings,
+    /// What happens to each finalized transcript, in order; presets may swap in their own
+    pub post_actions: Vec<PostAction>,
+    pub post_processing: PostProcessingSettings,
+    /// Spoken "new line", "comma", "vírgula"... become formatting in the final text
+    pub dictation_commands: bool,
+    /// Profile `optimize_transcript` uses when none is given
+    pub prompt_profile_id: String,
+    pub optimization_mode: OptimizationMode,
+    /// Items kept in history, newest first; 0 keeps everything
+    pub history_limit: usize,
+    /// Folder that gets a JSON copy of every history item, for backup tools
+    pub mirror_history_to_folder: Option<PathBuf>,
+    /// Measured with `measure_typing_wpm`; time saved assumes 130 WPM without it
+    pub typing_wpm: Option<f32>,
+    /// History as versions before the history database kept it; moved there at startup
+    #[serde(rename = "history", skip_serializing_if = "Vec::is_empty")]
+    pub legacy_history: Vec<HistoryItem>,
+    #[serde(rename = "history_trash", skip_serializing_if = "Option::is_none")]
+    pub legacy_history_trash: Option<HistoryTrash>,
+    /// Set once the legacy `.env` migration has run
+    pub env_import: Option<EnvImportRecord>,
+    /// Detected languages of recent dictations, for suggesting a better `language`
+    pub language_suggestion: LanguageSuggestionState,
 }
 
 impl Default for AppConfig {
@@ -38,25 +169,88 @@ impl Default for AppConfig {
             user_name: String::new(),
             use_case: DEFAULT_USE_CASE.to_string(),
             groq_api_key_obfuscated: None,
+            elevenlabs_api_key_obfuscated: None,
+            openrouter_api_key_obfuscated: None,
+            key_check: None,
+            local_engines: LocalEngineSettings::default(),
+            providers: ProviderOverrides::default(),
+            transcription: TranscriptionSettings::default(),
             input_device_name: None,
             hotkey: DEFAULT_HOTKEY.to_string(),
+            hotkeys: HotkeyMap::new(),
             language: DEFAULT_LANGUAGE.to_string(),
-            stats: Stats::default(),
-            history: Vec::new(),
+            privacy_mode: false,
+            offline_mode: false,
+            offline_queue: OfflineQueueSettings::default(),
+            audio_archive: AudioArchiveSettings::default(),
+            pill_focusable: false,
+            pill_follows_active_monitor: true,
+            restore_clipboard: false,
+            clipboard_restore_delay_ms: DEFAULT_CLIPBOARD_RESTORE_DELAY_MS,
+            live_transcription: true,
+            noise_suppression: false,
+            a11y_announcements: false,
+            session_warmup: false,
+            input_gain: DEFAULT_INPUT_GAIN,
+            pre_roll_ms: 0,
+            evdev_hotkeys: false,
+            usage_rates: BTreeMap::new(),
+            experimental_streaming_upload: false,
+            max_recording_secs: DEFAULT_MAX_RECORDING_SECS,
+            silence_gate: SilenceGate::default(),
+            session: SessionSettings::default(),
+            presets: presets::default_presets(),
+            active_preset: None,
+            integrations: IntegrationSettings::default(),
+            post_actions: post_actions::default_post_actions(),
+            post_processing: PostProcessingSettings::default(),
+            dictation_commands: false,
+            prompt_profile_id: DEFAULT_PROMPT_PROFILE.to_string(),
+            optimization_mode: OptimizationMode::default(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            mirror_history_to_folder: None,
+            typing_wpm: None,
+            legacy_history: Vec::new(),
+            legacy_history_trash: None,
+            env_import: None,
+            language_suggestion: LanguageSuggestionState::default(),
         }
     }
 }
 
+/// Paths for the offline engines; see [`provider_setup`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LocalEngineSettings {
+    pub whisper_cpp_bin: Option<String>,
+    pub whisper_model: Option<String>,
+    pub vosk_model_pt: Option<String>,
+    pub vosk_model_en: Option<String>,
+    /// whisper.cpp `--threads`; its own default when unset
+    pub whisper_threads: Option<u32>,
+    /// Have whisper.cpp translate into English instead of transcribing
+    pub whisper_translate: bool,
+}
+
+/// Transcript cleanup rules; the language comes from `AppConfig::language`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PostProcessingSettings {
+    pub clarity: bool,
+    pub remove_fillers: bool,
+    pub replacements: Vec<ReplacementRule>,
+}
+
+/// Totals over transcribed history items.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct Stats {
     pub total_transcriptions: u64,
     pub total_words: u64,
     pub total_recording_seconds: f32,
-    pub total_seconds_saved: f32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryItem {
     pub id: String,
@@ -66,6 +260,108 @@ pub struct HistoryItem {
     pub duration_seconds: f32,
     #[serde(alias = "word_count")]
     pub word_count: u32,
+    /// Provider of the current text; absent for items from older versions
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub audio_path: Option<String>,
+    #[serde(default)]
+    pub versions: Vec<HistoryVersion>,
+    #[serde(default)]
+    pub status: HistoryStatus,
+    /// Dictation that produced the item; absent for files and items from older versions.
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// Provider breakdown of a session recording; absent for single dictations and files
+    #[serde(default)]
+    pub session_stats: Option<SessionStats>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryStatus {
+    #[default]
+    Transcribed,
+    /// Recorded offline; the text is a placeholder until the queue transcribes the audio
+    PendingTranscription,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryVersion {
+    pub text: String,
+    pub provider: String,
+    pub created_at: String,
+}
+
+/// Cleared history as config.json kept it, for
+/// [`TRASH_RETENTION_HOURS`](history_store::TRASH_RETENTION_HOURS) after the clear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryTrash {
+    pub deleted_at: String,
+    pub items: Vec<HistoryItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryFilter {
+    Ids(Vec<String>),
+    DateRange {
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetranscribeOptions {
+    pub max_items: Option<usize>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetranscribeTarget {
+    pub id: String,
+    pub audio_path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedHistoryItem {
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetranscribePlan {
+    pub queued: Vec<RetranscribeTarget>,
+    pub skipped: Vec<SkippedHistoryItem>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HistorySearch {
+    /// Words that must all appear, ignoring case and accents; empty matches everything
+    pub query: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Zero-based
+    pub page: usize,
+    /// Items per page; 0 uses [`HISTORY_PAGE_SIZE`]
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchResult {
+    pub items: Vec<HistoryEntry>,
+    /// Matches across all pages
+    pub total: usize,
+    pub page: usize,
+    pub limit: usize,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -109,11 +405,44 @@ pub struct DashboardData {
     pub user_name: String,
     pub has_api_key: bool,
     pub api_key_masked: Option<String>,
+    pub elevenlabs_api_key_masked: Option<String>,
+    pub openrouter_api_key_masked: Option<String>,
+    pub key_status: KeyStatus,
     pub input_device_name: Option<String>,
     pub hotkey: String,
+    pub hotkeys: HotkeyMap,
     pub language: String,
+    pub privacy_mode: bool,
+    pub offline_mode: bool,
+    pub offline_queue: OfflineQueueSettings,
+    pub audio_archive: AudioArchiveSettings,
+    pub pending_transcriptions: usize,
+    pub pill_focusable: bool,
+    pub pill_follows_active_monitor: bool,
+    pub restore_clipboard: bool,
+    pub clipboard_restore_delay_ms: u64,
+    pub live_transcription: bool,
+    pub noise_suppression: bool,
+    pub a11y_announcements: bool,
+    pub session_warmup: bool,
+    pub input_gain: f32,
+    pub pre_roll_ms: u32,
+    pub max_recording_secs: u64,
+    pub silence_gate: SilenceGate,
+    pub integrations: IntegrationSettings,
+    pub post_actions: Vec<PostAction>,
+    pub post_processing: PostProcessingSettings,
+    pub dictation_commands: bool,
+    pub prompt_profile_id: String,
+    pub optimization_mode: OptimizationMode,
     pub stats: DashboardStats,
-    pub history: Vec<HistoryItem>,
+    /// The most recent page; `search_history` serves the rest
+    pub history: Vec<HistoryEntry>,
+    pub history_total: usize,
+    pub history_limit: usize,
+    pub mirror_history_to_folder: Option<PathBuf>,
+    pub typing_wpm: Option<f32>,
+    pub history_trash_count: usize,
     pub github_url: String,
     pub app_version: String,
 }
@@ -125,6 +454,8 @@ pub struct DashboardStats {
     pub total_words: u64,
     pub minutes_saved: f32,
     pub wpm: f32,
+    /// Speed `minutes_saved` assumes: the calibrated one or the default
+    pub typing_wpm: f32,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -134,16 +465,176 @@ pub struct RecordHistoryPayload {
     pub duration_seconds: f32,
     pub word_count: Option<u32>,
     pub timestamp: Option<String>,
+    /// Filled in by `record_transcription_history` from the current dictation when absent.
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub session_stats: Option<SessionStats>,
+    /// Falls back to the provider of most of the session's segments.
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Archived recording of the dictation; set by the backend, never taken from IPC
+    #[serde(skip)]
+    pub audio_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateSettingsPayload {
     pub user_name: Option<String>,
-    pub api_key: Option<String>,
+    #[serde(default)]
+    pub api_key: KeyUpdate,
+    #[serde(default)]
+    pub elevenlabs_api_key: KeyUpdate,
+    #[serde(default)]
+    pub openrouter_api_key: KeyUpdate,
     pub input_device_name: Option<String>,
     pub hotkey: Option<String>,
+    /// Replaces every extra shortcut; leave an action out to unbind it
+    pub hotkeys: Option<HotkeyMap>,
     pub language: Option<String>,
+    pub pill_focusable: Option<bool>,
+    pub pill_follows_active_monitor: Option<bool>,
+    pub restore_clipboard: Option<bool>,
+    pub clipboard_restore_delay_ms: Option<u64>,
+    pub live_transcription: Option<bool>,
+    pub noise_suppression: Option<bool>,
+    pub a11y_announcements: Option<bool>,
+    pub session_warmup: Option<bool>,
+    pub input_gain: Option<f32>,
+    /// Clamped to [`MAX_PRE_ROLL_MS`]
+    pub pre_roll_ms: Option<u32>,
+    pub max_recording_secs: Option<u64>,
+    pub history_limit: Option<usize>,
+    /// An empty path stops mirroring
+    pub mirror_history_to_folder: Option<PathBuf>,
+    /// 0 goes back to the default speed
+    pub typing_wpm: Option<f32>,
+    pub offline_queue: Option<OfflineQueueSettings>,
+    pub audio_archive: Option<AudioArchiveSettings>,
+    pub silence_gate: Option<SilenceGate>,
+    pub integrations: Option<IntegrationSettings>,
+    pub post_actions: Option<Vec<PostAction>>,
+    pub post_processing: Option<PostProcessingSettings>,
+    pub dictation_commands: Option<bool>,
+    pub prompt_profile_id: Option<String>,
+    pub optimization_mode: Option<OptimizationMode>,
+}
+
+/// What a settings save does to a stored API key: `"unchanged"`, `"clear"` or
+/// `{ "set": "gsk_..." }`. Keys are write-only; the dashboard only ever sees them masked, so
+/// leaving a field alone can never wipe a key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyUpdate {
+    #[default]
+    Unchanged,
+    Set(String),
+    Clear,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupHealth {
+    pub storage_available: bool,
+    pub storage_message: Option<String>,
+    /// Why the global shortcuts could not be registered, e.g. missing `input` group access
+    pub hotkey_message: Option<String>,
+    /// Set when the previous run panicked; `open_crash_folder` shows the reports
+    pub crash_message: Option<String>,
+}
+
+/// Whether the config directory is writable. When it is not, the app keeps running on an
+/// in-memory config and every save becomes a no-op (warned about once).
+#[derive(Debug, Default)]
+pub struct StorageStatus {
+    read_only: AtomicBool,
+    warned: AtomicBool,
+    reason: Mutex<Option<String>>,
+    fallback: Mutex<Option<AppConfig>>,
+}
+
+impl StorageStatus {
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    pub fn health(&self) -> StartupHealth {
+        let reason = lock_or_recover(&self.reason, "storage reason").clone();
+        StartupHealth {
+            storage_available: !self.is_read_only(),
+            storage_message: reason.map(|reason| {
+                format!(
+                    "Settings and history can't be saved ({}). Zentra will keep working, but changes are lost on exit.",
+                    reason
+                )
+            }),
+            hotkey_message: None,
+            crash_message: None,
+        }
+    }
+
+    fn enter_read_only(&self, config: &AppConfig, reason: String) {
+        tracing::error!("Config storage unavailable, running in memory: {}", reason);
+        *lock_or_recover(&self.reason, "storage reason") = Some(reason);
+        *lock_or_recover(&self.fallback, "fallback config") = Some(config.clone());
+        self.read_only.store(true, Ordering::Relaxed);
+    }
+
+    fn fallback_config(&self) -> Option<AppConfig> {
+        if !self.is_read_only() {
+            return None;
+        }
+        lock_or_recover(&self.fallback, "fallback config").clone()
+    }
+
+    /// Returns true when the write must be skipped. The in-memory copy still tracks
+    /// settings changes so the running session stays consistent.
+    fn skip_save(&self, config: Option<&AppConfig>) -> bool {
+        if !self.is_read_only() {
+            return false;
+        }
+        if let Some(config) = config {
+            *lock_or_recover(&self.fallback, "fallback config") = Some(config.clone());
+        }
+        if !self.warned.swap(true, Ordering::Relaxed) {
+            tracing::warn!("Config storage is read-only; changes will not be persisted");
+        }
+        true
+    }
+}
+
+/// Load the config at startup without ever failing: an unwritable directory falls back to
+/// whatever could be read (or defaults) and marks `status` read-only.
+pub fn open(dir: &Path, status: &StorageStatus) -> AppConfig {
+    let path = dir.join(CONFIG_FILE);
+    match probe_writable(dir).and_then(|_| load_or_create_at(&path)) {
+        Ok(config) => config,
+        Err(e) => {
+            let config = read_existing(&path).unwrap_or_default();
+            status.enter_read_only(&config, e);
+            config
+        }
+    }
+}
+
+fn probe_writable(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    let probe = dir.join(WRITE_PROBE_FILE);
+    fs::write(&probe, b"").map_err(|e| format!("Config dir is not writable: {}", e))?;
+    let _ = fs::remove_file(probe);
+    Ok(())
+}
+
+fn read_existing(path: &Path) -> Option<AppConfig> {
+    let raw = fs::read_to_string(path).ok()?;
+    let mut config = serde_json::from_str::<AppConfig>(&raw).ok()?;
+    normalize_config(&mut config);
+    Some(config)
+}
+
+fn storage_status(app: &AppHandle) -> Option<tauri::State<'_, StorageStatus>> {
+    app.try_state::<StorageStatus>()
 }
 
 pub fn normalize_hotkey(input: &str) -> String {
@@ -165,30 +656,50 @@ pub fn normalize_language(input: &str) -> String {
 }
 
 pub fn load_or_create(app: &AppHandle) -> Result<AppConfig, String> {
+    if let Some(config) = storage_status(app).and_then(|status| status.fallback_config()) {
+        return Ok(config);
+    }
     let path = config_path(app)?;
+    let mut config = load_or_create_at(&path)?;
+    if migrate_api_keys(&mut config) {
+        save_raw(&path, &config)?;
+    }
+    Ok(config)
+}
+
+pub(crate) fn load_or_create_at(path: &PathBuf) -> Result<AppConfig, String> {
     if !path.exists() {
         let config = AppConfig::default();
-        save_raw(&path, &config)?;
+        save_raw(path, &config)?;
         return Ok(config);
     }
 
-    let raw = fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
+    let raw = fs::read_to_string(path).map_err(|e| format!("Failed to read config: {}", e))?;
     match serde_json::from_str::<AppConfig>(&raw) {
         Ok(mut config) => {
             normalize_config(&mut config);
             Ok(config)
         }
-        Err(_) => {
+        Err(e) => {
             let backup = path.with_extension("json.bak");
-            let _ = fs::copy(&path, backup);
+            tracing::warn!(
+                "{}: config could not be parsed ({}); reset to defaults, old file kept at {}",
+                ErrorCode::CfgCorruptRecovered,
+                e,
+                backup.display()
+            );
+            let _ = fs::copy(path, backup);
             let config = AppConfig::default();
-            save_raw(&path, &config)?;
+            save_raw(path, &config)?;
             Ok(config)
         }
     }
 }
 
 pub fn save(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    if storage_status(app).is_some_and(|status| status.skip_save(Some(config))) {
+        return Ok(());
+    }
     let path = config_path(app)?;
     save_raw(&path, config)
 }
@@ -198,7 +709,7 @@ pub fn setup_state(config: &AppConfig) -> SetupState {
         setup_completed: config.setup_completed,
         user_name: config.user_name.clone(),
         use_case: config.use_case.clone(),
-        has_api_key: config.groq_api_key_obfuscated.is_some(),
+        has_api_key: decode_api_key(config).is_some(),
         input_device_name: config.input_device_name.clone(),
         hotkey: normalize_hotkey(&config.hotkey),
         language: normalize_language(&config.language),
@@ -209,7 +720,6 @@ pub fn setup_state(config: &AppConfig) -> SetupState {
 pub fn save_setup_partial(app: &AppHandle, payload: SetupPartialPayload) -> Result<AppConfig, String> {
     let mut config = load_or_create(app)?;
     apply_partial(&mut config, payload);
-    recompute_stats(&mut config);
     save(app, &config)?;
     Ok(config)
 }
@@ -223,65 +733,129 @@ pub fn complete_setup(app: &AppHandle, payload: CompleteSetupPayload) -> Result<
         payload.use_case.trim().to_string()
     };
     if !payload.api_key.trim().is_empty() {
-        config.groq_api_key_obfuscated = Some(obfuscate_api_key(payload.api_key.trim()));
+        store_api_key(&mut config, ApiKeySlot::Groq, &payload.api_key);
     }
     config.input_device_name = normalize_device_name(payload.input_device_name);
     config.hotkey = normalize_hotkey(&payload.hotkey);
     config.language = normalize_language(&payload.language);
     config.setup_completed = true;
-    recompute_stats(&mut config);
     save(app, &config)?;
     Ok(config)
 }
 
 pub fn dashboard_data(app: &AppHandle, app_version: &str) -> Result<DashboardData, String> {
-    let mut config = load_or_create(app)?;
-    recompute_stats(&mut config);
-    save(app, &config)?;
+    let config = load_or_create(app)?;
+    let store = history_store::for_app(app)?;
+    let totals = store.stats()?;
 
-    let minutes_saved = if config.stats.total_words == 0 {
-        0.0
-    } else {
-        ((config.stats.total_words as f32 / 130.0) * 10.0).round() / 10.0
-    };
-    let wpm = if config.stats.total_words == 0 || config.stats.total_recording_seconds <= 0.1 {
+    let typing_wpm = stats::typing_wpm(config.typing_wpm);
+    let minutes_saved = (stats::time_saved(totals.total_words, typing_wpm) * 10.0).round() / 10.0;
+    let wpm = if totals.total_words == 0 || totals.total_recording_seconds <= 0.1 {
         0.0
     } else {
-        ((config.stats.total_words as f32 / (config.stats.total_recording_seconds / 60.0)) * 10.0)
-            .round()
+        ((totals.total_words as f32 / (totals.total_recording_seconds / 60.0)) * 10.0).round()
             / 10.0
     };
 
     Ok(DashboardData {
         user_name: config.user_name.clone(),
-        has_api_key: config.groq_api_key_obfuscated.is_some(),
+        has_api_key: decode_api_key(&config).is_some(),
         api_key_masked: decode_api_key(&config).map(|key| mask_api_key(&key)),
+        elevenlabs_api_key_masked: decode_elevenlabs_key(&config).map(|key| mask_api_key(&key)),
+        openrouter_api_key_masked: decode_openrouter_key(&config).map(|key| mask_api_key(&key)),
+        key_status: key_health::current_status(&config),
         input_device_name: config.input_device_name.clone(),
         hotkey: normalize_hotkey(&config.hotkey),
+        hotkeys: config.hotkeys.clone(),
         language: normalize_language(&config.language),
+        privacy_mode: config.privacy_mode,
+        offline_mode: config.offline_mode,
+        offline_queue: config.offline_queue,
+        audio_archive: config.audio_archive,
+        pending_transcriptions: pending_ids(&store)?.len(),
+        pill_focusable: config.pill_focusable,
+        pill_follows_active_monitor: config.pill_follows_active_monitor,
+        restore_clipboard: config.restore_clipboard,
+        clipboard_restore_delay_ms: config.clipboard_restore_delay_ms,
+        live_transcription: config.live_transcription,
+        noise_suppression: config.noise_suppression,
+        a11y_announcements: config.a11y_announcements,
+        session_warmup: config.session_warmup,
+        input_gain: config.input_gain,
+        pre_roll_ms: config.pre_roll_ms,
+        max_recording_secs: config.max_recording_secs,
+        silence_gate: config.silence_gate,
+        integrations: config.integrations.clone(),
+        post_actions: config.post_actions.clone(),
+        post_processing: config.post_processing.clone(),
+        dictation_commands: config.dictation_commands,
+        prompt_profile_id: config.prompt_profile_id.clone(),
+        optimization_mode: config.optimization_mode.clone(),
         stats: DashboardStats {
-            total_transcriptions: config.stats.total_transcriptions,
-            total_words: config.stats.total_words,
+            total_transcriptions: totals.total_transcriptions,
+            total_words: totals.total_words,
             minutes_saved,
             wpm,
+            typing_wpm,
         },
-        history: config.history,
+        history_trash_count: store.trash_count(Utc::now())?,
+        history_total: store.count()?,
+        history_limit: config.history_limit,
+        mirror_history_to_folder: config.mirror_history_to_folder.clone(),
+        typing_wpm: config.typing_wpm,
+        history: store
+            .recent(HISTORY_PAGE_SIZE)?
+            .into_iter()
+            .map(|item| HistoryEntry::new(item, typing_wpm))
+            .collect(),
         github_url: GITHUB_URL.to_string(),
         app_version: app_version.to_string(),
     })
 }
 
-pub fn record_history(app: &AppHandle, payload: RecordHistoryPayload) -> Result<(), String> {
+/// Append a transcription to history. Returns what changed, which is nothing when no
+/// item was written (empty text or privacy mode).
+pub fn record_history(
+    app: &AppHandle,
+    payload: RecordHistoryPayload,
+    privacy: &PrivacyGuard,
+) -> Result<ChangeSummary, String> {
+    let config = load_or_create(app)?;
+    let store = history_store::for_app(app)?;
+    record_history_in(&store, &config, payload, privacy)
+}
+
+fn record_history_in(
+    store: &HistoryStore,
+    config: &AppConfig,
+    payload: RecordHistoryPayload,
+    privacy: &PrivacyGuard,
+) -> Result<ChangeSummary, String> {
+    if !privacy.allows_persistence() {
+        tracing::info!("Privacy mode: transcription not written to history");
+        return Ok(ChangeSummary::default());
+    }
+
     let cleaned_text = payload.text.trim();
     if cleaned_text.is_empty() {
-        return Ok(());
+        return Ok(ChangeSummary::default());
     }
 
-    let mut config = load_or_create(app)?;
+    let mut archived = store.audio_paths()?;
+    archived.extend(payload.audio_path.as_deref().map(PathBuf::from));
     let word_count = payload
         .word_count
         .unwrap_or_else(|| count_words(cleaned_text) as u32);
     let duration_seconds = payload.duration_seconds.max(0.0);
+    let provider = payload.provider.or_else(|| {
+        payload.session_stats.as_ref().and_then(|stats| {
+            stats
+                .provider_breakdown
+                .iter()
+                .max_by_key(|entry| entry.segments)
+                .map(|entry| entry.provider.clone())
+        })
+    });
 
     let item = HistoryItem {
         id: uuid::Uuid::new_v4().to_string(),
@@ -289,45 +863,457 @@ pub fn record_history(app: &AppHandle, payload: RecordHistoryPayload) -> Result<
         timestamp: payload.timestamp.unwrap_or_else(|| Utc::now().to_rfc3339()),
         duration_seconds,
         word_count,
+        provider,
+        audio_path: payload.audio_path,
+        versions: Vec::new(),
+        status: HistoryStatus::Transcribed,
+        request_id: payload.request_id,
+        session_stats: payload.session_stats,
     };
 
-    config.history.insert(0, item);
-    if config.history.len() > HISTORY_LIMIT {
-        config.history.truncate(HISTORY_LIMIT);
+    store.insert(&item)?;
+    let mut change = ChangeSummary::added(1);
+    change.merge(store.enforce_limit(config.history_limit, &config.audio_archive)?);
+    audio_archive::remove_released(&archived, store);
+    Ok(change)
+}
+
+pub fn delete_history_item(app: &AppHandle, id: &str) -> Result<ChangeSummary, String> {
+    let store = history_store::for_app(app)?;
+    let archived = store.audio_paths()?;
+    let removed = store.delete(id)?;
+    audio_archive::remove_released(&archived, &store);
+    Ok(ChangeSummary::removed(u32::from(removed)))
+}
+
+pub fn clear_history_scope(app: &AppHandle) -> Result<DestructiveScope, String> {
+    history_store::for_app(app)?.scope()
+}
+
+/// Move every history item to the trash; see [`restore_history_trash`].
+pub fn clear_history(app: &AppHandle) -> Result<(), String> {
+    history_store::for_app(app)?
+        .move_to_trash(Utc::now())
+        .map(|_| ())
+}
+
+/// Put trashed items back into history, unless that would take it past `history_limit`.
+/// Returns how many were restored.
+pub fn restore_history_trash(app: &AppHandle) -> Result<usize, String> {
+    let config = load_or_create(app)?;
+    let store = history_store::for_app(app)?;
+    let archived = store.audio_paths()?;
+    let restored = store.restore_trash(Utc::now(), config.history_limit)?;
+    store.enforce_limit(config.history_limit, &config.audio_archive)?;
+    audio_archive::remove_released(&archived, &store);
+    Ok(restored)
+}
+
+/// Bring history and trash written to config.json by older versions into `store`, then
+/// drop them from the config. Nothing is dropped unless the import went through, and an
+/// import interrupted before the config is saved runs again harmlessly next time.
+pub fn migrate_legacy_history(
+    app: &AppHandle,
+    config: &mut AppConfig,
+    store: &HistoryStore,
+) -> Result<usize, String> {
+    if config.legacy_history.is_empty() && config.legacy_history_trash.is_none() {
+        return Ok(0);
     }
+    let imported = import_legacy_history(config, store)?;
+    save(app, config)?;
+    Ok(imported)
+}
 
-    recompute_stats(&mut config);
-    save(app, &config)
+fn import_legacy_history(config: &mut AppConfig, store: &HistoryStore) -> Result<usize, String> {
+    let imported =
+        store.import_legacy(&config.legacy_history, config.legacy_history_trash.as_ref())?;
+    config.legacy_history.clear();
+    config.legacy_history_trash = None;
+    Ok(imported)
+}
+
+/// Select the history items matching `filter` that can be re-transcribed.
+/// Items without archived audio (or whose file is gone) are reported as skipped.
+pub fn plan_retranscription(
+    history: &[HistoryItem],
+    filter: &HistoryFilter,
+    options: &RetranscribeOptions,
+) -> RetranscribePlan {
+    let mut plan = RetranscribePlan::default();
+
+    let matches = history.iter().filter(|item| match filter {
+        HistoryFilter::Ids(ids) => ids.iter().any(|id| id == &item.id),
+        HistoryFilter::DateRange { from, to } => {
+            timestamp_in_range(&item.timestamp, from.as_deref(), to.as_deref())
+        }
+    });
+
+    for item in matches {
+        if options
+            .max_items
+            .is_some_and(|max| plan.queued.len() >= max)
+        {
+            plan.skipped.push(SkippedHistoryItem {
+                id: item.id.clone(),
+                reason: "max_items_reached".to_string(),
+            });
+            continue;
+        }
+
+        match item.audio_path.as_deref() {
+            Some(path) if Path::new(path).exists() => plan.queued.push(RetranscribeTarget {
+                id: item.id.clone(),
+                audio_path: path.to_string(),
+            }),
+            Some(_) => plan.skipped.push(SkippedHistoryItem {
+                id: item.id.clone(),
+                reason: "audio_missing".to_string(),
+            }),
+            None => plan.skipped.push(SkippedHistoryItem {
+                id: item.id.clone(),
+                reason: "no_archived_audio".to_string(),
+            }),
+        }
+    }
+
+    if let HistoryFilter::Ids(ids) = filter {
+        for id in ids {
+            if !history.iter().any(|item| &item.id == id) {
+                plan.skipped.push(SkippedHistoryItem {
+                    id: id.clone(),
+                    reason: "not_found".to_string(),
+                });
+            }
+        }
+    }
+
+    plan
+}
+
+/// One page of history items matching `search`, newest first.
+/// `typing_wpm` sets the time saved shown for each item.
+pub fn search_history(
+    history: &[HistoryItem],
+    search: &HistorySearch,
+    typing_wpm: f32,
+) -> HistorySearchResult {
+    let terms: Vec<String> = normalize_for_match(&search.query)
+        .split(' ')
+        .filter(|term| !term.is_empty())
+        .map(str::to_string)
+        .collect();
+    let limit = match search.limit {
+        0 => HISTORY_PAGE_SIZE,
+        limit => limit.min(MAX_HISTORY_PAGE_SIZE),
+    };
+
+    let ranged = search.from.is_some() || search.to.is_some();
+
+    let matches: Vec<&HistoryItem> = history
+        .iter()
+        .filter(|item| {
+            !ranged
+                || timestamp_in_range(
+                    &item.timestamp,
+                    search.from.as_deref(),
+                    search.to.as_deref(),
+                )
+        })
+        .filter(|item| {
+            if terms.is_empty() {
+                return true;
+            }
+            let text = normalize_for_match(&item.text);
+            terms.iter().all(|term| text.contains(term.as_str()))
+        })
+        .collect();
+
+    HistorySearchResult {
+        total: matches.len(),
+        items: matches
+            .into_iter()
+            .skip(search.page.saturating_mul(limit))
+            .take(limit)
+            .map(|item| HistoryEntry::new(item.clone(), typing_wpm))
+            .collect(),
+        page: search.page,
+        limit,
+    }
 }
 
-pub fn delete_history_item(app: &AppHandle, id: &str) -> Result<(), String> {
+/// Store a re-transcription result as the newest version of a history item.
+pub fn record_history_version(
+    app: &AppHandle,
+    id: &str,
+    text: &str,
+    provider: &str,
+) -> Result<bool, String> {
+    let store = history_store::for_app(app)?;
+    let Some(mut item) = store.get(id)? else {
+        return Ok(false);
+    };
+
+    if !append_history_version(&mut item, text, provider) {
+        return Ok(false);
+    }
+    store.update(&item)
+}
+
+pub fn set_privacy_mode(app: &AppHandle, enabled: bool) -> Result<AppConfig, String> {
     let mut config = load_or_create(app)?;
-    config.history.retain(|item| item.id != id);
-    recompute_stats(&mut config);
-    save(app, &config)
+    config.privacy_mode = enabled;
+    save(app, &config)?;
+    Ok(config)
 }
 
-pub fn clear_history(app: &AppHandle) -> Result<(), String> {
+pub fn set_language(app: &AppHandle, language: &str) -> Result<AppConfig, String> {
+    let mut config = load_or_create(app)?;
+    config.language = normalize_language(language);
+    save(app, &config)?;
+    Ok(config)
+}
+
+/// Count the language a dictation was `detected` in. Returns a suggestion to switch once
+/// most recent dictations disagree with the configured language.
+pub fn observe_detected_language(
+    app: &AppHandle,
+    detected: &str,
+) -> Result<Option<LanguageSuggestion>, String> {
     let mut config = load_or_create(app)?;
-    config.history.clear();
-    recompute_stats(&mut config);
+    let before = config.language_suggestion.clone();
+    let configured = normalize_language(&config.language);
+    let suggestion = config.language_suggestion.observe(&configured, detected);
+    if config.language_suggestion != before {
+        save(app, &config)?;
+    }
+    Ok(suggestion)
+}
+
+/// Keep the configured language and stop suggesting another for it.
+pub fn dismiss_language_suggestion(app: &AppHandle) -> Result<(), String> {
+    let mut config = load_or_create(app)?;
+    config.language_suggestion.dismiss();
     save(app, &config)
 }
 
+/// Take the pending language suggestion; applying it is up to the caller.
+pub fn take_language_suggestion(app: &AppHandle) -> Result<Option<String>, String> {
+    let mut config = load_or_create(app)?;
+    let suggested = config.language_suggestion.accept();
+    save(app, &config)?;
+    Ok(suggested)
+}
+
+pub fn set_optimization_mode(app: &AppHandle, mode: OptimizationMode) -> Result<AppConfig, String> {
+    let mut config = load_or_create(app)?;
+    config.optimization_mode = mode;
+    save(app, &config)?;
+    Ok(config)
+}
+
+/// Point the default profile back at clarity when `deleted` was it.
+pub fn forget_prompt_profile(app: &AppHandle, deleted: &str) -> Result<AppConfig, String> {
+    let mut config = load_or_create(app)?;
+    if config.prompt_profile_id == deleted {
+        config.prompt_profile_id = DEFAULT_PROMPT_PROFILE.to_string();
+        save(app, &config)?;
+    }
+    Ok(config)
+}
+
+/// Blank profile ids fall back to the clarity profile.
+fn normalize_prompt_profile(profile_id: &str) -> String {
+    match profile_id.trim() {
+        "" => DEFAULT_PROMPT_PROFILE.to_string(),
+        id => id.to_string(),
+    }
+}
+
+pub fn set_offline_mode(app: &AppHandle, enabled: bool) -> Result<AppConfig, String> {
+    let mut config = load_or_create(app)?;
+    config.offline_mode = enabled;
+    save(app, &config)?;
+    Ok(config)
+}
+
+/// Ids of items waiting in the offline queue, oldest first.
+pub fn pending_ids(store: &HistoryStore) -> Result<Vec<String>, String> {
+    Ok(store
+        .pending()?
+        .into_iter()
+        .rev()
+        .map(|item| item.id)
+        .collect())
+}
+
+/// Keep `audio` for later: archive it and add a pending placeholder to history.
+pub fn enqueue_pending(
+    app: &AppHandle,
+    audio: &AudioBuffer,
+    privacy: &PrivacyGuard,
+) -> Result<HistoryItem, String> {
+    if storage_status(app).is_some_and(|status| status.skip_save(None)) {
+        return Err("Storage is read-only; the recording cannot be kept".to_string());
+    }
+    let archive_dir = Resolver::for_app(app)?.audio_archive_dir();
+    let config = load_or_create(app)?;
+    let store = history_store::for_app(app)?;
+    let mut queued = enqueue_pending_in(
+        &store,
+        &config,
+        &archive_dir,
+        std::slice::from_ref(audio),
+        privacy,
+    )?;
+    queued
+        .pop()
+        .ok_or_else(|| "The recording was not queued".to_string())
+}
+
+/// Keep `recordings` for later, oldest first, as one batch: the queue takes all of them or,
+/// when they don't fit its bounds together, none.
+pub(crate) fn enqueue_pending_in(
+    store: &HistoryStore,
+    config: &AppConfig,
+    archive_dir: &Path,
+    recordings: &[AudioBuffer],
+    privacy: &PrivacyGuard,
+) -> Result<Vec<HistoryItem>, String> {
+    if !privacy.allows_persistence() {
+        return Err("Privacy mode is on; the recording is not kept".to_string());
+    }
+
+    let archived = store.audio_paths()?;
+    let encoded: Vec<Vec<u8>> = recordings.iter().map(wav::encode_wav).collect();
+    let sizes: Vec<u64> = encoded.iter().map(|bytes| bytes.len() as u64).collect();
+    offline_queue::check_capacity(&config.offline_queue, &store.pending()?, &sizes)?;
+
+    fs::create_dir_all(archive_dir)
+        .map_err(|e| format!("Failed to create audio archive dir: {}", e))?;
+    let mut items = Vec::with_capacity(recordings.len());
+    let mut written = Vec::with_capacity(recordings.len());
+    let remove_written = |written: &[PathBuf]| {
+        for path in written {
+            let _ = fs::remove_file(path);
+        }
+    };
+    for (audio, bytes) in recordings.iter().zip(encoded) {
+        let id = uuid::Uuid::new_v4().to_string();
+        let audio_path = archive_dir.join(format!("{}.wav", id));
+        if let Err(e) = fs::write(&audio_path, bytes) {
+            remove_written(&written);
+            return Err(format!("Failed to archive audio: {}", e));
+        }
+        items.push(HistoryItem {
+            id,
+            text: PENDING_TRANSCRIPTION_TEXT.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            duration_seconds: audio.duration_secs.max(0.0),
+            word_count: 0,
+            provider: None,
+            audio_path: Some(audio_path.display().to_string()),
+            versions: Vec::new(),
+            status: HistoryStatus::PendingTranscription,
+            request_id: None,
+            session_stats: None,
+        });
+        written.push(audio_path);
+    }
+    if let Err(e) = store.insert_all(&items) {
+        remove_written(&written);
+        return Err(e);
+    }
+    store.enforce_limit(config.history_limit, &config.audio_archive)?;
+    audio_archive::remove_released(&archived, store);
+    Ok(items)
+}
+
+/// Replace a pending placeholder with its transcript. The queued audio stays as the item's
+/// archived recording when the audio archive is on and is deleted otherwise. An empty
+/// transcript removes the item. Returns what changed, nothing when the item is no longer
+/// pending.
+pub(crate) fn complete_pending_in(
+    store: &HistoryStore,
+    config: &AppConfig,
+    id: &str,
+    text: &str,
+    provider: &str,
+) -> Result<ChangeSummary, String> {
+    let Some(mut item) = store
+        .get(id)?
+        .filter(|item| item.status == HistoryStatus::PendingTranscription)
+    else {
+        return Ok(ChangeSummary::default());
+    };
+
+    let archived = store.audio_paths()?;
+    let mut change = if append_history_version(&mut item, text, provider) {
+        item.status = HistoryStatus::Transcribed;
+        store.update(&item)?;
+        ChangeSummary::updated(1)
+    } else {
+        store.delete(id)?;
+        ChangeSummary::removed(1)
+    };
+    change.merge(store.enforce_limit(config.history_limit, &config.audio_archive)?);
+    audio_archive::remove_released(&archived, store);
+    Ok(change)
+}
+
+/// Drop a pending item and its audio. Returns false when no pending item has this id.
+pub fn cancel_pending(app: &AppHandle, id: &str) -> Result<bool, String> {
+    let store = history_store::for_app(app)?;
+    cancel_pending_in(&store, id)
+}
+
+fn cancel_pending_in(store: &HistoryStore, id: &str) -> Result<bool, String> {
+    let Some(mut item) = store
+        .get(id)?
+        .filter(|item| item.status == HistoryStatus::PendingTranscription)
+    else {
+        return Ok(false);
+    };
+    store.delete(id)?;
+    remove_queued_audio(&mut item);
+    Ok(true)
+}
+
+fn remove_queued_audio(item: &mut HistoryItem) {
+    if let Some(path) = item.audio_path.take() {
+        if let Err(e) = fs::remove_file(&path) {
+            tracing::warn!("Failed to remove queued audio {}: {}", path, e);
+        }
+    }
+}
+
+/// Apply preset `name`, or end the active preset when `name` is `None`.
+pub fn set_active_preset(app: &AppHandle, name: Option<&str>) -> Result<AppConfig, String> {
+    let mut config = load_or_create(app)?;
+    match name {
+        Some(name) => presets::apply(&mut config, name)?,
+        None => {
+            presets::end(&mut config);
+        }
+    }
+    save(app, &config)?;
+    Ok(config)
+}
+
 pub fn update_settings(app: &AppHandle, payload: UpdateSettingsPayload) -> Result<AppConfig, String> {
     let mut config = load_or_create(app)?;
+    let previous_limits = (config.history_limit, config.audio_archive);
 
     if let Some(user_name) = payload.user_name {
         config.user_name = user_name.trim().to_string();
     }
 
-    if let Some(api_key) = payload.api_key {
-        let trimmed = api_key.trim();
-        if trimmed.is_empty() {
-            config.groq_api_key_obfuscated = None;
-        } else {
-            config.groq_api_key_obfuscated = Some(obfuscate_api_key(trimmed));
-        }
+    for (slot, update) in [
+        (ApiKeySlot::Groq, payload.api_key),
+        (ApiKeySlot::ElevenLabs, payload.elevenlabs_api_key),
+        (ApiKeySlot::OpenRouter, payload.openrouter_api_key),
+    ] {
+        apply_key_update(&mut config, slot, update)?;
     }
 
     if payload.input_device_name.is_some() {
@@ -338,27 +1324,319 @@ pub fn update_settings(app: &AppHandle, payload: UpdateSettingsPayload) -> Resul
         config.hotkey = normalize_hotkey(&hotkey);
     }
 
+    if let Some(extra) = payload.hotkeys {
+        config.hotkeys = hotkeys::normalize(extra);
+    }
+    hotkeys::bindings(&config.hotkey, &config.hotkeys)?;
+
     if let Some(language) = payload.language {
         config.language = normalize_language(&language);
     }
 
-    recompute_stats(&mut config);
+    if let Some(pill_focusable) = payload.pill_focusable {
+        config.pill_focusable = pill_focusable;
+    }
+    if let Some(follows) = payload.pill_follows_active_monitor {
+        config.pill_follows_active_monitor = follows;
+    }
+    if let Some(restore_clipboard) = payload.restore_clipboard {
+        config.restore_clipboard = restore_clipboard;
+    }
+    if let Some(delay_ms) = payload.clipboard_restore_delay_ms {
+        config.clipboard_restore_delay_ms = delay_ms.min(MAX_CLIPBOARD_RESTORE_DELAY_MS);
+    }
+    if let Some(live_transcription) = payload.live_transcription {
+        config.live_transcription = live_transcription;
+    }
+
+    if let Some(noise_suppression) = payload.noise_suppression {
+        config.noise_suppression = noise_suppression;
+    }
+    if let Some(a11y_announcements) = payload.a11y_announcements {
+        config.a11y_announcements = a11y_announcements;
+    }
+    if let Some(session_warmup) = payload.session_warmup {
+        config.session_warmup = session_warmup;
+    }
+    if let Some(gain) = payload.input_gain.filter(|gain| gain.is_finite()) {
+        config.input_gain = gain.clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN);
+    }
+    if let Some(pre_roll_ms) = payload.pre_roll_ms {
+        config.pre_roll_ms = pre_roll_ms.min(MAX_PRE_ROLL_MS);
+    }
+    if let Some(max_recording_secs) = payload.max_recording_secs {
+        config.max_recording_secs = max_recording_secs;
+    }
+    if let Some(history_limit) = payload.history_limit {
+        config.history_limit = history_limit;
+    }
+    if let Some(folder) = payload.mirror_history_to_folder {
+        config.mirror_history_to_folder =
+            Some(folder).filter(|folder| !folder.as_os_str().is_empty());
+    }
+    if let Some(wpm) = payload.typing_wpm {
+        config.typing_wpm =
+            (wpm > 0.0).then(|| wpm.clamp(stats::MIN_TYPING_WPM, stats::MAX_TYPING_WPM));
+    }
+    if let Some(offline_queue) = payload.offline_queue {
+        config.offline_queue = offline_queue;
+    }
+    if let Some(audio_archive) = payload.audio_archive {
+        config.audio_archive = audio_archive;
+    }
+    if let Some(silence_gate) = payload.silence_gate {
+        config.silence_gate = silence_gate.clamped();
+    }
+    if let Some(actions) = payload.post_actions {
+        post_actions::validate(&actions)?;
+        config.post_actions = actions;
+    }
+    if let Some(profile_id) = payload.prompt_profile_id {
+        config.prompt_profile_id = normalize_prompt_profile(&profile_id);
+    }
+    if let Some(mode) = payload.optimization_mode {
+        config.optimization_mode = mode;
+    }
+
+    if let Some(integrations) = payload.integrations {
+        config.integrations = integrations;
+    }
+
+    if let Some(post_processing) = payload.post_processing {
+        config.post_processing = post_processing;
+    }
+    if let Some(dictation_commands) = payload.dictation_commands {
+        config.dictation_commands = dictation_commands;
+    }
+
     save(app, &config)?;
+    if (config.history_limit, config.audio_archive) != previous_limits {
+        let store = history_store::for_app(app)?;
+        let archived = store.audio_paths()?;
+        store.enforce_limit(config.history_limit, &config.audio_archive)?;
+        audio_archive::remove_released(&archived, &store);
+    }
     Ok(config)
 }
 
-pub fn decode_api_key(config: &AppConfig) -> Option<String> {
+/// How long to wait before restoring the clipboard after a paste; `None` leaves the
+/// transcript on it.
+pub fn clipboard_restore_delay(config: &AppConfig) -> Option<std::time::Duration> {
     config
-        .groq_api_key_obfuscated
-        .as_deref()
-        .and_then(deobfuscate_api_key)
+        .restore_clipboard
+        .then(|| std::time::Duration::from_millis(config.clipboard_restore_delay_ms))
+}
+
+/// Rates for the usage estimate: the built-in ones, overridden by `usage_rates`. Negative
+/// or non-numeric rates are ignored.
+pub fn usage_rates(config: &AppConfig) -> BTreeMap<String, f64> {
+    let mut rates = usage::default_rates();
+    rates.extend(
+        config
+            .usage_rates
+            .iter()
+            .filter(|(_, rate)| rate.is_finite() && **rate >= 0.0)
+            .map(|(provider, rate)| (provider.clone(), *rate)),
+    );
+    rates
+}
+
+pub fn post_processing_options(config: &AppConfig) -> PostProcessingOptions {
+    PostProcessingOptions {
+        language: normalize_language(&config.language),
+        clarity: config.post_processing.clarity,
+        remove_fillers: config.post_processing.remove_fillers,
+        replacements: config.post_processing.replacements.clone(),
+        dictation_commands: config.dictation_commands,
+    }
+}
+
+pub fn decode_api_key(config: &AppConfig) -> Option<String> {
+    decode_key(config, ApiKeySlot::Groq)
+}
+
+pub fn decode_elevenlabs_key(config: &AppConfig) -> Option<String> {
+    decode_key(config, ApiKeySlot::ElevenLabs)
+}
+
+pub fn decode_openrouter_key(config: &AppConfig) -> Option<String> {
+    decode_key(config, ApiKeySlot::OpenRouter)
+}
+
+/// What the transcription providers are built from: the stored keys, the local engines,
+/// the session's preferred order, the `providers` overrides and the `transcription`
+/// settings. Local engines find their models and write their scratch files in `resolver`'s
+/// directories.
+pub fn provider_setup(config: &AppConfig, resolver: &Resolver) -> ProviderSetup {
+    let engines = &config.local_engines;
+    let path = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let language = normalize_language(&config.language);
+    ProviderSetup {
+        groq_key: decode_api_key(config),
+        elevenlabs_key: decode_elevenlabs_key(config),
+        language: matches!(language.as_str(), "pt" | "en").then_some(language),
+        whisper: path(&engines.whisper_cpp_bin).map(|bin_path| LocalWhisper {
+            bin_path,
+            model_path: path(&engines.whisper_model),
+            options: WhisperOptions {
+                threads: engines.whisper_threads.filter(|threads| *threads > 0),
+                translate: engines.whisper_translate,
+                ..WhisperOptions::default()
+            },
+        }),
+        preferred: config.session.preferred_providers.clone(),
+        overrides: config.providers.clone(),
+        transcription: config.transcription.clone(),
+        vosk_model_pt: engines.vosk_model_pt.clone(),
+        vosk_model_en: engines.vosk_model_en.clone(),
+        models_dir: Some(resolver.models_dir()),
+        temp_dir: Some(resolver.temp_dir()),
+    }
+}
+
+/// Keys of the AI mode's LLM providers.
+pub fn llm_keys(config: &AppConfig) -> LlmKeys {
+    LlmKeys {
+        openrouter: decode_openrouter_key(config),
+        groq: decode_api_key(config),
+        gemini: None,
+    }
+}
+
+/// Config field holding `slot` before migration, or when the keychain refused it.
+fn obfuscated_field(config: &mut AppConfig, slot: ApiKeySlot) -> &mut Option<String> {
+    match slot {
+        ApiKeySlot::Groq => &mut config.groq_api_key_obfuscated,
+        ApiKeySlot::ElevenLabs => &mut config.elevenlabs_api_key_obfuscated,
+        ApiKeySlot::OpenRouter => &mut config.openrouter_api_key_obfuscated,
+    }
+}
+
+/// The obfuscated field wins when set: it is a key not migrated yet, one the keychain
+/// refused, or any key of a portable install.
+pub(crate) fn decode_key(config: &AppConfig, slot: ApiKeySlot) -> Option<String> {
+    let obfuscated = match slot {
+        ApiKeySlot::Groq => &config.groq_api_key_obfuscated,
+        ApiKeySlot::ElevenLabs => &config.elevenlabs_api_key_obfuscated,
+        ApiKeySlot::OpenRouter => &config.openrouter_api_key_obfuscated,
+    };
+    if let Some(obfuscated) = obfuscated.as_deref() {
+        return deobfuscate_api_key(obfuscated);
+    }
+    if !secrets::keychain_allowed() {
+        return None;
+    }
+    secrets::read(slot).unwrap_or_else(|e| {
+        tracing::debug!("Could not read {:?} key from the keychain: {}", slot, e);
+        None
+    })
+}
+
+/// Apply a settings save to the key in `slot`. A blank value or one carrying the mask (the
+/// dashboard's placeholder sent back) is refused rather than stored over the real key.
+fn apply_key_update(
+    config: &mut AppConfig,
+    slot: ApiKeySlot,
+    update: KeyUpdate,
+) -> Result<(), String> {
+    match update {
+        KeyUpdate::Unchanged => Ok(()),
+        KeyUpdate::Clear => {
+            store_api_key(config, slot, "");
+            Ok(())
+        }
+        KeyUpdate::Set(value) if value.trim().is_empty() => Err(format!(
+            "The {:?} API key is empty; clear it instead of saving a blank key",
+            slot
+        )),
+        KeyUpdate::Set(value) if value.contains(KEY_MASK) => Err(format!(
+            "The {:?} API key looks like the masked placeholder; paste the full key",
+            slot
+        )),
+        KeyUpdate::Set(value) => {
+            store_api_key(config, slot, &value);
+            Ok(())
+        }
+    }
+}
+
+/// The plain key in `slot`, for copying it back out. Callers gate this behind a
+/// confirmation token.
+pub fn reveal_api_key(app: &AppHandle, slot: ApiKeySlot) -> Result<String, String> {
+    let config = load_or_create(app)?;
+    decode_key(&config, slot).ok_or_else(|| format!("No {:?} API key is stored", slot))
+}
+
+/// Save a key in the keychain, or obfuscated in the config when the keychain is
+/// unavailable or the install is portable. An empty value clears the stored key.
+pub(crate) fn store_api_key(config: &mut AppConfig, slot: ApiKeySlot, api_key: &str) {
+    let trimmed = api_key.trim();
+    let keychain = secrets::keychain_allowed();
+    let field = obfuscated_field(config, slot);
+    if trimmed.is_empty() {
+        *field = None;
+        if !keychain {
+            return;
+        }
+        if let Err(e) = secrets::delete(slot) {
+            tracing::debug!("Could not remove {:?} key from the keychain: {}", slot, e);
+        }
+        return;
+    }
+
+    if !keychain {
+        tracing::warn!(
+            "Portable mode: {:?} key kept obfuscated in the config file, which is not encrypted",
+            slot
+        );
+        *field = Some(obfuscate_api_key(trimmed));
+        return;
+    }
+    match secrets::write(slot, trimmed) {
+        Ok(()) => *field = None,
+        Err(e) => {
+            tracing::warn!("Keeping {:?} key in the config: {}", slot, e);
+            *field = Some(obfuscate_api_key(trimmed));
+        }
+    }
+}
+
+/// Move keys still obfuscated in the config into the keychain. Returns true when the
+/// config changed; keys stay where they are while the keychain is unavailable, and always
+/// in portable mode.
+pub(crate) fn migrate_api_keys(config: &mut AppConfig) -> bool {
+    if !secrets::keychain_allowed() {
+        return false;
+    }
+    let mut changed = false;
+    for slot in ApiKeySlot::ALL {
+        let field = obfuscated_field(config, slot);
+        let Some(key) = field.as_deref().and_then(deobfuscate_api_key) else {
+            continue;
+        };
+        match secrets::write(slot, &key) {
+            Ok(()) => {
+                tracing::info!("Moved {:?} key into the OS keychain", slot);
+                *field = None;
+                changed = true;
+            }
+            Err(e) => {
+                tracing::debug!("{:?} key stays in the config: {}", slot, e);
+                break;
+            }
+        }
+    }
+    changed
 }
 
-fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let dir = app
-        .path()
-        .resolve(CONFIG_DIR, BaseDirectory::AppData)
-        .map_err(|e| format!("Failed to resolve config dir: {}", e))?;
+pub(crate) fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = Resolver::for_app(app)?.config_dir();
     fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
     Ok(dir.join(CONFIG_FILE))
 }
@@ -371,12 +1649,25 @@ fn save_raw(path: &PathBuf, config: &AppConfig) -> Result<(), String> {
 
 fn normalize_config(config: &mut AppConfig) {
     config.hotkey = normalize_hotkey(&config.hotkey);
+    config.hotkeys = hotkeys::normalize(std::mem::take(&mut config.hotkeys));
     config.language = normalize_language(&config.language);
     config.input_device_name = normalize_device_name(config.input_device_name.clone());
+    config.silence_gate = config.silence_gate.clamped();
+    migrate_session_settings(config);
     if config.use_case.trim().is_empty() {
         config.use_case = DEFAULT_USE_CASE.to_string();
     }
-    recompute_stats(config);
+}
+
+fn migrate_session_settings(config: &mut AppConfig) {
+    let configured = config.post_actions.clone();
+    config.session.migrate(&configured);
+    for preset in &mut config.presets {
+        preset.settings.migrate(&configured);
+    }
+    if let Some(active) = &mut config.active_preset {
+        active.previous.migrate(&configured);
+    }
 }
 
 fn apply_partial(config: &mut AppConfig, payload: SetupPartialPayload) {
@@ -392,9 +1683,8 @@ fn apply_partial(config: &mut AppConfig, payload: SetupPartialPayload) {
     }
 
     if let Some(api_key) = payload.api_key {
-        let trimmed = api_key.trim();
-        if !trimmed.is_empty() {
-            config.groq_api_key_obfuscated = Some(obfuscate_api_key(trimmed));
+        if !api_key.trim().is_empty() {
+            store_api_key(config, ApiKeySlot::Groq, &api_key);
         }
     }
 
@@ -411,34 +1701,57 @@ fn apply_partial(config: &mut AppConfig, payload: SetupPartialPayload) {
     }
 }
 
-fn count_words(text: &str) -> usize {
-    text.split_whitespace().count()
+fn append_history_version(item: &mut HistoryItem, text: &str, provider: &str) -> bool {
+    let cleaned_text = text.trim();
+    if cleaned_text.is_empty() {
+        return false;
+    }
+
+    // The first overwrite keeps what the item said before. A pending item's text is only
+    // a placeholder.
+    if item.versions.is_empty() && item.status == HistoryStatus::Transcribed {
+        item.versions.push(HistoryVersion {
+            text: item.text.clone(),
+            provider: item.provider.clone().unwrap_or_default(),
+            created_at: item.timestamp.clone(),
+        });
+    }
+    item.versions.push(HistoryVersion {
+        text: cleaned_text.to_string(),
+        provider: provider.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+    });
+    if item.versions.len() > HISTORY_VERSION_LIMIT {
+        let excess = item.versions.len() - HISTORY_VERSION_LIMIT;
+        item.versions.drain(0..excess);
+    }
+
+    item.text = cleaned_text.to_string();
+    item.word_count = count_words(cleaned_text) as u32;
+    item.provider = Some(provider.to_string());
+    true
 }
 
-fn recompute_stats(config: &mut AppConfig) {
-    let total_transcriptions = config.history.len() as u64;
-    let total_words = config
-        .history
-        .iter()
-        .map(|item| item.word_count as u64)
-        .sum::<u64>();
-    let total_recording_seconds = config
-        .history
-        .iter()
-        .map(|item| item.duration_seconds.max(0.0))
-        .filter(|duration| *duration > 0.05)
-        .sum::<f32>();
-    let total_seconds_saved = (total_words as f32 / 130.0) * 60.0;
-
-    config.stats = Stats {
-        total_transcriptions,
-        total_words,
-        total_recording_seconds,
-        total_seconds_saved,
+fn timestamp_in_range(timestamp: &str, from: Option<&str>, to: Option<&str>) -> bool {
+    let Ok(value) = DateTime::parse_from_rfc3339(timestamp) else {
+        return false;
     };
+
+    let after_from = from
+        .and_then(|bound| DateTime::parse_from_rfc3339(bound).ok())
+        .is_none_or(|bound| value >= bound);
+    let before_to = to
+        .and_then(|bound| DateTime::parse_from_rfc3339(bound).ok())
+        .is_none_or(|bound| value <= bound);
+
+    after_from && before_to
 }
 
-fn obfuscate_api_key(api_key: &str) -> String {
+fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+pub(crate) fn obfuscate_api_key(api_key: &str) -> String {
     let mut bytes = api_key.as_bytes().to_vec();
     for (idx, byte) in bytes.iter_mut().enumerate() {
         *byte ^= API_KEY_XOR_KEY[idx % API_KEY_XOR_KEY.len()];
@@ -454,14 +1767,14 @@ fn deobfuscate_api_key(obfuscated: &str) -> Option<String> {
     String::from_utf8(bytes).ok()
 }
 
-fn mask_api_key(api_key: &str) -> String {
+pub(crate) fn mask_api_key(api_key: &str) -> String {
     if api_key.len() <= 10 {
-        return "******".to_string();
+        return KEY_MASK.to_string();
     }
 
     let prefix = &api_key[..6];
     let suffix = &api_key[api_key.len().saturating_sub(4)..];
-    format!("{}********{}", prefix, suffix)
+    format!("{}{}**{}", prefix, KEY_MASK, suffix)
 }
 
 fn normalize_device_name(name: Option<String>) -> Option<String> {
@@ -474,3 +1787,741 @@ fn normalize_device_name(name: Option<String>) -> Option<String> {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zentra_core::session::ProviderBreakdown;
+
+    fn history_item(id: &str, timestamp: &str, audio_path: Option<String>) -> HistoryItem {
+        HistoryItem {
+            id: id.to_string(),
+            text: "texto original".to_string(),
+            timestamp: timestamp.to_string(),
+            duration_seconds: 3.0,
+            word_count: 2,
+            provider: None,
+            audio_path,
+            versions: Vec::new(),
+            status: HistoryStatus::Transcribed,
+            request_id: None,
+            session_stats: None,
+        }
+    }
+
+    #[test]
+    fn test_history_versions_are_capped_and_latest_is_shown() {
+        let mut item = history_item("a", "2026-01-01T10:00:00+00:00", None);
+
+        for idx in 1..=5 {
+            assert!(append_history_version(
+                &mut item,
+                &format!("versao {}", idx),
+                "groq"
+            ));
+        }
+
+        assert_eq!(item.versions.len(), HISTORY_VERSION_LIMIT);
+        assert_eq!(item.versions.first().unwrap().text, "versao 3");
+        assert_eq!(item.versions.last().unwrap().text, "versao 5");
+        assert_eq!(item.text, "versao 5");
+        assert_eq!(item.word_count, 2);
+        assert_eq!(item.provider.as_deref(), Some("groq"));
+    }
+
+    #[test]
+    fn test_first_version_keeps_the_original_text() {
+        let mut item = history_item("a", "2026-01-01T10:00:00+00:00", None);
+        item.provider = Some("elevenlabs".to_string());
+        assert!(append_history_version(&mut item, "texto melhor", "groq"));
+
+        let versions: Vec<_> = item
+            .versions
+            .iter()
+            .map(|version| (version.text.as_str(), version.provider.as_str()))
+            .collect();
+        assert_eq!(
+            versions,
+            [("texto original", "elevenlabs"), ("texto melhor", "groq")]
+        );
+        assert_eq!(item.versions[0].created_at, item.timestamp);
+        assert_eq!(item.text, "texto melhor");
+
+        let mut pending = history_item("b", "2026-01-01T10:00:00+00:00", None);
+        pending.status = HistoryStatus::PendingTranscription;
+        assert!(append_history_version(&mut pending, "transcrito", "groq"));
+        assert_eq!(pending.versions.len(), 1);
+    }
+
+    #[test]
+    fn test_empty_version_is_ignored() {
+        let mut item = history_item("a", "2026-01-01T10:00:00+00:00", None);
+        assert!(!append_history_version(&mut item, "   ", "groq"));
+        assert!(item.versions.is_empty());
+        assert_eq!(item.text, "texto original");
+    }
+
+    #[test]
+    fn test_history_item_without_versions_deserializes() {
+        let raw = r#"{"id":"a","text":"oi","timestamp":"2026-01-01T10:00:00+00:00","durationSeconds":1.0,"wordCount":1}"#;
+        let item: HistoryItem = serde_json::from_str(raw).unwrap();
+        assert!(item.versions.is_empty());
+        assert!(item.audio_path.is_none());
+    }
+
+    #[test]
+    fn test_plan_skips_items_without_archived_audio() {
+        let archived =
+            std::env::temp_dir().join(format!("zentra-plan-{}.wav", uuid::Uuid::new_v4()));
+        fs::write(&archived, b"RIFF").unwrap();
+
+        let history = vec![
+            history_item(
+                "with-audio",
+                "2026-01-03T10:00:00+00:00",
+                Some(archived.display().to_string()),
+            ),
+            history_item("no-audio", "2026-01-02T10:00:00+00:00", None),
+            history_item(
+                "gone",
+                "2026-01-01T10:00:00+00:00",
+                Some("/nonexistent/zentra.wav".to_string()),
+            ),
+        ];
+
+        let filter = HistoryFilter::Ids(vec![
+            "with-audio".to_string(),
+            "no-audio".to_string(),
+            "gone".to_string(),
+            "unknown".to_string(),
+        ]);
+        let plan = plan_retranscription(&history, &filter, &RetranscribeOptions::default());
+        let _ = fs::remove_file(&archived);
+
+        assert_eq!(plan.queued.len(), 1);
+        assert_eq!(plan.queued[0].id, "with-audio");
+        let reasons = plan
+            .skipped
+            .iter()
+            .map(|s| (s.id.as_str(), s.reason.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                ("no-audio", "no_archived_audio"),
+                ("gone", "audio_missing"),
+                ("unknown", "not_found"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_privacy_mode_blocks_history_writes() {
+        let store = HistoryStore::in_memory().unwrap();
+        let config = AppConfig::default();
+        let payload = || RecordHistoryPayload {
+            text: "conteudo sensivel".to_string(),
+            duration_seconds: 2.0,
+            word_count: None,
+            timestamp: None,
+            request_id: Some("r1".to_string()),
+            session_stats: Some(SessionStats {
+                provider_breakdown: vec![
+                    ProviderBreakdown {
+                        provider: "groq".to_string(),
+                        segments: 1,
+                        avg_confidence: 0.9,
+                    },
+                    ProviderBreakdown {
+                        provider: "elevenlabs".to_string(),
+                        segments: 3,
+                        avg_confidence: 0.8,
+                    },
+                ],
+                ..Default::default()
+            }),
+            provider: None,
+            audio_path: None,
+        };
+
+        let privacy = PrivacyGuard::new(true);
+        assert!(record_history_in(&store, &config, payload(), &privacy)
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.count().unwrap(), 0);
+
+        privacy.set_enabled(false);
+        assert_eq!(
+            record_history_in(&store, &config, payload(), &privacy).unwrap(),
+            ChangeSummary::added(1)
+        );
+        let stored = store.items().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].request_id.as_deref(), Some("r1"));
+        assert_eq!(stored[0].provider.as_deref(), Some("elevenlabs"));
+        assert_eq!(store.stats().unwrap().total_words, 2);
+    }
+
+    #[test]
+    fn test_recording_rotated_out_of_history_takes_its_audio_along() {
+        let dir = temp_dir("archive");
+        let store = HistoryStore::in_memory().unwrap();
+        let archive = dir.join("audio");
+        let config = AppConfig {
+            history_limit: 2,
+            audio_archive: AudioArchiveSettings {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![2_000i16; 1_600]);
+
+        let mut recordings = Vec::new();
+        let mut changes = Vec::new();
+        for text in ["um", "dois", "três"] {
+            let audio_path = audio_archive::archive(&archive, &audio).unwrap();
+            let payload = RecordHistoryPayload {
+                text: text.to_string(),
+                duration_seconds: 0.1,
+                word_count: None,
+                timestamp: None,
+                request_id: None,
+                session_stats: None,
+                provider: Some("groq".to_string()),
+                audio_path: Some(audio_path.display().to_string()),
+            };
+            let change =
+                record_history_in(&store, &config, payload, &PrivacyGuard::default()).unwrap();
+            changes.push(change);
+            recordings.push(audio_path);
+        }
+        let rotated = ChangeSummary {
+            added: 1,
+            removed: 1,
+            updated: 0,
+        };
+        assert_eq!(
+            changes,
+            vec![ChangeSummary::added(1), ChangeSummary::added(1), rotated]
+        );
+
+        assert!(!recordings[0].exists());
+        assert!(recordings[1].exists() && recordings[2].exists());
+        let stored = store.items().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(
+            stored[0].audio_path,
+            Some(recordings[2].display().to_string())
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_plan_filters_by_date_range() {
+        let history = vec![
+            history_item("new", "2026-01-10T10:00:00+00:00", None),
+            history_item("mid", "2026-01-05T10:00:00+00:00", None),
+            history_item("old", "2026-01-01T10:00:00+00:00", None),
+        ];
+
+        let filter = HistoryFilter::DateRange {
+            from: Some("2026-01-02T00:00:00+00:00".to_string()),
+            to: Some("2026-01-06T00:00:00+00:00".to_string()),
+        };
+        let plan = plan_retranscription(&history, &filter, &RetranscribeOptions::default());
+
+        assert!(plan.queued.is_empty());
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].id, "mid");
+    }
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("zentra-{}-{}", label, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_writable_dir_is_not_read_only() {
+        let dir = temp_dir("config-ok");
+        let status = StorageStatus::default();
+        let config = open(&dir, &status);
+        assert!(!status.is_read_only());
+        assert!(status.health().storage_available);
+        assert!(!status.skip_save(Some(&config)));
+        assert!(dir.join(CONFIG_FILE).exists());
+        assert!(!dir.join(WRITE_PROBE_FILE).exists());
+    }
+
+    #[test]
+    fn test_uncreatable_dir_falls_back_to_defaults() {
+        let parent = temp_dir("config-blocked");
+        let blocker = parent.join("not-a-dir");
+        fs::write(&blocker, b"").unwrap();
+
+        let status = StorageStatus::default();
+        let config = open(&blocker.join("zentra"), &status);
+        assert!(status.is_read_only());
+        assert!(!status.health().storage_available);
+        assert!(status.health().storage_message.is_some());
+        assert_eq!(config.hotkey, DEFAULT_HOTKEY);
+
+        let mut updated = config.clone();
+        updated.user_name = "Ana".to_string();
+        assert!(status.skip_save(Some(&updated)));
+        assert!(status.skip_save(None));
+        assert_eq!(status.fallback_config().unwrap().user_name, "Ana");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_dir_keeps_existing_settings_in_memory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("config-readonly");
+        let existing = AppConfig {
+            user_name: "Bia".to_string(),
+            ..AppConfig::default()
+        };
+        save_raw(&dir.join(CONFIG_FILE), &existing).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Permission bits don't bind root; nothing to simulate in that case.
+        if probe_writable(&dir).is_ok() {
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let status = StorageStatus::default();
+        let config = open(&dir, &status);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(status.is_read_only());
+        assert_eq!(config.user_name, "Bia");
+        assert_eq!(status.fallback_config().unwrap().user_name, "Bia");
+    }
+
+    #[test]
+    fn test_legacy_history_moves_out_of_config_json() {
+        let dir = temp_dir("config-legacy-history");
+        let path = dir.join(CONFIG_FILE);
+        let raw = r#"{
+            "user_name": "Bia",
+            "stats": {"total_transcriptions": 2, "total_words": 4},
+            "history": [
+                {"id":"b","text":"dois","timestamp":"2026-02-02T10:00:00Z","durationSeconds":1.0,"wordCount":1},
+                {"id":"a","text":"um","timestamp":"2026-02-01T10:00:00Z","durationSeconds":1.0,"wordCount":1}
+            ],
+            "history_trash": {"deletedAt": "2026-02-03T10:00:00Z", "items": []}
+        }"#;
+        fs::write(&path, raw).unwrap();
+
+        let mut config = load_or_create_at(&path).unwrap();
+        assert_eq!(config.legacy_history.len(), 2);
+        let store = HistoryStore::in_memory().unwrap();
+        assert_eq!(import_legacy_history(&mut config, &store).unwrap(), 2);
+        save_raw(&path, &config).unwrap();
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved.get("history").is_none());
+        assert!(saved.get("history_trash").is_none());
+        assert!(saved.get("stats").is_none());
+        assert_eq!(saved["user_name"], "Bia");
+        let ids: Vec<_> = store.items().unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_search_ignores_case_and_accents() {
+        let mut history = vec![
+            history_item("a", "2026-02-03T10:00:00Z", None),
+            history_item("b", "2026-02-02T10:00:00Z", None),
+            history_item("c", "2026-02-01T10:00:00Z", None),
+        ];
+        history[0].text = "Plano de AÇÃO para a reunião".to_string();
+        history[1].text = "acao judicial, prazo amanhã".to_string();
+        history[2].text = "Lista de compras".to_string();
+
+        let search = |query: &str| {
+            search_history(
+                &history,
+                &HistorySearch {
+                    query: query.to_string(),
+                    ..HistorySearch::default()
+                },
+                stats::DEFAULT_TYPING_WPM,
+            )
+        };
+        let ids = |result: HistorySearchResult| -> Vec<String> {
+            result
+                .items
+                .into_iter()
+                .map(|entry| entry.item.id)
+                .collect()
+        };
+
+        assert_eq!(ids(search("acao")), ["a", "b"]);
+        assert_eq!(ids(search("Ação")), ["a", "b"]);
+        assert_eq!(ids(search("reuniao plano")), ["a"]);
+        assert_eq!(ids(search("amanha")), ["b"]);
+        assert!(search("ferias").items.is_empty());
+        assert_eq!(search("  ").total, 3);
+    }
+
+    #[test]
+    fn test_search_pages_and_filters_by_date() {
+        let history: Vec<HistoryItem> = (0..25)
+            .map(|day| {
+                history_item(
+                    &format!("h{}", day),
+                    &format!("2026-01-{:02}T10:00:00Z", 31 - day),
+                    None,
+                )
+            })
+            .collect();
+
+        let second = search_history(
+            &history,
+            &HistorySearch {
+                page: 1,
+                limit: 10,
+                ..HistorySearch::default()
+            },
+            stats::DEFAULT_TYPING_WPM,
+        );
+        assert_eq!(second.total, 25);
+        assert_eq!(second.items.len(), 10);
+        assert_eq!(second.items[0].item.id, "h10");
+
+        let last = search_history(
+            &history,
+            &HistorySearch {
+                page: 1,
+                ..HistorySearch::default()
+            },
+            stats::DEFAULT_TYPING_WPM,
+        );
+        assert_eq!(last.limit, HISTORY_PAGE_SIZE);
+        assert_eq!(last.items.len(), 5);
+
+        let ranged = search_history(
+            &history,
+            &HistorySearch {
+                query: "original".to_string(),
+                from: Some("2026-01-20T00:00:00Z".to_string()),
+                to: Some("2026-01-22T23:59:59Z".to_string()),
+                ..HistorySearch::default()
+            },
+            stats::DEFAULT_TYPING_WPM,
+        );
+        assert_eq!(ranged.total, 3);
+        assert!(search_history(
+            &history,
+            &HistorySearch {
+                page: 9,
+                ..HistorySearch::default()
+            },
+            stats::DEFAULT_TYPING_WPM,
+        )
+        .items
+        .is_empty());
+    }
+
+    #[test]
+    fn test_history_limit_zero_keeps_everything() {
+        let store = HistoryStore::in_memory().unwrap();
+        for n in (0..80).rev() {
+            store
+                .insert(&history_item(&n.to_string(), "2026-02-01T10:00:00Z", None))
+                .unwrap();
+        }
+        let archive = AudioArchiveSettings::default();
+        store.enforce_limit(0, &archive).unwrap();
+        assert_eq!(store.count().unwrap(), 80);
+
+        store.enforce_limit(30, &archive).unwrap();
+        assert_eq!(store.count().unwrap(), 30);
+        assert_eq!(store.items().unwrap()[0].id, "0");
+    }
+
+    #[test]
+    fn test_pending_items_outlive_the_history_limit_until_cancelled() {
+        let dir = temp_dir("config-pending");
+        let store = HistoryStore::in_memory().unwrap();
+        let mut config = AppConfig::default();
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&[500; 1_600]);
+
+        let pending = enqueue_pending_in(
+            &store,
+            &config,
+            &dir.join("audio"),
+            &[audio],
+            &PrivacyGuard::default(),
+        )
+        .unwrap()
+        .remove(0);
+        for n in (0..3).rev() {
+            store
+                .insert(&history_item(&n.to_string(), "2026-02-01T10:00:00Z", None))
+                .unwrap();
+        }
+        config.history_limit = 2;
+        store
+            .enforce_limit(config.history_limit, &config.audio_archive)
+            .unwrap();
+        assert_eq!(store.count().unwrap(), 3);
+        assert_eq!(pending_ids(&store).unwrap(), vec![pending.id.clone()]);
+        assert_eq!(store.stats().unwrap().total_transcriptions, 2);
+
+        assert!(cancel_pending_in(&store, &pending.id).unwrap());
+        assert!(!cancel_pending_in(&store, &pending.id).unwrap());
+        assert!(!Path::new(pending.audio_path.as_deref().unwrap()).exists());
+        assert!(!cancel_pending_in(&store, "0").unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_silence_gate_round_trips_through_save_and_load() {
+        let dir = temp_dir("config-silence-gate");
+        let path = dir.join(CONFIG_FILE);
+        let gate = SilenceGate {
+            enabled: true,
+            rms_threshold: 0.002,
+            peak_threshold: 0.02,
+            speech_ratio_threshold: 0.05,
+            min_duration_secs: 0.5,
+        };
+        let config = AppConfig {
+            silence_gate: gate,
+            ..AppConfig::default()
+        };
+        save_raw(&path, &config).unwrap();
+        assert_eq!(load_or_create_at(&path).unwrap().silence_gate, gate);
+
+        // Configs written before the block existed get the built-in defaults.
+        fs::write(&path, r#"{ "user_name": "Ana" }"#).unwrap();
+        let loaded = load_or_create_at(&path).unwrap();
+        assert_eq!(loaded.user_name, "Ana");
+        assert_eq!(loaded.silence_gate, SilenceGate::default());
+
+        // Out-of-range thresholds are clamped on load.
+        fs::write(
+            &path,
+            r#"{ "silence_gate": { "enabled": true, "peakThreshold": 4.0 } }"#,
+        )
+        .unwrap();
+        let loaded = load_or_create_at(&path).unwrap().silence_gate;
+        assert!(loaded.enabled);
+        assert_eq!(loaded.peak_threshold, 1.0);
+        assert_eq!(loaded.rms_threshold, SilenceGate::default().rms_threshold);
+    }
+
+    #[test]
+    fn test_prompt_settings_round_trip_with_defaults_for_old_configs() {
+        let dir = temp_dir("config-prompt-settings");
+        let path = dir.join(CONFIG_FILE);
+        fs::write(&path, r#"{ "user_name": "Ana" }"#).unwrap();
+        let mut config = load_or_create_at(&path).unwrap();
+        assert_eq!(config.prompt_profile_id, DEFAULT_PROMPT_PROFILE);
+        assert_eq!(config.optimization_mode, OptimizationMode::ClarityOnly);
+
+        config.prompt_profile_id = normalize_prompt_profile(" coding-prompt ");
+        config.optimization_mode = OptimizationMode::AIOptimize;
+        save_raw(&path, &config).unwrap();
+        let loaded = load_or_create_at(&path).unwrap();
+        assert_eq!(loaded.prompt_profile_id, "coding-prompt");
+        assert_eq!(loaded.optimization_mode, OptimizationMode::AIOptimize);
+        assert_eq!(normalize_prompt_profile("  "), DEFAULT_PROMPT_PROFILE);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_provider_overrides_load_with_unknown_ids_and_partial_blocks() {
+        let dir = temp_dir("config-providers");
+        let path = dir.join(CONFIG_FILE);
+        fs::write(
+            &path,
+            r#"{ "providers": { "groq": { "timeoutSecs": 20 }, "deepgram": { "enabled": false } } }"#,
+        )
+        .unwrap();
+        let config = load_or_create_at(&path).unwrap();
+        assert_eq!(config.providers["groq"].timeout_secs, Some(20));
+        assert!(config.providers["groq"].enabled);
+        assert_eq!(config.providers["groq"].max_retries, None);
+        assert!(!config.providers["deepgram"].enabled);
+
+        fs::write(&path, r#"{ "user_name": "Ana" }"#).unwrap();
+        assert!(load_or_create_at(&path).unwrap().providers.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_usage_rates_override_the_built_in_ones() {
+        let dir = temp_dir("config-usage-rates");
+        let path = dir.join(CONFIG_FILE);
+        fs::write(
+            &path,
+            r#"{ "usage_rates": { "groq": 0.004, "local-whisper": -1, "deepgram": 0.0043 } }"#,
+        )
+        .unwrap();
+        let rates = usage_rates(&load_or_create_at(&path).unwrap());
+        assert_eq!(rates["groq"], 0.004);
+        assert_eq!(rates["elevenlabs"], usage::default_rates()["elevenlabs"]);
+        assert_eq!(rates["deepgram"], 0.0043);
+        assert!(!rates.contains_key("local-whisper"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cleared_local_engine_paths_drop_the_engine() {
+        let resolver = Resolver::from_parts(None, false, temp_dir("config-engines"));
+        let provider_setup = |config: &AppConfig| super::provider_setup(config, &resolver);
+        let mut config = AppConfig::default();
+        config.local_engines.whisper_cpp_bin = Some("/opt/whisper/main".to_string());
+        config.local_engines.whisper_model = Some(" ".to_string());
+        config.local_engines.whisper_threads = Some(0);
+        let whisper = provider_setup(&config).whisper.unwrap();
+        assert_eq!(whisper.bin_path, PathBuf::from("/opt/whisper/main"));
+        assert_eq!(whisper.model_path, None);
+        assert_eq!(whisper.options, WhisperOptions::default());
+        let setup = provider_setup(&config);
+        assert_eq!(setup.models_dir, Some(resolver.models_dir()));
+        assert_eq!(setup.temp_dir, Some(resolver.temp_dir()));
+
+        // Nothing of the old path survives it being cleared, blank or not.
+        config.local_engines.whisper_cpp_bin = Some(String::new());
+        assert!(provider_setup(&config).whisper.is_none());
+        config.local_engines.whisper_cpp_bin = None;
+        assert!(provider_setup(&config).whisper.is_none());
+    }
+
+    #[test]
+    fn test_provider_keys_round_trip_and_clear() {
+        let mut config = AppConfig::default();
+        store_api_key(
+            &mut config,
+            ApiKeySlot::OpenRouter,
+            " sk-or-v1-abcdef123456 ",
+        );
+        store_api_key(&mut config, ApiKeySlot::ElevenLabs, "sk_eleven_0123456789");
+
+        assert_eq!(
+            decode_openrouter_key(&config).as_deref(),
+            Some("sk-or-v1-abcdef123456")
+        );
+        // Kept in the keychain, not the config file.
+        assert!(config.elevenlabs_api_key_obfuscated.is_none());
+        assert_eq!(
+            secrets::read(ApiKeySlot::ElevenLabs).unwrap().as_deref(),
+            Some("sk_eleven_0123456789")
+        );
+        assert_eq!(
+            decode_elevenlabs_key(&config).map(|key| mask_api_key(&key)),
+            Some("sk_ele********6789".to_string())
+        );
+
+        store_api_key(&mut config, ApiKeySlot::OpenRouter, "  ");
+        assert!(decode_openrouter_key(&config).is_none());
+        assert!(secrets::read(ApiKeySlot::OpenRouter).unwrap().is_none());
+        assert!(decode_elevenlabs_key(&config).is_some());
+    }
+
+    #[test]
+    fn test_key_updates_are_write_only() {
+        let mut config = AppConfig::default();
+        let key = "gsk_write_only_0123456789";
+
+        let set = |value: &str| KeyUpdate::Set(value.to_string());
+
+        apply_key_update(&mut config, ApiKeySlot::Groq, set(key)).unwrap();
+        assert_eq!(decode_api_key(&config).as_deref(), Some(key));
+
+        // Leaving the field alone keeps the key, and so does sending the mask back.
+        apply_key_update(&mut config, ApiKeySlot::Groq, KeyUpdate::Unchanged).unwrap();
+        let masked = mask_api_key(key);
+        assert!(apply_key_update(&mut config, ApiKeySlot::Groq, set(&masked)).is_err());
+        assert!(apply_key_update(&mut config, ApiKeySlot::Groq, set("  ")).is_err());
+        assert_eq!(decode_api_key(&config).as_deref(), Some(key));
+
+        apply_key_update(&mut config, ApiKeySlot::Groq, KeyUpdate::Clear).unwrap();
+        assert!(decode_api_key(&config).is_none());
+    }
+
+    #[test]
+    fn test_key_update_payloads_parse() {
+        let payload: UpdateSettingsPayload = serde_json::from_value(serde_json::json!({
+            "apiKey": { "set": "gsk_new" },
+            "elevenlabsApiKey": "clear",
+        }))
+        .unwrap();
+        assert_eq!(payload.api_key, KeyUpdate::Set("gsk_new".to_string()));
+        assert_eq!(payload.elevenlabs_api_key, KeyUpdate::Clear);
+        assert_eq!(payload.openrouter_api_key, KeyUpdate::Unchanged);
+    }
+
+    #[test]
+    fn test_obfuscated_keys_migrate_to_the_keychain() {
+        let mut config = AppConfig {
+            groq_api_key_obfuscated: Some(obfuscate_api_key("gsk_legacy_0123456789")),
+            ..AppConfig::default()
+        };
+
+        // No keychain: the old field keeps working and nothing moves.
+        secrets::backend::set_unavailable(true);
+        assert!(!migrate_api_keys(&mut config));
+        assert_eq!(
+            decode_api_key(&config).as_deref(),
+            Some("gsk_legacy_0123456789")
+        );
+        store_api_key(&mut config, ApiKeySlot::OpenRouter, "sk-or-v1-abcdef123456");
+        assert!(config.openrouter_api_key_obfuscated.is_some());
+
+        secrets::backend::set_unavailable(false);
+        assert!(migrate_api_keys(&mut config));
+        assert!(config.groq_api_key_obfuscated.is_none());
+        assert!(config.openrouter_api_key_obfuscated.is_none());
+        assert_eq!(
+            decode_api_key(&config).as_deref(),
+            Some("gsk_legacy_0123456789")
+        );
+        assert_eq!(
+            decode_openrouter_key(&config).as_deref(),
+            Some("sk-or-v1-abcdef123456")
+        );
+        assert!(!migrate_api_keys(&mut config));
+    }
+
+    #[test]
+    fn test_portable_installs_keep_keys_out_of_the_keychain() {
+        let resolver = Resolver::from_parts(
+            Some(&temp_dir("config-portable")),
+            true,
+            temp_dir("config-portable-appdata"),
+        );
+        secrets::set_keychain_allowed(resolver.allows_keychain());
+        let mut config = AppConfig {
+            elevenlabs_api_key_obfuscated: Some(obfuscate_api_key("sk_legacy_0123456789")),
+            ..AppConfig::default()
+        };
+
+        store_api_key(&mut config, ApiKeySlot::Groq, "gsk_portable_0123456789");
+        assert!(!migrate_api_keys(&mut config));
+        assert_eq!(
+            decode_api_key(&config).as_deref(),
+            Some("gsk_portable_0123456789")
+        );
+        assert!(config.groq_api_key_obfuscated.is_some());
+        assert!(decode_openrouter_key(&config).is_none());
+        store_api_key(&mut config, ApiKeySlot::ElevenLabs, "");
+        assert!(decode_elevenlabs_key(&config).is_none());
+
+        assert_eq!(secrets::backend::calls(), 0);
+        secrets::set_keychain_allowed(true);
+    }
+}
@@ -0,0 +1,513 @@
+//! Local crash reports. A panic hook writes a JSON report for every panic, on the main
+//! thread or in a background task, to the crashes folder; nothing is uploaded. The next
+//! launch says the app recovered through `startup-health`.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use zentra_core::privacy::PrivacyGuard;
+
+/// Reports kept; older ones are pruned when a new one is written.
+pub const MAX_REPORTS: usize = 10;
+/// Log lines captured with each report.
+pub const LOG_TAIL_LINES: usize = 50;
+/// Reports are cut down to this size, oldest log lines first, then the backtrace.
+const MAX_REPORT_BYTES: usize = 64 * 1024;
+/// A panic this soon after the last report (a task panicking in a loop) is not written.
+const MIN_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+const REPORT_PREFIX: &str = "panic-";
+const REPORT_EXTENSION: &str = "json";
+/// Name of the newest report a launch already told the user about.
+const SEEN_MARKER: &str = "last-seen";
+/// Quoted text with this many words or more is treated as a transcript and redacted.
+const TRANSCRIPT_WORDS: usize = 3;
+
+/// What the app was doing, recorded with each report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AppPhase {
+    Starting,
+    Idle,
+    Recording,
+    Transcribing,
+    PostActions,
+}
+
+impl AppPhase {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => AppPhase::Idle,
+            2 => AppPhase::Recording,
+            3 => AppPhase::Transcribing,
+            4 => AppPhase::PostActions,
+            _ => AppPhase::Starting,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicReport {
+    /// RFC 3339, UTC
+    pub timestamp: String,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub app_version: String,
+    pub phase: AppPhase,
+    /// Latest log lines, oldest first, with transcript-looking text redacted. Empty in
+    /// privacy mode.
+    pub log_tail: Vec<String>,
+}
+
+static PHASE: AtomicU8 = AtomicU8::new(0);
+static APP_VERSION: OnceLock<&'static str> = OnceLock::new();
+static REPORTS_DIR: OnceLock<PathBuf> = OnceLock::new();
+static PRIVACY: OnceLock<PrivacyGuard> = OnceLock::new();
+static LAST_REPORT: Mutex<Option<Instant>> = Mutex::new(None);
+static LOG_TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+/// Reports written since the previous launch, counted once at startup.
+static RECOVERED: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn set_phase(phase: AppPhase) {
+    PHASE.store(phase as u8, Ordering::Relaxed);
+}
+
+pub fn phase() -> AppPhase {
+    AppPhase::from_u8(PHASE.load(Ordering::Relaxed))
+}
+
+/// Install the panic hook and the log capture. Runs before Tauri starts; reports are only
+/// written once [`set_reports_dir`] knows where, earlier panics are just printed.
+pub fn install(app_version: &'static str) {
+    let _ = APP_VERSION.set(app_version);
+    let subscriber = tracing_subscriber::registry().with(LogTail);
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        eprintln!("A tracing subscriber was already installed; crash reports carry no logs");
+    }
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // A panic while writing a report would abort the process; never nest.
+        if !IN_HOOK.with(|in_hook| in_hook.replace(true)) {
+            report_panic(info);
+            IN_HOOK.with(|in_hook| in_hook.set(false));
+        }
+        previous(info);
+    }));
+}
+
+/// Where reports go, and whether their log tails may be kept. Also counts the reports the
+/// previous launch left, for [`recovered_message`].
+pub fn set_reports_dir(dir: PathBuf, privacy: PrivacyGuard) {
+    RECOVERED.store(take_unseen(&dir), Ordering::Relaxed);
+    let _ = REPORTS_DIR.set(dir);
+    let _ = PRIVACY.set(privacy);
+}
+
+pub fn reports_dir() -> Option<&'static Path> {
+    REPORTS_DIR.get().map(PathBuf::as_path)
+}
+
+/// Startup notice when the previous run panicked.
+pub fn recovered_message() -> Option<String> {
+    match RECOVERED.load(Ordering::Relaxed) {
+        0 => None,
+        _ => Some("Zentra recovered from an internal error".to_string()),
+    }
+}
+
+/// Run a background task, logging which one died if it panics. The panic itself is
+/// reported by the hook.
+pub fn supervise<F>(name: &'static str, task: F) -> tauri::async_runtime::JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        if CatchUnwind(Box::pin(task)).await.is_err() {
+            tracing::error!("Background task '{}' panicked and stopped", name);
+        }
+    })
+}
+
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.0.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+fn report_panic(info: &PanicHookInfo<'_>) {
+    let Some(dir) = REPORTS_DIR.get() else {
+        return;
+    };
+    // `try_lock`: the panic may have happened with the lock held on this thread.
+    let Ok(mut last_report) = LAST_REPORT.try_lock() else {
+        return;
+    };
+    let now = Instant::now();
+    if !rate_limit_allows(*last_report, now) {
+        return;
+    }
+    *last_report = Some(now);
+    drop(last_report);
+
+    let keep_logs = PRIVACY
+        .get()
+        .is_none_or(|privacy| privacy.allows_persistence());
+    let report = PanicReport {
+        timestamp: Utc::now().to_rfc3339(),
+        message: panic_message(info.payload()),
+        location: info.location().map(|location| location.to_string()),
+        thread: std::thread::current().name().map(str::to_string),
+        backtrace: Backtrace::force_capture().to_string(),
+        app_version: APP_VERSION.get().copied().unwrap_or_default().to_string(),
+        phase: phase(),
+        log_tail: if keep_logs {
+            LOG_TAIL
+                .try_lock()
+                .map(|tail| tail.iter().cloned().collect())
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        },
+    };
+    match write_report(dir, &report) {
+        Ok(path) => eprintln!("Crash report written to {}", path.display()),
+        Err(e) => eprintln!("Crash report not written: {}", e),
+    }
+}
+
+fn rate_limit_allows(last_report: Option<Instant>, now: Instant) -> bool {
+    last_report.is_none_or(|last| now.duration_since(last) >= MIN_REPORT_INTERVAL)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Write `report` to `dir`, then prune all but the newest [`MAX_REPORTS`].
+pub fn write_report(dir: &Path, report: &PanicReport) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!(
+        "{}{}.{}",
+        REPORT_PREFIX,
+        Utc::now().timestamp_millis(),
+        REPORT_EXTENSION
+    ));
+    std::fs::write(&path, fit_to_size(report.clone(), MAX_REPORT_BYTES))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    prune(dir, MAX_REPORTS);
+    Ok(path)
+}
+
+/// Serialize `report` within `max_bytes`, dropping the oldest log lines, then trimming the
+/// backtrace and the message.
+fn fit_to_size(mut report: PanicReport, max_bytes: usize) -> String {
+    let serialize = |report: &PanicReport| serde_json::to_string_pretty(report).unwrap_or_default();
+    let mut json = serialize(&report);
+    while json.len() > max_bytes && !report.log_tail.is_empty() {
+        report.log_tail.remove(0);
+        json = serialize(&report);
+    }
+    if json.len() > max_bytes {
+        truncate(&mut report.backtrace, json.len() - max_bytes);
+        json = serialize(&report);
+    }
+    if json.len() > max_bytes {
+        truncate(&mut report.message, json.len() - max_bytes);
+        json = serialize(&report);
+    }
+    json
+}
+
+/// Shorten `text` by at least `excess` bytes, marking the cut.
+fn truncate(text: &mut String, excess: usize) {
+    const MARK: &str = " [truncated]";
+    let mut keep = text.len().saturating_sub(excess + MARK.len());
+    while !text.is_char_boundary(keep) {
+        keep -= 1;
+    }
+    text.truncate(keep);
+    text.push_str(MARK);
+}
+
+/// Report files in `dir`, oldest first.
+fn report_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == REPORT_EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(REPORT_PREFIX))
+        })
+        .collect();
+    files.sort_by_key(|path| report_millis(path));
+    files
+}
+
+fn report_millis(path: &Path) -> i64 {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.strip_prefix(REPORT_PREFIX))
+        .and_then(|millis| millis.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Delete the oldest reports beyond `keep`; returns how many were removed.
+fn prune(dir: &Path, keep: usize) -> usize {
+    let files = report_files(dir);
+    let excess = files.len().saturating_sub(keep);
+    files
+        .iter()
+        .take(excess)
+        .filter(|path| std::fs::remove_file(path).is_ok())
+        .count()
+}
+
+/// Count the reports newer than the last one a launch announced, and mark them seen.
+fn take_unseen(dir: &Path) -> usize {
+    let marker = dir.join(SEEN_MARKER);
+    let seen = std::fs::read_to_string(&marker)
+        .ok()
+        .and_then(|millis| millis.trim().parse::<i64>().ok())
+        .unwrap_or_default();
+    let unseen: Vec<i64> = report_files(dir)
+        .iter()
+        .map(|path| report_millis(path))
+        .filter(|millis| *millis > seen)
+        .collect();
+    if let Some(newest) = unseen.last() {
+        if let Err(e) = std::fs::write(&marker, newest.to_string()) {
+            tracing::warn!("Failed to mark crash reports seen: {}", e);
+        }
+    }
+    unseen.len()
+}
+
+/// Replace quoted runs of [`TRANSCRIPT_WORDS`] or more words, which is how dictated text
+/// shows up in debug output, with their length.
+fn redact_transcripts(line: &str) -> String {
+    let mut redacted = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('"') {
+        redacted.push_str(&rest[..=open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('"') else {
+            rest = after;
+            break;
+        };
+        let quoted = &after[..close];
+        if quoted.split_whitespace().count() >= TRANSCRIPT_WORDS {
+            redacted.push_str(&format!("<redacted {} chars>", quoted.chars().count()));
+        } else {
+            redacted.push_str(quoted);
+        }
+        redacted.push('"');
+        rest = &after[close + 1..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+fn push_log_line(line: String) {
+    // Not `lock_or_recover`: it logs, which would land back here with the lock held.
+    let mut tail = LOG_TAIL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if tail.len() == LOG_TAIL_LINES {
+        tail.pop_front();
+    }
+    tail.push_back(line);
+}
+
+/// Keeps the last [`LOG_TAIL_LINES`] info-and-above events for crash reports.
+struct LogTail;
+
+impl<S: Subscriber> Layer<S> for LogTail {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if *metadata.level() <= Level::INFO {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn enabled(
+        &self,
+        metadata: &Metadata<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) -> bool {
+        *metadata.level() <= Level::INFO
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let mut fields = LineFields::default();
+        event.record(&mut fields);
+        push_log_line(redact_transcripts(&format!(
+            "{} {:>5} {}: {}",
+            Utc::now().format("%H:%M:%S%.3f"),
+            event.metadata().level(),
+            event.metadata().target(),
+            fields.0
+        )));
+    }
+}
+
+#[derive(Default)]
+struct LineFields(String);
+
+impl Visit for LineFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            self.0.push_str(&format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn report(log_lines: usize) -> PanicReport {
+        PanicReport {
+            timestamp: Utc::now().to_rfc3339(),
+            message: "index out of bounds".to_string(),
+            location: Some("src/lib.rs:1:1".to_string()),
+            thread: Some("main".to_string()),
+            backtrace: "frame\n".repeat(200),
+            app_version: "1.1.0".to_string(),
+            phase: AppPhase::Recording,
+            log_tail: (0..log_lines).map(|idx| format!("line {}", idx)).collect(),
+        }
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("zentra-crashes-{}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_report_is_written_and_capped_in_size() {
+        let dir = temp_dir();
+        let path = write_report(&dir, &report(LOG_TAIL_LINES)).unwrap();
+        let written: PanicReport =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written.phase, AppPhase::Recording);
+        assert_eq!(written.log_tail.len(), LOG_TAIL_LINES);
+        assert_eq!(written.message, "index out of bounds");
+
+        let mut huge = report(LOG_TAIL_LINES);
+        huge.log_tail = vec!["x".repeat(1_000); LOG_TAIL_LINES];
+        let json = fit_to_size(huge.clone(), 8 * 1024);
+        assert!(json.len() <= 8 * 1024);
+        let fitted: PanicReport = serde_json::from_str(&json).unwrap();
+        assert!(fitted.log_tail.len() < LOG_TAIL_LINES);
+        assert_eq!(fitted.log_tail.last(), huge.log_tail.last());
+
+        huge.backtrace = "frame ".repeat(4_000);
+        let json = fit_to_size(huge, 8 * 1024);
+        assert!(json.len() <= 8 * 1024);
+        let fitted: PanicReport = serde_json::from_str(&json).unwrap();
+        assert!(fitted.log_tail.is_empty());
+        assert!(fitted.backtrace.ends_with("[truncated]"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_oldest_reports_are_pruned_and_unseen_ones_counted_once() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "keep").unwrap();
+        for millis in 1..=12 {
+            std::fs::write(dir.join(format!("{}{}.json", REPORT_PREFIX, millis)), "{}").unwrap();
+        }
+
+        assert_eq!(prune(&dir, MAX_REPORTS), 2);
+        let kept: Vec<i64> = report_files(&dir)
+            .iter()
+            .map(|p| report_millis(p))
+            .collect();
+        assert_eq!(kept, (3..=12).collect::<Vec<_>>());
+        assert!(dir.join("notes.txt").exists());
+
+        assert_eq!(take_unseen(&dir), MAX_REPORTS);
+        assert_eq!(take_unseen(&dir), 0);
+        std::fs::write(dir.join(format!("{}13.json", REPORT_PREFIX)), "{}").unwrap();
+        assert_eq!(take_unseen(&dir), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_log_lines_lose_transcripts_but_keep_short_values() {
+        assert_eq!(
+            redact_transcripts(r#"Transcript { text: "olá tudo bem com você", provider: "Groq" }"#),
+            r#"Transcript { text: "<redacted 21 chars>", provider: "Groq" }"#
+        );
+        assert_eq!(
+            redact_transcripts(r#"Device "USB Mic" failed"#),
+            r#"Device "USB Mic" failed"#
+        );
+        assert_eq!(
+            redact_transcripts(r#"dangling "quote"#),
+            r#"dangling "quote"#
+        );
+    }
+
+    #[test]
+    fn test_reports_are_rate_limited() {
+        let start = Instant::now();
+        assert!(rate_limit_allows(None, start));
+        assert!(!rate_limit_allows(
+            Some(start),
+            start + Duration::from_secs(1)
+        ));
+        assert!(rate_limit_allows(Some(start), start + MIN_REPORT_INTERVAL));
+    }
+}
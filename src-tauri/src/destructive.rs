@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::locks::lock_or_recover;
+
+pub const TOKEN_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DestructiveActionKind {
+    ClearHistory,
+    /// Not destructive, but the plain key should only leave the backend on purpose.
+    RevealApiKey,
+}
+
+/// What the action will remove, so the confirm dialog can spell it out.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DestructiveScope {
+    pub history_items: usize,
+    pub oldest: Option<String>,
+    pub newest: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DestructiveActionToken {
+    pub token: String,
+    pub kind: DestructiveActionKind,
+    pub expires_in_secs: u64,
+    pub scope: DestructiveScope,
+}
+
+/// One-time, short-lived tokens that destructive commands must present.
+#[derive(Debug, Default)]
+pub struct DestructiveTokens {
+    issued: Mutex<HashMap<String, (DestructiveActionKind, Instant)>>,
+}
+
+impl DestructiveTokens {
+    pub fn issue(
+        &self,
+        kind: DestructiveActionKind,
+        scope: DestructiveScope,
+    ) -> Result<DestructiveActionToken, String> {
+        let token = self.issue_at(kind, Instant::now())?;
+        Ok(DestructiveActionToken {
+            token,
+            kind,
+            expires_in_secs: TOKEN_TTL.as_secs(),
+            scope,
+        })
+    }
+
+    /// Validate and burn `token`. Expired, reused or mismatched tokens are rejected.
+    pub fn consume(&self, token: &str, kind: DestructiveActionKind) -> Result<(), String> {
+        self.consume_at(token, kind, Instant::now())
+    }
+
+    fn issue_at(&self, kind: DestructiveActionKind, now: Instant) -> Result<String, String> {
+        let mut issued = lock_or_recover(&self.issued, "destructive tokens");
+        issued.retain(|_, (_, issued_at)| now.duration_since(*issued_at) < TOKEN_TTL);
+        let token = uuid::Uuid::new_v4().to_string();
+        issued.insert(token.clone(), (kind, now));
+        Ok(token)
+    }
+
+    fn consume_at(
+        &self,
+        token: &str,
+        kind: DestructiveActionKind,
+        now: Instant,
+    ) -> Result<(), String> {
+        let mut issued = lock_or_recover(&self.issued, "destructive tokens");
+        let Some((token_kind, issued_at)) = issued.remove(token) else {
+            return Err("Confirmation required: request a new confirmation token".to_string());
+        };
+        if token_kind != kind {
+            return Err("Confirmation token was issued for a different action".to_string());
+        }
+        if now.duration_since(issued_at) >= TOKEN_TTL {
+            return Err("Confirmation expired, please confirm again".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_single_use() {
+        let tokens = DestructiveTokens::default();
+        let now = Instant::now();
+        let token = tokens
+            .issue_at(DestructiveActionKind::ClearHistory, now)
+            .unwrap();
+
+        assert!(tokens
+            .consume_at(&token, DestructiveActionKind::ClearHistory, now)
+            .is_ok());
+        assert!(tokens
+            .consume_at(&token, DestructiveActionKind::ClearHistory, now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_token_expires_after_ttl() {
+        let tokens = DestructiveTokens::default();
+        let now = Instant::now();
+        let token = tokens
+            .issue_at(DestructiveActionKind::ClearHistory, now)
+            .unwrap();
+
+        let err = tokens
+            .consume_at(&token, DestructiveActionKind::ClearHistory, now + TOKEN_TTL)
+            .unwrap_err();
+        assert!(err.contains("expired"));
+    }
+
+    #[test]
+    fn test_unknown_token_is_rejected() {
+        let tokens = DestructiveTokens::default();
+        assert!(tokens
+            .consume("not-issued", DestructiveActionKind::ClearHistory)
+            .is_err());
+    }
+
+    #[test]
+    fn test_stale_tokens_are_pruned_on_issue() {
+        let tokens = DestructiveTokens::default();
+        let now = Instant::now();
+        tokens
+            .issue_at(DestructiveActionKind::ClearHistory, now)
+            .unwrap();
+        tokens
+            .issue_at(DestructiveActionKind::ClearHistory, now + TOKEN_TTL)
+            .unwrap();
+        assert_eq!(tokens.issued.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,58 @@
+//! Correlation id for one dictation. A new `request_id` is drawn when a real recording
+//! starts and stays current until the next one, so the segment, finalize, history and paste
+//! commands that follow it all log under the same `dictation` span and report the same id.
+
+use crate::locks::lock_or_recover;
+use std::sync::{Arc, Mutex};
+use tracing::Span;
+
+#[derive(Debug, Clone, Default)]
+pub struct DictationTracker {
+    current: Arc<Mutex<Option<String>>>,
+}
+
+impl DictationTracker {
+    /// Start a new dictation and return its id.
+    pub fn begin(&self) -> String {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        *lock_or_recover(&self.current, "dictation id") = Some(request_id.clone());
+        request_id
+    }
+
+    /// Id of the latest dictation; `None` before the first recording.
+    pub fn current(&self) -> Option<String> {
+        lock_or_recover(&self.current, "dictation id").clone()
+    }
+
+    /// Span for work on the current dictation. Disabled before the first recording, so
+    /// callers can always instrument with it.
+    pub fn span(&self) -> Span {
+        match self.current() {
+            Some(request_id) => span_for(&request_id),
+            None => Span::none(),
+        }
+    }
+}
+
+pub fn span_for(request_id: &str) -> Span {
+    tracing::info_span!("dictation", request_id = %request_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_recording_gets_a_new_id() {
+        let tracker = DictationTracker::default();
+        assert!(tracker.current().is_none());
+
+        let first = tracker.begin();
+        let shared = tracker.clone();
+        assert_eq!(shared.current().as_deref(), Some(first.as_str()));
+
+        let second = tracker.begin();
+        assert_ne!(first, second);
+        assert_eq!(shared.current(), Some(second));
+    }
+}
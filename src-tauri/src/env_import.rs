@@ -0,0 +1,237 @@
+//! One-time migration of a legacy `.env` (from before settings-based configuration) into
+//! `AppConfig`, so users who configured Zentra through environment files keep working
+//! after they delete it.
+
+use crate::config::{self, AppConfig};
+use crate::secrets::ApiKeySlot;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use zentra_core::orchestrator::TranscriptionSettings;
+use zentra_core::stt::UploadFormat;
+
+/// Migration marker stored in the config. `keys` records which settings came from `.env`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EnvImportRecord {
+    pub imported_at: String,
+    pub keys: Vec<String>,
+}
+
+/// Parse `.env` without touching the process environment.
+pub fn read_env_file(path: &Path) -> HashMap<String, String> {
+    match dotenvy::from_path_iter(path) {
+        Ok(iter) => iter.filter_map(Result::ok).collect(),
+        Err(e) => {
+            tracing::warn!("Could not read {}: {}", path.display(), e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Import once per config. Returns the imported variable names, or `None` when the
+/// migration already ran.
+pub fn run(config: &mut AppConfig, env: &HashMap<String, String>) -> Option<Vec<String>> {
+    if config.env_import.is_some() {
+        return None;
+    }
+
+    let keys = import_env(config, env);
+    if keys.is_empty() {
+        tracing::info!(".env import: nothing new to import");
+    } else {
+        tracing::info!(".env import: imported {}", keys.join(", "));
+    }
+    config.env_import = Some(EnvImportRecord {
+        imported_at: Utc::now().to_rfc3339(),
+        keys: keys.clone(),
+    });
+    Some(keys)
+}
+
+/// Copy recognized values into `config`. Settings that already have a value are never
+/// overwritten, and keys in the wrong format are skipped.
+fn import_env(config: &mut AppConfig, env: &HashMap<String, String>) -> Vec<String> {
+    let value = |name: &str| {
+        env.get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let mut imported = Vec::new();
+    for (name, slot, prefix) in [
+        ("GROQ_API_KEY", ApiKeySlot::Groq, "gsk_"),
+        ("ELEVENLABS_API_KEY", ApiKeySlot::ElevenLabs, "sk_"),
+        ("OPENROUTER_API_KEY", ApiKeySlot::OpenRouter, "sk-or-"),
+    ] {
+        if config::decode_key(config, slot).is_some() {
+            continue;
+        }
+        if let Some(key) = value(name).filter(|key| key.starts_with(prefix)) {
+            config::store_api_key(config, slot, key);
+            imported.push(name.to_string());
+        }
+    }
+
+    let mut fill = |name: &str, slot: &mut Option<String>, value: Option<String>| {
+        if slot.is_none() {
+            if let Some(value) = value {
+                *slot = Some(value);
+                imported.push(name.to_string());
+            }
+        }
+    };
+
+    let engines = &mut config.local_engines;
+    for (name, slot) in [
+        ("WHISPER_CPP_BIN", &mut engines.whisper_cpp_bin),
+        ("WHISPER_MODEL", &mut engines.whisper_model),
+        ("VOSK_MODEL_PT", &mut engines.vosk_model_pt),
+        ("VOSK_MODEL_EN", &mut engines.vosk_model_en),
+    ] {
+        fill(name, slot, value(name).map(str::to_string));
+    }
+
+    // Groq settings that now live in the `providers` section
+    let base_url = value("GROQ_API_BASE");
+    let timestamps = value("GROQ_STT_TIMESTAMPS")
+        .is_some_and(|value| matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes"));
+    if base_url.is_some() || timestamps {
+        let groq = config.providers.entry("groq".to_string()).or_default();
+        if let Some(base_url) = base_url.filter(|_| groq.base_url.is_none()) {
+            groq.base_url = Some(base_url.to_string());
+            imported.push("GROQ_API_BASE".to_string());
+        }
+        if timestamps && !groq.timestamps {
+            groq.timestamps = true;
+            imported.push("GROQ_STT_TIMESTAMPS".to_string());
+        }
+    }
+
+    // Tuning flags that now live in the `transcription` section, imported while it still
+    // has the built-in values
+    let transcription = &mut config.transcription;
+    if *transcription == TranscriptionSettings::default() {
+        if value("ZENTRA_STT_STRATEGY").is_some_and(|value| value.eq_ignore_ascii_case("race")) {
+            transcription.race = true;
+            imported.push("ZENTRA_STT_STRATEGY".to_string());
+            if let Some(providers) = value("ZENTRA_RACE_PROVIDERS").and_then(|v| v.parse().ok()) {
+                transcription.race_providers = providers;
+                imported.push("ZENTRA_RACE_PROVIDERS".to_string());
+            }
+        }
+        if value("ZENTRA_COLLAPSE_REPETITIONS").is_some_and(|value| {
+            matches!(value.to_ascii_lowercase().as_str(), "0" | "false" | "off")
+        }) {
+            transcription.collapse_repetitions = false;
+            imported.push("ZENTRA_COLLAPSE_REPETITIONS".to_string());
+        }
+        if value("ZENTRA_UPLOAD_FORMAT").is_some_and(|value| value.eq_ignore_ascii_case("wav")) {
+            transcription.upload_format = UploadFormat::Wav;
+            imported.push("ZENTRA_UPLOAD_FORMAT".to_string());
+        }
+    }
+
+    imported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_recognized_values_are_imported_once() {
+        let mut config = AppConfig::default();
+        let env = env(&[
+            ("GROQ_API_KEY", " gsk_abc "),
+            ("WHISPER_CPP_BIN", "/opt/whisper/main"),
+            ("VOSK_MODEL_PT", "models/pt"),
+            ("GROQ_API_BASE", "http://localhost:8000/v1"),
+            ("ZENTRA_STT_STRATEGY", "race"),
+            ("ZENTRA_RACE_PROVIDERS", "3"),
+            ("ZENTRA_UPLOAD_FORMAT", "WAV"),
+            ("UNRELATED", "x"),
+        ]);
+
+        let keys = run(&mut config, &env).unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                "GROQ_API_KEY",
+                "WHISPER_CPP_BIN",
+                "VOSK_MODEL_PT",
+                "GROQ_API_BASE",
+                "ZENTRA_STT_STRATEGY",
+                "ZENTRA_RACE_PROVIDERS",
+                "ZENTRA_UPLOAD_FORMAT"
+            ]
+        );
+        let transcription = &config.transcription;
+        assert!(transcription.race && transcription.collapse_repetitions);
+        assert_eq!(transcription.race_providers, 3);
+        assert_eq!(transcription.upload_format, UploadFormat::Wav);
+        let groq = &config.providers["groq"];
+        assert_eq!(groq.base_url.as_deref(), Some("http://localhost:8000/v1"));
+        assert!(groq.enabled && !groq.timestamps);
+        assert_eq!(config::decode_api_key(&config).as_deref(), Some("gsk_abc"));
+        assert_eq!(
+            config.local_engines.whisper_cpp_bin.as_deref(),
+            Some("/opt/whisper/main")
+        );
+        assert_eq!(config.env_import.as_ref().unwrap().keys, keys);
+
+        assert!(run(&mut config, &env).is_none(), "migration runs once");
+    }
+
+    #[test]
+    fn test_existing_settings_are_never_overwritten() {
+        let mut config = AppConfig::default();
+        config.groq_api_key_obfuscated = Some(config::obfuscate_api_key("gsk_saved"));
+        config.local_engines.vosk_model_en = Some("models/en-custom".to_string());
+
+        let keys = run(
+            &mut config,
+            &env(&[
+                ("GROQ_API_KEY", "gsk_from_env"),
+                ("VOSK_MODEL_EN", "models/en"),
+                ("VOSK_MODEL_PT", "models/pt"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(keys, vec!["VOSK_MODEL_PT"]);
+        assert_eq!(
+            config::decode_api_key(&config).as_deref(),
+            Some("gsk_saved")
+        );
+        assert_eq!(
+            config.local_engines.vosk_model_en.as_deref(),
+            Some("models/en-custom")
+        );
+    }
+
+    #[test]
+    fn test_malformed_and_placeholder_keys_are_skipped() {
+        let mut config = AppConfig::default();
+        let keys = run(
+            &mut config,
+            &env(&[
+                ("GROQ_API_KEY", "your_groq_api_key_here"),
+                ("ELEVENLABS_API_KEY", "   "),
+            ]),
+        )
+        .unwrap();
+
+        assert!(keys.is_empty());
+        assert!(config::decode_api_key(&config).is_none());
+        assert!(config::decode_elevenlabs_key(&config).is_none());
+        assert!(config.env_import.is_some());
+    }
+}
@@ -0,0 +1,240 @@
+//! History copied into a folder of the user's choosing, one `<recorded at>_<id>.json` file per
+//! item in the [`HistoryItemV1`] shape exports use, for backup and sync tools that only see
+//! plain files. Audio paths and the provider stay out of it, as they do in exports.
+//!
+//! The history store keeps the folder in step as items are added, edited and deleted. A write
+//! that fails warns once and leaves the mirror stale; the next change then rebuilds the whole
+//! folder instead of writing one file. Nothing is written in privacy mode.
+
+use crate::api_types::HistoryItemV1;
+use crate::config::HistoryItem;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use zentra_core::privacy::PrivacyGuard;
+
+/// `20260301T100000Z`: sorts by recording time and is a valid file name everywhere.
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const STAMP_LEN: usize = 16;
+const EXTENSION: &str = ".json";
+
+pub struct HistoryMirror {
+    folder: PathBuf,
+    privacy: PrivacyGuard,
+    /// Changes may be missing from the folder; the next one rebuilds it.
+    stale: AtomicBool,
+    /// A failure was already reported.
+    warned: AtomicBool,
+}
+
+impl HistoryMirror {
+    /// Stale from the start, since the folder may hold an older copy of history.
+    pub fn new(folder: PathBuf, privacy: PrivacyGuard) -> Self {
+        Self {
+            folder,
+            privacy,
+            stale: AtomicBool::new(true),
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(Ordering::Relaxed)
+    }
+
+    pub fn allows_writes(&self) -> bool {
+        self.privacy.allows_persistence()
+    }
+
+    /// Write or replace the file of `item`. In privacy mode the change is left out and
+    /// picked up by the rebuild after it.
+    pub fn write(&self, item: &HistoryItem) {
+        if !self.allows_writes() {
+            self.stale.store(true, Ordering::Relaxed);
+            return;
+        }
+        let result = self.write_file(item);
+        self.report(result);
+    }
+
+    /// Delete the file of the item with this id, if there is one.
+    pub fn remove(&self, id: &str) {
+        let result = self.remove_where(|mirrored| mirrored == id);
+        self.report(result);
+    }
+
+    /// Delete the files of every item not in `ids`.
+    pub fn retain(&self, ids: &HashSet<&str>) {
+        let result = self.remove_where(|mirrored| !ids.contains(mirrored));
+        self.report(result);
+    }
+
+    /// Have the next change rebuild the folder, after changes too large to mirror one by one.
+    pub fn invalidate(&self) {
+        self.stale.store(true, Ordering::Relaxed);
+    }
+
+    /// Make the folder hold exactly `items`: every item is written and the files of items
+    /// no longer in history are deleted. Other files in the folder are left alone. Returns
+    /// how many items are mirrored.
+    pub fn resync(&self, items: &[HistoryItem]) -> Result<usize, String> {
+        if !self.allows_writes() {
+            return Err("Privacy mode is on; history is not mirrored".to_string());
+        }
+        match self.rebuild(items) {
+            Ok(()) => {
+                self.report(Ok(()));
+                self.stale.store(false, Ordering::Relaxed);
+                Ok(items.len())
+            }
+            Err(e) => {
+                let message = self.failure_message(&e);
+                self.report(Err(e));
+                Err(message)
+            }
+        }
+    }
+
+    fn rebuild(&self, items: &[HistoryItem]) -> io::Result<()> {
+        fs::create_dir_all(&self.folder)?;
+        let ids: HashSet<&str> = items.iter().map(|item| item.id.as_str()).collect();
+        self.remove_where(|mirrored| !ids.contains(mirrored))?;
+        items.iter().try_for_each(|item| self.write_file(item))
+    }
+
+    fn remove_where(&self, remove: impl Fn(&str) -> bool) -> io::Result<()> {
+        self.mirrored_files()?
+            .into_iter()
+            .filter(|(_, id)| remove(id))
+            .try_for_each(|(path, _)| fs::remove_file(path))
+    }
+
+    /// Through a temporary file, so a sync tool never picks up half an item. Files that
+    /// already hold the item are not touched.
+    fn write_file(&self, item: &HistoryItem) -> io::Result<()> {
+        let path = self.folder.join(file_name(item));
+        let json = serde_json::to_vec_pretty(&HistoryItemV1::from(item))?;
+        if fs::read(&path).is_ok_and(|existing| existing == json) {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.folder)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &path)
+    }
+
+    /// Mirrored files in the folder with the id each belongs to.
+    fn mirrored_files(&self) -> io::Result<Vec<(PathBuf, String)>> {
+        let entries = match fs::read_dir(&self.folder) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            if let Some(id) = name.to_str().and_then(mirrored_id) {
+                files.push((entry.path(), id.to_string()));
+            }
+        }
+        Ok(files)
+    }
+
+    fn report(&self, result: io::Result<()>) {
+        match result {
+            Ok(()) => {
+                if self.warned.swap(false, Ordering::Relaxed) {
+                    tracing::info!("History mirror in {} works again", self.folder.display());
+                }
+            }
+            Err(e) => {
+                self.stale.store(true, Ordering::Relaxed);
+                if !self.warned.swap(true, Ordering::Relaxed) {
+                    tracing::warn!(
+                        "{}; retrying with the next history change",
+                        self.failure_message(&e)
+                    );
+                } else {
+                    tracing::debug!("History mirror still failing: {}", e);
+                }
+            }
+        }
+    }
+
+    fn failure_message(&self, e: &io::Error) -> String {
+        format!(
+            "Failed to mirror history to {}: {}",
+            self.folder.display(),
+            e
+        )
+    }
+}
+
+/// `<recorded at>_<id>.json`. Timestamps that don't parse sort first.
+pub fn file_name(item: &HistoryItem) -> String {
+    let stamp = DateTime::parse_from_rfc3339(&item.timestamp)
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or(DateTime::UNIX_EPOCH)
+        .format(STAMP_FORMAT);
+    format!("{}_{}{}", stamp, item.id, EXTENSION)
+}
+
+/// The item id in the name of a mirrored file.
+fn mirrored_id(name: &str) -> Option<&str> {
+    let (stamp, id) = name.strip_suffix(EXTENSION)?.split_once('_')?;
+    let stamp = stamp.as_bytes();
+    let looks_mirrored = stamp.len() == STAMP_LEN
+        && stamp[8] == b'T'
+        && stamp[15] == b'Z'
+        && stamp[..8]
+            .iter()
+            .chain(&stamp[9..15])
+            .all(u8::is_ascii_digit);
+    (looks_mirrored && !id.is_empty()).then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HistoryStatus;
+
+    #[test]
+    fn test_file_names_sort_by_recording_time_and_round_trip_the_id() {
+        let mut item = HistoryItem {
+            id: "9f1c_2".to_string(),
+            text: "texto".to_string(),
+            timestamp: "2026-03-01T07:05:09-03:00".to_string(),
+            duration_seconds: 1.0,
+            word_count: 1,
+            provider: None,
+            audio_path: None,
+            versions: Vec::new(),
+            status: HistoryStatus::Transcribed,
+            request_id: None,
+            session_stats: None,
+        };
+        let name = file_name(&item);
+        assert_eq!(name, "20260301T100509Z_9f1c_2.json");
+        assert_eq!(mirrored_id(&name), Some("9f1c_2"));
+
+        item.timestamp = "yesterday".to_string();
+        assert_eq!(file_name(&item), "19700101T000000Z_9f1c_2.json");
+
+        for other in [
+            "notes.json",
+            "2026_a.json",
+            "20260301T100509Z_.json",
+            "x_a.json.tmp",
+        ] {
+            assert_eq!(mirrored_id(other), None, "{other}");
+        }
+    }
+}
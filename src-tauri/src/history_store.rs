@@ -0,0 +1,851 @@
+//! Transcription history in SQLite, beside config.json. Recording a dictation writes one
+//! row instead of rewriting the whole config with every item in it, so an interrupted
+//! write can no longer take the settings and API keys down with the history. Stats are SQL
+//! aggregates over the table. Cleared items stay in it, marked with when they were
+//! cleared, until the trash expires. When a mirror folder is set, every change is copied
+//! there too; see [`crate::history_mirror`].
+
+use crate::audio_archive::{self, AudioArchiveSettings};
+use crate::config::{HistoryItem, HistoryStatus, HistoryTrash, Stats};
+use crate::destructive::DestructiveScope;
+use crate::history_mirror::HistoryMirror;
+use crate::history_updates::ChangeSummary;
+use crate::locks::lock_or_recover;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{AppHandle, Manager};
+use zentra_core::privacy::PrivacyGuard;
+
+pub const HISTORY_DB_FILE: &str = "history.db";
+/// Cleared history can be restored for this long.
+pub const TRASH_RETENTION_HOURS: i64 = 24;
+/// `PRAGMA user_version` of the current schema.
+const SCHEMA_VERSION: i32 = 1;
+
+/// Items are ordered by `seq`: newest recorded first, as the JSON history was.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL UNIQUE,
+    text TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    duration_seconds REAL NOT NULL DEFAULT 0,
+    word_count INTEGER NOT NULL DEFAULT 0,
+    provider TEXT,
+    audio_path TEXT,
+    versions TEXT NOT NULL DEFAULT '[]',
+    status TEXT NOT NULL DEFAULT 'transcribed',
+    request_id TEXT,
+    session_stats TEXT,
+    trashed_at TEXT
+);
+CREATE INDEX IF NOT EXISTS history_live ON history (trashed_at, status);
+";
+
+const COLUMNS: &str = "id, text, timestamp, duration_seconds, word_count, provider, \
+    audio_path, versions, status, request_id, session_stats";
+
+const TRANSCRIBED: &str = "transcribed";
+const PENDING: &str = "pendingTranscription";
+
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+    mirror: Mutex<Option<Arc<HistoryMirror>>>,
+}
+
+impl HistoryStore {
+    /// Open the database at `path`, creating it and its folder when missing.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create history dir: {}", e))?;
+        }
+        let conn = Connection::open(path).map_err(db_error)?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(db_error)?;
+        Self::init(conn)
+    }
+
+    /// History that lives only as long as the app, for when the data folder is read-only.
+    pub fn in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(db_error)?;
+        if version < SCHEMA_VERSION {
+            conn.execute_batch(SCHEMA).map_err(db_error)?;
+            conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+                .map_err(db_error)?;
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+            mirror: Mutex::new(None),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        lock_or_recover(&self.conn, "history store")
+    }
+
+    /// Copy history into `folder` from now on; `None` stops mirroring and leaves the files.
+    /// Returns the new mirror when the folder changed, for the caller to fill with
+    /// [`HistoryStore::resync_mirror`].
+    pub fn mirror_to(
+        &self,
+        folder: Option<PathBuf>,
+        privacy: &PrivacyGuard,
+    ) -> Option<Arc<HistoryMirror>> {
+        let mut mirror = lock_or_recover(&self.mirror, "history mirror");
+        if mirror.as_ref().map(|current| current.folder()) == folder.as_deref() {
+            return None;
+        }
+        *mirror = folder.map(|folder| Arc::new(HistoryMirror::new(folder, privacy.clone())));
+        mirror.clone()
+    }
+
+    /// Rebuild the mirror folder from history. Returns how many items it holds.
+    pub fn resync_mirror(&self) -> Result<usize, String> {
+        let mirror = lock_or_recover(&self.mirror, "history mirror")
+            .clone()
+            .ok_or_else(|| "No history mirror folder is set".to_string())?;
+        mirror.resync(&self.items()?)
+    }
+
+    /// Copy a change to the mirror, or rebuild it when earlier changes didn't make it.
+    fn sync_mirror(&self, change: impl FnOnce(&HistoryMirror)) {
+        let Some(mirror) = lock_or_recover(&self.mirror, "history mirror").clone() else {
+            return;
+        };
+        if !mirror.is_stale() || !mirror.allows_writes() {
+            change(&mirror);
+            return;
+        }
+        match self.items() {
+            // A failure is reported by the mirror and retried on the next change.
+            Ok(items) => {
+                let _ = mirror.resync(&items);
+            }
+            Err(e) => tracing::warn!("History mirror not rebuilt: {}", e),
+        }
+    }
+
+    /// After changes too large to copy one item at a time.
+    fn rebuild_mirror(&self) {
+        if let Some(mirror) = lock_or_recover(&self.mirror, "history mirror").as_ref() {
+            mirror.invalidate();
+        }
+        self.sync_mirror(|_| {});
+    }
+
+    /// Copy history and trash from a config.json written before the store existed. Items
+    /// already in the store are left alone, so an import cut short can simply run again.
+    /// Returns how many items were added.
+    pub fn import_legacy(
+        &self,
+        history: &[HistoryItem],
+        trash: Option<&HistoryTrash>,
+    ) -> Result<usize, String> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(db_error)?;
+        let mut imported = 0;
+        // Oldest first, so the newest item gets the highest `seq`.
+        if let Some(trash) = trash {
+            for item in trash.items.iter().rev() {
+                imported += insert_row(&tx, item, Some(&trash.deleted_at))?;
+            }
+        }
+        for item in history.iter().rev() {
+            imported += insert_row(&tx, item, None)?;
+        }
+        tx.commit().map_err(db_error)?;
+        if imported > 0 {
+            self.rebuild_mirror();
+        }
+        Ok(imported)
+    }
+
+    /// Add `item` as the newest in history.
+    pub fn insert(&self, item: &HistoryItem) -> Result<(), String> {
+        let inserted = insert_row(&self.lock(), item, None)?;
+        if inserted > 0 {
+            self.sync_mirror(|mirror| mirror.write(item));
+        }
+        Ok(())
+    }
+
+    /// Add `items`, oldest first, in one transaction: all of them or none.
+    pub fn insert_all(&self, items: &[HistoryItem]) -> Result<(), String> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(db_error)?;
+        for item in items {
+            insert_row(&tx, item, None)?;
+        }
+        tx.commit().map_err(db_error)?;
+        drop(conn);
+        for item in items {
+            self.sync_mirror(|mirror| mirror.write(item));
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<HistoryItem>, String> {
+        self.lock()
+            .query_row(
+                &format!(
+                    "SELECT {} FROM history WHERE id = ?1 AND trashed_at IS NULL",
+                    COLUMNS
+                ),
+                [id],
+                read_item,
+            )
+            .optional()
+            .map_err(db_error)
+    }
+
+    /// Write back the fields that change after an item is recorded. Returns false when the
+    /// item is gone.
+    pub fn update(&self, item: &HistoryItem) -> Result<bool, String> {
+        let updated = self
+            .lock()
+            .execute(
+                "UPDATE history SET text = ?2, word_count = ?3, provider = ?4, audio_path = ?5,
+                    versions = ?6, status = ?7
+                 WHERE id = ?1 AND trashed_at IS NULL",
+                params![
+                    item.id,
+                    item.text,
+                    item.word_count,
+                    item.provider,
+                    item.audio_path,
+                    versions_json(item),
+                    status_name(item.status),
+                ],
+            )
+            .map_err(db_error)?;
+        if updated > 0 {
+            self.sync_mirror(|mirror| mirror.write(item));
+        }
+        Ok(updated > 0)
+    }
+
+    /// Returns false when no item has this id.
+    pub fn delete(&self, id: &str) -> Result<bool, String> {
+        let deleted = self
+            .lock()
+            .execute(
+                "DELETE FROM history WHERE id = ?1 AND trashed_at IS NULL",
+                [id],
+            )
+            .map_err(db_error)?;
+        if deleted > 0 {
+            self.sync_mirror(|mirror| mirror.remove(id));
+        }
+        Ok(deleted > 0)
+    }
+
+    /// Every item, newest first.
+    pub fn items(&self) -> Result<Vec<HistoryItem>, String> {
+        self.query("trashed_at IS NULL", -1)
+    }
+
+    /// The newest `limit` items.
+    pub fn recent(&self, limit: usize) -> Result<Vec<HistoryItem>, String> {
+        self.query("trashed_at IS NULL", i64::try_from(limit).unwrap_or(-1))
+    }
+
+    /// Items waiting in the offline queue, newest first.
+    pub fn pending(&self) -> Result<Vec<HistoryItem>, String> {
+        self.query(
+            &format!("trashed_at IS NULL AND status = '{}'", PENDING),
+            -1,
+        )
+    }
+
+    fn query(&self, filter: &str, limit: i64) -> Result<Vec<HistoryItem>, String> {
+        let conn = self.lock();
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT {} FROM history WHERE {} ORDER BY seq DESC LIMIT ?1",
+                COLUMNS, filter
+            ))
+            .map_err(db_error)?;
+        let items = statement
+            .query_map([limit], read_item)
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error);
+        items
+    }
+
+    pub fn count(&self) -> Result<usize, String> {
+        self.lock()
+            .query_row(
+                "SELECT COUNT(*) FROM history WHERE trashed_at IS NULL",
+                [],
+                read_count,
+            )
+            .map(|count| count as usize)
+            .map_err(db_error)
+    }
+
+    /// Totals over transcribed items; pending ones have no words yet. Recordings shorter
+    /// than 50 ms count as typed, without a duration.
+    pub fn stats(&self) -> Result<Stats, String> {
+        let (total_transcriptions, total_words, total_recording_seconds) = self
+            .lock()
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*), COALESCE(SUM(word_count), 0),
+                        COALESCE(SUM(CASE WHEN duration_seconds > 0.05
+                            THEN duration_seconds ELSE 0 END), 0)
+                     FROM history WHERE trashed_at IS NULL AND status = '{}'",
+                    TRANSCRIBED
+                ),
+                [],
+                |row| {
+                    Ok((
+                        read_count(row)?,
+                        row.get::<_, i64>(1)? as u64,
+                        row.get::<_, f64>(2)?,
+                    ))
+                },
+            )
+            .map_err(db_error)?;
+        Ok(Stats {
+            total_transcriptions,
+            total_words,
+            total_recording_seconds: total_recording_seconds as f32,
+        })
+    }
+
+    /// What clearing history would remove.
+    pub fn scope(&self) -> Result<DestructiveScope, String> {
+        self.lock()
+            .query_row(
+                "SELECT COUNT(*), MIN(timestamp), MAX(timestamp)
+                 FROM history WHERE trashed_at IS NULL",
+                [],
+                |row| {
+                    Ok(DestructiveScope {
+                        history_items: read_count(row)? as usize,
+                        oldest: row.get(1)?,
+                        newest: row.get(2)?,
+                    })
+                },
+            )
+            .map_err(db_error)
+    }
+
+    pub fn trash_count(&self, now: DateTime<Utc>) -> Result<usize, String> {
+        let conn = self.lock();
+        purge_expired_trash(&conn, now)?;
+        conn.query_row(
+            "SELECT COUNT(*) FROM history WHERE trashed_at IS NOT NULL",
+            [],
+            read_count,
+        )
+        .map(|count| count as usize)
+        .map_err(db_error)
+    }
+
+    /// Move every item to the trash, joining anything cleared before. Each item keeps the
+    /// date it was cleared and expires on its own. Returns how many were moved.
+    pub fn move_to_trash(&self, now: DateTime<Utc>) -> Result<usize, String> {
+        let conn = self.lock();
+        purge_expired_trash(&conn, now)?;
+        let moved = conn
+            .execute(
+                "UPDATE history SET trashed_at = ?1 WHERE trashed_at IS NULL",
+                [now.to_rfc3339()],
+            )
+            .map_err(db_error)?;
+        drop(conn);
+        if moved > 0 {
+            self.sync_mirror(|mirror| mirror.retain(&HashSet::new()));
+        }
+        Ok(moved)
+    }
+
+    /// Put trashed items back where they were. Returns how many were restored. Refused when
+    /// they would take history past `history_limit` (0 keeps everything), which would evict
+    /// the oldest of them again.
+    pub fn restore_trash(&self, now: DateTime<Utc>, history_limit: usize) -> Result<usize, String> {
+        let restored = {
+            let conn = self.lock();
+            purge_expired_trash(&conn, now)?;
+            let transcribed = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM history WHERE status = ?1",
+                    [TRANSCRIBED],
+                    read_count,
+                )
+                .map_err(db_error)? as usize;
+            if history_limit > 0 && transcribed > history_limit {
+                return Err(format!(
+                    "Restoring the trash would take history to {} items, past its limit of {}. \
+                     Raise the limit or delete some items first.",
+                    transcribed, history_limit
+                ));
+            }
+            conn.execute(
+                "UPDATE history SET trashed_at = NULL WHERE trashed_at IS NOT NULL",
+                [],
+            )
+            .map_err(db_error)?
+        };
+        if restored > 0 {
+            self.rebuild_mirror();
+        }
+        Ok(restored)
+    }
+
+    /// Drop trashed items once their grace period is over. Returns whether any were.
+    pub fn purge_expired_trash(&self, now: DateTime<Utc>) -> Result<bool, String> {
+        purge_expired_trash(&self.lock(), now)
+    }
+
+    /// Audio files history or the trash still points at.
+    pub fn audio_paths(&self) -> Result<HashSet<PathBuf>, String> {
+        let conn = self.lock();
+        let mut statement = conn
+            .prepare("SELECT audio_path FROM history WHERE audio_path IS NOT NULL")
+            .map_err(db_error)?;
+        let paths = statement
+            .query_map([], |row| row.get::<_, String>(0).map(PathBuf::from))
+            .map_err(db_error)?
+            .collect::<Result<HashSet<_>, _>>()
+            .map_err(db_error);
+        paths
+    }
+
+    /// Drop the oldest items beyond `history_limit` (0 keeps everything), then unlink the
+    /// archived audio beyond the archive's bounds. Pending items are never dropped; their
+    /// audio would be lost. The offline queue has its own bounds.
+    pub fn enforce_limit(
+        &self,
+        history_limit: usize,
+        archive: &AudioArchiveSettings,
+    ) -> Result<ChangeSummary, String> {
+        let before = self.items()?;
+        if history_limit > 0 {
+            self.lock()
+                .execute(
+                    &format!(
+                        "DELETE FROM history WHERE trashed_at IS NULL AND status = '{0}'
+                         AND seq NOT IN (
+                            SELECT seq FROM history WHERE trashed_at IS NULL AND status = '{0}'
+                            ORDER BY seq DESC LIMIT ?1)",
+                        TRANSCRIBED
+                    ),
+                    [i64::try_from(history_limit).unwrap_or(i64::MAX)],
+                )
+                .map_err(db_error)?;
+        }
+
+        let kept = self.items()?;
+        if kept.len() < before.len() {
+            let ids: HashSet<&str> = kept.iter().map(|item| item.id.as_str()).collect();
+            self.sync_mirror(|mirror| mirror.retain(&ids));
+        }
+        let mut trimmed = kept.clone();
+        audio_archive::enforce_limit(&mut trimmed, archive);
+        for (old, new) in kept.iter().zip(&trimmed) {
+            if old.audio_path != new.audio_path {
+                self.update(new)?;
+            }
+        }
+        Ok(ChangeSummary::between(&before, &trimmed))
+    }
+}
+
+/// The store opened at startup.
+pub fn for_app(app: &AppHandle) -> Result<tauri::State<'_, HistoryStore>, String> {
+    app.try_state::<HistoryStore>()
+        .ok_or_else(|| "History is not open yet".to_string())
+}
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("History database error: {}", e)
+}
+
+fn insert_row(
+    conn: &Connection,
+    item: &HistoryItem,
+    trashed_at: Option<&str>,
+) -> Result<usize, String> {
+    conn.execute(
+        &format!(
+            "INSERT OR IGNORE INTO history ({}, trashed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            COLUMNS
+        ),
+        params![
+            item.id,
+            item.text,
+            item.timestamp,
+            f64::from(item.duration_seconds),
+            item.word_count,
+            item.provider,
+            item.audio_path,
+            versions_json(item),
+            status_name(item.status),
+            item.request_id,
+            item.session_stats
+                .as_ref()
+                .and_then(|stats| serde_json::to_string(stats).ok()),
+            trashed_at,
+        ],
+    )
+    .map_err(db_error)
+}
+
+/// The `COUNT(*)` in the first column.
+fn read_count(row: &Row<'_>) -> rusqlite::Result<u64> {
+    row.get::<_, i64>(0).map(|count| count as u64)
+}
+
+fn read_item(row: &Row<'_>) -> rusqlite::Result<HistoryItem> {
+    let versions: String = row.get(7)?;
+    let status: String = row.get(8)?;
+    let session_stats: Option<String> = row.get(10)?;
+    Ok(HistoryItem {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        timestamp: row.get(2)?,
+        duration_seconds: row.get::<_, f64>(3)? as f32,
+        word_count: row.get(4)?,
+        provider: row.get(5)?,
+        audio_path: row.get(6)?,
+        versions: serde_json::from_str(&versions).unwrap_or_default(),
+        status: if status == PENDING {
+            HistoryStatus::PendingTranscription
+        } else {
+            HistoryStatus::Transcribed
+        },
+        request_id: row.get(9)?,
+        session_stats: session_stats.and_then(|raw| serde_json::from_str(&raw).ok()),
+    })
+}
+
+fn versions_json(item: &HistoryItem) -> String {
+    serde_json::to_string(&item.versions).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn status_name(status: HistoryStatus) -> &'static str {
+    match status {
+        HistoryStatus::Transcribed => TRANSCRIBED,
+        HistoryStatus::PendingTranscription => PENDING,
+    }
+}
+
+/// Unparseable clear dates are purged too.
+fn purge_expired_trash(conn: &Connection, now: DateTime<Utc>) -> Result<bool, String> {
+    let cleared: Vec<String> = {
+        let mut statement = conn
+            .prepare("SELECT DISTINCT trashed_at FROM history WHERE trashed_at IS NOT NULL")
+            .map_err(db_error)?;
+        let cleared = statement
+            .query_map([], |row| row.get(0))
+            .map_err(db_error)?
+            .collect::<Result<_, _>>()
+            .map_err(db_error);
+        cleared?
+    };
+    let mut purged = 0;
+    for deleted_at in cleared {
+        let expired = DateTime::parse_from_rfc3339(&deleted_at)
+            .map(|deleted_at| {
+                now.signed_duration_since(deleted_at)
+                    >= chrono::Duration::hours(TRASH_RETENTION_HOURS)
+            })
+            .unwrap_or(true);
+        if expired {
+            purged += conn
+                .execute("DELETE FROM history WHERE trashed_at = ?1", [&deleted_at])
+                .map_err(db_error)?;
+        }
+    }
+    if purged > 0 {
+        tracing::info!(
+            "Purged {} history item(s) past the trash's retention window",
+            purged
+        );
+    }
+    Ok(purged > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, words: u32, status: HistoryStatus) -> HistoryItem {
+        HistoryItem {
+            id: id.to_string(),
+            text: "texto".to_string(),
+            timestamp: format!("2026-02-0{}T10:00:00Z", words.clamp(1, 9)),
+            duration_seconds: 2.0,
+            word_count: words,
+            provider: Some("groq".to_string()),
+            audio_path: None,
+            versions: Vec::new(),
+            status,
+            request_id: None,
+            session_stats: None,
+        }
+    }
+
+    fn ids(items: Vec<HistoryItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn test_stats_are_aggregates_over_transcribed_items() {
+        let store = HistoryStore::in_memory().unwrap();
+        store
+            .insert(&item("a", 3, HistoryStatus::Transcribed))
+            .unwrap();
+        store
+            .insert(&item("b", 5, HistoryStatus::Transcribed))
+            .unwrap();
+        store
+            .insert(&item("p", 0, HistoryStatus::PendingTranscription))
+            .unwrap();
+        let mut typed = item("t", 2, HistoryStatus::Transcribed);
+        typed.duration_seconds = 0.0;
+        store.insert(&typed).unwrap();
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.total_transcriptions, 3);
+        assert_eq!(stats.total_words, 10);
+        assert_eq!(stats.total_recording_seconds, 4.0);
+        assert_eq!(ids(store.items().unwrap()), ["t", "p", "b", "a"]);
+        assert_eq!(ids(store.pending().unwrap()), ["p"]);
+        assert_eq!(ids(store.recent(2).unwrap()), ["t", "p"]);
+
+        let mut updated = store.get("a").unwrap().unwrap();
+        updated.word_count = 10;
+        assert!(store.update(&updated).unwrap());
+        assert_eq!(store.stats().unwrap().total_words, 17);
+        assert!(store.delete("a").unwrap());
+        assert!(!store.delete("a").unwrap());
+        assert_eq!(store.count().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_limit_keeps_the_newest_and_every_pending_item() {
+        let store = HistoryStore::in_memory().unwrap();
+        store
+            .insert(&item("p", 0, HistoryStatus::PendingTranscription))
+            .unwrap();
+        for n in 1..=4 {
+            store
+                .insert(&item(&n.to_string(), n, HistoryStatus::Transcribed))
+                .unwrap();
+        }
+        let archive = AudioArchiveSettings::default();
+        assert!(store.enforce_limit(0, &archive).unwrap().is_empty());
+        assert_eq!(store.count().unwrap(), 5);
+
+        assert_eq!(
+            store.enforce_limit(2, &archive).unwrap(),
+            ChangeSummary::removed(2)
+        );
+        assert_eq!(ids(store.items().unwrap()), ["4", "3", "p"]);
+    }
+
+    #[test]
+    fn test_trash_round_trip_and_expiry() {
+        let store = HistoryStore::in_memory().unwrap();
+        store
+            .insert(&item("a", 1, HistoryStatus::Transcribed))
+            .unwrap();
+        store
+            .insert(&item("b", 2, HistoryStatus::Transcribed))
+            .unwrap();
+        let scope = store.scope().unwrap();
+        assert_eq!(scope.history_items, 2);
+        assert_eq!(scope.oldest.as_deref(), Some("2026-02-01T10:00:00Z"));
+
+        let cleared_at = Utc::now();
+        assert_eq!(store.move_to_trash(cleared_at).unwrap(), 2);
+        assert_eq!(store.count().unwrap(), 0);
+        assert_eq!(store.stats().unwrap().total_transcriptions, 0);
+        store
+            .insert(&item("c", 3, HistoryStatus::Transcribed))
+            .unwrap();
+        assert_eq!(store.trash_count(cleared_at).unwrap(), 2);
+        // Restoring past the history limit would evict what was just restored.
+        assert!(store.restore_trash(cleared_at, 2).is_err());
+        assert_eq!(store.trash_count(cleared_at).unwrap(), 2);
+        assert_eq!(store.restore_trash(cleared_at, 3).unwrap(), 2);
+        assert_eq!(ids(store.items().unwrap()), ["c", "b", "a"]);
+
+        store.move_to_trash(cleared_at).unwrap();
+        let almost = cleared_at + chrono::Duration::hours(TRASH_RETENTION_HOURS - 1);
+        assert!(!store.purge_expired_trash(almost).unwrap());
+        let expired = cleared_at + chrono::Duration::hours(TRASH_RETENTION_HOURS);
+        assert_eq!(store.restore_trash(expired, 0).unwrap(), 0);
+        assert_eq!(store.trash_count(expired).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_trashed_items_keep_their_own_clear_date() {
+        let store = HistoryStore::in_memory().unwrap();
+        let first_clear = Utc::now();
+        store
+            .insert(&item("a", 1, HistoryStatus::Transcribed))
+            .unwrap();
+        store.move_to_trash(first_clear).unwrap();
+
+        let second_clear = first_clear + chrono::Duration::hours(TRASH_RETENTION_HOURS - 1);
+        store
+            .insert(&item("b", 2, HistoryStatus::Transcribed))
+            .unwrap();
+        store.move_to_trash(second_clear).unwrap();
+        assert_eq!(store.trash_count(second_clear).unwrap(), 2);
+
+        // The first clear's grace period is over; the second one's isn't.
+        let later = first_clear + chrono::Duration::hours(TRASH_RETENTION_HOURS);
+        assert!(store.purge_expired_trash(later).unwrap());
+        assert_eq!(store.restore_trash(later, 0).unwrap(), 1);
+        assert_eq!(ids(store.items().unwrap()), ["b"]);
+    }
+
+    #[test]
+    fn test_legacy_import_is_ordered_and_can_run_again() {
+        let dir = std::env::temp_dir().join(format!("zentra-store-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(HISTORY_DB_FILE);
+        let history = vec![
+            item("new", 2, HistoryStatus::Transcribed),
+            item("old", 1, HistoryStatus::Transcribed),
+        ];
+        let trash = HistoryTrash {
+            deleted_at: Utc::now().to_rfc3339(),
+            items: vec![item("gone", 1, HistoryStatus::Transcribed)],
+        };
+
+        let store = HistoryStore::open(&path).unwrap();
+        assert_eq!(store.import_legacy(&history, Some(&trash)).unwrap(), 3);
+        assert_eq!(store.import_legacy(&history, Some(&trash)).unwrap(), 0);
+        drop(store);
+
+        let reopened = HistoryStore::open(&path).unwrap();
+        assert_eq!(ids(reopened.items().unwrap()), ["new", "old"]);
+        assert_eq!(reopened.get("new").unwrap().unwrap(), history[0]);
+        assert_eq!(reopened.trash_count(Utc::now()).unwrap(), 1);
+        drop(reopened);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Names of the mirrored files in `dir`, sorted.
+    fn mirrored(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".json"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_mirror_follows_new_edited_and_deleted_items() {
+        let dir = std::env::temp_dir().join(format!("zentra-mirror-{}", uuid::Uuid::new_v4()));
+        let privacy = PrivacyGuard::default();
+        let store = HistoryStore::in_memory().unwrap();
+        assert!(store.mirror_to(Some(dir.clone()), &privacy).is_some());
+        assert!(store.mirror_to(Some(dir.clone()), &privacy).is_none());
+        assert_eq!(store.resync_mirror().unwrap(), 0);
+
+        store
+            .insert(&item("a", 1, HistoryStatus::Transcribed))
+            .unwrap();
+        store
+            .insert(&item("b", 2, HistoryStatus::Transcribed))
+            .unwrap();
+        assert_eq!(
+            mirrored(&dir),
+            ["20260201T100000Z_a.json", "20260202T100000Z_b.json"]
+        );
+
+        let mut edited = store.get("a").unwrap().unwrap();
+        edited.text = "texto corrigido".to_string();
+        store.update(&edited).unwrap();
+        let raw = std::fs::read_to_string(dir.join("20260201T100000Z_a.json")).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(saved["text"], "texto corrigido");
+        assert!(saved.get("audio_path").is_none() && saved.get("provider").is_none());
+
+        store.delete("b").unwrap();
+        assert_eq!(mirrored(&dir), ["20260201T100000Z_a.json"]);
+
+        let now = Utc::now();
+        store.move_to_trash(now).unwrap();
+        assert!(mirrored(&dir).is_empty());
+        store.restore_trash(now, 0).unwrap();
+        assert_eq!(mirrored(&dir), ["20260201T100000Z_a.json"]);
+
+        // Nothing is written in privacy mode; the first change after it catches up.
+        privacy.set_enabled(true);
+        store
+            .insert(&item("c", 3, HistoryStatus::Transcribed))
+            .unwrap();
+        assert_eq!(mirrored(&dir), ["20260201T100000Z_a.json"]);
+        assert!(store.resync_mirror().is_err());
+        privacy.set_enabled(false);
+        store
+            .insert(&item("d", 4, HistoryStatus::Transcribed))
+            .unwrap();
+        assert_eq!(mirrored(&dir).len(), 3);
+
+        assert!(store.mirror_to(None, &privacy).is_none());
+        store.delete("a").unwrap();
+        assert_eq!(mirrored(&dir).len(), 3, "files stay once mirroring stops");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_resync_rebuilds_the_mirror_and_leaves_other_files_alone() {
+        let dir = std::env::temp_dir().join(format!("zentra-mirror-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("20250101T000000Z_gone.json"), "{}").unwrap();
+        std::fs::write(dir.join("20260201T100000Z_a.json"), "{}").unwrap();
+        std::fs::write(dir.join("notes.json"), "{}").unwrap();
+
+        let store = HistoryStore::in_memory().unwrap();
+        store
+            .insert(&item("a", 1, HistoryStatus::Transcribed))
+            .unwrap();
+        store
+            .insert(&item("b", 2, HistoryStatus::Transcribed))
+            .unwrap();
+        store.mirror_to(Some(dir.clone()), &PrivacyGuard::default());
+        assert_eq!(store.resync_mirror().unwrap(), 2);
+        assert_eq!(
+            mirrored(&dir),
+            [
+                "20260201T100000Z_a.json",
+                "20260202T100000Z_b.json",
+                "notes.json"
+            ]
+        );
+        let raw = std::fs::read_to_string(dir.join("20260201T100000Z_a.json")).unwrap();
+        assert!(raw.contains("\"id\": \"a\""));
+
+        // A folder that can't be written is reported, and history keeps working.
+        let blocked = dir.join("notes.json");
+        store.mirror_to(Some(blocked), &PrivacyGuard::default());
+        store
+            .insert(&item("c", 3, HistoryStatus::Transcribed))
+            .unwrap();
+        assert!(store.resync_mirror().is_err());
+        assert_eq!(store.count().unwrap(), 3);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,211 @@
+//! `dashboard:history-updated` without the storm. Batch jobs write history dozens of times
+//! in a row, and a dashboard refetch per write makes it stutter, so changes are summed and
+//! sent at most once per [`MIN_INTERVAL`]: the first change of a burst goes out at once,
+//! the rest in one event when the interval is over.
+
+use crate::config::HistoryItem;
+use crate::locks::lock_or_recover;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// History items added, removed and changed since the last event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSummary {
+    pub added: u32,
+    pub removed: u32,
+    pub updated: u32,
+}
+
+impl ChangeSummary {
+    pub fn added(count: u32) -> Self {
+        Self {
+            added: count,
+            ..Self::default()
+        }
+    }
+
+    pub fn removed(count: u32) -> Self {
+        Self {
+            removed: count,
+            ..Self::default()
+        }
+    }
+
+    pub fn updated(count: u32) -> Self {
+        Self {
+            updated: count,
+            ..Self::default()
+        }
+    }
+
+    /// What turned `before` into `after`, matching items by id.
+    pub fn between(before: &[HistoryItem], after: &[HistoryItem]) -> Self {
+        let previous: HashMap<&str, &HistoryItem> =
+            before.iter().map(|item| (item.id.as_str(), item)).collect();
+        let mut summary = Self::default();
+        let mut kept = 0;
+        for item in after {
+            match previous.get(item.id.as_str()) {
+                Some(old) => {
+                    kept += 1;
+                    if *old != item {
+                        summary.updated += 1;
+                    }
+                }
+                None => summary.added += 1,
+            }
+        }
+        summary.removed = before.len().saturating_sub(kept) as u32;
+        summary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.added += other.added;
+        self.removed += other.removed;
+        self.updated += other.updated;
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    summary: ChangeSummary,
+    last_sent: Option<Instant>,
+    flush_scheduled: bool,
+}
+
+/// Sums history changes and hands them to `sink`, throttled to [`MIN_INTERVAL`].
+pub struct HistoryNotifier {
+    pending: Arc<Mutex<Pending>>,
+    sink: Arc<dyn Fn(ChangeSummary) + Send + Sync>,
+}
+
+impl HistoryNotifier {
+    pub fn new(sink: impl Fn(ChangeSummary) + Send + Sync + 'static) -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(Pending::default())),
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Add `change` to the next event. Empty changes send nothing.
+    pub fn record(&self, change: ChangeSummary) {
+        if change.is_empty() {
+            return;
+        }
+        let mut pending = lock_or_recover(&self.pending, "history updates");
+        pending.summary.merge(change);
+        if pending.flush_scheduled {
+            return;
+        }
+
+        let now = Instant::now();
+        let wait = pending.last_sent.map_or(Duration::ZERO, |last| {
+            MIN_INTERVAL.saturating_sub(now.duration_since(last))
+        });
+        if wait.is_zero() {
+            let summary = std::mem::take(&mut pending.summary);
+            pending.last_sent = Some(now);
+            drop(pending);
+            (self.sink)(summary);
+            return;
+        }
+
+        pending.flush_scheduled = true;
+        drop(pending);
+        let shared = self.pending.clone();
+        let sink = self.sink.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(wait);
+            let mut pending = lock_or_recover(&shared, "history updates");
+            let summary = std::mem::take(&mut pending.summary);
+            pending.flush_scheduled = false;
+            pending.last_sent = Some(Instant::now());
+            drop(pending);
+            sink(summary);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HistoryStatus;
+
+    fn item(id: &str, text: &str) -> HistoryItem {
+        HistoryItem {
+            id: id.to_string(),
+            text: text.to_string(),
+            timestamp: "2026-01-01T10:00:00Z".to_string(),
+            duration_seconds: 1.0,
+            word_count: 1,
+            provider: None,
+            audio_path: None,
+            versions: Vec::new(),
+            status: HistoryStatus::Transcribed,
+            request_id: None,
+            session_stats: None,
+        }
+    }
+
+    #[test]
+    fn test_rapid_inserts_are_coalesced_with_exact_totals() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        let notifier = HistoryNotifier::new(move |summary| sink.lock().unwrap().push(summary));
+
+        let started = Instant::now();
+        notifier.record(ChangeSummary::added(1));
+        // The first change of a burst is not held back.
+        assert_eq!(sent.lock().unwrap().len(), 1);
+        for _ in 1..100 {
+            std::thread::sleep(Duration::from_millis(5));
+            notifier.record(ChangeSummary::added(1));
+        }
+        notifier.record(ChangeSummary::default());
+        let elapsed = started.elapsed();
+        std::thread::sleep(MIN_INTERVAL + Duration::from_millis(300));
+
+        let sent = sent.lock().unwrap();
+        let bound = (elapsed.as_millis() / MIN_INTERVAL.as_millis()) as usize + 2;
+        assert!(sent.len() >= 2, "the end of the burst must be sent");
+        assert!(
+            sent.len() <= bound,
+            "{} events for {:?}",
+            sent.len(),
+            elapsed
+        );
+        assert!(sent.iter().all(|summary| !summary.is_empty()));
+        assert_eq!(sent.iter().map(|summary| summary.added).sum::<u32>(), 100);
+        assert!(sent
+            .iter()
+            .all(|summary| summary.removed == 0 && summary.updated == 0));
+    }
+
+    #[test]
+    fn test_summary_between_history_snapshots() {
+        let before = vec![item("a", "um"), item("b", "dois"), item("c", "três")];
+        let after = vec![
+            item("d", "quatro"),
+            item("a", "um, revisado"),
+            item("c", "três"),
+        ];
+        assert_eq!(
+            ChangeSummary::between(&before, &after),
+            ChangeSummary {
+                added: 1,
+                removed: 1,
+                updated: 1,
+            }
+        );
+        assert!(ChangeSummary::between(&before, &before).is_empty());
+    }
+}
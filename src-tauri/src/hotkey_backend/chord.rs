@@ -0,0 +1,284 @@
+//! Accelerator strings, as stored in `hotkey` and `hotkeys` (`CommandOrControl+Shift+Space`),
+//! read as the key chords the portal and evdev backends work with: modifiers plus one key,
+//! identified by its Linux input event code.
+
+use crate::hotkey_capture::Modifiers;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    /// Linux input event code of the key (`KEY_*` in `input-event-codes.h`)
+    pub code: u16,
+    /// XKB keysym name of the key, as the shortcuts spec writes triggers
+    keysym: String,
+}
+
+impl Chord {
+    /// Read an accelerator. Modifier names and their order are as loose as the plugin's;
+    /// `CommandOrControl` is Ctrl, there being no Command key on Linux.
+    pub fn parse(accelerator: &str) -> Result<Self, String> {
+        let parts: Vec<String> = accelerator
+            .split('+')
+            .map(|part| part.trim().to_ascii_lowercase())
+            .collect();
+        let Some((key, modifier_names)) = parts.split_last() else {
+            return Err("empty shortcut".to_string());
+        };
+
+        let mut modifiers = Modifiers::default();
+        for name in modifier_names {
+            match name.as_str() {
+                "ctrl" | "control" | "commandorcontrol" | "commandorctrl" | "cmdorctrl"
+                | "cmdorcontrol" => modifiers.control = true,
+                "shift" => modifiers.shift = true,
+                "alt" | "option" => modifiers.alt = true,
+                "super" | "meta" | "cmd" | "command" => modifiers.meta = true,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", name, accelerator)),
+            }
+        }
+        let (code, keysym) =
+            key_code(key).ok_or_else(|| format!("unknown key '{}' in '{}'", key, accelerator))?;
+        Ok(Self {
+            modifiers,
+            code,
+            keysym,
+        })
+    }
+
+    /// The chord as a shortcuts-spec trigger for the portal, e.g. `CTRL+SHIFT+space`.
+    pub fn portal_trigger(&self) -> String {
+        let mut parts = Vec::new();
+        if self.modifiers.control {
+            parts.push("CTRL");
+        }
+        if self.modifiers.alt {
+            parts.push("ALT");
+        }
+        if self.modifiers.shift {
+            parts.push("SHIFT");
+        }
+        if self.modifiers.meta {
+            parts.push("LOGO");
+        }
+        parts.push(&self.keysym);
+        parts.join("+")
+    }
+}
+
+/// Event code and keysym of a key name, accepting the plugin's `KeyA`/`Digit1` spellings.
+fn key_code(name: &str) -> Option<(u16, String)> {
+    // a to z, in the kernel's QWERTY-position codes
+    const LETTERS: [u16; 26] = [
+        30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47, 17,
+        45, 21, 44,
+    ];
+    const NUMPAD: [u16; 10] = [82, 79, 80, 81, 75, 76, 77, 71, 72, 73];
+
+    let name = name
+        .strip_prefix("key")
+        .filter(|rest| rest.len() == 1)
+        .or_else(|| name.strip_prefix("digit"))
+        .unwrap_or(name);
+    if let [c] = name.as_bytes() {
+        match c {
+            b'a'..=b'z' => return Some((LETTERS[(c - b'a') as usize], name.to_string())),
+            b'1'..=b'9' => return Some(((c - b'0' + 1) as u16, name.to_string())),
+            b'0' => return Some((11, name.to_string())),
+            _ => {}
+        }
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u16>().ok()) {
+        let code = match n {
+            1..=10 => 58 + n,
+            11 | 12 => 76 + n,
+            13..=24 => 170 + n,
+            _ => return None,
+        };
+        return Some((code, format!("F{}", n)));
+    }
+    if let Some(n) = name
+        .strip_prefix("numpad")
+        .and_then(|n| n.parse::<usize>().ok())
+    {
+        return NUMPAD.get(n).map(|&code| (code, format!("KP_{}", n)));
+    }
+
+    let (code, keysym) = match name {
+        "space" => (57, "space"),
+        "enter" | "return" => (28, "Return"),
+        "tab" => (15, "Tab"),
+        "esc" | "escape" => (1, "Escape"),
+        "backspace" => (14, "BackSpace"),
+        "delete" | "del" => (111, "Delete"),
+        "insert" => (110, "Insert"),
+        "home" => (102, "Home"),
+        "end" => (107, "End"),
+        "pageup" => (104, "Page_Up"),
+        "pagedown" => (109, "Page_Down"),
+        "arrowup" | "up" => (103, "Up"),
+        "arrowdown" | "down" => (108, "Down"),
+        "arrowleft" | "left" => (105, "Left"),
+        "arrowright" | "right" => (106, "Right"),
+        "minus" | "-" => (12, "minus"),
+        "equal" | "=" => (13, "equal"),
+        "comma" | "," => (51, "comma"),
+        "period" | "." => (52, "period"),
+        "slash" | "/" => (53, "slash"),
+        "backslash" | "\\" => (43, "backslash"),
+        "semicolon" | ";" => (39, "semicolon"),
+        "quote" | "'" => (40, "apostrophe"),
+        "backquote" | "`" => (41, "grave"),
+        "bracketleft" | "[" => (26, "bracketleft"),
+        "bracketright" | "]" => (27, "bracketright"),
+        _ => return None,
+    };
+    Some((code, keysym.to_string()))
+}
+
+/// Modifier keys by event code: left and right Ctrl, Shift, Alt, then Super.
+const MODIFIER_CODES: [u16; 8] = [29, 97, 42, 54, 56, 100, 125, 126];
+
+/// Follows one keyboard's key events and says when a chord was pressed: its key went down
+/// while exactly its modifiers were held. Auto-repeat does not press it again.
+#[derive(Debug, Default)]
+pub struct ChordMatcher {
+    /// Bit per entry of [`MODIFIER_CODES`]
+    held: u8,
+}
+
+impl ChordMatcher {
+    /// Feed a key event (`value` 1 down, 0 up, 2 repeat) and get the binding it pressed.
+    pub fn key<'a, T>(
+        &mut self,
+        code: u16,
+        value: i32,
+        bindings: &'a [(Chord, T)],
+    ) -> Option<&'a T> {
+        if let Some(index) = MODIFIER_CODES.iter().position(|&c| c == code) {
+            match value {
+                0 => self.held &= !(1 << index),
+                1 => self.held |= 1 << index,
+                _ => {}
+            }
+            return None;
+        }
+        if value != 1 {
+            return None;
+        }
+        let held = self.modifiers();
+        bindings
+            .iter()
+            .find(|(chord, _)| chord.code == code && chord.modifiers == held)
+            .map(|(_, binding)| binding)
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        let pair = |index: u8| self.held & (0b11 << index) != 0;
+        Modifiers {
+            control: pair(0),
+            shift: pair(2),
+            alt: pair(4),
+            meta: pair(6),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_LEFTCTRL: u16 = 29;
+    const KEY_RIGHTCTRL: u16 = 97;
+    const KEY_LEFTSHIFT: u16 = 42;
+    const KEY_LEFTALT: u16 = 56;
+    const KEY_SPACE: u16 = 57;
+    const KEY_V: u16 = 47;
+
+    fn chord(accelerator: &str) -> Chord {
+        Chord::parse(accelerator).unwrap()
+    }
+
+    #[test]
+    fn test_stored_accelerators_normalize_to_the_same_chord() {
+        let toggle = chord("CommandOrControl+Shift+Space");
+        for spelling in [
+            "ctrl+shift+space",
+            "Shift+Control+SPACE",
+            "CmdOrCtrl + Shift + Space",
+            "CommandOrControl+Shift+Shift+Space",
+        ] {
+            assert_eq!(chord(spelling), toggle, "{}", spelling);
+        }
+        assert_eq!(toggle.code, KEY_SPACE);
+        assert_eq!(chord("Alt+KeyR"), chord("Option+r"));
+        assert_eq!(chord("Ctrl+Digit1"), chord("Ctrl+1"));
+        assert_eq!(chord("Ctrl+Digit1").code, 2);
+        assert_eq!(chord("Ctrl+0").code, 11);
+        assert_eq!(chord("Super+F9").code, 67);
+        assert_eq!(chord("Super+F12").code, 88);
+        assert_eq!(chord("Super+F13").code, 183);
+        assert_eq!(chord("Ctrl+Q").code, 16);
+        assert_eq!(chord("Ctrl+M").code, 50);
+        assert_eq!(chord("Ctrl+Numpad7").code, 71);
+        assert_eq!(chord("Ctrl+Esc"), chord("Control+Escape"));
+        // A bare key is a chord too; requiring a modifier is the settings' job.
+        assert_eq!(chord("F8").modifiers, Modifiers::default());
+
+        for invalid in [
+            "",
+            "Ctrl+Shift",
+            "Ctrl+Hyper+A",
+            "Ctrl+Foo",
+            "Ctrl+F25",
+            "Ctrl+",
+        ] {
+            assert!(Chord::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_portal_triggers_follow_the_shortcuts_spec() {
+        assert_eq!(
+            chord("CommandOrControl+Shift+Space").portal_trigger(),
+            "CTRL+SHIFT+space"
+        );
+        assert_eq!(chord("Shift+Super+KeyV").portal_trigger(), "SHIFT+LOGO+v");
+        assert_eq!(chord("Alt+PageDown").portal_trigger(), "ALT+Page_Down");
+        assert_eq!(chord("Ctrl+F5").portal_trigger(), "CTRL+F5");
+    }
+
+    #[test]
+    fn test_matcher_fires_on_the_key_with_exactly_its_modifiers() {
+        let bindings = vec![
+            (chord("Ctrl+Shift+Space"), "toggle"),
+            (chord("Ctrl+Shift+V"), "paste"),
+        ];
+        let mut matcher = ChordMatcher::default();
+        let mut press = |code: u16, value: i32| matcher.key(code, value, &bindings).copied();
+
+        assert_eq!(press(KEY_SPACE, 1), None);
+        assert_eq!(press(KEY_SPACE, 0), None);
+        press(KEY_LEFTCTRL, 1);
+        press(KEY_LEFTSHIFT, 1);
+        assert_eq!(press(KEY_SPACE, 1), Some("toggle"));
+        // Holding the key down repeats it; that is not another press.
+        assert_eq!(press(KEY_SPACE, 2), None);
+        assert_eq!(press(KEY_SPACE, 0), None);
+        assert_eq!(press(KEY_V, 1), Some("paste"));
+        press(KEY_V, 0);
+
+        // An extra modifier is a different chord.
+        press(KEY_LEFTALT, 1);
+        assert_eq!(press(KEY_SPACE, 1), None);
+        press(KEY_LEFTALT, 0);
+        press(KEY_SPACE, 0);
+
+        // Either Ctrl counts, and letting go of one leaves the other held.
+        press(KEY_RIGHTCTRL, 1);
+        press(KEY_LEFTCTRL, 0);
+        assert_eq!(press(KEY_SPACE, 1), Some("toggle"));
+        press(KEY_SPACE, 0);
+        press(KEY_RIGHTCTRL, 0);
+        assert_eq!(press(KEY_SPACE, 1), None);
+    }
+}
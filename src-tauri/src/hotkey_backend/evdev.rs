@@ -0,0 +1,139 @@
+//! Shortcuts read straight from the keyboards in /dev/input, for Wayland desktops without a
+//! GlobalShortcuts portal. Keys are only observed, never grabbed, so the focused app still
+//! gets them. Reading the devices needs membership of the `input` group, which also lets
+//! any program of the user read every key typed; that is why it is opt-in.
+
+use super::chord::{Chord, ChordMatcher};
+use super::{ActionSink, HotkeyBackend, HotkeyBackendKind};
+use crate::hotkeys::{HotkeyAction, HotkeyBinding};
+use crate::locks::lock_or_recover;
+use evdev::{Device, EventSummary, KeyCode};
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub const OPT_IN_HINT: &str = "the evdev fallback is off; set \"evdev_hotkeys\": true in config.json and add yourself to the input group (sudo usermod -aG input $USER, then log in again)";
+pub const PERMISSION_HINT: &str = "no permission to read the keyboards in /dev/input; add yourself to the input group (sudo usermod -aG input $USER) and log in again";
+
+type Bindings = Arc<Mutex<Vec<(Chord, HotkeyAction)>>>;
+
+pub struct EvdevHotkeys {
+    bindings: Bindings,
+    stop: Arc<AtomicBool>,
+}
+
+impl EvdevHotkeys {
+    /// Start a listener thread per keyboard. Fails when none can be read.
+    pub fn open(sink: ActionSink) -> Result<Self, String> {
+        let entries = std::fs::read_dir("/dev/input")
+            .map_err(|e| format!("Failed to list /dev/input: {}", e))?;
+        let mut keyboards = Vec::new();
+        let mut denied = false;
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if !is_event_node(&path) {
+                continue;
+            }
+            match Device::open(&path) {
+                Ok(device) if is_keyboard(&device) => keyboards.push(device),
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::PermissionDenied => denied = true,
+                Err(e) => tracing::debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+        if keyboards.is_empty() {
+            return Err(if denied {
+                PERMISSION_HINT.to_string()
+            } else {
+                "no keyboard found in /dev/input".to_string()
+            });
+        }
+
+        let backend = Self {
+            bindings: Arc::default(),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        for device in keyboards {
+            let name = device.name().unwrap_or("keyboard").to_string();
+            let (bindings, stop, sink) =
+                (backend.bindings.clone(), backend.stop.clone(), sink.clone());
+            std::thread::Builder::new()
+                .name("evdev-hotkeys".to_string())
+                .spawn(move || listen(device, &bindings, &stop, &sink))
+                .map_err(|e| format!("Failed to start listening to {}: {}", name, e))?;
+        }
+        Ok(backend)
+    }
+}
+
+impl HotkeyBackend for EvdevHotkeys {
+    fn kind(&self) -> HotkeyBackendKind {
+        HotkeyBackendKind::Evdev
+    }
+
+    fn register(&self, bindings: &[HotkeyBinding]) -> Result<(), String> {
+        let chords = bindings
+            .iter()
+            .map(|binding| {
+                Chord::parse(&binding.accelerator)
+                    .map(|chord| (chord, binding.action))
+                    .map_err(|e| format!("Invalid shortcut '{}': {}", binding.accelerator, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        *lock_or_recover(&self.bindings, "evdev bindings") = chords;
+        Ok(())
+    }
+
+    fn unregister_all(&self) {
+        lock_or_recover(&self.bindings, "evdev bindings").clear();
+    }
+}
+
+impl Drop for EvdevHotkeys {
+    fn drop(&mut self) {
+        // The threads notice on their next key event.
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn is_event_node(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("event"))
+}
+
+/// Something with letters and a space bar; mice and power buttons also report keys.
+fn is_keyboard(device: &Device) -> bool {
+    device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(KeyCode::KEY_A) && keys.contains(KeyCode::KEY_SPACE))
+}
+
+fn listen(mut device: Device, bindings: &Bindings, stop: &AtomicBool, sink: &ActionSink) {
+    let name = device.name().unwrap_or("keyboard").to_string();
+    let mut matcher = ChordMatcher::default();
+    while !stop.load(Ordering::Relaxed) {
+        let events = match device.fetch_events() {
+            Ok(events) => events,
+            Err(e) => {
+                // Unplugged keyboards end up here.
+                tracing::info!("Stopped reading {}: {}", name, e);
+                return;
+            }
+        };
+        for event in events {
+            if let EventSummary::Key(_, code, value) = event.destructure() {
+                let action = matcher
+                    .key(
+                        code.code(),
+                        value,
+                        &lock_or_recover(bindings, "evdev bindings"),
+                    )
+                    .copied();
+                if let Some(action) = action {
+                    sink(action);
+                }
+            }
+        }
+    }
+}
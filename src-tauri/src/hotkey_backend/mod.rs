@@ -0,0 +1,271 @@
+//! Where global shortcuts come from. The global-shortcut plugin is the default. On Linux,
+//! when it can't register them (usual on Wayland), the desktop portal's GlobalShortcuts
+//! interface is tried, and last an evdev listener on the keyboards in /dev/input. The
+//! listener sees every key typed, so it only runs when `evdev_hotkeys` is on, and it needs
+//! the user in the `input` group.
+
+pub mod chord;
+#[cfg(target_os = "linux")]
+pub mod evdev;
+#[cfg(target_os = "linux")]
+pub mod portal;
+
+use crate::hotkeys::{HotkeyAction, HotkeyBinding};
+use crate::locks::lock_or_recover;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyBackendKind {
+    Plugin,
+    Portal,
+    Evdev,
+}
+
+impl HotkeyBackendKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            HotkeyBackendKind::Plugin => "global shortcut plugin",
+            HotkeyBackendKind::Portal => "desktop portal",
+            HotkeyBackendKind::Evdev => "evdev listener",
+        }
+    }
+}
+
+pub trait HotkeyBackend: Send + Sync {
+    fn kind(&self) -> HotkeyBackendKind;
+    /// Replace the registered shortcuts with `bindings`.
+    fn register(&self, bindings: &[HotkeyBinding]) -> Result<(), String>;
+    /// Drop every shortcut; the backend is being replaced.
+    fn unregister_all(&self);
+}
+
+/// Where the portal and evdev backends report a pressed shortcut.
+pub type ActionSink = Arc<dyn Fn(HotkeyAction) + Send + Sync>;
+
+/// Creates a backend when it is its turn.
+pub type Connect = Box<dyn FnOnce() -> Result<Box<dyn HotkeyBackend>, String>>;
+
+/// The backends to try after the plugin on this platform.
+#[cfg(target_os = "linux")]
+pub fn fallbacks(sink: ActionSink, evdev_enabled: bool) -> Vec<(HotkeyBackendKind, Connect)> {
+    let portal_sink = sink.clone();
+    vec![
+        (
+            HotkeyBackendKind::Portal,
+            Box::new(move || {
+                portal::PortalHotkeys::connect(portal_sink)
+                    .map(|backend| Box::new(backend) as Box<dyn HotkeyBackend>)
+            }),
+        ),
+        (
+            HotkeyBackendKind::Evdev,
+            Box::new(move || {
+                if !evdev_enabled {
+                    return Err(evdev::OPT_IN_HINT.to_string());
+                }
+                evdev::EvdevHotkeys::open(sink)
+                    .map(|backend| Box::new(backend) as Box<dyn HotkeyBackend>)
+            }),
+        ),
+    ]
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn fallbacks(_sink: ActionSink, _evdev_enabled: bool) -> Vec<(HotkeyBackendKind, Connect)> {
+    Vec::new()
+}
+
+/// The backend in use, and why shortcuts are missing when none took them.
+#[derive(Default)]
+pub struct Hotkeys {
+    active: Mutex<Option<Box<dyn HotkeyBackend>>>,
+    problem: Mutex<Option<String>>,
+}
+
+impl Hotkeys {
+    pub fn backend(&self) -> Option<HotkeyBackendKind> {
+        lock_or_recover(&self.active, "hotkey backend")
+            .as_ref()
+            .map(|backend| backend.kind())
+    }
+
+    /// Why no backend registered the shortcuts, for startup health.
+    pub fn problem(&self) -> Option<String> {
+        lock_or_recover(&self.problem, "hotkey problem").clone()
+    }
+
+    /// Register `bindings` with the backend in use, or when it refuses, or before there is
+    /// one, with the first of `candidates` that takes them. A replaced backend lets go of
+    /// its shortcuts.
+    pub fn register(
+        &self,
+        bindings: &[HotkeyBinding],
+        candidates: Vec<(HotkeyBackendKind, Connect)>,
+    ) -> Result<HotkeyBackendKind, String> {
+        let mut active = lock_or_recover(&self.active, "hotkey backend");
+        let mut failures = Vec::new();
+        if let Some(backend) = active.as_ref() {
+            match backend.register(bindings) {
+                Ok(()) => {
+                    *lock_or_recover(&self.problem, "hotkey problem") = None;
+                    return Ok(backend.kind());
+                }
+                Err(e) => failures.push((backend.kind(), e)),
+            }
+        }
+
+        let current = active.as_ref().map(|backend| backend.kind());
+        for (kind, connect) in candidates {
+            if Some(kind) == current {
+                continue;
+            }
+            match connect().and_then(|backend| backend.register(bindings).map(|()| backend)) {
+                Ok(backend) => {
+                    for (failed, reason) in &failures {
+                        tracing::warn!(
+                            "Shortcuts not registered by the {}: {}",
+                            failed.label(),
+                            reason
+                        );
+                    }
+                    tracing::info!("Global shortcuts registered by the {}", kind.label());
+                    if let Some(previous) = active.replace(backend) {
+                        previous.unregister_all();
+                    }
+                    *lock_or_recover(&self.problem, "hotkey problem") = None;
+                    return Ok(kind);
+                }
+                Err(e) => failures.push((kind, e)),
+            }
+        }
+
+        let message = match failures.as_slice() {
+            [(_, reason)] => reason.clone(),
+            _ => failures
+                .iter()
+                .map(|(kind, reason)| format!("{}: {}", kind.label(), reason))
+                .collect::<Vec<_>>()
+                .join("; "),
+        };
+        *lock_or_recover(&self.problem, "hotkey problem") = Some(message.clone());
+        Err(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fake {
+        kind: HotkeyBackendKind,
+        works: bool,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HotkeyBackend for Fake {
+        fn kind(&self) -> HotkeyBackendKind {
+            self.kind
+        }
+
+        fn register(&self, bindings: &[HotkeyBinding]) -> Result<(), String> {
+            if !self.works {
+                return Err("no luck".to_string());
+            }
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{:?} {}", self.kind, bindings.len()));
+            Ok(())
+        }
+
+        fn unregister_all(&self) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{:?} off", self.kind));
+        }
+    }
+
+    fn candidate(
+        kind: HotkeyBackendKind,
+        works: bool,
+        log: &Arc<Mutex<Vec<String>>>,
+    ) -> (HotkeyBackendKind, Connect) {
+        let log = log.clone();
+        (
+            kind,
+            Box::new(move || Ok(Box::new(Fake { kind, works, log }) as Box<dyn HotkeyBackend>)),
+        )
+    }
+
+    fn toggle() -> Vec<HotkeyBinding> {
+        vec![HotkeyBinding {
+            action: HotkeyAction::ToggleRecording,
+            accelerator: "Ctrl+Space".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_the_first_backend_that_registers_is_kept() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let hotkeys = Hotkeys::default();
+        let kind = hotkeys
+            .register(
+                &toggle(),
+                vec![
+                    candidate(HotkeyBackendKind::Plugin, false, &log),
+                    candidate(HotkeyBackendKind::Portal, true, &log),
+                    candidate(HotkeyBackendKind::Evdev, true, &log),
+                ],
+            )
+            .unwrap();
+        assert_eq!(kind, HotkeyBackendKind::Portal);
+        assert_eq!(hotkeys.backend(), Some(HotkeyBackendKind::Portal));
+        assert_eq!(hotkeys.problem(), None);
+
+        // Later registrations stay with it while it keeps working.
+        hotkeys
+            .register(
+                &toggle(),
+                vec![candidate(HotkeyBackendKind::Plugin, true, &log)],
+            )
+            .unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["Portal 1", "Portal 1"]);
+    }
+
+    #[test]
+    fn test_every_failure_is_reported_when_nothing_registers() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let hotkeys = Hotkeys::default();
+        let err = hotkeys
+            .register(
+                &toggle(),
+                vec![
+                    candidate(HotkeyBackendKind::Plugin, false, &log),
+                    (
+                        HotkeyBackendKind::Evdev,
+                        Box::new(|| Err("not allowed".to_string())),
+                    ),
+                ],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "global shortcut plugin: no luck; evdev listener: not allowed"
+        );
+        assert_eq!(hotkeys.problem().as_deref(), Some(err.as_str()));
+        assert_eq!(hotkeys.backend(), None);
+
+        // A single backend's error is passed on as it is.
+        let err = hotkeys
+            .register(
+                &toggle(),
+                vec![candidate(HotkeyBackendKind::Plugin, false, &log)],
+            )
+            .unwrap_err();
+        assert_eq!(err, "no luck");
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
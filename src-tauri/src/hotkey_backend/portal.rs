@@ -0,0 +1,172 @@
+//! Shortcuts through the desktop portal's GlobalShortcuts interface (GNOME 48+, KDE Plasma
+//! and other Wayland desktops). The portal lives on its own thread and runtime so
+//! registering never waits on D-Bus from an async command. Binding is asynchronous: the
+//! desktop may ask the user to confirm the keys or let them pick others, so a refusal is
+//! only logged.
+
+use super::chord::Chord;
+use super::{ActionSink, HotkeyBackend, HotkeyBackendKind};
+use crate::hotkeys::{HotkeyAction, HotkeyBinding};
+use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+use ashpd::desktop::Session;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How long to wait for the portal to answer before passing over it.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub struct PortalHotkeys {
+    bindings: mpsc::UnboundedSender<Vec<HotkeyBinding>>,
+}
+
+impl PortalHotkeys {
+    /// Connect to the portal; fails when the desktop has no GlobalShortcuts portal.
+    pub fn connect(sink: ActionSink) -> Result<Self, String> {
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        let (bindings, receiver) = mpsc::unbounded_channel();
+        std::thread::Builder::new()
+            .name("portal-hotkeys".to_string())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+                runtime.block_on(async move {
+                    let portal = match GlobalShortcuts::new().await {
+                        Ok(portal) => portal,
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e.to_string()));
+                            return;
+                        }
+                    };
+                    let activated = match portal.receive_activated().await {
+                        Ok(activated) => activated,
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e.to_string()));
+                            return;
+                        }
+                    };
+                    let _ = ready_tx.send(Ok(()));
+                    run(&portal, activated, receiver, sink).await;
+                });
+            })
+            .map_err(|e| format!("Failed to start the portal thread: {}", e))?;
+
+        match ready_rx.recv_timeout(CONNECT_TIMEOUT) {
+            Ok(Ok(())) => Ok(Self { bindings }),
+            Ok(Err(e)) => Err(format!("GlobalShortcuts portal unavailable ({})", e)),
+            Err(_) => Err("the desktop portal did not answer".to_string()),
+        }
+    }
+}
+
+impl HotkeyBackend for PortalHotkeys {
+    fn kind(&self) -> HotkeyBackendKind {
+        HotkeyBackendKind::Portal
+    }
+
+    fn register(&self, bindings: &[HotkeyBinding]) -> Result<(), String> {
+        for binding in bindings {
+            Chord::parse(&binding.accelerator)
+                .map_err(|e| format!("Invalid shortcut '{}': {}", binding.accelerator, e))?;
+        }
+        self.bindings
+            .send(bindings.to_vec())
+            .map_err(|_| "the portal thread stopped".to_string())
+    }
+
+    fn unregister_all(&self) {
+        let _ = self.bindings.send(Vec::new());
+    }
+}
+
+/// Bind each list of shortcuts that arrives in a new session, closing the previous one,
+/// and pass activations on to `sink`. Ends when the backend is dropped.
+async fn run(
+    portal: &GlobalShortcuts<'_>,
+    activated: impl futures_util::Stream<Item = ashpd::desktop::global_shortcuts::Activated>,
+    mut receiver: mpsc::UnboundedReceiver<Vec<HotkeyBinding>>,
+    sink: ActionSink,
+) {
+    let mut activated = std::pin::pin!(activated);
+    let mut session = None;
+    let mut actions: HashMap<&'static str, HotkeyAction> = HashMap::new();
+    loop {
+        tokio::select! {
+            bindings = receiver.recv() => {
+                let Some(bindings) = bindings else { break };
+                if let Some(previous) = session.take() {
+                    close(previous).await;
+                }
+                actions.clear();
+                if bindings.is_empty() {
+                    continue;
+                }
+                match bind(portal, &bindings).await {
+                    Ok(bound) => {
+                        session = Some(bound);
+                        actions = bindings
+                            .iter()
+                            .map(|binding| (binding.action.id(), binding.action))
+                            .collect();
+                    }
+                    Err(e) => {
+                        tracing::warn!("The desktop portal did not bind the shortcuts: {}", e);
+                    }
+                }
+            }
+            Some(event) = activated.next() => {
+                if let Some(action) = actions.get(event.shortcut_id()) {
+                    sink(*action);
+                }
+            }
+        }
+    }
+    if let Some(previous) = session {
+        close(previous).await;
+    }
+}
+
+async fn bind<'a>(
+    portal: &GlobalShortcuts<'a>,
+    bindings: &[HotkeyBinding],
+) -> Result<Session<'a, GlobalShortcuts<'a>>, String> {
+    let shortcuts = bindings
+        .iter()
+        .map(|binding| {
+            let trigger = Chord::parse(&binding.accelerator)?.portal_trigger();
+            Ok(
+                NewShortcut::new(binding.action.id(), binding.action.label())
+                    .preferred_trigger(trigger.as_str()),
+            )
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let session = portal.create_session().await.map_err(|e| e.to_string())?;
+    let bound = portal
+        .bind_shortcuts(&session, &shortcuts, None)
+        .await
+        .and_then(|request| request.response())
+        .map_err(|e| e.to_string())?;
+    for shortcut in bound.shortcuts() {
+        tracing::info!(
+            "Portal shortcut for {} is {}",
+            shortcut.description(),
+            shortcut.trigger_description()
+        );
+    }
+    Ok(session)
+}
+
+async fn close(session: Session<'_, GlobalShortcuts<'_>>) {
+    if let Err(e) = session.close().await {
+        tracing::debug!("Failed to close the portal shortcut session: {}", e);
+    }
+}
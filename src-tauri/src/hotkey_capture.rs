@@ -0,0 +1,586 @@
+//! Backend-assisted hotkey capture for setup. A low-level keyboard listener (a
+//! `WH_KEYBOARD_LL` hook on Windows, a listen-only event tap on macOS) runs on its own
+//! thread until the next modifier+key chord, then removes itself. Keys are only observed,
+//! never swallowed, and the listener gives up after [`CAPTURE_TIMEOUT`].
+
+use crate::locks::lock_or_recover;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the listener thread checks for cancellation and the deadline.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub control: bool,
+    pub shift: bool,
+    pub alt: bool,
+    /// Windows key / Command
+    pub meta: bool,
+}
+
+impl Modifiers {
+    fn any(self) -> bool {
+        self.control || self.shift || self.alt || self.meta
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureEnd {
+    /// Accelerator string, e.g. `CommandOrControl+Shift+Space`
+    Captured(String),
+    TimedOut,
+    /// Cancelled, superseded by a new capture, or Escape pressed on its own
+    Cancelled,
+}
+
+/// Accelerator for `key` held with `modifiers`, or `None` when no modifier is held. The
+/// platform's primary modifier (Ctrl, or Command on macOS) becomes `CommandOrControl`.
+pub fn accelerator(modifiers: Modifiers, key: &str) -> Option<String> {
+    if !modifiers.any() || key.is_empty() {
+        return None;
+    }
+    let (primary, secondary, secondary_name) = if cfg!(target_os = "macos") {
+        (modifiers.meta, modifiers.control, "Control")
+    } else {
+        (modifiers.control, modifiers.meta, "Super")
+    };
+
+    let mut parts = Vec::new();
+    if primary {
+        parts.push("CommandOrControl");
+    }
+    if secondary {
+        parts.push(secondary_name);
+    }
+    if modifiers.shift {
+        parts.push("Shift");
+    }
+    if modifiers.alt {
+        parts.push("Alt");
+    }
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+/// The running capture, if any. Only one listener exists at a time.
+#[derive(Default)]
+pub struct HotkeyCapture {
+    active: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl HotkeyCapture {
+    /// Install the listener and return once it is in place, or with the reason it could
+    /// not be. `on_end` runs on the listener thread after the listener is removed.
+    pub fn begin<F>(&self, on_end: F) -> Result<(), String>
+    where
+        F: FnOnce(CaptureEnd) + Send + 'static,
+    {
+        self.cancel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::sync_channel(1);
+        let deadline = Instant::now() + CAPTURE_TIMEOUT;
+
+        let flag = cancel.clone();
+        std::thread::Builder::new()
+            .name("hotkey-capture".to_string())
+            .spawn(move || on_end(platform::capture(&flag, deadline, ready_tx)))
+            .map_err(|e| format!("Failed to start hotkey capture: {}", e))?;
+        ready_rx
+            .recv()
+            .map_err(|_| "Hotkey capture stopped unexpectedly".to_string())??;
+
+        *lock_or_recover(&self.active, "hotkey capture") = Some(cancel);
+        Ok(())
+    }
+
+    /// Remove the listener without reporting anything. Safe to call when none is running.
+    pub fn cancel(&self) {
+        if let Some(cancel) = lock_or_recover(&self.active, "hotkey capture").take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn should_stop(cancel: &AtomicBool, deadline: Instant) -> Option<CaptureEnd> {
+    if cancel.load(Ordering::Relaxed) {
+        Some(CaptureEnd::Cancelled)
+    } else if Instant::now() >= deadline {
+        Some(CaptureEnd::TimedOut)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{accelerator, should_stop, CaptureEnd, Modifiers, POLL_INTERVAL};
+    use std::cell::RefCell;
+    use std::ptr::null_mut;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc::SyncSender;
+    use std::time::Instant;
+    use winapi::shared::minwindef::{LPARAM, LRESULT, WPARAM};
+    use winapi::um::winuser::{
+        CallNextHookEx, DispatchMessageW, GetMessageW, KillTimer, PostQuitMessage, SetTimer,
+        SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx, HC_ACTION, KBDLLHOOKSTRUCT, MSG,
+        WH_KEYBOARD_LL, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP,
+    };
+
+    const VK_ESCAPE: u32 = 0x1B;
+
+    #[derive(Default)]
+    struct HookState {
+        modifiers: Modifiers,
+        end: Option<CaptureEnd>,
+    }
+
+    thread_local! {
+        // The hook procedure runs on the thread that installed it.
+        static STATE: RefCell<HookState> = RefCell::new(HookState::default());
+    }
+
+    pub fn capture(
+        cancel: &AtomicBool,
+        deadline: Instant,
+        ready: SyncSender<Result<(), String>>,
+    ) -> CaptureEnd {
+        STATE.with(|state| *state.borrow_mut() = HookState::default());
+        let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_proc), null_mut(), 0) };
+        if hook.is_null() {
+            let _ = ready.send(Err("Could not install the keyboard hook".to_string()));
+            return CaptureEnd::Cancelled;
+        }
+        // Wakes GetMessageW so cancellation and the deadline are noticed.
+        let timer = unsafe { SetTimer(null_mut(), 0, POLL_INTERVAL.as_millis() as u32, None) };
+        let _ = ready.send(Ok(()));
+
+        let end = loop {
+            if let Some(end) = STATE.with(|state| state.borrow_mut().end.take()) {
+                break end;
+            }
+            if let Some(end) = should_stop(cancel, deadline) {
+                break end;
+            }
+            let mut msg: MSG = unsafe { std::mem::zeroed() };
+            if unsafe { GetMessageW(&mut msg, null_mut(), 0, 0) } <= 0 {
+                // WM_QUIT from the hook, or an error; the state says which.
+                break STATE
+                    .with(|state| state.borrow_mut().end.take())
+                    .unwrap_or(CaptureEnd::Cancelled);
+            }
+            unsafe {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        };
+
+        unsafe {
+            KillTimer(null_mut(), timer);
+            UnhookWindowsHookEx(hook);
+        }
+        end
+    }
+
+    unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION {
+            let info = &*(lparam as *const KBDLLHOOKSTRUCT);
+            let pressed = matches!(wparam as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+            let released = matches!(wparam as u32, WM_KEYUP | WM_SYSKEYUP);
+            if pressed || released {
+                STATE.with(|state| on_key(&mut state.borrow_mut(), info.vkCode, pressed));
+            }
+        }
+        // Always pass the key on: the chord is observed, not consumed.
+        CallNextHookEx(null_mut(), code, wparam, lparam)
+    }
+
+    fn on_key(state: &mut HookState, vk: u32, pressed: bool) {
+        if state.end.is_some() {
+            return;
+        }
+        if set_modifier(&mut state.modifiers, vk, pressed) || !pressed {
+            return;
+        }
+        let end = if vk == VK_ESCAPE && !state.modifiers.any() {
+            CaptureEnd::Cancelled
+        } else {
+            match key_name(vk).and_then(|key| accelerator(state.modifiers, &key)) {
+                Some(accelerator) => CaptureEnd::Captured(accelerator),
+                None => return,
+            }
+        };
+        state.end = Some(end);
+        unsafe { PostQuitMessage(0) };
+    }
+
+    /// Track modifier state; `true` when `vk` is a modifier key.
+    fn set_modifier(modifiers: &mut Modifiers, vk: u32, pressed: bool) -> bool {
+        let flag = match vk {
+            0x11 | 0xA2 | 0xA3 => &mut modifiers.control,
+            0x10 | 0xA0 | 0xA1 => &mut modifiers.shift,
+            0x12 | 0xA4 | 0xA5 => &mut modifiers.alt,
+            0x5B | 0x5C => &mut modifiers.meta,
+            _ => return false,
+        };
+        *flag = pressed;
+        true
+    }
+
+    /// Accelerator key name for a virtual-key code.
+    pub(super) fn key_name(vk: u32) -> Option<String> {
+        let name = match vk {
+            0x41..=0x5A | 0x30..=0x39 => return char::from_u32(vk).map(String::from),
+            0x60..=0x69 => return Some(format!("Numpad{}", vk - 0x60)),
+            0x70..=0x87 => return Some(format!("F{}", vk - 0x6F)),
+            0x20 => "Space",
+            0x0D => "Enter",
+            0x09 => "Tab",
+            0x08 => "Backspace",
+            0x1B => "Esc",
+            0x2D => "Insert",
+            0x2E => "Delete",
+            0x24 => "Home",
+            0x23 => "End",
+            0x21 => "PageUp",
+            0x22 => "PageDown",
+            0x25 => "ArrowLeft",
+            0x26 => "ArrowUp",
+            0x27 => "ArrowRight",
+            0x28 => "ArrowDown",
+            0xBA => "Semicolon",
+            0xBB => "Equal",
+            0xBC => "Comma",
+            0xBD => "Minus",
+            0xBE => "Period",
+            0xBF => "Slash",
+            0xC0 => "Backquote",
+            0xDB => "BracketLeft",
+            0xDC => "Backslash",
+            0xDD => "BracketRight",
+            0xDE => "Quote",
+            _ => return None,
+        };
+        Some(name.to_string())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn chord(keys: &[u32]) -> Option<CaptureEnd> {
+            let mut state = HookState::default();
+            for &vk in keys {
+                on_key(&mut state, vk, true);
+            }
+            state.end
+        }
+
+        #[test]
+        fn test_virtual_keys_map_to_accelerators() {
+            let cases: &[(&[u32], Option<&str>)] = &[
+                (&[0xA2, 0xA0, 0x20], Some("CommandOrControl+Shift+Space")),
+                (&[0x11, 0x12, 0x4B], Some("CommandOrControl+Alt+K")),
+                (&[0x5B, 0x35], Some("Super+5")),
+                (&[0xA3, 0x78], Some("CommandOrControl+F9")),
+                (&[0xA4, 0xBD], Some("Alt+Minus")),
+                (&[0xA1, 0x26], Some("Shift+ArrowUp")),
+                (&[0x11, 0x63], Some("CommandOrControl+Numpad3")),
+                // No modifier, or only modifiers: nothing to report yet.
+                (&[0x20], None),
+                (&[0xA2, 0xA0], None),
+                // Unmapped keys are ignored.
+                (&[0x11, 0xFF], None),
+            ];
+            assert_eq!(chord(&[0x1B]), Some(CaptureEnd::Cancelled));
+            for (keys, expected) in cases {
+                assert_eq!(
+                    chord(keys),
+                    expected.map(|a| CaptureEnd::Captured(a.to_string())),
+                    "{:x?}",
+                    keys
+                );
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{accelerator, should_stop, CaptureEnd, Modifiers, POLL_INTERVAL};
+    use core_foundation::runloop::{kCFRunLoopCommonModes, kCFRunLoopDefaultMode, CFRunLoop};
+    use core_graphics::event::{
+        CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+        CGEventType, EventField,
+    };
+    use std::cell::RefCell;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc::SyncSender;
+    use std::time::Instant;
+
+    const KEY_ESCAPE: u16 = 0x35;
+
+    pub fn capture(
+        cancel: &AtomicBool,
+        deadline: Instant,
+        ready: SyncSender<Result<(), String>>,
+    ) -> CaptureEnd {
+        if !crate::paste::accessibility_granted(false) {
+            let _ = ready.send(Err(
+                "Allow Zentra under Privacy & Security > Accessibility to capture shortcuts"
+                    .to_string(),
+            ));
+            return CaptureEnd::Cancelled;
+        }
+
+        let captured: RefCell<Option<CaptureEnd>> = RefCell::new(None);
+        // Listen-only: the tap sees key events but cannot hold them back.
+        let tap = CGEventTap::new(
+            CGEventTapLocation::Session,
+            CGEventTapPlacement::HeadInsertEventTap,
+            CGEventTapOptions::ListenOnly,
+            vec![CGEventType::KeyDown],
+            |_proxy, _kind, event| {
+                let autorepeat =
+                    event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT);
+                if captured.borrow().is_none() && autorepeat == 0 {
+                    let keycode =
+                        event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as u16;
+                    *captured.borrow_mut() = on_key_down(keycode, modifiers(event.get_flags()));
+                }
+                None
+            },
+        );
+        let tap = match tap {
+            Ok(tap) => tap,
+            Err(()) => {
+                let _ = ready.send(Err("Could not create the keyboard event tap".to_string()));
+                return CaptureEnd::Cancelled;
+            }
+        };
+        let source = match tap.mach_port.create_runloop_source(0) {
+            Ok(source) => source,
+            Err(()) => {
+                let _ = ready.send(Err("Could not attach the keyboard event tap".to_string()));
+                return CaptureEnd::Cancelled;
+            }
+        };
+        let run_loop = CFRunLoop::get_current();
+        unsafe { run_loop.add_source(&source, kCFRunLoopCommonModes) };
+        tap.enable();
+        let _ = ready.send(Ok(()));
+
+        let end = loop {
+            if let Some(end) = captured.borrow_mut().take() {
+                break end;
+            }
+            if let Some(end) = should_stop(cancel, deadline) {
+                break end;
+            }
+            CFRunLoop::run_in_mode(unsafe { kCFRunLoopDefaultMode }, POLL_INTERVAL, true);
+        };
+
+        unsafe { run_loop.remove_source(&source, kCFRunLoopCommonModes) };
+        end
+    }
+
+    fn modifiers(flags: CGEventFlags) -> Modifiers {
+        Modifiers {
+            control: flags.contains(CGEventFlags::CGEventFlagControl),
+            shift: flags.contains(CGEventFlags::CGEventFlagShift),
+            alt: flags.contains(CGEventFlags::CGEventFlagAlternate),
+            meta: flags.contains(CGEventFlags::CGEventFlagCommand),
+        }
+    }
+
+    fn on_key_down(keycode: u16, modifiers: Modifiers) -> Option<CaptureEnd> {
+        if keycode == KEY_ESCAPE && modifiers == Modifiers::default() {
+            return Some(CaptureEnd::Cancelled);
+        }
+        key_name(keycode)
+            .and_then(|key| accelerator(modifiers, key))
+            .map(CaptureEnd::Captured)
+    }
+
+    /// Accelerator key name for a virtual keycode (`kVK_*`, US layout positions).
+    pub(super) fn key_name(keycode: u16) -> Option<&'static str> {
+        Some(match keycode {
+            0x00 => "A",
+            0x0B => "B",
+            0x08 => "C",
+            0x02 => "D",
+            0x0E => "E",
+            0x03 => "F",
+            0x05 => "G",
+            0x04 => "H",
+            0x22 => "I",
+            0x26 => "J",
+            0x28 => "K",
+            0x25 => "L",
+            0x2E => "M",
+            0x2D => "N",
+            0x1F => "O",
+            0x23 => "P",
+            0x0C => "Q",
+            0x0F => "R",
+            0x01 => "S",
+            0x11 => "T",
+            0x20 => "U",
+            0x09 => "V",
+            0x0D => "W",
+            0x07 => "X",
+            0x10 => "Y",
+            0x06 => "Z",
+            0x1D => "0",
+            0x12 => "1",
+            0x13 => "2",
+            0x14 => "3",
+            0x15 => "4",
+            0x17 => "5",
+            0x16 => "6",
+            0x1A => "7",
+            0x1C => "8",
+            0x19 => "9",
+            0x31 => "Space",
+            0x24 => "Enter",
+            0x30 => "Tab",
+            0x33 => "Backspace",
+            0x75 => "Delete",
+            0x35 => "Esc",
+            0x73 => "Home",
+            0x77 => "End",
+            0x74 => "PageUp",
+            0x79 => "PageDown",
+            0x7B => "ArrowLeft",
+            0x7C => "ArrowRight",
+            0x7D => "ArrowDown",
+            0x7E => "ArrowUp",
+            0x7A => "F1",
+            0x78 => "F2",
+            0x63 => "F3",
+            0x76 => "F4",
+            0x60 => "F5",
+            0x61 => "F6",
+            0x62 => "F7",
+            0x64 => "F8",
+            0x65 => "F9",
+            0x6D => "F10",
+            0x67 => "F11",
+            0x6F => "F12",
+            0x1B => "Minus",
+            0x18 => "Equal",
+            0x21 => "BracketLeft",
+            0x1E => "BracketRight",
+            0x2A => "Backslash",
+            0x29 => "Semicolon",
+            0x27 => "Quote",
+            0x2B => "Comma",
+            0x2F => "Period",
+            0x2C => "Slash",
+            0x32 => "Backquote",
+            _ => return None,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const CMD: Modifiers = Modifiers {
+            control: false,
+            shift: false,
+            alt: false,
+            meta: true,
+        };
+
+        #[test]
+        fn test_keycodes_map_to_accelerators() {
+            let cmd_shift = Modifiers { shift: true, ..CMD };
+            let ctrl_alt = Modifiers {
+                control: true,
+                alt: true,
+                ..Modifiers::default()
+            };
+            let cases = [
+                (0x31, cmd_shift, Some("CommandOrControl+Shift+Space")),
+                (0x28, CMD, Some("CommandOrControl+K")),
+                (0x17, ctrl_alt, Some("Control+Alt+5")),
+                (0x7E, cmd_shift, Some("CommandOrControl+Shift+ArrowUp")),
+                (0x65, CMD, Some("CommandOrControl+F9")),
+                (0x31, Modifiers::default(), None),
+                (0x7F, CMD, None),
+            ];
+            for (keycode, modifiers, expected) in cases {
+                assert_eq!(
+                    on_key_down(keycode, modifiers),
+                    expected.map(|a| CaptureEnd::Captured(a.to_string())),
+                    "{:#x}",
+                    keycode
+                );
+            }
+            assert_eq!(
+                on_key_down(KEY_ESCAPE, Modifiers::default()),
+                Some(CaptureEnd::Cancelled)
+            );
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::CaptureEnd;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc::SyncSender;
+    use std::time::Instant;
+
+    pub fn capture(
+        _cancel: &AtomicBool,
+        _deadline: Instant,
+        ready: SyncSender<Result<(), String>>,
+    ) -> CaptureEnd {
+        let _ = ready.send(Err(
+            "Hotkey capture is not available on this platform; press the shortcut in the window"
+                .to_string(),
+        ));
+        CaptureEnd::Cancelled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accelerator_needs_a_modifier_and_orders_them() {
+        let all = Modifiers {
+            control: true,
+            shift: true,
+            alt: true,
+            meta: true,
+        };
+        assert_eq!(accelerator(Modifiers::default(), "Space"), None);
+        assert_eq!(accelerator(all, ""), None);
+        let expected = if cfg!(target_os = "macos") {
+            "CommandOrControl+Control+Shift+Alt+Space"
+        } else {
+            "CommandOrControl+Super+Shift+Alt+Space"
+        };
+        assert_eq!(accelerator(all, "Space").as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_cancelled_capture_stops_before_the_deadline() {
+        let cancel = AtomicBool::new(false);
+        let later = Instant::now() + CAPTURE_TIMEOUT;
+        assert_eq!(should_stop(&cancel, later), None);
+        assert_eq!(
+            should_stop(&cancel, Instant::now()),
+            Some(CaptureEnd::TimedOut)
+        );
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(should_stop(&cancel, later), Some(CaptureEnd::Cancelled));
+    }
+}
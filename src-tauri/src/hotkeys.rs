@@ -0,0 +1,151 @@
+//! Global shortcuts beyond the recording toggle: an optional dedicated stop key and a key
+//! that pastes the last transcript again. The toggle keeps living in `AppConfig.hotkey`;
+//! `AppConfig.hotkeys` holds the others.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyAction {
+    ToggleRecording,
+    StopRecording,
+    PasteLast,
+}
+
+impl HotkeyAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            HotkeyAction::ToggleRecording => "start/stop recording",
+            HotkeyAction::StopRecording => "stop recording",
+            HotkeyAction::PasteLast => "paste last transcript",
+        }
+    }
+
+    /// Stable name, as in `AppConfig.hotkeys`.
+    pub fn id(self) -> &'static str {
+        match self {
+            HotkeyAction::ToggleRecording => "toggleRecording",
+            HotkeyAction::StopRecording => "stopRecording",
+            HotkeyAction::PasteLast => "pasteLast",
+        }
+    }
+}
+
+/// Extra shortcuts by action; an action without an entry has no shortcut.
+pub type HotkeyMap = BTreeMap<HotkeyAction, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    pub accelerator: String,
+}
+
+/// Trim the accelerators and drop empty ones. A `toggleRecording` entry is dropped too;
+/// that key is `hotkey`.
+pub fn normalize(map: HotkeyMap) -> HotkeyMap {
+    map.into_iter()
+        .filter(|(action, _)| *action != HotkeyAction::ToggleRecording)
+        .map(|(action, accelerator)| (action, accelerator.trim().to_string()))
+        .filter(|(_, accelerator)| !accelerator.is_empty())
+        .collect()
+}
+
+/// Every shortcut to register, the toggle first. Two actions on the same key combination
+/// are refused, however the modifiers are spelled or ordered.
+pub fn bindings(toggle: &str, extra: &HotkeyMap) -> Result<Vec<HotkeyBinding>, String> {
+    let mut bindings = vec![HotkeyBinding {
+        action: HotkeyAction::ToggleRecording,
+        accelerator: toggle.trim().to_string(),
+    }];
+    for (action, accelerator) in normalize(extra.clone()) {
+        let key = canonical(&accelerator);
+        if let Some(taken) = bindings.iter().find(|b| canonical(&b.accelerator) == key) {
+            return Err(format!(
+                "'{}' is already the {} shortcut; pick another key for {}",
+                accelerator,
+                taken.action.label(),
+                action.label()
+            ));
+        }
+        bindings.push(HotkeyBinding {
+            action,
+            accelerator,
+        });
+    }
+    Ok(bindings)
+}
+
+/// Lowercased, with modifier aliases unified and sorted, so `Shift+Ctrl+V` and
+/// `control+shift+v` compare equal.
+fn canonical(accelerator: &str) -> String {
+    let mut parts: Vec<String> = accelerator
+        .split('+')
+        .map(|part| part.trim().to_ascii_lowercase())
+        .filter(|part| !part.is_empty())
+        .collect();
+    let key = parts.pop().unwrap_or_default();
+    let mut modifiers: Vec<&str> = parts
+        .iter()
+        .map(|part| match part.as_str() {
+            "control" => "ctrl",
+            "option" => "alt",
+            "command" | "cmd" | "meta" => "super",
+            "commandorcontrol" | "commandorctrl" | "cmdorcontrol" => "cmdorctrl",
+            other => other,
+        })
+        .collect();
+    modifiers.sort_unstable();
+    modifiers.dedup();
+    modifiers.push(&key);
+    modifiers.join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_list_the_toggle_then_the_extra_keys() {
+        let extra = normalize(HotkeyMap::from([
+            (HotkeyAction::PasteLast, " Ctrl+Shift+V ".to_string()),
+            (HotkeyAction::StopRecording, "".to_string()),
+            (HotkeyAction::ToggleRecording, "Ctrl+T".to_string()),
+        ]));
+        assert_eq!(extra.len(), 1);
+
+        let bindings = bindings("Ctrl+Space", &extra).unwrap();
+        assert_eq!(
+            bindings,
+            vec![
+                HotkeyBinding {
+                    action: HotkeyAction::ToggleRecording,
+                    accelerator: "Ctrl+Space".to_string(),
+                },
+                HotkeyBinding {
+                    action: HotkeyAction::PasteLast,
+                    accelerator: "Ctrl+Shift+V".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_the_same_key_bound_twice_is_refused() {
+        let extra = HotkeyMap::from([(HotkeyAction::StopRecording, "control+SPACE".to_string())]);
+        let err = bindings("Ctrl+Space", &extra).unwrap_err();
+        assert!(err.contains("start/stop recording"), "{}", err);
+        assert!(err.contains("stop recording"), "{}", err);
+
+        let extra = HotkeyMap::from([
+            (HotkeyAction::StopRecording, "Alt+Shift+S".to_string()),
+            (HotkeyAction::PasteLast, "Shift+Option+S".to_string()),
+        ]);
+        let err = bindings("Ctrl+Space", &extra).unwrap_err();
+        assert!(err.contains("paste last transcript"), "{}", err);
+
+        let extra =
+            HotkeyMap::from([(HotkeyAction::StopRecording, "Ctrl+Shift+Space".to_string())]);
+        assert_eq!(bindings("Ctrl+Space", &extra).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,543 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const TEMPLATE_VARIABLES: &[&str] = &[
+    "text",
+    "date",
+    "time",
+    "timestamp",
+    "language",
+    "duration",
+    "words",
+];
+
+const SAMPLE_TRANSCRIPT: &str =
+    "Remind me to send the \"Q3 report\" to the team before Friday's meeting.";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NotesSinkSettings {
+    pub enabled: bool,
+    pub folder: String,
+    pub filename_template: String,
+    pub entry_template: String,
+}
+
+impl Default for NotesSinkSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: String::new(),
+            filename_template: "{date}.md".to_string(),
+            entry_template: "- {time} {text}\n".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WebhookSettings {
+    pub enabled: bool,
+    pub url: String,
+    pub payload_template: String,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            payload_template: "{{\"text\": \"{text}\", \"timestamp\": \"{timestamp}\"}}"
+                .to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IntegrationSettings {
+    pub notes_sink: NotesSinkSettings,
+    pub webhook: WebhookSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrationKind {
+    NotesSink,
+    Webhook,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationIssue {
+    pub kind: IntegrationKind,
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationPreview {
+    pub kind: IntegrationKind,
+    /// File path for the notes sink, URL for the webhook
+    pub target: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Variable(String),
+}
+
+/// `{name}` inserts a variable; `{{` and `}}` produce literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '{' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|(_, c)| *c) == Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(format!(
+                            "Unclosed '{{' at position {}. Use '{{{{' for a literal brace.",
+                            index
+                        ));
+                    }
+                    let name = name.trim().to_string();
+                    if !TEMPLATE_VARIABLES.contains(&name.as_str()) {
+                        return Err(format!(
+                            "Unknown variable '{{{}}}'. Available: {}",
+                            name,
+                            TEMPLATE_VARIABLES
+                                .iter()
+                                .map(|v| format!("{{{}}}", v))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Variable(name));
+                }
+                '}' => {
+                    return Err(format!(
+                        "Unmatched '}}' at position {}. Use '}}}}' for a literal brace.",
+                        index
+                    ));
+                }
+                _ => literal.push(ch),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn render(&self, context: &TemplateContext, escape: fn(&str) -> String) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => text.clone(),
+                Part::Variable(name) => escape(&context.value(name)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub text: String,
+    pub language: String,
+    pub duration_seconds: f32,
+    pub recorded_at: DateTime<Local>,
+}
+
+impl TemplateContext {
+    pub fn sample(language: &str) -> Self {
+        Self {
+            text: SAMPLE_TRANSCRIPT.to_string(),
+            language: language.to_string(),
+            duration_seconds: 6.4,
+            recorded_at: Local::now(),
+        }
+    }
+
+    fn value(&self, name: &str) -> String {
+        match name {
+            "text" => self.text.clone(),
+            "date" => self.recorded_at.format("%Y-%m-%d").to_string(),
+            "time" => self.recorded_at.format("%H:%M").to_string(),
+            "timestamp" => self.recorded_at.to_rfc3339(),
+            "language" => self.language.clone(),
+            "duration" => format!("{:.1}", self.duration_seconds),
+            "words" => self.text.split_whitespace().count().to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+fn raw(value: &str) -> String {
+    value.to_string()
+}
+
+/// Webhook payloads are JSON; substituted values must not break out of their string.
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Check the folder exists and that a file can actually be created in it.
+pub fn check_folder_writable(folder: &Path) -> Result<(), String> {
+    if !folder.is_dir() {
+        return Err(format!("Folder {} does not exist", folder.display()));
+    }
+    let probe = folder.join(format!(".zentra-probe-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Folder {} is not writable: {}", folder.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Scheme/host check only; DNS resolution happens in [`resolve_webhook_host`].
+pub fn parse_webhook_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed =
+        reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Webhook URL must use http or https, got '{}'",
+            parsed.scheme()
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err("Webhook URL has no host".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Resolve the webhook host without sending anything.
+pub async fn resolve_webhook_host(url: &reqwest::Url) -> Result<(), String> {
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let mut addrs = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Could not resolve webhook host '{}': {}", host, e))?;
+    if addrs.next().is_none() {
+        return Err(format!("Webhook host '{}' has no addresses", host));
+    }
+    Ok(())
+}
+
+/// POST the rendered payload to the webhook. Any non-2xx response is an error.
+pub async fn send_webhook(
+    settings: &WebhookSettings,
+    context: &TemplateContext,
+) -> Result<(), String> {
+    if !settings.enabled {
+        return Err("Webhook is not enabled".to_string());
+    }
+    let url = parse_webhook_url(&settings.url)?;
+    let body = Template::parse(&settings.payload_template)?.render(context, json_escape);
+    let response = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .timeout(std::time::Duration::from_secs(10))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook answered {}", response.status()));
+    }
+    Ok(())
+}
+
+fn notes_target(
+    settings: &NotesSinkSettings,
+    context: &TemplateContext,
+) -> Result<PathBuf, String> {
+    let filename = Template::parse(&settings.filename_template)?.render(context, raw);
+    let filename = filename.trim();
+    if filename.is_empty() {
+        return Err("Filename template renders to an empty name".to_string());
+    }
+    if filename.contains(['/', '\\']) {
+        return Err(format!(
+            "Filename '{}' must not contain path separators",
+            filename
+        ));
+    }
+    Ok(Path::new(settings.folder.trim()).join(filename))
+}
+
+pub fn preview(
+    settings: &IntegrationSettings,
+    kind: IntegrationKind,
+    context: &TemplateContext,
+) -> Result<IntegrationPreview, String> {
+    match kind {
+        IntegrationKind::NotesSink => {
+            let sink = &settings.notes_sink;
+            let target = notes_target(sink, context)?;
+            let body = Template::parse(&sink.entry_template)?.render(context, raw);
+            Ok(IntegrationPreview {
+                kind,
+                target: target.display().to_string(),
+                body,
+            })
+        }
+        IntegrationKind::Webhook => {
+            let webhook = &settings.webhook;
+            let body = Template::parse(&webhook.payload_template)?.render(context, json_escape);
+            Ok(IntegrationPreview {
+                kind,
+                target: webhook.url.trim().to_string(),
+                body,
+            })
+        }
+    }
+}
+
+/// Everything that can be checked without network or disk side effects.
+pub fn validate_offline(settings: &IntegrationSettings) -> Vec<IntegrationIssue> {
+    let mut issues = Vec::new();
+    let context = TemplateContext::sample("en");
+    let mut issue = |kind, field: &str, message: String| {
+        issues.push(IntegrationIssue {
+            kind,
+            field: field.to_string(),
+            message,
+        })
+    };
+
+    let sink = &settings.notes_sink;
+    if sink.enabled {
+        if sink.folder.trim().is_empty() {
+            issue(
+                IntegrationKind::NotesSink,
+                "folder",
+                "Choose a folder for the notes file".to_string(),
+            );
+        }
+        if let Err(e) = notes_target(sink, &context) {
+            issue(IntegrationKind::NotesSink, "filenameTemplate", e);
+        }
+        if let Err(e) = Template::parse(&sink.entry_template) {
+            issue(IntegrationKind::NotesSink, "entryTemplate", e);
+        }
+    }
+
+    let webhook = &settings.webhook;
+    if webhook.enabled {
+        if let Err(e) = parse_webhook_url(&webhook.url) {
+            issue(IntegrationKind::Webhook, "url", e);
+        }
+        match Template::parse(&webhook.payload_template) {
+            Ok(template) => {
+                let body = template.render(&context, json_escape);
+                if let Err(e) = serde_json::from_str::<serde_json::Value>(&body) {
+                    issue(
+                        IntegrationKind::Webhook,
+                        "payloadTemplate",
+                        format!("Payload is not valid JSON once rendered: {}", e),
+                    );
+                }
+            }
+            Err(e) => issue(IntegrationKind::Webhook, "payloadTemplate", e),
+        }
+    }
+
+    issues
+}
+
+/// Full validation: templates, folder writability probe and webhook DNS lookup.
+pub async fn validate(settings: &IntegrationSettings) -> Vec<IntegrationIssue> {
+    let mut issues = validate_local(settings);
+    issues.extend(check_webhook_host(settings).await);
+    issues
+}
+
+/// Issues that keep `settings` from replacing `saved`: none when they are unchanged. A
+/// webhook host that doesn't resolve is only logged, since that is also what being offline
+/// looks like.
+pub async fn validate_update(
+    settings: &IntegrationSettings,
+    saved: &IntegrationSettings,
+) -> Vec<IntegrationIssue> {
+    if settings == saved {
+        return Vec::new();
+    }
+    if settings.webhook != saved.webhook {
+        if let Some(issue) = check_webhook_host(settings).await {
+            tracing::warn!("Saving integration settings anyway: {}", issue.message);
+        }
+    }
+    validate_local(settings)
+}
+
+/// Templates and the folder writability probe; nothing goes over the network.
+fn validate_local(settings: &IntegrationSettings) -> Vec<IntegrationIssue> {
+    let mut issues = validate_offline(settings);
+    let sink = &settings.notes_sink;
+    if sink.enabled && !sink.folder.trim().is_empty() {
+        if let Err(message) = check_folder_writable(Path::new(sink.folder.trim())) {
+            issues.push(IntegrationIssue {
+                kind: IntegrationKind::NotesSink,
+                field: "folder".to_string(),
+                message,
+            });
+        }
+    }
+    issues
+}
+
+/// The enabled webhook's DNS lookup.
+async fn check_webhook_host(settings: &IntegrationSettings) -> Option<IntegrationIssue> {
+    let webhook = &settings.webhook;
+    if !webhook.enabled {
+        return None;
+    }
+    let url = parse_webhook_url(&webhook.url).ok()?;
+    let message = resolve_webhook_host(&url).await.err()?;
+    Some(IntegrationIssue {
+        kind: IntegrationKind::Webhook,
+        field: "url".to_string(),
+        message,
+    })
+}
+
+pub fn issues_to_error(issues: &[IntegrationIssue]) -> Result<(), String> {
+    if issues.is_empty() {
+        return Ok(());
+    }
+    Err(issues
+        .iter()
+        .map(|issue| issue.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext::sample("en")
+    }
+
+    #[test]
+    fn test_unknown_variable_is_rejected() {
+        let err = Template::parse("{date}-{title}.md").unwrap_err();
+        assert!(err.contains("Unknown variable '{title}'"), "{}", err);
+        assert!(err.contains("{date}"));
+    }
+
+    #[test]
+    fn test_double_braces_are_literals() {
+        let template = Template::parse("{{{text}}} {{not a var}}").unwrap();
+        let mut ctx = context();
+        ctx.text = "hi".to_string();
+        assert_eq!(template.render(&ctx, raw), "{hi} {not a var}");
+    }
+
+    #[test]
+    fn test_unbalanced_braces_are_rejected() {
+        assert!(Template::parse("{text").unwrap_err().contains("Unclosed"));
+        assert!(Template::parse("text}").unwrap_err().contains("Unmatched"));
+    }
+
+    #[test]
+    fn test_webhook_payload_escapes_values() {
+        let settings = IntegrationSettings::default();
+        let preview = preview(&settings, IntegrationKind::Webhook, &context()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&preview.body).unwrap();
+        assert_eq!(value["text"], SAMPLE_TRANSCRIPT);
+    }
+
+    #[test]
+    fn test_offline_validation_reports_actionable_fields() {
+        let mut settings = IntegrationSettings::default();
+        settings.notes_sink.enabled = true;
+        settings.notes_sink.filename_template = "notes/{date}.md".to_string();
+        settings.webhook.enabled = true;
+        settings.webhook.url = "ftp://example.com/hook".to_string();
+        settings.webhook.payload_template = "{text}".to_string();
+
+        let fields: Vec<_> = validate_offline(&settings)
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["folder", "filenameTemplate", "url", "payloadTemplate"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_saving_only_validates_changed_settings() {
+        let mut saved = IntegrationSettings::default();
+        saved.webhook.enabled = true;
+        saved.webhook.url = "https://hooks.zentra.invalid/notes".to_string();
+        saved.webhook.payload_template = "{text}".to_string();
+        assert!(validate_update(&saved, &saved).await.is_empty());
+
+        // The host doesn't resolve, which alone never blocks a save.
+        let mut settings = saved.clone();
+        settings.webhook.payload_template = WebhookSettings::default().payload_template;
+        assert!(validate_update(&settings, &saved).await.is_empty());
+
+        settings.notes_sink.enabled = true;
+        let fields: Vec<_> = validate_update(&settings, &saved)
+            .await
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect();
+        assert_eq!(fields, vec!["folder"]);
+    }
+
+    #[test]
+    fn test_notes_preview_resolves_file_path() {
+        let dir = std::env::temp_dir().join(format!("zentra-notes-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut settings = IntegrationSettings::default();
+        settings.notes_sink.folder = dir.display().to_string();
+
+        let ctx = context();
+        let preview = preview(&settings, IntegrationKind::NotesSink, &ctx).unwrap();
+        let expected = dir.join(format!("{}.md", ctx.recorded_at.format("%Y-%m-%d")));
+        assert_eq!(preview.target, expected.display().to_string());
+        assert!(preview.body.ends_with(&format!("{}\n", SAMPLE_TRANSCRIPT)));
+
+        assert!(check_folder_writable(&dir).is_ok());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(check_folder_writable(&dir.join("missing")).is_err());
+    }
+}
@@ -0,0 +1,716 @@
+//! Every event the backend emits: its name, its payload type and the windows it reaches.
+//! Emit through [`emit_event`] so a name can never be sent with the wrong payload.
+
+use crate::audio::{CaptureInfo, DeviceChange};
+use crate::config::StartupHealth;
+use crate::history_updates::{ChangeSummary, HistoryNotifier};
+use crate::presets::ActivePreset;
+use crate::suggestions::LanguageSuggestion;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use zentra_core::error_codes::ErrorCode;
+use zentra_core::session::{ProgressSink, SegmentProgress, SessionProgress};
+use zentra_core::stt::{TranscriptionProgress, TranscriptionProgressSink};
+
+pub const TOGGLE_RECORDING: &str = "toggle-recording";
+pub const STOP_RECORDING: &str = "stop-recording";
+pub const AUDIO_LEVEL: &str = "audio-level";
+pub const CAPTURE_STARTED: &str = "capture-started";
+pub const MIC_POSSIBLY_EXCLUSIVE: &str = "mic-possibly-exclusive";
+pub const RECORDING_ERROR: &str = "recording-error";
+pub const INPUT_DEVICES_CHANGED: &str = "input-devices-changed";
+pub const DEVICE_CHANGED: &str = "device-changed";
+pub const TRANSCRIPT_PARTIAL: &str = "transcript-partial";
+pub const RECORDING_TOO_SHORT: &str = "recording-too-short";
+pub const RECORDING_AUTO_STOPPED: &str = "recording-auto-stopped";
+pub const WHISPER_PROGRESS: &str = "whisper-progress";
+pub const SESSION_PAUSED: &str = "session-paused";
+pub const SESSION_RESUMED: &str = "session-resumed";
+pub const SESSION_SEGMENT_STATUS: &str = "session:segment-status";
+pub const SESSION_SEGMENT_CUT: &str = "session:segment-cut";
+pub const PRIVACY_MODE_CHANGED: &str = "privacy-mode-changed";
+pub const PRESET_CHANGED: &str = "preset-changed";
+pub const SETTINGS_CHANGED: &str = "settings-changed";
+pub const STARTUP_HEALTH: &str = "startup-health";
+pub const ENV_IMPORTED: &str = "env-imported";
+pub const API_KEY_INVALID: &str = "api-key-invalid";
+pub const LANGUAGE_MISMATCH_SUGGESTION: &str = "language-mismatch-suggestion";
+pub const PROVIDER_CIRCUIT_OPENED: &str = "provider-circuit-opened";
+pub const PROVIDER_CIRCUIT_CLOSED: &str = "provider-circuit-closed";
+pub const DASHBOARD_REFRESH: &str = "dashboard:refresh";
+pub const DASHBOARD_NAVIGATE: &str = "dashboard:navigate";
+pub const DASHBOARD_HISTORY_UPDATED: &str = "dashboard:history-updated";
+pub const HISTORY_RETRANSCRIBE_PROGRESS: &str = "history:retranscribe-progress";
+pub const PENDING_TRANSCRIPTION_COMPLETED: &str = "pending-transcription-completed";
+pub const HOTKEY_CAPTURED: &str = "hotkey-captured";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowTarget {
+    Pill,
+    Dashboard,
+    Setup,
+    All,
+}
+
+impl WindowTarget {
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            WindowTarget::Pill => Some("main"),
+            WindowTarget::Dashboard => Some("dashboard"),
+            WindowTarget::Setup => Some("setup"),
+            WindowTarget::All => None,
+        }
+    }
+}
+
+/// An event and its payload. Serializes as the bare payload; unit variants send `null`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Event {
+    ToggleRecording,
+    /// The dedicated stop shortcut; ignored unless a recording is running
+    StopRecording,
+    AudioLevel(AudioLevel),
+    CaptureStarted(CaptureInfo),
+    MicPossiblyExclusive(MicPossiblyExclusive),
+    RecordingError(RecordingError),
+    InputDevicesChanged(DeviceChange),
+    DeviceChanged(DeviceChanged),
+    TranscriptPartial(TranscriptPartial),
+    RecordingTooShort(RecordingTooShort),
+    RecordingAutoStopped(RecordingAutoStopped),
+    /// A local engine working through a recording
+    WhisperProgress(TranscriptionProgress),
+    SessionPaused(SessionProgress),
+    SessionResumed(SessionProgress),
+    SessionSegmentStatus(SegmentProgress),
+    SessionSegmentCut(SegmentCut),
+    PrivacyModeChanged(PrivacyModeChanged),
+    PresetChanged(PresetChanged),
+    SettingsChanged(SettingsChanged),
+    StartupHealth(StartupHealth),
+    EnvImported(EnvImported),
+    ApiKeyInvalid(ApiKeyInvalid),
+    LanguageMismatchSuggestion(LanguageSuggestion),
+    ProviderCircuitOpened(ProviderCircuitOpened),
+    ProviderCircuitClosed(ProviderCircuitClosed),
+    DashboardRefresh,
+    DashboardNavigate(DashboardNavigate),
+    DashboardHistoryUpdated(HistoryUpdated),
+    HistoryRetranscribeProgress(RetranscribeProgress),
+    PendingTranscriptionCompleted(PendingTranscriptionCompleted),
+    HotkeyCaptured(HotkeyCaptured),
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::ToggleRecording => TOGGLE_RECORDING,
+            Event::StopRecording => STOP_RECORDING,
+            Event::AudioLevel(_) => AUDIO_LEVEL,
+            Event::CaptureStarted(_) => CAPTURE_STARTED,
+            Event::MicPossiblyExclusive(_) => MIC_POSSIBLY_EXCLUSIVE,
+            Event::RecordingError(_) => RECORDING_ERROR,
+            Event::InputDevicesChanged(_) => INPUT_DEVICES_CHANGED,
+            Event::DeviceChanged(_) => DEVICE_CHANGED,
+            Event::TranscriptPartial(_) => TRANSCRIPT_PARTIAL,
+            Event::RecordingTooShort(_) => RECORDING_TOO_SHORT,
+            Event::RecordingAutoStopped(_) => RECORDING_AUTO_STOPPED,
+            Event::WhisperProgress(_) => WHISPER_PROGRESS,
+            Event::SessionPaused(_) => SESSION_PAUSED,
+            Event::SessionResumed(_) => SESSION_RESUMED,
+            Event::SessionSegmentStatus(_) => SESSION_SEGMENT_STATUS,
+            Event::SessionSegmentCut(_) => SESSION_SEGMENT_CUT,
+            Event::PrivacyModeChanged(_) => PRIVACY_MODE_CHANGED,
+            Event::PresetChanged(_) => PRESET_CHANGED,
+            Event::SettingsChanged(_) => SETTINGS_CHANGED,
+            Event::StartupHealth(_) => STARTUP_HEALTH,
+            Event::EnvImported(_) => ENV_IMPORTED,
+            Event::ApiKeyInvalid(_) => API_KEY_INVALID,
+            Event::LanguageMismatchSuggestion(_) => LANGUAGE_MISMATCH_SUGGESTION,
+            Event::ProviderCircuitOpened(_) => PROVIDER_CIRCUIT_OPENED,
+            Event::ProviderCircuitClosed(_) => PROVIDER_CIRCUIT_CLOSED,
+            Event::DashboardRefresh => DASHBOARD_REFRESH,
+            Event::DashboardNavigate(_) => DASHBOARD_NAVIGATE,
+            Event::DashboardHistoryUpdated(_) => DASHBOARD_HISTORY_UPDATED,
+            Event::HistoryRetranscribeProgress(_) => HISTORY_RETRANSCRIBE_PROGRESS,
+            Event::PendingTranscriptionCompleted(_) => PENDING_TRANSCRIPTION_COMPLETED,
+            Event::HotkeyCaptured(_) => HOTKEY_CAPTURED,
+        }
+    }
+}
+
+/// Input level of the running capture, 0-1: smoothed RMS after the input gain, the
+/// highest sample since the previous event, and whether the input has been clipping for
+/// several events in a row.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevel {
+    pub rms: f32,
+    pub peak: f32,
+    pub clipping: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicPossiblyExclusive {
+    pub device: String,
+}
+
+/// A new input device selection took effect, with the format the next recording will
+/// have. Selections made during a recording wait for it to stop.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceChanged {
+    /// `None` is the system default
+    pub selected: Option<String>,
+    /// The device capture will open, after any fallback
+    pub device_name: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Selected during a recording and applied when it stopped
+    pub was_queued: bool,
+}
+
+/// Capture stopped on its own mid-recording; the audio so far was discarded.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingError {
+    /// `device_lost` when the input device went away
+    pub code: &'static str,
+    pub error_code: ErrorCode,
+    pub message: String,
+    pub device: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptPartial {
+    /// Everything transcribed so far in this recording.
+    pub text: String,
+    pub windows: u32,
+    pub request_id: String,
+}
+
+/// A recording stopped before the minimum duration; it was dropped without a provider
+/// call, a paste or a history entry.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingTooShort {
+    pub duration_secs: f32,
+    pub min_duration_secs: f32,
+    pub request_id: Option<String>,
+}
+
+/// The recording hit `max_recording_secs` and was stopped; `stop_recording` hands back
+/// its audio.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingAutoStopped {
+    /// Audio captured before the stop
+    pub duration_secs: f32,
+    pub max_recording_secs: u64,
+    pub request_id: Option<String>,
+}
+
+/// An auto-split session cut a segment off the running recording and queued it; its
+/// transcription reports through `session:segment-status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentCut {
+    pub segment_id: String,
+    pub duration_secs: f32,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyModeChanged {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetChanged {
+    /// `None` once the preset has ended
+    pub active: Option<ActivePreset>,
+}
+
+/// Settings were saved outside the window that shows them (tray, another window).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChanged {
+    pub language: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvImported {
+    pub keys: Vec<String>,
+}
+
+/// The key health check found the stored key revoked or expired.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyInvalid {
+    pub provider: String,
+    pub message: String,
+}
+
+/// A provider failed repeatedly and is skipped for `cooldown_secs`; dictations use the
+/// fallbacks meanwhile.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCircuitOpened {
+    pub provider: String,
+    pub cooldown_secs: u64,
+    pub recent_errors: Vec<String>,
+}
+
+/// The provider answered again, or the user closed its breaker by hand.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCircuitClosed {
+    pub provider: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DashboardSection {
+    Settings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardNavigate {
+    pub section: DashboardSection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RetranscribeStatus {
+    Transcribing,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetranscribeProgress {
+    pub id: String,
+    pub index: usize,
+    pub total: usize,
+    pub status: RetranscribeStatus,
+    pub provider: Option<String>,
+    pub error: Option<String>,
+}
+
+/// History changed since the last event; sent through [`history_changed`], which coalesces
+/// bursts of writes into one event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryUpdated {
+    pub change_summary: ChangeSummary,
+}
+
+/// A recording queued while offline has been transcribed; `text` replaced its placeholder.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTranscriptionCompleted {
+    pub id: String,
+    pub text: String,
+    pub provider: String,
+}
+
+/// `begin_hotkey_capture` saw a chord, or gave up after ten seconds without one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyCaptured {
+    /// Normalized accelerator; `None` when the capture timed out
+    pub accelerator: Option<String>,
+    pub timed_out: bool,
+}
+
+/// Which windows listen to each event. The setup window only needs level/capture
+/// feedback for the mic test; it must never see recording toggles.
+pub fn route(event: &str) -> &'static [WindowTarget] {
+    match event {
+        TOGGLE_RECORDING
+        | STOP_RECORDING
+        | SESSION_PAUSED
+        | SESSION_RESUMED
+        | SESSION_SEGMENT_STATUS
+        | SESSION_SEGMENT_CUT
+        | TRANSCRIPT_PARTIAL
+        | RECORDING_TOO_SHORT
+        | RECORDING_AUTO_STOPPED => &[WindowTarget::Pill],
+        // Dashboard re-transcriptions run through the local engine too.
+        WHISPER_PROGRESS => &[WindowTarget::Pill, WindowTarget::Dashboard],
+        AUDIO_LEVEL | CAPTURE_STARTED | MIC_POSSIBLY_EXCLUSIVE | RECORDING_ERROR => {
+            &[WindowTarget::Pill, WindowTarget::Setup]
+        }
+        DASHBOARD_REFRESH
+        | DASHBOARD_NAVIGATE
+        | DASHBOARD_HISTORY_UPDATED
+        | HISTORY_RETRANSCRIBE_PROGRESS
+        | PENDING_TRANSCRIPTION_COMPLETED => &[WindowTarget::Dashboard],
+        ENV_IMPORTED | INPUT_DEVICES_CHANGED | HOTKEY_CAPTURED => {
+            &[WindowTarget::Setup, WindowTarget::Dashboard]
+        }
+        DEVICE_CHANGED
+        | PRIVACY_MODE_CHANGED
+        | PRESET_CHANGED
+        | SETTINGS_CHANGED
+        | STARTUP_HEALTH
+        | API_KEY_INVALID
+        | LANGUAGE_MISMATCH_SUGGESTION
+        | PROVIDER_CIRCUIT_OPENED
+        | PROVIDER_CIRCUIT_CLOSED => &[WindowTarget::All],
+        _ => {
+            tracing::debug!("Event '{}' has no route, broadcasting", event);
+            &[WindowTarget::All]
+        }
+    }
+}
+
+/// Emit `event` only to the windows it is routed to.
+pub fn emit_event<R: Runtime>(app: &AppHandle<R>, event: Event) {
+    let name = event.name();
+    for target in route(name) {
+        let result = match target.label() {
+            Some(label) => app.emit_to(label, name, &event),
+            None => app.emit(name, &event),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to emit '{}': {}", name, e);
+        }
+    }
+}
+
+/// Report a history change to the dashboard. Goes through the managed [`HistoryNotifier`]
+/// so batch jobs send a few events instead of one per write; empty changes send nothing.
+pub fn history_changed<R: Runtime>(app: &AppHandle<R>, change: ChangeSummary) {
+    match app.try_state::<HistoryNotifier>() {
+        Some(notifier) => notifier.record(change),
+        None if !change.is_empty() => emit_event(
+            app,
+            Event::DashboardHistoryUpdated(HistoryUpdated {
+                change_summary: change,
+            }),
+        ),
+        None => {}
+    }
+}
+
+/// Sends the session's segment status changes to the pill as they happen.
+pub struct SegmentStatusEmitter<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> SegmentStatusEmitter<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self { app }
+    }
+}
+
+impl<R: Runtime> ProgressSink for SegmentStatusEmitter<R> {
+    fn segment_status(&self, progress: SegmentProgress) {
+        emit_event(&self.app, Event::SessionSegmentStatus(progress));
+    }
+}
+
+/// Sends local engine progress to the windows waiting on a transcription.
+pub struct TranscriptionProgressEmitter<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> TranscriptionProgressEmitter<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self { app }
+    }
+}
+
+impl<R: Runtime> TranscriptionProgressSink for TranscriptionProgressEmitter<R> {
+    fn progress(&self, progress: TranscriptionProgress) {
+        emit_event(&self.app, Event::WhisperProgress(progress));
+    }
+}
+
+/// Global hotkey toggles are ignored until onboarding finishes.
+pub fn hotkey_toggle_allowed(setup_completed: &AtomicBool) -> bool {
+    let allowed = setup_completed.load(Ordering::Relaxed);
+    if !allowed {
+        tracing::info!("Ignoring recording hotkey: setup not completed");
+    }
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use zentra_core::session::SegmentStatus;
+
+    #[test]
+    fn test_recording_toggle_only_reaches_pill() {
+        assert_eq!(route(TOGGLE_RECORDING), &[WindowTarget::Pill]);
+        assert_eq!(route(STOP_RECORDING), &[WindowTarget::Pill]);
+    }
+
+    #[test]
+    fn test_audio_level_skips_dashboard() {
+        let targets = route(AUDIO_LEVEL);
+        assert!(targets.contains(&WindowTarget::Pill));
+        assert!(targets.contains(&WindowTarget::Setup));
+        assert!(!targets.contains(&WindowTarget::Dashboard));
+        assert!(!targets.contains(&WindowTarget::All));
+    }
+
+    #[test]
+    fn test_dashboard_events_stay_in_dashboard() {
+        for event in [
+            DASHBOARD_REFRESH,
+            DASHBOARD_NAVIGATE,
+            DASHBOARD_HISTORY_UPDATED,
+            HISTORY_RETRANSCRIBE_PROGRESS,
+            PENDING_TRANSCRIPTION_COMPLETED,
+        ] {
+            assert_eq!(route(event), &[WindowTarget::Dashboard], "{}", event);
+        }
+    }
+
+    #[test]
+    fn test_unknown_events_broadcast() {
+        assert_eq!(route("something-new"), &[WindowTarget::All]);
+        assert_eq!(WindowTarget::All.label(), None);
+    }
+
+    #[test]
+    fn test_events_serialize_as_their_payload() {
+        let snapshot = |event: Event| serde_json::to_value(&event).unwrap();
+
+        assert_eq!(snapshot(Event::ToggleRecording), json!(null));
+        assert_eq!(snapshot(Event::StopRecording), json!(null));
+        assert_eq!(snapshot(Event::DashboardRefresh), json!(null));
+        assert_eq!(
+            snapshot(Event::DashboardHistoryUpdated(HistoryUpdated {
+                change_summary: ChangeSummary::added(3),
+            })),
+            json!({ "changeSummary": { "added": 3, "removed": 0, "updated": 0 } })
+        );
+        assert_eq!(
+            snapshot(Event::AudioLevel(AudioLevel {
+                rms: 0.5,
+                peak: 1.0,
+                clipping: true,
+            })),
+            json!({ "rms": 0.5, "peak": 1.0, "clipping": true })
+        );
+        assert_eq!(
+            snapshot(Event::RecordingError(RecordingError {
+                code: "device_lost",
+                error_code: ErrorCode::RecDeviceLost,
+                message: "The requested device is no longer available.".to_string(),
+                device: Some("USB Mic".to_string()),
+            })),
+            json!({
+                "code": "device_lost",
+                "errorCode": "REC_DEVICE_LOST",
+                "message": "The requested device is no longer available.",
+                "device": "USB Mic"
+            })
+        );
+        assert_eq!(
+            snapshot(Event::InputDevicesChanged(DeviceChange {
+                devices: vec!["Built-in".to_string()],
+                selected: Some("USB Mic".to_string()),
+                selected_missing: true,
+            })),
+            json!({
+                "devices": ["Built-in"],
+                "selected": "USB Mic",
+                "selectedMissing": true
+            })
+        );
+        assert_eq!(
+            snapshot(Event::DeviceChanged(DeviceChanged {
+                selected: None,
+                device_name: "Built-in".to_string(),
+                sample_rate: 48_000,
+                channels: 2,
+                was_queued: true,
+            })),
+            json!({
+                "selected": null,
+                "deviceName": "Built-in",
+                "sampleRate": 48000,
+                "channels": 2,
+                "wasQueued": true
+            })
+        );
+        assert_eq!(
+            snapshot(Event::TranscriptPartial(TranscriptPartial {
+                text: "olá".to_string(),
+                windows: 2,
+                request_id: "r1".to_string(),
+            })),
+            json!({ "text": "olá", "windows": 2, "requestId": "r1" })
+        );
+        assert_eq!(
+            snapshot(Event::RecordingTooShort(RecordingTooShort {
+                duration_secs: 0.25,
+                min_duration_secs: 0.5,
+                request_id: None,
+            })),
+            json!({ "durationSecs": 0.25, "minDurationSecs": 0.5, "requestId": null })
+        );
+        assert_eq!(
+            snapshot(Event::RecordingAutoStopped(RecordingAutoStopped {
+                duration_secs: 300.5,
+                max_recording_secs: 300,
+                request_id: Some("r1".to_string()),
+            })),
+            json!({ "durationSecs": 300.5, "maxRecordingSecs": 300, "requestId": "r1" })
+        );
+        assert_eq!(
+            snapshot(Event::WhisperProgress(TranscriptionProgress {
+                provider: "Whisper.cpp".to_string(),
+                percent: 40,
+                position_secs: Some(12.5),
+            })),
+            json!({ "provider": "Whisper.cpp", "percent": 40, "positionSecs": 12.5 })
+        );
+        assert_eq!(
+            snapshot(Event::SessionSegmentStatus(SegmentProgress {
+                segment_id: "s2".to_string(),
+                sequence_number: 2,
+                status: SegmentStatus::Failed,
+                provider: None,
+                error: Some("timeout".to_string()),
+            })),
+            json!({
+                "segmentId": "s2",
+                "sequenceNumber": 2,
+                "status": "failed",
+                "provider": null,
+                "error": "timeout"
+            })
+        );
+        assert_eq!(
+            snapshot(Event::SessionSegmentCut(SegmentCut {
+                segment_id: "s1".to_string(),
+                duration_secs: 31.5,
+                request_id: Some("r1".to_string()),
+            })),
+            json!({ "segmentId": "s1", "durationSecs": 31.5, "requestId": "r1" })
+        );
+        assert_eq!(
+            snapshot(Event::PrivacyModeChanged(PrivacyModeChanged {
+                enabled: true
+            })),
+            json!({ "enabled": true })
+        );
+        assert_eq!(
+            snapshot(Event::PresetChanged(PresetChanged { active: None })),
+            json!({ "active": null })
+        );
+        assert_eq!(
+            snapshot(Event::SettingsChanged(SettingsChanged {
+                language: "en".to_string(),
+            })),
+            json!({ "language": "en" })
+        );
+        assert_eq!(
+            snapshot(Event::DashboardNavigate(DashboardNavigate {
+                section: DashboardSection::Settings,
+            })),
+            json!({ "section": "settings" })
+        );
+        assert_eq!(
+            snapshot(Event::HistoryRetranscribeProgress(RetranscribeProgress {
+                id: "h1".to_string(),
+                index: 0,
+                total: 3,
+                status: RetranscribeStatus::Transcribing,
+                provider: None,
+                error: None,
+            })),
+            json!({
+                "id": "h1",
+                "index": 0,
+                "total": 3,
+                "status": "transcribing",
+                "provider": null,
+                "error": null
+            })
+        );
+        assert_eq!(
+            snapshot(Event::StartupHealth(StartupHealth {
+                storage_available: false,
+                storage_message: Some("read-only".to_string()),
+                hotkey_message: None,
+                crash_message: Some("recovered".to_string()),
+            })),
+            json!({
+                "storageAvailable": false,
+                "storageMessage": "read-only",
+                "hotkeyMessage": null,
+                "crashMessage": "recovered"
+            })
+        );
+        assert_eq!(
+            snapshot(Event::ApiKeyInvalid(ApiKeyInvalid {
+                provider: "groq".to_string(),
+                message: "rejected".to_string(),
+            })),
+            json!({ "provider": "groq", "message": "rejected" })
+        );
+        assert_eq!(
+            snapshot(Event::LanguageMismatchSuggestion(LanguageSuggestion {
+                configured: "pt".to_string(),
+                suggested: "en".to_string(),
+                mismatches: 5,
+                window: 7,
+            })),
+            json!({ "configured": "pt", "suggested": "en", "mismatches": 5, "window": 7 })
+        );
+        assert_eq!(
+            snapshot(Event::ProviderCircuitOpened(ProviderCircuitOpened {
+                provider: "groq".to_string(),
+                cooldown_secs: 600,
+                recent_errors: vec!["Request timeout".to_string()],
+            })),
+            json!({
+                "provider": "groq",
+                "cooldownSecs": 600,
+                "recentErrors": ["Request timeout"]
+            })
+        );
+        assert_eq!(
+            snapshot(Event::ProviderCircuitClosed(ProviderCircuitClosed {
+                provider: "groq".to_string(),
+            })),
+            json!({ "provider": "groq" })
+        );
+        assert_eq!(
+            snapshot(Event::PendingTranscriptionCompleted(
+                PendingTranscriptionCompleted {
+                    id: "h1".to_string(),
+                    text: "olá".to_string(),
+                    provider: "Groq".to_string(),
+                }
+            )),
+            json!({ "id": "h1", "text": "olá", "provider": "Groq" })
+        );
+    }
+
+    #[test]
+    fn test_hotkey_is_noop_before_setup_completes() {
+        let setup_completed = AtomicBool::new(false);
+        assert!(!hotkey_toggle_allowed(&setup_completed));
+
+        setup_completed.store(true, Ordering::Relaxed);
+        assert!(hotkey_toggle_allowed(&setup_completed));
+    }
+}
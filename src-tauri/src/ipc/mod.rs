@@ -0,0 +1,278 @@
+//! Shapes that cross the IPC boundary. Everything the frontend sees serializes camelCase;
+//! the snapshot tests pin each shape so a renamed field fails CI instead of the UI.
+//! Event payloads live in [`events`].
+
+pub mod events;
+
+use crate::audio::CaptureError;
+use crate::config::SkippedHistoryItem;
+use serde::{Deserialize, Serialize};
+use zentra_core::error_codes::ErrorCode;
+use zentra_core::session::{SessionError, StitchedResult};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicrophoneInfo {
+    pub available: bool,
+    pub name: Option<String>,
+}
+
+/// Structured `start_recording` failure; `code` tells a missing key from a busy mic.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartRecordingError {
+    pub code: &'static str,
+    /// Catalogue code; `code` is the finer-grained reason the UI branches on
+    pub error_code: ErrorCode,
+    pub message: String,
+    pub device: Option<String>,
+}
+
+impl From<CaptureError> for StartRecordingError {
+    fn from(err: CaptureError) -> Self {
+        Self {
+            code: err.code(),
+            error_code: err.error_code(),
+            message: err.to_string(),
+            device: err.device().map(str::to_string),
+        }
+    }
+}
+
+/// `start_auto_session` could not open its session.
+impl From<SessionError> for StartRecordingError {
+    fn from(err: SessionError) -> Self {
+        Self {
+            code: "session_failed",
+            error_code: ErrorCode::from(&err),
+            message: err.to_string(),
+            device: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDevicesResponse {
+    pub devices: Vec<String>,
+    pub selected: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetranscribeReport {
+    pub queued: Vec<String>,
+    pub updated: Vec<String>,
+    pub failed: Vec<SkippedHistoryItem>,
+    pub skipped: Vec<SkippedHistoryItem>,
+}
+
+/// What `export_transcript` writes: a saved history item, or the session the frontend
+/// just finalized.
+#[derive(Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ExportSource {
+    History { id: String },
+    Session { result: StitchedResult },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::CaptureInfo;
+    use crate::config::{DashboardStats, HistoryItem, HistoryStatus, SetupState};
+    use crate::paste::{PasteAttempt, PasteMethod};
+    use crate::stats::HistoryEntry;
+    use serde_json::{json, Value};
+
+    fn snapshot<T: Serialize>(value: &T) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_export_source_reads_both_kinds() {
+        let history: ExportSource =
+            serde_json::from_value(json!({ "kind": "history", "id": "h1" })).unwrap();
+        assert!(matches!(history, ExportSource::History { id } if id == "h1"));
+
+        let session: ExportSource = serde_json::from_value(json!({
+            "kind": "session",
+            "result": {
+                "fullText": "Olá",
+                "totalDurationSecs": 4.5,
+                "segmentCount": 1,
+                "confidenceAvg": 0.9,
+                "providersUsed": ["Groq"],
+                "segments": [{ "durationSecs": 4.5, "wordCount": 1 }]
+            }
+        }))
+        .unwrap();
+        let ExportSource::Session { result } = session else {
+            panic!("expected a session");
+        };
+        assert_eq!(result.full_text, "Olá");
+        assert_eq!(result.segments.len(), 1);
+    }
+
+    #[test]
+    fn test_command_results_serialize_camel_case() {
+        let busy = CaptureError::DeviceBusy {
+            device: "USB Mic".to_string(),
+        };
+        assert_eq!(
+            snapshot(&StartRecordingError::from(busy.clone())),
+            json!({
+                "code": "device_busy",
+                "errorCode": "REC_DEVICE_BUSY",
+                "message": busy.to_string(),
+                "device": "USB Mic"
+            })
+        );
+        assert_eq!(
+            snapshot(&MicrophoneInfo {
+                available: true,
+                name: Some("USB Mic".to_string()),
+            }),
+            json!({ "available": true, "name": "USB Mic" })
+        );
+        assert_eq!(
+            snapshot(&InputDevicesResponse {
+                devices: vec!["USB Mic".to_string()],
+                selected: None,
+            }),
+            json!({ "devices": ["USB Mic"], "selected": null })
+        );
+        assert_eq!(
+            snapshot(&RetranscribeReport {
+                queued: vec!["a".to_string()],
+                failed: vec![SkippedHistoryItem {
+                    id: "b".to_string(),
+                    reason: "missing audio".to_string(),
+                }],
+                ..Default::default()
+            }),
+            json!({
+                "queued": ["a"],
+                "updated": [],
+                "failed": [{ "id": "b", "reason": "missing audio" }],
+                "skipped": []
+            })
+        );
+        assert_eq!(
+            snapshot(&PasteAttempt {
+                pasted: false,
+                method: PasteMethod::None,
+                reason: Some("no_target_window".to_string()),
+                error_code: Some(ErrorCode::PasteNoTarget),
+                request_id: Some("r1".to_string()),
+            }),
+            json!({
+                "pasted": false,
+                "method": "none",
+                "reason": "no_target_window",
+                "errorCode": "PASTE_NO_TARGET",
+                "requestId": "r1"
+            })
+        );
+        assert_eq!(
+            snapshot(&CaptureInfo {
+                device_name: "USB Mic".to_string(),
+                host: "ALSA".to_string(),
+                sample_rate: 48_000,
+                channels: 2,
+                sample_format: "f32".to_string(),
+                pre_roll_ms: 0,
+            }),
+            json!({
+                "deviceName": "USB Mic",
+                "host": "ALSA",
+                "sampleRate": 48000,
+                "channels": 2,
+                "sampleFormat": "f32",
+                "preRollMs": 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_views_serialize_camel_case() {
+        assert_eq!(
+            snapshot(&DashboardStats {
+                total_transcriptions: 3,
+                total_words: 120,
+                minutes_saved: 1.5,
+                wpm: 40.0,
+                typing_wpm: 80.0,
+            }),
+            json!({
+                "totalTranscriptions": 3,
+                "totalWords": 120,
+                "minutesSaved": 1.5,
+                "wpm": 40.0,
+                "typingWpm": 80.0
+            })
+        );
+        assert_eq!(
+            snapshot(&HistoryEntry::new(
+                HistoryItem {
+                    id: "h1".to_string(),
+                    text: "olá".to_string(),
+                    timestamp: "2025-03-07T09:30:00Z".to_string(),
+                    duration_seconds: 2.0,
+                    word_count: 1,
+                    provider: None,
+                    audio_path: None,
+                    versions: Vec::new(),
+                    status: HistoryStatus::PendingTranscription,
+                    request_id: Some("r1".to_string()),
+                    session_stats: None,
+                },
+                8.0,
+            )),
+            json!({
+                "id": "h1",
+                "text": "olá",
+                "timestamp": "2025-03-07T09:30:00Z",
+                "durationSeconds": 2.0,
+                "wordCount": 1,
+                "provider": null,
+                "audioPath": null,
+                "versions": [],
+                "status": "pendingTranscription",
+                "requestId": "r1",
+                "sessionStats": null,
+                "minutesSaved": 0.125
+            })
+        );
+
+        let setup = snapshot(&SetupState {
+            setup_completed: true,
+            user_name: "Ana".to_string(),
+            use_case: "general".to_string(),
+            has_api_key: true,
+            input_device_name: None,
+            hotkey: "Ctrl+Shift+Space".to_string(),
+            language: "pt".to_string(),
+            github_url: String::new(),
+        });
+        let keys: Vec<&str> = setup
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "githubUrl",
+                "hasApiKey",
+                "hotkey",
+                "inputDeviceName",
+                "language",
+                "setupCompleted",
+                "useCase",
+                "userName"
+            ]
+        );
+    }
+}
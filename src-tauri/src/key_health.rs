@@ -0,0 +1,218 @@
+//! Background check of the stored Groq key: once a day and after every authentication
+//! failure. A revoked or expired key is flagged in the tray and blocks the next recording,
+//! instead of surfacing as a failed dictation hours later.
+
+use crate::config::{self, AppConfig};
+use crate::crash_reports;
+use crate::ipc::events::{self, ApiKeyInvalid, Event};
+use crate::tray;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::AppHandle;
+use zentra_core::key_health::{
+    classify_http_status, has_groq_key_shape, KeyHealth, KeyStatus, ProbeOutcome,
+};
+use zentra_core::privacy::PrivacyGuard;
+
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Retry sooner when the last probe could not reach Groq.
+const OFFLINE_RETRY: Duration = Duration::from_secs(60 * 60);
+/// Let startup settle before the first probe.
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(8);
+const GROQ_MODELS_URL: &str = "https://api.groq.com/openai/v1/models";
+
+/// Result of the last conclusive probe. `key_hint` is the masked key it was made with, so a
+/// newly entered key starts out `Unknown`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyCheck {
+    pub status: KeyStatus,
+    pub checked_at: String,
+    pub key_hint: String,
+}
+
+/// Status of the key currently in `config`.
+pub fn current_status(config: &AppConfig) -> KeyStatus {
+    current_check(config)
+        .map(|check| check.status)
+        .unwrap_or_default()
+}
+
+fn current_check(config: &AppConfig) -> Option<&KeyCheck> {
+    let key = config::decode_api_key(config)?;
+    config
+        .key_check
+        .as_ref()
+        .filter(|check| check.key_hint == config::mask_api_key(key.trim()))
+}
+
+/// Time until the next scheduled probe.
+pub fn next_check_in(check: Option<&KeyCheck>, now: DateTime<Utc>) -> Duration {
+    let Some(checked_at) = check.and_then(|check| {
+        DateTime::parse_from_rfc3339(&check.checked_at)
+            .ok()
+            .map(|checked_at| checked_at.with_timezone(&Utc))
+    }) else {
+        return Duration::ZERO;
+    };
+    let elapsed = (now - checked_at).to_std().unwrap_or(Duration::ZERO);
+    CHECK_INTERVAL.saturating_sub(elapsed)
+}
+
+/// Ask Groq's models endpoint whether it accepts `api_key`. Costs no transcription quota.
+pub async fn probe_groq_key(api_key: &str) -> ProbeOutcome {
+    if !has_groq_key_shape(api_key) {
+        return ProbeOutcome::Rejected;
+    }
+
+    let response = Client::new()
+        .get(GROQ_MODELS_URL)
+        .bearer_auth(api_key)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await;
+    match response {
+        Ok(response) => classify_http_status(response.status().as_u16()),
+        Err(e) => {
+            tracing::info!("Groq key check could not reach the API: {}", e);
+            ProbeOutcome::Unreachable
+        }
+    }
+}
+
+pub fn spawn(app: AppHandle, health: KeyHealth, privacy: PrivacyGuard) {
+    let first_check = config::load_or_create(&app)
+        .map(|config| next_check_in(current_check(&config), Utc::now()))
+        .unwrap_or(Duration::ZERO)
+        .max(STARTUP_DELAY);
+
+    crash_reports::supervise("key health", async move {
+        let mut wait = first_check;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = health.check_requested() => {}
+            }
+            wait = match run_check(&app, &health, &privacy).await {
+                Some(ProbeOutcome::Unreachable) => OFFLINE_RETRY,
+                _ => CHECK_INTERVAL,
+            };
+        }
+    });
+}
+
+/// Probe the stored key and persist the result. `None` when there was nothing to check.
+async fn run_check(
+    app: &AppHandle,
+    health: &KeyHealth,
+    privacy: &PrivacyGuard,
+) -> Option<ProbeOutcome> {
+    if privacy.is_enabled() {
+        tracing::debug!("Groq key check skipped: privacy mode");
+        return None;
+    }
+
+    let api_key = config::load_or_create(app)
+        .ok()
+        .and_then(|config| config::decode_api_key(&config))
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())?;
+    let outcome = probe_groq_key(&api_key).await;
+    if outcome == ProbeOutcome::Unreachable {
+        return Some(outcome);
+    }
+
+    // Reload after the request so nothing written meanwhile is lost.
+    let mut config = config::load_or_create(app).ok()?;
+    let key_hint = config::mask_api_key(&api_key);
+    if config::decode_api_key(&config).map(|key| config::mask_api_key(key.trim()))
+        != Some(key_hint.clone())
+    {
+        // The key changed while the probe ran; the new one gets its own check.
+        return None;
+    }
+
+    let changed = health.record(outcome);
+    config.key_check = Some(KeyCheck {
+        status: health.status(),
+        checked_at: Utc::now().to_rfc3339(),
+        key_hint,
+    });
+    if let Err(e) = config::save(app, &config) {
+        tracing::warn!("Failed to save Groq key status: {}", e);
+    }
+
+    if let Some(status) = changed {
+        tracing::info!("Groq key status: {:?}", status);
+        tray::set_key_indicator(app, status == KeyStatus::Invalid);
+        if status == KeyStatus::Invalid {
+            events::emit_event(
+                app,
+                Event::ApiKeyInvalid(ApiKeyInvalid {
+                    provider: "groq".to_string(),
+                    message:
+                        "Groq rejected the saved API key. It may have been revoked or expired."
+                            .to_string(),
+                }),
+            );
+        }
+    }
+    Some(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_check(key: &str, check_key: &str, status: KeyStatus) -> AppConfig {
+        AppConfig {
+            groq_api_key_obfuscated: Some(config::obfuscate_api_key(key)),
+            key_check: Some(KeyCheck {
+                status,
+                checked_at: "2026-03-01T08:00:00Z".to_string(),
+                key_hint: config::mask_api_key(check_key),
+            }),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_status_belongs_to_the_key_it_was_checked_with() {
+        let key = "gsk_0123456789abcdefABCDEF";
+        let checked = config_with_check(key, key, KeyStatus::Invalid);
+        assert_eq!(current_status(&checked), KeyStatus::Invalid);
+
+        let replaced = config_with_check("gsk_9876543210fedcbaFEDCBA", key, KeyStatus::Invalid);
+        assert_eq!(current_status(&replaced), KeyStatus::Unknown);
+        assert_eq!(current_status(&AppConfig::default()), KeyStatus::Unknown);
+    }
+
+    #[test]
+    fn test_next_check_is_a_day_after_the_last() {
+        let key = "gsk_0123456789abcdefABCDEF";
+        let config = config_with_check(key, key, KeyStatus::Valid);
+        let check = current_check(&config);
+        let checked_at = DateTime::parse_from_rfc3339("2026-03-01T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            next_check_in(check, checked_at + chrono::Duration::hours(6)),
+            Duration::from_secs(18 * 60 * 60)
+        );
+        assert_eq!(
+            next_check_in(check, checked_at + chrono::Duration::days(3)),
+            Duration::ZERO
+        );
+        assert_eq!(next_check_in(None, checked_at), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_key_is_rejected_without_a_request() {
+        assert_eq!(probe_groq_key("gsk_short").await, ProbeOutcome::Rejected);
+        assert_eq!(probe_groq_key("not a key").await, ProbeOutcome::Rejected);
+    }
+}
@@ -1,87 +1,226 @@
+mod announce;
+mod api_types;
 mod audio;
+mod audio_archive;
+mod clipboard_files;
 mod config;
-mod orchestrator;
+mod crash_reports;
+mod destructive;
+mod dictation;
+mod env_import;
+mod history_mirror;
+mod history_store;
+mod history_updates;
+mod hotkey_backend;
+mod hotkey_capture;
+mod hotkeys;
+mod integrations;
+mod ipc;
+mod key_health;
+mod live;
+mod locks;
+mod mini_history;
+mod offline_queue;
 mod paste;
-mod prompt_engine;
-mod session;
-mod stt;
+mod paths;
+mod pill;
+mod post_actions;
+mod presets;
+mod secrets;
+mod snippet;
+mod stats;
+mod suggestions;
+mod transitions;
 mod tray;
 
-use audio::{AudioBuffer, AudioRecorder};
+use announce::Announcement;
+use audio::{
+    capture::{AudioCapture, EXCLUSIVE_PROBE_SECS},
+    devices::WATCH_INTERVAL,
+    AudioBuffer, AudioRecorder, CaptureError, CaptureFault, CaptureInfo, CaptureQuality,
+    ClipDetector, DeviceManager, LevelMeter, SharedRecorder,
+};
 use config::{
-    AppConfig, CompleteSetupPayload, RecordHistoryPayload, SetupPartialPayload, SetupState,
+    AppConfig, CompleteSetupPayload, HistoryFilter, HistorySearch, HistorySearchResult,
+    RecordHistoryPayload, RetranscribeOptions, SetupPartialPayload, SetupState, SkippedHistoryItem,
     UpdateSettingsPayload,
 };
-use cpal::traits::{DeviceTrait, HostTrait};
-use orchestrator::FailoverOrchestrator;
+use crash_reports::AppPhase;
+use history_store::HistoryStore;
+use history_updates::{ChangeSummary, HistoryNotifier};
+use hotkey_backend::{HotkeyBackend, HotkeyBackendKind};
+use hotkeys::{HotkeyAction, HotkeyBinding};
+use ipc::events::{self, Event};
+use ipc::{
+    ExportSource, InputDevicesResponse, MicrophoneInfo, RetranscribeReport, StartRecordingError,
+};
+use locks::lock_or_recover;
+use post_actions::{PostActionEffects, ProcessedResult};
 use reqwest::{multipart, Client};
-use serde::Serialize;
-use session::{SegmentResult, SessionProgress, SessionStitcher, StitchedResult};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
     Arc, Mutex,
 };
-use tauri::{Emitter, Manager, State};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri::{Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
 use tokio::sync::Mutex as TokioMutex;
+use tokio::sync::RwLock as TokioRwLock;
 use tokio::time::sleep;
+use tracing::Instrument;
+use transitions::{
+    Transition, TransitionError, TransitionQueue, TransitionReply, TransitionResult,
+};
+use zentra_core::error_codes::{self, describe_orchestrator_error, ErrorCode};
+use zentra_core::key_health::{KeyHealth, KeyStatus};
+use zentra_core::offline::OfflineMode;
+use zentra_core::orchestrator::{
+    usage, CircuitEvent, CircuitObserver, FailoverOrchestrator, ProviderSetup, UsageTracker,
+};
+use zentra_core::privacy::PrivacyGuard;
+use zentra_core::prompt_engine::{
+    OptimizationMode, OptimizedPrompt, Profile, PromptEngine, USER_PROFILES_FILE,
+};
+use zentra_core::replay::{self, FixtureBundle, ReplayReport};
+use zentra_core::session::{
+    autosplit, recovery, streamed, RecoverableSession, RecoveryStore, SegmentDiagnostics,
+    SegmentResult, SegmentStream, SessionProgress, SessionStats, SessionStitcher,
+    SharedSilenceGate, StitchedResult, TOO_SHORT_PROVIDER,
+};
+use zentra_core::stt::{self, LanguagePin};
+use zentra_core::text::postprocess::{OverrideOptions, PostProcessingPreview, PostProcessor};
+use zentra_core::text::subtitles::{self, SegmentTiming, TranscriptFormat};
 
 struct AppState {
-    recorder: Arc<Mutex<AudioRecorder>>,
-    orchestrator: Arc<TokioMutex<FailoverOrchestrator>>,
+    /// Stream control only (start/stop/cancel); never held during device enumeration.
+    recorder: Arc<SharedRecorder>,
+    /// Every start, stop, cancel and toggle of a dictation, one at a time.
+    transitions: Arc<TransitionQueue>,
+    devices: Arc<DeviceManager>,
+    live: Arc<live::LiveTranscriber>,
+    orchestrator: Arc<TokioRwLock<FailoverOrchestrator>>,
+    prompt_engine: Arc<TokioMutex<PromptEngine>>,
     session_stitcher: Arc<TokioMutex<SessionStitcher>>,
     audio_level_flag: Arc<AtomicBool>,
     audio_level_task: Arc<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>>,
     paste_context: Arc<Mutex<paste::PasteContext>>,
-    hotkey: Arc<Mutex<String>>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct MicrophoneInfo {
-    available: bool,
-    name: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct InputDevicesResponse {
-    devices: Vec<String>,
-    selected: Option<String>,
+    /// Registered shortcut ids and what each one does
+    hotkeys: Arc<Mutex<HashMap<u32, HotkeyAction>>>,
+    /// Final text of the last dictation, for the paste-last shortcut
+    last_transcript: Arc<Mutex<Option<String>>>,
+    privacy: PrivacyGuard,
+    silence_gate: SharedSilenceGate,
+    key_health: KeyHealth,
+    offline: OfflineMode,
+    /// Audio sent to each provider, shared by every orchestrator build
+    usage: UsageTracker,
+    /// Language of the current session, once its warm-up heard it
+    language_pin: LanguagePin,
+    dictation: dictation::DictationTracker,
+    setup_completed: Arc<AtomicBool>,
+    max_recording_secs: Arc<AtomicU64>,
+    /// Bumped on every capture start, so a recording watchdog only stops its own capture.
+    capture_generation: Arc<AtomicU64>,
+    /// Audio of a recording the watchdog stopped, until `stop_recording` collects it.
+    auto_stopped: Arc<Mutex<Option<AudioBuffer>>>,
+    /// Mirrors `audio_archive.enabled`; when set, `stop_recording` keeps a copy of the audio
+    /// in `last_recording` for the history item to archive.
+    archive_audio: Arc<AtomicBool>,
+    /// Mirrors `pill_follows_active_monitor`; when set, a recording brings the pill to the
+    /// monitor under the cursor.
+    pill_follows_monitor: Arc<AtomicBool>,
+    /// Mirrors `experimental_streaming_upload`; when set, an auto session cuts segments at
+    /// a fixed length and uploads each while it is still being recorded.
+    streaming_upload: Arc<AtomicBool>,
+    /// Whether the config holds a Groq key the providers can use; recording needs one.
+    groq_key_set: Arc<AtomicBool>,
+    last_recording: Arc<Mutex<Option<AudioBuffer>>>,
+    hotkey_capture: hotkey_capture::HotkeyCapture,
+    tray_recording: Arc<tray::RecordingIndicator>,
+    announcements: Arc<announce::Announcements>,
+    hotkey_backend: hotkey_backend::Hotkeys,
 }
 
 fn start_audio_level_loop(
     state: &AppState,
     app_handle: tauri::AppHandle,
-    level: Arc<std::sync::atomic::AtomicU32>,
+    level: Arc<LevelMeter>,
+    fault: CaptureFault,
+    device: String,
 ) {
     state.audio_level_flag.store(true, Ordering::Relaxed);
     let flag = state.audio_level_flag.clone();
     let emit_handle = app_handle.clone();
     let handle = tauri::async_runtime::spawn(async move {
+        let mut clip = ClipDetector::default();
         while flag.load(Ordering::Relaxed) {
-            let bits = level.load(Ordering::Relaxed);
-            let value = f32::from_bits(bits).clamp(0.0, 1.0);
-            let _ = emit_handle.emit("audio-level", value);
+            let failure = lock_or_recover(&fault, "capture fault").clone();
+            if let Some(message) = failure {
+                abort_failed_capture(&emit_handle, device.clone(), message);
+                break;
+            }
+            let reading = level.take();
+            events::emit_event(
+                &emit_handle,
+                Event::AudioLevel(events::AudioLevel {
+                    rms: reading.rms,
+                    peak: reading.peak,
+                    clipping: clip.update(reading.peak),
+                }),
+            );
             sleep(std::time::Duration::from_millis(16)).await;
         }
-        let _ = emit_handle.emit("audio-level", 0.0f32);
+        events::emit_event(
+            &emit_handle,
+            Event::AudioLevel(events::AudioLevel::default()),
+        );
     });
 
-    if let Ok(mut guard) = state.audio_level_task.lock() {
-        if let Some(existing) = guard.take() {
-            existing.abort();
-        }
-        *guard = Some(handle);
+    let mut guard = lock_or_recover(&state.audio_level_task, "audio level task");
+    if let Some(existing) = guard.take() {
+        existing.abort();
+    }
+    *guard = Some(handle);
+}
+
+/// The input device failed mid-capture, usually because it was unplugged. Stop the stream
+/// and tell the frontend, instead of waiting for a stop that would hand back a truncated
+/// recording.
+fn abort_failed_capture(app_handle: &tauri::AppHandle, device: String, message: String) {
+    let state = app_handle.state::<AppState>();
+    tracing::warn!(
+        "Input device '{}' failed during capture: {}",
+        device,
+        message
+    );
+    state.audio_level_flag.store(false, Ordering::Relaxed);
+    state.live.stop();
+    state.tray_recording.set_recording(false);
+    if let Err(e) = state.recorder.lock().cancel_recording() {
+        tracing::warn!("Failed to stop the failed capture: {}", e);
     }
+    apply_queued_device(&state, app_handle);
+    lock_or_recover(&state.paste_context, "paste context").clear_target();
+    state.announcements.announce(Announcement::MicrophoneLost);
+    events::emit_event(
+        app_handle,
+        Event::RecordingError(events::RecordingError {
+            code: "device_lost",
+            error_code: ErrorCode::RecDeviceLost,
+            message,
+            device: Some(device),
+        }),
+    );
 }
 
 fn stop_audio_level_loop(state: &AppState) {
     state.audio_level_flag.store(false, Ordering::Relaxed);
-    if let Ok(mut guard) = state.audio_level_task.lock() {
-        if let Some(handle) = guard.take() {
-            handle.abort();
-        }
+    if let Some(handle) = lock_or_recover(&state.audio_level_task, "audio level task").take() {
+        handle.abort();
     }
 }
 
@@ -89,320 +228,2357 @@ fn start_capture(
     state: &AppState,
     app_handle: &tauri::AppHandle,
     capture_paste_target: bool,
-) -> Result<(), String> {
-    let mut recorder = state.recorder.lock().map_err(|e| e.to_string())?;
-    recorder.start_recording().map_err(|e| e.to_string())?;
+) -> Result<CaptureInfo, CaptureError> {
+    let device = state.devices.selected();
+    let mut recorder = state.recorder.lock();
+    let info = recorder.start_recording(device.as_deref())?;
     let level = recorder.audio_level_handle();
+    let live_buffer = recorder.live_buffer();
+    let fault = recorder.fault_handle();
     drop(recorder);
+    let generation = state.capture_generation.fetch_add(1, Ordering::Relaxed) + 1;
+    lock_or_recover(&state.auto_stopped, "auto-stopped audio").take();
+    lock_or_recover(&state.last_recording, "last recording").take();
 
+    // The mic monitor in setup also captures; only real recordings start a dictation, get
+    // a paste target and partial transcripts.
     if capture_paste_target {
-        let zentra_window = current_zentra_window_handle(app_handle);
-        if let Ok(mut paste_context) = state.paste_context.lock() {
-            paste_context.capture_target(zentra_window);
+        let request_id = state.dictation.begin();
+        let _span = dictation::span_for(&request_id).entered();
+        tracing::info!("Dictation started on '{}'", info.device_name);
+        lock_or_recover(&state.paste_context, "paste context").capture_target();
+        state.live.start(
+            app_handle.clone(),
+            live_buffer,
+            state.orchestrator.clone(),
+            request_id.clone(),
+        );
+        state.tray_recording.set_recording(true);
+        state.announcements.announce(Announcement::RecordingStarted);
+        spawn_recording_watchdog(state, app_handle.clone(), generation, request_id);
+    }
+
+    start_audio_level_loop(
+        state,
+        app_handle.clone(),
+        level,
+        fault,
+        info.device_name.clone(),
+    );
+    spawn_exclusive_probe(state, app_handle.clone(), info.device_name.clone());
+    events::emit_event(app_handle, Event::CaptureStarted(info.clone()));
+    Ok(info)
+}
+
+/// Stop a recording left running after `max_recording_secs` and keep its audio for
+/// `stop_recording`, so the pill transcribes it like a manual stop.
+fn spawn_recording_watchdog(
+    state: &AppState,
+    app_handle: tauri::AppHandle,
+    generation: u64,
+    request_id: String,
+) {
+    let limit = state.max_recording_secs.load(Ordering::Relaxed);
+    if limit == 0 {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        sleep(std::time::Duration::from_secs(limit)).await;
+        let state = app_handle.state::<AppState>();
+        if state.capture_generation.load(Ordering::Relaxed) != generation {
+            return;
+        }
+        // Fails when the recording was already stopped or cancelled.
+        let Ok(buffer) = stop_capture_and_return_buffer(&state, &app_handle) else {
+            return;
+        };
+        let _span = dictation::span_for(&request_id).entered();
+        tracing::warn!(
+            "Recording reached the {}s limit; stopped with {:.1}s of audio",
+            limit,
+            buffer.duration_secs
+        );
+        let duration_secs = buffer.duration_secs;
+        *lock_or_recover(&state.auto_stopped, "auto-stopped audio") = Some(buffer);
+        events::emit_event(
+            &app_handle,
+            Event::RecordingAutoStopped(events::RecordingAutoStopped {
+                duration_secs,
+                max_recording_secs: limit,
+                request_id: Some(request_id),
+            }),
+        );
+    });
+}
+
+/// Some drivers open a device held exclusively by another app and deliver exact zeros
+/// instead of failing. Warn once if the start of the recording looks like that.
+fn spawn_exclusive_probe(state: &AppState, app_handle: tauri::AppHandle, device: String) {
+    let recorder = state.recorder.clone();
+    tauri::async_runtime::spawn(async move {
+        let probe = std::time::Duration::from_secs_f32(EXCLUSIVE_PROBE_SECS + 0.1);
+        sleep(probe).await;
+        let suspicious = recorder.lock().possibly_exclusive();
+        if suspicious {
+            tracing::warn!("Input device '{}' delivers digital silence", device);
+            events::emit_event(
+                &app_handle,
+                Event::MicPossiblyExclusive(events::MicPossiblyExclusive { device }),
+            );
+        }
+    });
+}
+
+/// Re-enumerate inputs every [`WATCH_INTERVAL`] so the settings pick up hotplugged devices,
+/// and select the configured device again when it comes back.
+fn spawn_device_watcher(
+    app_handle: tauri::AppHandle,
+    devices: Arc<DeviceManager>,
+    mut known: Vec<String>,
+) {
+    crash_reports::supervise("device watcher", async move {
+        loop {
+            sleep(WATCH_INTERVAL).await;
+            let watched = devices.clone();
+            let previous = known.clone();
+            // Enumeration can block for a while on some backends.
+            let polled =
+                tauri::async_runtime::spawn_blocking(move || watched.poll(&previous)).await;
+            let change = match polled {
+                Ok(Ok(Some(change))) => change,
+                Ok(Ok(None)) => continue,
+                Ok(Err(e)) => {
+                    tracing::debug!("Input device enumeration failed: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Input device watcher poll failed: {}", e);
+                    continue;
+                }
+            };
+            tracing::info!(
+                "Input devices changed: {:?} (selected {:?}, missing: {})",
+                change.devices,
+                change.selected,
+                change.selected_missing
+            );
+            known.clone_from(&change.devices);
+            events::emit_event(&app_handle, Event::InputDevicesChanged(change));
+            // A standby stream on a device that went away has failed; reopen it.
+            resume_standby(&app_handle);
+        }
+    });
+}
+
+fn stop_capture_and_return_buffer(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<AudioBuffer, String> {
+    state.live.stop();
+    state.tray_recording.set_recording(false);
+    let stopped = state.recorder.lock().stop_recording();
+    apply_queued_device(state, app_handle);
+    resume_standby(app_handle);
+    let buffer = stopped?;
+    stop_audio_level_loop(state);
+    state.announcements.announce(Announcement::RecordingStopped);
+    Ok(buffer)
+}
+
+fn stop_capture_safely(state: &AppState, app_handle: &tauri::AppHandle) {
+    state.live.stop();
+    state.tray_recording.set_recording(false);
+    let mut recorder = state.recorder.lock();
+    let was_capturing = recorder.is_recording();
+    let _ = recorder.stop_recording();
+    drop(recorder);
+    apply_queued_device(state, app_handle);
+    // Right before a recording starts; don't hold it up reopening a standby it takes over.
+    if was_capturing {
+        resume_standby(app_handle);
+    }
+    stop_audio_level_loop(state);
+}
+
+/// Open the pre-roll stream on the selected device between recordings, or close it when
+/// pre-roll is off. Opening a stream takes a moment, so it runs off the caller's thread.
+fn resume_standby(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        let device = state.devices.selected();
+        if let Err(e) = state.recorder.lock().resume_standby(device.as_deref()) {
+            tracing::warn!("No pre-roll until the next recording: {}", e);
+        }
+    });
+}
+
+/// Switch to the input device selected while the capture that just stopped was running.
+fn apply_queued_device(state: &AppState, app_handle: &tauri::AppHandle) {
+    if state.devices.apply_queued() {
+        announce_device(state, app_handle, true);
+    }
+}
+
+/// Tell the windows which device and format the next recording will use. Probing
+/// enumerates the devices, so it runs off the caller's thread.
+fn announce_device(state: &AppState, app_handle: &tauri::AppHandle, was_queued: bool) {
+    let selected = state.devices.selected();
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        match AudioCapture::probe(selected.as_deref()) {
+            Ok(info) => {
+                tracing::info!(
+                    "Input device is now '{}' ({} Hz, {} ch)",
+                    info.device_name,
+                    info.sample_rate,
+                    info.channels
+                );
+                events::emit_event(
+                    &app_handle,
+                    Event::DeviceChanged(events::DeviceChanged {
+                        selected,
+                        device_name: info.device_name,
+                        sample_rate: info.sample_rate,
+                        channels: info.channels,
+                        was_queued,
+                    }),
+                );
+            }
+            // The next recording start reports it properly.
+            Err(e) => tracing::warn!("Selected input device can't be probed: {}", e),
+        }
+    });
+}
+
+/// The global-shortcut plugin, the default hotkey backend.
+struct PluginHotkeys(tauri::AppHandle);
+
+impl HotkeyBackend for PluginHotkeys {
+    fn kind(&self) -> HotkeyBackendKind {
+        HotkeyBackendKind::Plugin
+    }
+
+    fn register(&self, bindings: &[HotkeyBinding]) -> Result<(), String> {
+        let shortcuts = self.0.global_shortcut();
+        shortcuts
+            .unregister_all()
+            .map_err(|e| format!("Failed to clear shortcuts: {}", e))?;
+        let mut registered = HashMap::new();
+        for binding in bindings {
+            let shortcut: Shortcut = binding
+                .accelerator
+                .parse()
+                .map_err(|e| format!("Invalid shortcut '{}': {}", binding.accelerator, e))?;
+            shortcuts.register(shortcut).map_err(|e| {
+                format!(
+                    "Failed to register shortcut '{}' ({}): {}",
+                    binding.accelerator,
+                    binding.action.label(),
+                    e
+                )
+            })?;
+            registered.insert(shortcut.id(), binding.action);
+        }
+        *lock_or_recover(&self.0.state::<AppState>().hotkeys, "hotkeys") = registered;
+        Ok(())
+    }
+
+    fn unregister_all(&self) {
+        if let Err(e) = self.0.global_shortcut().unregister_all() {
+            tracing::warn!("Failed to clear shortcuts: {}", e);
+        }
+    }
+}
+
+/// Register with the plugin, or on Linux, when it can't, the portal or evdev fallback.
+fn register_hotkeys(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    bindings: &[HotkeyBinding],
+    evdev_enabled: bool,
+) -> Result<(), String> {
+    let plugin = app_handle.clone();
+    let sink_handle = app_handle.clone();
+    let sink: hotkey_backend::ActionSink =
+        Arc::new(move |action| run_hotkey_action(&sink_handle, action));
+    let mut candidates: Vec<(HotkeyBackendKind, hotkey_backend::Connect)> = vec![(
+        HotkeyBackendKind::Plugin,
+        Box::new(move || Ok(Box::new(PluginHotkeys(plugin)) as Box<dyn HotkeyBackend>)),
+    )];
+    candidates.extend(hotkey_backend::fallbacks(sink, evdev_enabled));
+    state
+        .hotkey_backend
+        .register(bindings, candidates)
+        .map(|_| ())
+}
+
+fn handle_hotkey(app: &tauri::AppHandle, shortcut: &Shortcut) {
+    let action = lock_or_recover(&app.state::<AppState>().hotkeys, "hotkeys")
+        .get(&shortcut.id())
+        .copied();
+    match action {
+        Some(action) => run_hotkey_action(app, action),
+        None => tracing::warn!("Unknown shortcut {} pressed", shortcut.into_string()),
+    }
+}
+
+fn run_hotkey_action(app: &tauri::AppHandle, action: HotkeyAction) {
+    let state = app.state::<AppState>();
+    if !events::hotkey_toggle_allowed(&state.setup_completed) {
+        return;
+    }
+    match action {
+        HotkeyAction::ToggleRecording => request_toggle(app),
+        HotkeyAction::StopRecording => events::emit_event(app, Event::StopRecording),
+        HotkeyAction::PasteLast => paste_last_transcript(app),
+    }
+}
+
+/// Start or stop recording the way the shortcut does: the pill is shown and toggles.
+fn toggle_recording(app: &tauri::AppHandle) {
+    if let Some(main_window) = app.get_webview_window("main") {
+        if let Ok(false) = main_window.is_visible() {
+            // Before showing, so the pill never flashes on the old monitor.
+            follow_cursor_monitor(app, &app.state::<AppState>());
+            // Shown only: focusing the pill would take keyboard focus from the paste target
+            let _ = main_window.show();
+        }
+    }
+    events::emit_event(app, Event::ToggleRecording);
+}
+
+/// Brings the pill to the cursor's monitor, unless the user pinned it in Settings.
+fn follow_cursor_monitor(app: &tauri::AppHandle, state: &AppState) {
+    if state.pill_follows_monitor.load(Ordering::Relaxed) {
+        pill::follow_cursor_monitor(app);
+    }
+}
+
+/// The tray's Start/Stop recording item.
+fn toggle_recording_from_tray(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    // The check mark follows the capture, not the click
+    state.tray_recording.restore_check();
+    if !events::hotkey_toggle_allowed(&state.setup_completed) {
+        return;
+    }
+    request_toggle(app);
+}
+
+/// Toggle through the transition queue, so the shortcut can't race a start or stop sent
+/// from a window.
+fn request_toggle(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        if let Err(e) = state.transitions.request(Transition::Toggle).await {
+            tracing::info!("Ignored the recording toggle: {}", e);
+        }
+    });
+}
+
+/// Paste the last dictation again into the window in front, without recording.
+fn paste_last_transcript(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    if state.audio_level_flag.load(Ordering::Relaxed) {
+        // The paste target belongs to the running recording.
+        tracing::info!("Ignoring paste-last shortcut while recording");
+        return;
+    }
+    let Some(text) = lock_or_recover(&state.last_transcript, "last transcript").clone() else {
+        tracing::info!("Paste-last shortcut pressed before any dictation");
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        lock_or_recover(&state.paste_context, "paste context").capture_target();
+        if let Err(e) = paste_text(state.inner(), app.clone(), text).await {
+            tracing::warn!("Failed to paste the last transcript: {}", e);
+        }
+    });
+}
+
+fn apply_runtime_config(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    config: &AppConfig,
+) -> Result<(), String> {
+    let resolver = paths::Resolver::for_app(app_handle)?;
+    let provider_setup = config::provider_setup(config, &resolver);
+    let groq_key_set = provider_setup.groq_key().is_some();
+    if !groq_key_set {
+        tracing::warn!("Groq API key missing or invalid in local config");
+    }
+    state.groq_key_set.store(groq_key_set, Ordering::Relaxed);
+
+    if config.session.capture_source == presets::CaptureSource::Mixed {
+        tracing::warn!("System audio capture is not available yet; recording the microphone only");
+    }
+    if config.session.segmentation == presets::Segmentation::Continuous {
+        tracing::warn!("Continuous segmentation is not available yet; segments are cut on stop");
+    }
+
+    state.privacy.set_enabled(config.privacy_mode);
+    state.silence_gate.set(config.silence_gate);
+    state.offline.set_manual(config.offline_mode);
+    let key_status = key_health::current_status(config);
+    if state.key_health.set_status(key_status) {
+        tray::set_key_indicator(app_handle, key_status == KeyStatus::Invalid);
+    }
+    if key_status == KeyStatus::Unknown && config::decode_api_key(config).is_some() {
+        // Usually a key that was just entered; check it now rather than at the daily probe.
+        state.key_health.request_check();
+    }
+    pill::apply_focus_style(app_handle, config.pill_focusable);
+    tray::set_language_indicator(app_handle, &config.language);
+    state
+        .setup_completed
+        .store(config.setup_completed, Ordering::Relaxed);
+    state
+        .max_recording_secs
+        .store(config.max_recording_secs, Ordering::Relaxed);
+    state
+        .archive_audio
+        .store(config.audio_archive.enabled, Ordering::Relaxed);
+    state
+        .pill_follows_monitor
+        .store(config.pill_follows_active_monitor, Ordering::Relaxed);
+    state
+        .streaming_upload
+        .store(config.experimental_streaming_upload, Ordering::Relaxed);
+    if let Ok(store) = history_store::for_app(app_handle) {
+        if store
+            .mirror_to(config.mirror_history_to_folder.clone(), &state.privacy)
+            .is_some()
+        {
+            // A new folder gets all of history; that can take a while.
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                match history_store::for_app(&app_handle).and_then(|store| store.resync_mirror()) {
+                    Ok(count) => tracing::info!("Mirrored {} history items", count),
+                    Err(e) => tracing::debug!("History mirror not filled: {}", e),
+                }
+            });
+        }
+    }
+
+    {
+        let mut orchestrator = state.orchestrator.blocking_write();
+        *orchestrator = FailoverOrchestrator::from_config(&provider_setup)
+            .with_privacy(state.privacy.clone())
+            .with_key_health(state.key_health.clone())
+            .with_offline(state.offline.clone())
+            .with_usage(state.usage.clone())
+            .with_language_pin(state.language_pin.clone())
+            .with_circuit_observer(Arc::new(CircuitNotifier {
+                app: app_handle.clone(),
+            }))
+            .with_progress_sink(Arc::new(events::TranscriptionProgressEmitter::new(
+                app_handle.clone(),
+            )));
+    }
+    {
+        let mut engine = state.prompt_engine.blocking_lock();
+        engine.set_mode(config.optimization_mode.clone());
+        if engine.reload_llm(&config::llm_keys(config)) {
+            tracing::info!("LLM keys changed; rebuilt the LLM providers");
+        }
+    }
+
+    state.live.set_enabled(config.live_transcription);
+    state.live.set_warmup(config.session_warmup);
+    {
+        let mut recorder = state.recorder.lock();
+        recorder.set_noise_suppression(config.noise_suppression);
+        recorder.set_input_gain(config.input_gain);
+        recorder.set_pre_roll(config.pre_roll_ms);
+    }
+    state.announcements.configure(
+        config.a11y_announcements,
+        announce::Language::from_setting(&config.language),
+    );
+    state.devices.configure(config.input_device_name.clone());
+    if !state.devices.selected_available() {
+        if let Some(default_device) = state.devices.default_input_device_name() {
+            state.devices.select(Some(default_device.clone()));
+            tracing::info!("Using system default input device '{}'", default_device);
+        }
+    }
+    resume_standby(app_handle);
+
+    let bindings = hotkeys::bindings(&config.hotkey, &config.hotkeys).unwrap_or_else(|e| {
+        tracing::warn!("Only registering the recording shortcut: {}", e);
+        hotkeys::bindings(&config.hotkey, &Default::default()).unwrap_or_default()
+    });
+    register_hotkeys(app_handle, state, &bindings, config.evdev_hotkeys)
+}
+
+/// Carry out one transition for the [`TransitionQueue`]; dictations start, stop and
+/// cancel nowhere else.
+async fn run_transition(app_handle: tauri::AppHandle, transition: Transition) -> TransitionResult {
+    let state = app_handle.state::<AppState>();
+    match transition {
+        Transition::Toggle => {
+            toggle_recording(&app_handle);
+            Ok(TransitionReply::Toggled)
         }
+        Transition::Start => begin_recording(state.inner(), &app_handle)
+            .map(TransitionReply::Started)
+            .map_err(TransitionError::StartFailed),
+        Transition::Stop => end_recording(state.inner(), &app_handle)
+            .map(TransitionReply::Stopped)
+            .map_err(TransitionError::Failed),
+        Transition::Cancel => discard_recording(state.inner(), &app_handle)
+            .await
+            .map(|()| TransitionReply::Cancelled)
+            .map_err(TransitionError::Failed),
+    }
+}
+
+#[tauri::command]
+async fn start_recording(state: State<'_, AppState>) -> Result<CaptureInfo, StartRecordingError> {
+    Ok(state.transitions.start().await?)
+}
+
+#[tauri::command]
+async fn stop_recording(state: State<'_, AppState>) -> Result<AudioBuffer, String> {
+    Ok(state.transitions.stop().await?)
+}
+
+/// Discard the recording in progress. Segments already transcribed in the current session
+/// are kept; a session with nothing transcribed yet is dropped.
+#[tauri::command]
+async fn cancel_recording(state: State<'_, AppState>) -> Result<(), String> {
+    state.transitions.request(Transition::Cancel).await?;
+    Ok(())
+}
+
+fn begin_recording(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<CaptureInfo, StartRecordingError> {
+    if !state.groq_key_set.load(Ordering::Relaxed) {
+        return Err(StartRecordingError {
+            code: "groq_key_missing",
+            error_code: ErrorCode::SttNoKey,
+            message: "Groq API key missing or invalid. Configure it in Setup/Settings.".to_string(),
+            device: None,
+        });
+    }
+    if !state.privacy.is_enabled() && state.key_health.status() == KeyStatus::Invalid {
+        return Err(StartRecordingError {
+            code: "key_invalid",
+            error_code: ErrorCode::SttAuth,
+            message: "Groq rejected the saved API key. Update it in Settings.".to_string(),
+            device: None,
+        });
+    }
+
+    // Ensure monitor capture (setup step 4) never competes with real recording capture.
+    stop_capture_safely(state, app_handle);
+    follow_cursor_monitor(app_handle, state);
+    Ok(start_capture(state, app_handle, true)?)
+}
+
+fn end_recording(state: &AppState, app_handle: &tauri::AppHandle) -> Result<AudioBuffer, String> {
+    let auto_stopped = lock_or_recover(&state.auto_stopped, "auto-stopped audio").take();
+    let buffer = match auto_stopped {
+        Some(buffer) => buffer,
+        None => stop_capture_and_return_buffer(state, app_handle)?,
+    };
+    if state.archive_audio.load(Ordering::Relaxed) {
+        *lock_or_recover(&state.last_recording, "last recording") = Some(buffer.clone());
+    }
+    Ok(buffer)
+}
+
+async fn discard_recording(state: &AppState, app_handle: &tauri::AppHandle) -> Result<(), String> {
+    state.live.stop();
+    state.tray_recording.set_recording(false);
+    let cancelled = state.recorder.lock().cancel_recording();
+    apply_queued_device(state, app_handle);
+    resume_standby(app_handle);
+    cancelled?;
+    stop_audio_level_loop(state);
+    lock_or_recover(&state.paste_context, "paste context").clear_target();
+    lock_or_recover(&state.auto_stopped, "auto-stopped audio").take();
+    lock_or_recover(&state.last_recording, "last recording").take();
+
+    let mut stitcher = state.session_stitcher.lock().await;
+    let progress = stitcher.get_progress();
+    if progress.segment_count == 0 && progress.transcribing_segment_ids.is_empty() {
+        let _ = stitcher.discard_session();
     }
+    tracing::info!("Recording cancelled");
+    Ok(())
+}
+
+#[tauri::command]
+fn start_mic_monitor(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<CaptureInfo, String> {
+    start_capture(state.inner(), &app_handle, false).map_err(|e| e.error_code().tag(e))
+}
+
+#[tauri::command]
+fn stop_mic_monitor(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    stop_capture_safely(state.inner(), &app_handle);
+    Ok(())
+}
+
+/// Samples the current or last recording lost because the capture ring was full.
+#[tauri::command]
+fn get_capture_quality(state: State<'_, AppState>) -> CaptureQuality {
+    state.recorder.lock().capture_quality()
+}
+
+#[tauri::command]
+fn get_microphone_info(state: State<'_, AppState>) -> Result<MicrophoneInfo, String> {
+    let selected = state.devices.selected();
+    let selected_available = state.devices.selected_available();
+    let default_name = state.devices.default_input_device_name();
+
+    let (available, name) = match selected {
+        Some(selected_name) if selected_available => (true, Some(selected_name)),
+        Some(_) => (default_name.is_some(), default_name),
+        None => (default_name.is_some(), default_name),
+    };
+
+    Ok(MicrophoneInfo { available, name })
+}
+
+#[tauri::command]
+fn list_input_devices(state: State<'_, AppState>) -> Result<InputDevicesResponse, String> {
+    Ok(InputDevicesResponse {
+        devices: state.devices.refresh()?,
+        selected: state.devices.selected(),
+    })
+}
 
-    start_audio_level_loop(state, app_handle.clone(), level);
+/// Select the input device. It takes effect at once, or, during a recording, once that
+/// recording stops; `device-changed` reports the new format either way.
+#[tauri::command]
+fn select_input_device(
+    name: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    // Held so the capture can't stop between the check and the queueing.
+    let recorder = state.recorder.lock();
+    let applied = state.devices.request(name, recorder.is_recording());
+    drop(recorder);
+    if applied {
+        announce_device(&state, &app_handle, false);
+        resume_standby(&app_handle);
+    } else {
+        tracing::info!("Input device change queued until the recording stops");
+    }
     Ok(())
 }
 
-fn stop_capture_and_return_buffer(state: &AppState) -> Result<AudioBuffer, String> {
-    let mut recorder = state.recorder.lock().map_err(|e| e.to_string())?;
-    let buffer = recorder.stop_recording().map_err(|e| e.to_string())?;
-    drop(recorder);
-    stop_audio_level_loop(state);
-    Ok(buffer)
+#[tauri::command]
+async fn transcribe_audio(
+    audio: AudioBuffer,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<stt::Transcript, String> {
+    async {
+        // A short accidental hotkey press shouldn't cost a provider request.
+        if let Some(gated) = state.silence_gate.get().check(&audio) {
+            report_too_short(&app_handle, &state, &gated);
+            return Ok(gated);
+        }
+        let orchestrator = state.orchestrator.read().await;
+        let transcript = zentra_core::session::chunking::transcribe_long(&orchestrator, &audio)
+            .await
+            .map_err(|e| describe_orchestrator_error(&e));
+        announce_transcript(&state, transcript.as_ref().map(|t| t.text.as_str()));
+        if let Ok(transcript) = &transcript {
+            suggest_language(&app_handle, transcript.language.as_deref());
+        }
+        transcript
+    }
+    .instrument(state.dictation.span())
+    .await
+}
+
+/// Count the language a dictation was detected in, and offer to switch once most recent
+/// dictations disagree with the configured one.
+fn suggest_language(app_handle: &tauri::AppHandle, detected: Option<&str>) {
+    let Some(detected) = detected else {
+        return;
+    };
+    let suggestion = match config::observe_detected_language(app_handle, detected) {
+        Ok(Some(suggestion)) => suggestion,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::debug!("Detected language not counted: {}", e);
+            return;
+        }
+    };
+    tracing::info!(
+        "{} of the last {} dictations disagreed with language '{}'; suggesting '{}'",
+        suggestion.mismatches,
+        suggestion.window,
+        suggestion.configured,
+        suggestion.suggested
+    );
+    notify(
+        app_handle,
+        "Wrong dictation language?",
+        &format!(
+            "{} of your last {} dictations sounded like {}. Switch the language in settings, or dismiss to keep {}.",
+            suggestion.mismatches,
+            suggestion.window,
+            language_name(&suggestion.suggested),
+            language_name(&suggestion.configured)
+        ),
+    );
+    events::emit_event(app_handle, Event::LanguageMismatchSuggestion(suggestion));
+}
+
+fn language_name(setting: &str) -> &'static str {
+    match setting {
+        "pt" => "Portuguese",
+        "en" => "English",
+        _ => "another language",
+    }
+}
+
+/// Switch to the language `language-mismatch-suggestion` offered. Returns the new language.
+#[tauri::command]
+fn accept_language_suggestion(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let language = config::take_language_suggestion(&app_handle)?
+        .ok_or_else(|| "There is no language suggestion to accept".to_string())?;
+    apply_language(&app_handle, &language)?;
+    Ok(language)
+}
+
+/// Keep the configured language; it won't be questioned again until it changes.
+#[tauri::command]
+fn dismiss_language_suggestion(app_handle: tauri::AppHandle) -> Result<(), String> {
+    config::dismiss_language_suggestion(&app_handle)
+}
+
+/// Transcribe an audio file on disk, post-process it and add it to history.
+async fn transcribe_file_at(app_handle: &tauri::AppHandle, path: &Path) -> Result<String, String> {
+    if !clipboard_files::is_supported(path) {
+        return Err(format!(
+            "Unsupported file type (supported: {})",
+            clipboard_files::SUPPORTED_EXTENSIONS.join(", ")
+        ));
+    }
+    let audio = zentra_core::audio::wav::read_wav_file(path)?;
+
+    let state = app_handle.state::<AppState>();
+    let transcript = {
+        let orchestrator = state.orchestrator.read().await;
+        zentra_core::session::chunking::transcribe_long(&orchestrator, &audio)
+            .await
+            .map_err(|e| describe_orchestrator_error(&e))?
+    };
+    let options = config::post_processing_options(&config::load_or_create(app_handle)?)
+        .with_detected_language(transcript.language.as_deref());
+    let text = PostProcessor::new(options).run(&transcript.text).output;
+
+    let payload = RecordHistoryPayload {
+        text: text.clone(),
+        duration_seconds: audio.duration_secs,
+        word_count: None,
+        timestamp: None,
+        request_id: None,
+        session_stats: None,
+        provider: Some(transcript.provider.clone()),
+        audio_path: None,
+    };
+    let change = config::record_history(app_handle, payload, &state.privacy)?;
+    events::history_changed(app_handle, change);
+    Ok(text)
+}
+
+#[tauri::command]
+async fn transcribe_file(path: String, app_handle: tauri::AppHandle) -> Result<String, String> {
+    transcribe_file_at(&app_handle, Path::new(&path)).await
+}
+
+/// Show a notification unless the active settings hold them until finalize.
+fn notify(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    let suppressed = config::load_or_create(app_handle)
+        .map(|config| config.session.suppress_notifications)
+        .unwrap_or(false);
+    if suppressed {
+        tracing::debug!("Notification suppressed: {}", title);
+        return;
+    }
+    show_notification(app_handle, title, body);
+}
+
+/// Tells the windows, and the user through a notification, when a provider's circuit
+/// breaker opens or closes.
+struct CircuitNotifier {
+    app: tauri::AppHandle,
+}
+
+impl CircuitObserver for CircuitNotifier {
+    fn circuit_changed(&self, event: CircuitEvent) {
+        match event {
+            CircuitEvent::Opened {
+                provider,
+                cooldown_secs,
+                recent_errors,
+            } => {
+                notify(
+                    &self.app,
+                    &format!("Provider {} paused", provider),
+                    &format!(
+                        "It failed {} times in a row. Using the fallback providers for {} min",
+                        recent_errors.len(),
+                        cooldown_secs.div_ceil(60)
+                    ),
+                );
+                events::emit_event(
+                    &self.app,
+                    Event::ProviderCircuitOpened(events::ProviderCircuitOpened {
+                        provider,
+                        cooldown_secs,
+                        recent_errors,
+                    }),
+                );
+            }
+            CircuitEvent::Closed { provider } => {
+                notify(
+                    &self.app,
+                    &format!("Provider {} is back", provider),
+                    "Dictations use it again",
+                );
+                events::emit_event(
+                    &self.app,
+                    Event::ProviderCircuitClosed(events::ProviderCircuitClosed { provider }),
+                );
+            }
+        }
+    }
+}
+
+/// Reads announcements with VoiceOver on macOS. Elsewhere they are notifications, which
+/// Narrator and Orca read out.
+struct SystemAnnouncer(tauri::AppHandle);
+
+impl announce::Announcer for SystemAnnouncer {
+    fn announce(&self, text: &str) {
+        #[cfg(target_os = "macos")]
+        {
+            let text = text.to_string();
+            if let Err(e) = self
+                .0
+                .run_on_main_thread(move || announce::post_voiceover(&text))
+            {
+                tracing::warn!("Failed to post a VoiceOver announcement: {}", e);
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        show_notification(&self.0, "Zentra", text);
+    }
+}
+
+/// Announce a dictation's transcript, or its failure.
+fn announce_transcript<E>(state: &AppState, transcript: Result<&str, E>) {
+    state.announcements.announce(match transcript {
+        Ok(text) => Announcement::TranscriptReady {
+            words: text.split_whitespace().count(),
+        },
+        Err(_) => Announcement::TranscriptionFailed,
+    });
+}
+
+fn show_notification(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        tracing::warn!("Failed to show notification '{}': {}", title, e);
+    }
+}
+
+fn file_label(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Tray action: transcribe every supported audio file the clipboard references, one after
+/// another, with a notification per result.
+fn transcribe_clipboard_audio(app_handle: &tauri::AppHandle) {
+    let native_files = clipboard_files::read_file_list();
+    let text = if native_files.is_empty() {
+        app_handle.clipboard().read_text().ok()
+    } else {
+        None
+    };
+
+    let (files, skipped) = match clipboard_files::classify(native_files, text.as_deref()) {
+        clipboard_files::ClipboardAudio::NoFiles => {
+            notify(
+                app_handle,
+                "Nothing to transcribe",
+                "The clipboard does not contain an audio file.",
+            );
+            return;
+        }
+        clipboard_files::ClipboardAudio::Unsupported(files) => (Vec::new(), files),
+        clipboard_files::ClipboardAudio::Files { files, skipped } => (files, skipped),
+    };
+
+    if !skipped.is_empty() {
+        let names: Vec<String> = skipped.iter().map(|path| file_label(path)).collect();
+        notify(
+            app_handle,
+            "Unsupported audio format",
+            &format!(
+                "Skipped {} (supported: {})",
+                names.join(", "),
+                clipboard_files::SUPPORTED_EXTENSIONS.join(", ")
+            ),
+        );
+    }
+    if files.is_empty() {
+        return;
+    }
+
+    tracing::info!("Transcribing {} file(s) from the clipboard", files.len());
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        for file in files {
+            let label = file_label(&file);
+            match transcribe_file_at(&app_handle, &file).await {
+                Ok(text) if text.trim().is_empty() => {
+                    notify(&app_handle, &label, "No speech found in the recording.")
+                }
+                // Notifications stay in the system's notification center, so privacy mode
+                // hands the text over on the clipboard instead; history isn't kept either.
+                Ok(text) if app_handle.state::<AppState>().privacy.is_enabled() => {
+                    let body = match app_handle.clipboard().write_text(text) {
+                        Ok(()) => "Copied to the clipboard.",
+                        Err(e) => {
+                            tracing::warn!("Could not copy the transcript of {}: {}", label, e);
+                            "Done, but the text could not be copied to the clipboard."
+                        }
+                    };
+                    notify(&app_handle, &format!("Transcribed {}", label), body)
+                }
+                Ok(text) => notify(
+                    &app_handle,
+                    &format!("Transcribed {}", label),
+                    &snippet::snippet(&text, 120),
+                ),
+                Err(e) => {
+                    tracing::warn!("Clipboard transcription of {} failed: {}", label, e);
+                    notify(&app_handle, &format!("Could not transcribe {}", label), &e);
+                }
+            }
+        }
+    });
+}
+
+/// Start a recording session; with `recovery_id` (from `get_recoverable_session`) resume
+/// that session's transcribed segments instead.
+#[tauri::command]
+async fn start_recording_session(
+    recovery_id: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let config = config::load_or_create(&app_handle)?;
+    let mut stitcher = state.session_stitcher.lock().await;
+    stitcher.set_max_session_minutes(config.session.max_session_minutes);
+    stitcher
+        .start_session(recovery_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn add_audio_segment(
+    audio: AudioBuffer,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SegmentResult, String> {
+    async {
+        let mut stitcher = state.session_stitcher.lock().await;
+        let result = stitcher.add_segment(audio).map_err(|e| e.to_string())?;
+        drop(stitcher);
+        if let Some(transcript) = &result.transcript {
+            report_too_short(&app_handle, &state, transcript);
+        }
+        Ok(result)
+    }
+    .instrument(state.dictation.span())
+    .await
+}
+
+/// Start a session and a recording that cuts itself into segments at pauses, so a long
+/// dictation is transcribed while it is still being spoken. Each cut goes into the session
+/// and is announced with `session:segment-cut`; stop as usual and add the audio
+/// `stop_recording` returns as the last segment.
+#[tauri::command]
+async fn start_auto_session(
+    recovery_id: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<CaptureInfo, StartRecordingError> {
+    {
+        let mut stitcher = state.session_stitcher.lock().await;
+        match config::load_or_create(&app_handle) {
+            Ok(config) => stitcher.set_max_session_minutes(config.session.max_session_minutes),
+            Err(e) => tracing::warn!("Keeping the previous session limit: {}", e),
+        }
+        stitcher.start_session(recovery_id.as_deref()).await?;
+    }
+    let info = state.transitions.start().await?;
+    state.live.stop_partials();
+    let generation = state.capture_generation.load(Ordering::Relaxed);
+    spawn_auto_splitter(app_handle, generation);
+    Ok(info)
+}
+
+/// Watch the running recording for the next cut (see [`autosplit::next_cut`]) and hand
+/// the audio before it to the session. Ends when the recording it was started for stops.
+fn spawn_auto_splitter(app_handle: tauri::AppHandle, generation: u64) {
+    let state = app_handle.state::<AppState>();
+    let recorder = state.recorder.clone();
+    let live_buffer = recorder.lock().live_buffer();
+    let span = state.dictation.span();
+    let task = async move {
+        // In streaming mode, the upload of the segment being recorded
+        let mut stream = StreamedCut::default();
+        loop {
+            sleep(autosplit::CHECK_INTERVAL).await;
+            let state = app_handle.state::<AppState>();
+            if state.capture_generation.load(Ordering::Relaxed) != generation {
+                return;
+            }
+            let cut = if state.streaming_upload.load(Ordering::Relaxed) {
+                stream.next_cut(&state, &live_buffer).await
+            } else {
+                // Switched off mid-segment: the upload can't match a cut at a pause
+                stream = StreamedCut::default();
+                autosplit::next_cut(&lock_or_recover(&live_buffer, "capture buffer"))
+            };
+            let Some(cut) = cut else {
+                continue;
+            };
+            // Cut under the session lock, so the audio a stop hands back meanwhile can't
+            // be added ahead of this segment.
+            let mut stitcher = state.session_stitcher.lock().await;
+            if state.capture_generation.load(Ordering::Relaxed) != generation {
+                return;
+            }
+            let Some(segment) = recorder.lock().take_snapshot_and_reset(cut) else {
+                return;
+            };
+            let duration_secs = segment.duration_secs;
+            let added = match stream.take() {
+                Some(upload) => stitcher.add_streamed_segment(segment, upload),
+                None => stitcher.add_segment(segment),
+            };
+            drop(stitcher);
+            match added {
+                Ok(result) => {
+                    tracing::info!("Cut a {:.1}s segment off the recording", duration_secs);
+                    events::emit_event(
+                        &app_handle,
+                        Event::SessionSegmentCut(events::SegmentCut {
+                            segment_id: result.segment_id,
+                            duration_secs,
+                            request_id: state.dictation.current(),
+                        }),
+                    );
+                }
+                Err(e) => tracing::warn!("Dropped a {:.1}s cut: {}", duration_secs, e),
+            }
+        }
+    };
+    tauri::async_runtime::spawn(task.instrument(span));
+}
+
+/// The segment an auto session records in streaming mode; see
+/// [`zentra_core::session::streamed`]. Dropping it cancels the upload.
+#[derive(Default)]
+struct StreamedCut {
+    upload: Option<SegmentStream>,
+    /// The segment's upload failed; it is sent buffered once cut
+    failed: bool,
+}
+
+impl StreamedCut {
+    /// Start the upload once enough of the segment is recorded, feed it what arrived since
+    /// the last check, and return the cut once the whole segment is in. Denoising needs
+    /// the whole segment, so with noise suppression on segments are only cut here and go
+    /// up buffered.
+    async fn next_cut(
+        &mut self,
+        state: &AppState,
+        live_buffer: &Mutex<AudioBuffer>,
+    ) -> Option<usize> {
+        let (recorded, sample_rate, channels) = {
+            let guard = lock_or_recover(live_buffer, "capture buffer");
+            (guard.samples.len(), guard.sample_rate, guard.channels)
+        };
+        let len = streamed::segment_len(sample_rate, channels);
+        if len == 0 || recorded < streamed::stream_start(len) {
+            return None;
+        }
+
+        if self.upload.is_none() && !self.failed {
+            let denoised = state.recorder.lock().noise_suppression();
+            if !denoised && state.orchestrator.read().await.can_stream() {
+                self.upload = Some(SegmentStream::start(
+                    state.orchestrator.clone(),
+                    len,
+                    sample_rate,
+                    channels,
+                ));
+            }
+        }
+        if let Some(upload) = self.upload.as_mut() {
+            if let Err(e) = upload.feed_from(live_buffer).await {
+                tracing::warn!("Streaming upload failed, the segment goes buffered: {}", e);
+                self.upload = None;
+                self.failed = true;
+            }
+        }
+        (recorded >= len).then_some(len)
+    }
+
+    /// The upload of the segment just cut, if it is still going.
+    fn take(&mut self) -> Option<SegmentStream> {
+        self.failed = false;
+        self.upload.take()
+    }
+}
+
+/// Tell the pill a recording was dropped for being below the minimum duration. Emitted
+/// here only, once per recording, so the pill never reports it as an error too.
+fn report_too_short(app_handle: &tauri::AppHandle, state: &AppState, transcript: &stt::Transcript) {
+    if transcript.provider != TOO_SHORT_PROVIDER {
+        return;
+    }
+    events::emit_event(
+        app_handle,
+        Event::RecordingTooShort(events::RecordingTooShort {
+            duration_secs: transcript.duration_secs,
+            min_duration_secs: state.silence_gate.get().min_duration_secs,
+            request_id: state.dictation.current(),
+        }),
+    );
+}
+
+/// Wait for the segments still being transcribed; the progress then lists any to retry.
+#[tauri::command]
+async fn wait_for_session_segments(state: State<'_, AppState>) -> Result<SessionProgress, String> {
+    let mut stitcher = state.session_stitcher.lock().await;
+    stitcher
+        .wait_for_segments()
+        .instrument(state.dictation.span())
+        .await;
+    Ok(stitcher.get_progress())
+}
+
+/// Transcribe a failed segment of the current session again from its retained audio.
+#[tauri::command]
+async fn retry_segment(
+    segment_id: String,
+    state: State<'_, AppState>,
+) -> Result<SegmentResult, String> {
+    let mut stitcher = state.session_stitcher.lock().await;
+    stitcher
+        .retry_segment(&segment_id)
+        .instrument(state.dictation.span())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn finalize_recording_session(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<StitchedResult, String> {
+    async {
+        let mut stitcher = state.session_stitcher.lock().await;
+        let finalized = stitcher.finalize_session().await;
+        drop(stitcher);
+        if finalized.is_err() {
+            crash_reports::set_phase(AppPhase::Idle);
+        }
+        announce_transcript(
+            &state,
+            finalized.as_ref().map(|result| result.full_text.as_str()),
+        );
+        let mut result = finalized.map_err(|e| e.to_string())?;
+        let config = config::load_or_create(&app_handle)?;
+        let options = config::post_processing_options(&config);
+        result.full_text = tracing::info_span!("post_process")
+            .in_scope(|| PostProcessor::new(options).run(&result.full_text).output);
+        Ok(result)
+    }
+    .instrument(state.dictation.span())
+    .await
+}
+
+/// Run the post actions in effect (the active preset's, else the user's) on a finalized
+/// transcript: history, paste or copy, notes, webhook.
+#[tauri::command]
+async fn run_post_actions(
+    text: String,
+    duration_seconds: f32,
+    session_stats: Option<SessionStats>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<ProcessedResult, String> {
+    crash_reports::set_phase(AppPhase::PostActions);
+    let processed = async {
+        let config = config::load_or_create(&app_handle)?;
+        let actions =
+            post_actions::effective(&config.post_actions, config.session.post_actions.as_deref())
+                .to_vec();
+        let mut effects = AppPostActions {
+            app_handle: &app_handle,
+            state: state.inner(),
+            config: &config,
+            duration_seconds,
+            session_stats,
+        };
+        let result = post_actions::run(&actions, &text, &mut effects, &state.privacy).await;
+        if result.paste.as_ref().is_some_and(|attempt| attempt.pasted) {
+            state.announcements.announce(Announcement::Pasted);
+        }
+        if !result.text.trim().is_empty() {
+            *lock_or_recover(&state.last_transcript, "last transcript") = Some(result.text.clone());
+        }
+        Ok(result)
+    }
+    .instrument(state.dictation.span())
+    .await;
+    crash_reports::set_phase(AppPhase::Idle);
+    processed
+}
+
+struct AppPostActions<'a> {
+    app_handle: &'a tauri::AppHandle,
+    state: &'a AppState,
+    config: &'a AppConfig,
+    duration_seconds: f32,
+    session_stats: Option<SessionStats>,
+}
+
+impl PostActionEffects for AppPostActions<'_> {
+    fn clarity(&mut self, text: &str) -> String {
+        zentra_core::prompt_engine::clarity::transform(text, &self.config.language)
+    }
+
+    async fn optimize(&mut self, text: &str, profile: &str) -> Result<(String, String), String> {
+        let optimized = self
+            .state
+            .prompt_engine
+            .lock()
+            .await
+            .optimize(text, profile.trim(), &self.config.language)
+            .await
+            .map_err(|e| ErrorCode::from(&e).tag(e))?;
+        let provider = optimized.provider.unwrap_or_else(|| "clarity".to_string());
+        Ok((optimized.text, provider))
+    }
+
+    async fn paste(&mut self, text: &str) -> Result<paste::PasteAttempt, String> {
+        paste_text(self.state, self.app_handle.clone(), text.to_string()).await
+    }
+
+    fn copy(&mut self, text: &str) -> Result<(), String> {
+        use paste::ClipboardText;
+        AppClipboard(self.app_handle.clone()).write_text(text)
+    }
+
+    /// Append to the dated Markdown file in the notes folder, named after the active preset.
+    /// This is also where notifications held back during the session are delivered.
+    fn save_to_notes(&mut self, text: &str) -> Result<String, String> {
+        save_session_notes(self.app_handle, self.config, text)
+    }
+
+    async fn webhook(&mut self, text: &str) -> Result<(), String> {
+        let context = integrations::TemplateContext {
+            text: text.to_string(),
+            language: self.config.language.clone(),
+            duration_seconds: self.duration_seconds,
+            recorded_at: chrono::Local::now(),
+        };
+        integrations::send_webhook(&self.config.integrations.webhook, &context).await
+    }
+
+    fn record_history(&mut self, text: &str) -> Result<(), String> {
+        let audio_path = self.archive_recording();
+        let payload = RecordHistoryPayload {
+            text: text.to_string(),
+            duration_seconds: self.duration_seconds,
+            word_count: None,
+            timestamp: None,
+            request_id: self.state.dictation.current(),
+            session_stats: self.session_stats.clone(),
+            provider: None,
+            audio_path: audio_path.clone(),
+        };
+        let recorded = config::record_history(self.app_handle, payload, &self.state.privacy);
+        if !matches!(&recorded, Ok(change) if change.added > 0) {
+            if let Some(path) = audio_path {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+        events::history_changed(self.app_handle, recorded?);
+        Ok(())
+    }
+}
+
+impl AppPostActions<'_> {
+    /// Write the dictation's audio to the archive when it is on. An archive failure only
+    /// costs the recording; the transcript is still saved.
+    fn archive_recording(&self) -> Option<String> {
+        let audio = lock_or_recover(&self.state.last_recording, "last recording").take()?;
+        if !self.config.audio_archive.enabled || !self.state.privacy.allows_persistence() {
+            return None;
+        }
+        let archived = paths::Resolver::for_app(self.app_handle)
+            .and_then(|resolver| audio_archive::archive(&resolver.audio_archive_dir(), &audio));
+        match archived {
+            Ok(path) => Some(path.display().to_string()),
+            Err(e) => {
+                tracing::warn!("Recording not archived: {}", e);
+                None
+            }
+        }
+    }
+}
+
+fn save_session_notes(
+    app_handle: &tauri::AppHandle,
+    config: &AppConfig,
+    text: &str,
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to save".to_string());
+    }
+    let label = config
+        .active_preset
+        .as_ref()
+        .and_then(|active| {
+            config
+                .presets
+                .iter()
+                .find(|preset| preset.name == active.name)
+        })
+        .map_or("Session", |preset| preset.label.as_str());
+
+    let exported = paths::Resolver::for_app(app_handle).and_then(|resolver| {
+        presets::export_markdown(&resolver.notes_dir(), label, text, chrono::Local::now())
+    });
+    match exported {
+        Ok(path) => {
+            let path = path.display().to_string();
+            show_notification(app_handle, &format!("{} notes saved", label), &path);
+            Ok(path)
+        }
+        Err(e) => {
+            tracing::error!("Markdown export failed: {}", e);
+            show_notification(app_handle, "Could not save notes", &e);
+            Err(e)
+        }
+    }
+}
+
+/// Run the live post-processing chain over `text`, stage by stage, without dictating.
+#[tauri::command]
+fn preview_post_processing(
+    text: String,
+    options: Option<OverrideOptions>,
+    app_handle: tauri::AppHandle,
+) -> Result<PostProcessingPreview, String> {
+    let persisted = config::post_processing_options(&config::load_or_create(&app_handle)?);
+    let options = options.unwrap_or_default().apply(persisted);
+    Ok(PostProcessor::new(options).run(&text))
+}
+
+/// Replay a recorded fixture bundle through the current post-STT pipeline and diff each
+/// stage against the recording. Not used by any window; for QA from the devtools console.
+#[tauri::command]
+async fn replay_fixture(path: String) -> Result<ReplayReport, String> {
+    let bundle = FixtureBundle::load(Path::new(&path)).map_err(|e| e.to_string())?;
+    let report = replay::replay(&bundle).await.map_err(|e| e.to_string())?;
+    tracing::info!("{}", report.summary);
+    Ok(report)
+}
+
+#[tauri::command]
+async fn list_prompt_profiles(state: State<'_, AppState>) -> Result<Vec<Profile>, String> {
+    let engine = state.prompt_engine.lock().await;
+    Ok(engine.list_profiles().into_iter().cloned().collect())
+}
+
+/// Save a new user profile; returns every profile.
+#[tauri::command]
+async fn create_profile(
+    profile: Profile,
+    state: State<'_, AppState>,
+) -> Result<Vec<Profile>, String> {
+    let mut engine = state.prompt_engine.lock().await;
+    engine
+        .create_profile(profile)
+        .map_err(|e| ErrorCode::from(&e).tag(e))?;
+    Ok(engine.list_profiles().into_iter().cloned().collect())
+}
+
+/// Replace a profile; editing a built-in saves a user copy over it. Returns every profile.
+#[tauri::command]
+async fn update_profile(
+    profile: Profile,
+    state: State<'_, AppState>,
+) -> Result<Vec<Profile>, String> {
+    let mut engine = state.prompt_engine.lock().await;
+    engine
+        .update_profile(profile)
+        .map_err(|e| ErrorCode::from(&e).tag(e))?;
+    Ok(engine.list_profiles().into_iter().cloned().collect())
+}
+
+/// Delete a user profile, restoring the built-in it replaced. Returns every profile.
+#[tauri::command]
+async fn delete_profile(
+    id: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<Profile>, String> {
+    let mut engine = state.prompt_engine.lock().await;
+    let restored = engine
+        .delete_profile(&id)
+        .map_err(|e| ErrorCode::from(&e).tag(e))?
+        .is_some();
+    if !restored {
+        config::forget_prompt_profile(&app_handle, id.trim())?;
+    }
+    Ok(engine.list_profiles().into_iter().cloned().collect())
+}
+
+/// Switch between clarity-only and LLM optimization; saved for the next launch.
+#[tauri::command]
+async fn set_optimization_mode(
+    mode: OptimizationMode,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    config::set_optimization_mode(&app_handle, mode.clone())?;
+    state.prompt_engine.lock().await.set_mode(mode);
+    Ok(())
+}
+
+/// Optimize `text` with `profile_id`, or with the profile saved in settings.
+#[tauri::command]
+async fn optimize_transcript(
+    text: String,
+    profile_id: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<OptimizedPrompt, String> {
+    let config = config::load_or_create(&app_handle)?;
+    let profile_id = match profile_id.filter(|id| !id.trim().is_empty()) {
+        Some(id) => id,
+        None => config.prompt_profile_id,
+    };
+    let engine = state.prompt_engine.lock().await;
+    engine
+        .optimize(&text, profile_id.trim(), &config.language)
+        .instrument(state.dictation.span())
+        .await
+        .map_err(|e| ErrorCode::from(&e).tag(e))
+}
+
+#[tauri::command]
+async fn pause_recording_session(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SessionProgress, String> {
+    // Under the session lock, like an auto cut, so the tail can't be queued ahead of one.
+    let mut stitcher = state.session_stitcher.lock().await;
+    let tail = if state.recorder.lock().is_recording() {
+        stop_capture_and_return_buffer(&state, &app_handle)
+            .map_err(|e| tracing::warn!("No audio kept from before the pause: {}", e))
+            .ok()
+    } else {
+        stop_capture_safely(&state, &app_handle);
+        None
+    };
+    stitcher.pause_session(tail).map_err(|e| e.to_string())?;
+    let progress = stitcher.get_progress();
+    events::emit_event(&app_handle, Event::SessionPaused(progress.clone()));
+    Ok(progress)
+}
+
+#[tauri::command]
+async fn resume_recording_session(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SessionProgress, String> {
+    let mut stitcher = state.session_stitcher.lock().await;
+    stitcher.resume_session().map_err(|e| e.to_string())?;
+    let progress = stitcher.get_progress();
+    events::emit_event(&app_handle, Event::SessionResumed(progress.clone()));
+    Ok(progress)
+}
+
+#[tauri::command]
+async fn discard_recording_session(state: State<'_, AppState>) -> Result<(), String> {
+    let mut stitcher = state.session_stitcher.lock().await;
+    stitcher.discard_session().map_err(|e| e.to_string())
+}
+
+fn recovery_dir(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let resolver = paths::Resolver::for_app(app_handle)?;
+    Ok(resolver.base_dir().join(recovery::RECOVERY_DIR))
+}
+
+#[tauri::command]
+fn get_recoverable_sessions(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<RecoverableSession>, String> {
+    Ok(recovery::load_recoverable(&recovery_dir(&app_handle)?))
+}
+
+/// The last session that was never finalized, which `start_recording_session` can resume.
+#[tauri::command]
+fn get_recoverable_session(
+    app_handle: tauri::AppHandle,
+) -> Result<Option<RecoverableSession>, String> {
+    let dir = recovery_dir(&app_handle)?;
+    Ok(recovery::load_recoverable_session(&dir))
+}
+
+#[tauri::command]
+fn discard_recoverable_sessions(app_handle: tauri::AppHandle) -> Result<(), String> {
+    recovery::discard_recoverable(&recovery_dir(&app_handle)?)
+}
+
+#[tauri::command]
+async fn get_session_progress(state: State<'_, AppState>) -> Result<SessionProgress, String> {
+    let mut stitcher = state.session_stitcher.lock().await;
+    Ok(stitcher.get_progress())
+}
+
+/// Energy heat strip for a segment of the current session, or of the last finalized one
+/// when diagnostics capture is on. `None` when nothing was kept for that segment.
+#[tauri::command]
+async fn get_segment_diagnostics(
+    segment_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<SegmentDiagnostics>, String> {
+    let stitcher = state.session_stitcher.lock().await;
+    Ok(stitcher.segment_diagnostics(&segment_id))
+}
+
+struct AppClipboard(tauri::AppHandle);
+
+impl paste::ClipboardText for AppClipboard {
+    fn read_text(&self) -> Option<String> {
+        self.0.clipboard().read_text().ok()
+    }
+
+    fn write_text(&self, text: &str) -> Result<(), String> {
+        self.0
+            .clipboard()
+            .write_text(text.to_string())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Copy `text`, paste it into the target window and, when `restore_clipboard` is on, put
+/// the previous clipboard text back afterwards.
+#[tauri::command]
+async fn paste_transcript(
+    text: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<paste::PasteAttempt, String> {
+    paste_text(state.inner(), app_handle, text).await
+}
+
+async fn paste_text(
+    state: &AppState,
+    app_handle: tauri::AppHandle,
+    text: String,
+) -> Result<paste::PasteAttempt, String> {
+    let restore_after = config::load_or_create(&app_handle)
+        .ok()
+        .and_then(|config| config::clipboard_restore_delay(&config));
+    let windows_app = app_handle.clone();
+    let notify_app = app_handle.clone();
+    let paste_context = state.paste_context.clone();
+    let clipboard = AppClipboard(app_handle);
+    let request_id = state.dictation.current();
+    let span = state.dictation.span();
+    // Focus restoration, paste tools and the restore delay all block; keep them off the IPC
+    // thread.
+    let attempt = tauri::async_runtime::spawn_blocking(move || {
+        let _span = span.entered();
+        let mut context = lock_or_recover(&paste_context, "paste context");
+        let attempt = paste::paste_transcript(&clipboard, &text, restore_after, || {
+            // macOS pastes into the frontmost app without a captured target, so check focus
+            // here instead of comparing handles.
+            if cfg!(target_os = "macos") && zentra_window_focused(&windows_app) {
+                return paste::PasteAttempt::fallback(paste::TARGET_IS_ZENTRA);
+            }
+            let attempt = context.try_auto_paste(|| zentra_window_handles(&windows_app));
+            paste::type_if_refused(attempt, &text, paste::type_text)
+        })?;
+        tracing::info!(
+            "Paste finished: pasted={}, method={:?}, reason={:?}",
+            attempt.pasted,
+            attempt.method,
+            attempt.reason
+        );
+        Ok::<_, String>(attempt)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if attempt.reason.as_deref() == Some(paste::TARGET_IS_ZENTRA) {
+        notify(
+            &notify_app,
+            "Transcript copied",
+            "Zentra was in front, so the text is on the clipboard instead",
+        );
+    }
+    Ok(attempt.with_request_id(request_id))
+}
+
+/// Lets setup verify the grant before the first paste; `prompt` shows the macOS dialog.
+#[tauri::command]
+fn check_accessibility_permission(prompt: Option<bool>) -> paste::AccessibilityPermission {
+    paste::accessibility_permission(prompt.unwrap_or(false))
+}
+
+#[tauri::command]
+fn open_accessibility_settings() -> Result<(), String> {
+    paste::open_accessibility_settings()
+}
+
+#[tauri::command]
+fn get_setup_state(app_handle: tauri::AppHandle) -> Result<SetupState, String> {
+    let config = config::load_or_create(&app_handle)?;
+    Ok(config::setup_state(&config))
+}
+
+#[tauri::command]
+fn save_setup_partial(
+    payload: SetupPartialPayload,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let _ = config::save_setup_partial(&app_handle, payload)?;
+    Ok(())
+}
+
+/// Listen for the next modifier+key chord pressed anywhere and report it through
+/// `hotkey-captured`. Errors when the platform has no listener or lacks the permission;
+/// the setup window then captures keys itself.
+#[tauri::command]
+fn begin_hotkey_capture(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    state.hotkey_capture.begin(move |end| {
+        let payload = match end {
+            hotkey_capture::CaptureEnd::Captured(accelerator) => events::HotkeyCaptured {
+                accelerator: Some(config::normalize_hotkey(&accelerator)),
+                timed_out: false,
+            },
+            hotkey_capture::CaptureEnd::TimedOut => events::HotkeyCaptured {
+                accelerator: None,
+                timed_out: true,
+            },
+            hotkey_capture::CaptureEnd::Cancelled => return,
+        };
+        events::emit_event(&app_handle, Event::HotkeyCaptured(payload));
+    })
+}
+
+#[tauri::command]
+fn cancel_hotkey_capture(state: State<'_, AppState>) {
+    state.hotkey_capture.cancel();
+}
+
+#[tauri::command]
+fn complete_setup(
+    payload: CompleteSetupPayload,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    stop_capture_safely(state.inner(), &app_handle);
+    let config = config::complete_setup(&app_handle, payload)?;
+    apply_runtime_config(&app_handle, state.inner(), &config)?;
+
+    if let Some(setup_window) = app_handle.get_webview_window("setup") {
+        let _ = setup_window.hide();
+    }
+    // The dashboard takes focus; the pill is only an overlay
+    if let Some(main_window) = app_handle.get_webview_window("main") {
+        let _ = main_window.show();
+    }
+    let _ = tray::show_dashboard(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn validate_groq_key(api_key: String) -> Result<bool, String> {
+    if api_key.trim().is_empty() {
+        return Ok(false);
+    }
+
+    let wav_probe = build_validation_wav_probe();
+    let file_part = multipart::Part::bytes(wav_probe)
+        .file_name("probe.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| e.to_string())?;
+
+    let form = multipart::Form::new()
+        .text("model", "whisper-large-v3")
+        .text("response_format", "text")
+        .part("file", file_part);
+
+    let response = Client::new()
+        .post("https://api.groq.com/openai/v1/audio/transcriptions")
+        .bearer_auth(api_key.trim())
+        .multipart(form)
+        .timeout(std::time::Duration::from_secs(8))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.status().is_success())
+}
+
+#[tauri::command]
+async fn validate_elevenlabs_key(api_key: String) -> Result<bool, String> {
+    if !api_key.trim().starts_with("sk_") {
+        return Ok(false);
+    }
+
+    let response = Client::new()
+        .get("https://api.elevenlabs.io/v1/user")
+        .header("xi-api-key", api_key.trim())
+        .timeout(std::time::Duration::from_secs(8))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.status().is_success())
+}
+
+#[tauri::command]
+async fn validate_openrouter_key(api_key: String) -> Result<bool, String> {
+    if api_key.trim().is_empty() {
+        return Ok(false);
+    }
+
+    let response = Client::new()
+        .get("https://openrouter.ai/api/v1/key")
+        .bearer_auth(api_key.trim())
+        .timeout(std::time::Duration::from_secs(8))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.status().is_success())
+}
+
+#[tauri::command]
+fn get_startup_health(
+    storage: State<'_, config::StorageStatus>,
+    state: State<'_, AppState>,
+) -> config::StartupHealth {
+    startup_health(&storage, &state)
 }
 
-fn stop_capture_safely(state: &AppState) {
-    if let Ok(mut recorder) = state.recorder.lock() {
-        let _ = recorder.stop_recording();
+fn startup_health(storage: &config::StorageStatus, state: &AppState) -> config::StartupHealth {
+    config::StartupHealth {
+        hotkey_message: state
+            .hotkey_backend
+            .problem()
+            .map(|problem| format!("Global shortcuts are not working: {}", problem)),
+        crash_message: crash_reports::recovered_message(),
+        ..storage.health()
     }
-    stop_audio_level_loop(state);
 }
 
-fn register_hotkey(
-    app_handle: &tauri::AppHandle,
-    state: &AppState,
-    hotkey: &str,
-) -> Result<(), String> {
-    let hotkey = config::normalize_hotkey(hotkey);
-    app_handle
-        .global_shortcut()
-        .unregister_all()
-        .map_err(|e| format!("Failed to clear shortcuts: {}", e))?;
+/// Open the folder crash reports are written to.
+#[tauri::command]
+fn open_crash_folder(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let dir = crash_reports::reports_dir().ok_or("Crash reports are not set up yet")?;
+    paths::Resolver::ensure_dir(dir.to_path_buf())?;
     app_handle
-        .global_shortcut()
-        .register(hotkey.as_str())
-        .map_err(|e| format!("Failed to register shortcut '{}': {}", hotkey, e))?;
-    if let Ok(mut current) = state.hotkey.lock() {
-        *current = hotkey;
-    }
-    Ok(())
+        .opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", dir.display(), e))
 }
 
-fn apply_runtime_config(
-    app_handle: &tauri::AppHandle,
-    state: &AppState,
-    config: &AppConfig,
-) -> Result<(), String> {
-    let decoded_key = config::decode_api_key(config)
-        .map(|key| key.trim().to_string())
-        .filter(|key| key.starts_with("gsk_"));
-
-    match decoded_key {
-        Some(api_key) => std::env::set_var("GROQ_API_KEY", api_key),
-        None => {
-            std::env::remove_var("GROQ_API_KEY");
-            tracing::warn!("Groq API key missing or invalid in local config");
-        }
-    }
-
-    match config.language.trim().to_ascii_lowercase().as_str() {
-        "pt" => std::env::set_var("GROQ_STT_LANGUAGE", "pt"),
-        "en" => std::env::set_var("GROQ_STT_LANGUAGE", "en"),
-        _ => std::env::remove_var("GROQ_STT_LANGUAGE"),
-    }
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppInfo {
+    version: String,
+    /// Where the global shortcuts come from; `None` when none could be registered
+    hotkey_backend: Option<HotkeyBackendKind>,
+}
 
-    {
-        let mut orchestrator = state.orchestrator.blocking_lock();
-        *orchestrator = FailoverOrchestrator::from_env();
+#[tauri::command]
+fn get_app_info(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> AppInfo {
+    AppInfo {
+        version: app_handle.package_info().version.to_string(),
+        hotkey_backend: state.hotkey_backend.backend(),
     }
+}
 
-    {
-        let mut recorder = state.recorder.lock().map_err(|e| e.to_string())?;
-        recorder.set_selected_input_device(config.input_device_name.clone());
-        let needs_default = recorder.selected_input_device().is_none() || !recorder.selected_device_available();
-        if needs_default {
-            if let Some(default_device) = recorder.default_input_device_name() {
-                recorder.set_selected_input_device(Some(default_device.clone()));
-                tracing::info!("Using system default input device '{}'", default_device);
-            }
-        }
-    }
+/// Audio sent to each provider and its estimated cost, all time and by month.
+#[tauri::command]
+fn get_usage_stats(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<usage::UsageStats, String> {
+    let config = config::load_or_create(&app_handle)?;
+    Ok(state.usage.stats(&config::usage_rates(&config)))
+}
 
-    register_hotkey(app_handle, state, &config.hotkey)
+#[tauri::command]
+fn reset_usage_stats(state: State<'_, AppState>) {
+    state.usage.reset();
+    tracing::info!("Usage statistics reset");
 }
 
+/// Skip the cooldown of a provider whose circuit breaker opened, e.g. after fixing the
+/// network. The next dictation tries it first again.
 #[tauri::command]
-fn start_recording(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<(), String> {
-    if std::env::var("GROQ_API_KEY")
-        .ok()
-        .filter(|key| key.starts_with("gsk_"))
-        .is_none()
-    {
-        return Err("Groq API key missing or invalid. Configure it in Setup/Settings.".to_string());
+async fn force_close_circuit(
+    provider_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let orchestrator = state.orchestrator.read().await;
+    if orchestrator.force_close_circuit(&provider_id) {
+        Ok(())
+    } else {
+        Err(format!("Unknown provider '{}'", provider_id))
     }
+}
 
-    // Ensure monitor capture (setup step 4) never competes with real recording capture.
-    stop_capture_safely(state.inner());
-    start_capture(state.inner(), &app_handle, true)
+/// JSON Schema of the versioned payloads sent to webhooks, exports and the HTTP API.
+#[tauri::command]
+fn get_api_schema() -> api_types::ApiSchema {
+    api_types::api_schema()
 }
 
+/// Every error code with its severity and hint key, for the dashboard's help links.
 #[tauri::command]
-fn stop_recording(state: State<'_, AppState>) -> Result<AudioBuffer, String> {
-    stop_capture_and_return_buffer(state.inner())
+fn get_error_catalogue() -> Vec<error_codes::ErrorInfo> {
+    error_codes::catalogue()
 }
 
 #[tauri::command]
-fn start_mic_monitor(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<(), String> {
-    start_capture(state.inner(), &app_handle, false)
+fn get_dashboard_data(app_handle: tauri::AppHandle) -> Result<config::DashboardData, String> {
+    let version = app_handle.package_info().version.to_string();
+    config::dashboard_data(&app_handle, &version)
 }
 
 #[tauri::command]
-fn stop_mic_monitor(state: State<'_, AppState>) -> Result<(), String> {
-    stop_capture_safely(state.inner());
+fn record_transcription_history(
+    mut payload: RecordHistoryPayload,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let _span = state.dictation.span().entered();
+    if payload.request_id.is_none() {
+        payload.request_id = state.dictation.current();
+    }
+    let change = config::record_history(&app_handle, payload, &state.privacy)?;
+    events::history_changed(&app_handle, change);
     Ok(())
 }
 
 #[tauri::command]
-fn get_microphone_info(state: State<'_, AppState>) -> Result<MicrophoneInfo, String> {
-    let recorder = state.recorder.lock().map_err(|e| e.to_string())?;
-    let selected = recorder.selected_input_device();
-    let selected_available = recorder.selected_device_available();
-    drop(recorder);
-
-    let host = cpal::default_host();
-    let default_name = host
-        .default_input_device()
-        .and_then(|d| d.description().ok().map(|desc| desc.name().to_string()));
-
-    let (available, name) = match selected {
-        Some(selected_name) if selected_available => (true, Some(selected_name)),
-        Some(_) => (default_name.is_some(), default_name),
-        None => (default_name.is_some(), default_name),
-    };
+fn search_history(
+    search: HistorySearch,
+    app_handle: tauri::AppHandle,
+) -> Result<HistorySearchResult, String> {
+    let typing_wpm = stats::typing_wpm(config::load_or_create(&app_handle)?.typing_wpm);
+    let history = history_store::for_app(&app_handle)?.items()?;
+    Ok(config::search_history(&history, &search, typing_wpm))
+}
 
-    Ok(MicrophoneInfo { available, name })
+/// Typing speed measured from the keystroke times (ms) of a typed sample, for the
+/// `typing_wpm` setting. Nothing is saved.
+#[tauri::command]
+fn measure_typing_wpm(keystrokes_ms: Vec<f64>) -> Result<f32, String> {
+    stats::measure_wpm(&keystrokes_ms)
 }
 
+/// The newest history items, trimmed for the tray's recent dictations popover; the text is
+/// replaced by a placeholder while privacy mode is on.
 #[tauri::command]
-fn list_input_devices(state: State<'_, AppState>) -> Result<InputDevicesResponse, String> {
-    let recorder = state.recorder.lock().map_err(|e| e.to_string())?;
-    let mut devices = recorder.list_input_devices()?;
-    devices.sort();
-    devices.dedup();
-    Ok(InputDevicesResponse {
-        devices,
-        selected: recorder.selected_input_device(),
-    })
+fn get_mini_history(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<mini_history::MiniHistoryItem>, String> {
+    let recent = history_store::for_app(&app_handle)?.recent(mini_history::MAX_ITEMS)?;
+    Ok(mini_history::mini_items(
+        &recent,
+        limit.unwrap_or_default(),
+        state.privacy.is_enabled(),
+        chrono::Utc::now(),
+    ))
 }
 
 #[tauri::command]
-fn select_input_device(name: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
-    let mut recorder = state.recorder.lock().map_err(|e| e.to_string())?;
-    recorder.set_selected_input_device(name);
-    Ok(())
+fn show_mini_history_window(app_handle: tauri::AppHandle) -> Result<(), String> {
+    tray::show_mini_history(&app_handle)
 }
 
+/// Copy a history item's full text; the popover only holds a snippet of it.
 #[tauri::command]
-async fn transcribe_audio(
-    audio: AudioBuffer,
-    state: State<'_, AppState>,
-) -> Result<stt::Transcript, String> {
-    let mut orchestrator = state.orchestrator.lock().await;
-    orchestrator
-        .transcribe(&audio)
-        .await
+fn copy_history_item(id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let item = history_store::for_app(&app_handle)?
+        .get(&id)?
+        .ok_or_else(|| format!("History item not found: {}", id))?;
+    app_handle
+        .clipboard()
+        .write_text(item.text)
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn start_recording_session(state: State<'_, AppState>) -> Result<String, String> {
-    let mut stitcher = state.session_stitcher.lock().await;
-    stitcher.start_session().await.map_err(|e| format!("{:?}", e))
+fn delete_history_item(id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let change = config::delete_history_item(&app_handle, &id)?;
+    events::history_changed(&app_handle, change);
+    Ok(())
 }
 
+/// Write a history item or a finalized session to `path` as SRT, WebVTT, text or Markdown.
+/// Returns the path written, with the format's extension added when it had none.
 #[tauri::command]
-async fn add_audio_segment(
-    audio: AudioBuffer,
-    state: State<'_, AppState>,
-) -> Result<SegmentResult, String> {
-    let mut stitcher = state.session_stitcher.lock().await;
-    stitcher.add_segment(audio).await.map_err(|e| format!("{:?}", e))
+fn export_transcript(
+    source: ExportSource,
+    format: String,
+    path: String,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let format = TranscriptFormat::parse(&format)
+        .ok_or_else(|| format!("Unsupported export format: {}", format))?;
+    let (text, segments, title) = match source {
+        ExportSource::History { id } => {
+            let item = history_store::for_app(&app_handle)?
+                .get(&id)?
+                .ok_or_else(|| format!("History item not found: {}", id))?;
+            // A history item is one recording, so its cues share its whole duration.
+            let timing = SegmentTiming {
+                duration_secs: item.duration_seconds,
+                word_count: item.word_count,
+            };
+            let title = format!("Transcript {}", item.timestamp);
+            (item.text, vec![timing], title)
+        }
+        ExportSource::Session { result } => {
+            let mut segments = result.segments;
+            if segments.is_empty() {
+                segments.push(SegmentTiming {
+                    duration_secs: result.total_duration_secs,
+                    word_count: 0,
+                });
+            }
+            (result.full_text, segments, "Session".to_string())
+        }
+    };
+    if text.trim().is_empty() {
+        return Err("Nothing to export".to_string());
+    }
+
+    let mut path = std::path::PathBuf::from(path);
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+    let content = subtitles::render(format, &text, &segments, &title);
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    Ok(path.display().to_string())
 }
 
 #[tauri::command]
-async fn finalize_recording_session(state: State<'_, AppState>) -> Result<StitchedResult, String> {
-    let mut stitcher = state.session_stitcher.lock().await;
-    stitcher.finalize_session().await.map_err(|e| format!("{:?}", e))
+fn request_destructive_action(
+    kind: destructive::DestructiveActionKind,
+    tokens: State<'_, destructive::DestructiveTokens>,
+    app_handle: tauri::AppHandle,
+) -> Result<destructive::DestructiveActionToken, String> {
+    let scope = match kind {
+        destructive::DestructiveActionKind::ClearHistory => {
+            config::clear_history_scope(&app_handle)?
+        }
+        destructive::DestructiveActionKind::RevealApiKey => {
+            destructive::DestructiveScope::default()
+        }
+    };
+    tokens.issue(kind, scope)
 }
 
 #[tauri::command]
-async fn get_session_progress(state: State<'_, AppState>) -> Result<SessionProgress, String> {
-    let stitcher = state.session_stitcher.lock().await;
-    Ok(stitcher.get_progress())
+fn reveal_api_key(
+    slot: secrets::ApiKeySlot,
+    token: String,
+    tokens: State<'_, destructive::DestructiveTokens>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    tokens.consume(&token, destructive::DestructiveActionKind::RevealApiKey)?;
+    tracing::info!("Revealing the {:?} API key", slot);
+    config::reveal_api_key(&app_handle, slot)
 }
 
 #[tauri::command]
-fn paste_text(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<paste::PasteAttempt, String> {
-    let zentra_window = current_zentra_window_handle(&app_handle);
-    let mut context = state.paste_context.lock().map_err(|e| e.to_string())?;
-    Ok(context.try_auto_paste(zentra_window))
+fn clear_history(
+    token: String,
+    tokens: State<'_, destructive::DestructiveTokens>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    tokens.consume(&token, destructive::DestructiveActionKind::ClearHistory)?;
+    config::clear_history(&app_handle)
 }
 
 #[tauri::command]
-fn get_setup_state(app_handle: tauri::AppHandle) -> Result<SetupState, String> {
-    let config = config::load_or_create(&app_handle)?;
-    Ok(config::setup_state(&config))
+fn restore_history_trash(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    config::restore_history_trash(&app_handle)
 }
 
+/// Rewrite the history mirror folder from history, dropping files of deleted items.
+/// Returns how many items it holds.
 #[tauri::command]
-fn save_setup_partial(
-    payload: SetupPartialPayload,
-    app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let _ = config::save_setup_partial(&app_handle, payload)?;
-    Ok(())
+async fn resync_history_mirror(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        history_store::for_app(&app_handle)?.resync_mirror()
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-fn complete_setup(
-    payload: CompleteSetupPayload,
+async fn retranscribe_history_range(
+    filter: HistoryFilter,
+    options: Option<RetranscribeOptions>,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    stop_capture_safely(state.inner());
-    let config = config::complete_setup(&app_handle, payload)?;
-    apply_runtime_config(&app_handle, state.inner(), &config)?;
+) -> Result<RetranscribeReport, String> {
+    let options = options.unwrap_or_default();
+    let config = config::load_or_create(&app_handle)?;
+    let history = history_store::for_app(&app_handle)?.items()?;
+    let plan = config::plan_retranscription(&history, &filter, &options);
 
-    if let Some(setup_window) = app_handle.get_webview_window("setup") {
-        let _ = setup_window.hide();
+    let mut report = RetranscribeReport {
+        queued: plan.queued.iter().map(|target| target.id.clone()).collect(),
+        skipped: plan.skipped,
+        ..Default::default()
+    };
+    for skipped in &report.skipped {
+        tracing::info!("Re-transcription skipped {}: {}", skipped.id, skipped.reason);
     }
-    if let Some(main_window) = app_handle.get_webview_window("main") {
-        let _ = main_window.show();
-        let _ = main_window.set_focus();
+    if options.dry_run {
+        return Ok(report);
     }
-    let _ = tray::show_dashboard(&app_handle);
-    Ok(())
+
+    let total = plan.queued.len();
+    for (index, target) in plan.queued.into_iter().enumerate() {
+        let mut progress = events::RetranscribeProgress {
+            id: target.id.clone(),
+            index,
+            total,
+            status: events::RetranscribeStatus::Transcribing,
+            provider: None,
+            error: None,
+        };
+        events::emit_event(
+            &app_handle,
+            Event::HistoryRetranscribeProgress(progress.clone()),
+        );
+
+        match retranscribe_target(&app_handle, &state, &config, &target).await {
+            Ok(provider) => {
+                events::history_changed(&app_handle, ChangeSummary::updated(1));
+                progress.status = events::RetranscribeStatus::Completed;
+                progress.provider = Some(provider);
+                report.updated.push(target.id);
+            }
+            Err(error) => {
+                tracing::warn!("Re-transcription of {} failed: {}", target.id, error);
+                progress.status = events::RetranscribeStatus::Failed;
+                progress.error = Some(error.clone());
+                report.failed.push(SkippedHistoryItem {
+                    id: target.id,
+                    reason: error,
+                });
+            }
+        }
+        events::emit_event(&app_handle, Event::HistoryRetranscribeProgress(progress));
+    }
+    Ok(report)
 }
 
+/// Transcribe a history item's archived recording again with the current providers and
+/// settings, and keep the result as the item's newest version.
 #[tauri::command]
-async fn validate_groq_key(api_key: String) -> Result<bool, String> {
-    if api_key.trim().is_empty() {
-        return Ok(false);
+async fn retranscribe_history_item(
+    id: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<config::HistoryItem, String> {
+    let config = config::load_or_create(&app_handle)?;
+    let store = history_store::for_app(&app_handle)?;
+    let item: Vec<_> = store.get(&id)?.into_iter().collect();
+    let plan = config::plan_retranscription(
+        &item,
+        &HistoryFilter::Ids(vec![id.clone()]),
+        &RetranscribeOptions::default(),
+    );
+    let Some(target) = plan.queued.into_iter().next() else {
+        let reason = plan
+            .skipped
+            .first()
+            .map_or("not_found", |skipped| skipped.reason.as_str());
+        return Err(format!("History item cannot be re-transcribed: {}", reason));
+    };
+
+    let provider = retranscribe_target(&app_handle, &state, &config, &target).await?;
+    tracing::info!("Re-transcribed history item {} with {}", id, provider);
+    events::history_changed(&app_handle, ChangeSummary::updated(1));
+
+    store
+        .get(&id)?
+        .ok_or_else(|| "History item was deleted meanwhile".to_string())
+}
+
+/// Transcribe an archived recording the way a dictation is, in chunks when it is long and
+/// through the configured post-processing, and keep the result as the item's newest
+/// version. Returns the provider that transcribed it.
+async fn retranscribe_target(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    config: &AppConfig,
+    target: &config::RetranscribeTarget,
+) -> Result<String, String> {
+    let audio = audio::wav::read_wav_file(Path::new(&target.audio_path))?;
+    let transcript = {
+        let orchestrator = state.orchestrator.read().await;
+        zentra_core::session::chunking::transcribe_long(&orchestrator, &audio)
+            .await
+            .map_err(|e| describe_orchestrator_error(&e))?
+    };
+    let options = config::post_processing_options(config)
+        .with_detected_language(transcript.language.as_deref());
+    let text = PostProcessor::new(options).run(&transcript.text).output;
+    if !config::record_history_version(app_handle, &target.id, &text, &transcript.provider)? {
+        return Err(
+            "The recording transcribed to nothing; the item was left as it was".to_string(),
+        );
     }
+    Ok(transcript.provider)
+}
 
-    let wav_probe = build_validation_wav_probe();
-    let file_part = multipart::Part::bytes(wav_probe)
-        .file_name("probe.wav")
-        .mime_str("audio/wav")
-        .map_err(|e| e.to_string())?;
+fn apply_privacy_mode(app_handle: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    config::set_privacy_mode(app_handle, enabled)?;
+    app_handle.state::<AppState>().privacy.set_enabled(enabled);
+    tray::set_privacy_indicator(app_handle, enabled);
+    events::emit_event(
+        app_handle,
+        Event::PrivacyModeChanged(events::PrivacyModeChanged { enabled }),
+    );
+    Ok(())
+}
 
-    let form = multipart::Form::new()
-        .text("model", "whisper-large-v3")
-        .text("response_format", "text")
-        .part("file", file_part);
+/// Apply preset `name` (or end the active one) and push the result to the running app.
+fn apply_preset_settings(app_handle: &tauri::AppHandle, name: Option<&str>) -> Result<(), String> {
+    let config = config::set_active_preset(app_handle, name)?;
+    apply_runtime_config(app_handle, app_handle.state::<AppState>().inner(), &config)?;
+    tray::set_meeting_indicator(
+        app_handle,
+        presets::is_active(&config, presets::MEETING_PRESET),
+    );
+    events::emit_event(
+        app_handle,
+        Event::PresetChanged(events::PresetChanged {
+            active: config.active_preset,
+        }),
+    );
+    Ok(())
+}
 
-    let response = Client::new()
-        .post("https://api.groq.com/openai/v1/audio/transcriptions")
-        .bearer_auth(api_key.trim())
-        .multipart(form)
-        .timeout(std::time::Duration::from_secs(8))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+fn toggle_meeting_mode(app_handle: &tauri::AppHandle) {
+    let active = config::load_or_create(app_handle)
+        .is_ok_and(|config| presets::is_active(&config, presets::MEETING_PRESET));
+    let name = (!active).then_some(presets::MEETING_PRESET);
+    if let Err(e) = apply_preset_settings(app_handle, name) {
+        tracing::error!("Failed to toggle meeting mode: {}", e);
+    }
+}
 
-    Ok(response.status().is_success())
+#[tauri::command]
+fn start_meeting_mode(app_handle: tauri::AppHandle) -> Result<(), String> {
+    apply_preset_settings(&app_handle, Some(presets::MEETING_PRESET))
 }
 
+/// Ends whichever preset is active, restoring the settings from before it.
 #[tauri::command]
-fn get_dashboard_data(app_handle: tauri::AppHandle) -> Result<config::DashboardData, String> {
-    let version = app_handle.package_info().version.to_string();
-    config::dashboard_data(&app_handle, &version)
+fn end_meeting_mode(app_handle: tauri::AppHandle) -> Result<(), String> {
+    apply_preset_settings(&app_handle, None)
 }
 
 #[tauri::command]
-fn record_transcription_history(
-    payload: RecordHistoryPayload,
+fn apply_preset(name: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    apply_preset_settings(&app_handle, Some(&name))
+}
+
+/// Save and apply `language` like `update_settings` does.
+fn apply_language(app_handle: &tauri::AppHandle, language: &str) -> Result<(), String> {
+    let config = config::set_language(app_handle, language)?;
+    apply_runtime_config(app_handle, app_handle.state::<AppState>().inner(), &config)?;
+    events::emit_event(
+        app_handle,
+        Event::SettingsChanged(events::SettingsChanged {
+            language: config.language,
+        }),
+    );
+    Ok(())
+}
+
+/// Tray language switch.
+fn switch_language(app_handle: &tauri::AppHandle, language: &str) {
+    if let Err(e) = apply_language(app_handle, language) {
+        tracing::error!("Failed to switch language: {}", e);
+        // The clicked item toggled itself; put the checks back on the saved language.
+        if let Ok(config) = config::load_or_create(app_handle) {
+            tray::set_language_indicator(app_handle, &config.language);
+        }
+    }
+}
+
+fn toggle_privacy_mode(app_handle: &tauri::AppHandle) {
+    let enabled = !app_handle.state::<AppState>().privacy.is_enabled();
+    if let Err(e) = apply_privacy_mode(app_handle, enabled) {
+        tracing::error!("Failed to toggle privacy mode: {}", e);
+    }
+}
+
+#[tauri::command]
+fn set_privacy_mode(enabled: bool, app_handle: tauri::AppHandle) -> Result<(), String> {
+    apply_privacy_mode(&app_handle, enabled)
+}
+
+#[tauri::command]
+fn get_privacy_mode(state: State<'_, AppState>) -> bool {
+    state.privacy.is_enabled()
+}
+
+#[tauri::command]
+fn set_offline_mode(
+    enabled: bool,
+    state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
-    config::record_history(&app_handle, payload)?;
-    let _ = app_handle.emit_to("dashboard", "dashboard:history-updated", ());
+    config::set_offline_mode(&app_handle, enabled)?;
+    state.offline.set_manual(enabled);
     Ok(())
 }
 
+/// True when offline by hand or after repeated network failures.
 #[tauri::command]
-fn delete_history_item(id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
-    config::delete_history_item(&app_handle, &id)
+fn get_offline_mode(state: State<'_, AppState>) -> bool {
+    state.offline.is_offline()
 }
 
+/// Keep a recording that could not be transcribed for lack of a connection. Ends the
+/// current session; the offline queue transcribes the audio once providers are reachable.
 #[tauri::command]
-fn clear_history(app_handle: tauri::AppHandle) -> Result<(), String> {
-    config::clear_history(&app_handle)
+async fn queue_recording_for_later(
+    audio: AudioBuffer,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<config::HistoryItem, String> {
+    if !state.offline.is_offline() && !state.offline.has_network_failures() {
+        return Err("Providers are reachable; the recording was not queued".to_string());
+    }
+    let _ = state.session_stitcher.lock().await.discard_session();
+    let item = config::enqueue_pending(&app_handle, &audio, &state.privacy)?;
+    events::history_changed(&app_handle, ChangeSummary::added(1));
+    Ok(item)
+}
+
+#[tauri::command]
+fn cancel_pending_transcription(id: String, app_handle: tauri::AppHandle) -> Result<bool, String> {
+    let cancelled = config::cancel_pending(&app_handle, &id)?;
+    if cancelled {
+        events::history_changed(&app_handle, ChangeSummary::removed(1));
+    }
+    Ok(cancelled)
 }
 
 #[tauri::command]
-fn update_settings(
+async fn update_settings(
     payload: UpdateSettingsPayload,
-    state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
-    let config = config::update_settings(&app_handle, payload)?;
-    apply_runtime_config(&app_handle, state.inner(), &config)?;
+    if let Some(integrations) = &payload.integrations {
+        let saved = config::load_or_create(&app_handle)?.integrations;
+        integrations::issues_to_error(&integrations::validate_update(integrations, &saved).await)?;
+    }
+    // Applying takes the orchestrator and engine locks with `blocking_*`, which panics on
+    // a runtime thread.
+    let config = tauri::async_runtime::spawn_blocking({
+        let app_handle = app_handle.clone();
+        move || {
+            let config = config::update_settings(&app_handle, payload)?;
+            apply_runtime_config(&app_handle, app_handle.state::<AppState>().inner(), &config)?;
+            Ok::<_, String>(config)
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to apply settings: {}", e))??;
+    events::emit_event(
+        &app_handle,
+        Event::SettingsChanged(events::SettingsChanged {
+            language: config.language,
+        }),
+    );
     Ok(())
 }
 
+#[tauri::command]
+async fn validate_integration_settings(
+    payload: integrations::IntegrationSettings,
+) -> Result<Vec<integrations::IntegrationIssue>, String> {
+    Ok(integrations::validate(&payload).await)
+}
+
+#[tauri::command]
+fn preview_integration(
+    kind: integrations::IntegrationKind,
+    app_handle: tauri::AppHandle,
+) -> Result<integrations::IntegrationPreview, String> {
+    let config = config::load_or_create(&app_handle)?;
+    let context = integrations::TemplateContext::sample(&config.language);
+    integrations::preview(&config.integrations, kind, &context)
+}
+
 #[tauri::command]
 fn open_dashboard(app_handle: tauri::AppHandle) -> Result<(), String> {
     tray::show_dashboard(&app_handle)
@@ -447,7 +2623,7 @@ fn dashboard_close(app_handle: tauri::AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 fn hide_main_window(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<(), String> {
-    stop_capture_safely(state.inner());
+    stop_capture_safely(state.inner(), &app_handle);
     if let Some(main_window) = app_handle.get_webview_window("main") {
         main_window.hide().map_err(|e| e.to_string())?;
     }
@@ -483,32 +2659,66 @@ fn build_validation_wav_probe() -> Vec<u8> {
     wav
 }
 
-fn current_zentra_window_handle(app_handle: &tauri::AppHandle) -> isize {
-    if let Some(window) = app_handle.get_webview_window("main") {
-        #[cfg(target_os = "windows")]
-        {
-            return window
-                .hwnd()
-                .map(|hwnd| hwnd.0 as isize)
-                .unwrap_or_default();
-        }
+/// Native handles of every open Zentra window (pill, dashboard, setup); none of them may
+/// receive a paste. Windows without a handle are skipped.
+fn zentra_window_handles(app_handle: &tauri::AppHandle) -> Vec<isize> {
+    app_handle
+        .webview_windows()
+        .values()
+        .map(native_window_handle)
+        .filter(|&handle| handle != 0)
+        .collect()
+}
 
-        #[cfg(target_os = "macos")]
-        {
-            return window
-                .ns_window()
-                .map(|handle| handle as isize)
-                .unwrap_or_default();
-        }
+fn native_window_handle(window: &tauri::WebviewWindow) -> isize {
+    #[cfg(target_os = "windows")]
+    {
+        return window
+            .hwnd()
+            .map(|hwnd| hwnd.0 as isize)
+            .unwrap_or_default();
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        return window
+            .ns_window()
+            .map(|handle| handle as isize)
+            .unwrap_or_default();
+    }
+
+    // The X11 window id, comparable with `xdotool getactivewindow`. Wayland surfaces
+    // have no global id; focus cannot be inspected there anyway.
+    #[cfg(target_os = "linux")]
+    {
+        use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+        return match window.window_handle().map(|handle| handle.as_raw()) {
+            Ok(RawWindowHandle::Xlib(handle)) => handle.window as isize,
+            Ok(RawWindowHandle::Xcb(handle)) => handle.window.get() as isize,
+            _ => 0,
+        };
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = window;
+        0
     }
+}
 
-    0
+fn zentra_window_focused(app_handle: &tauri::AppHandle) -> bool {
+    app_handle
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    crash_reports::install(env!("CARGO_PKG_VERSION"));
     // Load environment variables from .env file
-    let _ = dotenvy::dotenv();
+    let env_file = dotenvy::dotenv().ok();
 
     let recorder = match AudioRecorder::new() {
         Ok(r) => r,
@@ -518,62 +2728,217 @@ pub fn run() {
         }
     };
 
-    let configured_hotkey = Arc::new(Mutex::new(config::DEFAULT_HOTKEY.to_string()));
-    let orchestrator = Arc::new(TokioMutex::new(FailoverOrchestrator::from_env()));
-    let session_stitcher = SessionStitcher::new(orchestrator.clone());
+    let privacy = PrivacyGuard::default();
+    let key_health = KeyHealth::default();
+    let offline = OfflineMode::default();
+    let usage = UsageTracker::default();
+    let language_pin = LanguagePin::default();
+    // Rebuilt with the configured provider overrides once setup loads the config.
+    let orchestrator = Arc::new(TokioRwLock::new(
+        FailoverOrchestrator::from_config(&ProviderSetup::default())
+            .with_privacy(privacy.clone())
+            .with_key_health(key_health.clone())
+            .with_offline(offline.clone())
+            .with_usage(usage.clone())
+            .with_language_pin(language_pin.clone()),
+    ));
+    let silence_gate = SharedSilenceGate::default();
+    let session_stitcher = SessionStitcher::new(orchestrator.clone())
+        .with_silence_gate(silence_gate.clone())
+        .with_language_pin(language_pin.clone());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app, _shortcut, event| {
+                .with_handler(|app, shortcut, event| {
                     if event.state == ShortcutState::Pressed {
-                        if let Some(main_window) = app.get_webview_window("main") {
-                            if let Ok(false) = main_window.is_visible() {
-                                let _ = main_window.show();
-                                let _ = main_window.set_focus();
-                            }
-                        }
-                        let _ = app.emit("toggle-recording", ());
+                        handle_hotkey(app, shortcut);
                     }
                 })
                 .build(),
         )
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(destructive::DestructiveTokens::default())
         .manage(AppState {
-            recorder: Arc::new(Mutex::new(recorder)),
+            recorder: Arc::new(SharedRecorder::new(recorder)),
+            transitions: Arc::new(TransitionQueue::default()),
+            devices: Arc::new(DeviceManager::new()),
+            live: Arc::new(
+                live::LiveTranscriber::default()
+                    .with_privacy(privacy.clone())
+                    .with_offline(offline.clone())
+                    .with_language_pin(language_pin.clone()),
+            ),
             orchestrator,
+            prompt_engine: Arc::new(TokioMutex::new(
+                PromptEngine::new().with_privacy(privacy.clone()),
+            )),
             session_stitcher: Arc::new(TokioMutex::new(session_stitcher)),
             audio_level_flag: Arc::new(AtomicBool::new(false)),
             audio_level_task: Arc::new(Mutex::new(None)),
             paste_context: Arc::new(Mutex::new(paste::PasteContext::default())),
-            hotkey: configured_hotkey.clone(),
+            hotkeys: Arc::new(Mutex::new(HashMap::new())),
+            last_transcript: Arc::new(Mutex::new(None)),
+            privacy,
+            silence_gate,
+            key_health,
+            offline,
+            usage,
+            language_pin,
+            dictation: dictation::DictationTracker::default(),
+            setup_completed: Arc::new(AtomicBool::new(false)),
+            max_recording_secs: Arc::new(AtomicU64::new(config::DEFAULT_MAX_RECORDING_SECS)),
+            capture_generation: Arc::new(AtomicU64::new(0)),
+            auto_stopped: Arc::new(Mutex::new(None)),
+            archive_audio: Arc::new(AtomicBool::new(false)),
+            pill_follows_monitor: Arc::new(AtomicBool::new(true)),
+            streaming_upload: Arc::new(AtomicBool::new(false)),
+            groq_key_set: Arc::new(AtomicBool::new(false)),
+            last_recording: Arc::new(Mutex::new(None)),
+            hotkey_capture: hotkey_capture::HotkeyCapture::default(),
+            tray_recording: Arc::new(tray::RecordingIndicator::default()),
+            announcements: Arc::new(announce::Announcements::default()),
+            hotkey_backend: hotkey_backend::Hotkeys::default(),
         })
-        .setup(|app| {
+        .setup(move |app| {
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.set_shadow(false);
                 if let Ok(Some(monitor)) = window.primary_monitor() {
-                    let work = monitor.work_area();
-                    let scale = monitor.scale_factor();
-                    let win_size = window
-                        .outer_size()
-                        .unwrap_or(tauri::PhysicalSize::new(360, 72));
-                    let win_width = win_size.width as f64 / scale;
-                    let win_height = win_size.height as f64 / scale;
-                    let work_x = work.position.x as f64 / scale;
-                    let work_y = work.position.y as f64 / scale;
-                    let work_w = work.size.width as f64 / scale;
-                    let work_h = work.size.height as f64 / scale;
-                    let x = work_x + (work_w - win_width) / 2.0;
-                    let y = work_y + work_h - win_height - 16.0;
-                    let _ = window.set_position(tauri::LogicalPosition::new(x, y));
+                    pill::place_on(&window, &monitor);
+                }
+            }
+
+            let transitions_handle = app.handle().clone();
+            app.state::<AppState>()
+                .transitions
+                .run(move |transition| run_transition(transitions_handle.clone(), transition));
+
+            let resolver = paths::Resolver::detect(app.handle())?;
+            secrets::set_keychain_allowed(resolver.allows_keychain());
+            app.state::<AppState>()
+                .recorder
+                .lock()
+                .set_models_dir(resolver.models_dir());
+            crash_reports::set_reports_dir(
+                resolver.crashes_dir(),
+                app.state::<AppState>().privacy.clone(),
+            );
+            let storage = config::StorageStatus::default();
+            let mut config = config::open(&resolver.config_dir(), &storage);
+            app.manage(storage);
+            if let Err(e) = app
+                .state::<AppState>()
+                .prompt_engine
+                .blocking_lock()
+                .set_user_profiles_path(resolver.config_dir().join(USER_PROFILES_FILE))
+            {
+                tracing::warn!("Failed to load user profiles: {}", e);
+            }
+            let history_handle = app.handle().clone();
+            app.manage(HistoryNotifier::new(move |change| {
+                events::emit_event(
+                    &history_handle,
+                    Event::DashboardHistoryUpdated(events::HistoryUpdated {
+                        change_summary: change,
+                    }),
+                );
+            }));
+            let store =
+                HistoryStore::open(&resolver.history_dir().join(history_store::HISTORY_DB_FILE))
+                    .or_else(|e| {
+                        tracing::warn!("{}; history is kept in memory until restart", e);
+                        HistoryStore::in_memory()
+                    })?;
+            match config::migrate_legacy_history(app.handle(), &mut config, &store) {
+                Ok(0) => {}
+                Ok(imported) => tracing::info!(
+                    "Moved {} history items from config.json to the history database",
+                    imported
+                ),
+                Err(e) => tracing::warn!("History stays in config.json for now: {}", e),
+            }
+            if let Err(e) = store.purge_expired_trash(chrono::Utc::now()) {
+                tracing::warn!("History trash not purged: {}", e);
+            }
+            // Audio of history still waiting in config.json is not in the store yet.
+            if config.legacy_history.is_empty() && config.legacy_history_trash.is_none() {
+                let orphans = audio_archive::remove_orphans(&resolver.audio_archive_dir(), &store);
+                if orphans > 0 {
+                    tracing::info!(
+                        "Removed {} archived recordings no history item uses",
+                        orphans
+                    );
                 }
             }
+            app.manage(store);
+            app.state::<AppState>()
+                .usage
+                .persist_to(resolver.base_dir().join(usage::USAGE_FILE));
+            let recovery_store = RecoveryStore::new(
+                resolver.base_dir().join(recovery::RECOVERY_DIR),
+                app.state::<AppState>().privacy.clone(),
+            );
+            app.manage(resolver);
+
+            let env_imported = env_file.as_deref().and_then(|path| {
+                let keys = env_import::run(&mut config, &env_import::read_env_file(path))?;
+                if let Err(e) = config::save(app.handle(), &config) {
+                    tracing::warn!("Failed to save imported .env settings: {}", e);
+                }
+                Some(events::EnvImported { keys }).filter(|imported| !imported.keys.is_empty())
+            });
 
             let state = app.state::<AppState>();
-            let config = config::load_or_create(&app.handle())?;
-            apply_runtime_config(&app.handle(), state.inner(), &config)?;
-            tray::init_tray(&app.handle())?;
+            {
+                let mut stitcher = state.session_stitcher.blocking_lock();
+                stitcher.set_recovery(recovery_store);
+                stitcher.set_progress_sink(Arc::new(events::SegmentStatusEmitter::new(
+                    app.handle().clone(),
+                )));
+            }
+            state
+                .announcements
+                .attach(Arc::new(SystemAnnouncer(app.handle().clone())));
+            if let Err(e) = apply_runtime_config(&app.handle(), state.inner(), &config) {
+                // Reported through startup health; everything but the shortcuts works.
+                tracing::error!("Global shortcuts unavailable: {}", e);
+            }
+            tray::init_tray(
+                app.handle(),
+                &state.tray_recording,
+                config.privacy_mode,
+                presets::is_active(&config, presets::MEETING_PRESET),
+                state.key_health.status() == KeyStatus::Invalid,
+                &config.language,
+            )?;
+            key_health::spawn(
+                app.handle().clone(),
+                state.key_health.clone(),
+                state.privacy.clone(),
+            );
+            offline_queue::spawn(
+                app.handle().clone(),
+                state.orchestrator.clone(),
+                state.offline.clone(),
+                state.privacy.clone(),
+            );
+            spawn_device_watcher(
+                app.handle().clone(),
+                state.devices.clone(),
+                state.devices.refresh().unwrap_or_default(),
+            );
+            let health = startup_health(&app.state::<config::StorageStatus>(), state.inner());
+            if !health.storage_available
+                || health.hotkey_message.is_some()
+                || health.crash_message.is_some()
+            {
+                events::emit_event(app.handle(), Event::StartupHealth(health));
+            }
+            if let Some(imported) = env_imported {
+                events::emit_event(app.handle(), Event::EnvImported(imported));
+            }
 
             if let Some(dashboard) = app.get_webview_window("dashboard") {
                 let _ = dashboard.hide();
@@ -595,31 +2960,93 @@ pub fn run() {
                     let _ = setup.set_focus();
                 }
             }
+            crash_reports::set_phase(AppPhase::Idle);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             start_recording,
             stop_recording,
+            cancel_recording,
             start_mic_monitor,
             stop_mic_monitor,
+            get_capture_quality,
             get_microphone_info,
             list_input_devices,
             select_input_device,
             transcribe_audio,
             start_recording_session,
             add_audio_segment,
+            start_auto_session,
+            wait_for_session_segments,
+            retry_segment,
             finalize_recording_session,
+            preview_post_processing,
+            replay_fixture,
+            list_prompt_profiles,
+            create_profile,
+            update_profile,
+            delete_profile,
+            set_optimization_mode,
+            optimize_transcript,
+            pause_recording_session,
+            resume_recording_session,
+            discard_recording_session,
+            get_recoverable_sessions,
+            get_recoverable_session,
+            discard_recoverable_sessions,
             get_session_progress,
-            paste_text,
+            get_segment_diagnostics,
+            paste_transcript,
+            run_post_actions,
+            check_accessibility_permission,
+            open_accessibility_settings,
             get_setup_state,
             save_setup_partial,
             complete_setup,
+            begin_hotkey_capture,
+            cancel_hotkey_capture,
+            transcribe_file,
             validate_groq_key,
+            validate_elevenlabs_key,
+            validate_openrouter_key,
+            get_startup_health,
+            open_crash_folder,
+            get_app_info,
+            get_api_schema,
+            get_error_catalogue,
+            force_close_circuit,
+            get_usage_stats,
+            reset_usage_stats,
             get_dashboard_data,
             record_transcription_history,
+            search_history,
+            measure_typing_wpm,
+            get_mini_history,
+            show_mini_history_window,
+            copy_history_item,
             delete_history_item,
+            export_transcript,
+            request_destructive_action,
             clear_history,
+            reveal_api_key,
+            restore_history_trash,
+            resync_history_mirror,
+            accept_language_suggestion,
+            dismiss_language_suggestion,
+            retranscribe_history_range,
+            retranscribe_history_item,
+            set_privacy_mode,
+            start_meeting_mode,
+            end_meeting_mode,
+            apply_preset,
+            get_privacy_mode,
+            set_offline_mode,
+            get_offline_mode,
+            queue_recording_for_later,
+            cancel_pending_transcription,
             update_settings,
+            validate_integration_settings,
+            preview_integration,
             open_dashboard,
             hide_dashboard,
             dashboard_minimize,
@@ -627,6 +3054,11 @@ pub fn run() {
             dashboard_close,
             hide_main_window
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().hotkey_capture.cancel();
+            }
+        });
 }
@@ -0,0 +1,240 @@
+//! Partial transcripts while a recording is still running. A background task cuts the live
+//! capture buffer into fixed windows and sends each through the orchestrator; the final
+//! transcript still comes from the full recording after `stop_recording`.
+//!
+//! With `session_warmup`, a second task watches the same buffer for the session's first
+//! couple of seconds of speech and sends them as a warm-up (see
+//! [`zentra_core::session::warmup`]), whether or not partials are on.
+
+use crate::audio::AudioBuffer;
+use crate::dictation;
+use crate::ipc::events::{self, Event, TranscriptPartial};
+use crate::locks::lock_or_recover;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::AppHandle;
+use tokio::sync::RwLock as TokioRwLock;
+use tracing::Instrument;
+use zentra_core::offline::OfflineMode;
+use zentra_core::orchestrator::FailoverOrchestrator;
+use zentra_core::privacy::PrivacyGuard;
+use zentra_core::session::warmup::{self, WARMUP_DEADLINE_SECS};
+use zentra_core::stt::{LanguagePin, Transcript};
+
+/// Audio per partial request.
+pub const LIVE_WINDOW_SECS: f32 = 10.0;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct LiveTranscriber {
+    enabled: AtomicBool,
+    warmup: AtomicBool,
+    privacy: PrivacyGuard,
+    offline: OfflineMode,
+    language_pin: LanguagePin,
+    task: Mutex<Option<JoinHandle<()>>>,
+    warmup_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for LiveTranscriber {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            warmup: AtomicBool::new(false),
+            privacy: PrivacyGuard::default(),
+            offline: OfflineMode::default(),
+            language_pin: LanguagePin::default(),
+            task: Mutex::new(None),
+            warmup_task: Mutex::new(None),
+        }
+    }
+}
+
+impl LiveTranscriber {
+    /// The warm-up is skipped in privacy mode, so it shares the app-wide switch.
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    pub fn with_offline(mut self, offline: OfflineMode) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// The session's language pin, which the warm-up fills.
+    pub fn with_language_pin(mut self, language_pin: LanguagePin) -> Self {
+        self.language_pin = language_pin;
+        self
+    }
+
+    /// Turning partials off also stops a running task; a warm-up carries on.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.stop_partials();
+        }
+    }
+
+    /// End the partials of the running recording, leaving any warm-up running. Windows are
+    /// offsets into the capture buffer, so partials can't follow a session that cuts
+    /// segments off its front.
+    pub fn stop_partials(&self) {
+        if let Some(handle) = lock_or_recover(&self.task, "live transcriber").take() {
+            handle.abort();
+        }
+    }
+
+    /// Applies from the next recording.
+    pub fn set_warmup(&self, enabled: bool) {
+        self.warmup.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn start(
+        &self,
+        app_handle: AppHandle,
+        buffer: Arc<Mutex<AudioBuffer>>,
+        orchestrator: Arc<TokioRwLock<FailoverOrchestrator>>,
+        request_id: String,
+    ) {
+        self.stop();
+        self.start_warmup(&buffer, &orchestrator, &request_id);
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let span = dictation::span_for(&request_id);
+        let task = async move {
+            let mut offset = 0;
+            let mut partial = TranscriptPartial {
+                text: String::new(),
+                windows: 0,
+                request_id,
+            };
+            loop {
+                tokio::time::sleep(POLL_INTERVAL).await;
+
+                let window = {
+                    let guard = lock_or_recover(&buffer, "capture buffer");
+                    next_window(&guard, offset, LIVE_WINDOW_SECS)
+                };
+                let Some(window) = window else {
+                    continue;
+                };
+                offset += window.samples.len();
+
+                match orchestrator.read().await.transcribe(&window).await {
+                    Ok(transcript) => push_partial(&app_handle, &mut partial, &transcript),
+                    Err(e) => tracing::debug!("Live transcription window failed: {}", e),
+                }
+            }
+        };
+        let handle = tauri::async_runtime::spawn(task.instrument(span));
+        *lock_or_recover(&self.task, "live transcriber") = Some(handle);
+    }
+
+    /// Watch `buffer` for enough speech to warm the session up with. Skipped when the
+    /// session's language is already pinned, e.g. on resuming a paused session, and in
+    /// privacy or offline mode, where nothing may go out.
+    fn start_warmup(
+        &self,
+        buffer: &Arc<Mutex<AudioBuffer>>,
+        orchestrator: &Arc<TokioRwLock<FailoverOrchestrator>>,
+        request_id: &str,
+    ) {
+        if !self.warmup.load(Ordering::Relaxed)
+            || self.language_pin.get().is_some()
+            || self.privacy.is_enabled()
+            || self.offline.is_offline()
+        {
+            return;
+        }
+        let (buffer, orchestrator, pin) = (
+            buffer.clone(),
+            orchestrator.clone(),
+            self.language_pin.clone(),
+        );
+        let task = async move {
+            loop {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                let slice = {
+                    let guard = lock_or_recover(&buffer, "capture buffer");
+                    let recorded_secs = guard.samples.len() as f32
+                        / (guard.sample_rate.max(1) as f32 * guard.channels.max(1) as f32);
+                    if recorded_secs > WARMUP_DEADLINE_SECS {
+                        tracing::debug!("No warm-up: not enough speech early in the recording");
+                        return;
+                    }
+                    warmup::warmup_slice(&guard)
+                };
+                if let Some(slice) = slice {
+                    warmup::warm_up(&orchestrator, &pin, &slice).await;
+                    return;
+                }
+            }
+        };
+        let handle = tauri::async_runtime::spawn(task.instrument(dictation::span_for(request_id)));
+        *lock_or_recover(&self.warmup_task, "session warm-up") = Some(handle);
+    }
+
+    pub fn stop(&self) {
+        self.stop_partials();
+        if let Some(handle) = lock_or_recover(&self.warmup_task, "session warm-up").take() {
+            handle.abort();
+        }
+    }
+}
+
+fn push_partial(app_handle: &AppHandle, partial: &mut TranscriptPartial, transcript: &Transcript) {
+    let piece = transcript.text.trim();
+    if piece.is_empty() {
+        return;
+    }
+    if !partial.text.is_empty() {
+        partial.text.push(' ');
+    }
+    partial.text.push_str(piece);
+    partial.windows += 1;
+    events::emit_event(app_handle, Event::TranscriptPartial(partial.clone()));
+}
+
+/// Interleaved samples in a window of `window_secs`.
+fn window_len(sample_rate: u32, channels: u16, window_secs: f32) -> usize {
+    (window_secs * sample_rate as f32) as usize * channels.max(1) as usize
+}
+
+/// The full window of `window_secs` starting `offset` interleaved samples into `buffer`,
+/// or `None` until that much audio has arrived.
+pub fn next_window(buffer: &AudioBuffer, offset: usize, window_secs: f32) -> Option<AudioBuffer> {
+    let len = window_len(buffer.sample_rate, buffer.channels, window_secs);
+    let end = offset.checked_add(len)?;
+    if len == 0 || buffer.samples.len() < end {
+        return None;
+    }
+
+    let mut window = AudioBuffer::new(buffer.sample_rate, buffer.channels);
+    window.append(&buffer.samples[offset..end]);
+    Some(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_are_cut_only_once_complete() {
+        let mut buffer = AudioBuffer::new(16_000, 2);
+        buffer.append(&vec![1i16; 16_000 * 2 * 25]);
+
+        let first = next_window(&buffer, 0, LIVE_WINDOW_SECS).unwrap();
+        assert_eq!(first.samples.len(), 320_000);
+        assert_eq!((first.sample_rate, first.channels), (16_000, 2));
+        assert!((first.duration_secs - 10.0).abs() < 1e-3);
+
+        assert!(next_window(&buffer, 320_000, LIVE_WINDOW_SECS).is_some());
+        // Only 5 s left after two windows.
+        assert!(next_window(&buffer, 640_000, LIVE_WINDOW_SECS).is_none());
+        assert!(next_window(&AudioBuffer::new(0, 1), 0, LIVE_WINDOW_SECS).is_none());
+    }
+}
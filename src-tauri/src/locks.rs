@@ -0,0 +1,36 @@
+//! Poison handling for the app's std mutexes. A panic while a guard is held (for example
+//! inside an audio driver callback) poisons the mutex; erroring on every later `lock()`
+//! would leave the command permanently broken, so the data is taken back instead.
+
+use std::sync::{Mutex, MutexGuard};
+
+/// Lock `mutex`, clearing poison left by a panicked holder. `name` is only used for logging.
+pub fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        tracing::error!("Recovered poisoned lock '{}'", name);
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_poisoned_lock_is_recovered() {
+        let shared = Arc::new(Mutex::new(1));
+        let poisoner = shared.clone();
+        let _ = std::thread::spawn(move || {
+            let mut guard = poisoner.lock().unwrap();
+            *guard = 2;
+            panic!("callback panicked");
+        })
+        .join();
+        assert!(shared.is_poisoned());
+
+        assert_eq!(*lock_or_recover(&shared, "test"), 2);
+        assert!(!shared.is_poisoned());
+    }
+}
@@ -0,0 +1,411 @@
+//! Recent dictations at a glance: a small popover next to the tray icon with the last few
+//! transcripts and a copy button each, without opening the dashboard. It hides as soon as
+//! it loses focus. Being one of Zentra's windows, it is never a paste target.
+
+use crate::config::HistoryItem;
+use crate::snippet::snippet;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+pub const MINI_HISTORY_WINDOW: &str = "mini-history";
+pub const DEFAULT_ITEMS: usize = 5;
+pub const MAX_ITEMS: usize = 20;
+/// Shown instead of the text while privacy mode is on
+pub const PRIVATE_PLACEHOLDER: &str = "Hidden in privacy mode";
+/// Logical size of the popover
+pub const WINDOW_WIDTH: f64 = 320.0;
+pub const WINDOW_HEIGHT: f64 = 360.0;
+
+const SNIPPET_GRAPHEMES: usize = 90;
+/// Logical pixels kept between the popover, the tray icon and the edges of the work area
+const MARGIN: f64 = 12.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniHistoryItem {
+    pub id: String,
+    pub snippet: String,
+    /// "just now", "5 min ago", "yesterday"...
+    pub relative_time: String,
+    pub word_count: u32,
+    /// Privacy mode is on and `snippet` is a placeholder
+    pub hidden: bool,
+}
+
+/// The newest `limit` history items, trimmed for the popover. `limit` 0 means
+/// [`DEFAULT_ITEMS`]; more than [`MAX_ITEMS`] is capped.
+pub fn mini_items(
+    history: &[HistoryItem],
+    limit: usize,
+    private: bool,
+    now: DateTime<Utc>,
+) -> Vec<MiniHistoryItem> {
+    let limit = match limit {
+        0 => DEFAULT_ITEMS,
+        limit => limit.min(MAX_ITEMS),
+    };
+    history
+        .iter()
+        .take(limit)
+        .map(|item| MiniHistoryItem {
+            id: item.id.clone(),
+            snippet: if private {
+                PRIVATE_PLACEHOLDER.to_string()
+            } else {
+                snippet(&item.text, SNIPPET_GRAPHEMES)
+            },
+            relative_time: relative_time(&item.timestamp, now),
+            word_count: item.word_count,
+            hidden: private,
+        })
+        .collect()
+}
+
+/// How long before `now` an RFC 3339 `timestamp` was, in words; the date past a week.
+pub fn relative_time(timestamp: &str, now: DateTime<Utc>) -> String {
+    let Ok(at) = DateTime::parse_from_rfc3339(timestamp) else {
+        return timestamp.to_string();
+    };
+    let at = at.with_timezone(&Utc);
+    let minutes = (now - at).num_minutes();
+    match minutes {
+        // A clock set back can put items in the future.
+        i64::MIN..=0 => "just now".to_string(),
+        1..=59 => format!("{} min ago", minutes),
+        60..=1439 => format!("{} h ago", minutes / 60),
+        1440..=2879 => "yesterday".to_string(),
+        2880..=10079 => format!("{} days ago", minutes / 1440),
+        _ => at.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// A rectangle in physical pixels of the virtual desktop; monitors left of or above the
+/// primary one have negative coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn right(&self) -> i32 {
+        self.x.saturating_add(self.width as i32)
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.height as i32)
+    }
+
+    fn center(&self) -> (i32, i32) {
+        (
+            self.x + (self.width / 2) as i32,
+            self.y + (self.height / 2) as i32,
+        )
+    }
+
+    fn contains(&self, (x, y): (i32, i32)) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Squared distance from `point` to the nearest point of the rectangle
+    fn distance_sq(&self, (x, y): (i32, i32)) -> i64 {
+        let dx = (self.x as i64 - x as i64)
+            .max(x as i64 - self.right() as i64)
+            .max(0);
+        let dy = (self.y as i64 - y as i64)
+            .max(y as i64 - self.bottom() as i64)
+            .max(0);
+        dx * dx + dy * dy
+    }
+}
+
+/// A monitor: its whole area, the part not taken by taskbars, docks and menu bars, and its
+/// scale factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Display {
+    pub bounds: Rect,
+    pub work_area: Rect,
+    pub scale_factor: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Where the popover of physical size `size` goes: next to the tray icon at `anchor`, on the
+/// side away from the bar the icon sits in, inside the work area of the icon's monitor.
+/// Without an anchor (the platform reports none, or never sent a tray event) it goes to the
+/// bottom-right corner of the first display, which should be the primary one. `None` when
+/// there are no displays.
+pub fn popover_position(
+    anchor: Option<Rect>,
+    displays: &[Display],
+    size: (u32, u32),
+) -> Option<(i32, i32)> {
+    // Platforms without tray geometry report an empty rect at the origin.
+    let anchor = anchor.filter(|rect| rect.width > 0 && rect.height > 0);
+    let Some(anchor) = anchor else {
+        let display = displays.first()?;
+        let area = display.work_area;
+        let margin = scaled(MARGIN, display.scale_factor);
+        let x = area.right() - margin - size.0 as i32;
+        let y = area.bottom() - margin - size.1 as i32;
+        return Some(clamp_into(area, margin, size, (x, y)));
+    };
+
+    let center = anchor.center();
+    let display = displays
+        .iter()
+        .find(|display| display.bounds.contains(center))
+        .or_else(|| {
+            displays
+                .iter()
+                .min_by_key(|display| display.bounds.distance_sq(center))
+        })?;
+    let area = display.work_area;
+    let margin = scaled(MARGIN, display.scale_factor);
+    let (width, height) = (size.0 as i32, size.1 as i32);
+
+    let position = match bar_edge(display, center) {
+        Edge::Bottom => (
+            center.0 - width / 2,
+            anchor.y.min(area.bottom()) - margin - height,
+        ),
+        Edge::Top => (center.0 - width / 2, anchor.bottom().max(area.y) + margin),
+        Edge::Left => (anchor.right().max(area.x) + margin, center.1 - height / 2),
+        Edge::Right => (
+            anchor.x.min(area.right()) - margin - width,
+            center.1 - height / 2,
+        ),
+    };
+    Some(clamp_into(area, margin, size, position))
+}
+
+/// The screen edge holding the bar the tray icon at `center` sits in: the side of the work
+/// area the icon is outside of, or, when the bar hides itself and takes no space, the
+/// nearest edge of the monitor.
+fn bar_edge(display: &Display, center: (i32, i32)) -> Edge {
+    let area = display.work_area;
+    if center.1 >= area.bottom() {
+        return Edge::Bottom;
+    }
+    if center.1 < area.y {
+        return Edge::Top;
+    }
+    if center.0 < area.x {
+        return Edge::Left;
+    }
+    if center.0 >= area.right() {
+        return Edge::Right;
+    }
+    let bounds = display.bounds;
+    [
+        (Edge::Bottom, bounds.bottom() - center.1),
+        (Edge::Top, center.1 - bounds.y),
+        (Edge::Left, center.0 - bounds.x),
+        (Edge::Right, bounds.right() - center.0),
+    ]
+    .into_iter()
+    .min_by_key(|(_, distance)| *distance)
+    .map(|(edge, _)| edge)
+    .unwrap_or(Edge::Bottom)
+}
+
+/// Move `position` so a window of `size` fits in `area` with `margin` around it; a window
+/// too big for the area is aligned to its top-left corner.
+fn clamp_into(area: Rect, margin: i32, size: (u32, u32), (x, y): (i32, i32)) -> (i32, i32) {
+    let clamp = |value: i32, start: i32, end: i32, length: u32| {
+        let max = end - margin - length as i32;
+        let min = start + margin;
+        if max < min {
+            start
+        } else {
+            value.clamp(min, max)
+        }
+    };
+    (
+        clamp(x, area.x, area.right(), size.0),
+        clamp(y, area.y, area.bottom(), size.1),
+    )
+}
+
+fn scaled(logical: f64, scale_factor: f64) -> i32 {
+    (logical * scale_factor.max(1.0)).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const POPOVER: (u32, u32) = (320, 360);
+
+    fn display(bounds: Rect, work_area: Rect) -> Display {
+        Display {
+            bounds,
+            work_area,
+            scale_factor: 1.0,
+        }
+    }
+
+    /// 1920x1080 with a 40 px taskbar at the bottom
+    fn primary() -> Display {
+        display(Rect::new(0, 0, 1920, 1080), Rect::new(0, 0, 1920, 1040))
+    }
+
+    #[test]
+    fn test_popover_sits_above_a_bottom_taskbar_icon() {
+        let icon = Rect::new(1700, 1044, 32, 32);
+        let (x, y) = popover_position(Some(icon), &[primary()], POPOVER).unwrap();
+        assert_eq!(x, 1716 - 160);
+        assert_eq!(y, 1040 - 12 - 360);
+
+        // An icon in the corner keeps the popover on screen.
+        let corner = Rect::new(1880, 1044, 32, 32);
+        let (x, _) = popover_position(Some(corner), &[primary()], POPOVER).unwrap();
+        assert_eq!(x, 1920 - 12 - 320);
+    }
+
+    #[test]
+    fn test_popover_drops_below_a_top_menu_bar() {
+        let mac = display(Rect::new(0, 0, 1440, 900), Rect::new(0, 25, 1440, 875));
+        let icon = Rect::new(1200, 0, 24, 24);
+        assert_eq!(
+            popover_position(Some(icon), &[mac], POPOVER),
+            Some((1212 - 160, 25 + 12))
+        );
+    }
+
+    #[test]
+    fn test_popover_beside_vertical_taskbars() {
+        let left = display(Rect::new(0, 0, 1920, 1080), Rect::new(60, 0, 1860, 1080));
+        // Tray icons sit near the bottom of a vertical taskbar.
+        let icon = Rect::new(14, 1000, 32, 32);
+        assert_eq!(
+            popover_position(Some(icon), &[left], POPOVER),
+            Some((60 + 12, 1080 - 12 - 360))
+        );
+
+        let right = display(Rect::new(0, 0, 1920, 1080), Rect::new(0, 0, 1860, 1080));
+        let icon = Rect::new(1874, 500, 32, 32);
+        assert_eq!(
+            popover_position(Some(icon), &[right], POPOVER),
+            Some((1860 - 12 - 320, 516 - 180))
+        );
+    }
+
+    #[test]
+    fn test_popover_follows_the_icon_to_a_secondary_monitor() {
+        // A 2560x1440 monitor at 150 % left of the primary one, taskbar at its bottom
+        let secondary = Display {
+            bounds: Rect::new(-2560, 0, 2560, 1440),
+            work_area: Rect::new(-2560, 0, 2560, 1380),
+            scale_factor: 1.5,
+        };
+        let icon = Rect::new(-300, 1390, 48, 48);
+        let (x, y) = popover_position(Some(icon), &[primary(), secondary], POPOVER).unwrap();
+        assert_eq!(x, -276 - 160);
+        assert_eq!(y, 1380 - 18 - 360);
+
+        // An icon reported just outside every monitor goes to the nearest one.
+        let off = Rect::new(-2600, 1400, 20, 20);
+        let (x, _) = popover_position(Some(off), &[primary(), secondary], POPOVER).unwrap();
+        assert_eq!(x, -2560 + 18);
+    }
+
+    #[test]
+    fn test_auto_hidden_taskbar_uses_the_nearest_edge() {
+        let hidden = display(Rect::new(0, 0, 1920, 1080), Rect::new(0, 0, 1920, 1080));
+        let icon = Rect::new(1000, 1050, 24, 24);
+        assert_eq!(
+            popover_position(Some(icon), &[hidden], POPOVER),
+            Some((1012 - 160, 1050 - 12 - 360))
+        );
+    }
+
+    #[test]
+    fn test_without_an_anchor_the_popover_takes_the_primary_corner() {
+        let expected = Some((1920 - 12 - 320, 1040 - 12 - 360));
+        assert_eq!(popover_position(None, &[primary()], POPOVER), expected);
+        // Linux reports an empty rect at the origin.
+        let empty = Rect::new(0, 0, 0, 0);
+        assert_eq!(
+            popover_position(Some(empty), &[primary()], POPOVER),
+            expected
+        );
+        assert_eq!(popover_position(None, &[], POPOVER), None);
+
+        let tiny = display(Rect::new(0, 0, 300, 300), Rect::new(0, 0, 300, 300));
+        assert_eq!(popover_position(None, &[tiny], POPOVER), Some((0, 0)));
+    }
+
+    fn item(id: &str, text: &str, timestamp: &str) -> HistoryItem {
+        HistoryItem {
+            id: id.to_string(),
+            text: text.to_string(),
+            timestamp: timestamp.to_string(),
+            duration_seconds: 2.0,
+            word_count: text.split_whitespace().count() as u32,
+            provider: None,
+            audio_path: None,
+            versions: Vec::new(),
+            status: Default::default(),
+            request_id: None,
+            session_stats: None,
+        }
+    }
+
+    #[test]
+    fn test_mini_items_are_trimmed_and_hidden_in_privacy_mode() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let long = "palavra ".repeat(40);
+        let history = vec![
+            item("a", "Bom dia", "2026-10-16T11:59:40Z"),
+            item("b", &long, "2026-10-16T11:45:00Z"),
+            item("c", "Três", "2026-10-16T09:00:00Z"),
+            item("d", "Ontem", "2026-10-15T08:00:00Z"),
+            item("e", "Semana", "2026-10-12T12:00:00Z"),
+            item("f", "Antigo", "2026-09-01T12:00:00Z"),
+        ];
+
+        let items = mini_items(&history, 0, false, now);
+        assert_eq!(items.len(), DEFAULT_ITEMS);
+        let times: Vec<&str> = items.iter().map(|i| i.relative_time.as_str()).collect();
+        assert_eq!(
+            times,
+            vec![
+                "just now",
+                "15 min ago",
+                "3 h ago",
+                "yesterday",
+                "4 days ago"
+            ]
+        );
+        assert!(items[1].snippet.ends_with('…'));
+        assert_eq!(items[1].word_count, 40);
+        assert_eq!(relative_time("2026-09-01T12:00:00Z", now), "2026-09-01");
+        assert_eq!(mini_items(&history, 100, false, now).len(), 6);
+
+        let private = mini_items(&history, 2, true, now);
+        assert_eq!(private.len(), 2);
+        assert!(private
+            .iter()
+            .all(|i| i.hidden && i.snippet == PRIVATE_PLACEHOLDER));
+        assert_eq!(private[0].relative_time, "just now");
+    }
+}
@@ -0,0 +1,460 @@
+//! Recordings made while offline. Their audio is archived next to a pending history item;
+//! once a provider is reachable again the queue transcribes them through the
+//! re-transcription planner and fills the items in. Long recordings keep their finished
+//! chunks in a progress file beside the audio, so a restart picks up where it stopped.
+
+use crate::audio_archive::CHUNK_PROGRESS_SUFFIX;
+use crate::config::{self, HistoryFilter, HistoryItem, RetranscribeOptions};
+use crate::crash_reports;
+use crate::history_store::{self, HistoryStore};
+use crate::history_updates::ChangeSummary;
+use crate::ipc::events::{self, Event, PendingTranscriptionCompleted};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::RwLock as TokioRwLock;
+use zentra_core::audio::wav;
+use zentra_core::offline::OfflineMode;
+use zentra_core::orchestrator::FailoverOrchestrator;
+use zentra_core::privacy::PrivacyGuard;
+use zentra_core::session::chunking::{transcribe_long_resumable, ChunkProgress};
+use zentra_core::text::postprocess::PostProcessor;
+
+const RECONNECT_POLL: Duration = Duration::from_secs(30);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Any HTTP answer from here means the cloud providers are reachable again.
+const CONNECTIVITY_URL: &str = "https://api.groq.com/openai/v1/models";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OfflineQueueSettings {
+    pub max_pending_items: usize,
+    /// Total size of the queued audio
+    pub max_pending_mb: u64,
+}
+
+impl Default for OfflineQueueSettings {
+    fn default() -> Self {
+        Self {
+            max_pending_items: 25,
+            max_pending_mb: 200,
+        }
+    }
+}
+
+/// Refuse new recordings of `incoming` bytes each, all of them, when together with the
+/// `pending` items they would take the queue past either bound.
+pub fn check_capacity(
+    limits: &OfflineQueueSettings,
+    pending: &[HistoryItem],
+    incoming: &[u64],
+) -> Result<(), String> {
+    if pending.len() + incoming.len() > limits.max_pending_items {
+        return Err(format!(
+            "Offline queue is full ({} recordings waiting)",
+            pending.len()
+        ));
+    }
+
+    let queued_bytes: u64 = pending
+        .iter()
+        .filter_map(|item| item.audio_path.as_deref())
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let incoming_bytes: u64 = incoming.iter().sum();
+    if queued_bytes + incoming_bytes > limits.max_pending_mb * 1024 * 1024 {
+        return Err(format!(
+            "Offline queue is full ({} MB waiting)",
+            queued_bytes / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct BackfillReport {
+    pub completed: Vec<PendingTranscriptionCompleted>,
+    /// Stopped early because the providers were unreachable again
+    pub interrupted: bool,
+    pub changes: ChangeSummary,
+}
+
+/// Transcribe pending items, oldest first. Stops at the first connectivity failure; any
+/// other failure leaves that item pending for the next round.
+pub async fn backfill_at(
+    path: &PathBuf,
+    store: &HistoryStore,
+    orchestrator: &FailoverOrchestrator,
+) -> Result<BackfillReport, String> {
+    let config = config::load_or_create_at(path)?;
+    let pending = store.pending()?;
+    let plan = config::plan_retranscription(
+        &pending,
+        &HistoryFilter::Ids(config::pending_ids(store)?),
+        &RetranscribeOptions::default(),
+    );
+    for skipped in &plan.skipped {
+        tracing::warn!("Pending item {} not sent: {}", skipped.id, skipped.reason);
+    }
+    let options = config::post_processing_options(&config);
+
+    let mut report = BackfillReport::default();
+    // The plan follows history order, newest first.
+    for target in plan.queued.into_iter().rev() {
+        let audio = match wav::read_wav_file(Path::new(&target.audio_path)) {
+            Ok(audio) => audio,
+            Err(e) => {
+                tracing::warn!("Pending item {} has unreadable audio: {}", target.id, e);
+                continue;
+            }
+        };
+
+        let progress = chunk_progress(Path::new(&target.audio_path), &target.id);
+        match transcribe_long_resumable(orchestrator, &audio, &progress).await {
+            Ok(transcript) => {
+                let text = PostProcessor::new(
+                    options
+                        .clone()
+                        .with_detected_language(transcript.language.as_deref()),
+                )
+                .run(&transcript.text)
+                .output;
+                let change = config::complete_pending_in(
+                    store,
+                    &config,
+                    &target.id,
+                    &text,
+                    &transcript.provider,
+                )?;
+                progress.remove();
+                report.changes.merge(change);
+                if !change.is_empty() {
+                    report.completed.push(PendingTranscriptionCompleted {
+                        id: target.id,
+                        text,
+                        provider: transcript.provider,
+                    });
+                }
+            }
+            Err(e) if e.is_connectivity_failure() => {
+                tracing::info!("Offline queue paused: {}", e);
+                report.interrupted = true;
+                break;
+            }
+            Err(e) => {
+                tracing::warn!("Pending item {} stays queued: {}", target.id, e);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Progress of the pending item `id`, beside its audio.
+fn chunk_progress(audio_path: &Path, id: &str) -> ChunkProgress {
+    ChunkProgress::new(audio_path.with_file_name(format!("{}{}", id, CHUNK_PROGRESS_SUFFIX)))
+}
+
+async fn is_reachable() -> bool {
+    Client::new()
+        .get(CONNECTIVITY_URL)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .is_ok()
+}
+
+/// Watch for connectivity while anything is queued or offline mode was detected, and
+/// drain the queue once providers answer again. Never runs while offline mode is on by
+/// hand or privacy mode is on.
+pub fn spawn(
+    app: AppHandle,
+    orchestrator: Arc<TokioRwLock<FailoverOrchestrator>>,
+    offline: OfflineMode,
+    privacy: PrivacyGuard,
+) {
+    crash_reports::supervise("offline queue", async move {
+        loop {
+            tokio::time::sleep(RECONNECT_POLL).await;
+            if offline.is_manual() || privacy.is_enabled() {
+                continue;
+            }
+            let (Ok(path), Ok(store)) = (config::config_path(&app), history_store::for_app(&app))
+            else {
+                continue;
+            };
+            let has_pending = config::pending_ids(&store).is_ok_and(|ids| !ids.is_empty());
+            if !has_pending && !offline.is_offline() {
+                continue;
+            }
+            if !is_reachable().await {
+                continue;
+            }
+
+            let orchestrator = orchestrator.read().await;
+            if orchestrator.reconnected() {
+                tracing::info!("Connectivity restored, leaving offline mode");
+            }
+            if !has_pending {
+                continue;
+            }
+            match backfill_at(&path, &store, &orchestrator).await {
+                Ok(report) => {
+                    for completed in &report.completed {
+                        events::emit_event(
+                            &app,
+                            Event::PendingTranscriptionCompleted(completed.clone()),
+                        );
+                    }
+                    events::history_changed(&app, report.changes);
+                }
+                Err(e) => tracing::warn!("Offline queue failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, HistoryStatus};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use zentra_core::audio::AudioBuffer;
+    use zentra_core::orchestrator::{ProviderConfig, DEFAULT_MAX_CONCURRENT};
+    use zentra_core::stt::{STTAdapter, STTError, Transcript};
+
+    /// Fails with a network error until `online` is set.
+    struct FlakyAdapter {
+        online: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for FlakyAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            if !self.online.load(Ordering::SeqCst) {
+                return Err(STTError::NetworkError("no route to host".to_string()));
+            }
+            Ok(Transcript {
+                text: "ditado no avião".to_string(),
+                confidence: 0.9,
+                duration_secs: audio.duration_secs,
+                provider: "flaky".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "flaky"
+        }
+    }
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("zentra-offline-{}-{}", label, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn speech() -> AudioBuffer {
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![2_000i16; 16_000]);
+        audio
+    }
+
+    #[tokio::test]
+    async fn test_offline_recording_is_queued_and_backfilled_on_reconnect() {
+        let dir = temp_dir("backfill");
+        let path = dir.join(config::CONFIG_FILE);
+        let store = HistoryStore::in_memory().unwrap();
+        let archive = dir.join("audio");
+        let online = Arc::new(AtomicBool::new(false));
+        let offline = OfflineMode::default();
+        let orchestrator = FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "flaky".to_string(),
+            priority: 1,
+            adapter: Box::new(FlakyAdapter {
+                online: online.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 5,
+            confidence_threshold: 0.5,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }])
+        .with_offline(offline.clone());
+
+        let error = orchestrator.transcribe(&speech()).await.unwrap_err();
+        assert!(error.is_connectivity_failure());
+        let item = config::enqueue_pending_in(
+            &store,
+            &AppConfig::default(),
+            &archive,
+            &[speech()],
+            &PrivacyGuard::default(),
+        )
+        .unwrap()
+        .remove(0);
+        assert_eq!(item.status, HistoryStatus::PendingTranscription);
+        let audio_path = PathBuf::from(item.audio_path.clone().unwrap());
+        assert!(audio_path.exists());
+
+        // Still offline: the item stays pending.
+        let report = backfill_at(&path, &store, &orchestrator).await.unwrap();
+        assert!(report.interrupted);
+        assert!(report.completed.is_empty());
+
+        online.store(true, Ordering::SeqCst);
+        orchestrator.reconnected();
+        let report = backfill_at(&path, &store, &orchestrator).await.unwrap();
+        assert!(!report.interrupted);
+        assert_eq!(report.completed.len(), 1);
+        assert_eq!(report.completed[0].id, item.id);
+        assert_eq!(report.completed[0].text, "ditado no avião");
+
+        let stored = &store.items().unwrap()[0];
+        assert_eq!(stored.status, HistoryStatus::Transcribed);
+        assert_eq!(stored.text, "ditado no avião");
+        assert_eq!(stored.provider.as_deref(), Some("flaky"));
+        assert!(stored.audio_path.is_none());
+        assert!(!audio_path.exists());
+        assert_eq!(store.stats().unwrap().total_transcriptions, 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Records every chunk it transcribes; while `online` is unset it drops the connection
+    /// after two chunks, like a machine going to sleep mid-file.
+    struct ChunkCounter {
+        online: Arc<AtomicBool>,
+        transcribed: Arc<std::sync::Mutex<Vec<(usize, i64)>>>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for ChunkCounter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            let mut transcribed = self.transcribed.lock().unwrap();
+            if !self.online.load(Ordering::SeqCst) && transcribed.len() == 2 {
+                return Err(STTError::NetworkError("connection reset".to_string()));
+            }
+            transcribed.push((
+                audio.samples.len(),
+                audio.samples.iter().map(|&s| s as i64).sum(),
+            ));
+            Ok(Transcript {
+                text: format!("trecho {}", transcribed.len()),
+                confidence: 0.9,
+                duration_secs: audio.duration_secs,
+                provider: "counter".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "counter"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_long_recording_resumes_from_its_last_chunk_after_a_restart() {
+        let dir = temp_dir("resume");
+        let path = dir.join(config::CONFIG_FILE);
+        let db = dir.join(history_store::HISTORY_DB_FILE);
+        let archive = dir.join("audio");
+        let mut recording = AudioBuffer::new(16_000, 1);
+        let samples: Vec<i16> = (0..16_000 * 180)
+            .map(|idx| ((idx as f32 * 0.05).sin() * 6_000.0) as i16)
+            .collect();
+        recording.append(&samples);
+        let item = config::enqueue_pending_in(
+            &HistoryStore::open(&db).unwrap(),
+            &AppConfig::default(),
+            &archive,
+            &[recording],
+            &PrivacyGuard::default(),
+        )
+        .unwrap()
+        .remove(0);
+
+        let online = Arc::new(AtomicBool::new(false));
+        let transcribed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        // Each run builds the queue from disk, as the app does at startup.
+        let orchestrator = || {
+            FailoverOrchestrator::new(vec![ProviderConfig {
+                id: "counter".to_string(),
+                priority: 1,
+                adapter: Box::new(ChunkCounter {
+                    online: online.clone(),
+                    transcribed: transcribed.clone(),
+                }),
+                max_retries: 0,
+                timeout_secs: 5,
+                confidence_threshold: 0.5,
+                local: false,
+                max_concurrent: DEFAULT_MAX_CONCURRENT,
+            }])
+        };
+
+        let store = || HistoryStore::open(&db).unwrap();
+        let report = backfill_at(&path, &store(), &orchestrator()).await.unwrap();
+        assert!(report.interrupted);
+        let progress = archive.join(format!("{}{}", item.id, CHUNK_PROGRESS_SUFFIX));
+        assert_eq!(
+            std::fs::read_to_string(&progress).unwrap().lines().count(),
+            2
+        );
+
+        online.store(true, Ordering::SeqCst);
+        let report = backfill_at(&path, &store(), &orchestrator()).await.unwrap();
+        assert_eq!(report.completed.len(), 1);
+        assert_eq!(
+            report.completed[0].text,
+            "Trecho 1 trecho 2 trecho 3 trecho 4"
+        );
+        let mut chunks = transcribed.lock().unwrap().clone();
+        assert_eq!(chunks.len(), 4);
+        chunks.sort_unstable();
+        chunks.dedup();
+        assert_eq!(chunks.len(), 4, "a chunk was transcribed twice");
+        assert!(!progress.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_queue_respects_item_and_size_bounds() {
+        let dir = temp_dir("bounds");
+        let store = HistoryStore::in_memory().unwrap();
+        let archive = dir.join("audio");
+        let privacy = PrivacyGuard::default();
+
+        let mut config = AppConfig::default();
+        config.offline_queue.max_pending_items = 2;
+        let enqueue = |recordings: usize, privacy: &PrivacyGuard| {
+            let recordings = vec![speech(); recordings];
+            config::enqueue_pending_in(&store, &config, &archive, &recordings, privacy)
+        };
+
+        enqueue(1, &privacy).unwrap();
+        // Two more don't fit; neither is kept.
+        assert!(enqueue(2, &privacy).is_err());
+        assert_eq!(store.pending().unwrap().len(), 1);
+        assert_eq!(std::fs::read_dir(&archive).unwrap().count(), 1);
+
+        enqueue(1, &privacy).unwrap();
+        let full = enqueue(1, &privacy);
+        assert!(full.unwrap_err().contains("2 recordings waiting"));
+
+        let limits = OfflineQueueSettings {
+            max_pending_items: 10,
+            max_pending_mb: 0,
+        };
+        assert!(check_capacity(&limits, &store.pending().unwrap(), &[1]).is_err());
+
+        assert!(enqueue(1, &PrivacyGuard::new(true)).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,90 +1,199 @@
 ﻿use serde::Serialize;
 use std::{thread, time::Duration};
+use zentra_core::error_codes::ErrorCode;
+
+/// Fallback reason when the paste would land in one of Zentra's own windows.
+pub const TARGET_IS_ZENTRA: &str = "target_is_zentra";
+/// Fallback reason on macOS when Zentra may not post keyboard events; the UI links to
+/// Privacy & Security > Accessibility.
+pub const ACCESSIBILITY_NOT_GRANTED: &str = "accessibility_not_granted";
+
+/// How the transcript reached the target, if it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMethod {
+    ClipboardPaste,
+    /// Injected key by key after the target refused the paste keystroke
+    Typed,
+    None,
+}
 
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PasteAttempt {
+    /// The text reached the target, pasted or typed
     pub pasted: bool,
+    pub method: PasteMethod,
+    /// Why the clipboard paste was not used; kept when the text was typed instead
     pub reason: Option<String>,
+    /// Catalogue code of `reason`
+    pub error_code: Option<ErrorCode>,
+    /// Dictation this paste belongs to
+    pub request_id: Option<String>,
 }
 
 impl PasteAttempt {
     fn pasted() -> Self {
         Self {
             pasted: true,
+            method: PasteMethod::ClipboardPaste,
             reason: None,
+            error_code: None,
+            request_id: None,
         }
     }
 
-    fn fallback(reason: impl Into<String>) -> Self {
+    fn typed(reason: String) -> Self {
+        Self {
+            pasted: true,
+            method: PasteMethod::Typed,
+            reason: Some(reason),
+            error_code: None,
+            request_id: None,
+        }
+    }
+
+    pub fn fallback(reason: impl Into<String>) -> Self {
+        let reason = reason.into();
         Self {
             pasted: false,
-            reason: Some(reason.into()),
+            method: PasteMethod::None,
+            error_code: Some(reason_code(&reason)),
+            reason: Some(reason),
+            request_id: None,
         }
     }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+/// Catalogue code for a fallback reason. Tool reasons are built as `<tool>_missing`,
+/// `<tool>_timeout`, `<tool>_failed[: stderr]` and `<tool>_error: ...`.
+pub fn reason_code(reason: &str) -> ErrorCode {
+    match reason {
+        "no_target_window"
+        | "no_foreground_window"
+        | "no_focused_control"
+        | "focus_changed"
+        | "restore_focus_failed" => ErrorCode::PasteNoTarget,
+        TARGET_IS_ZENTRA => ErrorCode::PasteTargetIsZentra,
+        ACCESSIBILITY_NOT_GRANTED => ErrorCode::PastePermission,
+        "ydotoold_not_running" | "wayland_paste_tool_missing" => ErrorCode::PasteToolMissing,
+        "unsupported_platform"
+        | "no_display_server"
+        | "x11_display_unavailable"
+        | "wayland_virtual_keyboard_unsupported" => ErrorCode::PasteUnsupported,
+        other if other.starts_with("unsupported_target_class:") => ErrorCode::PasteUnsupported,
+        other if other.ends_with("_missing") => ErrorCode::PasteToolMissing,
+        other if other.ends_with("_timeout") => ErrorCode::PasteTimeout,
+        _ => ErrorCode::PasteFailed,
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct PasteContext {
     #[cfg(target_os = "windows")]
     target_hwnd: Option<isize>,
+    /// X11 window id; Wayland never exposes the focused window
+    #[cfg(target_os = "linux")]
+    target_window: Option<isize>,
 }
 
 impl PasteContext {
-    pub fn capture_target(&mut self, zentra_window: isize) {
+    /// Remember the foreground window as the paste target. A Zentra window is kept too, so
+    /// the paste can report [`TARGET_IS_ZENTRA`] instead of a generic failure.
+    pub fn capture_target(&mut self) {
         #[cfg(target_os = "windows")]
         {
-            self.target_hwnd = capture_target_window(zentra_window);
+            self.target_hwnd = capture_target_window();
         }
 
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(target_os = "linux")]
         {
-            let _ = zentra_window;
+            self.target_window = capture_target_window_linux();
         }
     }
 
-    pub fn try_auto_paste(&mut self, zentra_window: isize) -> PasteAttempt {
+    /// Forget the window captured at recording start.
+    pub fn clear_target(&mut self) {
         #[cfg(target_os = "windows")]
         {
-            let attempt = try_auto_paste_windows(self.target_hwnd, zentra_window);
+            self.target_hwnd = None;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.target_window = None;
+        }
+    }
+
+    /// `zentra_windows` lists the handles of every Zentra window; it is called once the
+    /// paste is about to happen, so windows opened during the recording count too.
+    pub fn try_auto_paste(&mut self, zentra_windows: impl FnOnce() -> Vec<isize>) -> PasteAttempt {
+        #[cfg(target_os = "windows")]
+        {
+            let attempt = try_auto_paste_windows(self.target_hwnd, &zentra_windows());
             self.target_hwnd = None;
             return attempt;
         }
 
         #[cfg(target_os = "macos")]
         {
-            let _ = zentra_window;
+            let _ = zentra_windows;
             return try_auto_paste_macos();
         }
 
-        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
         {
-            let _ = zentra_window;
-            PasteAttempt::fallback("unsupported_platform")
+            let _ = zentra_windows;
+            return PasteAttempt::fallback("unsupported_platform");
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let target = self.target_window.take();
+            try_auto_paste_linux(target, &zentra_windows())
         }
     }
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux", test))]
 fn is_same_window(a: isize, b: isize) -> bool {
     a != 0 && b != 0 && a == b
 }
 
+#[cfg(any(target_os = "windows", target_os = "linux", test))]
+fn is_zentra_window(window: isize, zentra_windows: &[isize]) -> bool {
+    zentra_windows
+        .iter()
+        .any(|&zentra| is_same_window(window, zentra))
+}
+
+/// The window captured at recording start, if it may receive the paste. With the
+/// non-focusable pill it is the user's app; when the dashboard or setup window was in
+/// front, a paste there could land in a settings field, so every Zentra window is refused.
+#[cfg(any(target_os = "windows", target_os = "linux", test))]
+fn validate_target(target: Option<isize>, zentra_windows: &[isize]) -> Result<isize, &'static str> {
+    match target {
+        None | Some(0) => Err("no_target_window"),
+        Some(target) if is_zentra_window(target, zentra_windows) => Err(TARGET_IS_ZENTRA),
+        Some(target) => Ok(target),
+    }
+}
+
 #[cfg(target_os = "windows")]
-fn capture_target_window(zentra_window: isize) -> Option<isize> {
+fn capture_target_window() -> Option<isize> {
     use winapi::um::winuser::GetForegroundWindow;
 
-    unsafe {
-        let hwnd = GetForegroundWindow() as isize;
-        if hwnd == 0 || is_same_window(hwnd, zentra_window) {
-            None
-        } else {
-            Some(hwnd)
-        }
-    }
+    let hwnd = unsafe { GetForegroundWindow() as isize };
+    Some(hwnd).filter(|&hwnd| hwnd != 0)
 }
 
 #[cfg(target_os = "windows")]
-fn try_auto_paste_windows(target_hwnd: Option<isize>, zentra_window: isize) -> PasteAttempt {
+fn try_auto_paste_windows(target_hwnd: Option<isize>, zentra_windows: &[isize]) -> PasteAttempt {
     use std::mem;
     use winapi::shared::windef::HWND;
     use winapi::um::winuser::{
@@ -95,9 +204,9 @@ fn try_auto_paste_windows(target_hwnd: Option<isize>, zentra_window: isize) -> P
 
     thread::sleep(Duration::from_millis(150));
 
-    let target_hwnd = match target_hwnd {
-        Some(hwnd) if hwnd != 0 => hwnd,
-        _ => return PasteAttempt::fallback("no_target_window"),
+    let target_hwnd = match validate_target(target_hwnd, zentra_windows) {
+        Ok(hwnd) => hwnd,
+        Err(reason) => return PasteAttempt::fallback(reason),
     };
 
     unsafe {
@@ -106,7 +215,8 @@ fn try_auto_paste_windows(target_hwnd: Option<isize>, zentra_window: isize) -> P
             return PasteAttempt::fallback("no_foreground_window");
         }
 
-        if is_same_window(current_hwnd, zentra_window) {
+        // A focusable pill (or another Zentra window) took focus during the recording.
+        if is_zentra_window(current_hwnd, zentra_windows) {
             let restored = SetForegroundWindow(target_hwnd as HWND) != 0;
             if !restored {
                 return PasteAttempt::fallback("restore_focus_failed");
@@ -221,30 +331,951 @@ unsafe fn make_key_input(vk: u16, key_up: bool) -> winapi::um::winuser::INPUT {
 const MACOS_PASTE_DELAY_MS: u64 = 180;
 
 #[cfg(target_os = "macos")]
-fn try_auto_paste_macos() -> PasteAttempt {
-    use std::process::Command;
+const ACCESSIBILITY_SETTINGS_URL: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+
+#[cfg(any(target_os = "linux", test))]
+const SCRIPT_POLL: Duration = Duration::from_millis(20);
+
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScriptOutcome {
+    Completed {
+        success: bool,
+        stderr: String,
+    },
+    TimedOut,
+    /// The program is not on PATH
+    NotInstalled,
+    SpawnFailed(String),
+}
+
+/// The bits of a child process the timeout wrapper needs; stubbed in tests.
+#[cfg(any(target_os = "linux", test))]
+trait ScriptProcess {
+    /// `Some((success, stderr))` once the process has exited.
+    fn try_finish(&mut self) -> std::io::Result<Option<(bool, String)>>;
+    fn kill(&mut self);
+}
+
+#[cfg(target_os = "linux")]
+impl ScriptProcess for std::process::Child {
+    fn try_finish(&mut self) -> std::io::Result<Option<(bool, String)>> {
+        use std::io::Read;
+
+        let Some(status) = self.try_wait()? else {
+            return Ok(None);
+        };
+        let mut stderr = String::new();
+        if let Some(pipe) = self.stderr.as_mut() {
+            let _ = pipe.read_to_string(&mut stderr);
+        }
+        Ok(Some((status.success(), stderr.trim().to_string())))
+    }
+
+    fn kill(&mut self) {
+        let _ = std::process::Child::kill(self);
+        let _ = self.wait();
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn wait_with_timeout(
+    process: &mut impl ScriptProcess,
+    timeout: Duration,
+    poll: Duration,
+) -> ScriptOutcome {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        match process.try_finish() {
+            Ok(Some((success, stderr))) => return ScriptOutcome::Completed { success, stderr },
+            Ok(None) => {}
+            Err(err) => {
+                process.kill();
+                return ScriptOutcome::SpawnFailed(err.to_string());
+            }
+        }
+        if std::time::Instant::now() >= deadline {
+            process.kill();
+            return ScriptOutcome::TimedOut;
+        }
+        thread::sleep(poll);
+    }
+}
+
+/// Whether macOS lets Zentra post keyboard events. With `prompt`, macOS shows its
+/// Accessibility dialog when the answer is no; a paste never asks, only setup does.
+#[cfg(target_os = "macos")]
+pub(crate) fn accessibility_granted(prompt: bool) -> bool {
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::string::{CFString, CFStringRef};
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        static kAXTrustedCheckOptionPrompt: CFStringRef;
+        fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> bool;
+    }
+
+    let key = unsafe { CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt) };
+    let options = CFDictionary::from_CFType_pairs(&[(key, CFBoolean::from(prompt))]);
+    unsafe { AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef()) }
+}
 
+/// Cmd+V through Quartz events: Cmd down, V down, V up, Cmd up. Needs Accessibility and
+/// nothing else, so it works where System Events scripting is disabled.
+#[cfg(target_os = "macos")]
+fn post_native_paste() -> Result<(), String> {
+    use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    const KEY_COMMAND: u16 = 0x37;
+    const KEY_V: u16 = 0x09;
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "event_source_unavailable".to_string())?;
+    let sequence = [
+        (KEY_COMMAND, true, CGEventFlags::CGEventFlagCommand),
+        (KEY_V, true, CGEventFlags::CGEventFlagCommand),
+        (KEY_V, false, CGEventFlags::CGEventFlagCommand),
+        (KEY_COMMAND, false, CGEventFlags::CGEventFlagNull),
+    ];
+    for (keycode, key_down, flags) in sequence {
+        let event = CGEvent::new_keyboard_event(source.clone(), keycode, key_down)
+            .map_err(|_| "keyboard_event_failed".to_string())?;
+        event.set_flags(flags);
+        event.post(CGEventTapLocation::HID);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn try_auto_paste_macos() -> PasteAttempt {
     thread::sleep(Duration::from_millis(MACOS_PASTE_DELAY_MS));
 
-    let output = Command::new("osascript")
-        .args([
-            "-e",
-            r#"tell application "System Events" to keystroke "v" using command down"#,
-        ])
-        .output();
-
-    match output {
-        Ok(out) if out.status.success() => PasteAttempt::pasted(),
-        Ok(out) => {
-            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-            if stderr.is_empty() {
-                PasteAttempt::fallback("macos_applescript_failed_accessibility")
+    if !accessibility_granted(false) {
+        return PasteAttempt::fallback(ACCESSIBILITY_NOT_GRANTED);
+    }
+    match post_native_paste() {
+        Ok(()) => PasteAttempt::pasted(),
+        Err(reason) => PasteAttempt::fallback(reason),
+    }
+}
+
+#[cfg(target_os = "linux")]
+const LINUX_PASTE_DELAY_MS: u64 = 150;
+
+/// Paste tools are killed after this long; `windowactivate --sync` waits forever for a
+/// window that refuses focus.
+#[cfg(target_os = "linux")]
+const LINUX_TOOL_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DisplayServer {
+    X11,
+    Wayland,
+    Unknown,
+}
+
+/// Wayland wins when both are present: under XWayland `DISPLAY` is set too, but X11 tools
+/// only reach other XWayland clients there.
+#[cfg(any(target_os = "linux", test))]
+fn detect_display_server(
+    wayland_display: Option<&str>,
+    session_type: Option<&str>,
+    x_display: Option<&str>,
+) -> DisplayServer {
+    let is_set = |value: Option<&str>| value.is_some_and(|value| !value.trim().is_empty());
+    let session_is =
+        |kind: &str| session_type.is_some_and(|value| value.eq_ignore_ascii_case(kind));
+
+    if is_set(wayland_display) || session_is("wayland") {
+        DisplayServer::Wayland
+    } else if is_set(x_display) || session_is("x11") {
+        DisplayServer::X11
+    } else {
+        DisplayServer::Unknown
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_display_server() -> DisplayServer {
+    let var = |name: &str| std::env::var(name).ok();
+    detect_display_server(
+        var("WAYLAND_DISPLAY").as_deref(),
+        var("XDG_SESSION_TYPE").as_deref(),
+        var("DISPLAY").as_deref(),
+    )
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_window_id(stdout: &str) -> Option<isize> {
+    stdout.trim().parse::<isize>().ok().filter(|id| *id != 0)
+}
+
+#[cfg(target_os = "linux")]
+fn x11_active_window() -> Result<isize, String> {
+    match std::process::Command::new("xdotool")
+        .arg("getactivewindow")
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            parse_window_id(&String::from_utf8_lossy(&output.stdout))
+                .ok_or_else(|| "no_foreground_window".to_string())
+        }
+        Ok(_) => Err("no_foreground_window".to_string()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err("xdotool_missing".to_string())
+        }
+        Err(err) => Err(format!("xdotool_error: {}", err)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn capture_target_window_linux() -> Option<isize> {
+    if current_display_server() != DisplayServer::X11 {
+        return None;
+    }
+    match x11_active_window() {
+        Ok(window) => Some(window).filter(|&window| window != 0),
+        Err(reason) => {
+            tracing::debug!("No paste target captured: {}", reason);
+            None
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn run_tool(program: &str, args: &[&str]) -> ScriptOutcome {
+    use std::process::{Command, Stdio};
+
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    match child {
+        Ok(mut child) => wait_with_timeout(&mut child, LINUX_TOOL_TIMEOUT, SCRIPT_POLL),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => ScriptOutcome::NotInstalled,
+        Err(err) => ScriptOutcome::SpawnFailed(err.to_string()),
+    }
+}
+
+/// Map a paste tool result to a reason the UI can act on; `None` means the keys were sent.
+#[cfg(any(target_os = "linux", test))]
+fn classify_linux_tool(tool: &str, outcome: &ScriptOutcome) -> Option<String> {
+    match outcome {
+        ScriptOutcome::Completed { success: true, .. } => None,
+        ScriptOutcome::Completed { stderr, .. } => {
+            let lower = stderr.to_ascii_lowercase();
+            let reason = if lower.contains("virtual keyboard") {
+                // wtype on compositors without zwp_virtual_keyboard (GNOME, for one)
+                "wayland_virtual_keyboard_unsupported".to_string()
+            } else if lower.contains("ydotoold") || lower.contains("socket") {
+                "ydotoold_not_running".to_string()
+            } else if lower.contains("open display") {
+                "x11_display_unavailable".to_string()
+            } else if stderr.is_empty() {
+                format!("{}_failed", tool)
             } else {
-                PasteAttempt::fallback(format!("macos_applescript_failed: {}", stderr))
-            }
+                format!("{}_failed: {}", tool, stderr)
+            };
+            Some(reason)
+        }
+        ScriptOutcome::TimedOut => Some(format!("{}_timeout", tool)),
+        ScriptOutcome::NotInstalled => Some(format!("{}_missing", tool)),
+        ScriptOutcome::SpawnFailed(err) => Some(format!("{}_error: {}", tool, err)),
+    }
+}
+
+/// wtype is tried first; when it fails, ydotool's reason is the useful one unless ydotool
+/// is not installed at all.
+#[cfg(any(target_os = "linux", test))]
+fn wayland_fallback_reason(wtype: String, ydotool: String) -> String {
+    match (wtype.as_str(), ydotool.as_str()) {
+        ("wtype_missing", "ydotool_missing") => "wayland_paste_tool_missing".to_string(),
+        (_, "ydotool_missing") => wtype,
+        _ => ydotool,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn try_auto_paste_linux(target_window: Option<isize>, zentra_windows: &[isize]) -> PasteAttempt {
+    thread::sleep(Duration::from_millis(LINUX_PASTE_DELAY_MS));
+
+    match current_display_server() {
+        DisplayServer::X11 => try_auto_paste_x11(target_window, zentra_windows),
+        DisplayServer::Wayland => try_auto_paste_wayland(),
+        DisplayServer::Unknown => PasteAttempt::fallback("no_display_server"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn try_auto_paste_x11(target_window: Option<isize>, zentra_windows: &[isize]) -> PasteAttempt {
+    let mut current = match x11_active_window() {
+        Ok(window) => window,
+        Err(reason) => return PasteAttempt::fallback(reason),
+    };
+    let target = match validate_target(target_window, zentra_windows) {
+        Ok(target) => target,
+        Err(reason) => return PasteAttempt::fallback(reason),
+    };
+
+    if is_zentra_window(current, zentra_windows) {
+        let target_id = target.to_string();
+        let activate = run_tool("xdotool", &["windowactivate", "--sync", &target_id]);
+        if let Some(reason) = classify_linux_tool("xdotool", &activate) {
+            tracing::warn!("Restoring focus to {} failed: {}", target, reason);
+            return PasteAttempt::fallback("restore_focus_failed");
+        }
+        thread::sleep(Duration::from_millis(60));
+        current = x11_active_window().unwrap_or_default();
+    }
+
+    if current != target {
+        return PasteAttempt::fallback("focus_changed");
+    }
+
+    let paste = run_tool("xdotool", &["key", "--clearmodifiers", "ctrl+v"]);
+    match classify_linux_tool("xdotool", &paste) {
+        None => PasteAttempt::pasted(),
+        Some(reason) => PasteAttempt::fallback(reason),
+    }
+}
+
+/// Wayland clients cannot see or change focus, so this relies on the pill never taking it.
+#[cfg(target_os = "linux")]
+fn try_auto_paste_wayland() -> PasteAttempt {
+    let wtype = run_tool("wtype", &["-M", "ctrl", "v", "-m", "ctrl"]);
+    let Some(wtype_reason) = classify_linux_tool("wtype", &wtype) else {
+        return PasteAttempt::pasted();
+    };
+
+    // Linux input event codes: KEY_LEFTCTRL = 29, KEY_V = 47.
+    let ydotool = run_tool("ydotool", &["key", "29:1", "47:1", "47:0", "29:0"]);
+    match classify_linux_tool("ydotool", &ydotool) {
+        None => PasteAttempt::pasted(),
+        Some(ydotool_reason) => {
+            PasteAttempt::fallback(wayland_fallback_reason(wtype_reason, ydotool_reason))
+        }
+    }
+}
+
+/// Clipboard text access for [`paste_transcript`]; the app implements it over the
+/// clipboard plugin.
+pub trait ClipboardText {
+    fn read_text(&self) -> Option<String>;
+    fn write_text(&self, text: &str) -> Result<(), String>;
+}
+
+/// Put `text` on the clipboard, paste it with `paste`, and with `restore_after` set put the
+/// previous clipboard text back once the target app has had time to read it. A failed paste
+/// leaves the transcript on the clipboard for a manual Ctrl+V, and so does a clipboard the
+/// user changed during the wait.
+pub fn paste_transcript(
+    clipboard: &impl ClipboardText,
+    text: &str,
+    restore_after: Option<Duration>,
+    paste: impl FnOnce() -> PasteAttempt,
+) -> Result<PasteAttempt, String> {
+    let previous = restore_after.and_then(|_| clipboard.read_text());
+    clipboard.write_text(text)?;
+    let attempt = paste();
+
+    if let (true, Some(delay), Some(previous)) = (attempt.pasted, restore_after, previous) {
+        thread::sleep(delay);
+        if clipboard.read_text().as_deref() == Some(text) {
+            clipboard.write_text(&previous)?;
+        }
+    }
+    Ok(attempt)
+}
+
+/// Typing runs at about this many characters per second; faster input gets dropped by
+/// terminals and remote-desktop clients.
+const TYPE_CHARS_PER_SEC: u64 = 200;
+
+/// Reasons where the target window has focus but refused (or never got) the paste
+/// keystroke. Typing the text in key by key still reaches it.
+pub fn typing_may_work(reason: &str) -> bool {
+    reason == "send_input_incomplete" || reason.starts_with("unsupported_target_class:")
+}
+
+/// Type `text` into the target when the paste was refused there; otherwise, or when
+/// typing fails too, `attempt` is returned unchanged.
+pub fn type_if_refused(
+    attempt: PasteAttempt,
+    text: &str,
+    type_text: impl FnOnce(&str) -> Result<(), String>,
+) -> PasteAttempt {
+    let Some(reason) = attempt.reason.as_deref().filter(|_| !attempt.pasted) else {
+        return attempt;
+    };
+    if !typing_may_work(reason) {
+        return attempt;
+    }
+    match type_text(text) {
+        Ok(()) => PasteAttempt::typed(reason.to_string()),
+        Err(e) => {
+            tracing::warn!("Typing the transcript failed after '{}': {}", reason, e);
+            attempt
         }
-        Err(err) => PasteAttempt::fallback(format!("macos_applescript_error: {}", err)),
     }
 }
 
+/// One injected keystroke: Enter for a line break, otherwise the UTF-16 units of a single
+/// character, so a surrogate pair (emoji) is sent as one key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TypedKey {
+    Enter,
+    Unicode(Vec<u16>),
+}
+
+fn typed_keys(text: &str) -> Vec<TypedKey> {
+    text.replace("\r\n", "\n")
+        .chars()
+        .filter(|&c| c != '\r')
+        .map(|c| match c {
+            '\n' => TypedKey::Enter,
+            c => TypedKey::Unicode(c.encode_utf16(&mut [0; 2]).to_vec()),
+        })
+        .collect()
+}
+
+/// Inject `text` into the focused window one character at a time.
+pub fn type_text(text: &str) -> Result<(), String> {
+    let interval = Duration::from_millis(1000 / TYPE_CHARS_PER_SEC);
+    for key in typed_keys(text) {
+        send_typed_key(&key)?;
+        thread::sleep(interval);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn send_typed_key(key: &TypedKey) -> Result<(), String> {
+    use winapi::um::winuser::{
+        SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VK_RETURN,
+    };
+
+    let mut inputs: Vec<INPUT> = match key {
+        TypedKey::Enter => unsafe {
+            vec![
+                make_key_input(VK_RETURN as u16, false),
+                make_key_input(VK_RETURN as u16, true),
+            ]
+        },
+        TypedKey::Unicode(units) => units
+            .iter()
+            .flat_map(|&unit| [0, KEYEVENTF_KEYUP].map(|up| (unit, up)))
+            .map(|(unit, up)| unsafe {
+                let mut input: INPUT = std::mem::zeroed();
+                input.type_ = INPUT_KEYBOARD;
+                *input.u.ki_mut() = KEYBDINPUT {
+                    wVk: 0,
+                    wScan: unit,
+                    dwFlags: KEYEVENTF_UNICODE | up,
+                    time: 0,
+                    dwExtraInfo: 0,
+                };
+                input
+            })
+            .collect(),
+    };
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_mut_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        )
+    };
+    if sent == inputs.len() as u32 {
+        Ok(())
+    } else {
+        Err("send_input_incomplete".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn send_typed_key(key: &TypedKey) -> Result<(), String> {
+    use core_graphics::event::{CGEvent, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    const KEY_RETURN: u16 = 0x24;
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "event_source_unavailable".to_string())?;
+    for key_down in [true, false] {
+        let keycode = if *key == TypedKey::Enter {
+            KEY_RETURN
+        } else {
+            0
+        };
+        let event = CGEvent::new_keyboard_event(source.clone(), keycode, key_down)
+            .map_err(|_| "keyboard_event_failed".to_string())?;
+        if let TypedKey::Unicode(units) = key {
+            event.set_string_from_utf16_unchecked(units);
+        }
+        event.post(CGEventTapLocation::HID);
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn send_typed_key(_key: &TypedKey) -> Result<(), String> {
+    Err("unsupported_platform".to_string())
+}
+
+/// Whether pasting and shortcut capture need the Accessibility grant, and whether it is
+/// there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilityPermission {
+    /// Only macOS asks for the grant
+    pub required: bool,
+    pub granted: bool,
+}
+
+/// Check the Accessibility grant; with `prompt`, macOS offers to open System Settings
+/// when it is missing.
+pub fn accessibility_permission(prompt: bool) -> AccessibilityPermission {
+    #[cfg(target_os = "macos")]
+    {
+        AccessibilityPermission {
+            required: true,
+            granted: accessibility_granted(prompt),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = prompt;
+        AccessibilityPermission {
+            required: false,
+            granted: true,
+        }
+    }
+}
+
+/// Open System Settings on the Accessibility pane.
+pub fn open_accessibility_settings() -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(ACCESSIBILITY_SETTINGS_URL)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err("Accessibility permission only exists on macOS".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictation::DictationTracker;
+    use std::cell::RefCell;
+
+    const PILL: isize = 0x100;
+    const EDITOR: isize = 0x200;
+    const DASHBOARD: isize = 0x300;
+    const SETUP: isize = 0x400;
+
+    #[derive(Default)]
+    struct FakeClipboard {
+        text: RefCell<Option<String>>,
+        writes: RefCell<Vec<String>>,
+    }
+
+    impl FakeClipboard {
+        fn holding(text: &str) -> Self {
+            let clipboard = Self::default();
+            *clipboard.text.borrow_mut() = Some(text.to_string());
+            clipboard
+        }
+
+        fn current(&self) -> Option<String> {
+            self.text.borrow().clone()
+        }
+    }
+
+    impl ClipboardText for FakeClipboard {
+        fn read_text(&self) -> Option<String> {
+            self.current()
+        }
+
+        fn write_text(&self, text: &str) -> Result<(), String> {
+            *self.text.borrow_mut() = Some(text.to_string());
+            self.writes.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_clipboard_is_restored_after_a_successful_paste() {
+        let clipboard = FakeClipboard::holding("snippet I copied");
+        let attempt = paste_transcript(
+            &clipboard,
+            "olá mundo",
+            Some(Duration::from_millis(1)),
+            || {
+                // The paste keystroke sees the transcript.
+                assert_eq!(clipboard.current().as_deref(), Some("olá mundo"));
+                PasteAttempt::pasted()
+            },
+        )
+        .unwrap();
+
+        assert!(attempt.pasted);
+        assert_eq!(clipboard.current().as_deref(), Some("snippet I copied"));
+        assert_eq!(
+            *clipboard.writes.borrow(),
+            ["olá mundo", "snippet I copied"]
+        );
+    }
+
+    #[test]
+    fn test_failed_paste_leaves_the_transcript_for_manual_paste() {
+        let clipboard = FakeClipboard::holding("snippet I copied");
+        let attempt = paste_transcript(
+            &clipboard,
+            "olá mundo",
+            Some(Duration::from_millis(1)),
+            || PasteAttempt::fallback("no_focused_control"),
+        )
+        .unwrap();
+
+        assert!(!attempt.pasted);
+        assert_eq!(clipboard.current().as_deref(), Some("olá mundo"));
+    }
+
+    #[test]
+    fn test_nothing_to_restore_without_the_option_or_previous_text() {
+        let clipboard = FakeClipboard::holding("snippet I copied");
+        paste_transcript(&clipboard, "olá mundo", None, PasteAttempt::pasted).unwrap();
+        assert_eq!(clipboard.current().as_deref(), Some("olá mundo"));
+
+        // An image or an empty clipboard has no text to put back.
+        let clipboard = FakeClipboard::default();
+        paste_transcript(
+            &clipboard,
+            "olá mundo",
+            Some(Duration::from_millis(1)),
+            PasteAttempt::pasted,
+        )
+        .unwrap();
+        assert_eq!(*clipboard.writes.borrow(), ["olá mundo"]);
+    }
+
+    #[test]
+    fn test_refused_paste_is_typed_instead() {
+        let typed = RefCell::new(String::new());
+        let typist = |text: &str| {
+            typed.borrow_mut().push_str(text);
+            Ok(())
+        };
+        let refused = PasteAttempt::fallback("unsupported_target_class:ConsoleWindowClass");
+        let attempt = type_if_refused(refused, "olá 👍", typist);
+        assert!(attempt.pasted);
+        assert_eq!(attempt.method, PasteMethod::Typed);
+        assert_eq!(attempt.error_code, None);
+        assert_eq!(
+            attempt.reason.as_deref(),
+            Some("unsupported_target_class:ConsoleWindowClass")
+        );
+        assert_eq!(*typed.borrow(), "olá 👍");
+
+        // Without a focused target there is nothing to type into.
+        let attempt = type_if_refused(
+            PasteAttempt::fallback("no_focused_control"),
+            "olá",
+            |_| unreachable!(),
+        );
+        assert_eq!(attempt.method, PasteMethod::None);
+        assert!(type_if_refused(PasteAttempt::pasted(), "olá", |_| unreachable!()).pasted);
+
+        // Typing failing too keeps the original fallback.
+        let attempt = type_if_refused(
+            PasteAttempt::fallback("send_input_incomplete"),
+            "olá",
+            |_| Err("send_input_incomplete".to_string()),
+        );
+        assert!(!attempt.pasted);
+        assert_eq!(attempt.error_code, Some(ErrorCode::PasteFailed));
+    }
+
+    #[test]
+    fn test_typed_keys_keep_surrogate_pairs_together() {
+        assert_eq!(
+            typed_keys("a👍\r\nç\n"),
+            vec![
+                TypedKey::Unicode(vec![0x61]),
+                TypedKey::Unicode(vec![0xD83D, 0xDC4D]),
+                TypedKey::Enter,
+                TypedKey::Unicode(vec![0xE7]),
+                TypedKey::Enter,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_activating_pill_keeps_user_app_as_target() {
+        // Clicking a non-focusable pill leaves the editor in the foreground.
+        assert_eq!(validate_target(Some(EDITOR), &[PILL]), Ok(EDITOR));
+    }
+
+    #[test]
+    fn test_focused_pill_is_never_a_paste_target() {
+        assert_eq!(validate_target(Some(PILL), &[PILL]), Err(TARGET_IS_ZENTRA));
+        assert_eq!(validate_target(Some(0), &[PILL]), Err("no_target_window"));
+        assert_eq!(validate_target(None, &[PILL]), Err("no_target_window"));
+    }
 
+    #[test]
+    fn test_every_zentra_window_is_refused_as_a_target() {
+        let zentra = [PILL, DASHBOARD, SETUP];
+        for window in zentra {
+            assert_eq!(
+                validate_target(Some(window), &zentra),
+                Err(TARGET_IS_ZENTRA)
+            );
+        }
+        assert_eq!(validate_target(Some(EDITOR), &zentra), Ok(EDITOR));
+        // A window that was not created yet (no handle) matches nothing.
+        assert_eq!(validate_target(Some(EDITOR), &[PILL, 0]), Ok(EDITOR));
+        assert!(is_zentra_window(DASHBOARD, &zentra));
+        assert!(!is_zentra_window(0, &[0]));
+    }
+
+    #[test]
+    fn test_unknown_pill_handle_does_not_match_foreground() {
+        assert_eq!(validate_target(Some(EDITOR), &[0]), Ok(EDITOR));
+        assert_eq!(validate_target(Some(EDITOR), &[]), Ok(EDITOR));
+    }
+
+    struct StubProcess {
+        finishes_after: Option<usize>,
+        polls: usize,
+        killed: bool,
+    }
+
+    impl ScriptProcess for StubProcess {
+        fn try_finish(&mut self) -> std::io::Result<Option<(bool, String)>> {
+            self.polls += 1;
+            Ok(match self.finishes_after {
+                Some(polls) if self.polls >= polls => Some((true, String::new())),
+                _ => None,
+            })
+        }
+
+        fn kill(&mut self) {
+            self.killed = true;
+        }
+    }
+
+    #[test]
+    fn test_hung_script_is_killed_and_reported_as_timeout() {
+        let mut process = StubProcess {
+            finishes_after: None,
+            polls: 0,
+            killed: false,
+        };
+        let outcome = wait_with_timeout(
+            &mut process,
+            Duration::from_millis(30),
+            Duration::from_millis(5),
+        );
+        assert_eq!(outcome, ScriptOutcome::TimedOut);
+        assert!(process.killed);
+        assert_eq!(
+            classify_linux_tool("xdotool", &outcome).as_deref(),
+            Some("xdotool_timeout")
+        );
+    }
+
+    #[test]
+    fn test_script_finishing_in_time_is_not_killed() {
+        let mut process = StubProcess {
+            finishes_after: Some(3),
+            polls: 0,
+            killed: false,
+        };
+        let outcome = wait_with_timeout(
+            &mut process,
+            Duration::from_secs(2),
+            Duration::from_millis(1),
+        );
+        assert!(!process.killed);
+        assert_eq!(classify_linux_tool("xdotool", &outcome), None);
+    }
+
+    #[test]
+    fn test_wayland_wins_over_xwayland_display() {
+        assert_eq!(
+            detect_display_server(Some("wayland-0"), Some("wayland"), Some(":0")),
+            DisplayServer::Wayland
+        );
+        assert_eq!(
+            detect_display_server(None, Some("Wayland"), None),
+            DisplayServer::Wayland
+        );
+        assert_eq!(
+            detect_display_server(Some(""), Some("x11"), Some(":1")),
+            DisplayServer::X11
+        );
+        assert_eq!(
+            detect_display_server(None, None, Some(":0")),
+            DisplayServer::X11
+        );
+        assert_eq!(
+            detect_display_server(None, Some("tty"), None),
+            DisplayServer::Unknown
+        );
+    }
+
+    #[test]
+    fn test_xdotool_window_ids_parse() {
+        assert_eq!(parse_window_id("41943047\n"), Some(41_943_047));
+        assert_eq!(parse_window_id("0"), None);
+        assert_eq!(parse_window_id(""), None);
+    }
+
+    #[test]
+    fn test_linux_tool_failures_are_classified() {
+        let failed = |stderr: &str| ScriptOutcome::Completed {
+            success: false,
+            stderr: stderr.to_string(),
+        };
+        assert_eq!(
+            classify_linux_tool(
+                "wtype",
+                &failed("Compositor does not support the virtual keyboard protocol")
+            )
+            .as_deref(),
+            Some("wayland_virtual_keyboard_unsupported")
+        );
+        assert_eq!(
+            classify_linux_tool(
+                "ydotool",
+                &failed("failed to connect socket `/tmp/.ydotool_socket': No such file")
+            )
+            .as_deref(),
+            Some("ydotoold_not_running")
+        );
+        assert_eq!(
+            classify_linux_tool("xdotool", &failed("Error: Can't open display: (null)")).as_deref(),
+            Some("x11_display_unavailable")
+        );
+        assert_eq!(
+            classify_linux_tool("xdotool", &ScriptOutcome::NotInstalled).as_deref(),
+            Some("xdotool_missing")
+        );
+        assert_eq!(
+            classify_linux_tool("xdotool", &ScriptOutcome::TimedOut).as_deref(),
+            Some("xdotool_timeout")
+        );
+        assert_eq!(
+            classify_linux_tool(
+                "wtype",
+                &ScriptOutcome::Completed {
+                    success: true,
+                    stderr: String::new(),
+                }
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_every_fallback_reason_has_a_catalogue_code() {
+        let cases = [
+            ("no_target_window", ErrorCode::PasteNoTarget),
+            ("no_foreground_window", ErrorCode::PasteNoTarget),
+            ("no_focused_control", ErrorCode::PasteNoTarget),
+            ("focus_changed", ErrorCode::PasteNoTarget),
+            ("restore_focus_failed", ErrorCode::PasteNoTarget),
+            (TARGET_IS_ZENTRA, ErrorCode::PasteTargetIsZentra),
+            (ACCESSIBILITY_NOT_GRANTED, ErrorCode::PastePermission),
+            ("event_source_unavailable", ErrorCode::PasteFailed),
+            ("keyboard_event_failed", ErrorCode::PasteFailed),
+            ("send_input_incomplete", ErrorCode::PasteFailed),
+            ("unsupported_target_class:Foo", ErrorCode::PasteUnsupported),
+            ("unsupported_platform", ErrorCode::PasteUnsupported),
+            ("no_display_server", ErrorCode::PasteUnsupported),
+            ("x11_display_unavailable", ErrorCode::PasteUnsupported),
+            (
+                "wayland_virtual_keyboard_unsupported",
+                ErrorCode::PasteUnsupported,
+            ),
+            ("wayland_paste_tool_missing", ErrorCode::PasteToolMissing),
+            ("ydotoold_not_running", ErrorCode::PasteToolMissing),
+            ("xdotool_missing", ErrorCode::PasteToolMissing),
+            ("wtype_missing", ErrorCode::PasteToolMissing),
+            ("ydotool_timeout", ErrorCode::PasteTimeout),
+            ("xdotool_failed", ErrorCode::PasteFailed),
+            ("xdotool_failed: missing window", ErrorCode::PasteFailed),
+            ("xdotool_error: boom", ErrorCode::PasteFailed),
+        ];
+        for (reason, code) in cases {
+            assert_eq!(reason_code(reason), code, "{}", reason);
+            assert_eq!(PasteAttempt::fallback(reason).error_code, Some(code));
+        }
+    }
+
+    #[test]
+    fn test_wayland_reason_prefers_the_installed_tool() {
+        let reason = |wtype: &str, ydotool: &str| {
+            wayland_fallback_reason(wtype.to_string(), ydotool.to_string())
+        };
+        assert_eq!(
+            reason("wtype_missing", "ydotool_missing"),
+            "wayland_paste_tool_missing"
+        );
+        assert_eq!(
+            reason("wayland_virtual_keyboard_unsupported", "ydotool_missing"),
+            "wayland_virtual_keyboard_unsupported"
+        );
+        assert_eq!(
+            reason(
+                "wayland_virtual_keyboard_unsupported",
+                "ydotoold_not_running"
+            ),
+            "ydotoold_not_running"
+        );
+        assert_eq!(
+            reason("wtype_missing", "ydotool_timeout"),
+            "ydotool_timeout"
+        );
+    }
+
+    #[test]
+    fn test_paste_reports_the_dictation_started_with_the_capture() {
+        let tracker = DictationTracker::default();
+        // What `start_capture` does for a real recording.
+        let request_id = tracker.begin();
+
+        let clipboard = FakeClipboard::default();
+        let attempt = paste_transcript(&clipboard, "olá", None, PasteAttempt::pasted)
+            .unwrap()
+            .with_request_id(tracker.current());
+        assert_eq!(attempt.request_id.as_deref(), Some(request_id.as_str()));
+        assert_eq!(
+            serde_json::to_value(&attempt).unwrap(),
+            serde_json::json!({
+                "pasted": true,
+                "method": "clipboard_paste",
+                "reason": null,
+                "errorCode": null,
+                "requestId": request_id
+            })
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+const APP_DIR: &str = "zentra";
+const PORTABLE_MARKER: &str = "portable.marker";
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_DATA_DIR: &str = "data";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageMode {
+    AppData,
+    Portable,
+}
+
+/// Single source of truth for every on-disk location Zentra writes to.
+/// In portable mode everything lives under `./data/` next to the executable.
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    mode: StorageMode,
+    base: PathBuf,
+    app_data_base: Option<PathBuf>,
+}
+
+impl Resolver {
+    /// Detect the storage mode from the marker file / CLI flag and build the resolver.
+    pub fn detect(app: &AppHandle) -> Result<Self, String> {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        let portable_flag = std::env::args().any(|arg| arg == PORTABLE_FLAG);
+        let app_data_base = app
+            .path()
+            .resolve(APP_DIR, BaseDirectory::AppData)
+            .map_err(|e| format!("Failed to resolve config dir: {}", e))?;
+
+        let resolver = Self::from_parts(exe_dir.as_deref(), portable_flag, app_data_base);
+        if let Some(hint) = resolver.migration_hint() {
+            tracing::warn!("{}", hint);
+        }
+        tracing::info!(
+            "Storage mode: {:?} (base={})",
+            resolver.mode,
+            resolver.base.display()
+        );
+        Ok(resolver)
+    }
+
+    /// Resolution precedence: `--portable` flag, then `portable.marker` next to the
+    /// executable, otherwise the AppData directory.
+    pub fn from_parts(exe_dir: Option<&Path>, portable_flag: bool, app_data_base: PathBuf) -> Self {
+        let portable_base = exe_dir.and_then(|dir| {
+            let marker_present = dir.join(PORTABLE_MARKER).exists();
+            (portable_flag || marker_present).then(|| dir.join(PORTABLE_DATA_DIR))
+        });
+
+        match portable_base {
+            Some(base) => Self {
+                mode: StorageMode::Portable,
+                base,
+                app_data_base: Some(app_data_base),
+            },
+            None => Self {
+                mode: StorageMode::AppData,
+                base: app_data_base,
+                app_data_base: None,
+            },
+        }
+    }
+
+    /// Resolver stored in Tauri state, falling back to detection when not managed yet.
+    pub fn for_app(app: &AppHandle) -> Result<Self, String> {
+        match app.try_state::<Resolver>() {
+            Some(resolver) => Ok(resolver.inner().clone()),
+            None => Self::detect(app),
+        }
+    }
+
+    pub fn mode(&self) -> StorageMode {
+        self.mode
+    }
+
+    pub fn is_portable(&self) -> bool {
+        self.mode == StorageMode::Portable
+    }
+
+    /// OS keychains are tied to the machine, so portable installs keep secrets in the config file.
+    pub fn allows_keychain(&self) -> bool {
+        !self.is_portable()
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base
+    }
+
+    pub fn config_dir(&self) -> PathBuf {
+        self.base.clone()
+    }
+
+    pub fn history_dir(&self) -> PathBuf {
+        self.base.join("history")
+    }
+
+    /// Panic reports; see `crash_reports`.
+    pub fn crashes_dir(&self) -> PathBuf {
+        self.base.join("crashes")
+    }
+
+    /// Local models (whisper.cpp, Vosk, the VAD) looked up before the ones next to the app.
+    pub fn models_dir(&self) -> PathBuf {
+        self.base.join("models")
+    }
+
+    pub fn audio_archive_dir(&self) -> PathBuf {
+        self.base.join("audio")
+    }
+
+    /// Markdown exports of finalized sessions (meeting notes).
+    pub fn notes_dir(&self) -> PathBuf {
+        self.base.join("notes")
+    }
+
+    /// Scratch files of local engines.
+    pub fn temp_dir(&self) -> PathBuf {
+        match self.mode {
+            StorageMode::Portable => self.base.join("tmp"),
+            StorageMode::AppData => std::env::temp_dir().join(APP_DIR),
+        }
+    }
+
+    /// Create `dir` if needed and return it.
+    pub fn ensure_dir(dir: PathBuf) -> Result<PathBuf, String> {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(dir)
+    }
+
+    /// When portable mode is active but data only exists in AppData, explain how to move it.
+    pub fn migration_hint(&self) -> Option<String> {
+        let app_data_base = self.app_data_base.as_ref()?;
+        let legacy_config = app_data_base.join(crate::config::CONFIG_FILE);
+        let portable_config = self.base.join(crate::config::CONFIG_FILE);
+        if legacy_config.exists() && !portable_config.exists() {
+            Some(format!(
+                "Portable mode is active but existing data was found in {}. Copy its contents into {} to keep your settings and history.",
+                app_data_base.display(),
+                self.base.display()
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_layout() -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("zentra-paths-{}", uuid::Uuid::new_v4()));
+        let exe_dir = root.join("usb");
+        let app_data = root.join("appdata").join(APP_DIR);
+        fs::create_dir_all(&exe_dir).unwrap();
+        fs::create_dir_all(&app_data).unwrap();
+        (exe_dir, app_data)
+    }
+
+    #[test]
+    fn test_app_data_is_default_without_marker_or_flag() {
+        let (exe_dir, app_data) = temp_layout();
+        let resolver = Resolver::from_parts(Some(&exe_dir), false, app_data.clone());
+        assert_eq!(resolver.mode(), StorageMode::AppData);
+        assert_eq!(resolver.config_dir(), app_data);
+        assert!(resolver.allows_keychain());
+    }
+
+    #[test]
+    fn test_marker_file_selects_portable_data_dir() {
+        let (exe_dir, app_data) = temp_layout();
+        fs::write(exe_dir.join(PORTABLE_MARKER), b"").unwrap();
+
+        let resolver = Resolver::from_parts(Some(&exe_dir), false, app_data);
+        assert_eq!(resolver.mode(), StorageMode::Portable);
+        assert_eq!(resolver.config_dir(), exe_dir.join(PORTABLE_DATA_DIR));
+        assert_eq!(resolver.temp_dir(), exe_dir.join(PORTABLE_DATA_DIR).join("tmp"));
+        assert!(!resolver.allows_keychain());
+    }
+
+    #[test]
+    fn test_flag_selects_portable_without_marker() {
+        let (exe_dir, app_data) = temp_layout();
+        let resolver = Resolver::from_parts(Some(&exe_dir), true, app_data);
+        assert!(resolver.is_portable());
+        assert_eq!(resolver.audio_archive_dir(), exe_dir.join(PORTABLE_DATA_DIR).join("audio"));
+    }
+
+    #[test]
+    fn test_unknown_exe_dir_falls_back_to_app_data() {
+        let (_, app_data) = temp_layout();
+        let resolver = Resolver::from_parts(None, true, app_data.clone());
+        assert_eq!(resolver.mode(), StorageMode::AppData);
+        assert_eq!(resolver.base_dir(), app_data.as_path());
+    }
+
+    #[test]
+    fn test_migration_hint_only_when_portable_is_empty() {
+        let (exe_dir, app_data) = temp_layout();
+        fs::write(app_data.join(crate::config::CONFIG_FILE), b"{}").unwrap();
+
+        let resolver = Resolver::from_parts(Some(&exe_dir), true, app_data);
+        assert!(resolver.migration_hint().is_some());
+
+        fs::create_dir_all(resolver.config_dir()).unwrap();
+        fs::write(resolver.config_dir().join(crate::config::CONFIG_FILE), b"{}").unwrap();
+        assert!(resolver.migration_hint().is_none());
+    }
+}
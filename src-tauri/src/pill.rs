@@ -0,0 +1,294 @@
+use tauri::{AppHandle, Manager, Monitor, Position, WebviewWindow};
+
+pub const PILL_WINDOW: &str = "main";
+/// Gap between the pill and the bottom of the work area, in logical pixels.
+const BOTTOM_MARGIN: f64 = 16.0;
+/// Logical size of the pill when the window can't report it.
+const DEFAULT_PILL_SIZE: (f64, f64) = (360.0, 72.0);
+
+/// A rectangle in physical pixels of the virtual desktop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x as f64
+            && y >= self.y as f64
+            && x < self.x as f64 + self.width as f64
+            && y < self.y as f64 + self.height as f64
+    }
+
+    fn scaled(&self, factor: f64) -> Rect {
+        Rect {
+            x: (self.x as f64 * factor).round() as i32,
+            y: (self.y as f64 * factor).round() as i32,
+            width: (self.width as f64 * factor).round() as u32,
+            height: (self.height as f64 * factor).round() as u32,
+        }
+    }
+}
+
+/// A monitor as Tauri reports it: physical bounds and work area, and its own scale factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitorLayout {
+    pub bounds: Rect,
+    pub work_area: Rect,
+    pub scale: f64,
+}
+
+impl MonitorLayout {
+    fn from_monitor(monitor: &Monitor) -> Self {
+        let work = monitor.work_area();
+        Self {
+            bounds: Rect {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+            },
+            work_area: Rect {
+                x: work.position.x,
+                y: work.position.y,
+                width: work.size.width,
+                height: work.size.height,
+            },
+            scale: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Where the cursor is, in the coordinate space the platform reports it in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CursorPosition {
+    /// Physical pixels of the virtual desktop (Windows, X11).
+    Physical { x: f64, y: f64 },
+    /// Points of the global display space (macOS). A monitor spans its physical bounds
+    /// divided by its own scale factor there.
+    Logical { x: f64, y: f64 },
+}
+
+/// Index of the monitor under `cursor`.
+pub fn monitor_at(monitors: &[MonitorLayout], cursor: CursorPosition) -> Option<usize> {
+    monitors.iter().position(|monitor| match cursor {
+        CursorPosition::Physical { x, y } => monitor.bounds.contains(x, y),
+        CursorPosition::Logical { x, y } => monitor
+            .bounds
+            .scaled(1.0 / monitor.scale.max(f64::EPSILON))
+            .contains(x, y),
+    })
+}
+
+/// Physical position that puts a window of `logical_size` at the bottom center of
+/// `monitor`'s work area, sized for that monitor's scale factor.
+pub fn bottom_center(monitor: &MonitorLayout, logical_size: (f64, f64)) -> (i32, i32) {
+    let work = &monitor.work_area;
+    let width = logical_size.0 * monitor.scale;
+    let height = logical_size.1 * monitor.scale;
+    let x = work.x as f64 + (work.width as f64 - width) / 2.0;
+    let y = work.y as f64 + work.height as f64 - height - BOTTOM_MARGIN * monitor.scale;
+    (x.round() as i32, y.round() as i32)
+}
+
+/// Put the pill at the bottom center of `monitor`.
+pub fn place_on(window: &WebviewWindow, monitor: &Monitor) {
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let logical_size = window
+        .outer_size()
+        .map(|size| (size.width as f64 / scale, size.height as f64 / scale))
+        .unwrap_or(DEFAULT_PILL_SIZE);
+    let layout = MonitorLayout::from_monitor(monitor);
+    let (x, y) = bottom_center(&layout, logical_size);
+    // macOS places windows in points; a physical position would be converted with the
+    // scale of the monitor the pill is leaving.
+    #[cfg(target_os = "macos")]
+    let position: Position =
+        tauri::LogicalPosition::new(x as f64 / layout.scale, y as f64 / layout.scale).into();
+    #[cfg(not(target_os = "macos"))]
+    let position: Position = tauri::PhysicalPosition::new(x, y).into();
+    if let Err(e) = window.set_position(position) {
+        tracing::warn!("Failed to move the pill: {}", e);
+    }
+}
+
+/// Move the pill to the monitor under the cursor, unless it is already on it (where it may
+/// have been dragged to).
+pub fn follow_cursor_monitor(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(PILL_WINDOW) else {
+        return;
+    };
+    let Some(cursor) = cursor_position(&window) else {
+        return;
+    };
+    let monitors = window.available_monitors().unwrap_or_default();
+    let layouts: Vec<MonitorLayout> = monitors.iter().map(MonitorLayout::from_monitor).collect();
+    let Some(target) = monitor_at(&layouts, cursor) else {
+        return;
+    };
+    let current = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| MonitorLayout::from_monitor(&monitor));
+    if current == Some(layouts[target]) {
+        return;
+    }
+    tracing::info!(
+        "Moving the pill to the monitor at ({}, {})",
+        layouts[target].bounds.x,
+        layouts[target].bounds.y
+    );
+    place_on(&window, &monitors[target]);
+}
+
+#[cfg(target_os = "windows")]
+fn cursor_position(_window: &WebviewWindow) -> Option<CursorPosition> {
+    use winapi::shared::windef::POINT;
+    use winapi::um::winuser::GetCursorPos;
+
+    let mut point = POINT { x: 0, y: 0 };
+    // The app is per-monitor DPI aware, so this is in physical pixels.
+    (unsafe { GetCursorPos(&mut point) } != 0).then_some(CursorPosition::Physical {
+        x: point.x as f64,
+        y: point.y as f64,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn cursor_position(_window: &WebviewWindow) -> Option<CursorPosition> {
+    use core_graphics::event::CGEvent;
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState).ok()?;
+    let location = CGEvent::new(source).ok()?.location();
+    Some(CursorPosition::Logical {
+        x: location.x,
+        y: location.y,
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn cursor_position(window: &WebviewWindow) -> Option<CursorPosition> {
+    // Unavailable on Wayland, where the pill stays put.
+    let position = window.cursor_position().ok()?;
+    Some(CursorPosition::Physical {
+        x: position.x,
+        y: position.y,
+    })
+}
+
+/// Make the pill a pure overlay (default) or a regular focusable window.
+///
+/// A non-activating pill never becomes the foreground window, so the paste target captured
+/// at recording start stays valid and no focus restore is needed before pasting.
+pub fn apply_focus_style(app: &AppHandle, focusable: bool) {
+    let Some(window) = app.get_webview_window(PILL_WINDOW) else {
+        return;
+    };
+
+    if let Err(e) = window.set_focusable(focusable) {
+        tracing::warn!("Failed to update pill focusability: {}", e);
+    }
+    let _ = window.set_skip_taskbar(true);
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(hwnd) = window.hwnd() {
+            set_no_activate(hwnd.0 as isize, !focusable);
+        }
+    }
+
+    tracing::info!("Pill window focusable: {}", focusable);
+}
+
+/// WS_EX_NOACTIVATE keeps clicks on the stop/cancel buttons working without stealing focus.
+#[cfg(target_os = "windows")]
+fn set_no_activate(hwnd: isize, enabled: bool) {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        GetWindowLongPtrW, SetWindowLongPtrW, GWL_EXSTYLE, WS_EX_NOACTIVATE,
+    };
+
+    unsafe {
+        let hwnd = hwnd as HWND;
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        let updated = if enabled {
+            style | WS_EX_NOACTIVATE as isize
+        } else {
+            style & !(WS_EX_NOACTIVATE as isize)
+        };
+        if updated != style {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, updated);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(bounds: (i32, i32, u32, u32), taskbar: u32, scale: f64) -> MonitorLayout {
+        let (x, y, width, height) = bounds;
+        MonitorLayout {
+            bounds: Rect {
+                x,
+                y,
+                width,
+                height,
+            },
+            work_area: Rect {
+                x,
+                y,
+                width,
+                height: height - taskbar,
+            },
+            scale,
+        }
+    }
+
+    #[test]
+    fn test_cursor_finds_its_monitor_in_physical_and_logical_layouts() {
+        // Windows: a 100% laptop panel and a 200% 4K monitor to its right, raised a bit.
+        let windows = [
+            monitor((0, 0, 1920, 1080), 40, 1.0),
+            monitor((1920, -200, 3840, 2160), 80, 2.0),
+        ];
+        let physical = |x, y| CursorPosition::Physical { x, y };
+        assert_eq!(monitor_at(&windows, physical(100.0, 100.0)), Some(0));
+        assert_eq!(monitor_at(&windows, physical(1920.0, -150.0)), Some(1));
+        assert_eq!(monitor_at(&windows, physical(5000.0, 1900.0)), Some(1));
+        assert_eq!(monitor_at(&windows, physical(100.0, -150.0)), None);
+        assert_eq!(monitor_at(&windows, physical(5760.0, 0.0)), None);
+
+        // macOS: a Retina panel (1440x900 points) and a 1x external display right of it.
+        // Positions are the point origin times each monitor's own scale.
+        let macos = [
+            monitor((0, 0, 2880, 1800), 50, 2.0),
+            monitor((1440, 0, 1920, 1080), 25, 1.0),
+        ];
+        let logical = |x, y| CursorPosition::Logical { x, y };
+        assert_eq!(monitor_at(&macos, logical(1000.0, 800.0)), Some(0));
+        assert_eq!(monitor_at(&macos, logical(1500.0, 100.0)), Some(1));
+        assert_eq!(monitor_at(&macos, logical(1000.0, 950.0)), None);
+        // Read as physical pixels, the same point would land on the Retina panel.
+        assert_eq!(monitor_at(&macos, physical(1500.0, 100.0)), Some(0));
+    }
+
+    #[test]
+    fn test_pill_sits_at_the_bottom_center_of_each_work_area() {
+        let pill = (360.0, 72.0);
+        let laptop = monitor((0, 0, 1920, 1080), 40, 1.0);
+        assert_eq!(bottom_center(&laptop, pill), (780, 952));
+
+        // Twice the physical size and margin on the 200% monitor.
+        let external = monitor((1920, -200, 3840, 2160), 80, 2.0);
+        assert_eq!(bottom_center(&external, pill), (3480, 1704));
+
+        let fractional = monitor((-2560, 0, 2560, 1440), 48, 1.25);
+        assert_eq!(bottom_center(&fractional, pill), (-1505, 1282));
+    }
+}
@@ -0,0 +1,494 @@
+//! What happens to a transcript once the dictation is finalized: an ordered list of
+//! actions (clean up, optimize, paste, save, send) run by [`run`]. Each action gets the text
+//! the earlier ones produced; one failing doesn't stop the rest. In privacy mode the
+//! actions that would send the text out or write it to disk are skipped.
+
+use crate::paste::PasteAttempt;
+use serde::{Deserialize, Serialize};
+use zentra_core::privacy::PrivacyGuard;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PostAction {
+    /// Local clean-up: spacing, punctuation, capitalization, filler words
+    Clarity,
+    /// Rewrite with a prompt profile
+    Optimize {
+        profile: String,
+    },
+    /// Copy, then paste into the window that was focused when recording started
+    Paste,
+    /// Copy to the clipboard without pasting
+    CopyOnly,
+    /// Append to the dated Markdown file in the notes folder
+    SaveToNotes,
+    /// POST the text with the webhook integration's template
+    Webhook,
+    RecordHistory,
+}
+
+impl PostAction {
+    fn transforms_text(&self) -> bool {
+        matches!(self, PostAction::Clarity | PostAction::Optimize { .. })
+    }
+
+    fn delivers_text(&self) -> bool {
+        matches!(self, PostAction::Paste | PostAction::CopyOnly)
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            PostAction::Clarity => "Clarity",
+            PostAction::Optimize { .. } => "Optimize",
+            PostAction::Paste => "Paste",
+            PostAction::CopyOnly => "Copy only",
+            PostAction::SaveToNotes => "Save to notes",
+            PostAction::Webhook => "Webhook",
+            PostAction::RecordHistory => "Record history",
+        }
+    }
+
+    /// Why privacy mode keeps this action from running, if it does. History applies
+    /// privacy mode itself.
+    fn held_back_by(&self, privacy: &PrivacyGuard) -> Option<&'static str> {
+        match self {
+            PostAction::Optimize { .. } if !privacy.allows_provider(false) => {
+                Some("Privacy mode is on; the text is not sent to an LLM provider")
+            }
+            PostAction::Webhook if !privacy.allows_provider(false) => {
+                Some("Privacy mode is on; the text is not sent to the webhook")
+            }
+            PostAction::SaveToNotes if !privacy.allows_persistence() => {
+                Some("Privacy mode is on; the text is not saved to notes")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Today's behavior: keep the transcript in history, then paste it.
+pub fn default_post_actions() -> Vec<PostAction> {
+    vec![PostAction::RecordHistory, PostAction::Paste]
+}
+
+/// Reject lists that can't do what the user meant: text changed after it was already
+/// pasted, two deliveries, repeated side effects, or nothing that keeps the transcript.
+pub fn validate(actions: &[PostAction]) -> Result<(), String> {
+    let mut delivered: Option<&PostAction> = None;
+    for (index, action) in actions.iter().enumerate() {
+        let position = index + 1;
+        if let PostAction::Optimize { profile } = action {
+            if profile.trim().is_empty() {
+                return Err(format!("Action {} (Optimize) needs a profile", position));
+            }
+        }
+        if !action.transforms_text() && actions[..index].contains(action) {
+            return Err(format!(
+                "Action {} ({}) appears more than once",
+                position,
+                action.label()
+            ));
+        }
+        if action.delivers_text() {
+            if let Some(earlier) = delivered {
+                return Err(format!(
+                    "Action {} ({}) conflicts with {}; pick one",
+                    position,
+                    action.label(),
+                    earlier.label()
+                ));
+            }
+            delivered = Some(action);
+        }
+        if action.transforms_text() {
+            if let Some(earlier) = delivered {
+                return Err(format!(
+                    "Action {} ({}) must come before {}",
+                    position,
+                    action.label(),
+                    earlier.label()
+                ));
+            }
+        }
+    }
+    if actions.iter().all(PostAction::transforms_text) {
+        return Err("Add at least one action that keeps the transcript".to_string());
+    }
+    Ok(())
+}
+
+/// How one action went. `detail` is what it produced: a file path, a provider, a paste
+/// fallback reason.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionResult {
+    pub action: PostAction,
+    pub ok: bool,
+    pub detail: Option<String>,
+    pub error: Option<String>,
+    /// Why the action didn't run; it counts as ok
+    pub skipped: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessedResult {
+    /// The text after every transform that succeeded
+    pub text: String,
+    pub actions: Vec<ActionResult>,
+    /// Set when a `Paste` action ran
+    pub paste: Option<PasteAttempt>,
+}
+
+/// Side effects of the actions; the app wires them to the clipboard, the prompt engine,
+/// the notes folder, the webhook and history.
+pub trait PostActionEffects {
+    fn clarity(&mut self, text: &str) -> String;
+    /// The optimized text and the provider that produced it
+    async fn optimize(&mut self, text: &str, profile: &str) -> Result<(String, String), String>;
+    async fn paste(&mut self, text: &str) -> Result<PasteAttempt, String>;
+    fn copy(&mut self, text: &str) -> Result<(), String>;
+    /// The file written
+    fn save_to_notes(&mut self, text: &str) -> Result<String, String>;
+    async fn webhook(&mut self, text: &str) -> Result<(), String>;
+    fn record_history(&mut self, text: &str) -> Result<(), String>;
+}
+
+pub async fn run<E: PostActionEffects>(
+    actions: &[PostAction],
+    text: &str,
+    effects: &mut E,
+    privacy: &PrivacyGuard,
+) -> ProcessedResult {
+    let mut text = text.to_string();
+    let mut results = Vec::with_capacity(actions.len());
+    let mut paste = None;
+
+    for action in actions {
+        if let Some(reason) = action.held_back_by(privacy) {
+            tracing::info!("Post action {} skipped: {}", action.label(), reason);
+            results.push(ActionResult {
+                action: action.clone(),
+                ok: true,
+                detail: None,
+                error: None,
+                skipped: Some(reason.to_string()),
+            });
+            continue;
+        }
+        let outcome: Result<Option<String>, String> = match action {
+            PostAction::Clarity => {
+                text = effects.clarity(&text);
+                Ok(None)
+            }
+            PostAction::Optimize { profile } => {
+                effects
+                    .optimize(&text, profile)
+                    .await
+                    .map(|(optimized, provider)| {
+                        text = optimized;
+                        Some(provider)
+                    })
+            }
+            PostAction::Paste => effects.paste(&text).await.map(|attempt| {
+                let reason = attempt.reason.clone();
+                paste = Some(attempt);
+                reason
+            }),
+            PostAction::CopyOnly => effects.copy(&text).map(|_| None),
+            PostAction::SaveToNotes => effects.save_to_notes(&text).map(Some),
+            PostAction::Webhook => effects.webhook(&text).await.map(|_| None),
+            PostAction::RecordHistory => effects.record_history(&text).map(|_| None),
+        };
+        if let Err(e) = &outcome {
+            tracing::warn!("Post action {} failed: {}", action.label(), e);
+        }
+        results.push(ActionResult {
+            action: action.clone(),
+            ok: outcome.is_ok(),
+            detail: outcome.as_ref().ok().cloned().flatten(),
+            error: outcome.err(),
+            skipped: None,
+        });
+    }
+
+    ProcessedResult {
+        text,
+        actions: results,
+        paste,
+    }
+}
+
+/// The actions in effect: the active preset's list when it has one, else the user's.
+pub fn effective<'a>(
+    configured: &'a [PostAction],
+    preset: Option<&'a [PostAction]>,
+) -> &'a [PostAction] {
+    preset.unwrap_or(configured)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeEffects {
+        calls: Vec<String>,
+        failing: Vec<&'static str>,
+    }
+
+    impl FakeEffects {
+        fn call(&mut self, name: &'static str, text: &str) -> Result<(), String> {
+            self.calls.push(format!("{}: {}", name, text));
+            if self.failing.contains(&name) {
+                Err(format!("{} failed", name))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl PostActionEffects for FakeEffects {
+        fn clarity(&mut self, text: &str) -> String {
+            self.calls.push(format!("clarity: {}", text));
+            format!("{}.", text.trim())
+        }
+
+        async fn optimize(
+            &mut self,
+            text: &str,
+            profile: &str,
+        ) -> Result<(String, String), String> {
+            self.call("optimize", text)?;
+            Ok((format!("[{}] {}", profile, text), "Groq".to_string()))
+        }
+
+        async fn paste(&mut self, text: &str) -> Result<PasteAttempt, String> {
+            self.call("paste", text)?;
+            Ok(PasteAttempt::fallback("no_target_window"))
+        }
+
+        fn copy(&mut self, text: &str) -> Result<(), String> {
+            self.call("copy", text)
+        }
+
+        fn save_to_notes(&mut self, text: &str) -> Result<String, String> {
+            self.call("notes", text)?;
+            Ok("notes/2025-03-07 Dictation.md".to_string())
+        }
+
+        async fn webhook(&mut self, text: &str) -> Result<(), String> {
+            self.call("webhook", text)
+        }
+
+        fn record_history(&mut self, text: &str) -> Result<(), String> {
+            self.call("history", text)
+        }
+    }
+
+    fn optimize(profile: &str) -> PostAction {
+        PostAction::Optimize {
+            profile: profile.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_actions_run_in_order_on_the_transformed_text() {
+        let actions = vec![
+            PostAction::Clarity,
+            optimize("email"),
+            PostAction::RecordHistory,
+            PostAction::Paste,
+            PostAction::SaveToNotes,
+        ];
+        validate(&actions).unwrap();
+
+        let mut effects = FakeEffects::default();
+        let result = run(&actions, "oi", &mut effects, &PrivacyGuard::default()).await;
+        assert_eq!(
+            effects.calls,
+            [
+                "clarity: oi",
+                "optimize: oi.",
+                "history: [email] oi.",
+                "paste: [email] oi.",
+                "notes: [email] oi.",
+            ]
+        );
+        assert_eq!(result.text, "[email] oi.");
+        assert!(result.actions.iter().all(|action| action.ok));
+        assert_eq!(result.actions[1].detail.as_deref(), Some("Groq"));
+        assert_eq!(
+            result.actions[3].detail.as_deref(),
+            Some("no_target_window")
+        );
+        assert_eq!(
+            result.actions[4].detail.as_deref(),
+            Some("notes/2025-03-07 Dictation.md")
+        );
+        assert!(!result.paste.unwrap().pasted);
+    }
+
+    #[tokio::test]
+    async fn test_a_failing_action_does_not_stop_the_rest() {
+        let actions = vec![
+            optimize("email"),
+            PostAction::Webhook,
+            PostAction::RecordHistory,
+            PostAction::CopyOnly,
+        ];
+        let mut effects = FakeEffects {
+            failing: vec!["optimize", "webhook"],
+            ..Default::default()
+        };
+        let result = run(&actions, "texto", &mut effects, &PrivacyGuard::default()).await;
+
+        // A failed transform leaves the text as it was.
+        assert_eq!(result.text, "texto");
+        assert_eq!(
+            effects.calls,
+            [
+                "optimize: texto",
+                "webhook: texto",
+                "history: texto",
+                "copy: texto"
+            ]
+        );
+        let ok: Vec<bool> = result.actions.iter().map(|action| action.ok).collect();
+        assert_eq!(ok, [false, false, true, true]);
+        assert_eq!(result.actions[1].error.as_deref(), Some("webhook failed"));
+        assert!(result.paste.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_default_actions_record_then_paste() {
+        let actions = default_post_actions();
+        validate(&actions).unwrap();
+        let mut effects = FakeEffects::default();
+        let result = run(&actions, "olá", &mut effects, &PrivacyGuard::default()).await;
+        assert_eq!(effects.calls, ["history: olá", "paste: olá"]);
+        assert!(result.paste.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_privacy_mode_skips_what_would_leave_the_device() {
+        let actions = vec![
+            PostAction::Clarity,
+            optimize("email"),
+            PostAction::RecordHistory,
+            PostAction::SaveToNotes,
+            PostAction::Webhook,
+            PostAction::Paste,
+        ];
+        let mut effects = FakeEffects::default();
+        let result = run(&actions, "oi", &mut effects, &PrivacyGuard::new(true)).await;
+
+        assert_eq!(effects.calls, ["clarity: oi", "history: oi.", "paste: oi."]);
+        assert_eq!(result.text, "oi.");
+        let skipped: Vec<bool> = result
+            .actions
+            .iter()
+            .map(|action| action.skipped.is_some())
+            .collect();
+        assert_eq!(skipped, [false, true, false, true, true, false]);
+        assert!(result.actions.iter().all(|action| action.ok));
+        assert!(result.actions[4]
+            .skipped
+            .as_deref()
+            .unwrap()
+            .contains("webhook"));
+    }
+
+    #[test]
+    fn test_validation_rules() {
+        let rejected = [
+            (vec![PostAction::Paste, PostAction::Paste], "more than once"),
+            (
+                vec![PostAction::RecordHistory, PostAction::RecordHistory],
+                "more than once",
+            ),
+            (
+                vec![PostAction::Paste, PostAction::CopyOnly],
+                "conflicts with Paste",
+            ),
+            (
+                vec![PostAction::Paste, optimize("email")],
+                "must come before Paste",
+            ),
+            (
+                vec![PostAction::CopyOnly, PostAction::Clarity],
+                "must come before Copy only",
+            ),
+            (vec![optimize(" "), PostAction::Paste], "needs a profile"),
+            (vec![], "at least one"),
+            (vec![PostAction::Clarity, optimize("email")], "at least one"),
+        ];
+        for (actions, message) in rejected {
+            let err = validate(&actions).unwrap_err();
+            assert!(err.contains(message), "{:?}: {}", actions, err);
+        }
+
+        let accepted = [
+            vec![PostAction::CopyOnly],
+            vec![PostAction::Webhook],
+            // Raw text to history, cleaned text pasted.
+            vec![
+                PostAction::RecordHistory,
+                PostAction::Clarity,
+                optimize("email"),
+                optimize("short"),
+                PostAction::Paste,
+            ],
+            // Saving after the paste is fine; only the text can't change any more.
+            vec![
+                PostAction::Paste,
+                PostAction::SaveToNotes,
+                PostAction::Webhook,
+            ],
+        ];
+        for actions in accepted {
+            assert_eq!(validate(&actions), Ok(()), "{:?}", actions);
+        }
+    }
+
+    #[test]
+    fn test_preset_actions_override_the_configured_ones() {
+        let configured = default_post_actions();
+        let meeting = vec![PostAction::RecordHistory, PostAction::SaveToNotes];
+        assert_eq!(effective(&configured, None), configured.as_slice());
+        assert_eq!(effective(&configured, Some(&meeting)), meeting.as_slice());
+    }
+
+    #[test]
+    fn test_actions_serialize_tagged() {
+        let actions = vec![optimize("email"), PostAction::CopyOnly];
+        let json = serde_json::to_value(&actions).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "kind": "optimize", "profile": "email" },
+                { "kind": "copyOnly" }
+            ])
+        );
+        let parsed: Vec<PostAction> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, actions);
+
+        let result = ActionResult {
+            action: PostAction::SaveToNotes,
+            ok: false,
+            detail: None,
+            error: Some("disk full".to_string()),
+            skipped: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            serde_json::json!({
+                "action": { "kind": "saveToNotes" },
+                "ok": false,
+                "detail": null,
+                "error": "disk full",
+                "skipped": null
+            })
+        );
+    }
+}
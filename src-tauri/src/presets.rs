@@ -0,0 +1,277 @@
+//! Named settings presets such as meeting mode. A preset swaps in a whole
+//! [`SessionSettings`] block; the block it replaced is kept in the config so ending the
+//! preset restores it exactly, even across restarts.
+
+use crate::config::AppConfig;
+use crate::post_actions::PostAction;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const MEETING_PRESET: &str = "meeting";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureSource {
+    #[default]
+    Microphone,
+    /// Microphone plus system audio (loopback)
+    Mixed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Segmentation {
+    /// The frontend sends the recording when it stops
+    #[default]
+    Manual,
+    /// The backend cuts segments on its own while recording
+    Continuous,
+}
+
+/// Everything a preset controls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SessionSettings {
+    pub capture_source: CaptureSource,
+    pub segmentation: Segmentation,
+    /// Session length after which new segments are refused; `None` keeps the built-in limit
+    pub max_session_minutes: Option<u32>,
+    /// Provider ids tried first, in this order
+    pub preferred_providers: Vec<String>,
+    /// What to do with each finalized session; `None` keeps the user's own list
+    pub post_actions: Option<Vec<PostAction>>,
+    /// Hold notifications back until the session is finalized
+    pub suppress_notifications: bool,
+    /// Configs before post actions wrote sessions to the notes folder with this flag;
+    /// [`SessionSettings::migrate`] turns it into a `SaveToNotes` action.
+    #[serde(skip_serializing)]
+    pub export_markdown: bool,
+}
+
+impl SessionSettings {
+    /// Fold the legacy `exportMarkdown` flag into `post_actions`.
+    pub fn migrate(&mut self, configured: &[PostAction]) {
+        if !std::mem::take(&mut self.export_markdown) {
+            return;
+        }
+        let actions = self.post_actions.get_or_insert_with(|| configured.to_vec());
+        if !actions.contains(&PostAction::SaveToNotes) {
+            actions.push(PostAction::SaveToNotes);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    pub name: String,
+    pub label: String,
+    pub settings: SessionSettings,
+}
+
+/// The preset in effect and the settings it replaced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivePreset {
+    pub name: String,
+    pub started_at: String,
+    pub previous: SessionSettings,
+}
+
+/// Presets shipped with the app; users can edit them in the config.
+pub fn default_presets() -> Vec<Preset> {
+    vec![Preset {
+        name: MEETING_PRESET.to_string(),
+        label: "Meeting".to_string(),
+        settings: SessionSettings {
+            capture_source: CaptureSource::Mixed,
+            segmentation: Segmentation::Continuous,
+            max_session_minutes: Some(90),
+            // Scribe returns speaker labels
+            preferred_providers: vec!["elevenlabs".to_string()],
+            post_actions: Some(vec![
+                PostAction::RecordHistory,
+                PostAction::Paste,
+                PostAction::SaveToNotes,
+            ]),
+            suppress_notifications: true,
+            ..SessionSettings::default()
+        },
+    }]
+}
+
+/// Switch to preset `name`. Switching from one preset to another keeps the settings from
+/// before the first, so ending always returns to the user's own configuration.
+pub fn apply(config: &mut AppConfig, name: &str) -> Result<(), String> {
+    let preset = config
+        .presets
+        .iter()
+        .find(|preset| preset.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown preset '{}'", name))?;
+
+    let previous = match config.active_preset.take() {
+        Some(active) => active.previous,
+        None => config.session.clone(),
+    };
+    config.session = preset.settings;
+    config.active_preset = Some(ActivePreset {
+        name: preset.name,
+        started_at: Utc::now().to_rfc3339(),
+        previous,
+    });
+    Ok(())
+}
+
+/// Restore the settings from before the active preset. Returns the preset that ended.
+pub fn end(config: &mut AppConfig) -> Option<String> {
+    let active = config.active_preset.take()?;
+    config.session = active.previous;
+    Some(active.name)
+}
+
+pub fn is_active(config: &AppConfig, name: &str) -> bool {
+    config
+        .active_preset
+        .as_ref()
+        .is_some_and(|active| active.name == name)
+}
+
+/// Append `text` to `<dir>/<date> <label>.md`, creating the file with a heading.
+pub fn export_markdown(
+    dir: &Path,
+    label: &str,
+    text: &str,
+    finished_at: DateTime<Local>,
+) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{} {}.md", finished_at.format("%Y-%m-%d"), label));
+    let is_new = !path.exists();
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut entry = String::new();
+    if is_new {
+        entry.push_str(&format!("# {} {}\n", label, finished_at.format("%Y-%m-%d")));
+    }
+    entry.push_str(&format!(
+        "\n## {}\n\n{}\n",
+        finished_at.format("%H:%M"),
+        text.trim()
+    ));
+    file.write_all(entry.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn custom_settings() -> SessionSettings {
+        SessionSettings {
+            max_session_minutes: Some(20),
+            preferred_providers: vec!["groq".to_string()],
+            ..SessionSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_then_end_restores_settings_exactly() {
+        let mut config = AppConfig {
+            session: custom_settings(),
+            ..AppConfig::default()
+        };
+        config.presets.push(Preset {
+            name: "interview".to_string(),
+            label: "Interview".to_string(),
+            settings: SessionSettings {
+                post_actions: Some(vec![PostAction::CopyOnly]),
+                ..SessionSettings::default()
+            },
+        });
+
+        apply(&mut config, MEETING_PRESET).unwrap();
+        assert!(is_active(&config, MEETING_PRESET));
+        assert_eq!(config.session.capture_source, CaptureSource::Mixed);
+        assert_eq!(config.session.max_session_minutes, Some(90));
+
+        // Hopping to another preset must not overwrite the original snapshot.
+        apply(&mut config, "interview").unwrap();
+        assert_eq!(
+            config.session.post_actions,
+            Some(vec![PostAction::CopyOnly])
+        );
+        assert!(!config.session.suppress_notifications);
+
+        // The snapshot survives a save/load round trip.
+        let json = serde_json::to_string(&config).unwrap();
+        let mut config: AppConfig = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(end(&mut config).as_deref(), Some("interview"));
+        assert_eq!(config.session, custom_settings());
+        assert!(config.active_preset.is_none());
+        assert_eq!(end(&mut config), None);
+    }
+
+    #[test]
+    fn test_legacy_export_flag_becomes_a_save_action() {
+        let mut settings: SessionSettings =
+            serde_json::from_str(r#"{ "exportMarkdown": true, "suppressNotifications": true }"#)
+                .unwrap();
+        settings.migrate(&crate::post_actions::default_post_actions());
+        assert_eq!(
+            settings.post_actions,
+            Some(vec![
+                PostAction::RecordHistory,
+                PostAction::Paste,
+                PostAction::SaveToNotes
+            ])
+        );
+        assert!(!settings.export_markdown);
+        let json = serde_json::to_value(&settings).unwrap();
+        assert!(json.get("exportMarkdown").is_none());
+
+        // Already migrated: nothing changes.
+        let before = settings.clone();
+        settings.migrate(&[]);
+        assert_eq!(settings, before);
+    }
+
+    #[test]
+    fn test_unknown_preset_leaves_config_untouched() {
+        let mut config = AppConfig {
+            session: custom_settings(),
+            ..AppConfig::default()
+        };
+        assert!(apply(&mut config, "podcast").is_err());
+        assert_eq!(config.session, custom_settings());
+        assert!(config.active_preset.is_none());
+    }
+
+    #[test]
+    fn test_export_appends_to_dated_file() {
+        let dir = std::env::temp_dir().join(format!("zentra-presets-{}", uuid::Uuid::new_v4()));
+        let morning = Local.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap();
+        let afternoon = Local.with_ymd_and_hms(2025, 3, 7, 14, 5, 0).unwrap();
+
+        let path = export_markdown(&dir, "Meeting", "Kickoff notes ", morning).unwrap();
+        assert_eq!(path, dir.join("2025-03-07 Meeting.md"));
+        export_markdown(&dir, "Meeting", "Follow-up", afternoon).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            contents,
+            "# Meeting 2025-03-07\n\n## 09:30\n\nKickoff notes\n\n## 14:05\n\nFollow-up\n"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,185 @@
+//! API keys in the OS keychain (Windows Credential Manager, macOS Keychain, Secret Service
+//! on Linux) under the `zentra` service. When no keychain answers, e.g. on a headless Linux
+//! box without a Secret Service, callers keep the key obfuscated in the config instead.
+//! Portable installs never use the keychain (see [`set_keychain_allowed`]).
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const SERVICE: &str = "zentra";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeySlot {
+    Groq,
+    ElevenLabs,
+    OpenRouter,
+}
+
+impl ApiKeySlot {
+    pub const ALL: [ApiKeySlot; 3] = [Self::Groq, Self::ElevenLabs, Self::OpenRouter];
+
+    fn account(self) -> &'static str {
+        match self {
+            Self::Groq => "groq_api_key",
+            Self::ElevenLabs => "elevenlabs_api_key",
+            Self::OpenRouter => "openrouter_api_key",
+        }
+    }
+}
+
+/// Whether keys may go to the keychain at all; off in portable mode, where they must
+/// travel with the install (`paths::Resolver::allows_keychain`). On until set.
+pub fn set_keychain_allowed(allowed: bool) {
+    backend::set_allowed(allowed);
+}
+
+pub fn keychain_allowed() -> bool {
+    backend::allowed()
+}
+
+/// The stored key, or `None` when the slot is empty.
+pub fn read(slot: ApiKeySlot) -> Result<Option<String>, String> {
+    backend::read(slot)
+}
+
+pub fn write(slot: ApiKeySlot, api_key: &str) -> Result<(), String> {
+    backend::write(slot, api_key)
+}
+
+/// Remove the key; an empty slot is not an error.
+pub fn delete(slot: ApiKeySlot) -> Result<(), String> {
+    backend::delete(slot)
+}
+
+#[cfg(not(test))]
+mod backend {
+    use super::{ApiKeySlot, HashMap, SERVICE};
+    use crate::locks::lock_or_recover;
+    use keyring::{Entry, Error};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    /// Set after the first platform failure, so a missing keychain costs one warning and
+    /// not one per config load.
+    static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+    static ALLOWED: AtomicBool = AtomicBool::new(true);
+
+    pub fn set_allowed(allowed: bool) {
+        ALLOWED.store(allowed, Ordering::Relaxed);
+    }
+
+    pub fn allowed() -> bool {
+        ALLOWED.load(Ordering::Relaxed)
+    }
+
+    /// Keys read this session; macOS may prompt on every keychain read otherwise.
+    fn cache() -> &'static Mutex<HashMap<ApiKeySlot, Option<String>>> {
+        static CACHE: OnceLock<Mutex<HashMap<ApiKeySlot, Option<String>>>> = OnceLock::new();
+        CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    fn entry(slot: ApiKeySlot) -> Result<Entry, String> {
+        if UNAVAILABLE.load(Ordering::Relaxed) {
+            return Err("OS keychain unavailable".to_string());
+        }
+        Entry::new(SERVICE, slot.account()).map_err(unavailable)
+    }
+
+    fn unavailable(error: Error) -> String {
+        if !UNAVAILABLE.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                "OS keychain unavailable, API keys stay in the config: {}",
+                error
+            );
+        }
+        format!("OS keychain unavailable: {}", error)
+    }
+
+    pub fn read(slot: ApiKeySlot) -> Result<Option<String>, String> {
+        if let Some(cached) = lock_or_recover(cache(), "secrets cache").get(&slot) {
+            return Ok(cached.clone());
+        }
+        let value = match entry(slot)?.get_password() {
+            Ok(value) => Some(value),
+            Err(Error::NoEntry) => None,
+            Err(e) => return Err(unavailable(e)),
+        };
+        lock_or_recover(cache(), "secrets cache").insert(slot, value.clone());
+        Ok(value)
+    }
+
+    pub fn write(slot: ApiKeySlot, api_key: &str) -> Result<(), String> {
+        entry(slot)?.set_password(api_key).map_err(unavailable)?;
+        lock_or_recover(cache(), "secrets cache").insert(slot, Some(api_key.to_string()));
+        Ok(())
+    }
+
+    pub fn delete(slot: ApiKeySlot) -> Result<(), String> {
+        match entry(slot)?.delete_credential() {
+            Ok(()) | Err(Error::NoEntry) => {}
+            Err(e) => return Err(unavailable(e)),
+        }
+        lock_or_recover(cache(), "secrets cache").insert(slot, None);
+        Ok(())
+    }
+}
+
+/// Per-thread stand-in for the keychain, so tests neither touch the user's keychain nor
+/// see each other's keys.
+#[cfg(test)]
+pub(crate) mod backend {
+    use super::{ApiKeySlot, HashMap};
+    use std::cell::{Cell, RefCell};
+
+    thread_local! {
+        static KEYS: RefCell<HashMap<ApiKeySlot, String>> = RefCell::new(HashMap::new());
+        static UNAVAILABLE: Cell<bool> = const { Cell::new(false) };
+        static ALLOWED: Cell<bool> = const { Cell::new(true) };
+        static CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub fn set_allowed(allowed: bool) {
+        ALLOWED.with(|flag| flag.set(allowed));
+    }
+
+    pub fn allowed() -> bool {
+        ALLOWED.with(Cell::get)
+    }
+
+    /// Reads, writes and deletes that reached the keychain on this thread.
+    pub fn calls() -> usize {
+        CALLS.with(Cell::get)
+    }
+
+    /// Make every call on this thread fail, like a headless Linux session.
+    pub fn set_unavailable(unavailable: bool) {
+        UNAVAILABLE.with(|flag| flag.set(unavailable));
+    }
+
+    fn check() -> Result<(), String> {
+        CALLS.with(|calls| calls.set(calls.get() + 1));
+        if UNAVAILABLE.with(Cell::get) {
+            Err("OS keychain unavailable".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn read(slot: ApiKeySlot) -> Result<Option<String>, String> {
+        check()?;
+        Ok(KEYS.with(|keys| keys.borrow().get(&slot).cloned()))
+    }
+
+    pub fn write(slot: ApiKeySlot, api_key: &str) -> Result<(), String> {
+        check()?;
+        KEYS.with(|keys| keys.borrow_mut().insert(slot, api_key.to_string()));
+        Ok(())
+    }
+
+    pub fn delete(slot: ApiKeySlot) -> Result<(), String> {
+        check()?;
+        KEYS.with(|keys| keys.borrow_mut().remove(&slot));
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+//! Short previews of transcripts for notifications and the tray's recent dictations.
+//! Cuts fall between graphemes, so an accent, an emoji skin tone or half of a flag is never
+//! left behind on its own.
+
+/// The first `max_graphemes` graphemes of `text`, with an ellipsis when it was cut.
+pub fn snippet(text: &str, max_graphemes: usize) -> String {
+    let text = text.trim();
+    match grapheme_starts(text).nth(max_graphemes) {
+        Some(cut) => format!("{}…", text[..cut].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+/// Byte offsets where each grapheme of `text` starts. An approximation of the Unicode
+/// rules that covers what dictation produces: combining marks, variation selectors, emoji
+/// modifiers and tags, zero-width joiner sequences and regional indicator pairs.
+fn grapheme_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut previous: Option<char> = None;
+    let mut regional_run = 0usize;
+    text.char_indices().filter_map(move |(offset, c)| {
+        let joined = previous == Some('\u{200D}');
+        let paired = is_regional_indicator(c) && regional_run % 2 == 1;
+        regional_run = if is_regional_indicator(c) {
+            regional_run + 1
+        } else {
+            0
+        };
+        let starts = previous.is_none() || !(joined || paired || extends(c));
+        previous = Some(c);
+        starts.then_some(offset)
+    })
+}
+
+/// Characters that always belong to the grapheme before them.
+fn extends(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{200D}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0020}'..='\u{E007F}'
+            | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_kept_and_long_text_cut_with_an_ellipsis() {
+        assert_eq!(snippet("  olá mundo ", 20), "olá mundo");
+        assert_eq!(snippet("one two three", 8), "one two…");
+        assert_eq!(snippet("", 5), "");
+    }
+
+    #[test]
+    fn test_cuts_never_split_a_grapheme() {
+        // "e" followed by a combining acute accent
+        assert_eq!(snippet("cafe\u{301} com leite", 4), "cafe\u{301}…");
+        // Thumbs up with a skin tone, then a family joined with ZWJ
+        let emoji = "👍🏽👨\u{200D}👩\u{200D}👧 fim";
+        assert_eq!(snippet(emoji, 1), "👍🏽…");
+        assert_eq!(snippet(emoji, 2), "👍🏽👨\u{200D}👩\u{200D}👧…");
+        // Two flags are four regional indicators
+        assert_eq!(snippet("🇧🇷🇵🇹 ok", 1), "🇧🇷…");
+        assert_eq!(snippet("🇧🇷🇵🇹 ok", 2), "🇧🇷🇵🇹…");
+    }
+}
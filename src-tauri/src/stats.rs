@@ -0,0 +1,131 @@
+//! Time saved by dictating: how long the words would have taken to type. The typing speed
+//! is the user's own once calibrated from a typed sample, 130 WPM until then. Nothing is
+//! stored per item, so calibrating changes every figure, past dictations included.
+
+use crate::config::HistoryItem;
+use serde::Serialize;
+
+/// Typing speed assumed until the user calibrates.
+pub const DEFAULT_TYPING_WPM: f32 = 130.0;
+/// Calibrated speeds outside this range are taken for a broken sample.
+pub const MIN_TYPING_WPM: f32 = 5.0;
+pub const MAX_TYPING_WPM: f32 = 250.0;
+/// Keystrokes a calibration sample needs.
+pub const MIN_CALIBRATION_KEYSTROKES: usize = 30;
+/// Gaps longer than this are pauses to think or read, not typing.
+const MAX_KEYSTROKE_GAP_MS: f64 = 2_000.0;
+/// Share of the shortest and longest gaps dropped before averaging.
+const TRIM_FRACTION: f64 = 0.1;
+/// Characters in a standard "word" of typing speed.
+const CHARS_PER_WORD: f64 = 5.0;
+
+/// Minutes `words` would take to type at `wpm`; invalid speeds use the default.
+pub fn time_saved(words: u64, wpm: f32) -> f32 {
+    words as f32 / typing_wpm(Some(wpm))
+}
+
+/// The calibrated speed when there is a usable one, else [`DEFAULT_TYPING_WPM`].
+pub fn typing_wpm(calibrated: Option<f32>) -> f32 {
+    calibrated
+        .filter(|wpm| (MIN_TYPING_WPM..=MAX_TYPING_WPM).contains(wpm))
+        .unwrap_or(DEFAULT_TYPING_WPM)
+}
+
+/// Typing speed from the times (ms) of the keystrokes of a typed sample. Pauses are left
+/// out and a tenth of the gaps at each end is trimmed, so a hesitation or a burst of
+/// repeated keys does not skew the result.
+pub fn measure_wpm(keystrokes_ms: &[f64]) -> Result<f32, String> {
+    if keystrokes_ms.len() < MIN_CALIBRATION_KEYSTROKES {
+        return Err(format!(
+            "Type at least {} characters to measure your speed",
+            MIN_CALIBRATION_KEYSTROKES
+        ));
+    }
+    let mut gaps: Vec<f64> = keystrokes_ms
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|gap| gap.is_finite() && *gap > 0.0 && *gap <= MAX_KEYSTROKE_GAP_MS)
+        .collect();
+    if gaps.len() < MIN_CALIBRATION_KEYSTROKES / 2 {
+        return Err("Too many pauses in the sample; type it without stopping".to_string());
+    }
+    gaps.sort_by(f64::total_cmp);
+    let trim = (gaps.len() as f64 * TRIM_FRACTION) as usize;
+    let kept = &gaps[trim..gaps.len() - trim];
+    let mean_gap = kept.iter().sum::<f64>() / kept.len() as f64;
+
+    let wpm = (60_000.0 / mean_gap / CHARS_PER_WORD) as f32;
+    if !(MIN_TYPING_WPM..=MAX_TYPING_WPM).contains(&wpm) {
+        return Err(format!(
+            "Measured {:.0} WPM, which doesn't look like typing; try again",
+            wpm
+        ));
+    }
+    Ok((wpm * 10.0).round() / 10.0)
+}
+
+/// A history item as the dashboard shows it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    #[serde(flatten)]
+    pub item: HistoryItem,
+    /// Typing time the dictation saved, at the current typing speed
+    pub minutes_saved: f32,
+}
+
+impl HistoryEntry {
+    pub fn new(item: HistoryItem, wpm: f32) -> Self {
+        let minutes_saved = time_saved(u64::from(item.word_count), wpm);
+        Self {
+            item,
+            minutes_saved,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` keystrokes `gap` ms apart.
+    fn typed(count: usize, gap: f64) -> Vec<f64> {
+        (0..count).map(|i| 1_000.0 + i as f64 * gap).collect()
+    }
+
+    #[test]
+    fn test_time_saved_uses_the_personal_speed_and_falls_back() {
+        assert_eq!(time_saved(130, DEFAULT_TYPING_WPM), 1.0);
+        assert_eq!(time_saved(120, 40.0), 3.0);
+        assert_eq!(time_saved(0, 40.0), 0.0);
+        for broken in [0.0, -3.0, f32::NAN, 10_000.0] {
+            assert_eq!(time_saved(260, broken), 2.0, "{broken}");
+        }
+        assert_eq!(typing_wpm(None), DEFAULT_TYPING_WPM);
+        assert_eq!(typing_wpm(Some(72.5)), 72.5);
+    }
+
+    #[test]
+    fn test_measures_steady_typing() {
+        // 200 ms a key is 300 characters, 60 words, a minute.
+        assert_eq!(measure_wpm(&typed(40, 200.0)), Ok(60.0));
+    }
+
+    #[test]
+    fn test_pauses_and_outliers_do_not_skew_the_measurement() {
+        let mut keystrokes = typed(20, 150.0);
+        let resumed = keystrokes.last().unwrap() + 8_000.0;
+        keystrokes.extend(typed(20, 150.0).iter().map(|t| t - 1_000.0 + resumed));
+        // A burst of auto-repeat and a slow key among the steady ones.
+        let last = *keystrokes.last().unwrap();
+        keystrokes.extend([last + 10.0, last + 20.0, last + 1_500.0]);
+        assert_eq!(measure_wpm(&keystrokes), Ok(80.0));
+    }
+
+    #[test]
+    fn test_short_or_implausible_samples_are_refused() {
+        assert!(measure_wpm(&typed(10, 200.0)).is_err());
+        assert!(measure_wpm(&typed(40, 5_000.0)).is_err());
+        assert!(measure_wpm(&typed(40, 1.0)).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Hints drawn from how Zentra is used. For now one: when most recent dictations come back
+//! detected in another language than the configured one, offer to switch, since a wrong
+//! fixed language makes the provider transcribe badly without saying why. The offer is made
+//! once; declining it is remembered for that configured language.
+
+use serde::{Deserialize, Serialize};
+
+/// Dictations looked at.
+pub const LANGUAGE_WINDOW: usize = 7;
+/// Disagreements among them that prompt a suggestion.
+pub const LANGUAGE_THRESHOLD: usize = 5;
+
+/// Kept in the config so the count and a dismissal survive restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LanguageSuggestionState {
+    /// The configured language `recent` was counted under; another one starts over
+    pub configured: String,
+    /// The latest dictations, oldest first: the language setting each one called for when
+    /// it disagreed with `configured`, `None` when it agreed
+    pub recent: Vec<Option<String>>,
+    /// Suggested and not answered yet
+    pub suggested: Option<String>,
+    /// The configured language the user chose to keep; nothing is suggested while it is set
+    pub dismissed: Option<String>,
+}
+
+/// Payload of `language-mismatch-suggestion`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageSuggestion {
+    pub configured: String,
+    pub suggested: String,
+    /// Of the last `window` dictations
+    pub mismatches: usize,
+    pub window: usize,
+}
+
+impl LanguageSuggestionState {
+    /// Count a dictation `detected` in some language while `configured` is set. Returns the
+    /// suggestion when this one makes too many disagree.
+    pub fn observe(&mut self, configured: &str, detected: &str) -> Option<LanguageSuggestion> {
+        if self.configured != configured {
+            self.configured = configured.to_string();
+            self.recent.clear();
+            self.suggested = None;
+        }
+        if configured == "auto"
+            || self.suggested.is_some()
+            || self.dismissed.as_deref() == Some(configured)
+        {
+            return None;
+        }
+
+        let setting = setting_for(detected);
+        self.recent
+            .push((setting != configured).then(|| setting.to_string()));
+        if self.recent.len() > LANGUAGE_WINDOW {
+            self.recent.remove(0);
+        }
+        let mismatches = self.recent.iter().flatten().count();
+        if mismatches < LANGUAGE_THRESHOLD {
+            return None;
+        }
+        let suggested = most_common(self.recent.iter().flatten())?;
+        self.suggested = Some(suggested.clone());
+        Some(LanguageSuggestion {
+            configured: configured.to_string(),
+            suggested,
+            mismatches,
+            window: LANGUAGE_WINDOW,
+        })
+    }
+
+    /// Keep the configured language; no more suggestions until it changes.
+    pub fn dismiss(&mut self) {
+        self.dismissed = Some(self.configured.clone());
+        self.suggested = None;
+        self.recent.clear();
+    }
+
+    /// The pending suggestion, for the caller to apply. Counting starts over.
+    pub fn accept(&mut self) -> Option<String> {
+        self.recent.clear();
+        self.suggested.take()
+    }
+}
+
+/// The language setting that fits a detected language: `pt` and `en` by their code,
+/// anything else automatic detection.
+fn setting_for(detected: &str) -> &'static str {
+    let code = detected.trim().to_lowercase();
+    match code.split(['-', '_']).next() {
+        Some("pt") => "pt",
+        Some("en") => "en",
+        _ => "auto",
+    }
+}
+
+/// Ties go to the most recent.
+fn most_common<'a>(settings: impl DoubleEndedIterator<Item = &'a String>) -> Option<String> {
+    let mut counts: Vec<(&String, usize)> = Vec::new();
+    for setting in settings.rev() {
+        match counts.iter_mut().find(|(seen, _)| *seen == setting) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((setting, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(setting, _)| setting.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observe_all(
+        state: &mut LanguageSuggestionState,
+        configured: &str,
+        detected: &[&str],
+    ) -> Vec<Option<LanguageSuggestion>> {
+        detected
+            .iter()
+            .map(|language| state.observe(configured, language))
+            .collect()
+    }
+
+    #[test]
+    fn test_suggests_once_five_of_the_last_seven_disagree() {
+        let mut state = LanguageSuggestionState::default();
+        let results = observe_all(&mut state, "pt", &["en", "pt", "en-US", "en", "pt", "en"]);
+        assert!(results.iter().all(Option::is_none));
+
+        let suggestion = state.observe("pt", "en").expect("five of seven in English");
+        assert_eq!(
+            suggestion,
+            LanguageSuggestion {
+                configured: "pt".to_string(),
+                suggested: "en".to_string(),
+                mismatches: 5,
+                window: LANGUAGE_WINDOW,
+            }
+        );
+        // One-time: nothing more until it is answered.
+        assert!(observe_all(&mut state, "pt", &["en"; 7])
+            .iter()
+            .all(Option::is_none));
+        assert_eq!(state.accept().as_deref(), Some("en"));
+        assert!(state.recent.is_empty());
+    }
+
+    #[test]
+    fn test_old_disagreements_slide_out_of_the_window() {
+        let mut state = LanguageSuggestionState::default();
+        let detected = ["en", "en", "en", "en", "pt", "pt", "pt", "en", "pt", "en"];
+        assert!(observe_all(&mut state, "pt", &detected)
+            .iter()
+            .all(Option::is_none));
+        assert_eq!(state.recent.len(), LANGUAGE_WINDOW);
+    }
+
+    #[test]
+    fn test_other_languages_suggest_automatic_detection_and_auto_is_never_questioned() {
+        let mut state = LanguageSuggestionState::default();
+        let results = observe_all(&mut state, "en", &["es", "fr", "es", "es", "de"]);
+        assert_eq!(
+            results[4].as_ref().map(|s| s.suggested.as_str()),
+            Some("auto")
+        );
+
+        let mut auto = LanguageSuggestionState::default();
+        assert!(observe_all(&mut auto, "auto", &["en"; 7])
+            .iter()
+            .all(Option::is_none));
+        assert!(auto.recent.is_empty());
+    }
+
+    #[test]
+    fn test_a_dismissal_holds_until_the_language_changes() {
+        let mut state = LanguageSuggestionState::default();
+        assert!(observe_all(&mut state, "pt", &["en"; 5])[4].is_some());
+        state.dismiss();
+        assert!(observe_all(&mut state, "pt", &["en"; 7])
+            .iter()
+            .all(Option::is_none));
+        assert_eq!(state.accept(), None);
+
+        // Switching starts a fresh count; the dismissal was for Portuguese only.
+        assert!(state.observe("en", "pt").is_none());
+        assert_eq!(state.recent, [Some("pt".to_string())]);
+        assert!(observe_all(&mut state, "en", &["pt"; 4])[3].is_some());
+    }
+
+    #[test]
+    fn test_the_state_round_trips_through_the_config() {
+        let mut state = LanguageSuggestionState::default();
+        observe_all(&mut state, "pt", &["en", "pt"]);
+        state.dismiss();
+        let raw = serde_json::to_string(&state).unwrap();
+        let restored: LanguageSuggestionState = serde_json::from_str(&raw).unwrap();
+        assert_eq!(restored, state);
+        let empty: LanguageSuggestionState = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty, LanguageSuggestionState::default());
+    }
+}
@@ -0,0 +1,285 @@
+//! Recording state changes go through one queue. The shortcut, the tray and every window
+//! send a [`Transition`] and wait for the reply, and a single task carries them out one at
+//! a time, so the dashboard's start button and the shortcut pressed in the same instant can't
+//! both reach the recorder. Requests that arrived while a transition ran are answered as it
+//! ends: the same transition shares its reply, any other is refused as
+//! `already_transitioning`. The queue also moves the [`AppPhase`] crash reports record.
+
+use crate::audio::{AudioBuffer, CaptureInfo};
+use crate::crash_reports::{self, AppPhase};
+use crate::ipc::StartRecordingError;
+use crate::locks::lock_or_recover;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use zentra_core::error_codes::ErrorCode;
+
+/// A toggle this soon after the previous one is a bouncing key or a double press.
+pub const TOGGLE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// The shortcut or the tray: show the pill, which then starts or stops
+    Toggle,
+    Start,
+    Stop,
+    Cancel,
+}
+
+impl Transition {
+    /// Where the app is once this transition succeeded.
+    fn phase_after(self) -> Option<AppPhase> {
+        match self {
+            Transition::Toggle => None,
+            Transition::Start => Some(AppPhase::Recording),
+            Transition::Stop => Some(AppPhase::Transcribing),
+            Transition::Cancel => Some(AppPhase::Idle),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TransitionReply {
+    Toggled,
+    Started(CaptureInfo),
+    Stopped(AudioBuffer),
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub enum TransitionError {
+    /// Another transition was running when this one arrived
+    AlreadyTransitioning,
+    /// A toggle within [`TOGGLE_DEBOUNCE`] of the previous one
+    Debounced,
+    /// The queue is gone; the app is shutting down
+    Closed,
+    StartFailed(StartRecordingError),
+    Failed(String),
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionError::AlreadyTransitioning => {
+                f.write_str("already_transitioning: another recording start or stop is running")
+            }
+            TransitionError::Debounced => f.write_str("already_transitioning: toggled too soon"),
+            TransitionError::Closed => f.write_str("The recording queue has shut down"),
+            TransitionError::StartFailed(err) => f.write_str(&err.message),
+            TransitionError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<TransitionError> for String {
+    fn from(err: TransitionError) -> Self {
+        err.to_string()
+    }
+}
+
+impl From<TransitionError> for StartRecordingError {
+    fn from(err: TransitionError) -> Self {
+        let (code, error_code) = match &err {
+            TransitionError::StartFailed(start) => return start.clone(),
+            TransitionError::AlreadyTransitioning | TransitionError::Debounced => {
+                ("already_transitioning", ErrorCode::RecAlreadyRecording)
+            }
+            TransitionError::Closed | TransitionError::Failed(_) => {
+                ("capture_failed", ErrorCode::RecCaptureFailed)
+            }
+        };
+        Self {
+            code,
+            error_code,
+            message: err.to_string(),
+            device: None,
+        }
+    }
+}
+
+pub type TransitionResult = Result<TransitionReply, TransitionError>;
+
+struct Request {
+    transition: Transition,
+    reply: oneshot::Sender<TransitionResult>,
+}
+
+/// Requests queue up from the start; [`TransitionQueue::run`] begins answering them.
+pub struct TransitionQueue {
+    sender: mpsc::UnboundedSender<Request>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<Request>>>,
+}
+
+impl Default for TransitionQueue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+}
+
+impl TransitionQueue {
+    /// Start the task that carries transitions out with `handler`. Only the first call
+    /// does anything.
+    pub fn run<H, F>(&self, mut handler: H)
+    where
+        H: FnMut(Transition) -> F + Send + 'static,
+        F: Future<Output = TransitionResult> + Send,
+    {
+        let Some(mut receiver) = lock_or_recover(&self.receiver, "transition queue").take() else {
+            tracing::warn!("Recording transitions are already running");
+            return;
+        };
+        tauri::async_runtime::spawn(async move {
+            let mut last_toggle: Option<Instant> = None;
+            while let Some(request) = receiver.recv().await {
+                let transition = request.transition;
+                if transition == Transition::Toggle {
+                    if last_toggle.is_some_and(|at| at.elapsed() < TOGGLE_DEBOUNCE) {
+                        let _ = request.reply.send(Err(TransitionError::Debounced));
+                        continue;
+                    }
+                    last_toggle = Some(Instant::now());
+                }
+
+                let result = handler(transition).await;
+                if let (Ok(_), Some(phase)) = (&result, transition.phase_after()) {
+                    crash_reports::set_phase(phase);
+                }
+                // Everything queued meanwhile raced this transition.
+                while let Ok(raced) = receiver.try_recv() {
+                    let answer = if raced.transition == transition {
+                        result.clone()
+                    } else {
+                        tracing::info!(
+                            "Refused {:?} while {:?} was running",
+                            raced.transition,
+                            transition
+                        );
+                        Err(TransitionError::AlreadyTransitioning)
+                    };
+                    let _ = raced.reply.send(answer);
+                }
+                let _ = request.reply.send(result);
+            }
+        });
+    }
+
+    pub async fn request(&self, transition: Transition) -> TransitionResult {
+        let (reply, answer) = oneshot::channel();
+        self.sender
+            .send(Request { transition, reply })
+            .map_err(|_| TransitionError::Closed)?;
+        answer.await.map_err(|_| TransitionError::Closed)?
+    }
+
+    pub async fn start(&self) -> Result<CaptureInfo, TransitionError> {
+        match self.request(Transition::Start).await? {
+            TransitionReply::Started(info) => Ok(info),
+            _ => Err(mismatched(Transition::Start)),
+        }
+    }
+
+    pub async fn stop(&self) -> Result<AudioBuffer, TransitionError> {
+        match self.request(Transition::Stop).await? {
+            TransitionReply::Stopped(audio) => Ok(audio),
+            _ => Err(mismatched(Transition::Stop)),
+        }
+    }
+}
+
+fn mismatched(transition: Transition) -> TransitionError {
+    TransitionError::Failed(format!(
+        "{:?} got the reply of another transition",
+        transition
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn capture_info() -> CaptureInfo {
+        CaptureInfo {
+            device_name: "Built-in".to_string(),
+            host: "ALSA".to_string(),
+            sample_rate: 48_000,
+            channels: 1,
+            sample_format: "i16".to_string(),
+            pre_roll_ms: 0,
+        }
+    }
+
+    /// A queue whose starts take 50 ms and are counted.
+    fn slow_queue(starts: Arc<AtomicUsize>) -> Arc<TransitionQueue> {
+        let queue = Arc::new(TransitionQueue::default());
+        queue.run(move |transition| {
+            let starts = starts.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                match transition {
+                    Transition::Start => {
+                        starts.fetch_add(1, Ordering::SeqCst);
+                        Ok(TransitionReply::Started(capture_info()))
+                    }
+                    Transition::Stop => Ok(TransitionReply::Stopped(AudioBuffer::new(16_000, 1))),
+                    Transition::Cancel => Ok(TransitionReply::Cancelled),
+                    Transition::Toggle => Ok(TransitionReply::Toggled),
+                }
+            }
+        });
+        queue
+    }
+
+    #[tokio::test]
+    async fn test_racing_starts_open_the_capture_once() {
+        let starts = Arc::new(AtomicUsize::new(0));
+        let queue = slow_queue(starts.clone());
+
+        let first = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.start().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let (button, shortcut, stop) = tokio::join!(
+            queue.start(),
+            queue.request(Transition::Toggle),
+            queue.request(Transition::Stop),
+        );
+
+        let first = first.await.unwrap().expect("the first start runs");
+        let button = button.expect("a second start shares the first one's capture");
+        assert_eq!(first.device_name, button.device_name);
+        assert!(matches!(
+            shortcut,
+            Err(TransitionError::AlreadyTransitioning)
+        ));
+        assert!(matches!(stop, Err(TransitionError::AlreadyTransitioning)));
+        assert_eq!(starts.load(Ordering::SeqCst), 1);
+
+        // Once the start is done, the queue takes new transitions again.
+        assert!(queue.stop().await.is_ok());
+        assert_eq!(
+            StartRecordingError::from(TransitionError::AlreadyTransitioning).code,
+            "already_transitioning"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_a_second_toggle_right_away_is_debounced() {
+        let queue = slow_queue(Arc::new(AtomicUsize::new(0)));
+        assert!(queue.request(Transition::Toggle).await.is_ok());
+        assert!(matches!(
+            queue.request(Transition::Toggle).await,
+            Err(TransitionError::Debounced)
+        ));
+        assert!(queue.start().await.is_ok());
+    }
+}
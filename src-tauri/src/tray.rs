@@ -1,12 +1,138 @@
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
-use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{AppHandle, Emitter, Manager, Runtime};
+use crate::config;
+use crate::ipc::events::{self, DashboardNavigate, DashboardSection, Event};
+use crate::locks::lock_or_recover;
+use crate::mini_history::{self, Display, Rect, MINI_HISTORY_WINDOW};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{
+    AppHandle, Manager, Monitor, PhysicalPosition, Runtime, WebviewUrl, WebviewWindowBuilder,
+    WindowEvent, Wry,
+};
 
+pub const TRAY_ID: &str = "zentra-tray";
 pub const MENU_OPEN_DASHBOARD: &str = "tray-open-dashboard";
 pub const MENU_OPEN_SETTINGS: &str = "tray-open-settings";
+pub const MENU_RECENT_DICTATIONS: &str = "tray-recent-dictations";
+pub const MENU_TRANSCRIBE_CLIPBOARD: &str = "tray-transcribe-clipboard";
+pub const MENU_TOGGLE_RECORDING: &str = "tray-toggle-recording";
+pub const MENU_PRIVACY_MODE: &str = "tray-privacy-mode";
+pub const MENU_MEETING_MODE: &str = "tray-meeting-mode";
 pub const MENU_QUIT: &str = "tray-quit";
+pub const MENU_LANGUAGE_PREFIX: &str = "tray-language-";
 
-pub fn init_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+/// Dictation languages in the tray submenu: the `language` setting value and its label.
+pub const LANGUAGE_OPTIONS: [(&str, &str); 3] =
+    [("pt", "Português"), ("en", "English"), ("auto", "Auto")];
+
+/// Menu items whose state changes after the tray is built.
+pub struct TrayHandles {
+    privacy_mode: CheckMenuItem<Wry>,
+    meeting_mode: CheckMenuItem<Wry>,
+    /// In [`LANGUAGE_OPTIONS`] order
+    languages: Vec<CheckMenuItem<Wry>>,
+    key_invalid: AtomicBool,
+    /// Where the tray icon was at its last click or hover, for the recent dictations
+    /// popover. Linux trays never report it.
+    anchor: Mutex<Option<Rect>>,
+}
+
+/// The tray while a dictation records: a red dot on the icon, the elapsed time in the
+/// tooltip and the Start/Stop item checked. Kept in `AppState`, so starting and stopping a
+/// capture can update it without an `AppHandle`.
+#[derive(Default)]
+pub struct RecordingIndicator {
+    tray: Mutex<Option<RecordingTray>>,
+    recording: AtomicBool,
+    ticker: Mutex<Option<JoinHandle<()>>>,
+}
+
+struct RecordingTray {
+    icon: TrayIcon<Wry>,
+    item: CheckMenuItem<Wry>,
+    idle_icon: Option<Image<'static>>,
+    recording_icon: Option<Image<'static>>,
+}
+
+impl RecordingIndicator {
+    fn attach(&self, tray: RecordingTray) {
+        *lock_or_recover(&self.tray, "tray recording indicator") = Some(tray);
+        let recording = self.recording.load(Ordering::Relaxed);
+        self.show(recording);
+    }
+
+    pub fn set_recording(&self, recording: bool) {
+        if self.recording.swap(recording, Ordering::Relaxed) != recording {
+            self.show(recording);
+        }
+    }
+
+    fn show(&self, recording: bool) {
+        if let Some(ticker) = lock_or_recover(&self.ticker, "tray ticker").take() {
+            ticker.abort();
+        }
+        let guard = lock_or_recover(&self.tray, "tray recording indicator");
+        let Some(tray) = guard.as_ref() else {
+            return;
+        };
+        let _ = tray.item.set_checked(recording);
+        let _ = tray.item.set_text(toggle_label(recording));
+        let icon = if recording {
+            &tray.recording_icon
+        } else {
+            &tray.idle_icon
+        };
+        if icon.is_some() {
+            let _ = tray.icon.set_icon(icon.clone());
+        }
+
+        if recording {
+            let icon = tray.icon.clone();
+            let started = Instant::now();
+            let ticker = tauri::async_runtime::spawn(async move {
+                loop {
+                    let _ = icon.set_tooltip(Some(recording_tooltip(started.elapsed())));
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            });
+            *lock_or_recover(&self.ticker, "tray ticker") = Some(ticker);
+        } else {
+            refresh_status(tray.icon.app_handle());
+        }
+    }
+
+    /// A click on the check item toggles its mark at once; put back the real state until
+    /// the recording actually starts or stops.
+    pub fn restore_check(&self) {
+        if let Some(tray) = lock_or_recover(&self.tray, "tray recording indicator").as_ref() {
+            let _ = tray
+                .item
+                .set_checked(self.recording.load(Ordering::Relaxed));
+        }
+    }
+}
+
+pub fn init_tray(
+    app: &AppHandle,
+    recording: &RecordingIndicator,
+    privacy_mode: bool,
+    meeting_mode: bool,
+    key_invalid: bool,
+    language: &str,
+) -> Result<(), String> {
+    let toggle_recording = CheckMenuItem::with_id(
+        app,
+        MENU_TOGGLE_RECORDING,
+        toggle_label(false),
+        true,
+        false,
+        None::<&str>,
+    )
+    .map_err(|e| e.to_string())?;
     let open_dashboard = MenuItem::with_id(
         app,
         MENU_OPEN_DASHBOARD,
@@ -15,6 +141,14 @@ pub fn init_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
         None::<&str>,
     )
     .map_err(|e| e.to_string())?;
+    let recent_dictations = MenuItem::with_id(
+        app,
+        MENU_RECENT_DICTATIONS,
+        "Recent dictations",
+        true,
+        None::<&str>,
+    )
+    .map_err(|e| e.to_string())?;
     let open_settings = MenuItem::with_id(
         app,
         MENU_OPEN_SETTINGS,
@@ -23,14 +157,77 @@ pub fn init_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
         None::<&str>,
     )
     .map_err(|e| e.to_string())?;
+    let transcribe_clipboard = MenuItem::with_id(
+        app,
+        MENU_TRANSCRIBE_CLIPBOARD,
+        "Transcribe audio from clipboard",
+        true,
+        None::<&str>,
+    )
+    .map_err(|e| e.to_string())?;
+    let privacy_item = CheckMenuItem::with_id(
+        app,
+        MENU_PRIVACY_MODE,
+        "Privacy mode",
+        true,
+        privacy_mode,
+        None::<&str>,
+    )
+    .map_err(|e| e.to_string())?;
+    let meeting_item = CheckMenuItem::with_id(
+        app,
+        MENU_MEETING_MODE,
+        "Meeting mode",
+        true,
+        meeting_mode,
+        None::<&str>,
+    )
+    .map_err(|e| e.to_string())?;
+    let language_items = language_check_states(language)
+        .into_iter()
+        .zip(LANGUAGE_OPTIONS)
+        .map(|((code, checked), (_, label))| {
+            CheckMenuItem::with_id(
+                app,
+                language_menu_id(code),
+                label,
+                true,
+                checked,
+                None::<&str>,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let language_refs: Vec<&dyn IsMenuItem<Wry>> = language_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let language_menu =
+        Submenu::with_items(app, "Language", true, &language_refs).map_err(|e| e.to_string())?;
     let quit = MenuItem::with_id(app, MENU_QUIT, "Quit Zentra", true, None::<&str>)
         .map_err(|e| e.to_string())?;
     let separator = PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?;
+    let recording_separator = PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?;
 
-    let menu = Menu::with_items(app, &[&open_dashboard, &open_settings, &separator, &quit])
-        .map_err(|e| e.to_string())?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &toggle_recording,
+            &recording_separator,
+            &open_dashboard,
+            &recent_dictations,
+            &open_settings,
+            &transcribe_clipboard,
+            &language_menu,
+            &meeting_item,
+            &privacy_item,
+            &separator,
+            &quit,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
 
-    let mut tray_builder = TrayIconBuilder::with_id("zentra-tray")
+    let mut tray_builder = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
         .show_menu_on_left_click(false)
         .tooltip("Zentra")
@@ -39,15 +236,34 @@ pub fn init_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
                 MENU_OPEN_DASHBOARD => {
                     let _ = show_dashboard(app);
                 }
+                MENU_RECENT_DICTATIONS => {
+                    if let Err(e) = show_mini_history(app) {
+                        tracing::warn!("Failed to show recent dictations: {}", e);
+                    }
+                }
                 MENU_OPEN_SETTINGS => {
                     let _ = show_dashboard(app);
-                    let _ = app.emit_to("dashboard", "dashboard:navigate", "settings");
+                    events::emit_event(
+                        app,
+                        Event::DashboardNavigate(DashboardNavigate {
+                            section: DashboardSection::Settings,
+                        }),
+                    );
                 }
+                MENU_TOGGLE_RECORDING => crate::toggle_recording_from_tray(app),
+                MENU_TRANSCRIBE_CLIPBOARD => crate::transcribe_clipboard_audio(app),
+                MENU_MEETING_MODE => crate::toggle_meeting_mode(app),
+                MENU_PRIVACY_MODE => crate::toggle_privacy_mode(app),
                 MENU_QUIT => app.exit(0),
-                _ => {}
+                id => {
+                    if let Some(language) = language_from_menu_id(id) {
+                        crate::switch_language(app, language);
+                    }
+                }
             }
         })
         .on_tray_icon_event(|tray, event| {
+            remember_anchor(tray.app_handle(), &event);
             if let TrayIconEvent::Click {
                 button: MouseButton::Left,
                 button_state: MouseButtonState::Up,
@@ -59,14 +275,175 @@ pub fn init_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
             }
         });
 
-    if let Some(icon) = app.default_window_icon() {
+    let idle_icon = app.default_window_icon().map(|icon| icon.clone().to_owned());
+    if let Some(icon) = &idle_icon {
         tray_builder = tray_builder.icon(icon.clone());
     }
 
-    tray_builder.build(app).map_err(|e| e.to_string())?;
+    let tray = tray_builder.build(app).map_err(|e| e.to_string())?;
+    recording.attach(RecordingTray {
+        icon: tray,
+        item: toggle_recording,
+        recording_icon: idle_icon.as_ref().map(|icon| {
+            Image::new_owned(
+                with_recording_dot(icon.rgba(), icon.width(), icon.height()),
+                icon.width(),
+                icon.height(),
+            )
+        }),
+        idle_icon,
+    });
+    app.manage(TrayHandles {
+        privacy_mode: privacy_item,
+        meeting_mode: meeting_item,
+        languages: language_items,
+        key_invalid: AtomicBool::new(key_invalid),
+        anchor: Mutex::new(None),
+    });
+    set_privacy_indicator(app, privacy_mode);
     Ok(())
 }
 
+/// Sync the privacy check item and show a badge on the tray while privacy mode is active.
+pub fn set_privacy_indicator(app: &AppHandle, enabled: bool) {
+    if let Some(handles) = app.try_state::<TrayHandles>() {
+        let _ = handles.privacy_mode.set_checked(enabled);
+    }
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip(app, enabled)));
+        let _ = tray.set_title(title(app, enabled));
+    }
+}
+
+/// Badge the tray while the Groq key health check reports the saved key as rejected.
+pub fn set_key_indicator(app: &AppHandle, invalid: bool) {
+    let Some(handles) = app.try_state::<TrayHandles>() else {
+        return;
+    };
+    handles.key_invalid.store(invalid, Ordering::Relaxed);
+    refresh_status(app);
+}
+
+/// Put the tooltip and title back to the current privacy and key state.
+fn refresh_status(app: &AppHandle) {
+    let privacy_mode = app
+        .try_state::<TrayHandles>()
+        .and_then(|handles| handles.privacy_mode.is_checked().ok())
+        .unwrap_or(false);
+    set_privacy_indicator(app, privacy_mode);
+}
+
+fn toggle_label(recording: bool) -> &'static str {
+    if recording {
+        "Stop recording"
+    } else {
+        "Start recording"
+    }
+}
+
+/// `Zentra — recording 0:42`, with hours once there are any.
+fn recording_tooltip(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("Zentra — recording {}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("Zentra — recording {}:{:02}", minutes, secs)
+    }
+}
+
+/// `rgba` with a red dot in the bottom-right corner, ringed in white so it shows on dark
+/// and light trays alike.
+fn with_recording_dot(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let mut out = rgba.to_vec();
+    let radius = width.min(height) as f32 * 0.22;
+    let ring = (radius * 0.25).max(1.0);
+    let center_x = width as f32 - radius - ring;
+    let center_y = height as f32 - radius - ring;
+    for y in 0..height {
+        for x in 0..width {
+            let distance = (x as f32 + 0.5 - center_x).hypot(y as f32 + 0.5 - center_y);
+            let color = if distance <= radius {
+                [0xe5, 0x39, 0x35, 0xff]
+            } else if distance <= radius + ring {
+                [0xff, 0xff, 0xff, 0xff]
+            } else {
+                continue;
+            };
+            let offset = ((y * width + x) * 4) as usize;
+            if let Some(pixel) = out.get_mut(offset..offset + 4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+    out
+}
+
+fn key_invalid(app: &AppHandle) -> bool {
+    app.try_state::<TrayHandles>()
+        .is_some_and(|handles| handles.key_invalid.load(Ordering::Relaxed))
+}
+
+fn title(app: &AppHandle, privacy_mode: bool) -> Option<&'static str> {
+    if key_invalid(app) {
+        Some("Key rejected")
+    } else {
+        privacy_mode.then_some("Private")
+    }
+}
+
+pub fn set_meeting_indicator(app: &AppHandle, active: bool) {
+    if let Some(handles) = app.try_state::<TrayHandles>() {
+        let _ = handles.meeting_mode.set_checked(active);
+    }
+}
+
+/// Check the saved language in the tray submenu and uncheck the others.
+pub fn set_language_indicator(app: &AppHandle, language: &str) {
+    let Some(handles) = app.try_state::<TrayHandles>() else {
+        return;
+    };
+    let states = language_check_states(language);
+    for (item, (_, checked)) in handles.languages.iter().zip(states) {
+        let _ = item.set_checked(checked);
+    }
+}
+
+fn language_menu_id(language: &str) -> String {
+    format!("{}{}", MENU_LANGUAGE_PREFIX, language)
+}
+
+/// Language for a tray menu id, when it is one of the language items.
+fn language_from_menu_id(id: &str) -> Option<&'static str> {
+    let code = id.strip_prefix(MENU_LANGUAGE_PREFIX)?;
+    LANGUAGE_OPTIONS
+        .iter()
+        .map(|(option, _)| *option)
+        .find(|option| *option == code)
+}
+
+/// Check state of each language item for a saved `language` value; exactly one is checked,
+/// and unknown values fall back like the setting itself does.
+fn language_check_states(language: &str) -> [(&'static str, bool); 3] {
+    let current = config::normalize_language(language);
+    LANGUAGE_OPTIONS.map(|(code, _)| (code, code == current))
+}
+
+fn tooltip(app: &AppHandle, privacy_mode: bool) -> &'static str {
+    let storage_unavailable = app
+        .try_state::<crate::config::StorageStatus>()
+        .is_some_and(|status| status.is_read_only());
+    if key_invalid(app) {
+        return "Zentra (Groq API key rejected, update it in Settings)";
+    }
+    match (privacy_mode, storage_unavailable) {
+        (_, true) => "Zentra (settings can't be saved)",
+        (true, false) => "Zentra (privacy mode)",
+        (false, false) => "Zentra",
+    }
+}
+
 pub fn show_dashboard<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
     let Some(window) = app.get_webview_window("dashboard") else {
         return Err("dashboard window not found".to_string());
@@ -75,6 +452,155 @@ pub fn show_dashboard<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
     window.show().map_err(|e| e.to_string())?;
     window.unminimize().map_err(|e| e.to_string())?;
     window.set_focus().map_err(|e| e.to_string())?;
-    let _ = app.emit_to("dashboard", "dashboard:refresh", ());
+    events::emit_event(app, Event::DashboardRefresh);
+    Ok(())
+}
+
+fn remember_anchor(app: &AppHandle, event: &TrayIconEvent) {
+    let rect = match event {
+        TrayIconEvent::Click { rect, .. }
+        | TrayIconEvent::DoubleClick { rect, .. }
+        | TrayIconEvent::Enter { rect, .. }
+        | TrayIconEvent::Move { rect, .. }
+        | TrayIconEvent::Leave { rect, .. } => rect,
+        _ => return,
+    };
+    let Some(handles) = app.try_state::<TrayHandles>() else {
+        return;
+    };
+    // Windows and macOS report the rect in physical pixels already.
+    let position = rect.position.to_physical::<i32>(1.0);
+    let size = rect.size.to_physical::<u32>(1.0);
+    *lock_or_recover(&handles.anchor, "tray anchor") =
+        Some(Rect::new(position.x, position.y, size.width, size.height));
+}
+
+/// Show the recent dictations popover next to the tray icon, creating its window the first
+/// time. It hides again when it loses focus.
+pub fn show_mini_history(app: &AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(MINI_HISTORY_WINDOW) {
+        Some(window) => window,
+        None => {
+            let window = WebviewWindowBuilder::new(
+                app,
+                MINI_HISTORY_WINDOW,
+                WebviewUrl::App("index.html".into()),
+            )
+            .title("Zentra - Recent dictations")
+            .inner_size(mini_history::WINDOW_WIDTH, mini_history::WINDOW_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()
+            .map_err(|e| e.to_string())?;
+            let hide = window.clone();
+            window.on_window_event(move |event| {
+                if let WindowEvent::Focused(false) = event {
+                    let _ = hide.hide();
+                }
+            });
+            window
+        }
+    };
+
+    let anchor = app
+        .try_state::<TrayHandles>()
+        .and_then(|handles| *lock_or_recover(&handles.anchor, "tray anchor"));
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    if let Some((x, y)) =
+        mini_history::popover_position(anchor, &displays(app), (size.width, size.height))
+    {
+        window
+            .set_position(PhysicalPosition::new(x, y))
+            .map_err(|e| e.to_string())?;
+    }
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
     Ok(())
 }
+
+/// Every monitor, the primary one first.
+fn displays(app: &AppHandle) -> Vec<Display> {
+    let display = |monitor: &Monitor| {
+        let (position, size, area) = (monitor.position(), monitor.size(), monitor.work_area());
+        Display {
+            bounds: Rect::new(position.x, position.y, size.width, size.height),
+            work_area: Rect::new(
+                area.position.x,
+                area.position.y,
+                area.size.width,
+                area.size.height,
+            ),
+            scale_factor: monitor.scale_factor(),
+        }
+    };
+    let mut displays: Vec<Display> = app
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(display)
+        .collect();
+    if let Some(primary) = app.primary_monitor().ok().flatten().map(|m| display(&m)) {
+        displays.retain(|d| *d != primary);
+        displays.insert(0, primary);
+    }
+    displays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checked(language: &str) -> Vec<&'static str> {
+        language_check_states(language)
+            .into_iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(code, _)| code)
+            .collect()
+    }
+
+    #[test]
+    fn test_language_items_follow_the_saved_setting() {
+        assert_eq!(checked("pt"), ["pt"]);
+        assert_eq!(checked(" EN "), ["en"]);
+        assert_eq!(checked("auto"), ["auto"]);
+        // Unknown values show the default the setting falls back to.
+        assert_eq!(checked("fr"), [config::normalize_language("fr").as_str()]);
+        assert_eq!(checked(""), [config::normalize_language("").as_str()]);
+    }
+
+    #[test]
+    fn test_recording_tooltip_and_icon_dot() {
+        assert_eq!(
+            recording_tooltip(Duration::from_secs(42)),
+            "Zentra — recording 0:42"
+        );
+        assert_eq!(
+            recording_tooltip(Duration::from_millis(605_900)),
+            "Zentra — recording 10:05"
+        );
+        assert_eq!(
+            recording_tooltip(Duration::from_secs(3_725)),
+            "Zentra — recording 1:02:05"
+        );
+
+        let icon = vec![0x10u8; 32 * 32 * 4];
+        let dotted = with_recording_dot(&icon, 32, 32);
+        assert_eq!(dotted.len(), icon.len());
+        let pixel = |x: usize, y: usize| &dotted[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(pixel(25, 25), [0xe5, 0x39, 0x35, 0xff]);
+        assert_eq!(pixel(2, 2), [0x10; 4]);
+        assert_eq!(pixel(31, 0), [0x10; 4]);
+    }
+
+    #[test]
+    fn test_menu_ids_map_back_to_languages() {
+        for (code, _) in LANGUAGE_OPTIONS {
+            assert_eq!(language_from_menu_id(&language_menu_id(code)), Some(code));
+        }
+        assert_eq!(language_from_menu_id("tray-language-fr"), None);
+        assert_eq!(language_from_menu_id(MENU_QUIT), None);
+    }
+}
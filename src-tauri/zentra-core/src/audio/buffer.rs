@@ -0,0 +1,173 @@
+﻿use serde::{Deserialize, Serialize};
+
+/// A buffer whose format cannot describe its samples. Encoded anyway, it makes a WAV
+/// header providers reject with an unhelpful error.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BufferError {
+    #[error("sample rate is 0")]
+    ZeroSampleRate,
+    #[error("channel count is 0")]
+    ZeroChannels,
+    #[error("{samples} samples do not split into frames of {channels} channels")]
+    PartialFrame { samples: usize, channels: u16 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioBuffer {
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Cached duration in seconds
+    #[serde(skip)]
+    pub duration_secs: f32,
+}
+
+impl AudioBuffer {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            samples: Vec::new(),
+            sample_rate,
+            channels,
+            duration_secs: 0.0,
+        }
+    }
+
+    /// Recalculate and update duration_secs
+    pub fn update_duration(&mut self) {
+        if self.sample_rate == 0 {
+            self.duration_secs = 0.0;
+        } else {
+            let channels = self.channels.max(1) as f32;
+            self.duration_secs = self.samples.len() as f32 / (self.sample_rate as f32 * channels);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.duration_secs = 0.0;
+    }
+
+    pub fn append(&mut self, data: &[i16]) {
+        self.samples.extend_from_slice(data);
+        self.update_duration();
+    }
+
+    /// Check the format before the samples are encoded or sent anywhere.
+    pub fn validate(&self) -> Result<(), BufferError> {
+        if self.sample_rate == 0 {
+            return Err(BufferError::ZeroSampleRate);
+        }
+        if self.channels == 0 {
+            return Err(BufferError::ZeroChannels);
+        }
+        if !self.samples.len().is_multiple_of(self.channels as usize) {
+            return Err(BufferError::PartialFrame {
+                samples: self.samples.len(),
+                channels: self.channels,
+            });
+        }
+        Ok(())
+    }
+
+    /// Downmix to mono and linearly resample to `target_rate`
+    pub fn to_mono(&self, target_rate: u32) -> Vec<i16> {
+        let mono = downmix_to_mono(&self.samples, self.channels.max(1));
+        resample_linear(&mono, self.sample_rate.max(1), target_rate)
+    }
+}
+
+fn downmix_to_mono(samples: &[i16], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.iter().map(|sample| *sample as f32).collect();
+    }
+
+    let ch = channels as usize;
+    let frame_count = samples.len() / ch;
+    let mut mono = Vec::with_capacity(frame_count);
+
+    for frame_idx in 0..frame_count {
+        let base = frame_idx * ch;
+        let mut sum = 0.0f32;
+        for channel_idx in 0..ch {
+            sum += samples[base + channel_idx] as f32;
+        }
+        mono.push(sum / channels as f32);
+    }
+
+    mono
+}
+
+fn resample_linear(input: &[f32], source_rate: u32, target_rate: u32) -> Vec<i16> {
+    if input.is_empty() {
+        return Vec::new();
+    }
+
+    if source_rate == target_rate {
+        return input
+            .iter()
+            .map(|sample| sample.clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+            .collect();
+    }
+
+    let ratio = target_rate as f64 / source_rate as f64;
+    let out_len = ((input.len() as f64) * ratio).round().max(1.0) as usize;
+    let mut output = Vec::with_capacity(out_len);
+
+    for out_idx in 0..out_len {
+        let src_pos = out_idx as f64 * (source_rate as f64 / target_rate as f64);
+        let left_idx = src_pos.floor() as usize;
+        let right_idx = usize::min(left_idx + 1, input.len() - 1);
+        let frac = (src_pos - left_idx as f64) as f32;
+        let interpolated = input[left_idx] * (1.0 - frac) + input[right_idx] * frac;
+        output.push(interpolated.clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_round_trips_camel_case() {
+        // The frontend sends recordings back in the shape `stop_recording` returned.
+        let parsed: AudioBuffer = serde_json::from_str(
+            r#"{ "samples": [1, -1, 2, -2], "sampleRate": 16000, "channels": 2 }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.sample_rate, 16_000);
+        assert_eq!(parsed.samples.len(), 4);
+
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::json!({ "samples": [1, -1, 2, -2], "sampleRate": 16000, "channels": 2 })
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_formats_that_cannot_hold_the_samples() {
+        let mut stereo = AudioBuffer::new(48_000, 2);
+        stereo.append(&[1, -1, 2, -2]);
+        assert_eq!(stereo.validate(), Ok(()));
+        assert_eq!(AudioBuffer::new(16_000, 1).validate(), Ok(()));
+
+        stereo.append(&[3]);
+        assert_eq!(
+            stereo.validate(),
+            Err(BufferError::PartialFrame {
+                samples: 5,
+                channels: 2
+            })
+        );
+        assert_eq!(
+            AudioBuffer::new(0, 1).validate(),
+            Err(BufferError::ZeroSampleRate)
+        );
+        assert_eq!(
+            AudioBuffer::new(16_000, 0).validate(),
+            Err(BufferError::ZeroChannels)
+        );
+    }
+}
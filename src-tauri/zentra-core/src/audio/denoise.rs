@@ -0,0 +1,344 @@
+//! Spectral subtraction for steady background noise such as a laptop fan. The noise
+//! spectrum is estimated from the quietest frames of the recording and subtracted from
+//! every frame, with a floor so pauses keep a little room tone instead of warbling.
+//!
+//! Frames are 32 ms with 50% overlap under a periodic Hann window, which overlap-adds back
+//! to unity, so a frame whose gains are all 1 is reconstructed exactly.
+
+use std::f32::consts::PI;
+use std::ops::Range;
+use std::thread;
+
+/// Noise is subtracted this many times over; more removes more noise and more speech.
+const OVER_SUBTRACTION: f32 = 2.0;
+/// Lowest gain of a bin, about -26 dB.
+const GAIN_FLOOR: f32 = 0.05;
+/// Share of frames, the quietest ones, the noise profile is averaged from.
+const NOISE_FRAME_SHARE: f32 = 0.1;
+/// Below this many frames per thread, spawning costs more than it saves.
+const FRAMES_PER_THREAD: usize = 256;
+
+/// Denoise 16-bit samples of one channel. The output has the same length; input shorter
+/// than one frame is returned unchanged.
+pub fn denoise(samples: &[i16], sample_rate: u32) -> Vec<i16> {
+    let frame_len = frame_len(sample_rate);
+    if samples.len() < frame_len {
+        return samples.to_vec();
+    }
+    let hop = frame_len / 2;
+    let fft = Fft::new(frame_len);
+    let window: Vec<f32> = (0..frame_len)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / frame_len as f32).cos())
+        .collect();
+
+    // Pad so every sample is covered by two frames, including the first and last hop.
+    let mut padded = vec![0.0f32; hop];
+    padded.extend(samples.iter().map(|&s| s as f32));
+    let frame_count = padded.len().div_ceil(hop);
+    padded.resize((frame_count + 1) * hop, 0.0);
+
+    // Only frames inside the recording; the padded edges would look quieter than the room.
+    let inner = 1..(samples.len() + hop - frame_len) / hop + 1;
+    let Some(noise) = noise_profile(&padded, inner, hop, &window, &fft) else {
+        // Digital silence throughout: nothing to subtract.
+        return samples.to_vec();
+    };
+
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(frame_count.div_ceil(FRAMES_PER_THREAD))
+        .max(1);
+    let per_thread = frame_count.div_ceil(threads);
+    let mut output = vec![0.0f32; padded.len()];
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..frame_count)
+            .step_by(per_thread)
+            .map(|first| {
+                let last = (first + per_thread).min(frame_count);
+                let (padded, window, noise, fft) = (&padded, &window, &noise, &fft);
+                scope.spawn(move || {
+                    let mut part = vec![0.0f32; (last - first + 1) * hop];
+                    let mut re = vec![0.0f32; frame_len];
+                    let mut im = vec![0.0f32; frame_len];
+                    for frame in first..last {
+                        analyze(padded, frame * hop, window, fft, &mut re, &mut im);
+                        for bin in 0..frame_len {
+                            let magnitude = re[bin].hypot(im[bin]);
+                            let gain = if magnitude > 0.0 {
+                                (1.0 - OVER_SUBTRACTION * noise[bin] / magnitude).max(GAIN_FLOOR)
+                            } else {
+                                GAIN_FLOOR
+                            };
+                            re[bin] *= gain;
+                            im[bin] *= gain;
+                        }
+                        fft.inverse(&mut re, &mut im);
+                        let offset = (frame - first) * hop;
+                        for (out, sample) in part[offset..offset + frame_len].iter_mut().zip(&re) {
+                            *out += sample;
+                        }
+                    }
+                    (first, part)
+                })
+            })
+            .collect();
+        for worker in workers {
+            let (first, part) = worker.join().expect("denoise worker panicked");
+            for (out, sample) in output[first * hop..].iter_mut().zip(part) {
+                *out += sample;
+            }
+        }
+    });
+
+    output[hop..hop + samples.len()]
+        .iter()
+        .map(|&s| s.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// About 32 ms, rounded up to a power of two for the FFT: 512 samples at 16 kHz.
+fn frame_len(sample_rate: u32) -> usize {
+    ((sample_rate as usize * 32) / 1000)
+        .max(64)
+        .next_power_of_two()
+}
+
+/// Average magnitude per bin over the quietest of `frames`. Frames of exact zeros, such as
+/// a device that delivered nothing yet, say nothing about the room and are skipped; `None`
+/// when every frame is zeros.
+fn noise_profile(
+    padded: &[f32],
+    frames: Range<usize>,
+    hop: usize,
+    window: &[f32],
+    fft: &Fft,
+) -> Option<Vec<f32>> {
+    let frame_len = window.len();
+    let mut energies: Vec<(f32, usize)> = frames
+        .map(|frame| {
+            let start = frame * hop;
+            let energy = padded[start..start + frame_len].iter().map(|s| s * s).sum();
+            (energy, frame)
+        })
+        .filter(|&(energy, _)| energy > 0.0)
+        .collect();
+    if energies.is_empty() {
+        return None;
+    }
+    energies.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let quiet = ((energies.len() as f32 * NOISE_FRAME_SHARE) as usize).max(1);
+    let mut profile = vec![0.0f32; frame_len];
+    let mut re = vec![0.0f32; frame_len];
+    let mut im = vec![0.0f32; frame_len];
+    for &(_, frame) in &energies[..quiet] {
+        analyze(padded, frame * hop, window, fft, &mut re, &mut im);
+        for (bin, value) in profile.iter_mut().enumerate() {
+            *value += re[bin].hypot(im[bin]);
+        }
+    }
+    for value in &mut profile {
+        *value /= quiet as f32;
+    }
+    Some(profile)
+}
+
+fn analyze(
+    padded: &[f32],
+    start: usize,
+    window: &[f32],
+    fft: &Fft,
+    re: &mut [f32],
+    im: &mut [f32],
+) {
+    for (i, w) in window.iter().enumerate() {
+        re[i] = padded[start + i] * w;
+        im[i] = 0.0;
+    }
+    fft.forward(re, im);
+}
+
+/// In-place iterative radix-2 FFT of a fixed power-of-two size.
+struct Fft {
+    n: usize,
+    cos: Vec<f32>,
+    sin: Vec<f32>,
+}
+
+impl Fft {
+    fn new(n: usize) -> Self {
+        let (cos, sin) = (0..n / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f32 / n as f32;
+                (angle.cos(), angle.sin())
+            })
+            .unzip();
+        Self { n, cos, sin }
+    }
+
+    fn forward(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im, false);
+    }
+
+    fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im, true);
+        let scale = 1.0 / self.n as f32;
+        for value in re.iter_mut() {
+            *value *= scale;
+        }
+    }
+
+    fn transform(&self, re: &mut [f32], im: &mut [f32], inverse: bool) {
+        let n = self.n;
+        let bits = n.trailing_zeros();
+        for i in 0..n {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if j > i {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut size = 2;
+        while size <= n {
+            let half = size / 2;
+            let stride = n / size;
+            for start in (0..n).step_by(size) {
+                for k in 0..half {
+                    let cos = self.cos[k * stride];
+                    let sin = if inverse {
+                        -self.sin[k * stride]
+                    } else {
+                        self.sin[k * stride]
+                    };
+                    let (a, b) = (start + k, start + k + half);
+                    let tre = re[b] * cos - im[b] * sin;
+                    let tim = re[b] * sin + im[b] * cos;
+                    re[b] = re[a] - tre;
+                    im[b] = im[a] - tim;
+                    re[a] += tre;
+                    im[a] += tim;
+                }
+            }
+            size *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    const RATE: u32 = 16_000;
+
+    /// Deterministic white noise in [-amplitude, amplitude].
+    fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    /// Bursts of a 300 Hz + 1200 Hz tone, off every other half second, like speech and pauses.
+    fn tone(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                if ((t * 2.0) as usize).is_multiple_of(2) {
+                    4_000.0 * (2.0 * PI * 300.0 * t).sin()
+                        + 2_000.0 * (2.0 * PI * 1_200.0 * t).sin()
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    fn to_i16(samples: &[f32]) -> Vec<i16> {
+        samples.iter().map(|&s| s.round() as i16).collect()
+    }
+
+    fn rms_error(a: &[i16], b: &[f32]) -> f32 {
+        let sum: f32 = a.iter().zip(b).map(|(&x, &y)| (x as f32 - y).powi(2)).sum();
+        (sum / a.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_fft_round_trip() {
+        let fft = Fft::new(512);
+        let signal = noise(512, 1_000.0);
+        let (mut re, mut im) = (signal.clone(), vec![0.0; 512]);
+        fft.forward(&mut re, &mut im);
+        fft.inverse(&mut re, &mut im);
+        assert!(re.iter().zip(&signal).all(|(a, b)| (a - b).abs() < 0.05));
+    }
+
+    #[test]
+    fn test_steady_noise_is_removed_and_the_tone_kept() {
+        let len = RATE as usize * 4;
+        let clean = tone(len);
+        let hiss = noise(len, 800.0);
+        let noisy: Vec<f32> = clean.iter().zip(&hiss).map(|(c, n)| c + n).collect();
+
+        let out = denoise(&to_i16(&noisy), RATE);
+        assert_eq!(out.len(), len);
+        let before = rms_error(&to_i16(&noisy), &clean);
+        let after = rms_error(&out, &clean);
+        assert!(after < before * 0.6, "error {} -> {}", before, after);
+
+        // A pause (0.5-1.0 s) loses most of its hiss.
+        let pause = RATE as usize / 2 + 800..RATE as usize - 800;
+        let zeros = vec![0.0; pause.len()];
+        let hiss_before = rms_error(&to_i16(&noisy[pause.clone()]), &zeros);
+        let hiss_after = rms_error(&out[pause], &zeros);
+        assert!(
+            hiss_after < hiss_before * 0.3,
+            "pause {} -> {}",
+            hiss_before,
+            hiss_after
+        );
+    }
+
+    #[test]
+    fn test_silence_and_short_input_pass_through() {
+        let silence = vec![0i16; RATE as usize];
+        assert_eq!(denoise(&silence, RATE), silence);
+        // Leading zeros from a slow device do not hide the hiss after them.
+        let mut late = vec![0.0; RATE as usize];
+        late.extend(noise(RATE as usize * 2, 800.0));
+        let out = denoise(&to_i16(&late), RATE);
+        let zeros = vec![0.0; RATE as usize];
+        assert!(rms_error(&out[RATE as usize * 2..], &zeros) < 200.0);
+        let short = to_i16(&noise(100, 500.0));
+        assert_eq!(denoise(&short, RATE), short);
+        assert!(denoise(&[], RATE).is_empty());
+    }
+
+    #[test]
+    fn test_a_minute_of_audio_takes_well_under_a_second() {
+        let len = RATE as usize * 60;
+        let noisy: Vec<f32> = tone(len)
+            .iter()
+            .zip(noise(len, 800.0))
+            .map(|(c, n)| c + n)
+            .collect();
+        let samples = to_i16(&noisy);
+        let started = Instant::now();
+        let out = denoise(&samples, RATE);
+        let elapsed = started.elapsed();
+        assert_eq!(out.len(), len);
+        // Release takes about 0.15 s here; unoptimized test builds are about 20x slower.
+        let budget = if cfg!(debug_assertions) {
+            Duration::from_secs(20)
+        } else {
+            Duration::from_millis(500)
+        };
+        assert!(elapsed < budget, "{:?}", elapsed);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod buffer;
+pub mod denoise;
+pub mod flac;
+pub mod vad;
+pub mod wav;
+
+pub use buffer::{AudioBuffer, BufferError};
@@ -0,0 +1,236 @@
+//! Silero voice activity detection, used to trim dead air from recordings before they
+//! reach a provider (Whisper models tend to hallucinate text on silence).
+
+use super::AudioBuffer;
+use std::ops::Range;
+#[cfg(feature = "onnx")]
+use std::path::Path;
+#[cfg(feature = "onnx")]
+use tracing::info;
+
+/// Silero runs on 16 kHz mono.
+pub const VAD_SAMPLE_RATE: u32 = 16_000;
+/// Samples per inference frame (32 ms at 16 kHz).
+pub const FRAME_SAMPLES: usize = 512;
+pub const DEFAULT_THRESHOLD: f32 = 0.5;
+pub const DEFAULT_MIN_SPEECH_MS: u32 = 250;
+/// Audio kept around the detected speech so word onsets and tails are not clipped.
+#[cfg(feature = "onnx")]
+const SPEECH_PAD_MS: u32 = 200;
+/// Silero v5 expects the last 64 samples of the previous frame in front of each frame.
+#[cfg(feature = "onnx")]
+const CONTEXT_SAMPLES: usize = 64;
+/// RNN state shape: [2, 1, 128].
+#[cfg(feature = "onnx")]
+const STATE_LEN: usize = 2 * 128;
+
+#[cfg(feature = "onnx")]
+pub struct Vad {
+    session: ort::session::Session,
+    state: Vec<f32>,
+    context: Vec<f32>,
+    threshold: f32,
+    min_speech_ms: u32,
+}
+
+#[cfg(feature = "onnx")]
+impl Vad {
+    pub fn new(model_path: &Path) -> Result<Self, String> {
+        Self::with_params(model_path, DEFAULT_THRESHOLD, DEFAULT_MIN_SPEECH_MS)
+    }
+
+    /// `threshold` is the speech probability (0-1) a frame needs to count as speech;
+    /// bursts shorter than `min_speech_ms` are treated as noise.
+    pub fn with_params(
+        model_path: &Path,
+        threshold: f32,
+        min_speech_ms: u32,
+    ) -> Result<Self, String> {
+        if !model_path.exists() {
+            return Err(format!("VAD model not found at {:?}", model_path));
+        }
+
+        let session = ort::session::Session::builder()
+            .map_err(|e| e.to_string())?
+            .with_intra_threads(1)
+            .map_err(|e| e.to_string())?
+            .commit_from_file(model_path)
+            .map_err(|e| format!("Failed to load VAD model: {}", e))?;
+        info!("VAD model loaded from {:?}", model_path);
+
+        Ok(Self {
+            session,
+            state: vec![0.0; STATE_LEN],
+            context: vec![0.0; CONTEXT_SAMPLES],
+            threshold: threshold.clamp(0.0, 1.0),
+            min_speech_ms,
+        })
+    }
+
+    /// Clear the RNN state and frame context. Call between recordings.
+    pub fn reset(&mut self) {
+        self.state.fill(0.0);
+        self.context.fill(0.0);
+    }
+
+    /// Speech probability for one frame of 16 kHz mono samples in [-1, 1]. Frames shorter
+    /// than [`FRAME_SAMPLES`] are zero-padded; longer ones are truncated.
+    pub fn speech_probability(&mut self, frame: &[f32]) -> Result<f32, String> {
+        use ort::value::Tensor;
+
+        let mut input = Vec::with_capacity(CONTEXT_SAMPLES + FRAME_SAMPLES);
+        input.extend_from_slice(&self.context);
+        input.extend(frame.iter().take(FRAME_SAMPLES).copied());
+        input.resize(CONTEXT_SAMPLES + FRAME_SAMPLES, 0.0);
+        self.context
+            .copy_from_slice(&input[input.len() - CONTEXT_SAMPLES..]);
+
+        let input = Tensor::from_array(([1usize, CONTEXT_SAMPLES + FRAME_SAMPLES], input))
+            .map_err(|e| e.to_string())?;
+        let state = Tensor::from_array(([2usize, 1, 128], self.state.clone()))
+            .map_err(|e| e.to_string())?;
+        let sr = Tensor::from_array(([1usize], vec![VAD_SAMPLE_RATE as i64]))
+            .map_err(|e| e.to_string())?;
+
+        let outputs = self
+            .session
+            .run(ort::inputs!["input" => input, "state" => state, "sr" => sr])
+            .map_err(|e| format!("VAD inference failed: {}", e))?;
+
+        let (_, probability) = outputs["output"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| e.to_string())?;
+        let (_, next_state) = outputs["stateN"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| e.to_string())?;
+        if next_state.len() == STATE_LEN {
+            self.state.copy_from_slice(next_state);
+        }
+
+        Ok(probability.first().copied().unwrap_or(0.0))
+    }
+
+    pub fn is_speech(&mut self, samples: &[f32]) -> Result<bool, String> {
+        if samples.is_empty() {
+            return Ok(false);
+        }
+        Ok(self.speech_probability(samples)? >= self.threshold)
+    }
+
+    /// Drop leading and trailing silence. Returns an empty buffer when no speech is found.
+    pub fn trim_silence(&mut self, audio: &AudioBuffer) -> Result<AudioBuffer, String> {
+        self.reset();
+        let mono: Vec<f32> = audio
+            .to_mono(VAD_SAMPLE_RATE)
+            .into_iter()
+            .map(|sample| sample as f32 / i16::MAX as f32)
+            .collect();
+
+        let mut probabilities = Vec::with_capacity(mono.len() / FRAME_SAMPLES + 1);
+        for frame in mono.chunks(FRAME_SAMPLES) {
+            probabilities.push(self.speech_probability(frame)?);
+        }
+        self.reset();
+
+        let span = speech_span(
+            &probabilities,
+            self.threshold,
+            ms_to_frames(self.min_speech_ms),
+            ms_to_frames(SPEECH_PAD_MS),
+        );
+        Ok(match span {
+            Some(frames) => slice_frames(audio, frames),
+            None => AudioBuffer::new(audio.sample_rate, audio.channels),
+        })
+    }
+}
+
+#[cfg(feature = "onnx")]
+fn ms_to_frames(ms: u32) -> usize {
+    (ms as usize * VAD_SAMPLE_RATE as usize / 1000).div_ceil(FRAME_SAMPLES)
+}
+
+/// Frames from the first to the last speech run of at least `min_speech_frames`, widened by
+/// `pad_frames` on each side. `None` when no run is long enough.
+pub fn speech_span(
+    probabilities: &[f32],
+    threshold: f32,
+    min_speech_frames: usize,
+    pad_frames: usize,
+) -> Option<Range<usize>> {
+    let min_speech_frames = min_speech_frames.max(1);
+    let mut first = None;
+    let mut last = None;
+    let mut run_start = None;
+
+    for idx in 0..=probabilities.len() {
+        let speech = probabilities
+            .get(idx)
+            .is_some_and(|&probability| probability >= threshold);
+        match (speech, run_start) {
+            (true, None) => run_start = Some(idx),
+            (false, Some(start)) => {
+                if idx - start >= min_speech_frames {
+                    first.get_or_insert(start);
+                    last = Some(idx);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let (first, last) = (first?, last?);
+    Some(first.saturating_sub(pad_frames)..(last + pad_frames).min(probabilities.len()))
+}
+
+/// Cut `audio` to a range of VAD frames, mapping 16 kHz frame positions back onto the
+/// buffer's own rate and channel layout.
+pub fn slice_frames(audio: &AudioBuffer, frames: Range<usize>) -> AudioBuffer {
+    let channels = audio.channels.max(1) as usize;
+    let to_sample = |frame: usize| {
+        let secs = (frame * FRAME_SAMPLES) as f64 / VAD_SAMPLE_RATE as f64;
+        ((secs * audio.sample_rate as f64) as usize * channels).min(audio.samples.len())
+    };
+
+    let mut trimmed = AudioBuffer::new(audio.sample_rate, audio.channels);
+    trimmed.append(&audio.samples[to_sample(frames.start)..to_sample(frames.end)]);
+    trimmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speech_span_skips_short_bursts_and_pads() {
+        let mut probabilities = vec![0.1f32; 40];
+        probabilities[3] = 0.9; // click, too short to count
+        for probability in &mut probabilities[10..20] {
+            *probability = 0.8;
+        }
+        probabilities[25] = 0.95; // short burst after speech, ignored too
+
+        assert_eq!(speech_span(&probabilities, 0.5, 4, 2), Some(8..22));
+        assert_eq!(speech_span(&probabilities, 0.5, 4, 30), Some(0..40));
+        assert_eq!(speech_span(&[0.2; 40], 0.5, 4, 2), None);
+    }
+
+    #[test]
+    fn test_slice_frames_maps_back_to_source_layout() {
+        let mut stereo = AudioBuffer::new(48_000, 2);
+        stereo.append(&vec![1i16; 48_000 * 2]);
+
+        // 10 frames of 512 samples at 16 kHz = 0.32 s
+        let trimmed = slice_frames(&stereo, 5..15);
+        assert_eq!(trimmed.samples.len(), 15_360 * 2);
+        assert!((trimmed.duration_secs - 0.32).abs() < 1e-3);
+
+        // Ranges past the end of the recording are clamped.
+        assert!(slice_frames(&stereo, 40..100).samples.is_empty());
+        assert_eq!(
+            slice_frames(&stereo, 20..100).samples.len(),
+            96_000 - 61_440
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::audio::AudioBuffer;
+use std::fs;
+use std::path::Path;
+
+/// Decode a 16-bit PCM WAV file into an AudioBuffer
+pub fn read_wav_file(path: &Path) -> Result<AudioBuffer, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    decode_wav(&bytes)
+}
+
+/// Decode 16-bit PCM WAV bytes into an AudioBuffer
+pub fn decode_wav(bytes: &[u8]) -> Result<AudioBuffer, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a RIFF/WAVE file".to_string());
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut offset = 12usize;
+
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = u32::from_le_bytes([
+            bytes[offset + 4],
+            bytes[offset + 5],
+            bytes[offset + 6],
+            bytes[offset + 7],
+        ]) as usize;
+        let body_start = offset + 8;
+        let body_end = usize::min(body_start + chunk_size, bytes.len());
+        let body = &bytes[body_start..body_end];
+
+        match chunk_id {
+            b"fmt " if body.len() >= 16 => {
+                let audio_format = u16::from_le_bytes([body[0], body[1]]);
+                let channels = u16::from_le_bytes([body[2], body[3]]);
+                let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits_per_sample = u16::from_le_bytes([body[14], body[15]]);
+                format = Some((audio_format, channels, sample_rate, bits_per_sample));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+
+        // Chunks are word-aligned
+        offset = body_start + chunk_size + (chunk_size % 2);
+    }
+
+    let (audio_format, channels, sample_rate, bits_per_sample) =
+        format.ok_or("WAV file has no fmt chunk")?;
+    let data = data.ok_or("WAV file has no data chunk")?;
+
+    if audio_format != 1 || bits_per_sample != 16 {
+        return Err(format!(
+            "Unsupported WAV encoding (format={}, bits={})",
+            audio_format, bits_per_sample
+        ));
+    }
+    if channels == 0 || sample_rate == 0 {
+        return Err("WAV file has an invalid channel count or sample rate".to_string());
+    }
+
+    let samples = data
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+
+    let mut buffer = AudioBuffer::new(sample_rate, channels);
+    buffer.append(&samples);
+    Ok(buffer)
+}
+
+/// Write `audio` to `path` as 16-bit PCM WAV, at its own rate and channel count.
+pub fn write_wav_file(path: &Path, audio: &AudioBuffer) -> Result<(), String> {
+    fs::write(path, encode_wav(audio))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Encode `audio` as 16-bit PCM WAV bytes
+pub fn encode_wav(audio: &AudioBuffer) -> Vec<u8> {
+    let mut wav = Vec::with_capacity(HEADER_LEN + audio.samples.len() * 2);
+    wav.extend_from_slice(&header(
+        audio.samples.len(),
+        audio.sample_rate,
+        audio.channels,
+    ));
+    for sample in &audio.samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+/// Size of the header [`header`] writes
+pub const HEADER_LEN: usize = 44;
+
+/// The 16-bit PCM header for `samples` interleaved samples, written before any of them
+/// exist so a WAV can be streamed as it is recorded.
+pub fn header(samples: usize, sample_rate: u32, channels: u16) -> [u8; HEADER_LEN] {
+    let channels = channels.max(1);
+    let data_size = (samples * 2) as u32;
+    let block_align = channels * 2;
+    let byte_rate = sample_rate * block_align as u32;
+
+    let mut wav = Vec::with_capacity(HEADER_LEN);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    wav.try_into().expect("header is HEADER_LEN bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoded_wav_decodes_to_the_same_audio() {
+        let mut audio = AudioBuffer::new(48_000, 2);
+        audio.append(&[0, 1, -1, i16::MAX, i16::MIN, 300]);
+
+        let decoded = decode_wav(&encode_wav(&audio)).unwrap();
+        assert_eq!(decoded.samples, audio.samples);
+        assert_eq!(decoded.sample_rate, 48_000);
+        assert_eq!(decoded.channels, 2);
+    }
+
+    #[test]
+    fn test_header_sizes_match_the_samples_still_to_come() {
+        // Ten seconds of 48 kHz stereo
+        let samples = 48_000 * 2 * 10;
+        let header = header(samples, 48_000, 2);
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+        assert_eq!(u32_at(4) as usize, HEADER_LEN + samples * 2 - 8);
+        assert_eq!(u32_at(24), 48_000);
+        assert_eq!(u32_at(28), 48_000 * 4);
+        assert_eq!(u32_at(40) as usize, samples * 2);
+
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&[5, -5, 7]);
+        let encoded = encode_wav(&audio);
+        assert_eq!(encoded.len(), HEADER_LEN + 6);
+        assert_eq!(encoded[..HEADER_LEN], super::header(3, 16_000, 1));
+    }
+}
@@ -0,0 +1,729 @@
+//! Stable codes for every failure the user can see. Messages change wording; codes don't,
+//! so support can grep a screenshot and the dashboard can link a code to its help page.
+//! String errors that cross IPC carry their code as a `[CODE] ` prefix (see [`ErrorCode::tag`]).
+
+use crate::orchestrator::OrchestratorError;
+use crate::prompt_engine::{EngineError, LLMError};
+use crate::provider_error::ApiErrorClass;
+use crate::session::SessionError;
+use crate::stt::STTError;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    RecNoDevice,
+    RecDeviceBusy,
+    RecUnsupportedFormat,
+    RecCaptureFailed,
+    RecDeviceLost,
+    RecAlreadyRecording,
+    SttNoKey,
+    SttAuth,
+    SttRateLimit,
+    SttTimeout,
+    SttNetwork,
+    SttInvalidAudio,
+    SttAudioTooLong,
+    SttAudioTooLarge,
+    SttProvider,
+    SttModelNotFound,
+    SttAllFailed,
+    SttPrivacyNoLocal,
+    SttOffline,
+    SessionNotActive,
+    SessionPaused,
+    SessionEmpty,
+    SessionSegmentTooLong,
+    SessionSegmentLimit,
+    SessionStitchFailed,
+    SessionSegmentNotFound,
+    SessionAudioNotRetained,
+    SessionSegmentsPending,
+    LlmNetwork,
+    LlmProvider,
+    LlmModelNotFound,
+    LlmInvalidResponse,
+    LlmTimeout,
+    LlmAllFailed,
+    PromptConfig,
+    PromptProfileNotFound,
+    PromptTemplate,
+    PasteNoTarget,
+    PasteTargetIsZentra,
+    PastePermission,
+    PasteToolMissing,
+    PasteTimeout,
+    PasteUnsupported,
+    PasteFailed,
+    CfgCorruptRecovered,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Nothing was lost; the user may want to know
+    Info,
+    /// Zentra recovered or fell back (e.g. copied instead of pasting)
+    Warning,
+    /// The action failed
+    Error,
+}
+
+/// Catalogue entry: the code, how loud to be about it and the i18n key of the hint that
+/// tells the user what to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorInfo {
+    pub code: ErrorCode,
+    pub severity: Severity,
+    pub hint_key: &'static str,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 45] = [
+        ErrorCode::RecNoDevice,
+        ErrorCode::RecDeviceBusy,
+        ErrorCode::RecUnsupportedFormat,
+        ErrorCode::RecCaptureFailed,
+        ErrorCode::RecDeviceLost,
+        ErrorCode::RecAlreadyRecording,
+        ErrorCode::SttNoKey,
+        ErrorCode::SttAuth,
+        ErrorCode::SttRateLimit,
+        ErrorCode::SttTimeout,
+        ErrorCode::SttNetwork,
+        ErrorCode::SttInvalidAudio,
+        ErrorCode::SttAudioTooLong,
+        ErrorCode::SttAudioTooLarge,
+        ErrorCode::SttProvider,
+        ErrorCode::SttModelNotFound,
+        ErrorCode::SttAllFailed,
+        ErrorCode::SttPrivacyNoLocal,
+        ErrorCode::SttOffline,
+        ErrorCode::SessionNotActive,
+        ErrorCode::SessionPaused,
+        ErrorCode::SessionEmpty,
+        ErrorCode::SessionSegmentTooLong,
+        ErrorCode::SessionSegmentLimit,
+        ErrorCode::SessionStitchFailed,
+        ErrorCode::SessionSegmentNotFound,
+        ErrorCode::SessionAudioNotRetained,
+        ErrorCode::SessionSegmentsPending,
+        ErrorCode::LlmNetwork,
+        ErrorCode::LlmProvider,
+        ErrorCode::LlmModelNotFound,
+        ErrorCode::LlmInvalidResponse,
+        ErrorCode::LlmTimeout,
+        ErrorCode::LlmAllFailed,
+        ErrorCode::PromptConfig,
+        ErrorCode::PromptProfileNotFound,
+        ErrorCode::PromptTemplate,
+        ErrorCode::PasteNoTarget,
+        ErrorCode::PasteTargetIsZentra,
+        ErrorCode::PastePermission,
+        ErrorCode::PasteToolMissing,
+        ErrorCode::PasteTimeout,
+        ErrorCode::PasteUnsupported,
+        ErrorCode::PasteFailed,
+        ErrorCode::CfgCorruptRecovered,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::RecNoDevice => "REC_NO_DEVICE",
+            ErrorCode::RecDeviceBusy => "REC_DEVICE_BUSY",
+            ErrorCode::RecUnsupportedFormat => "REC_UNSUPPORTED_FORMAT",
+            ErrorCode::RecCaptureFailed => "REC_CAPTURE_FAILED",
+            ErrorCode::RecDeviceLost => "REC_DEVICE_LOST",
+            ErrorCode::RecAlreadyRecording => "REC_ALREADY_RECORDING",
+            ErrorCode::SttNoKey => "STT_NO_KEY",
+            ErrorCode::SttAuth => "STT_AUTH",
+            ErrorCode::SttRateLimit => "STT_RATE_LIMIT",
+            ErrorCode::SttTimeout => "STT_TIMEOUT",
+            ErrorCode::SttNetwork => "STT_NETWORK",
+            ErrorCode::SttInvalidAudio => "STT_INVALID_AUDIO",
+            ErrorCode::SttAudioTooLong => "STT_AUDIO_TOO_LONG",
+            ErrorCode::SttAudioTooLarge => "STT_AUDIO_TOO_LARGE",
+            ErrorCode::SttProvider => "STT_PROVIDER",
+            ErrorCode::SttModelNotFound => "STT_MODEL_NOT_FOUND",
+            ErrorCode::SttAllFailed => "STT_ALL_FAILED",
+            ErrorCode::SttPrivacyNoLocal => "STT_PRIVACY_NO_LOCAL",
+            ErrorCode::SttOffline => "STT_OFFLINE",
+            ErrorCode::SessionNotActive => "SESSION_NOT_ACTIVE",
+            ErrorCode::SessionPaused => "SESSION_PAUSED",
+            ErrorCode::SessionEmpty => "SESSION_EMPTY",
+            ErrorCode::SessionSegmentTooLong => "SESSION_SEGMENT_TOO_LONG",
+            ErrorCode::SessionSegmentLimit => "SESSION_SEGMENT_LIMIT",
+            ErrorCode::SessionStitchFailed => "SESSION_STITCH_FAILED",
+            ErrorCode::SessionSegmentNotFound => "SESSION_SEGMENT_NOT_FOUND",
+            ErrorCode::SessionAudioNotRetained => "SESSION_AUDIO_NOT_RETAINED",
+            ErrorCode::SessionSegmentsPending => "SESSION_SEGMENTS_PENDING",
+            ErrorCode::LlmNetwork => "LLM_NETWORK",
+            ErrorCode::LlmProvider => "LLM_PROVIDER",
+            ErrorCode::LlmModelNotFound => "LLM_MODEL_NOT_FOUND",
+            ErrorCode::LlmInvalidResponse => "LLM_INVALID_RESPONSE",
+            ErrorCode::LlmTimeout => "LLM_TIMEOUT",
+            ErrorCode::LlmAllFailed => "LLM_ALL_FAILED",
+            ErrorCode::PromptConfig => "PROMPT_CONFIG",
+            ErrorCode::PromptProfileNotFound => "PROMPT_PROFILE_NOT_FOUND",
+            ErrorCode::PromptTemplate => "PROMPT_TEMPLATE",
+            ErrorCode::PasteNoTarget => "PASTE_NO_TARGET",
+            ErrorCode::PasteTargetIsZentra => "PASTE_TARGET_IS_ZENTRA",
+            ErrorCode::PastePermission => "PASTE_PERMISSION",
+            ErrorCode::PasteToolMissing => "PASTE_TOOL_MISSING",
+            ErrorCode::PasteTimeout => "PASTE_TIMEOUT",
+            ErrorCode::PasteUnsupported => "PASTE_UNSUPPORTED",
+            ErrorCode::PasteFailed => "PASTE_FAILED",
+            ErrorCode::CfgCorruptRecovered => "CFG_CORRUPT_RECOVERED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|code| code.as_str() == value)
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            ErrorCode::RecAlreadyRecording | ErrorCode::SessionPaused => Severity::Info,
+            // The transcript is on the clipboard; only the paste step was skipped.
+            ErrorCode::PasteNoTarget
+            | ErrorCode::PasteTargetIsZentra
+            | ErrorCode::PastePermission
+            | ErrorCode::PasteToolMissing
+            | ErrorCode::PasteTimeout
+            | ErrorCode::PasteUnsupported
+            | ErrorCode::PasteFailed => Severity::Warning,
+            ErrorCode::SttRateLimit | ErrorCode::CfgCorruptRecovered => Severity::Warning,
+            // Optimization falls back to the clarity-only text.
+            ErrorCode::LlmNetwork
+            | ErrorCode::LlmProvider
+            | ErrorCode::LlmModelNotFound
+            | ErrorCode::LlmInvalidResponse
+            | ErrorCode::LlmTimeout
+            | ErrorCode::LlmAllFailed => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// i18n key of the user-action hint, e.g. `errors.stt_auth`.
+    pub fn hint_key(self) -> &'static str {
+        match self {
+            ErrorCode::RecNoDevice => "errors.rec_no_device",
+            ErrorCode::RecDeviceBusy => "errors.rec_device_busy",
+            ErrorCode::RecUnsupportedFormat => "errors.rec_unsupported_format",
+            ErrorCode::RecCaptureFailed => "errors.rec_capture_failed",
+            ErrorCode::RecDeviceLost => "errors.rec_device_lost",
+            ErrorCode::RecAlreadyRecording => "errors.rec_already_recording",
+            ErrorCode::SttNoKey => "errors.stt_no_key",
+            ErrorCode::SttAuth => "errors.stt_auth",
+            ErrorCode::SttRateLimit => "errors.stt_rate_limit",
+            ErrorCode::SttTimeout => "errors.stt_timeout",
+            ErrorCode::SttNetwork => "errors.stt_network",
+            ErrorCode::SttInvalidAudio => "errors.stt_invalid_audio",
+            ErrorCode::SttAudioTooLong => "errors.stt_audio_too_long",
+            ErrorCode::SttAudioTooLarge => "errors.stt_audio_too_large",
+            ErrorCode::SttProvider => "errors.stt_provider",
+            ErrorCode::SttModelNotFound => "errors.stt_model_not_found",
+            ErrorCode::SttAllFailed => "errors.stt_all_failed",
+            ErrorCode::SttPrivacyNoLocal => "errors.stt_privacy_no_local",
+            ErrorCode::SttOffline => "errors.stt_offline",
+            ErrorCode::SessionNotActive => "errors.session_not_active",
+            ErrorCode::SessionPaused => "errors.session_paused",
+            ErrorCode::SessionEmpty => "errors.session_empty",
+            ErrorCode::SessionSegmentTooLong => "errors.session_segment_too_long",
+            ErrorCode::SessionSegmentLimit => "errors.session_segment_limit",
+            ErrorCode::SessionStitchFailed => "errors.session_stitch_failed",
+            ErrorCode::SessionSegmentNotFound => "errors.session_segment_not_found",
+            ErrorCode::SessionAudioNotRetained => "errors.session_audio_not_retained",
+            ErrorCode::SessionSegmentsPending => "errors.session_segments_pending",
+            ErrorCode::LlmNetwork => "errors.llm_network",
+            ErrorCode::LlmProvider => "errors.llm_provider",
+            ErrorCode::LlmModelNotFound => "errors.llm_model_not_found",
+            ErrorCode::LlmInvalidResponse => "errors.llm_invalid_response",
+            ErrorCode::LlmTimeout => "errors.llm_timeout",
+            ErrorCode::LlmAllFailed => "errors.llm_all_failed",
+            ErrorCode::PromptConfig => "errors.prompt_config",
+            ErrorCode::PromptProfileNotFound => "errors.prompt_profile_not_found",
+            ErrorCode::PromptTemplate => "errors.prompt_template",
+            ErrorCode::PasteNoTarget => "errors.paste_no_target",
+            ErrorCode::PasteTargetIsZentra => "errors.paste_target_is_zentra",
+            ErrorCode::PastePermission => "errors.paste_permission",
+            ErrorCode::PasteToolMissing => "errors.paste_tool_missing",
+            ErrorCode::PasteTimeout => "errors.paste_timeout",
+            ErrorCode::PasteUnsupported => "errors.paste_unsupported",
+            ErrorCode::PasteFailed => "errors.paste_failed",
+            ErrorCode::CfgCorruptRecovered => "errors.cfg_corrupt_recovered",
+        }
+    }
+
+    pub fn info(self) -> ErrorInfo {
+        ErrorInfo {
+            code: self,
+            severity: self.severity(),
+            hint_key: self.hint_key(),
+        }
+    }
+
+    /// `message` with this code in front, as string errors carry it over IPC.
+    pub fn tag(self, message: impl fmt::Display) -> String {
+        format!("[{}] {}", self.as_str(), message)
+    }
+
+    /// Split a tagged message into its code and the message after the tag.
+    pub fn untag(tagged: &str) -> Option<(Self, &str)> {
+        let rest = tagged.strip_prefix('[')?;
+        let (code, message) = rest.split_once("] ")?;
+        Some((Self::parse(code)?, message))
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Every entry, in catalogue order.
+pub fn catalogue() -> Vec<ErrorInfo> {
+    ErrorCode::ALL.into_iter().map(ErrorCode::info).collect()
+}
+
+impl From<&STTError> for ErrorCode {
+    fn from(err: &STTError) -> Self {
+        match err {
+            STTError::NetworkError(_) => ErrorCode::SttNetwork,
+            STTError::TimeoutError => ErrorCode::SttTimeout,
+            STTError::AudioTooLong => ErrorCode::SttAudioTooLong,
+            STTError::InvalidAudio | STTError::MalformedAudio(_) => ErrorCode::SttInvalidAudio,
+            STTError::AuthenticationError => ErrorCode::SttAuth,
+            STTError::RateLimitError { .. } => ErrorCode::SttRateLimit,
+            STTError::ProviderError(_) => ErrorCode::SttProvider,
+            STTError::ProviderApi(api) => match api.class() {
+                ApiErrorClass::InvalidAudio => ErrorCode::SttInvalidAudio,
+                ApiErrorClass::ModelNotFound => ErrorCode::SttModelNotFound,
+                ApiErrorClass::TooLarge => ErrorCode::SttAudioTooLarge,
+                ApiErrorClass::Other => ErrorCode::SttProvider,
+            },
+            STTError::ModelNotFound(_) => ErrorCode::SttModelNotFound,
+        }
+    }
+}
+
+impl From<&OrchestratorError> for ErrorCode {
+    fn from(err: &OrchestratorError) -> Self {
+        match err {
+            OrchestratorError::NoProvidersAvailable => ErrorCode::SttNoKey,
+            OrchestratorError::NoTrustedProviders => ErrorCode::SttPrivacyNoLocal,
+            OrchestratorError::Offline => ErrorCode::SttOffline,
+            OrchestratorError::InvalidAudio(_) => ErrorCode::SttInvalidAudio,
+            OrchestratorError::AllProvidersFailed(errors) => {
+                // The cause the user can act on wins over whatever failed last.
+                let causes: Vec<ErrorCode> = errors.iter().map(|(_, e)| e.into()).collect();
+                [
+                    ErrorCode::SttAuth,
+                    ErrorCode::SttRateLimit,
+                    ErrorCode::SttModelNotFound,
+                    ErrorCode::SttAudioTooLarge,
+                    ErrorCode::SttInvalidAudio,
+                    ErrorCode::SttTimeout,
+                ]
+                .into_iter()
+                .find(|code| causes.contains(code))
+                .unwrap_or(ErrorCode::SttAllFailed)
+            }
+        }
+    }
+}
+
+impl From<&SessionError> for ErrorCode {
+    fn from(err: &SessionError) -> Self {
+        match err {
+            SessionError::NoActiveSession => ErrorCode::SessionNotActive,
+            SessionError::SessionPaused => ErrorCode::SessionPaused,
+            SessionError::EmptySession => ErrorCode::SessionEmpty,
+            SessionError::SegmentTooLong { .. } => ErrorCode::SessionSegmentTooLong,
+            SessionError::SegmentLimitReached { .. } => ErrorCode::SessionSegmentLimit,
+            SessionError::StitchError(_) => ErrorCode::SessionStitchFailed,
+            // Carries the orchestrator's tagged message.
+            SessionError::TranscriptionFailed(message) => {
+                ErrorCode::untag(message).map_or(ErrorCode::SttAllFailed, |(code, _)| code)
+            }
+            SessionError::SegmentNotFound(_) => ErrorCode::SessionSegmentNotFound,
+            SessionError::AudioNotRetained(_) => ErrorCode::SessionAudioNotRetained,
+            SessionError::SegmentsNotTranscribed { .. } => ErrorCode::SessionSegmentsPending,
+            SessionError::NothingToRecover(_) => ErrorCode::SessionEmpty,
+        }
+    }
+}
+
+impl From<&LLMError> for ErrorCode {
+    fn from(err: &LLMError) -> Self {
+        match err {
+            LLMError::NetworkError(_) => ErrorCode::LlmNetwork,
+            LLMError::ProviderError(_) => ErrorCode::LlmProvider,
+            LLMError::ProviderApi { error, .. } => match error.class() {
+                ApiErrorClass::ModelNotFound => ErrorCode::LlmModelNotFound,
+                _ => ErrorCode::LlmProvider,
+            },
+            LLMError::InvalidResponse => ErrorCode::LlmInvalidResponse,
+            LLMError::Timeout => ErrorCode::LlmTimeout,
+            LLMError::AllProvidersFailed => ErrorCode::LlmAllFailed,
+        }
+    }
+}
+
+impl From<&EngineError> for ErrorCode {
+    fn from(err: &EngineError) -> Self {
+        match err {
+            EngineError::ConfigError(_) => ErrorCode::PromptConfig,
+            EngineError::ProfileNotFound(_) => ErrorCode::PromptProfileNotFound,
+            EngineError::InvalidProfile(_) => ErrorCode::PromptConfig,
+            EngineError::LLMError(_) => ErrorCode::LlmAllFailed,
+            EngineError::TemplateError(_) => ErrorCode::PromptTemplate,
+        }
+    }
+}
+
+/// User-facing message for an orchestrator failure, tagged with its code.
+pub fn describe_orchestrator_error(err: &OrchestratorError) -> String {
+    let code = ErrorCode::from(err);
+    let message = match code {
+        ErrorCode::SttNoKey => {
+            "Groq API key missing or invalid. Configure a valid key in Setup/Settings.".to_string()
+        }
+        ErrorCode::SttPrivacyNoLocal => {
+            "Privacy mode is on and no local transcription provider is configured.".to_string()
+        }
+        ErrorCode::SttOffline => {
+            "Offline and no local transcription provider is configured.".to_string()
+        }
+        ErrorCode::SttAuth => {
+            "Groq authentication failed. Check if your API key is valid.".to_string()
+        }
+        ErrorCode::SttRateLimit => match err.retry_after_secs() {
+            Some(secs) => format!("Groq rate limit reached. Try again in {}s.", secs.max(1)),
+            None => "Groq rate limit reached. Please wait and try again.".to_string(),
+        },
+        ErrorCode::SttTimeout => {
+            "Groq request timed out. Check your connection and try again.".to_string()
+        }
+        ErrorCode::SttModelNotFound => format!(
+            "The transcription model isn't available. Choose another in Settings. {}",
+            cause_detail(err, code)
+        ),
+        ErrorCode::SttAudioTooLarge => format!(
+            "The recording is too large to upload. Record in shorter parts. {}",
+            cause_detail(err, code)
+        ),
+        ErrorCode::SttInvalidAudio => format!(
+            "The provider couldn't read the audio. {}",
+            cause_detail(err, code)
+        ),
+        _ => {
+            let details = match err {
+                OrchestratorError::AllProvidersFailed(errors) => errors
+                    .iter()
+                    .map(|(provider, error)| format!("{}: {}", provider, error))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                other => other.to_string(),
+            };
+            format!("Groq transcription failed. {}", details)
+        }
+    };
+    code.tag(message)
+}
+
+/// The failure behind `code`, as its provider described it.
+fn cause_detail(err: &OrchestratorError, code: ErrorCode) -> String {
+    match err {
+        OrchestratorError::AllProvidersFailed(errors) => errors
+            .iter()
+            .find(|(_, error)| ErrorCode::from(error) == code)
+            .map(|(provider, error)| format!("{}: {}", provider, error))
+            .unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::BufferError;
+    use crate::provider_error::ProviderApiError;
+    use std::collections::HashSet;
+
+    fn api(status: u16, body: &str) -> ProviderApiError {
+        ProviderApiError::parse(status, body)
+    }
+
+    #[test]
+    fn test_catalogue_is_unique_and_matches_serde() {
+        let names: HashSet<&str> = ErrorCode::ALL.iter().map(|code| code.as_str()).collect();
+        assert_eq!(names.len(), ErrorCode::ALL.len());
+        let hints: HashSet<&str> = ErrorCode::ALL.iter().map(|code| code.hint_key()).collect();
+        assert_eq!(hints.len(), ErrorCode::ALL.len());
+
+        for code in ErrorCode::ALL {
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+            assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+        }
+        assert_eq!(
+            serde_json::to_value(ErrorCode::SttAuth.info()).unwrap(),
+            serde_json::json!({
+                "code": "STT_AUTH",
+                "severity": "error",
+                "hintKey": "errors.stt_auth"
+            })
+        );
+    }
+
+    #[test]
+    fn test_tagged_messages_round_trip() {
+        let tagged = ErrorCode::PasteNoTarget.tag("No window to paste into");
+        assert_eq!(tagged, "[PASTE_NO_TARGET] No window to paste into");
+        assert_eq!(
+            ErrorCode::untag(&tagged),
+            Some((ErrorCode::PasteNoTarget, "No window to paste into"))
+        );
+        assert_eq!(ErrorCode::untag("[NOT_A_CODE] x"), None);
+        assert_eq!(ErrorCode::untag("Plain message"), None);
+    }
+
+    // The `From` impls match exhaustively, so a new variant can't compile unmapped; these
+    // pin each variant to its entry.
+    #[test]
+    fn test_every_stt_error_maps_to_an_entry() {
+        let cases = [
+            (STTError::NetworkError("x".into()), ErrorCode::SttNetwork),
+            (STTError::TimeoutError, ErrorCode::SttTimeout),
+            (STTError::AudioTooLong, ErrorCode::SttAudioTooLong),
+            (STTError::InvalidAudio, ErrorCode::SttInvalidAudio),
+            (
+                STTError::MalformedAudio(BufferError::ZeroChannels),
+                ErrorCode::SttInvalidAudio,
+            ),
+            (STTError::AuthenticationError, ErrorCode::SttAuth),
+            (
+                STTError::RateLimitError {
+                    retry_after_secs: None,
+                },
+                ErrorCode::SttRateLimit,
+            ),
+            (STTError::ProviderError("x".into()), ErrorCode::SttProvider),
+            (STTError::ProviderApi(api(500, "x")), ErrorCode::SttProvider),
+            (
+                STTError::ProviderApi(api(
+                    400,
+                    r#"{"error":{"message":"file must be one of the following types: [flac mp3 wav]","type":"invalid_request_error"}}"#,
+                )),
+                ErrorCode::SttInvalidAudio,
+            ),
+            (
+                STTError::ProviderApi(api(
+                    404,
+                    r#"{"error":{"message":"The model `x` does not exist","type":"invalid_request_error","code":"model_not_found"}}"#,
+                )),
+                ErrorCode::SttModelNotFound,
+            ),
+            (
+                STTError::ProviderApi(api(
+                    400,
+                    r#"{"detail":{"status":"file_too_large","message":"The uploaded file is too large."}}"#,
+                )),
+                ErrorCode::SttAudioTooLarge,
+            ),
+            (
+                STTError::ModelNotFound("x".into()),
+                ErrorCode::SttModelNotFound,
+            ),
+        ];
+        for (err, code) in cases {
+            assert_eq!(ErrorCode::from(&err), code, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_every_session_error_maps_to_an_entry() {
+        let failed = describe_orchestrator_error(&OrchestratorError::AllProvidersFailed(vec![(
+            "Groq".to_string(),
+            STTError::RateLimitError {
+                retry_after_secs: None,
+            },
+        )]));
+        let cases = [
+            (SessionError::NoActiveSession, ErrorCode::SessionNotActive),
+            (SessionError::SessionPaused, ErrorCode::SessionPaused),
+            (SessionError::EmptySession, ErrorCode::SessionEmpty),
+            (
+                SessionError::SegmentTooLong {
+                    duration: 61.0,
+                    max: 59.0,
+                },
+                ErrorCode::SessionSegmentTooLong,
+            ),
+            (
+                SessionError::SegmentLimitReached { max: 4 },
+                ErrorCode::SessionSegmentLimit,
+            ),
+            (
+                SessionError::StitchError("x".into()),
+                ErrorCode::SessionStitchFailed,
+            ),
+            (
+                SessionError::TranscriptionFailed(failed),
+                ErrorCode::SttRateLimit,
+            ),
+            (
+                SessionError::TranscriptionFailed("untagged".into()),
+                ErrorCode::SttAllFailed,
+            ),
+            (
+                SessionError::SegmentNotFound("s1".into()),
+                ErrorCode::SessionSegmentNotFound,
+            ),
+            (
+                SessionError::AudioNotRetained("s1".into()),
+                ErrorCode::SessionAudioNotRetained,
+            ),
+            (
+                SessionError::SegmentsNotTranscribed {
+                    segment_ids: vec!["s1".into()],
+                },
+                ErrorCode::SessionSegmentsPending,
+            ),
+            (
+                SessionError::NothingToRecover("s1".into()),
+                ErrorCode::SessionEmpty,
+            ),
+        ];
+        for (err, code) in cases {
+            assert_eq!(ErrorCode::from(&err), code, "{:?}", err);
+            let message = err.to_string();
+            assert_eq!(ErrorCode::untag(&message).map(|(c, _)| c), Some(code));
+        }
+    }
+
+    #[test]
+    fn test_every_llm_and_engine_error_maps_to_an_entry() {
+        let llm = [
+            (LLMError::NetworkError("x".into()), ErrorCode::LlmNetwork),
+            (LLMError::ProviderError("x".into()), ErrorCode::LlmProvider),
+            (
+                LLMError::ProviderApi {
+                    provider: "Ollama llama3".into(),
+                    error: api(404, r#"{"error":"model \"llama3\" not found"}"#),
+                },
+                ErrorCode::LlmModelNotFound,
+            ),
+            (
+                LLMError::ProviderApi {
+                    provider: "Groq".into(),
+                    error: api(503, "x"),
+                },
+                ErrorCode::LlmProvider,
+            ),
+            (LLMError::InvalidResponse, ErrorCode::LlmInvalidResponse),
+            (LLMError::Timeout, ErrorCode::LlmTimeout),
+            (LLMError::AllProvidersFailed, ErrorCode::LlmAllFailed),
+        ];
+        for (err, code) in llm {
+            assert_eq!(ErrorCode::from(&err), code, "{:?}", err);
+        }
+        let engine = [
+            (
+                EngineError::ConfigError("x".into()),
+                ErrorCode::PromptConfig,
+            ),
+            (
+                EngineError::ProfileNotFound("x".into()),
+                ErrorCode::PromptProfileNotFound,
+            ),
+            (
+                EngineError::InvalidProfile("x".into()),
+                ErrorCode::PromptConfig,
+            ),
+            (EngineError::LLMError("x".into()), ErrorCode::LlmAllFailed),
+            (
+                EngineError::TemplateError("x".into()),
+                ErrorCode::PromptTemplate,
+            ),
+        ];
+        for (err, code) in engine {
+            assert_eq!(ErrorCode::from(&err), code, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_orchestrator_errors_prefer_the_actionable_cause() {
+        let all_failed = |errors: Vec<STTError>| {
+            OrchestratorError::AllProvidersFailed(
+                errors
+                    .into_iter()
+                    .map(|e| ("Groq".to_string(), e))
+                    .collect(),
+            )
+        };
+        let cases = [
+            (OrchestratorError::NoProvidersAvailable, ErrorCode::SttNoKey),
+            (
+                OrchestratorError::NoTrustedProviders,
+                ErrorCode::SttPrivacyNoLocal,
+            ),
+            (OrchestratorError::Offline, ErrorCode::SttOffline),
+            (
+                OrchestratorError::InvalidAudio(BufferError::ZeroSampleRate),
+                ErrorCode::SttInvalidAudio,
+            ),
+            (
+                all_failed(vec![STTError::TimeoutError, STTError::AuthenticationError]),
+                ErrorCode::SttAuth,
+            ),
+            (
+                all_failed(vec![STTError::ProviderError("500".into())]),
+                ErrorCode::SttAllFailed,
+            ),
+        ];
+        for (err, code) in cases {
+            assert_eq!(ErrorCode::from(&err), code, "{:?}", err);
+            let message = describe_orchestrator_error(&err);
+            assert!(message.starts_with(&format!("[{}] ", code)), "{}", message);
+        }
+        assert_eq!(
+            describe_orchestrator_error(&all_failed(vec![STTError::ProviderError("500".into())])),
+            "[STT_ALL_FAILED] Groq transcription failed. Groq: Provider error: 500"
+        );
+
+        // What the provider said reaches the message, not the JSON it said it in.
+        let no_model = all_failed(vec![
+            STTError::TimeoutError,
+            STTError::ProviderApi(api(
+                404,
+                r#"{"error":{"message":"The model `x` does not exist","type":"invalid_request_error","code":"model_not_found"}}"#,
+            )),
+        ]);
+        assert_eq!(
+            describe_orchestrator_error(&no_model),
+            "[STT_MODEL_NOT_FOUND] The transcription model isn't available. Choose another in \
+             Settings. Groq: The model `x` does not exist (HTTP 404, model_not_found)"
+        );
+
+        // The shortest wait any rate-limited provider asked for reaches the message.
+        let rate_limited = all_failed(vec![
+            STTError::RateLimitError {
+                retry_after_secs: Some(20),
+            },
+            STTError::TimeoutError,
+            STTError::RateLimitError {
+                retry_after_secs: Some(12),
+            },
+        ]);
+        assert_eq!(rate_limited.retry_after_secs(), Some(12));
+        assert_eq!(
+            describe_orchestrator_error(&rate_limited),
+            "[STT_RATE_LIMIT] Groq rate limit reached. Try again in 12s."
+        );
+    }
+}
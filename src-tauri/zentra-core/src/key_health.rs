@@ -0,0 +1,186 @@
+//! Health of the Groq API key between dictations. The app probes the key once a day and
+//! after every authentication failure, so a revoked or expired key is reported before the
+//! user records something that cannot be transcribed.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyStatus {
+    Valid,
+    Invalid,
+    /// Not probed yet, or the key changed since the last probe
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeOutcome {
+    /// The provider accepted the key
+    Accepted,
+    /// The key is malformed, or the provider answered 401/403
+    Rejected,
+    /// Network failure, timeout or a server-side error; says nothing about the key
+    Unreachable,
+}
+
+/// Status after a probe. Only an answer from the provider changes it; being offline never
+/// turns a good key bad.
+pub fn next_status(current: KeyStatus, outcome: ProbeOutcome) -> KeyStatus {
+    match outcome {
+        ProbeOutcome::Accepted => KeyStatus::Valid,
+        ProbeOutcome::Rejected => KeyStatus::Invalid,
+        ProbeOutcome::Unreachable => current,
+    }
+}
+
+/// Outcome for an HTTP status from the key probe. Rate limits and 5xx are inconclusive.
+pub fn classify_http_status(status: u16) -> ProbeOutcome {
+    match status {
+        200..=299 => ProbeOutcome::Accepted,
+        401 | 403 => ProbeOutcome::Rejected,
+        _ => ProbeOutcome::Unreachable,
+    }
+}
+
+/// Groq keys are `gsk_` followed by an alphanumeric token; anything else is rejected
+/// without a request.
+pub fn has_groq_key_shape(key: &str) -> bool {
+    key.strip_prefix("gsk_")
+        .is_some_and(|token| token.len() >= 20 && token.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Shared key status plus the wake-up signal for the health task.
+#[derive(Debug, Clone, Default)]
+pub struct KeyHealth {
+    inner: Arc<KeyHealthInner>,
+}
+
+#[derive(Debug, Default)]
+struct KeyHealthInner {
+    status: Mutex<KeyStatus>,
+    check_requested: Notify,
+}
+
+impl KeyHealth {
+    pub fn new(status: KeyStatus) -> Self {
+        Self {
+            inner: Arc::new(KeyHealthInner {
+                status: Mutex::new(status),
+                check_requested: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn status(&self) -> KeyStatus {
+        *self
+            .inner
+            .status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Replace the status outright, e.g. with `Unknown` after the user enters a new key.
+    /// Returns true when it changed.
+    pub fn set_status(&self, status: KeyStatus) -> bool {
+        let mut current = self
+            .inner
+            .status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let changed = *current != status;
+        *current = status;
+        changed
+    }
+
+    /// Apply a probe outcome. Returns the new status when it changed.
+    pub fn record(&self, outcome: ProbeOutcome) -> Option<KeyStatus> {
+        let next = next_status(self.status(), outcome);
+        self.set_status(next).then_some(next)
+    }
+
+    /// Ask the health task for a probe now; the orchestrator calls this when a provider
+    /// rejects its credentials.
+    pub fn request_check(&self) {
+        self.inner.check_requested.notify_one();
+    }
+
+    /// Resolves after the next [`request_check`](Self::request_check), or right away if
+    /// one arrived since the last wait.
+    pub async fn check_requested(&self) {
+        self.inner.check_requested.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn statuses(start: KeyStatus, outcomes: &[ProbeOutcome]) -> Vec<KeyStatus> {
+        let health = KeyHealth::new(start);
+        outcomes
+            .iter()
+            .map(|&outcome| {
+                health.record(outcome);
+                health.status()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_offline_probes_keep_the_last_known_status() {
+        use KeyStatus::*;
+        use ProbeOutcome::*;
+
+        assert_eq!(
+            statuses(Unknown, &[Unreachable, Accepted, Unreachable, Unreachable]),
+            [Unknown, Valid, Valid, Valid]
+        );
+        assert_eq!(
+            statuses(Valid, &[Rejected, Unreachable, Accepted]),
+            [Invalid, Invalid, Valid]
+        );
+        assert_eq!(statuses(Invalid, &[Unreachable]), [Invalid]);
+    }
+
+    #[test]
+    fn test_record_reports_only_changes() {
+        let health = KeyHealth::new(KeyStatus::Valid);
+        assert_eq!(health.record(ProbeOutcome::Accepted), None);
+        assert_eq!(health.record(ProbeOutcome::Unreachable), None);
+        assert_eq!(
+            health.record(ProbeOutcome::Rejected),
+            Some(KeyStatus::Invalid)
+        );
+        assert_eq!(health.record(ProbeOutcome::Rejected), None);
+        assert!(health.set_status(KeyStatus::Unknown));
+        assert!(!health.set_status(KeyStatus::Unknown));
+    }
+
+    #[test]
+    fn test_http_status_and_key_shape() {
+        assert_eq!(classify_http_status(200), ProbeOutcome::Accepted);
+        assert_eq!(classify_http_status(401), ProbeOutcome::Rejected);
+        assert_eq!(classify_http_status(403), ProbeOutcome::Rejected);
+        assert_eq!(classify_http_status(429), ProbeOutcome::Unreachable);
+        assert_eq!(classify_http_status(503), ProbeOutcome::Unreachable);
+
+        assert!(has_groq_key_shape("gsk_0123456789abcdefABCDEF"));
+        assert!(!has_groq_key_shape("gsk_short"));
+        assert!(!has_groq_key_shape("gsk_0123456789abcdef ABCDEF"));
+        assert!(!has_groq_key_shape("sk-or-v1-0123456789abcdef"));
+    }
+
+    #[tokio::test]
+    async fn test_check_request_wakes_the_waiter() {
+        let health = KeyHealth::default();
+        // A request that arrives before anyone waits is not lost.
+        health.clone().request_check();
+        tokio::time::timeout(Duration::from_secs(1), health.check_requested())
+            .await
+            .unwrap();
+    }
+}
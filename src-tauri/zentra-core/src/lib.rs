@@ -0,0 +1,14 @@
+//! Transcription pipeline without any Tauri or window dependencies.
+
+pub mod audio;
+pub mod error_codes;
+pub mod key_health;
+pub mod offline;
+pub mod orchestrator;
+pub mod privacy;
+pub mod prompt_engine;
+pub mod provider_error;
+pub mod replay;
+pub mod session;
+pub mod stt;
+pub mod text;
@@ -0,0 +1,103 @@
+//! Offline detection for cloud providers. The app goes offline when the user says so or
+//! after a run of transcriptions that failed only on the network; while offline the
+//! orchestrator skips cloud providers instead of waiting on their timeouts.
+
+use crate::stt::STTError;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Consecutive network-only failures before offline mode turns on by itself.
+pub const NETWORK_FAILURES_BEFORE_OFFLINE: u32 = 3;
+
+/// Errors that say the provider could not be reached, not that it refused the audio.
+pub fn is_network_error(error: &STTError) -> bool {
+    matches!(error, STTError::NetworkError(_) | STTError::TimeoutError)
+}
+
+/// Shared offline switch: the manual toggle plus the automatic failure count.
+#[derive(Debug, Clone, Default)]
+pub struct OfflineMode {
+    inner: Arc<OfflineModeInner>,
+}
+
+#[derive(Debug, Default)]
+struct OfflineModeInner {
+    manual: AtomicBool,
+    network_failures: AtomicU32,
+}
+
+impl OfflineMode {
+    pub fn new(manual: bool) -> Self {
+        let mode = Self::default();
+        mode.set_manual(manual);
+        mode
+    }
+
+    pub fn set_manual(&self, enabled: bool) {
+        self.inner.manual.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_manual(&self) -> bool {
+        self.inner.manual.load(Ordering::Relaxed)
+    }
+
+    /// True when turned on by hand or detected from repeated network failures.
+    pub fn is_offline(&self) -> bool {
+        self.is_manual() || self.is_detected()
+    }
+
+    /// True when the latest cloud attempts failed on the network, even if not yet enough of
+    /// them to switch offline mode on.
+    pub fn has_network_failures(&self) -> bool {
+        self.inner.network_failures.load(Ordering::Relaxed) > 0
+    }
+
+    fn is_detected(&self) -> bool {
+        self.inner.network_failures.load(Ordering::Relaxed) >= NETWORK_FAILURES_BEFORE_OFFLINE
+    }
+
+    /// Count a transcription that failed only on the network. Returns true when this
+    /// failure switched offline mode on.
+    pub fn record_network_failure(&self) -> bool {
+        let failures = self.inner.network_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        failures == NETWORK_FAILURES_BEFORE_OFFLINE
+    }
+
+    /// A cloud provider answered. Returns true when this ended detected offline mode;
+    /// the manual toggle stays as the user set it.
+    pub fn mark_online(&self) -> bool {
+        self.inner.network_failures.swap(0, Ordering::Relaxed) >= NETWORK_FAILURES_BEFORE_OFFLINE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_after_consecutive_network_failures() {
+        let mode = OfflineMode::default();
+        assert!(!mode.record_network_failure());
+        assert!(mode.has_network_failures());
+        assert!(!mode.record_network_failure());
+        assert!(!mode.is_offline());
+        assert!(mode.record_network_failure());
+        assert!(mode.is_offline());
+        assert!(!mode.record_network_failure());
+
+        assert!(mode.mark_online());
+        assert!(!mode.is_offline());
+        assert!(!mode.has_network_failures());
+        assert!(!mode.mark_online());
+    }
+
+    #[test]
+    fn test_manual_toggle_survives_reconnect() {
+        let mode = OfflineMode::new(true);
+        assert!(mode.clone().is_offline());
+        mode.mark_online();
+        assert!(mode.is_offline());
+        mode.set_manual(false);
+        assert!(!mode.is_offline());
+    }
+}
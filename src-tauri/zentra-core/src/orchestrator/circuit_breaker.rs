@@ -0,0 +1,181 @@
+﻿use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open { tripped_at: Instant },
+    HalfOpen,
+}
+
+/// A change worth telling the user about. Half-open probes are not reported: the breaker
+/// only counts as recovered once a request succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Opened,
+    Closed,
+}
+
+/// A provider's breaker opened or closed, as reported to a [`CircuitObserver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitEvent {
+    Opened {
+        provider: String,
+        cooldown_secs: u64,
+        recent_errors: Vec<String>,
+    },
+    Closed {
+        provider: String,
+    },
+}
+
+/// Told about each breaker transition once; rejected requests while open are not reported.
+pub trait CircuitObserver: Send + Sync {
+    fn circuit_changed(&self, event: CircuitEvent);
+}
+
+pub struct CircuitBreaker {
+    state: CircuitState,
+    failure_count: u8,
+    /// Failures since the breaker was last closed, newest last
+    recent_errors: VecDeque<String>,
+    last_failure_time: Option<Instant>,
+    trip_threshold: u8,
+    trip_window: Duration,
+    cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            failure_count: 0,
+            recent_errors: VecDeque::new(),
+            last_failure_time: None,
+            trip_threshold: 3,
+            trip_window: Duration::from_secs(300),
+            cooldown: Duration::from_secs(600),
+        }
+    }
+
+    pub fn is_request_allowed(&mut self) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open { tripped_at } => {
+                if tripped_at.elapsed() >= self.cooldown {
+                    self.state = CircuitState::HalfOpen;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state == CircuitState::Closed
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// The failures that led up to the current state, oldest first.
+    pub fn recent_errors(&self) -> Vec<String> {
+        self.recent_errors.iter().cloned().collect()
+    }
+
+    /// `Some(Closed)` when this success ends an open or half-open state.
+    pub fn record_success(&mut self) -> Option<Transition> {
+        let was_closed = self.is_closed();
+        self.failure_count = 0;
+        self.recent_errors.clear();
+        self.last_failure_time = None;
+        self.state = CircuitState::Closed;
+        (!was_closed).then_some(Transition::Closed)
+    }
+
+    /// Close the breaker now instead of waiting out the cooldown, e.g. after the user fixed
+    /// their network. Resets the failure count like a success would.
+    pub fn force_close(&mut self) -> Option<Transition> {
+        self.record_success()
+    }
+
+    /// `Some(Opened)` when this failure trips a closed breaker. A failed half-open probe
+    /// re-opens the breaker without reporting it again.
+    pub fn record_failure(&mut self, error: &str) -> Option<Transition> {
+        let now = Instant::now();
+        let was_closed = self.is_closed();
+
+        self.recent_errors.push_back(error.to_string());
+        while self.recent_errors.len() > usize::from(self.trip_threshold) {
+            self.recent_errors.pop_front();
+        }
+
+        if let Some(last_fail) = self.last_failure_time {
+            if now.duration_since(last_fail) > self.trip_window {
+                self.failure_count = 1;
+            } else {
+                self.failure_count = self.failure_count.saturating_add(1);
+            }
+        } else {
+            self.failure_count = 1;
+        }
+
+        self.last_failure_time = Some(now);
+
+        if self.failure_count >= self.trip_threshold {
+            self.state = CircuitState::Open { tripped_at: now };
+            tracing::warn!("Circuit breaker tripped, failure_count={}", self.failure_count);
+            return was_closed.then_some(Transition::Opened);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_transition_is_reported_once() {
+        let mut breaker = CircuitBreaker::new();
+        assert_eq!(breaker.record_failure("timeout"), None);
+        assert_eq!(breaker.record_failure("timeout"), None);
+        assert_eq!(
+            breaker.record_failure("rate limit"),
+            Some(Transition::Opened)
+        );
+        assert_eq!(
+            breaker.recent_errors(),
+            vec!["timeout", "timeout", "rate limit"]
+        );
+
+        // Still open: in-flight requests that fail late do not trip it again.
+        assert_eq!(breaker.record_failure("timeout"), None);
+        assert!(!breaker.is_request_allowed());
+
+        // A failed half-open probe re-opens quietly.
+        breaker.state = CircuitState::HalfOpen;
+        assert_eq!(breaker.record_failure("timeout"), None);
+        assert!(!breaker.is_closed());
+
+        assert_eq!(breaker.force_close(), Some(Transition::Closed));
+        assert!(breaker.is_request_allowed());
+        assert!(breaker.recent_errors().is_empty());
+        assert_eq!(breaker.record_success(), None);
+        assert_eq!(breaker.force_close(), None);
+
+        // Counters were reset: it takes three new failures to trip again.
+        assert_eq!(breaker.record_failure("timeout"), None);
+        assert_eq!(breaker.record_failure("timeout"), None);
+        assert_eq!(breaker.record_failure("timeout"), Some(Transition::Opened));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::audio::AudioBuffer;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Sample rate the audio is normalized to before hashing, so the same speech captured
+/// as 48 kHz stereo or 16 kHz mono yields the same key.
+const FINGERPRINT_SAMPLE_RATE: u32 = 16_000;
+
+/// Upper bound on tracked requests; past it, calls simply skip deduplication.
+pub const DEFAULT_IN_FLIGHT_CAPACITY: usize = 64;
+
+/// Stable key for a transcription request: normalized PCM plus everything that changes
+/// the provider's answer (model, language).
+pub fn fingerprint(audio: &AudioBuffer, context: &str) -> u64 {
+    let mut hasher = Xxh3::new();
+    for sample in audio.to_mono(FINGERPRINT_SAMPLE_RATE) {
+        hasher.update(&sample.to_le_bytes());
+    }
+    hasher.update(context.as_bytes());
+    hasher.digest()
+}
+
+/// Requests currently being transcribed, keyed by fingerprint. Entries are removed as soon
+/// as the last caller waiting on them returns (or is cancelled).
+pub struct InFlight<T> {
+    entries: Mutex<HashMap<u64, Entry<T>>>,
+    capacity: usize,
+}
+
+struct Entry<T> {
+    cell: Arc<OnceCell<T>>,
+    joiners: usize,
+}
+
+impl<T> InFlight<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Join the request for `key`, creating it if needed. Returns `None` when the map is
+    /// full and the caller should run its request on its own.
+    pub fn join(&self, key: u64) -> Option<InFlightGuard<'_, T>> {
+        let mut entries = self.entries.lock().ok()?;
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            tracing::debug!("In-flight map full ({}), not deduplicating", self.capacity);
+            return None;
+        }
+        let entry = entries.entry(key).or_insert_with(|| Entry {
+            cell: Arc::new(OnceCell::new()),
+            joiners: 0,
+        });
+        entry.joiners += 1;
+        if entry.joiners > 1 {
+            tracing::info!("Joining in-flight transcription {:016x}", key);
+        }
+        Some(InFlightGuard {
+            owner: self,
+            key,
+            cell: entry.cell.clone(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map(|entries| entries.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub struct InFlightGuard<'a, T> {
+    owner: &'a InFlight<T>,
+    key: u64,
+    cell: Arc<OnceCell<T>>,
+}
+
+impl<T> InFlightGuard<'_, T> {
+    /// Shared result slot. If the caller running the request is cancelled, the next
+    /// waiter's `get_or_init` runs its own request instead.
+    pub fn cell(&self) -> &OnceCell<T> {
+        &self.cell
+    }
+}
+
+impl<T> Drop for InFlightGuard<'_, T> {
+    fn drop(&mut self) {
+        let Ok(mut entries) = self.owner.entries.lock() else {
+            return;
+        };
+        if let Some(entry) = entries.get_mut(&self.key) {
+            entry.joiners -= 1;
+            if entry.joiners == 0 {
+                entries.remove(&self.key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_ignores_capture_format() {
+        let mut mono = AudioBuffer::new(16_000, 1);
+        mono.append(&[100, 200, 300, 400]);
+        let mut stereo = AudioBuffer::new(16_000, 2);
+        stereo.append(&[100, 100, 200, 200, 300, 300, 400, 400]);
+
+        assert_eq!(fingerprint(&mono, "groq"), fingerprint(&stereo, "groq"));
+        assert_ne!(fingerprint(&mono, "groq"), fingerprint(&mono, "groq:en"));
+    }
+
+    #[test]
+    fn test_entries_are_evicted_when_last_guard_drops() {
+        let in_flight = InFlight::<u32>::new(1);
+        let first = in_flight.join(7).unwrap();
+        let second = in_flight.join(7).unwrap();
+        assert_eq!(in_flight.len(), 1);
+        assert!(in_flight.join(8).is_none(), "capacity is respected");
+
+        drop(first);
+        assert_eq!(in_flight.len(), 1);
+        drop(second);
+        assert!(in_flight.is_empty());
+    }
+}
@@ -1,8 +1,17 @@
 ﻿use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct Metrics {
     success_counts: HashMap<String, u64>,
     failure_counts: HashMap<String, u64>,
+    /// Session warm-up requests, kept out of the success rates
+    warmup_counts: HashMap<String, u64>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Metrics {
@@ -10,6 +19,7 @@ impl Metrics {
         Self {
             success_counts: HashMap::new(),
             failure_counts: HashMap::new(),
+            warmup_counts: HashMap::new(),
         }
     }
 
@@ -27,6 +37,13 @@ impl Metrics {
             .or_insert(0) += 1;
     }
 
+    pub fn record_warmup(&mut self, provider_id: &str) {
+        *self
+            .warmup_counts
+            .entry(provider_id.to_string())
+            .or_insert(0) += 1;
+    }
+
     pub fn get_success_count(&self, provider_id: &str) -> u64 {
         *self.success_counts.get(provider_id).unwrap_or(&0)
     }
@@ -35,6 +52,10 @@ impl Metrics {
         *self.failure_counts.get(provider_id).unwrap_or(&0)
     }
 
+    pub fn get_warmup_count(&self, provider_id: &str) -> u64 {
+        *self.warmup_counts.get(provider_id).unwrap_or(&0)
+    }
+
     pub fn get_success_rate(&self, provider_id: &str) -> f32 {
         let success = self.get_success_count(provider_id) as f32;
         let total = success + self.get_failure_count(provider_id) as f32;
@@ -0,0 +1,1518 @@
+﻿use crate::audio::{AudioBuffer, BufferError};
+use crate::key_health::KeyHealth;
+use crate::offline::{is_network_error, OfflineMode};
+use crate::privacy::PrivacyGuard;
+use crate::stt::{
+    LanguagePin, PreparedAudio, STTAdapter, STTError, StreamingWav, Transcript,
+    TranscriptionProgressSink,
+};
+use crate::text::repetition::collapse_repetitions;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use self::circuit_breaker::{CircuitBreaker, Transition};
+pub use self::circuit_breaker::{CircuitEvent, CircuitObserver};
+use self::idempotency::{InFlight, DEFAULT_IN_FLIGHT_CAPACITY};
+use self::metrics::Metrics;
+use self::provider_registry::build_providers;
+pub use self::provider_registry::{
+    LocalWhisper, ProviderOverrides, ProviderSettings, ProviderSetup, TranscriptionSettings,
+};
+use self::retry::RetryPolicy;
+pub use self::strategy::Strategy;
+pub use self::usage::UsageTracker;
+
+pub mod circuit_breaker;
+pub mod idempotency;
+pub mod metrics;
+pub mod provider_registry;
+pub mod retry;
+pub mod strategy;
+pub mod usage;
+
+/// Below this a transcript is more likely noise or a hallucination than speech, so it is
+/// not used even when no provider did better.
+const MIN_FALLBACK_CONFIDENCE: f32 = 0.15;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum OrchestratorError {
+    #[error("All providers failed")]
+    AllProvidersFailed(Vec<(String, STTError)>),
+
+    #[error("No providers available")]
+    NoProvidersAvailable,
+
+    #[error("Privacy mode is on and no local provider is available")]
+    NoTrustedProviders,
+
+    #[error("Offline and no local provider is available")]
+    Offline,
+
+    /// Caught before any provider was asked; every one of them would fail the same way.
+    #[error("Invalid audio: {0}")]
+    InvalidAudio(BufferError),
+}
+
+impl OrchestratorError {
+    /// True when nothing reached a provider: offline mode, or every attempt failed on the
+    /// network. The audio is still good and can be sent later.
+    pub fn is_connectivity_failure(&self) -> bool {
+        match self {
+            OrchestratorError::Offline => true,
+            OrchestratorError::AllProvidersFailed(errors) => {
+                !errors.is_empty() && errors.iter().all(|(_, error)| is_network_error(error))
+            }
+            _ => false,
+        }
+    }
+
+    /// The shortest wait a rate-limited provider asked for, once every provider failed.
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            OrchestratorError::AllProvidersFailed(errors) => errors
+                .iter()
+                .filter_map(|(_, error)| error.retry_after_secs())
+                .min(),
+            _ => None,
+        }
+    }
+}
+
+/// Concurrent requests allowed per provider unless tuned otherwise.
+pub const DEFAULT_MAX_CONCURRENT: usize = 2;
+
+pub struct ProviderConfig {
+    pub id: String,
+    pub priority: u8,
+    pub adapter: Box<dyn STTAdapter + Send + Sync>,
+    pub max_retries: u8,
+    pub timeout_secs: u64,
+    pub confidence_threshold: f32,
+    /// Runs entirely on this machine; the only kind allowed in privacy mode
+    pub local: bool,
+    /// Upper bound on in-flight requests, shared by every caller of this orchestrator
+    pub max_concurrent: usize,
+}
+
+pub struct FailoverOrchestrator {
+    providers: Vec<ProviderConfig>,
+    circuit_breakers: Mutex<HashMap<String, CircuitBreaker>>,
+    limiters: HashMap<String, Arc<Semaphore>>,
+    metrics: Mutex<Metrics>,
+    usage: UsageTracker,
+    language_pin: LanguagePin,
+    privacy: PrivacyGuard,
+    key_health: KeyHealth,
+    offline: OfflineMode,
+    strategy: Strategy,
+    collapse_repetitions: bool,
+    circuit_observer: Option<Arc<dyn CircuitObserver>>,
+    in_flight: InFlight<Result<Transcript, OrchestratorError>>,
+}
+
+impl FailoverOrchestrator {
+    pub fn new(mut providers: Vec<ProviderConfig>) -> Self {
+        providers.sort_by_key(|p| p.priority);
+
+        let mut circuit_breakers = HashMap::new();
+        let mut limiters = HashMap::new();
+        for provider in &providers {
+            circuit_breakers.insert(provider.id.clone(), CircuitBreaker::new());
+            limiters.insert(
+                provider.id.clone(),
+                Arc::new(Semaphore::new(provider.max_concurrent.max(1))),
+            );
+        }
+
+        Self {
+            providers,
+            circuit_breakers: Mutex::new(circuit_breakers),
+            limiters,
+            metrics: Mutex::new(Metrics::new()),
+            usage: UsageTracker::default(),
+            language_pin: LanguagePin::default(),
+            privacy: PrivacyGuard::default(),
+            key_health: KeyHealth::default(),
+            offline: OfflineMode::default(),
+            strategy: Strategy::default(),
+            collapse_repetitions: true,
+            circuit_observer: None,
+            in_flight: InFlight::new(DEFAULT_IN_FLIGHT_CAPACITY),
+        }
+    }
+
+    /// Share the app-wide privacy switch; it is read on every `transcribe` call.
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Authentication failures are reported here so the app can re-check the key.
+    pub fn with_key_health(mut self, key_health: KeyHealth) -> Self {
+        self.key_health = key_health;
+        self
+    }
+
+    /// Share the app-wide offline switch. Network-only failures are counted into it, and
+    /// while it is on only local providers are tried.
+    pub fn with_offline(mut self, offline: OfflineMode) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Strategy for `transcribe` calls; [`Strategy::Sequential`] unless set.
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Collapse sentences a provider repeated back to back (on by default). The collapsed
+    /// transcript loses confidence in proportion to what was removed, so a segment that was
+    /// mostly a loop falls below the threshold and the next provider is tried.
+    pub fn with_repetition_collapse(mut self, enabled: bool) -> Self {
+        self.collapse_repetitions = enabled;
+        self
+    }
+
+    /// Share the app-wide usage ledger; every provider attempt is counted into it.
+    pub fn with_usage(mut self, usage: UsageTracker) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Share the session's language pin; a pinned language is sent with every request.
+    pub fn with_language_pin(mut self, language_pin: LanguagePin) -> Self {
+        self.language_pin = language_pin;
+        self
+    }
+
+    /// Report circuit breakers opening and closing to `observer`.
+    pub fn with_circuit_observer(mut self, observer: Arc<dyn CircuitObserver>) -> Self {
+        self.circuit_observer = Some(observer);
+        self
+    }
+
+    /// Give every provider that reports progress (local engines) somewhere to send it.
+    pub fn with_progress_sink(mut self, sink: Arc<dyn TranscriptionProgressSink>) -> Self {
+        for provider in &mut self.providers {
+            provider.adapter.set_progress_sink(sink.clone());
+        }
+        self
+    }
+
+    /// Providers from the keys and engines in `setup`, tuned by the `providers` section of
+    /// the app config, walked and checked as its `transcription` section says.
+    pub fn from_config(setup: &ProviderSetup) -> Self {
+        Self::new(build_providers(setup))
+            .with_strategy(setup.transcription.strategy())
+            .with_repetition_collapse(setup.transcription.collapse_repetitions)
+    }
+
+    /// Safe to call concurrently; each provider's limiter caps the requests actually sent.
+    /// Concurrent calls for identical audio share a single request.
+    pub async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, OrchestratorError> {
+        self.transcribe_with(audio, self.strategy).await
+    }
+
+    /// Like `transcribe`, with `strategy` for this call only.
+    pub async fn transcribe_with(
+        &self,
+        audio: &AudioBuffer,
+        strategy: Strategy,
+    ) -> Result<Transcript, OrchestratorError> {
+        audio.validate().map_err(OrchestratorError::InvalidAudio)?;
+        let key = self.fingerprint(audio);
+        match self.in_flight.join(key) {
+            Some(guard) => guard
+                .cell()
+                .get_or_init(|| self.transcribe_uncached(audio, key, strategy))
+                .await
+                .clone(),
+            None => self.transcribe_uncached(audio, key, strategy).await,
+        }
+    }
+
+    /// Count an attempt in the usage ledger, which privacy mode keeps off disk.
+    fn record_usage(&self, provider_id: &str, audio_secs: f64) {
+        if self.privacy.allows_persistence() {
+            self.usage.record(provider_id, audio_secs);
+        }
+    }
+
+    fn fingerprint(&self, audio: &AudioBuffer) -> u64 {
+        let mut context = self
+            .providers
+            .iter()
+            .map(|provider| provider.adapter.request_context())
+            .collect::<Vec<_>>()
+            .join("|");
+        if self.privacy.is_enabled() {
+            context.push_str("|private");
+        }
+        if self.offline.is_offline() {
+            context.push_str("|offline");
+        }
+        if let Some(language) = self.language_pin.get() {
+            context.push_str("|language:");
+            context.push_str(&language);
+        }
+        idempotency::fingerprint(audio, &context)
+    }
+
+    #[tracing::instrument(name = "transcribe", skip_all, fields(strategy = ?strategy))]
+    async fn transcribe_uncached(
+        &self,
+        audio: &AudioBuffer,
+        key: u64,
+        strategy: Strategy,
+    ) -> Result<Transcript, OrchestratorError> {
+        if self.providers.is_empty() {
+            return Err(OrchestratorError::NoProvidersAvailable);
+        }
+
+        if !self
+            .providers
+            .iter()
+            .any(|provider| self.privacy.allows_provider(provider.local))
+        {
+            tracing::warn!("Privacy mode: no local provider configured, refusing cloud fallback");
+            return Err(OrchestratorError::NoTrustedProviders);
+        }
+
+        let offline = self.offline.is_offline();
+        if offline
+            && !self
+                .providers
+                .iter()
+                .any(|provider| provider.local && self.privacy.allows_provider(provider.local))
+        {
+            tracing::info!("Offline: no local provider configured");
+            return Err(OrchestratorError::Offline);
+        }
+
+        let mut candidates = Vec::new();
+        for provider in &self.providers {
+            if !self.privacy.allows_provider(provider.local) {
+                tracing::info!(
+                    "Provider {} skipped: privacy mode allows local providers only",
+                    provider.id
+                );
+                continue;
+            }
+
+            if offline && !provider.local {
+                tracing::info!("Provider {} skipped: offline", provider.id);
+                continue;
+            }
+            candidates.push(provider);
+        }
+
+        let mut all_errors = Vec::new();
+        let mut best_rejected = None;
+
+        if let Strategy::Race { providers } = strategy {
+            let racers = candidates
+                .iter()
+                .copied()
+                .filter(|provider| self.is_circuit_closed(&provider.id))
+                .take(providers)
+                .collect::<Vec<_>>();
+            if racers.len() > 1 {
+                if let Some(transcript) = self
+                    .race(&racers, audio, key, &mut all_errors, &mut best_rejected)
+                    .await
+                {
+                    return Ok(transcript);
+                }
+                candidates.retain(|provider| !racers.iter().any(|racer| racer.id == provider.id));
+            }
+        }
+
+        for provider in candidates {
+            if !self.is_request_allowed(&provider.id) {
+                tracing::warn!("Provider {} skipped: circuit breaker open", provider.id);
+                all_errors.push((
+                    provider.id.clone(),
+                    STTError::ProviderError("Circuit breaker open".to_string()),
+                ));
+                continue;
+            }
+
+            match self.run_provider(provider, audio, key).await {
+                Ok(transcript) => return Ok(transcript),
+                Err(rejection) => rejection.note(&provider.id, &mut all_errors, &mut best_rejected),
+            }
+        }
+
+        if let Some(transcript) =
+            best_rejected.filter(|transcript| transcript.confidence >= MIN_FALLBACK_CONFIDENCE)
+        {
+            tracing::warn!(
+                "No provider reached its confidence threshold, using {}'s transcript: confidence={:.2}",
+                transcript.provider,
+                transcript.confidence
+            );
+            return Ok(transcript);
+        }
+
+        tracing::error!("All providers failed: {:?}", all_errors);
+        let error = OrchestratorError::AllProvidersFailed(all_errors);
+        if error.is_connectivity_failure() && self.offline.record_network_failure() {
+            tracing::warn!("Switching to offline mode after repeated network failures");
+        }
+        Err(error)
+    }
+
+    /// Send to every racer at once and return the first transcript that meets its
+    /// provider's confidence threshold, keeping the best one that fell short in
+    /// `best_rejected`. Returning drops the other requests mid-flight;
+    /// they never reach `record_success`/`record_failure`, so cancelled racers leave no
+    /// trace in the metrics or circuit breakers.
+    async fn race(
+        &self,
+        racers: &[&ProviderConfig],
+        audio: &AudioBuffer,
+        key: u64,
+        all_errors: &mut Vec<(String, STTError)>,
+        best_rejected: &mut Option<Transcript>,
+    ) -> Option<Transcript> {
+        tracing::info!(
+            "Racing providers: {}",
+            racers
+                .iter()
+                .map(|provider| provider.id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let mut pending = FuturesUnordered::new();
+        for &provider in racers {
+            pending.push(async move { (provider, self.run_provider(provider, audio, key).await) });
+        }
+
+        while let Some((provider, result)) = pending.next().await {
+            match result {
+                Ok(transcript) => {
+                    tracing::info!(
+                        "Provider {} won the race, cancelling {} other request(s)",
+                        provider.id,
+                        pending.len()
+                    );
+                    return Some(transcript);
+                }
+                Err(rejection) => rejection.note(&provider.id, all_errors, best_rejected),
+            }
+        }
+        None
+    }
+
+    /// One provider, retries included. The outcome is recorded once the attempt finishes;
+    /// a low-confidence transcript is neither a success nor a failure: the provider
+    /// answered, the audio was just hard to hear.
+    #[tracing::instrument(name = "provider", skip_all, fields(provider = %provider.id))]
+    async fn run_provider(
+        &self,
+        provider: &ProviderConfig,
+        audio: &AudioBuffer,
+        key: u64,
+    ) -> Result<Transcript, Rejection> {
+        tracing::info!(
+            "Attempting provider: {} (priority {})",
+            provider.id,
+            provider.priority
+        );
+
+        // Encode once per provider; every retry attempt reuses the same payload.
+        let prepared = match provider.adapter.prepare(audio) {
+            Ok(prepared) => PreparedAudio {
+                idempotency_key: Some(format!("{:016x}", key)),
+                language: self.language_pin.get(),
+                ..prepared
+            },
+            Err(e) => {
+                tracing::warn!("Provider {} could not prepare audio: {:?}", provider.id, e);
+                self.record_failure(&provider.id, &e);
+                return Err(Rejection::Failed(e));
+            }
+        };
+
+        let retry_policy = RetryPolicy::new(provider.max_retries);
+        let mut attempt = 0u8;
+        let audio_secs = usage::audio_secs(audio);
+
+        loop {
+            // Billed whether or not it succeeds.
+            self.record_usage(&provider.id, audio_secs);
+            match self.try_provider(provider, audio, &prepared).await {
+                Ok(mut transcript) => {
+                    if self.collapse_repetitions {
+                        collapse_transcript(&mut transcript);
+                    }
+                    if transcript.confidence >= provider.confidence_threshold {
+                        tracing::info!(
+                            "Provider {} succeeded: confidence={:.2}, text_len={}",
+                            provider.id,
+                            transcript.confidence,
+                            transcript.text.len()
+                        );
+
+                        self.record_success(&provider.id);
+                        if !provider.local && self.offline.mark_online() {
+                            tracing::info!("Back online: {} answered", provider.id);
+                        }
+                        return Ok(transcript);
+                    }
+
+                    tracing::warn!(
+                        "Provider {} returned low confidence: {:.2} < {:.2}",
+                        provider.id,
+                        transcript.confidence,
+                        provider.confidence_threshold
+                    );
+
+                    return Err(Rejection::LowConfidence(transcript));
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Provider {} attempt {}/{} failed: {:?}",
+                        provider.id,
+                        attempt + 1,
+                        provider.max_retries + 1,
+                        e
+                    );
+
+                    if retry_policy.should_retry(attempt, &e) {
+                        retry_policy.wait_before_retry(attempt, &e).await;
+                        attempt += 1;
+                        continue;
+                    }
+
+                    self.record_failure(&provider.id, &e);
+                    if matches!(e, STTError::AuthenticationError) {
+                        self.key_health.request_check();
+                    }
+                    return Err(Rejection::Failed(e));
+                }
+            }
+        }
+    }
+
+    /// Connectivity is back: leave offline mode and close the circuit breakers, which the
+    /// outage tripped along the way. Returns true when offline mode had been detected.
+    pub fn reconnected(&self) -> bool {
+        for provider in &self.providers {
+            self.update_breaker(&provider.id, CircuitBreaker::record_success);
+        }
+        self.offline.mark_online()
+    }
+
+    /// Close `provider_id`'s breaker without waiting for the cooldown. False for an
+    /// unknown provider.
+    pub fn force_close_circuit(&self, provider_id: &str) -> bool {
+        let found = self.update_breaker(provider_id, CircuitBreaker::force_close);
+        if found {
+            tracing::info!("Circuit breaker for {} closed by hand", provider_id);
+        }
+        found
+    }
+
+    /// Send `audio` once to the first cloud provider with a closed circuit and a free slot,
+    /// to open its connection and hear the language before the session's first segment.
+    /// Nothing is retried or failed over, and the attempt is counted apart from
+    /// transcriptions, never in success rates or circuit breakers; its audio does count as
+    /// usage. `None` in privacy or offline mode, when no provider is ready, or on failure.
+    pub async fn warm_up(&self, audio: &AudioBuffer) -> Option<Transcript> {
+        if self.privacy.is_enabled() || self.offline.is_offline() || audio.validate().is_err() {
+            return None;
+        }
+        let provider = self
+            .providers
+            .iter()
+            .find(|provider| !provider.local && self.is_circuit_closed(&provider.id))?;
+        // Low priority: never queue in front of a real request.
+        let _permit = match self.limiters.get(&provider.id) {
+            Some(limiter) => Some(limiter.clone().try_acquire_owned().ok()?),
+            None => None,
+        };
+        let prepared = provider
+            .adapter
+            .prepare(audio)
+            .map_err(|e| tracing::debug!("Warm-up audio not prepared: {:?}", e))
+            .ok()?;
+
+        self.record_usage(&provider.id, usage::audio_secs(audio));
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.record_warmup(&provider.id);
+        }
+        let timeout = Duration::from_secs(provider.timeout_secs);
+        match tokio::time::timeout(
+            timeout,
+            provider.adapter.transcribe_prepared(audio, &prepared),
+        )
+        .await
+        {
+            Ok(Ok(transcript)) => {
+                tracing::info!(
+                    "Warm-up through {} done (language {})",
+                    provider.id,
+                    transcript.language.as_deref().unwrap_or("unknown")
+                );
+                Some(transcript)
+            }
+            Ok(Err(e)) => {
+                tracing::info!("Warm-up through {} failed: {:?}", provider.id, e);
+                None
+            }
+            Err(_) => {
+                tracing::info!("Warm-up through {} timed out", provider.id);
+                None
+            }
+        }
+    }
+
+    /// Whether [`Self::transcribe_streamed`] has a provider to go to right now.
+    pub fn can_stream(&self) -> bool {
+        self.streaming_provider()
+            .is_some_and(|provider| self.is_circuit_closed(&provider.id))
+    }
+
+    /// Transcribe audio that is still being recorded through the first provider that takes
+    /// a progressive upload. Privacy and offline mode, the provider's circuit breaker,
+    /// concurrency limit and timeout, the language pin and the confidence threshold apply
+    /// as in [`Self::transcribe`]. A stream can't be replayed, so there is no retry or
+    /// failover: on an error the caller sends the audio again through `transcribe`.
+    #[tracing::instrument(name = "transcribe_streamed", skip_all)]
+    pub async fn transcribe_streamed(&self, upload: StreamingWav) -> Result<Transcript, STTError> {
+        let provider = self.streaming_provider().ok_or_else(|| {
+            STTError::ProviderError("No provider available for a streaming upload".to_string())
+        })?;
+        if !self.is_request_allowed(&provider.id) {
+            return Err(STTError::ProviderError("Circuit breaker open".to_string()));
+        }
+        let _permit = match self.limiters.get(&provider.id) {
+            Some(limiter) => Some(
+                limiter
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| STTError::ProviderError(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        tracing::info!("Streaming to provider {}", provider.id);
+        self.record_usage(&provider.id, f64::from(upload.duration_secs));
+        // The request stays open while the rest of the audio is recorded.
+        let timeout = Duration::from_secs(provider.timeout_secs)
+            + Duration::from_secs_f32(upload.duration_secs.max(0.0));
+        let result = match tokio::time::timeout(
+            timeout,
+            provider
+                .adapter
+                .transcribe_streaming(upload, self.language_pin.get()),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(STTError::TimeoutError),
+        };
+
+        match result {
+            Ok(mut transcript) => {
+                if self.collapse_repetitions {
+                    collapse_transcript(&mut transcript);
+                }
+                if transcript.confidence < provider.confidence_threshold {
+                    tracing::warn!(
+                        "Provider {} returned low confidence on a stream: {:.2} < {:.2}",
+                        provider.id,
+                        transcript.confidence,
+                        provider.confidence_threshold
+                    );
+                    return Err(STTError::ProviderError("Low confidence".to_string()));
+                }
+                self.record_success(&provider.id);
+                if !provider.local && self.offline.mark_online() {
+                    tracing::info!("Back online: {} answered", provider.id);
+                }
+                Ok(transcript)
+            }
+            Err(e) => {
+                tracing::warn!("Streaming upload to {} failed: {:?}", provider.id, e);
+                self.record_failure(&provider.id, &e);
+                if matches!(e, STTError::AuthenticationError) {
+                    self.key_health.request_check();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// The first provider that takes a streaming upload and that privacy and offline mode
+    /// allow.
+    fn streaming_provider(&self) -> Option<&ProviderConfig> {
+        let offline = self.offline.is_offline();
+        self.providers.iter().find(|provider| {
+            provider.adapter.streams()
+                && self.privacy.allows_provider(provider.local)
+                && (provider.local || !offline)
+        })
+    }
+
+    pub fn get_metrics(&self) -> Metrics {
+        self.metrics
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_else(|_| Metrics::new())
+    }
+
+    fn is_request_allowed(&self, provider_id: &str) -> bool {
+        let Ok(mut breakers) = self.circuit_breakers.lock() else {
+            return true;
+        };
+        breakers
+            .get_mut(provider_id)
+            .expect("Circuit breaker missing")
+            .is_request_allowed()
+    }
+
+    /// Only closed breakers count as healthy for a race; a half-open provider waits for
+    /// its turn in the sequential fallback.
+    fn is_circuit_closed(&self, provider_id: &str) -> bool {
+        let Ok(breakers) = self.circuit_breakers.lock() else {
+            return true;
+        };
+        breakers
+            .get(provider_id)
+            .is_some_and(CircuitBreaker::is_closed)
+    }
+
+    fn record_success(&self, provider_id: &str) {
+        self.update_breaker(provider_id, CircuitBreaker::record_success);
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.record_success(provider_id);
+        }
+    }
+
+    fn record_failure(&self, provider_id: &str, error: &STTError) {
+        let error = error.to_string();
+        self.update_breaker(provider_id, |cb| cb.record_failure(&error));
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.record_failure(provider_id);
+        }
+    }
+
+    /// Apply `update` to a provider's breaker and report the transition, if any, outside
+    /// the lock. False when the provider has no breaker.
+    fn update_breaker(
+        &self,
+        provider_id: &str,
+        update: impl FnOnce(&mut CircuitBreaker) -> Option<Transition>,
+    ) -> bool {
+        let event = {
+            let Ok(mut breakers) = self.circuit_breakers.lock() else {
+                return false;
+            };
+            let Some(cb) = breakers.get_mut(provider_id) else {
+                return false;
+            };
+            update(cb).map(|transition| match transition {
+                Transition::Opened => CircuitEvent::Opened {
+                    provider: provider_id.to_string(),
+                    cooldown_secs: cb.cooldown().as_secs(),
+                    recent_errors: cb.recent_errors(),
+                },
+                Transition::Closed => CircuitEvent::Closed {
+                    provider: provider_id.to_string(),
+                },
+            })
+        };
+        if let (Some(event), Some(observer)) = (event, &self.circuit_observer) {
+            observer.circuit_changed(event);
+        }
+        true
+    }
+
+    async fn try_provider(
+        &self,
+        provider: &ProviderConfig,
+        audio: &AudioBuffer,
+        prepared: &PreparedAudio,
+    ) -> Result<Transcript, STTError> {
+        // Waiting for a slot does not count against the request timeout.
+        let _permit = match self.limiters.get(&provider.id) {
+            Some(limiter) => Some(
+                limiter
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| STTError::ProviderError(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let timeout = Duration::from_secs(provider.timeout_secs);
+
+        match tokio::time::timeout(timeout, provider.adapter.transcribe_prepared(audio, prepared))
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(STTError::TimeoutError),
+        }
+    }
+}
+
+/// Why a provider's attempt gave no usable transcript.
+enum Rejection {
+    Failed(STTError),
+    /// Under the provider's confidence threshold; kept in case no provider does better.
+    LowConfidence(Transcript),
+}
+
+impl Rejection {
+    fn note(
+        self,
+        provider_id: &str,
+        errors: &mut Vec<(String, STTError)>,
+        best: &mut Option<Transcript>,
+    ) {
+        let error = match self {
+            Rejection::Failed(error) => error,
+            Rejection::LowConfidence(transcript) => {
+                if !best
+                    .as_ref()
+                    .is_some_and(|best| best.confidence >= transcript.confidence)
+                {
+                    *best = Some(transcript);
+                }
+                STTError::ProviderError("Low confidence".to_string())
+            }
+        };
+        errors.push((provider_id.to_string(), error));
+    }
+}
+
+/// Drop repeated sentences from `transcript` and scale its confidence by what is left.
+fn collapse_transcript(transcript: &mut Transcript) {
+    let Some(collapsed) = collapse_repetitions(&transcript.text) else {
+        return;
+    };
+    tracing::warn!(
+        "{} repeated itself: removed {:.0}% of the transcript",
+        transcript.provider,
+        collapsed.removed_ratio * 100.0
+    );
+    transcript.text = collapsed.text;
+    transcript.confidence *= 1.0 - collapsed.removed_ratio;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::NETWORK_FAILURES_BEFORE_OFFLINE;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    struct CountingAdapter {
+        encodes: Arc<AtomicUsize>,
+        sends: Arc<AtomicUsize>,
+        failures_before_success: usize,
+    }
+
+    #[async_trait]
+    impl STTAdapter for CountingAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            let prepared = self.prepare(audio)?;
+            self.transcribe_prepared(audio, &prepared).await
+        }
+
+        fn prepare(&self, audio: &AudioBuffer) -> Result<PreparedAudio, STTError> {
+            self.encodes.fetch_add(1, Ordering::SeqCst);
+            Ok(PreparedAudio {
+                bytes: vec![1, 2, 3],
+                duration_secs: audio.duration_secs,
+                idempotency_key: None,
+                language: None,
+            })
+        }
+
+        async fn transcribe_prepared(
+            &self,
+            _audio: &AudioBuffer,
+            prepared: &PreparedAudio,
+        ) -> Result<Transcript, STTError> {
+            assert_eq!(prepared.bytes, vec![1, 2, 3]);
+            let attempt = self.sends.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.failures_before_success {
+                return Err(STTError::TimeoutError);
+            }
+            Ok(Transcript {
+                text: "ok".to_string(),
+                confidence: 0.9,
+                language: None,
+                duration_secs: prepared.duration_secs,
+                provider: "mock".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    fn test_audio() -> AudioBuffer {
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+        audio
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_reuse_prepared_payload() {
+        let encodes = Arc::new(AtomicUsize::new(0));
+        let sends = Arc::new(AtomicUsize::new(0));
+        let usage = UsageTracker::default();
+        let orchestrator = FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "mock".to_string(),
+            priority: 1,
+            adapter: Box::new(CountingAdapter {
+                encodes: encodes.clone(),
+                sends: sends.clone(),
+                failures_before_success: 2,
+            }),
+            max_retries: 2,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }])
+        .with_usage(usage.clone());
+
+        let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+
+        assert_eq!(transcript.text, "ok");
+        assert_eq!(sends.load(Ordering::SeqCst), 3);
+        assert_eq!(encodes.load(Ordering::SeqCst), 1);
+        // Each attempt was sent, so each is counted.
+        let stats = usage.stats(&Default::default());
+        assert_eq!(stats.providers[0].requests, 3);
+        assert_eq!(stats.providers[0].audio_secs, 3.0);
+    }
+
+    fn counting_provider(
+        id: &str,
+        priority: u8,
+        local: bool,
+        sends: Arc<AtomicUsize>,
+    ) -> ProviderConfig {
+        ProviderConfig {
+            id: id.to_string(),
+            priority,
+            adapter: Box::new(CountingAdapter {
+                encodes: Arc::new(AtomicUsize::new(0)),
+                sends,
+                failures_before_success: 0,
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_privacy_mode_skips_cloud_providers_at_use_time() {
+        let cloud_sends = Arc::new(AtomicUsize::new(0));
+        let local_sends = Arc::new(AtomicUsize::new(0));
+        let privacy = PrivacyGuard::new(true);
+        let usage = UsageTracker::default();
+        let orchestrator = FailoverOrchestrator::new(vec![
+            counting_provider("cloud", 1, false, cloud_sends.clone()),
+            counting_provider("local", 2, true, local_sends.clone()),
+        ])
+        .with_privacy(privacy.clone())
+        .with_usage(usage.clone());
+
+        orchestrator.transcribe(&test_audio()).await.unwrap();
+        orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(cloud_sends.load(Ordering::SeqCst), 0);
+        assert_eq!(local_sends.load(Ordering::SeqCst), 2);
+        // Nothing goes to the usage log either.
+        assert!(usage.stats(&Default::default()).providers.is_empty());
+
+        // Toggling takes effect without rebuilding the orchestrator.
+        privacy.set_enabled(false);
+        orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(cloud_sends.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_privacy_mode_without_local_provider_fails_closed() {
+        let cloud_sends = Arc::new(AtomicUsize::new(0));
+        let orchestrator = FailoverOrchestrator::new(vec![counting_provider(
+            "cloud",
+            1,
+            false,
+            cloud_sends.clone(),
+        )])
+        .with_privacy(PrivacyGuard::new(true));
+
+        let result = orchestrator.transcribe(&test_audio()).await;
+        assert!(matches!(result, Err(OrchestratorError::NoTrustedProviders)));
+        assert_eq!(cloud_sends.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_audio_never_reaches_a_provider() {
+        let sends = Arc::new(AtomicUsize::new(0));
+        let orchestrator =
+            FailoverOrchestrator::new(vec![counting_provider("cloud", 1, false, sends.clone())]);
+
+        let mut audio = test_audio();
+        audio.channels = 0;
+        let result = orchestrator.transcribe(&audio).await;
+        assert!(matches!(
+            result,
+            Err(OrchestratorError::InvalidAudio(BufferError::ZeroChannels))
+        ));
+        assert_eq!(sends.load(Ordering::SeqCst), 0);
+        assert!(orchestrator.is_circuit_closed("cloud"));
+    }
+
+    /// Fails with a network error until `online` is set.
+    struct FlakyNetworkAdapter {
+        online: Arc<AtomicBool>,
+        sends: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for FlakyNetworkAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            self.sends.fetch_add(1, Ordering::SeqCst);
+            if !self.online.load(Ordering::SeqCst) {
+                return Err(STTError::NetworkError("connection refused".to_string()));
+            }
+            Ok(Transcript {
+                text: "back".to_string(),
+                confidence: 0.9,
+                duration_secs: audio.duration_secs,
+                provider: "flaky".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "flaky"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_network_failures_switch_to_offline_until_a_provider_answers() {
+        let online = Arc::new(AtomicBool::new(false));
+        let sends = Arc::new(AtomicUsize::new(0));
+        let offline = OfflineMode::default();
+        let orchestrator = FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "flaky".to_string(),
+            priority: 1,
+            adapter: Box::new(FlakyNetworkAdapter {
+                online: online.clone(),
+                sends: sends.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }])
+        .with_offline(offline.clone());
+
+        for _ in 0..NETWORK_FAILURES_BEFORE_OFFLINE {
+            let error = orchestrator.transcribe(&test_audio()).await.unwrap_err();
+            assert!(error.is_connectivity_failure());
+        }
+        assert!(offline.is_offline());
+
+        // Offline: the cloud provider is not even tried.
+        let error = orchestrator.transcribe(&test_audio()).await.unwrap_err();
+        assert!(matches!(error, OrchestratorError::Offline));
+        assert_eq!(sends.load(Ordering::SeqCst), 3);
+
+        // The outage also tripped the circuit breaker; reconnecting closes it.
+        online.store(true, Ordering::SeqCst);
+        assert!(orchestrator.reconnected());
+        let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(transcript.text, "back");
+        assert!(!offline.is_offline());
+    }
+
+    struct SlowAdapter {
+        in_flight: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for SlowAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(current, Ordering::SeqCst);
+            // Latency varies per request so completions interleave.
+            let latency_ms = 50 + (audio.samples.len() as u64 % 7) * 30;
+            tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(Transcript {
+                text: audio.samples.len().to_string(),
+                confidence: 0.9,
+                language: None,
+                duration_secs: audio.duration_secs,
+                provider: "slow".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_requests_respect_provider_limit() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let orchestrator = Arc::new(FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "slow".to_string(),
+            priority: 1,
+            adapter: Box::new(SlowAdapter {
+                in_flight: in_flight.clone(),
+                peak: peak.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: 2,
+        }]));
+
+        let tasks = (1..=5)
+            .map(|idx| {
+                let orchestrator = orchestrator.clone();
+                tokio::spawn(async move {
+                    let mut audio = AudioBuffer::new(16_000, 1);
+                    audio.append(&vec![100i16; 1_000 + idx]);
+                    orchestrator.transcribe(&audio).await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+        assert_eq!(orchestrator.get_metrics().get_success_count("slow"), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_identical_concurrent_requests_share_one_call() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let orchestrator = FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "slow".to_string(),
+            priority: 1,
+            adapter: Box::new(SlowAdapter {
+                in_flight: in_flight.clone(),
+                peak: peak.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: 2,
+        }]);
+
+        let audio = test_audio();
+        let retry = audio.clone();
+        let (first, second) = tokio::join!(
+            orchestrator.transcribe(&audio),
+            orchestrator.transcribe(&retry)
+        );
+
+        assert_eq!(first.unwrap().text, second.unwrap().text);
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+        assert_eq!(orchestrator.get_metrics().get_success_count("slow"), 1);
+        assert!(orchestrator.in_flight.is_empty());
+
+        // Completed requests are not cached: a later retry goes out again.
+        orchestrator.transcribe(&audio).await.unwrap();
+        assert_eq!(orchestrator.get_metrics().get_success_count("slow"), 2);
+    }
+
+    /// Answers with `confidence` after `latency_ms`, counting requests that were sent.
+    struct DelayedAdapter {
+        latency_ms: u64,
+        confidence: f32,
+        text: &'static str,
+        sends: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for DelayedAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            self.sends.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(self.latency_ms)).await;
+            Ok(Transcript {
+                text: self.text.to_string(),
+                confidence: self.confidence,
+                duration_secs: audio.duration_secs,
+                provider: self.text.to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            self.text
+        }
+    }
+
+    fn delayed_provider(
+        id: &'static str,
+        priority: u8,
+        latency_ms: u64,
+        confidence: f32,
+        sends: Arc<AtomicUsize>,
+    ) -> ProviderConfig {
+        ProviderConfig {
+            id: id.to_string(),
+            priority,
+            adapter: Box::new(DelayedAdapter {
+                latency_ms,
+                confidence,
+                text: id,
+                sends,
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_race_returns_the_fastest_valid_transcript() {
+        let slow_sends = Arc::new(AtomicUsize::new(0));
+        let fast_sends = Arc::new(AtomicUsize::new(0));
+        let orchestrator = FailoverOrchestrator::new(vec![
+            delayed_provider("slow", 1, 5_000, 0.9, slow_sends.clone()),
+            delayed_provider("fast", 2, 200, 0.9, fast_sends.clone()),
+        ]);
+
+        // Sequential by default: the first provider is waited on.
+        let started = tokio::time::Instant::now();
+        let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(transcript.text, "slow");
+        assert_eq!(started.elapsed(), Duration::from_millis(5_000));
+        assert_eq!(fast_sends.load(Ordering::SeqCst), 0);
+
+        let started = tokio::time::Instant::now();
+        let transcript = orchestrator
+            .transcribe_with(&test_audio(), Strategy::Race { providers: 2 })
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "fast");
+        assert_eq!(started.elapsed(), Duration::from_millis(200));
+        assert_eq!(slow_sends.load(Ordering::SeqCst), 2);
+
+        // The cancelled request is not counted either way.
+        let metrics = orchestrator.get_metrics();
+        assert_eq!(metrics.get_success_count("fast"), 1);
+        assert_eq!(metrics.get_success_count("slow"), 1);
+        assert_eq!(metrics.get_failure_count("slow"), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_race_skips_a_fast_low_confidence_transcript() {
+        let sends = Arc::new(AtomicUsize::new(0));
+        let orchestrator = FailoverOrchestrator::new(vec![
+            delayed_provider("sloppy", 1, 100, 0.3, sends.clone()),
+            delayed_provider("careful", 2, 800, 0.9, sends.clone()),
+            delayed_provider("spare", 3, 50, 0.9, sends.clone()),
+        ])
+        .with_strategy(Strategy::Race { providers: 2 });
+
+        let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(transcript.text, "careful");
+        // Only the top two raced.
+        assert_eq!(sends.load(Ordering::SeqCst), 2);
+        let metrics = orchestrator.get_metrics();
+        assert_eq!(metrics.get_failure_count("sloppy"), 0);
+        assert_eq!(metrics.get_success_count("careful"), 1);
+        assert_eq!(metrics.get_success_count("spare"), 0);
+    }
+
+    /// Always answers with `text` at 0.9 confidence.
+    struct FixedAdapter {
+        text: &'static str,
+    }
+
+    #[async_trait]
+    impl STTAdapter for FixedAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            Ok(Transcript {
+                text: self.text.to_string(),
+                confidence: 0.9,
+                duration_secs: audio.duration_secs,
+                provider: "fixed".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "fixed"
+        }
+    }
+
+    fn fixed_provider(id: &str, priority: u8, text: &'static str) -> ProviderConfig {
+        ProviderConfig {
+            id: id.to_string(),
+            priority,
+            adapter: Box::new(FixedAdapter { text }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repetition_loop_falls_through_to_the_next_provider() {
+        const LOOP: &str = "Obrigado por assistir. Obrigado por assistir. Obrigado por assistir.";
+        let providers = || {
+            vec![
+                fixed_provider("looping", 1, LOOP),
+                fixed_provider("steady", 2, "Reunião às dez."),
+            ]
+        };
+
+        let orchestrator = FailoverOrchestrator::new(providers());
+        let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(transcript.text, "Reunião às dez.");
+        assert_eq!(orchestrator.get_metrics().get_failure_count("looping"), 0);
+
+        let orchestrator = FailoverOrchestrator::new(providers()).with_repetition_collapse(false);
+        assert_eq!(
+            orchestrator.transcribe(&test_audio()).await.unwrap().text,
+            LOOP
+        );
+
+        // A single repeat in a longer transcript is cleaned up and still accepted.
+        let orchestrator = FailoverOrchestrator::new(vec![fixed_provider(
+            "mostly_fine",
+            1,
+            "Primeiro ponto da pauta. Segundo ponto da pauta. Terceiro ponto da pauta fica \
+             para depois. Quarto ponto: orçamento do trimestre que vem. Quarto ponto: \
+             orçamento do trimestre que vem. Quinto ponto, contratações.",
+        )]);
+        let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+        assert_eq!(transcript.text.matches("Quarto ponto").count(), 1);
+        assert!(transcript.confidence < 0.9 && transcript.confidence >= 0.7);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_low_confidence_is_used_when_nothing_does_better() {
+        let sends = Arc::new(AtomicUsize::new(0));
+        let orchestrator = FailoverOrchestrator::new(vec![
+            delayed_provider("muffled", 1, 10, 0.4, sends.clone()),
+            delayed_provider("faint", 2, 10, 0.5, sends.clone()),
+            delayed_provider("static", 3, 10, 0.1, sends.clone()),
+        ]);
+
+        // Hard audio is not the providers' fault: every one stays available.
+        for _ in 0..5 {
+            let transcript = orchestrator.transcribe(&test_audio()).await.unwrap();
+            assert_eq!(transcript.text, "faint");
+            assert_eq!(transcript.confidence, 0.5);
+        }
+        assert_eq!(sends.load(Ordering::SeqCst), 15);
+        let metrics = orchestrator.get_metrics();
+        for id in ["muffled", "faint", "static"] {
+            assert!(orchestrator.is_circuit_closed(id));
+            assert_eq!(metrics.get_failure_count(id), 0);
+        }
+
+        // Noise is not worth returning.
+        let orchestrator =
+            FailoverOrchestrator::new(vec![delayed_provider("static", 1, 10, 0.1, sends.clone())]);
+        let Err(OrchestratorError::AllProvidersFailed(errors)) =
+            orchestrator.transcribe(&test_audio()).await
+        else {
+            panic!("expected the noise to be rejected");
+        };
+        assert!(matches!(&errors[0].1, STTError::ProviderError(msg) if msg == "Low confidence"));
+    }
+
+    /// Takes streaming uploads and answers with the language it was asked for, or fails.
+    struct StreamingAdapter {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl STTAdapter for StreamingAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            Ok(Transcript {
+                text: "buffered".to_string(),
+                confidence: 0.9,
+                duration_secs: audio.duration_secs,
+                ..Default::default()
+            })
+        }
+
+        fn streams(&self) -> bool {
+            true
+        }
+
+        async fn transcribe_streaming(
+            &self,
+            upload: StreamingWav,
+            language: Option<String>,
+        ) -> Result<Transcript, STTError> {
+            if self.fail {
+                return Err(STTError::NetworkError("Connection reset".to_string()));
+            }
+            Ok(Transcript {
+                text: language.unwrap_or_else(|| "auto".to_string()),
+                confidence: 0.9,
+                duration_secs: upload.duration_secs,
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "streaming"
+        }
+    }
+
+    fn streaming_provider(id: &str, priority: u8, fail: bool) -> ProviderConfig {
+        ProviderConfig {
+            adapter: Box::new(StreamingAdapter { fail }),
+            ..fixed_provider(id, priority, "")
+        }
+    }
+
+    fn upload() -> StreamingWav {
+        crate::stt::streaming_wav(16_000, 16_000, 1).1
+    }
+
+    #[tokio::test]
+    async fn test_streamed_uploads_follow_the_same_rules_as_buffered_ones() {
+        let privacy = PrivacyGuard::new(true);
+        let pin = LanguagePin::default();
+        let usage = UsageTracker::default();
+        let orchestrator = FailoverOrchestrator::new(vec![
+            fixed_provider("plain", 1, "plain"),
+            streaming_provider("streaming", 2, false),
+        ])
+        .with_privacy(privacy.clone())
+        .with_language_pin(pin.clone())
+        .with_usage(usage.clone());
+
+        // Privacy mode keeps the audio on this machine.
+        assert!(!orchestrator.can_stream());
+        assert!(orchestrator.transcribe_streamed(upload()).await.is_err());
+        assert!(usage.stats(&Default::default()).providers.is_empty());
+
+        privacy.set_enabled(false);
+        assert!(orchestrator.can_stream());
+        pin.pin("pt");
+        let transcript = orchestrator.transcribe_streamed(upload()).await.unwrap();
+        assert_eq!(transcript.text, "pt");
+        assert_eq!(orchestrator.get_metrics().get_success_count("streaming"), 1);
+        assert_eq!(
+            usage.stats(&Default::default()).providers[0].audio_secs,
+            1.0
+        );
+
+        // Failures count against the provider until its breaker opens.
+        let orchestrator = FailoverOrchestrator::new(vec![streaming_provider("flaky", 1, true)]);
+        while orchestrator.can_stream() {
+            assert!(orchestrator.transcribe_streamed(upload()).await.is_err());
+        }
+        assert!(orchestrator.get_metrics().get_failure_count("flaky") > 0);
+        assert!(matches!(
+            orchestrator.transcribe_streamed(upload()).await,
+            Err(STTError::ProviderError(msg)) if msg == "Circuit breaker open"
+        ));
+    }
+
+    /// Always fails with a provider error.
+    struct BrokenAdapter;
+
+    #[async_trait]
+    impl STTAdapter for BrokenAdapter {
+        async fn transcribe(&self, _audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            Err(STTError::ProviderError("Internal error".to_string()))
+        }
+
+        fn name(&self) -> &str {
+            "broken"
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver(Mutex<Vec<CircuitEvent>>);
+
+    impl CircuitObserver for RecordingObserver {
+        fn circuit_changed(&self, event: CircuitEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl RecordingObserver {
+        fn take(&self) -> Vec<CircuitEvent> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_observer_hears_each_transition_once() {
+        let observer = Arc::new(RecordingObserver::default());
+        let mut broken = fixed_provider("broken", 1, "Bom dia.");
+        broken.adapter = Box::new(BrokenAdapter);
+        let orchestrator =
+            FailoverOrchestrator::new(vec![broken]).with_circuit_observer(observer.clone());
+
+        for _ in 0..5 {
+            assert!(orchestrator.transcribe(&test_audio()).await.is_err());
+        }
+        assert_eq!(
+            observer.take(),
+            vec![CircuitEvent::Opened {
+                provider: "broken".to_string(),
+                cooldown_secs: 600,
+                recent_errors: vec!["Provider error: Internal error".to_string(); 3],
+            }]
+        );
+        assert_eq!(orchestrator.get_metrics().get_failure_count("broken"), 3);
+
+        assert!(orchestrator.force_close_circuit("broken"));
+        assert!(orchestrator.force_close_circuit("broken"));
+        assert!(!orchestrator.force_close_circuit("missing"));
+        assert_eq!(
+            observer.take(),
+            vec![CircuitEvent::Closed {
+                provider: "broken".to_string()
+            }]
+        );
+
+        // Closed again: requests reach the provider and count towards a new trip.
+        assert!(orchestrator.transcribe(&test_audio()).await.is_err());
+        assert_eq!(orchestrator.get_metrics().get_failure_count("broken"), 4);
+        assert!(!orchestrator.reconnected());
+        assert!(observer.take().is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+use super::strategy::DEFAULT_RACE_PROVIDERS;
+use super::{ProviderConfig, Strategy, DEFAULT_MAX_CONCURRENT};
+use crate::stt::{ElevenLabsAdapter, GroqAdapter, UploadFormat, WhisperAdapter, WhisperOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Ids the registry builds providers for.
+pub const KNOWN_PROVIDERS: &[&str] = &["groq", "elevenlabs", "whisper"];
+
+/// Overrides for one provider, from the `providers` section of config.json. Missing fields
+/// keep the built-in values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProviderSettings {
+    /// `false` leaves the provider out even when its key is set
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence_threshold: Option<f32>,
+    /// Requests in flight at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+    /// Groq only: an OpenAI-compatible server (e.g. `http://localhost:8000/v1`) to send to
+    /// instead; its keys don't need Groq's `gsk_` format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Groq only: the model instead of `whisper-large-v3`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Groq only: return word and segment timings with the transcript
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timestamps: bool,
+}
+
+impl Default for ProviderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+            max_retries: None,
+            timeout_secs: None,
+            confidence_threshold: None,
+            max_concurrent: None,
+            base_url: None,
+            model: None,
+            timestamps: false,
+        }
+    }
+}
+
+/// Provider id to its overrides.
+pub type ProviderOverrides = BTreeMap<String, ProviderSettings>;
+
+/// How every provider is used, from the `transcription` section of config.json.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TranscriptionSettings {
+    /// Send to the top `race_providers` providers at once instead of one at a time
+    pub race: bool,
+    pub race_providers: usize,
+    /// Collapse Whisper repetition loops before the confidence check
+    pub collapse_repetitions: bool,
+    /// What Groq and ElevenLabs receive: `flac`, or `wav` for servers that reject FLAC
+    pub upload_format: UploadFormat,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            race: false,
+            race_providers: DEFAULT_RACE_PROVIDERS,
+            collapse_repetitions: true,
+            upload_format: UploadFormat::default(),
+        }
+    }
+}
+
+impl TranscriptionSettings {
+    pub fn strategy(&self) -> Strategy {
+        if self.race {
+            Strategy::race(self.race_providers)
+        } else {
+            Strategy::Sequential
+        }
+    }
+}
+
+/// Everything the providers are built from. The app fills it from its settings; nothing
+/// is read from the environment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderSetup {
+    pub groq_key: Option<String>,
+    pub elevenlabs_key: Option<String>,
+    /// Language asked of Groq, e.g. `en`; `None` keeps its default, Portuguese
+    pub language: Option<String>,
+    /// whisper.cpp, the local fallback; left out while `None`
+    pub whisper: Option<LocalWhisper>,
+    /// Ids moved ahead of the other providers, in this order
+    pub preferred: Vec<String>,
+    pub overrides: ProviderOverrides,
+    pub transcription: TranscriptionSettings,
+    /// Vosk models for `STTManager` (`vosk-stt` feature); the bundled ones when unset
+    pub vosk_model_pt: Option<String>,
+    pub vosk_model_en: Option<String>,
+    /// Searched for local models not given by path, before `models/` next to the app
+    pub models_dir: Option<PathBuf>,
+    /// Scratch files of local engines; the system temp dir when unset
+    pub temp_dir: Option<PathBuf>,
+}
+
+/// A whisper.cpp set up by the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalWhisper {
+    pub bin_path: PathBuf,
+    /// The first model found next to the app when unset
+    pub model_path: Option<PathBuf>,
+    pub options: WhisperOptions,
+}
+
+impl ProviderSetup {
+    /// The Groq key, when it is one Groq (or the configured `base_url`) can take.
+    pub fn groq_key(&self) -> Option<&str> {
+        let custom_base = self
+            .settings("groq")
+            .and_then(|settings| settings.base_url.as_deref())
+            .is_some_and(|base| !base.trim().is_empty());
+        self.groq_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|key| key.starts_with("gsk_") || (custom_base && !key.is_empty()))
+    }
+
+    /// The whisper.cpp adapter for `whisper`, run with `options` in this setup's dirs.
+    pub fn whisper_adapter(
+        &self,
+        whisper: &LocalWhisper,
+        options: WhisperOptions,
+    ) -> Option<WhisperAdapter> {
+        let adapter = WhisperAdapter::new(
+            Some(whisper.bin_path.clone()),
+            whisper.model_path.clone(),
+            self.models_dir.as_deref(),
+            "auto",
+        )?
+        .with_options(options);
+        Some(match &self.temp_dir {
+            Some(dir) => adapter.with_temp_dir(dir.clone()),
+            None => adapter,
+        })
+    }
+
+    fn settings(&self, id: &str) -> Option<&ProviderSettings> {
+        self.overrides.get(id)
+    }
+}
+
+/// Providers with a usable key or a local engine in `setup`, with its overrides and
+/// preferred order applied.
+pub fn build_providers(setup: &ProviderSetup) -> Vec<ProviderConfig> {
+    let mut providers = Vec::new();
+
+    if let Some(key) = setup.groq_key() {
+        let settings = setup.settings("groq").cloned().unwrap_or_default();
+        let mut adapter = GroqAdapter::new(key.to_string())
+            .with_timestamps(settings.timestamps)
+            .with_upload_format(setup.transcription.upload_format);
+        if let Some(base_url) = settings.base_url.as_deref().map(str::trim) {
+            if !base_url.is_empty() {
+                adapter = adapter.with_base_url(base_url);
+            }
+        }
+        if let Some(model) = settings.model.as_deref() {
+            adapter = adapter.with_model(model);
+        }
+        if let Some(language) = setup.language.as_deref() {
+            adapter = adapter.with_language(language);
+        }
+        providers.push(ProviderConfig {
+            id: "groq".to_string(),
+            priority: 1,
+            adapter: Box::new(adapter),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: GroqAdapter::CONFIDENCE_THRESHOLD,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        });
+    }
+
+    if let Some(key) = setup
+        .elevenlabs_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|k| k.starts_with("sk_"))
+    {
+        providers.push(ProviderConfig {
+            id: "elevenlabs".to_string(),
+            priority: 2,
+            adapter: Box::new(
+                ElevenLabsAdapter::new(key.to_string())
+                    .with_upload_format(setup.transcription.upload_format),
+            ),
+            max_retries: 0,
+            timeout_secs: 30,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        });
+    }
+
+    // Local fallback, only once a binary was set up; it runs one transcription at a time.
+    if let Some(adapter) = setup.whisper.as_ref().and_then(|whisper| {
+        let mut options = whisper.options.clone();
+        if let Some(secs) = setup
+            .settings("whisper")
+            .and_then(|settings| settings.timeout_secs)
+            .filter(|secs| *secs > 0)
+        {
+            options.timeout = Duration::from_secs(secs);
+        }
+        setup.whisper_adapter(whisper, options)
+    }) {
+        let timeout_secs = adapter.options().timeout.as_secs();
+        providers.push(ProviderConfig {
+            id: "whisper".to_string(),
+            priority: 3,
+            adapter: Box::new(adapter),
+            max_retries: 0,
+            timeout_secs,
+            confidence_threshold: 0.7,
+            local: true,
+            max_concurrent: 1,
+        });
+    }
+
+    apply_overrides(&mut providers, &setup.overrides);
+    apply_preference(&mut providers, &normalize_ids(&setup.preferred));
+
+    providers
+}
+
+fn apply_overrides(providers: &mut Vec<ProviderConfig>, overrides: &ProviderOverrides) {
+    for id in overrides.keys() {
+        if !KNOWN_PROVIDERS.contains(&id.as_str()) {
+            tracing::warn!("Ignoring settings for unknown provider '{}'", id);
+        }
+    }
+
+    providers.retain(|provider| {
+        let enabled = overrides
+            .get(&provider.id)
+            .is_none_or(|settings| settings.enabled);
+        if !enabled {
+            tracing::info!("Provider {} disabled in config", provider.id);
+        }
+        enabled
+    });
+
+    for provider in providers.iter_mut() {
+        let Some(settings) = overrides.get(&provider.id) else {
+            continue;
+        };
+        if let Some(priority) = settings.priority {
+            provider.priority = priority;
+        }
+        if let Some(max_retries) = settings.max_retries {
+            provider.max_retries = max_retries;
+        }
+        if let Some(timeout_secs) = settings.timeout_secs.filter(|secs| *secs > 0) {
+            provider.timeout_secs = timeout_secs;
+        }
+        if let Some(threshold) = settings.confidence_threshold {
+            provider.confidence_threshold = threshold.clamp(0.0, 1.0);
+        }
+        if let Some(max_concurrent) = settings.max_concurrent.filter(|value| *value > 0) {
+            provider.max_concurrent = max_concurrent;
+        }
+    }
+}
+
+fn normalize_ids(ids: &[String]) -> Vec<String> {
+    ids.iter()
+        .map(|id| id.trim().to_ascii_lowercase())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Move `preferred` providers (by id, in that order) ahead of the rest; the others keep
+/// their relative order behind them.
+fn apply_preference(providers: &mut [ProviderConfig], preferred: &[String]) {
+    if preferred.is_empty() {
+        return;
+    }
+    let offset = preferred.len() as u8;
+    for provider in providers.iter_mut() {
+        provider.priority = match preferred.iter().position(|id| *id == provider.id) {
+            Some(rank) => rank as u8,
+            None => provider.priority.saturating_add(offset),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(id: &str, priority: u8) -> ProviderConfig {
+        ProviderConfig {
+            id: id.to_string(),
+            priority,
+            adapter: Box::new(GroqAdapter::new("gsk_test".to_string())),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }
+    }
+
+    #[test]
+    fn test_preferred_providers_move_to_the_front() {
+        let mut providers = vec![provider("groq", 1), provider("elevenlabs", 2)];
+        let preferred = [" ElevenLabs", "unknown ", ""].map(String::from);
+        apply_preference(&mut providers, &normalize_ids(&preferred));
+        providers.sort_by_key(|p| p.priority);
+        let order = providers.iter().map(|p| p.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(order, vec!["elevenlabs", "groq"]);
+
+        let mut providers = vec![provider("groq", 1), provider("elevenlabs", 2)];
+        apply_preference(&mut providers, &[]);
+        assert_eq!(providers[0].priority, 1);
+        assert_eq!(providers[1].priority, 2);
+    }
+
+    #[test]
+    fn test_overrides_replace_only_the_fields_they_set() {
+        let overrides: ProviderOverrides = serde_json::from_value(serde_json::json!({
+            "groq": { "timeoutSecs": 20, "confidenceThreshold": 1.5 },
+            "elevenlabs": { "priority": 0, "maxRetries": 2 },
+            "deepgram": { "enabled": false }
+        }))
+        .unwrap();
+        let mut providers = vec![provider("groq", 1), provider("elevenlabs", 2)];
+        apply_overrides(&mut providers, &overrides);
+
+        assert_eq!(providers[0].timeout_secs, 20);
+        assert_eq!(providers[0].confidence_threshold, 1.0);
+        assert_eq!(providers[0].priority, 1);
+        assert_eq!(providers[0].max_retries, 0);
+        assert_eq!(providers[1].priority, 0);
+        assert_eq!(providers[1].max_retries, 2);
+        assert_eq!(providers[1].timeout_secs, 10);
+    }
+
+    #[test]
+    fn test_disabled_providers_are_left_out() {
+        let overrides = ProviderOverrides::from([(
+            "groq".to_string(),
+            ProviderSettings {
+                enabled: false,
+                ..Default::default()
+            },
+        )]);
+        let mut providers = vec![provider("groq", 1), provider("elevenlabs", 2)];
+        apply_overrides(&mut providers, &overrides);
+        let ids = providers.iter().map(|p| p.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["elevenlabs"]);
+
+        // An empty block keeps the provider on.
+        let settings: ProviderSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, ProviderSettings::default());
+        assert!(settings.enabled);
+    }
+
+    #[test]
+    fn test_providers_come_from_the_setup_only() {
+        let ids = |setup: &ProviderSetup| {
+            let mut providers = build_providers(setup);
+            providers.sort_by_key(|p| p.priority);
+            providers.into_iter().map(|p| p.id).collect::<Vec<_>>()
+        };
+        assert!(ids(&ProviderSetup::default()).is_empty());
+
+        let mut setup = ProviderSetup {
+            groq_key: Some(" gsk_abc ".to_string()),
+            elevenlabs_key: Some("not-a-key".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(setup.groq_key(), Some("gsk_abc"));
+        assert_eq!(ids(&setup), vec!["groq"]);
+
+        setup.elevenlabs_key = Some("sk_abc".to_string());
+        setup.preferred = vec!["ElevenLabs".to_string()];
+        assert_eq!(ids(&setup), vec!["elevenlabs", "groq"]);
+
+        // A self-hosted server takes keys in any format.
+        setup.groq_key = Some("local-key".to_string());
+        assert_eq!(setup.groq_key(), None);
+        setup.overrides = serde_json::from_value(serde_json::json!({
+            "groq": { "baseUrl": "http://localhost:8000/v1", "maxConcurrent": 4 }
+        }))
+        .unwrap();
+        assert_eq!(setup.groq_key(), Some("local-key"));
+        let groq = build_providers(&setup)
+            .into_iter()
+            .find(|p| p.id == "groq")
+            .unwrap();
+        assert_eq!(groq.max_concurrent, 4);
+    }
+
+    #[test]
+    fn test_transcription_settings_pick_the_strategy() {
+        let defaults = TranscriptionSettings::default();
+        assert_eq!(defaults.strategy(), Strategy::Sequential);
+        assert!(defaults.collapse_repetitions);
+        assert_eq!(defaults.upload_format, UploadFormat::Flac);
+
+        let settings: TranscriptionSettings = serde_json::from_value(serde_json::json!({
+            "race": true, "raceProviders": 3, "uploadFormat": "wav"
+        }))
+        .unwrap();
+        assert_eq!(settings.strategy(), Strategy::Race { providers: 3 });
+        assert!(settings.collapse_repetitions);
+        assert_eq!(settings.upload_format, UploadFormat::Wav);
+    }
+}
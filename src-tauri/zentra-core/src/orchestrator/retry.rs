@@ -0,0 +1,106 @@
+﻿use crate::stt::STTError;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Longest `retry-after` honoured; a provider asking for more is tried again sooner.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+/// Share by which each wait varies either way, so segments rate-limited together don't
+/// all retry in the same instant.
+const JITTER: f64 = 0.2;
+
+pub struct RetryPolicy {
+    max_retries: u8,
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u8) -> Self {
+        Self {
+            max_retries,
+            base_delay: Duration::from_secs(2),
+        }
+    }
+
+    pub fn should_retry(&self, attempt: u8, error: &STTError) -> bool {
+        if attempt >= self.max_retries {
+            return false;
+        }
+
+        error.is_retryable()
+    }
+
+    /// The wait the provider asked for, up to [`MAX_RETRY_AFTER`], else the exponential
+    /// schedule. Before jitter.
+    pub fn delay(&self, attempt: u8, error: &STTError) -> Duration {
+        if let Some(secs) = error.retry_after_secs() {
+            return Duration::from_secs(secs).min(MAX_RETRY_AFTER);
+        }
+        let multiplier = 2u64.saturating_pow(attempt as u32);
+        let delay_secs = self.base_delay.as_secs().saturating_mul(multiplier);
+        Duration::from_secs(delay_secs.max(1))
+    }
+
+    pub async fn wait_before_retry(&self, attempt: u8, error: &STTError) {
+        let delay = jittered(self.delay(attempt, error), random_unit());
+
+        tracing::info!(
+            "Retrying in {:.1}s (attempt {})",
+            delay.as_secs_f32(),
+            attempt + 2
+        );
+        sleep(delay).await;
+    }
+}
+
+/// `delay` scaled into [1 - JITTER, 1 + JITTER] by `unit`, a number in [0, 1].
+fn jittered(delay: Duration, unit: f64) -> Duration {
+    delay.mul_f64(1.0 - JITTER + 2.0 * JITTER * unit.clamp(0.0, 1.0))
+}
+
+fn random_unit() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() as u64) as f64 / u64::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limited(retry_after_secs: Option<u64>) -> STTError {
+        STTError::RateLimitError { retry_after_secs }
+    }
+
+    #[test]
+    fn test_the_providers_wait_wins_over_the_schedule_up_to_the_cap() {
+        let policy = RetryPolicy::new(3);
+        assert_eq!(
+            policy.delay(0, &rate_limited(Some(12))),
+            Duration::from_secs(12)
+        );
+        assert_eq!(policy.delay(2, &rate_limited(Some(300))), MAX_RETRY_AFTER);
+
+        let schedule: Vec<Duration> = (0..3)
+            .map(|attempt| policy.delay(attempt, &rate_limited(None)))
+            .collect();
+        assert_eq!(
+            schedule,
+            [2, 4, 8].map(Duration::from_secs),
+            "no hint falls back to 2 s, 4 s, 8 s"
+        );
+        assert_eq!(
+            policy.delay(1, &STTError::TimeoutError),
+            Duration::from_secs(4)
+        );
+    }
+
+    #[test]
+    fn test_jitter_stays_within_a_fifth_either_way() {
+        let delay = Duration::from_secs(10);
+        assert_eq!(jittered(delay, 0.0), Duration::from_secs(8));
+        assert_eq!(jittered(delay, 0.5), delay);
+        assert_eq!(jittered(delay, 1.0), Duration::from_secs(12));
+        for _ in 0..100 {
+            let waited = jittered(delay, random_unit());
+            assert!(waited >= Duration::from_secs(8) && waited <= Duration::from_secs(12));
+        }
+    }
+}
@@ -0,0 +1,46 @@
+/// Providers raced at once unless the settings say otherwise.
+pub const DEFAULT_RACE_PROVIDERS: usize = 2;
+
+/// How the orchestrator walks its providers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// One provider at a time, in priority order. A provider is only paid for when every
+    /// one before it failed.
+    #[default]
+    Sequential,
+    /// Send to the top `providers` healthy providers at once and keep the first transcript
+    /// that meets its provider's confidence threshold; the other requests are cancelled.
+    /// Providers left out of the race are tried in order if every racer fails.
+    Race { providers: usize },
+}
+
+impl Strategy {
+    /// Racing with `providers` racers, or [`DEFAULT_RACE_PROVIDERS`] when fewer than two.
+    pub fn race(providers: usize) -> Self {
+        let providers = if providers > 1 {
+            providers
+        } else {
+            DEFAULT_RACE_PROVIDERS
+        };
+        Self::Race { providers }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_race_needs_at_least_two_providers() {
+        assert_eq!(Strategy::race(3), Strategy::Race { providers: 3 });
+        // A race of one is just sequential with extra steps.
+        for providers in [0, 1] {
+            assert_eq!(
+                Strategy::race(providers),
+                Strategy::Race {
+                    providers: DEFAULT_RACE_PROVIDERS
+                }
+            );
+        }
+    }
+}
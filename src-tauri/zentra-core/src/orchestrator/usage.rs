@@ -0,0 +1,283 @@
+//! How much audio was sent to each provider, month by month, to give an idea of the bill.
+//! Every attempt counts, retries and failures included, since providers charge for what
+//! they received. Costs are estimated when read, from per-minute rates, so changing a rate
+//! reprices the past too.
+
+use crate::audio::AudioBuffer;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+pub const USAGE_FILE: &str = "usage.json";
+
+/// Per-minute USD rates from the providers' price lists: Groq's whisper-large-v3 at
+/// $0.111 an hour and ElevenLabs Scribe at $0.40 an hour.
+pub fn default_rates() -> BTreeMap<String, f64> {
+    BTreeMap::from([
+        ("groq".to_string(), 0.111 / 60.0),
+        ("elevenlabs".to_string(), 0.40 / 60.0),
+    ])
+}
+
+/// Length of `audio` as the provider sees it.
+pub fn audio_secs(audio: &AudioBuffer) -> f64 {
+    if audio.sample_rate == 0 {
+        return 0.0;
+    }
+    audio.samples.len() as f64 / (audio.sample_rate as f64 * audio.channels.max(1) as f64)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Tally {
+    audio_secs: f64,
+    requests: u64,
+}
+
+impl Tally {
+    fn add(&mut self, other: Tally) {
+        self.audio_secs += other.audio_secs;
+        self.requests += other.requests;
+    }
+}
+
+/// UTC month (`2026-10`) to provider id to what it was sent that month
+type Ledger = BTreeMap<String, BTreeMap<String, Tally>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub audio_secs: f64,
+    pub requests: u64,
+    /// USD; 0 for providers without a rate, such as local ones
+    pub estimated_cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthUsage {
+    /// UTC month, `YYYY-MM`
+    pub month: String,
+    pub audio_secs: f64,
+    pub requests: u64,
+    pub estimated_cost: f64,
+    pub providers: Vec<ProviderUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    /// All time, per provider
+    pub providers: Vec<ProviderUsage>,
+    /// Newest month first
+    pub months: Vec<MonthUsage>,
+}
+
+#[derive(Default)]
+struct Inner {
+    ledger: Ledger,
+    path: Option<PathBuf>,
+}
+
+/// The usage ledger. Clones share it, so it outlives orchestrator rebuilds.
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl UsageTracker {
+    /// Load the ledger kept at `path` and save it there after every change. Usage recorded
+    /// before this call is added on top.
+    pub fn persist_to(&self, path: PathBuf) {
+        let mut inner = self.lock();
+        let recorded = std::mem::take(&mut inner.ledger);
+        inner.ledger = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable usage file {}: {}", path.display(), e);
+                Ledger::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ledger::new(),
+            Err(e) => {
+                tracing::warn!("Failed to read usage file {}: {}", path.display(), e);
+                Ledger::new()
+            }
+        };
+        for (month, providers) in recorded {
+            let book = inner.ledger.entry(month).or_default();
+            for (provider, tally) in providers {
+                book.entry(provider).or_default().add(tally);
+            }
+        }
+        inner.path = Some(path);
+        save(&inner);
+    }
+
+    /// Count one request of `audio_secs` seconds to `provider_id`.
+    pub fn record(&self, provider_id: &str, audio_secs: f64) {
+        self.record_at(provider_id, audio_secs, Utc::now());
+    }
+
+    fn record_at(&self, provider_id: &str, audio_secs: f64, at: DateTime<Utc>) {
+        let mut inner = self.lock();
+        inner
+            .ledger
+            .entry(at.format("%Y-%m").to_string())
+            .or_default()
+            .entry(provider_id.to_string())
+            .or_default()
+            .add(Tally {
+                audio_secs,
+                requests: 1,
+            });
+        save(&inner);
+    }
+
+    /// Forget all usage, on disk too.
+    pub fn reset(&self) {
+        let mut inner = self.lock();
+        inner.ledger.clear();
+        save(&inner);
+    }
+
+    /// Totals per provider and per month, priced with `rates` (USD per minute).
+    pub fn stats(&self, rates: &BTreeMap<String, f64>) -> UsageStats {
+        let inner = self.lock();
+        let usage = |provider: &str, tally: Tally| ProviderUsage {
+            provider: provider.to_string(),
+            audio_secs: tally.audio_secs,
+            requests: tally.requests,
+            estimated_cost: tally.audio_secs / 60.0 * rates.get(provider).copied().unwrap_or(0.0),
+        };
+
+        let mut all_time: BTreeMap<&str, Tally> = BTreeMap::new();
+        let mut months = Vec::new();
+        for (month, providers) in inner.ledger.iter().rev() {
+            let providers: Vec<ProviderUsage> = providers
+                .iter()
+                .map(|(provider, tally)| {
+                    all_time.entry(provider).or_default().add(*tally);
+                    usage(provider, *tally)
+                })
+                .collect();
+            months.push(MonthUsage {
+                month: month.clone(),
+                audio_secs: providers.iter().map(|p| p.audio_secs).sum(),
+                requests: providers.iter().map(|p| p.requests).sum(),
+                estimated_cost: providers.iter().map(|p| p.estimated_cost).sum(),
+                providers,
+            });
+        }
+        UsageStats {
+            providers: all_time
+                .into_iter()
+                .map(|(provider, tally)| usage(provider, tally))
+                .collect(),
+            months,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Write the ledger through a temporary file, so a crash never leaves half of it.
+fn save(inner: &Inner) {
+    let Some(path) = &inner.path else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(&inner.ledger)
+        .map_err(|e| e.to_string())
+        .and_then(|raw| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, raw).map_err(|e| e.to_string())?;
+            fs::rename(&tmp, path).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        tracing::warn!("Failed to save usage to {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, 10, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_usage_is_totalled_per_provider_and_month() {
+        let usage = UsageTracker::default();
+        usage.record_at("groq", 60.0, at(2026, 9));
+        usage.record_at("groq", 30.0, at(2026, 10));
+        usage.record_at("groq", 30.0, at(2026, 10));
+        usage.record_at("elevenlabs", 120.0, at(2026, 10));
+        usage.record_at("local-whisper", 600.0, at(2026, 10));
+
+        let rates = BTreeMap::from([("groq".to_string(), 0.5), ("elevenlabs".to_string(), 1.0)]);
+        let stats = usage.stats(&rates);
+
+        let months: Vec<&str> = stats.months.iter().map(|m| m.month.as_str()).collect();
+        assert_eq!(months, vec!["2026-10", "2026-09"]);
+        let october = &stats.months[0];
+        assert_eq!(october.requests, 4);
+        assert_eq!(october.audio_secs, 780.0);
+        // groq 1 min at 0.5, elevenlabs 2 min at 1.0, local free
+        assert!((october.estimated_cost - 2.5).abs() < 1e-9);
+
+        let groq = stats
+            .providers
+            .iter()
+            .find(|p| p.provider == "groq")
+            .unwrap();
+        assert_eq!(groq.requests, 3);
+        assert_eq!(groq.audio_secs, 120.0);
+        assert!((groq.estimated_cost - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_usage_survives_a_restart_and_reset_clears_the_file() {
+        let dir = std::env::temp_dir().join(format!("zentra-usage-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(USAGE_FILE);
+
+        let first = UsageTracker::default();
+        // Recorded before the app data dir is known; kept once it is.
+        first.record_at("groq", 10.0, at(2026, 10));
+        first.persist_to(path.clone());
+        first.record_at("groq", 20.0, at(2026, 10));
+
+        let second = UsageTracker::default();
+        second.record_at("elevenlabs", 5.0, at(2026, 10));
+        second.persist_to(path.clone());
+        let stats = second.stats(&default_rates());
+        assert_eq!(stats.months.len(), 1);
+        assert_eq!(stats.months[0].requests, 3);
+        assert_eq!(stats.months[0].audio_secs, 35.0);
+
+        second.reset();
+        let third = UsageTracker::default();
+        third.persist_to(path.clone());
+        assert!(third.stats(&default_rates()).months.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_audio_length_counts_every_channel_once() {
+        let mut audio = AudioBuffer::new(16_000, 2);
+        audio.append(&vec![0i16; 64_000]);
+        assert_eq!(audio_secs(&audio), 2.0);
+        assert_eq!(audio_secs(&AudioBuffer::new(0, 1)), 0.0);
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Shared privacy-mode switch, read at use time by every persistence and egress point.
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyGuard {
+    enabled: Arc<AtomicBool>,
+}
+
+impl PrivacyGuard {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        let previous = self.enabled.swap(enabled, Ordering::Relaxed);
+        if previous != enabled {
+            tracing::info!(
+                "Privacy mode {}",
+                if enabled { "enabled" } else { "disabled" }
+            );
+        }
+    }
+
+    /// History, archives, logs and integrations may only write when this returns true.
+    pub fn allows_persistence(&self) -> bool {
+        !self.is_enabled()
+    }
+
+    /// Cloud providers are skipped entirely while privacy mode is on.
+    pub fn allows_provider(&self, local: bool) -> bool {
+        local || !self.is_enabled()
+    }
+}
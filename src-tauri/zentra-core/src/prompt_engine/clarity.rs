@@ -0,0 +1,386 @@
+// prompt_engine/clarity.rs — Rules-based PT-BR and English text cleanup
+
+/// Which shorthand table the clarity pass uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Portuguese,
+    English,
+}
+
+const PT_REPLACEMENTS: &[(&str, &str)] = &[
+    ("nao", "não"),
+    ("tb", "também"),
+    ("pq", "porque"),
+    ("vc", "você"),
+    ("vcs", "vocês"),
+    ("eh", "é"),
+    ("q", "que"),
+    ("tah", "tá"),
+    ("oq", "o que"),
+    ("td", "tudo"),
+    ("mt", "muito"),
+    ("ngm", "ninguém"),
+    ("msm", "mesmo"),
+];
+
+const EN_REPLACEMENTS: &[(&str, &str)] = &[
+    ("u", "you"),
+    ("ur", "your"),
+    ("im", "I'm"),
+    ("i", "I"),
+    ("ive", "I've"),
+    ("dont", "don't"),
+    ("doesnt", "doesn't"),
+    ("didnt", "didn't"),
+    ("isnt", "isn't"),
+    ("wasnt", "wasn't"),
+    ("cant", "can't"),
+    ("wont", "won't"),
+    ("thats", "that's"),
+    ("pls", "please"),
+    ("thx", "thanks"),
+];
+
+const PT_STOPWORDS: &[&str] = &[
+    "o", "os", "de", "da", "das", "dos", "que", "e", "é", "não", "um", "uma", "para", "com", "em",
+    "na", "por", "mas", "eu", "você", "isso", "está", "tem", "muito",
+];
+
+const EN_STOPWORDS: &[&str] = &[
+    "the", "and", "is", "are", "to", "of", "you", "i", "it", "that", "this", "for", "with", "was",
+    "not", "have", "what", "my", "be", "just",
+];
+
+impl Language {
+    /// `pt`, `pt-BR`, `por`, `en`, `en-US`, `eng`... `None` for `auto` and anything else.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_lowercase();
+        if code.starts_with("pt") || code.starts_with("por") {
+            Some(Self::Portuguese)
+        } else if code.starts_with("en") {
+            Some(Self::English)
+        } else {
+            None
+        }
+    }
+
+    /// Guess from which language's stopwords are more frequent. Portuguese, the app's
+    /// default, wins ties and text without any stopword.
+    pub fn detect(text: &str) -> Self {
+        let (mut pt, mut en) = (0, 0);
+        for word in text.split(|c: char| !c.is_alphanumeric() && c != '\'') {
+            let word = word.to_lowercase();
+            pt += PT_STOPWORDS.contains(&word.as_str()) as usize;
+            en += EN_STOPWORDS.contains(&word.as_str()) as usize;
+        }
+        if en > pt {
+            Self::English
+        } else {
+            Self::Portuguese
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Portuguese => "pt",
+            Self::English => "en",
+        }
+    }
+
+    /// The language named by `hint`, else the one detected in `text`.
+    pub fn resolve(hint: &str, text: &str) -> Self {
+        Self::from_code(hint).unwrap_or_else(|| Self::detect(text))
+    }
+
+    fn replacements(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Portuguese => PT_REPLACEMENTS,
+            Self::English => EN_REPLACEMENTS,
+        }
+    }
+}
+
+/// Apply rules-based clarity corrections without LLM. `language` is a code such as `pt`,
+/// `en` or `auto`; the detected language of the transcript when there is one.
+pub fn transform(text: &str, language: &str) -> String {
+    let mut result = text.to_string();
+
+    // 1. Normalize whitespace: multiple spaces → single
+    result = collapse_spaces(&result);
+
+    // 2. Common shorthand for the language
+    result = fix_common_typos(&result, Language::resolve(language, &result));
+
+    // 3. Fix punctuation spacing
+    result = fix_punctuation(&result);
+
+    // 4. Capitalize first letter of each sentence
+    result = capitalize_sentences(&result);
+
+    // 5. Trim
+    result.trim().to_string()
+}
+
+/// Light pass for the live session preview: steps 1, 3 and 4 of [`transform`]. No
+/// replacements (they would rewrite words while the user reads them) and no closing
+/// period, since the speaker is usually mid-sentence.
+pub fn preview(text: &str) -> String {
+    let punctuated = fix_punctuation(&collapse_spaces(text));
+    capitalize(&punctuated).trim().to_string()
+}
+
+/// [`preview`] fed piece by piece, so a growing session only processes the text that was
+/// appended. `text()` after any sequence of `push` calls equals `preview` of their
+/// concatenation.
+#[derive(Debug, Clone, Default)]
+pub struct PreviewCleaner {
+    out: String,
+    prev_space: bool,
+    after_punct: bool,
+    in_sentence: bool,
+}
+
+impl PreviewCleaner {
+    pub fn push(&mut self, text: &str) {
+        for ch in text.chars() {
+            // collapse_spaces
+            if ch == ' ' || ch == '\t' {
+                if self.prev_space {
+                    continue;
+                }
+                self.prev_space = true;
+                self.punctuate(' ');
+            } else {
+                self.prev_space = false;
+                self.punctuate(ch);
+            }
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.out.trim()
+    }
+
+    // fix_punctuation, which looks one character ahead: the space after a mark waits for
+    // the next character.
+    fn punctuate(&mut self, ch: char) {
+        if self.after_punct && ch.is_alphabetic() {
+            self.capitalize(' ');
+        }
+        self.after_punct = is_spaced_punct(ch);
+        if self.after_punct && self.out.ends_with(' ') {
+            self.out.pop();
+        }
+        self.capitalize(ch);
+    }
+
+    // capitalize
+    fn capitalize(&mut self, ch: char) {
+        if !self.in_sentence && ch.is_alphabetic() {
+            self.out.extend(ch.to_uppercase());
+            self.in_sentence = true;
+        } else {
+            self.out.push(ch);
+        }
+
+        if ch == '.' || ch == '!' || ch == '?' {
+            self.in_sentence = false;
+        }
+    }
+}
+
+fn is_spaced_punct(ch: char) -> bool {
+    ch == '.' || ch == ',' || ch == '!' || ch == '?' || ch == ':' || ch == ';'
+}
+
+fn collapse_spaces(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut prev_space = false;
+    for ch in text.chars() {
+        if ch == ' ' || ch == '\t' {
+            if !prev_space {
+                result.push(' ');
+            }
+            prev_space = true;
+        } else {
+            prev_space = false;
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// Replace whole words only, so "nao" inside "canao" or "u" in "you" stay as they are. An
+/// apostrophe between letters belongs to the word; a capitalized word keeps its capital.
+fn fix_common_typos(text: &str, language: Language) -> String {
+    let table = language.replacements();
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+
+    for ch in text.chars() {
+        if ch.is_alphanumeric() || (is_apostrophe(ch) && !word.is_empty()) {
+            word.push(ch);
+            continue;
+        }
+        push_word(&mut result, &word, table);
+        word.clear();
+        result.push(ch);
+    }
+    push_word(&mut result, &word, table);
+    result
+}
+
+fn push_word(result: &mut String, word: &str, table: &[(&str, &str)]) {
+    let lower = word.to_lowercase();
+    let Some((_, to)) = table.iter().find(|(from, _)| *from == lower) else {
+        result.push_str(word);
+        return;
+    };
+    if word.chars().next().is_some_and(char::is_uppercase) {
+        result.push_str(&capitalize(to));
+    } else {
+        result.push_str(to);
+    }
+}
+
+fn is_apostrophe(ch: char) -> bool {
+    ch == '\'' || ch == '’'
+}
+
+fn fix_punctuation(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let chars: Vec<char> = text.chars().collect();
+
+    for i in 0..chars.len() {
+        let ch = chars[i];
+
+        // Remove space before punctuation
+        if (ch == '.' || ch == ',' || ch == '!' || ch == '?' || ch == ':' || ch == ';')
+            && !result.is_empty()
+            && result.ends_with(' ')
+        {
+            result.pop(); // Remove trailing space
+        }
+
+        result.push(ch);
+
+        // Ensure space after punctuation (if next char is letter)
+        if (ch == '.' || ch == ',' || ch == '!' || ch == '?' || ch == ':' || ch == ';')
+            && i + 1 < chars.len()
+            && chars[i + 1].is_alphabetic()
+        {
+            result.push(' ');
+        }
+    }
+
+    result
+}
+
+fn capitalize_sentences(text: &str) -> String {
+    let mut result = capitalize(text);
+
+    // Ensure text ends with period if it doesn't end with punctuation
+    let trimmed = result.trim_end();
+    if !trimmed.is_empty() {
+        let last = trimmed.chars().last().unwrap();
+        if last != '.' && last != '!' && last != '?' {
+            result = trimmed.to_string();
+            result.push('.');
+        }
+    }
+
+    result
+}
+
+fn capitalize(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut capitalize_next = true;
+
+    for ch in text.chars() {
+        if capitalize_next && ch.is_alphabetic() {
+            result.extend(ch.to_uppercase());
+            capitalize_next = false;
+        } else {
+            result.push(ch);
+        }
+
+        if ch == '.' || ch == '!' || ch == '?' {
+            capitalize_next = true;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorthand_per_language() {
+        let cases = [
+            (
+                "vc vem hj? nao sei pq",
+                "pt",
+                "Você vem hj? Não sei porque.",
+            ),
+            ("eh isso, td certo", "pt-BR", "É isso, tudo certo."),
+            ("o canao da festa", "pt", "O canao da festa."),
+            ("nao,nao.", "pt", "Não, não."),
+            ("u know ur late", "en", "You know your late."),
+            ("im sure i dont know", "en", "I'm sure I don't know."),
+            ("you and your umbrella", "en", "You and your umbrella."),
+            ("thx, see u tomorrow", "eng", "Thanks, see you tomorrow."),
+            // A table only applies to its own language.
+            ("u vc", "pt", "U você."),
+            ("u vc", "en", "You vc."),
+        ];
+        for (input, language, expected) in cases {
+            assert_eq!(
+                transform(input, language),
+                expected,
+                "{:?} ({})",
+                input,
+                language
+            );
+        }
+    }
+
+    #[test]
+    fn test_auto_detects_the_dominant_language() {
+        let cases = [
+            (
+                "i think the meeting is at 3 and u should come",
+                Language::English,
+            ),
+            (
+                "eu acho que a reunião é às 3 e vc precisa vir",
+                Language::Portuguese,
+            ),
+            // Mostly Portuguese with an English phrase: the Portuguese table wins.
+            (
+                "o deploy de hoje, ship it, não ficou bom para o time",
+                Language::Portuguese,
+            ),
+            (
+                "the deploy, tá ok, is done and that is it",
+                Language::English,
+            ),
+            ("ok", Language::Portuguese),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(Language::detect(text), expected, "{:?}", text);
+            assert_eq!(Language::resolve("auto", text), expected, "{:?}", text);
+        }
+        assert_eq!(
+            transform("i think u should come to the demo", "auto"),
+            "I think you should come to the demo."
+        );
+        assert_eq!(
+            transform("eu acho que vc devia vir, nao?", "auto"),
+            "Eu acho que você devia vir, não?"
+        );
+        // An explicit hint beats detection.
+        assert_eq!(Language::resolve("pt", "the and is"), Language::Portuguese);
+    }
+}
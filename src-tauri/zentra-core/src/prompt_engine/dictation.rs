@@ -0,0 +1,204 @@
+// prompt_engine/dictation.rs — Spoken formatting commands ("new line", "vírgula")
+
+use super::clarity::Language;
+use crate::text::normalize::normalize_for_match;
+
+/// Said before a command to keep its words as text: "literal vírgula" → "vírgula".
+const ESCAPE_WORD: &str = "literal";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Attaches to the previous word
+    Mark(char),
+    /// Ends the line; `2` starts a paragraph
+    Break(usize),
+}
+
+/// Normalized phrase (see [`normalize_for_match`]) to the formatting it stands for.
+const EN_COMMANDS: &[(&str, Command)] = &[
+    ("new paragraph", Command::Break(2)),
+    ("new line", Command::Break(1)),
+    ("comma", Command::Mark(',')),
+    ("question mark", Command::Mark('?')),
+    ("exclamation mark", Command::Mark('!')),
+    ("exclamation point", Command::Mark('!')),
+];
+
+const PT_COMMANDS: &[(&str, Command)] = &[
+    ("novo paragrafo", Command::Break(2)),
+    ("nova linha", Command::Break(1)),
+    ("virgula", Command::Mark(',')),
+    ("ponto de interrogacao", Command::Mark('?')),
+    ("ponto de exclamacao", Command::Mark('!')),
+];
+
+/// Replace spoken commands with the formatting they name. Commands match whole words,
+/// ignoring case, accents and the punctuation a provider puts around them; `auto` (or any
+/// language without a table) recognizes both languages. Runs after every pass that
+/// collapses whitespace, since line breaks would not survive them.
+pub fn apply_dictation_commands(text: &str, language: &str) -> String {
+    let commands: Vec<&(&str, Command)> = match Language::from_code(language) {
+        Some(Language::English) => EN_COMMANDS.iter().collect(),
+        Some(Language::Portuguese) => PT_COMMANDS.iter().collect(),
+        None => PT_COMMANDS.iter().chain(EN_COMMANDS).collect(),
+    };
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let words: Vec<String> = tokens.iter().map(|t| normalize_for_match(t)).collect();
+
+    let mut out = String::with_capacity(text.len());
+    let mut capitalize_next = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let escaped = words[i] == ESCAPE_WORD;
+        let start = i + escaped as usize;
+        let Some((len, command)) = match_command(&words[start..], &commands) else {
+            push_word(&mut out, tokens[i], &mut capitalize_next);
+            i += 1;
+            continue;
+        };
+
+        if escaped {
+            for token in &tokens[start..start + len] {
+                push_word(&mut out, token, &mut capitalize_next);
+            }
+        } else {
+            apply(&mut out, command, &mut capitalize_next);
+        }
+        i = start + len;
+    }
+    out
+}
+
+/// Words of the longest command `words` starts with.
+fn match_command(words: &[String], commands: &[&(&str, Command)]) -> Option<(usize, Command)> {
+    commands
+        .iter()
+        .filter_map(|(phrase, command)| {
+            let len = phrase.split(' ').count();
+            let spoken = words.get(..len)?.join(" ");
+            (spoken == *phrase).then_some((len, *command))
+        })
+        .max_by_key(|(len, _)| *len)
+}
+
+fn apply(out: &mut String, command: Command, capitalize_next: &mut bool) {
+    match command {
+        Command::Mark(mark) => {
+            // Replaces the mark a provider heard in the pause before it.
+            let kept = out.trim_end_matches([',', '.', ';', ':', '?', '!']).len();
+            out.truncate(kept);
+            out.push(mark);
+            *capitalize_next = matches!(mark, '?' | '!');
+        }
+        Command::Break(lines) => {
+            let kept = out.trim_end_matches('\n').len();
+            out.truncate(kept);
+            if !out.is_empty() {
+                out.extend(std::iter::repeat_n('\n', lines));
+            }
+            *capitalize_next = true;
+        }
+    }
+}
+
+fn push_word(out: &mut String, token: &str, capitalize_next: &mut bool) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push(' ');
+    }
+    if std::mem::take(capitalize_next) {
+        let mut chars = token.chars();
+        out.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+        out.push_str(chars.as_str());
+    } else {
+        out.push_str(token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_commands() {
+        assert_eq!(
+            apply_dictation_commands("Hi Ana comma how are you question mark", "en"),
+            "Hi Ana, how are you?"
+        );
+        assert_eq!(
+            apply_dictation_commands(
+                "First point. New paragraph. second point new line third",
+                "en"
+            ),
+            "First point.\n\nSecond point\nThird"
+        );
+        // The provider's own punctuation around a command gives way to it.
+        assert_eq!(
+            apply_dictation_commands("Wait, Comma, really? Exclamation point.", "en-US"),
+            "Wait, really!"
+        );
+    }
+
+    #[test]
+    fn test_portuguese_commands() {
+        assert_eq!(
+            apply_dictation_commands("Oi Ana vírgula tudo bem Ponto de Interrogação", "pt"),
+            "Oi Ana, tudo bem?"
+        );
+        assert_eq!(
+            apply_dictation_commands(
+                "Pauta. Novo parágrafo. primeiro item nova linha segundo item",
+                "pt-BR"
+            ),
+            "Pauta.\n\nPrimeiro item\nSegundo item"
+        );
+        // Transcribed without accents still counts.
+        assert_eq!(
+            apply_dictation_commands("sim virgula claro", "pt"),
+            "sim, claro"
+        );
+    }
+
+    #[test]
+    fn test_only_whole_words_match() {
+        assert_eq!(
+            apply_dictation_commands("the commas and a newline stay", "en"),
+            "the commas and a newline stay"
+        );
+        assert_eq!(
+            apply_dictation_commands("a new lineup", "en"),
+            "a new lineup"
+        );
+        assert_eq!(
+            apply_dictation_commands("ponto de encontro vírgulas", "pt"),
+            "ponto de encontro vírgulas"
+        );
+    }
+
+    #[test]
+    fn test_literal_keeps_the_words() {
+        assert_eq!(
+            apply_dictation_commands("a palavra literal vírgula vírgula tem acento", "pt"),
+            "a palavra vírgula, tem acento"
+        );
+        assert_eq!(
+            apply_dictation_commands("type literal new line here", "en"),
+            "type new line here"
+        );
+        // Not before a command: "literal" is just a word.
+        assert_eq!(
+            apply_dictation_commands("a literal translation", "en"),
+            "a literal translation"
+        );
+    }
+
+    #[test]
+    fn test_auto_recognizes_both_languages_and_other_languages_do_not_cross() {
+        assert_eq!(
+            apply_dictation_commands("ok comma certo vírgula fim", "auto"),
+            "ok, certo, fim"
+        );
+        assert_eq!(apply_dictation_commands("ok vírgula", "en"), "ok vírgula");
+        assert_eq!(apply_dictation_commands("new line", "pt"), "new line");
+        assert_eq!(apply_dictation_commands("", "pt"), "");
+    }
+}
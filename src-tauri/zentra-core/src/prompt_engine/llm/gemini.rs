@@ -2,6 +2,7 @@
 
 use super::LLMAdapter;
 use crate::prompt_engine::types::LLMError;
+use crate::provider_error::ProviderApiError;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -67,14 +68,11 @@ impl GeminiAdapter {
 
         Self { client, api_key }
     }
-}
 
-#[async_trait]
-impl LLMAdapter for GeminiAdapter {
-    async fn generate(&self, prompt: &str) -> Result<String, LLMError> {
+    async fn call_model(&self, model: &str, prompt: &str) -> Result<String, LLMError> {
         let url = format!(
             "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            MODEL, self.api_key
+            model, self.api_key
         );
 
         let request = GeminiRequest {
@@ -100,9 +98,10 @@ impl LLMAdapter for GeminiAdapter {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(LLMError::ProviderError(format!(
-                "Gemini {}: {}", status, body
-            )));
+            return Err(LLMError::ProviderApi {
+                provider: "Gemini".to_string(),
+                error: ProviderApiError::parse(status.as_u16(), &body),
+            });
         }
 
         let gemini: GeminiResponse = response
@@ -117,6 +116,17 @@ impl LLMAdapter for GeminiAdapter {
             .map(|p| p.text.clone())
             .ok_or(LLMError::InvalidResponse)
     }
+}
+
+#[async_trait]
+impl LLMAdapter for GeminiAdapter {
+    async fn generate(&self, prompt: &str) -> Result<String, LLMError> {
+        self.call_model(MODEL, prompt).await
+    }
+
+    async fn generate_with_model(&self, prompt: &str, model: &str) -> Result<String, LLMError> {
+        self.call_model(model, prompt).await
+    }
 
     fn name(&self) -> &str {
         "gemini"
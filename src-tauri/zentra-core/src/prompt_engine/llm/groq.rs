@@ -2,6 +2,7 @@
 
 use super::LLMAdapter;
 use crate::prompt_engine::types::LLMError;
+use crate::provider_error::ProviderApiError;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -53,13 +54,10 @@ impl GroqLLMAdapter {
 
         Self { client, api_key }
     }
-}
 
-#[async_trait]
-impl LLMAdapter for GroqLLMAdapter {
-    async fn generate(&self, prompt: &str) -> Result<String, LLMError> {
+    async fn call_model(&self, model: &str, prompt: &str) -> Result<String, LLMError> {
         let request = ChatRequest {
-            model: MODEL.to_string(),
+            model: model.to_string(),
             messages: vec![Message {
                 role: "user".to_string(),
                 content: prompt.to_string(),
@@ -80,9 +78,10 @@ impl LLMAdapter for GroqLLMAdapter {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(LLMError::ProviderError(format!(
-                "Groq {}: {}", status, body
-            )));
+            return Err(LLMError::ProviderApi {
+                provider: "Groq".to_string(),
+                error: ProviderApiError::parse(status.as_u16(), &body),
+            });
         }
 
         let chat: ChatResponse = response
@@ -95,6 +94,17 @@ impl LLMAdapter for GroqLLMAdapter {
             .map(|c| c.message.content.clone())
             .ok_or(LLMError::InvalidResponse)
     }
+}
+
+#[async_trait]
+impl LLMAdapter for GroqLLMAdapter {
+    async fn generate(&self, prompt: &str) -> Result<String, LLMError> {
+        self.call_model(MODEL, prompt).await
+    }
+
+    async fn generate_with_model(&self, prompt: &str, model: &str) -> Result<String, LLMError> {
+        self.call_model(model, prompt).await
+    }
 
     fn name(&self) -> &str {
         "groq"
@@ -0,0 +1,359 @@
+// prompt_engine/llm/mod.rs — LLM trait + failover orchestrator
+
+pub mod openrouter;
+pub mod groq;
+pub mod gemini;
+pub mod ollama;
+pub mod replay;
+
+use super::types::LLMError;
+use async_trait::async_trait;
+
+/// Trait for LLM text generation adapters
+#[async_trait]
+pub trait LLMAdapter: Send + Sync {
+    /// Generate text from prompt
+    async fn generate(&self, prompt: &str) -> Result<String, LLMError>;
+
+    /// Generate with `model` instead of the adapter's own choice. Adapters without a
+    /// model choice ignore it.
+    async fn generate_with_model(&self, prompt: &str, model: &str) -> Result<String, LLMError> {
+        tracing::debug!(
+            "LLM: '{}' has no model choice, ignoring '{}'",
+            self.name(),
+            model
+        );
+        self.generate(prompt).await
+    }
+
+    /// Provider name
+    fn name(&self) -> &str;
+}
+
+/// Provider ids a profile can pin, in the normal failover order
+pub const PROVIDER_IDS: [&str; 4] = ["openrouter", "groq", "gemini", "ollama"];
+
+pub fn is_known_provider(id: &str) -> bool {
+    PROVIDER_IDS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(id))
+}
+
+/// The provider, and optionally its model, a profile wants tried before the normal order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderPin<'a> {
+    pub provider: &'a str,
+    pub model: Option<&'a str>,
+}
+
+/// API keys of the cloud providers; Ollama needs none
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LlmKeys {
+    pub openrouter: Option<String>,
+    pub groq: Option<String>,
+    pub gemini: Option<String>,
+}
+
+/// LLM Orchestrator with sequential failover
+pub struct LLMOrchestrator {
+    providers: Vec<Box<dyn LLMAdapter>>,
+    /// Hash of the keys the providers were built with
+    keys: u64,
+}
+
+impl LLMOrchestrator {
+    /// Every provider `keys` has a key for, plus the local fallback
+    pub fn from_keys(keys: &LlmKeys) -> Self {
+        let mut providers: Vec<Box<dyn LLMAdapter>> = Vec::new();
+        let key = |key: &Option<String>| {
+            key.as_deref()
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+        };
+
+        // 1. OpenRouter (primary)
+        if let Some(key) = key(&keys.openrouter) {
+            providers.push(Box::new(openrouter::OpenRouterAdapter::new(key)));
+            tracing::info!("LLM: OpenRouter adapter loaded");
+        }
+
+        // 2. Groq (secondary)
+        if let Some(key) = key(&keys.groq).filter(|key| key.starts_with("gsk_")) {
+            providers.push(Box::new(groq::GroqLLMAdapter::new(key)));
+            tracing::info!("LLM: Groq adapter loaded");
+        }
+
+        // 3. Gemini (tertiary)
+        if let Some(key) = key(&keys.gemini) {
+            providers.push(Box::new(gemini::GeminiAdapter::new(key)));
+            tracing::info!("LLM: Gemini adapter loaded");
+        }
+
+        // 4. Ollama (local fallback — always available)
+        providers.push(Box::new(ollama::OllamaAdapter::new()));
+        tracing::info!("LLM: Ollama adapter loaded (local fallback)");
+
+        tracing::info!("LLM Orchestrator: {} providers available", providers.len());
+
+        Self {
+            providers,
+            keys: key_fingerprint(keys),
+        }
+    }
+
+    /// Fixed providers, in failover order, built without keys
+    pub fn with_providers(providers: Vec<Box<dyn LLMAdapter>>) -> Self {
+        Self {
+            providers,
+            keys: key_fingerprint(&LlmKeys::default()),
+        }
+    }
+
+    /// True when `keys` differ from the ones this was built with
+    pub fn keys_changed(&self, keys: &LlmKeys) -> bool {
+        self.keys != key_fingerprint(keys)
+    }
+
+    /// Generate text with failover across all providers, starting with the pinned one.
+    /// Returns the text and the provider that wrote it; when a pin could not be honored
+    /// the provider says why, e.g. `groq (pinned 'gemini' failed)`.
+    pub async fn generate(
+        &self,
+        prompt: &str,
+        pin: Option<ProviderPin<'_>>,
+    ) -> Result<(String, String), LLMError> {
+        let mut last_error = LLMError::AllProvidersFailed;
+        let mut note = None;
+        // A pin without a model is the provider's normal attempt; no need to repeat it
+        let mut tried = None;
+
+        if let Some(pin) = pin {
+            match self
+                .providers
+                .iter()
+                .position(|provider| provider.name().eq_ignore_ascii_case(pin.provider))
+            {
+                Some(index) => {
+                    let provider = &self.providers[index];
+                    tracing::info!(
+                        "LLM: Trying pinned provider '{}' (model {})...",
+                        provider.name(),
+                        pin.model.unwrap_or("default")
+                    );
+                    let result = match pin.model {
+                        Some(model) => provider.generate_with_model(prompt, model).await,
+                        None => provider.generate(prompt).await,
+                    };
+                    match result {
+                        Ok(text) => {
+                            tracing::info!(
+                                "LLM: pinned '{}' succeeded ({} chars)",
+                                provider.name(),
+                                text.len()
+                            );
+                            let label = match pin.model {
+                                Some(model) => format!("{} ({})", provider.name(), model),
+                                None => provider.name().to_string(),
+                            };
+                            return Ok((text, label));
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "LLM: pinned '{}' failed: {:?}, using the normal order",
+                                provider.name(),
+                                e
+                            );
+                            note = Some(format!("pinned '{}' failed", pin.provider));
+                            last_error = e;
+                            if pin.model.is_none() {
+                                tried = Some(index);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    let reason = if is_known_provider(pin.provider) {
+                        "not configured"
+                    } else {
+                        "unknown"
+                    };
+                    tracing::warn!(
+                        "LLM: pinned provider '{}' is {}, using the normal order",
+                        pin.provider,
+                        reason
+                    );
+                    note = Some(format!("pinned '{}' {}", pin.provider, reason));
+                }
+            }
+        }
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            if tried == Some(index) {
+                continue;
+            }
+            tracing::info!("LLM: Trying provider '{}'...", provider.name());
+
+            match provider.generate(prompt).await {
+                Ok(text) => {
+                    tracing::info!(
+                        "LLM: '{}' succeeded ({} chars)",
+                        provider.name(),
+                        text.len()
+                    );
+                    let label = match &note {
+                        Some(note) => format!("{} ({})", provider.name(), note),
+                        None => provider.name().to_string(),
+                    };
+                    return Ok((text, label));
+                }
+                Err(e) => {
+                    tracing::warn!("LLM: '{}' failed: {:?}", provider.name(), e);
+                    last_error = e;
+                }
+            }
+        }
+
+        tracing::error!("LLM: All providers failed");
+        Err(last_error)
+    }
+}
+
+/// Hash of the provider keys, so they can be compared without being kept around
+fn key_fingerprint(keys: &LlmKeys) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    keys.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Records `name:model` for every call; fails when `fails` is set or the model is `missing`.
+    struct MockAdapter {
+        name: &'static str,
+        fails: bool,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockAdapter {
+        fn answer(&self, model: &str) -> Result<String, LLMError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", self.name, model));
+            if self.fails || model == "missing" {
+                Err(LLMError::ProviderError(format!("{} is down", self.name)))
+            } else {
+                Ok(format!("from {}", self.name))
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LLMAdapter for MockAdapter {
+        async fn generate(&self, _prompt: &str) -> Result<String, LLMError> {
+            self.answer("default")
+        }
+
+        async fn generate_with_model(
+            &self,
+            _prompt: &str,
+            model: &str,
+        ) -> Result<String, LLMError> {
+            self.answer(model)
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn orchestrator(failing: &[&str]) -> (LLMOrchestrator, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let providers = ["openrouter", "groq", "ollama"]
+            .into_iter()
+            .map(|name| {
+                Box::new(MockAdapter {
+                    name,
+                    fails: failing.contains(&name),
+                    calls: calls.clone(),
+                }) as Box<dyn LLMAdapter>
+            })
+            .collect();
+        (LLMOrchestrator::with_providers(providers), calls)
+    }
+
+    fn pin<'a>(provider: &'a str, model: Option<&'a str>) -> Option<ProviderPin<'a>> {
+        Some(ProviderPin { provider, model })
+    }
+
+    #[tokio::test]
+    async fn test_pinned_provider_and_model_are_tried_first() {
+        let (llm, calls) = orchestrator(&[]);
+        let (text, provider) = llm
+            .generate("prompt", pin("Groq", Some("llama-3.1-8b-instant")))
+            .await
+            .unwrap();
+        assert_eq!(text, "from groq");
+        assert_eq!(provider, "groq (llama-3.1-8b-instant)");
+        assert_eq!(*calls.lock().unwrap(), vec!["groq:llama-3.1-8b-instant"]);
+
+        let (llm, calls) = orchestrator(&[]);
+        let (_, provider) = llm.generate("prompt", None).await.unwrap();
+        assert_eq!(provider, "openrouter");
+        assert_eq!(*calls.lock().unwrap(), vec!["openrouter:default"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_pin_falls_back_to_the_normal_order() {
+        let (llm, calls) = orchestrator(&["openrouter", "groq"]);
+        let (text, provider) = llm.generate("prompt", pin("groq", None)).await.unwrap();
+        assert_eq!(text, "from ollama");
+        assert_eq!(provider, "ollama (pinned 'groq' failed)");
+        // The pinned attempt is not repeated in the normal order
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["groq:default", "openrouter:default", "ollama:default"]
+        );
+
+        // A pinned model that fails still leaves the provider's own models to try
+        let (llm, calls) = orchestrator(&["openrouter", "groq"]);
+        let (_, provider) = llm
+            .generate("prompt", pin("ollama", Some("missing")))
+            .await
+            .unwrap();
+        assert_eq!(provider, "ollama (pinned 'ollama' failed)");
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "ollama:missing",
+                "openrouter:default",
+                "groq:default",
+                "ollama:default"
+            ]
+        );
+
+        let (llm, _) = orchestrator(&["openrouter", "groq", "ollama"]);
+        assert!(llm.generate("prompt", pin("groq", None)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_or_unconfigured_pin_uses_the_normal_order() {
+        let (llm, calls) = orchestrator(&[]);
+        let (_, provider) = llm.generate("prompt", pin("gpt-9", None)).await.unwrap();
+        assert_eq!(provider, "openrouter (pinned 'gpt-9' unknown)");
+        assert_eq!(*calls.lock().unwrap(), vec!["openrouter:default"]);
+
+        // Gemini is a real provider, just not loaded here
+        let (_, provider) = llm.generate("prompt", pin("gemini", None)).await.unwrap();
+        assert_eq!(provider, "openrouter (pinned 'gemini' not configured)");
+
+        assert!(is_known_provider("OpenRouter"));
+        assert!(!is_known_provider("gpt-9"));
+    }
+}
@@ -2,6 +2,7 @@
 
 use super::LLMAdapter;
 use crate::prompt_engine::types::LLMError;
+use crate::provider_error::ProviderApiError;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -67,10 +68,10 @@ impl OllamaAdapter {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(LLMError::ProviderError(format!(
-                "Ollama {} ({}): {}",
-                model, status, body
-            )));
+            return Err(LLMError::ProviderApi {
+                provider: format!("Ollama {}", model),
+                error: ProviderApiError::parse(status.as_u16(), &body),
+            });
         }
 
         let ollama: OllamaResponse = response
@@ -105,6 +106,10 @@ impl LLMAdapter for OllamaAdapter {
         }
     }
 
+    async fn generate_with_model(&self, prompt: &str, model: &str) -> Result<String, LLMError> {
+        self.call_model(model, prompt).await
+    }
+
     fn name(&self) -> &str {
         "ollama"
     }
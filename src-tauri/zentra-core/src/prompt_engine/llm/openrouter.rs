@@ -2,6 +2,7 @@
 
 use super::LLMAdapter;
 use crate::prompt_engine::types::LLMError;
+use crate::provider_error::ProviderApiError;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -80,10 +81,10 @@ impl OpenRouterAdapter {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(LLMError::ProviderError(format!(
-                "OpenRouter {} ({}): {}",
-                model, status, body
-            )));
+            return Err(LLMError::ProviderApi {
+                provider: format!("OpenRouter {}", model),
+                error: ProviderApiError::parse(status.as_u16(), &body),
+            });
         }
 
         let chat: ChatResponse = response
@@ -111,6 +112,10 @@ impl LLMAdapter for OpenRouterAdapter {
         }
     }
 
+    async fn generate_with_model(&self, prompt: &str, model: &str) -> Result<String, LLMError> {
+        self.call_model(model, prompt).await
+    }
+
     fn name(&self) -> &str {
         "openrouter"
     }
@@ -0,0 +1,30 @@
+// prompt_engine/llm/replay.rs — Replays a recorded LLM response instead of calling a provider
+
+use super::LLMAdapter;
+use crate::prompt_engine::types::LLMError;
+use async_trait::async_trait;
+
+/// Answers every prompt with the response recorded with a fixture, so optimize replays
+/// exactly. Without a recorded response it fails, as the provider did at capture time.
+pub struct ReplayLLMAdapter {
+    response: Option<String>,
+}
+
+impl ReplayLLMAdapter {
+    pub fn new(response: Option<String>) -> Self {
+        Self { response }
+    }
+}
+
+#[async_trait]
+impl LLMAdapter for ReplayLLMAdapter {
+    async fn generate(&self, _prompt: &str) -> Result<String, LLMError> {
+        self.response
+            .clone()
+            .ok_or_else(|| LLMError::ProviderError("no response was recorded".to_string()))
+    }
+
+    fn name(&self) -> &str {
+        "replay"
+    }
+}
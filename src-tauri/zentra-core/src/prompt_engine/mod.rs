@@ -0,0 +1,530 @@
+// prompt_engine/mod.rs - Main Prompt Engine
+
+mod types;
+mod profiles;
+pub mod clarity;
+pub mod dictation;
+mod llm;
+
+pub use profiles::USER_PROFILES_FILE;
+pub use types::{EngineError, LLMError, OptimizationMode, OptimizedPrompt, Profile};
+
+use crate::error_codes::ErrorCode;
+use crate::privacy::PrivacyGuard;
+pub use llm::LlmKeys;
+use llm::{LLMOrchestrator, ProviderPin};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Prompt Engine - transforms transcripts into optimized LLM prompts
+pub struct PromptEngine {
+    /// Built-in profiles with the user's on top
+    profiles: HashMap<String, Profile>,
+    built_in: HashMap<String, Profile>,
+    /// Created or edited by the user; an id shared with a built-in shadows it
+    user: HashMap<String, Profile>,
+    user_profiles_path: Option<PathBuf>,
+    llm: LLMOrchestrator,
+    mode: OptimizationMode,
+    privacy: PrivacyGuard,
+}
+
+impl PromptEngine {
+    /// Create from the profiles config file, with only the local LLM until
+    /// [`Self::reload_llm`] passes the keys
+    pub fn new() -> Self {
+        let built_in = Self::load_built_in();
+        let llm = LLMOrchestrator::from_keys(&LlmKeys::default());
+
+        tracing::info!(
+            "PromptEngine initialized: {} profiles, mode=ClarityOnly",
+            built_in.len()
+        );
+
+        Self {
+            profiles: built_in.clone(),
+            built_in,
+            user: HashMap::new(),
+            user_profiles_path: None,
+            llm,
+            mode: OptimizationMode::ClarityOnly,
+            privacy: PrivacyGuard::default(),
+        }
+    }
+
+    /// Share the app-wide privacy switch; while it is on, AI mode only applies clarity.
+    pub fn with_privacy(mut self, privacy: PrivacyGuard) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// An engine in AI mode with `profile` whose LLM answers with the recorded `response`,
+    /// or fails when there is none. Used to replay fixtures.
+    pub(crate) fn replaying(profile: Profile, response: Option<String>) -> Self {
+        let llm = LLMOrchestrator::with_providers(vec![Box::new(
+            llm::replay::ReplayLLMAdapter::new(response),
+        )]);
+        let built_in = HashMap::from([(profile.id.clone(), profile)]);
+        Self {
+            profiles: built_in.clone(),
+            built_in,
+            user: HashMap::new(),
+            user_profiles_path: None,
+            llm,
+            mode: OptimizationMode::AIOptimize,
+            privacy: PrivacyGuard::default(),
+        }
+    }
+
+    /// Optimize a transcript using the given profile; `language` picks the clarity rules
+    pub async fn optimize(
+        &self,
+        transcript: &str,
+        profile_id: &str,
+        language: &str,
+    ) -> Result<OptimizedPrompt, EngineError> {
+        let profile = self
+            .profiles
+            .get(profile_id)
+            .ok_or_else(|| EngineError::ProfileNotFound(profile_id.to_string()))?;
+
+        // The LLM providers are remote, so the transcript stays here in privacy mode.
+        let mode = if self.privacy.allows_provider(false) {
+            self.mode.clone()
+        } else {
+            OptimizationMode::ClarityOnly
+        };
+        match mode {
+            OptimizationMode::ClarityOnly => {
+                let cleaned = clarity::transform(transcript, language);
+                let text = self.apply_template(profile, &cleaned);
+
+                Ok(OptimizedPrompt {
+                    text,
+                    profile_used: profile_id.to_string(),
+                    mode: OptimizationMode::ClarityOnly,
+                    provider: None,
+                    confidence: 1.0,
+                })
+            }
+            OptimizationMode::AIOptimize => {
+                // First apply clarity, then send to LLM
+                let cleaned = clarity::transform(transcript, language);
+                let prompt = self.build_llm_prompt(profile, &cleaned);
+
+                // Truncate to ~3000 tokens (~12000 chars)
+                let truncated = if prompt.len() > 12000 {
+                    format!("{}...[TRUNCATED]", &prompt[..12000])
+                } else {
+                    prompt
+                };
+
+                let pin = profile
+                    .preferred_provider
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|provider| !provider.is_empty())
+                    .map(|provider| ProviderPin {
+                        provider,
+                        model: profile
+                            .preferred_model
+                            .as_deref()
+                            .map(str::trim)
+                            .filter(|model| !model.is_empty()),
+                    });
+
+                match self.llm.generate(&truncated, pin).await {
+                    Ok((text, provider)) => Ok(OptimizedPrompt {
+                        text,
+                        profile_used: profile_id.to_string(),
+                        mode: OptimizationMode::AIOptimize,
+                        provider: Some(provider),
+                        confidence: 0.85,
+                    }),
+                    Err(e) => {
+                        tracing::warn!(
+                            "LLM failed ({}), falling back to clarity-only: {:?}",
+                            ErrorCode::from(&e),
+                            e
+                        );
+                        // Graceful fallback to clarity-only
+                        let text = self.apply_template(profile, &cleaned);
+                        Ok(OptimizedPrompt {
+                            text,
+                            profile_used: profile_id.to_string(),
+                            mode: OptimizationMode::ClarityOnly,
+                            provider: None,
+                            confidence: 0.5,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// Set the optimization mode
+    pub fn set_mode(&mut self, mode: OptimizationMode) {
+        if self.mode != mode {
+            tracing::info!("PromptEngine mode changed to: {:?}", mode);
+        }
+        self.mode = mode;
+    }
+
+    pub fn mode(&self) -> &OptimizationMode {
+        &self.mode
+    }
+
+    /// List available profiles, sorted by id
+    pub fn list_profiles(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<&Profile> = self.profiles.values().collect();
+        profiles.sort_by(|a, b| a.id.cmp(&b.id));
+        profiles
+    }
+
+    /// Keep user profiles in `path` from now on and load the ones already there.
+    pub fn set_user_profiles_path(&mut self, path: PathBuf) -> Result<(), EngineError> {
+        self.user_profiles_path = Some(path);
+        self.reload_profiles()
+    }
+
+    /// Read the built-in and user profiles again. A broken user file keeps the user
+    /// profiles loaded before it.
+    pub fn reload_profiles(&mut self) -> Result<(), EngineError> {
+        self.built_in = Self::load_built_in();
+        let loaded = match &self.user_profiles_path {
+            Some(path) => profiles::load_user_profiles(path),
+            None => Ok(HashMap::new()),
+        };
+        let result = loaded.map(|user| self.user = user);
+        self.merge();
+        result
+    }
+
+    /// Add a profile with a new id.
+    pub fn create_profile(&mut self, profile: Profile) -> Result<(), EngineError> {
+        let profile = Self::checked(profile)?;
+        if self.profiles.contains_key(&profile.id) {
+            return Err(EngineError::InvalidProfile(format!(
+                "A profile with id '{}' already exists",
+                profile.id
+            )));
+        }
+        let mut user = self.user.clone();
+        user.insert(profile.id.clone(), profile);
+        self.save_user(user)
+    }
+
+    /// Replace the profile with the same id. Editing a built-in saves a copy that shadows it.
+    pub fn update_profile(&mut self, profile: Profile) -> Result<(), EngineError> {
+        let profile = Self::checked(profile)?;
+        if !self.profiles.contains_key(&profile.id) {
+            return Err(EngineError::ProfileNotFound(profile.id));
+        }
+        let mut user = self.user.clone();
+        user.insert(profile.id.clone(), profile);
+        self.save_user(user)
+    }
+
+    /// Delete a user profile. Returns the built-in it shadowed, which is back in use;
+    /// built-ins themselves can't be deleted.
+    pub fn delete_profile(&mut self, id: &str) -> Result<Option<&Profile>, EngineError> {
+        let id = id.trim();
+        if !self.user.contains_key(id) {
+            return Err(if self.built_in.contains_key(id) {
+                EngineError::InvalidProfile(format!("Built-in profile '{}' can't be deleted", id))
+            } else {
+                EngineError::ProfileNotFound(id.to_string())
+            });
+        }
+        let mut user = self.user.clone();
+        user.remove(id);
+        self.save_user(user)?;
+        Ok(self.built_in.get(id))
+    }
+
+    /// Whether `id` is one of the profiles shipped with the app, shadowed or not.
+    pub fn is_built_in(&self, id: &str) -> bool {
+        self.built_in.contains_key(id)
+    }
+
+    /// Rebuild the LLM providers if `keys` differ from the ones they were built with.
+    /// Returns whether they were rebuilt.
+    pub fn reload_llm(&mut self, keys: &LlmKeys) -> bool {
+        if !self.llm.keys_changed(keys) {
+            return false;
+        }
+        self.llm = LLMOrchestrator::from_keys(keys);
+        true
+    }
+
+    // --- Private helpers ---
+
+    fn load_built_in() -> HashMap<String, Profile> {
+        // Resolve config path relative to executable
+        let config_path = Self::resolve_config_path();
+
+        match profiles::load_profiles(&config_path) {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!("Failed to load profiles: {}. Using defaults.", e);
+                Self::default_profiles()
+            }
+        }
+    }
+
+    fn checked(mut profile: Profile) -> Result<Profile, EngineError> {
+        profile.id = profile.id.trim().to_string();
+        profiles::validate_profile(&profile).map_err(EngineError::InvalidProfile)?;
+        profiles::warn_on_unknown_provider(&profile);
+        Ok(profile)
+    }
+
+    /// Write `user` to the user profiles file and use it; nothing changes when the write
+    /// fails.
+    fn save_user(&mut self, user: HashMap<String, Profile>) -> Result<(), EngineError> {
+        let path = self.user_profiles_path.as_ref().ok_or_else(|| {
+            EngineError::ConfigError("No folder to save user profiles in".to_string())
+        })?;
+        profiles::save_user_profiles(path, &user)?;
+        self.user = user;
+        self.merge();
+        Ok(())
+    }
+
+    fn merge(&mut self) {
+        self.profiles = self.built_in.clone();
+        self.profiles.extend(
+            self.user
+                .iter()
+                .map(|(id, profile)| (id.clone(), profile.clone())),
+        );
+    }
+
+    fn apply_template(&self, profile: &Profile, transcript: &str) -> String {
+        let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+
+        let context = profile
+            .context_template
+            .replace("{{transcript}}", transcript)
+            .replace("{{datetime}}", &now)
+            .replace("{{app_context}}", "Voice AI Desktop");
+
+        format!(
+            "# GOAL\n{}\n\n# RETURN FORMAT\n{}\n\n# WARNINGS\n{}\n\n# CONTEXT\n{}",
+            profile.goal,
+            profile.return_format,
+            profile
+                .warnings
+                .iter()
+                .map(|w| format!("- {}", w))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            context
+        )
+    }
+
+    fn build_llm_prompt(&self, profile: &Profile, transcript: &str) -> String {
+        let template = self.apply_template(profile, transcript);
+
+        format!(
+            "Voce e um assistente de otimizacao de prompts.\n\n\
+            O usuario disse o seguinte (transcricao fiel):\n\
+            \"{}\"\n\n\
+            Organize e estruture o que foi dito de forma clara para ser usado como input para uma IA.\n\
+            Mantenha todas as informacoes que o usuario mencionou.\n\
+            Nao invente nem adicione informacoes.\n\n\
+            Template do profile (mantenha GOAL, RETURN FORMAT e WARNINGS como estao; refine apenas CONTEXT):\n\n{}",
+            transcript,
+            template,
+        )
+    }
+
+    fn resolve_config_path() -> String {
+        // Try relative to executable first, then fallback paths
+        let paths = [
+            "config/profiles.json".to_string(),
+            "../config/profiles.json".to_string(),
+            "src-tauri/config/profiles.json".to_string(),
+        ];
+
+        for path in &paths {
+            if std::path::Path::new(path).exists() {
+                return path.clone();
+            }
+        }
+
+        // Default (will be caught by load_profiles error handler)
+        "config/profiles.json".to_string()
+    }
+
+    fn default_profiles() -> HashMap<String, Profile> {
+        let mut profiles = HashMap::new();
+        profiles.insert(
+            "clarity".to_string(),
+            Profile {
+                id: "clarity".to_string(),
+                name: "Clarity Only".to_string(),
+                goal: "Melhorar clareza e gramatica da transcricao".to_string(),
+                return_format: "Texto limpo e correto".to_string(),
+                warnings: vec!["NAO adicionar conteudo extra".to_string()],
+                context_template: "{{transcript}}".to_string(),
+                preferred_provider: None,
+                preferred_model: None,
+            },
+        );
+        profiles
+    }
+}
+
+impl Default for PromptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clarity_only_applies_the_profile_template() {
+        let mut engine = PromptEngine::new();
+        engine.set_mode(OptimizationMode::ClarityOnly);
+        assert!(!engine.reload_llm(&LlmKeys::default()));
+        let keys = LlmKeys {
+            openrouter: Some("sk-or-test".to_string()),
+            ..Default::default()
+        };
+        assert!(engine.reload_llm(&keys));
+        assert!(!engine.reload_llm(&keys));
+
+        let ids: Vec<&str> = engine
+            .list_profiles()
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(ids.contains(&"clarity"));
+
+        let optimized = engine
+            .optimize("reunião amanhã", "clarity", "pt")
+            .await
+            .unwrap();
+        assert_eq!(optimized.profile_used, "clarity");
+        assert_eq!(optimized.mode, OptimizationMode::ClarityOnly);
+        assert!(optimized.provider.is_none());
+        assert!(optimized.text.contains("# GOAL"));
+
+        assert!(matches!(
+            engine.optimize("reunião amanhã", "missing", "pt").await,
+            Err(EngineError::ProfileNotFound(id)) if id == "missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_privacy_mode_keeps_the_transcript_away_from_the_llm() {
+        let privacy = PrivacyGuard::new(true);
+        let engine = PromptEngine::replaying(
+            profile("email", "Write an email"),
+            Some("Resposta do LLM".to_string()),
+        )
+        .with_privacy(privacy.clone());
+
+        let optimized = engine.optimize("reunião amanhã", "email", "pt").await.unwrap();
+        assert_eq!(optimized.mode, OptimizationMode::ClarityOnly);
+        assert!(optimized.provider.is_none());
+        assert_ne!(optimized.text, "Resposta do LLM");
+
+        privacy.set_enabled(false);
+        let optimized = engine.optimize("reunião amanhã", "email", "pt").await.unwrap();
+        assert_eq!(optimized.mode, OptimizationMode::AIOptimize);
+        assert_eq!(optimized.text, "Resposta do LLM");
+    }
+
+    fn profile(id: &str, goal: &str) -> Profile {
+        Profile {
+            id: id.to_string(),
+            name: id.to_string(),
+            goal: goal.to_string(),
+            return_format: "Texto".to_string(),
+            warnings: Vec::new(),
+            context_template: "{{transcript}}".to_string(),
+            preferred_provider: None,
+            preferred_model: None,
+        }
+    }
+
+    fn goal_of<'a>(engine: &'a PromptEngine, id: &str) -> Option<&'a str> {
+        engine
+            .list_profiles()
+            .into_iter()
+            .find(|p| p.id == id)
+            .map(|p| p.goal.as_str())
+    }
+
+    #[test]
+    fn test_user_profiles_shadow_built_ins_and_are_saved() {
+        let dir = std::env::temp_dir().join(format!("zentra-profiles-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(USER_PROFILES_FILE);
+        let mut engine = PromptEngine::new();
+        engine.set_user_profiles_path(path.clone()).unwrap();
+        let original = goal_of(&engine, "clarity").unwrap().to_string();
+
+        engine
+            .create_profile(profile(" notes ", "Resumir a reunião"))
+            .unwrap();
+        assert_eq!(goal_of(&engine, "notes"), Some("Resumir a reunião"));
+        assert!(matches!(
+            engine.create_profile(profile("notes", "Outra")),
+            Err(EngineError::InvalidProfile(_))
+        ));
+        assert!(matches!(
+            engine.create_profile(profile("empty", " ")),
+            Err(EngineError::InvalidProfile(_))
+        ));
+        assert!(matches!(
+            engine.update_profile(profile("missing", "x")),
+            Err(EngineError::ProfileNotFound(_))
+        ));
+
+        // Editing a built-in shadows it; deleting the copy brings the original back.
+        engine.update_profile(profile("clarity", "Sombra")).unwrap();
+        assert_eq!(goal_of(&engine, "clarity"), Some("Sombra"));
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("\"return_format\""), "{}", raw);
+
+        let mut reopened = PromptEngine::new();
+        reopened.set_user_profiles_path(path.clone()).unwrap();
+        assert_eq!(goal_of(&reopened, "clarity"), Some("Sombra"));
+        assert_eq!(goal_of(&reopened, "notes"), Some("Resumir a reunião"));
+
+        let restored = engine.delete_profile("clarity").unwrap().cloned();
+        assert_eq!(restored.map(|p| p.goal), Some(original.clone()));
+        assert_eq!(goal_of(&engine, "clarity"), Some(original.as_str()));
+        assert!(matches!(
+            engine.delete_profile("clarity"),
+            Err(EngineError::InvalidProfile(_))
+        ));
+        assert!(engine.is_built_in("clarity"));
+        assert!(engine.delete_profile("notes").unwrap().is_none());
+        assert!(matches!(
+            engine.delete_profile("notes"),
+            Err(EngineError::ProfileNotFound(_))
+        ));
+
+        reopened.reload_profiles().unwrap();
+        assert_eq!(goal_of(&reopened, "notes"), None);
+        assert_eq!(goal_of(&reopened, "clarity"), Some(original.as_str()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_profiles_cannot_be_saved_without_a_folder() {
+        let mut engine = PromptEngine::new();
+        assert!(matches!(
+            engine.create_profile(profile("notes", "Resumir")),
+            Err(EngineError::ConfigError(_))
+        ));
+        assert_eq!(goal_of(&engine, "notes"), None);
+    }
+}
@@ -0,0 +1,93 @@
+// prompt_engine/profiles.rs — Profile loading and validation
+
+use std::collections::HashMap;
+use std::path::Path;
+use super::llm::{is_known_provider, PROVIDER_IDS};
+use super::types::{EngineError, Profile, ProfilesConfig, StoredProfile, StoredProfiles};
+
+/// File in the app data config folder holding the user's own and edited profiles
+pub const USER_PROFILES_FILE: &str = "user_profiles.json";
+
+/// Load profiles from a JSON file path
+pub fn load_profiles(path: &str) -> Result<HashMap<String, Profile>, EngineError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| EngineError::ConfigError(format!("Failed to read {}: {}", path, e)))?;
+
+    let config: ProfilesConfig = serde_json::from_str(&content)
+        .map_err(|e| EngineError::ConfigError(format!("Invalid JSON in {}: {}", path, e)))?;
+
+    let mut profiles = HashMap::new();
+    for profile in config.profiles {
+        validate_profile(&profile).map_err(EngineError::ConfigError)?;
+        warn_on_unknown_provider(&profile);
+        profiles.insert(profile.id.clone(), profile);
+    }
+
+    tracing::info!("Loaded {} profiles from {}", profiles.len(), path);
+    Ok(profiles)
+}
+
+/// Load the user profiles file; a missing file means the user has none yet.
+pub fn load_user_profiles(path: &Path) -> Result<HashMap<String, Profile>, EngineError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    load_profiles(&path.to_string_lossy())
+}
+
+/// Write the user profiles through a temporary file, sorted by id.
+pub fn save_user_profiles(
+    path: &Path,
+    profiles: &HashMap<String, Profile>,
+) -> Result<(), EngineError> {
+    let mut sorted: Vec<&Profile> = profiles.values().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+    let stored = StoredProfiles {
+        profiles: sorted.into_iter().map(StoredProfile::from).collect(),
+    };
+    let failed =
+        |e: String| EngineError::ConfigError(format!("Failed to save {}: {}", path.display(), e));
+    let raw = serde_json::to_string_pretty(&stored).map_err(|e| failed(e.to_string()))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| failed(e.to_string()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, raw).map_err(|e| failed(e.to_string()))?;
+    std::fs::rename(&tmp, path).map_err(|e| failed(e.to_string()))
+}
+
+/// A profile needs an id and a goal; the rest may be empty.
+pub fn validate_profile(profile: &Profile) -> Result<(), String> {
+    if profile.id.trim().is_empty() || profile.goal.trim().is_empty() {
+        return Err(format!(
+            "Profile missing required fields: id='{}', goal='{}'",
+            profile.id, profile.goal
+        ));
+    }
+    Ok(())
+}
+
+/// A bad pin is not fatal, the profile just uses the normal provider order, but it is
+/// worth a line in the log at load time rather than on every optimization.
+pub(super) fn warn_on_unknown_provider(profile: &Profile) {
+    match profile.preferred_provider.as_deref().map(str::trim) {
+        Some(provider) if !provider.is_empty() => {
+            if !is_known_provider(provider) {
+                tracing::warn!(
+                    "Profile '{}' pins unknown LLM provider '{}' (known: {}); it will use the normal order",
+                    profile.id,
+                    provider,
+                    PROVIDER_IDS.join(", ")
+                );
+            }
+        }
+        _ => {
+            if profile.preferred_model.is_some() {
+                tracing::warn!(
+                    "Profile '{}' sets preferred_model without preferred_provider; it is ignored",
+                    profile.id
+                );
+            }
+        }
+    }
+}
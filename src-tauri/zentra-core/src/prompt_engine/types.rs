@@ -0,0 +1,132 @@
+// prompt_engine/types.rs — Core types for Prompt Engine
+
+use crate::provider_error::ProviderApiError;
+use serde::{Deserialize, Serialize};
+
+/// A template profile for prompt optimization. Read from `profiles.json` in snake_case,
+/// sent to the dashboard in camelCase and accepted back from it in either.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub goal: String,
+    #[serde(alias = "returnFormat")]
+    pub return_format: String,
+    pub warnings: Vec<String>,
+    #[serde(alias = "contextTemplate")]
+    pub context_template: String,
+    /// LLM provider id tried first in AI mode, before the normal failover order
+    #[serde(default, alias = "preferredProvider")]
+    pub preferred_provider: Option<String>,
+    /// Model for the pinned provider instead of its own default
+    #[serde(default, alias = "preferredModel")]
+    pub preferred_model: Option<String>,
+}
+
+/// Optimization mode selector
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum OptimizationMode {
+    AIOptimize,
+    #[default]
+    ClarityOnly,
+}
+
+/// Result of prompt optimization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizedPrompt {
+    pub text: String,
+    pub profile_used: String,
+    pub mode: OptimizationMode,
+    pub provider: Option<String>,
+    pub confidence: f32,
+}
+
+/// Prompt Engine errors
+#[derive(Debug, thiserror::Error)]
+pub enum EngineError {
+    #[error("Config error: {0}")]
+    ConfigError(String),
+
+    #[error("Profile not found: {0}")]
+    ProfileNotFound(String),
+
+    /// A create, update or delete the profile rules refuse
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+
+    #[error("LLM error: {0}")]
+    LLMError(String),
+
+    #[error("Template error: {0}")]
+    TemplateError(String),
+}
+
+/// LLM adapter errors
+#[derive(Debug, thiserror::Error)]
+pub enum LLMError {
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    #[error("Provider error: {0}")]
+    ProviderError(String),
+
+    /// A non-2xx answer whose body said what went wrong
+    #[error("{provider}: {error}")]
+    ProviderApi {
+        provider: String,
+        error: ProviderApiError,
+    },
+
+    #[error("Invalid response from LLM")]
+    InvalidResponse,
+
+    #[error("Timeout")]
+    Timeout,
+
+    #[error("All LLM providers failed")]
+    AllProvidersFailed,
+}
+
+/// JSON structure for profiles.json
+#[derive(Debug, Deserialize)]
+pub struct ProfilesConfig {
+    pub profiles: Vec<Profile>,
+}
+
+/// A profile as written to the user profiles file: snake_case, like the built-in file.
+#[derive(Debug, Serialize)]
+pub struct StoredProfile<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub goal: &'a str,
+    pub return_format: &'a str,
+    pub warnings: &'a [String],
+    pub context_template: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_provider: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_model: Option<&'a str>,
+}
+
+impl<'a> From<&'a Profile> for StoredProfile<'a> {
+    fn from(profile: &'a Profile) -> Self {
+        Self {
+            id: &profile.id,
+            name: &profile.name,
+            goal: &profile.goal,
+            return_format: &profile.return_format,
+            warnings: &profile.warnings,
+            context_template: &profile.context_template,
+            preferred_provider: profile.preferred_provider.as_deref(),
+            preferred_model: profile.preferred_model.as_deref(),
+        }
+    }
+}
+
+/// JSON structure the user profiles file is written with
+#[derive(Debug, Serialize)]
+pub struct StoredProfiles<'a> {
+    pub profiles: Vec<StoredProfile<'a>>,
+}
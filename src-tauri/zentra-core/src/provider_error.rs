@@ -0,0 +1,257 @@
+//! Error bodies of the providers' HTTP APIs. Groq and the other OpenAI-style APIs answer
+//! `{"error": {"message", "type", "code"}}`, ElevenLabs `{"detail": ...}` and Ollama
+//! `{"error": "..."}`. The message, code and type are read from whichever shape the body
+//! has, so users see what the provider said instead of a JSON blob; the raw body is kept
+//! only when it is none of them.
+
+use serde_json::Value;
+use std::fmt;
+
+/// Longest raw body kept when it isn't a known shape, e.g. a proxy's HTML error page.
+const MAX_RAW_CHARS: usize = 300;
+
+/// A non-2xx answer from a provider, as the provider described it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderApiError {
+    /// HTTP status of the response
+    pub status: u16,
+    /// Machine-readable code, e.g. `model_not_found`
+    pub code: Option<String>,
+    pub message: String,
+    /// Error class, e.g. `invalid_request_error`
+    pub kind: Option<String>,
+}
+
+/// What a provider error means for the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorClass {
+    /// The provider couldn't read the uploaded audio
+    InvalidAudio,
+    /// The configured model doesn't exist or the key has no access to it
+    ModelNotFound,
+    /// The request body is over the provider's size limit
+    TooLarge,
+    Other,
+}
+
+impl ProviderApiError {
+    pub fn parse(status: u16, body: &str) -> Self {
+        serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|value| structured(status, &value))
+            .unwrap_or_else(|| Self {
+                status,
+                code: None,
+                message: raw_message(body),
+                kind: None,
+            })
+    }
+
+    pub fn class(&self) -> ApiErrorClass {
+        let code = self.code.as_deref().unwrap_or_default();
+        let kind = self.kind.as_deref().unwrap_or_default();
+        let message = self.message.to_lowercase();
+
+        if self.status == 413
+            || matches!(
+                code,
+                "request_too_large" | "file_too_large" | "payload_too_large"
+            )
+            || message.contains("too large")
+        {
+            ApiErrorClass::TooLarge
+        } else if matches!(code, "model_not_found" | "model_decommissioned")
+            || (message.contains("model")
+                && (message.contains("not found") || message.contains("does not exist")))
+        {
+            ApiErrorClass::ModelNotFound
+        } else if (kind == "invalid_request_error" || matches!(self.status, 400 | 422))
+            && mentions_audio_format(code, &message)
+        {
+            ApiErrorClass::InvalidAudio
+        } else {
+            ApiErrorClass::Other
+        }
+    }
+}
+
+impl fmt::Display for ProviderApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (HTTP {}", self.message, self.status)?;
+        if let Some(code) = self.code.as_deref().or(self.kind.as_deref()) {
+            write!(f, ", {}", code)?;
+        }
+        f.write_str(")")
+    }
+}
+
+fn structured(status: u16, body: &Value) -> Option<ProviderApiError> {
+    let (message, code, kind) = if let Some(error) = body.get("error") {
+        match error {
+            // OpenAI style; Gemini puts its HTTP status in `code` and the class in `status`.
+            Value::Object(_) => (
+                text(error.get("message"))?,
+                text(error.get("code")),
+                text(error.get("type")).or_else(|| text(error.get("status"))),
+            ),
+            // Ollama
+            _ => (text(Some(error))?, None, None),
+        }
+    } else {
+        // ElevenLabs: a `{status, message}` object, a plain string, or FastAPI's list of
+        // validation failures.
+        let detail = body.get("detail")?;
+        match detail {
+            Value::Object(_) => (
+                text(detail.get("message"))?,
+                text(detail.get("status")),
+                None,
+            ),
+            Value::Array(items) => {
+                let messages: Vec<String> = items
+                    .iter()
+                    .filter_map(|item| text(item.get("msg")))
+                    .collect();
+                let kind = items.first().and_then(|item| text(item.get("type")));
+                (non_empty(messages.join("; "))?, None, kind)
+            }
+            _ => (text(Some(detail))?, None, None),
+        }
+    };
+    Some(ProviderApiError {
+        status,
+        code,
+        message,
+        kind,
+    })
+}
+
+/// A non-empty string field.
+fn text(value: Option<&Value>) -> Option<String> {
+    value?
+        .as_str()
+        .map(str::trim)
+        .map(str::to_string)
+        .and_then(non_empty)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+fn raw_message(body: &str) -> String {
+    let body = body.trim();
+    if body.is_empty() {
+        return "No error details".to_string();
+    }
+    match body.char_indices().nth(MAX_RAW_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+fn mentions_audio_format(code: &str, message: &str) -> bool {
+    matches!(code, "invalid_file_format" | "invalid_content")
+        || message.contains("file must be one of")
+        || ((message.contains("audio") || message.contains("file"))
+            && ["format", "decode", "corrupt", "unsupported", "invalid file"]
+                .iter()
+                .any(|word| message.contains(word)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bodies as the providers sent them.
+    const GROQ_BAD_FILE: &str = r#"{"error":{"message":"file must be one of the following types: [flac mp3 mp4 mpeg mpga m4a ogg opus wav webm]","type":"invalid_request_error"}}"#;
+    const GROQ_NO_MODEL: &str = r#"{"error":{"message":"The model `whisper-large-v4` does not exist or you do not have access to it.","type":"invalid_request_error","code":"model_not_found"}}"#;
+    const GROQ_TOO_LARGE: &str = r#"{"error":{"message":"Request Entity Too Large","type":"invalid_request_error","code":"request_too_large"}}"#;
+    const ELEVENLABS_TOO_LARGE: &str = r#"{"detail":{"status":"file_too_large","message":"The uploaded file is too large. Maximum file size is 1GB."}}"#;
+    const ELEVENLABS_BAD_AUDIO: &str = r#"{"detail":{"status":"invalid_content","message":"The audio file could not be decoded."}}"#;
+    const ELEVENLABS_VALIDATION: &str = r#"{"detail":[{"loc":["body","model_id"],"msg":"field required","type":"value_error.missing"}]}"#;
+
+    #[test]
+    fn test_groq_bodies() {
+        let bad_file = ProviderApiError::parse(400, GROQ_BAD_FILE);
+        assert_eq!(bad_file.kind.as_deref(), Some("invalid_request_error"));
+        assert_eq!(bad_file.code, None);
+        assert!(bad_file.message.starts_with("file must be one of"));
+        assert_eq!(bad_file.class(), ApiErrorClass::InvalidAudio);
+
+        let no_model = ProviderApiError::parse(404, GROQ_NO_MODEL);
+        assert_eq!(
+            no_model,
+            ProviderApiError {
+                status: 404,
+                code: Some("model_not_found".to_string()),
+                message:
+                    "The model `whisper-large-v4` does not exist or you do not have access to it."
+                        .to_string(),
+                kind: Some("invalid_request_error".to_string()),
+            }
+        );
+        assert_eq!(no_model.class(), ApiErrorClass::ModelNotFound);
+        assert_eq!(
+            no_model.to_string(),
+            "The model `whisper-large-v4` does not exist or you do not have access to it. (HTTP 404, model_not_found)"
+        );
+
+        let too_large = ProviderApiError::parse(413, GROQ_TOO_LARGE);
+        assert_eq!(too_large.class(), ApiErrorClass::TooLarge);
+    }
+
+    #[test]
+    fn test_elevenlabs_bodies() {
+        let too_large = ProviderApiError::parse(400, ELEVENLABS_TOO_LARGE);
+        assert_eq!(too_large.code.as_deref(), Some("file_too_large"));
+        assert_eq!(too_large.class(), ApiErrorClass::TooLarge);
+
+        let bad_audio = ProviderApiError::parse(400, ELEVENLABS_BAD_AUDIO);
+        assert_eq!(bad_audio.message, "The audio file could not be decoded.");
+        assert_eq!(bad_audio.class(), ApiErrorClass::InvalidAudio);
+
+        let validation = ProviderApiError::parse(422, ELEVENLABS_VALIDATION);
+        assert_eq!(validation.message, "field required");
+        assert_eq!(validation.kind.as_deref(), Some("value_error.missing"));
+        assert_eq!(validation.class(), ApiErrorClass::Other);
+
+        let plain = ProviderApiError::parse(400, r#"{"detail":"Invalid language code"}"#);
+        assert_eq!(plain.message, "Invalid language code");
+        assert_eq!(plain.to_string(), "Invalid language code (HTTP 400)");
+    }
+
+    #[test]
+    fn test_llm_bodies() {
+        let gemini = ProviderApiError::parse(
+            404,
+            r#"{"error":{"code":404,"message":"models/gemini-9 is not found for API version v1beta","status":"NOT_FOUND"}}"#,
+        );
+        assert_eq!(gemini.code, None);
+        assert_eq!(gemini.kind.as_deref(), Some("NOT_FOUND"));
+        assert_eq!(gemini.class(), ApiErrorClass::ModelNotFound);
+
+        let ollama = ProviderApiError::parse(
+            404,
+            r#"{"error":"model \"llama9\" not found, try pulling it first"}"#,
+        );
+        assert_eq!(ollama.class(), ApiErrorClass::ModelNotFound);
+        assert_eq!(ollama.code, None);
+    }
+
+    #[test]
+    fn test_unknown_bodies_fall_back_to_the_raw_text() {
+        let html = ProviderApiError::parse(502, "<html>Bad Gateway</html>");
+        assert_eq!(html.message, "<html>Bad Gateway</html>");
+        assert_eq!(html.class(), ApiErrorClass::Other);
+
+        let other_json = ProviderApiError::parse(500, r#"{"oops":true}"#);
+        assert_eq!(other_json.message, r#"{"oops":true}"#);
+        assert_eq!(
+            ProviderApiError::parse(500, " ").message,
+            "No error details"
+        );
+        let long = ProviderApiError::parse(500, &"é".repeat(1_000));
+        assert_eq!(long.message.chars().count(), MAX_RAW_CHARS + 3);
+    }
+}
@@ -0,0 +1,444 @@
+//! Replay of the post-STT pipeline from a recorded fixture bundle, without the audio. A
+//! bundle holds the segment transcripts as the providers returned them, the settings in
+//! effect, the LLM response if optimize ran, and each stage's output at capture time. The
+//! replay runs today's code over the same inputs, stage by stage, and reports the first
+//! stage whose output no longer matches the recording.
+//!
+//! Bundles are JSON in snake_case, like the config file; see `fixtures/replay` for two
+//! examples. Every `cargo test` replays the bundles in that folder.
+
+use crate::prompt_engine::{Profile, PromptEngine};
+use crate::session::{AudioSegment, Stitcher};
+use crate::stt::Transcript;
+use crate::text::postprocess::{PostProcessingOptions, PostProcessor, ReplacementRule, Stage};
+use crate::text::repetition::collapse_repetitions;
+use crate::text::subtitles::{render, SegmentTiming, TranscriptFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Bundle format this build reads.
+pub const BUNDLE_VERSION: u32 = 1;
+/// Words of matching context shown before a difference.
+const DIFF_CONTEXT_WORDS: usize = 3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("Failed to read fixture: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid fixture: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Fixture version {0} is not supported (expected {BUNDLE_VERSION})")]
+    Version(u32),
+
+    #[error("Optimize failed: {0}")]
+    Optimize(String),
+}
+
+/// Pipeline stages, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStage {
+    /// Repetition loops collapsed in each segment; one line per segment
+    Sanitation,
+    Stitch,
+    Clarity,
+    Replacements,
+    FillerRemoval,
+    DictationCommands,
+    Optimize,
+    Format,
+}
+
+impl ReplayStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sanitation => "sanitation",
+            Self::Stitch => "stitch",
+            Self::Clarity => "clarity",
+            Self::Replacements => "replacements",
+            Self::FillerRemoval => "filler_removal",
+            Self::DictationCommands => "dictation_commands",
+            Self::Optimize => "optimize",
+            Self::Format => "format",
+        }
+    }
+}
+
+impl From<Stage> for ReplayStage {
+    fn from(stage: Stage) -> Self {
+        match stage {
+            Stage::Clarity => Self::Clarity,
+            Stage::Replacements => Self::Replacements,
+            Stage::FillerRemoval => Self::FillerRemoval,
+            Stage::DictationCommands => Self::DictationCommands,
+        }
+    }
+}
+
+/// One segment's transcript as the provider returned it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FixtureSegment {
+    pub text: String,
+    pub provider: String,
+    pub language: Option<String>,
+    pub duration_secs: f32,
+}
+
+/// The settings that shape the text after transcription.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FixtureSettings {
+    pub language: String,
+    pub clarity: bool,
+    pub remove_fillers: bool,
+    pub replacements: Vec<ReplacementRule>,
+    pub dictation_commands: bool,
+    pub collapse_repetitions: bool,
+    pub format: TranscriptFormat,
+}
+
+impl Default for FixtureSettings {
+    fn default() -> Self {
+        Self {
+            language: "auto".to_string(),
+            clarity: false,
+            remove_fillers: false,
+            replacements: Vec::new(),
+            dictation_commands: false,
+            collapse_repetitions: true,
+            format: TranscriptFormat::Txt,
+        }
+    }
+}
+
+/// The profile optimize ran with and what the LLM answered; no response when it failed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordedOptimize {
+    pub profile: Profile,
+    #[serde(default)]
+    pub response: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FixtureBundle {
+    pub version: u32,
+    pub name: String,
+    pub segments: Vec<FixtureSegment>,
+    #[serde(default)]
+    pub settings: FixtureSettings,
+    #[serde(default)]
+    pub optimize: Option<RecordedOptimize>,
+    /// Output of each stage at capture time; stages left out are not compared
+    #[serde(default)]
+    pub recorded: BTreeMap<ReplayStage, String>,
+}
+
+impl FixtureBundle {
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, ReplayError> {
+        let bundle: Self = serde_json::from_str(raw)?;
+        if bundle.version != BUNDLE_VERSION {
+            return Err(ReplayError::Version(bundle.version));
+        }
+        Ok(bundle)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageReplay {
+    pub stage: ReplayStage,
+    pub enabled: bool,
+    pub text: String,
+    pub recorded: Option<String>,
+}
+
+impl StageReplay {
+    pub fn diverged(&self) -> bool {
+        self.recorded
+            .as_ref()
+            .is_some_and(|recorded| *recorded != self.text)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    pub name: String,
+    pub stages: Vec<StageReplay>,
+    pub output: String,
+    /// Text report of the stages, see [`ReplayReport::render`]
+    pub summary: String,
+}
+
+impl ReplayReport {
+    /// The first stage whose output differs from the recording; later ones usually differ
+    /// only because of it.
+    pub fn first_divergence(&self) -> Option<&StageReplay> {
+        self.stages.iter().find(|stage| stage.diverged())
+    }
+
+    /// One line per stage, with the differing words under each stage that diverged:
+    ///
+    /// ```text
+    /// replay pt-meeting: 1 of 6 recorded stages differ, first at clarity
+    ///   sanitation      same
+    ///   clarity         differs from word 4
+    ///                   - recorded: … a reunião de amanhã
+    ///                   + replayed: … a Reunião de amanhã
+    ///   filler_removal  off, not recorded
+    /// ```
+    pub fn render(&self) -> String {
+        let recorded = self.stages.iter().filter(|s| s.recorded.is_some()).count();
+        let diverged = self.stages.iter().filter(|s| s.diverged()).count();
+        let mut out = match self.first_divergence() {
+            Some(first) => format!(
+                "replay {}: {} of {} recorded stages differ, first at {}\n",
+                self.name,
+                diverged,
+                recorded,
+                first.stage.name()
+            ),
+            None => format!(
+                "replay {}: all {} recorded stages match\n",
+                self.name, recorded
+            ),
+        };
+
+        for stage in &self.stages {
+            let status = match (&stage.recorded, stage.enabled) {
+                (Some(recorded), _) if *recorded != stage.text => {
+                    let diff = WordDiff::new(recorded, &stage.text);
+                    format!(
+                        "differs from word {}\n{:18}- recorded: {}\n{:18}+ replayed: {}",
+                        diff.word + 1,
+                        "",
+                        diff.recorded,
+                        "",
+                        diff.replayed
+                    )
+                }
+                (Some(_), _) => "same".to_string(),
+                (None, true) => "not recorded".to_string(),
+                (None, false) => "off, not recorded".to_string(),
+            };
+            out.push_str(&format!("  {:16}{}\n", stage.stage.name(), status));
+        }
+        out
+    }
+}
+
+/// Where two texts part ways, word by word: the words from the first difference to the
+/// end of it, with a few matching words before for context.
+struct WordDiff {
+    word: usize,
+    recorded: String,
+    replayed: String,
+}
+
+impl WordDiff {
+    fn new(recorded: &str, replayed: &str) -> Self {
+        let old: Vec<&str> = recorded.split_whitespace().collect();
+        let new: Vec<&str> = replayed.split_whitespace().collect();
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let context = prefix.saturating_sub(DIFF_CONTEXT_WORDS);
+        let excerpt = |words: &[&str]| {
+            let mut text = words[context..words.len() - suffix].join(" ");
+            if context > 0 {
+                text.insert_str(0, "… ");
+            }
+            if suffix > 0 {
+                text.push_str(" …");
+            }
+            text
+        };
+        Self {
+            word: prefix,
+            recorded: excerpt(&old),
+            replayed: excerpt(&new),
+        }
+    }
+}
+
+/// Run the bundle's inputs through the current pipeline. Nothing leaves the machine: the
+/// LLM is replaced by the recorded response.
+pub async fn replay(bundle: &FixtureBundle) -> Result<ReplayReport, ReplayError> {
+    let settings = &bundle.settings;
+    let mut stages = Vec::new();
+    let mut push = |stage: ReplayStage, enabled: bool, text: &str| {
+        stages.push(StageReplay {
+            stage,
+            enabled,
+            text: text.to_string(),
+            recorded: bundle.recorded.get(&stage).cloned(),
+        });
+    };
+
+    let sanitized: Vec<String> = bundle
+        .segments
+        .iter()
+        .map(|segment| match collapse_repetitions(&segment.text) {
+            Some(collapsed) if settings.collapse_repetitions => collapsed.text,
+            _ => segment.text.clone(),
+        })
+        .collect();
+    push(
+        ReplayStage::Sanitation,
+        settings.collapse_repetitions,
+        &sanitized.join("\n"),
+    );
+
+    let segments: Vec<AudioSegment> = bundle
+        .segments
+        .iter()
+        .zip(&sanitized)
+        .enumerate()
+        .map(|(index, (fixture, text))| {
+            let mut segment = AudioSegment::new(fixture.duration_secs, index as u32);
+            segment.set_transcript(Transcript {
+                text: text.clone(),
+                confidence: 1.0,
+                language: fixture.language.clone(),
+                duration_secs: fixture.duration_secs,
+                provider: fixture.provider.clone(),
+                ..Default::default()
+            });
+            segment
+        })
+        .collect();
+    let stitched = Stitcher::stitch_available(&segments);
+    push(ReplayStage::Stitch, true, &stitched);
+
+    let detected = bundle
+        .segments
+        .iter()
+        .find_map(|segment| segment.language.as_deref());
+    let options = PostProcessingOptions {
+        language: settings.language.clone(),
+        clarity: settings.clarity,
+        remove_fillers: settings.remove_fillers,
+        replacements: settings.replacements.clone(),
+        dictation_commands: settings.dictation_commands,
+    }
+    .with_detected_language(detected);
+    let processed = PostProcessor::new(options).run(&stitched);
+    for stage in &processed.stages {
+        push(stage.stage.into(), stage.enabled, &stage.text);
+    }
+
+    let text = match &bundle.optimize {
+        Some(optimize) => {
+            let profile_id = optimize.profile.id.clone();
+            let engine =
+                PromptEngine::replaying(optimize.profile.clone(), optimize.response.clone());
+            engine
+                .optimize(&processed.output, &profile_id, &settings.language)
+                .await
+                .map_err(|e| ReplayError::Optimize(e.to_string()))?
+                .text
+        }
+        None => processed.output,
+    };
+    push(ReplayStage::Optimize, bundle.optimize.is_some(), &text);
+
+    let timings: Vec<SegmentTiming> = bundle
+        .segments
+        .iter()
+        .zip(&sanitized)
+        .map(|(segment, text)| SegmentTiming {
+            duration_secs: segment.duration_secs,
+            word_count: text.split_whitespace().count() as u32,
+        })
+        .collect();
+    let output = render(settings.format, &text, &timings, &bundle.name);
+    push(ReplayStage::Format, true, &output);
+
+    let mut report = ReplayReport {
+        name: bundle.name.clone(),
+        stages,
+        output,
+        summary: String::new(),
+    };
+    report.summary = report.render();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundles() -> Vec<(String, FixtureBundle)> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/replay");
+        let mut bundles: Vec<(String, FixtureBundle)> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .map(|path| {
+                let bundle = FixtureBundle::load(&path)
+                    .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+                (path.display().to_string(), bundle)
+            })
+            .collect();
+        bundles.sort_by(|a, b| a.0.cmp(&b.0));
+        bundles
+    }
+
+    #[tokio::test]
+    async fn test_every_bundle_replays_as_recorded() {
+        let bundles = bundles();
+        assert!(bundles.len() >= 2);
+        for (path, bundle) in bundles {
+            let report = replay(&bundle).await.unwrap();
+            assert!(
+                report.first_divergence().is_none(),
+                "{}\n{}",
+                path,
+                report.summary
+            );
+            assert_eq!(
+                Some(&report.output),
+                bundle.recorded.get(&ReplayStage::Format)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_a_changed_stage_is_reported_with_its_words() {
+        let (_, mut bundle) = bundles().remove(0);
+        let recorded = bundle.recorded.get_mut(&ReplayStage::Clarity).unwrap();
+        *recorded = recorded.replacen(' ', " extra ", 5);
+
+        let report = replay(&bundle).await.unwrap();
+        assert_eq!(
+            report.first_divergence().map(|stage| stage.stage),
+            Some(ReplayStage::Clarity)
+        );
+        assert!(report.summary.contains("1 of"));
+        assert!(report.summary.contains("first at clarity"));
+        assert!(report.summary.contains("differs from word 2"));
+        assert!(report.summary.contains("- recorded: "));
+        assert!(report.summary.contains("extra"));
+    }
+
+    #[test]
+    fn test_other_versions_are_refused() {
+        let raw = r#"{"version": 2, "name": "future", "segments": []}"#;
+        assert!(matches!(
+            FixtureBundle::parse(raw),
+            Err(ReplayError::Version(2))
+        ));
+    }
+}
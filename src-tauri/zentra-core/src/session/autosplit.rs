@@ -0,0 +1,100 @@
+//! Where to cut a recording into session segments while it is still running: in the first
+//! long pause once the segment is [`MIN_SEGMENT_SECS`] long, or at the quietest point before
+//! [`MAX_CHUNK_SECS`] when the speaker never pauses.
+
+use super::chunking::{chunk_boundaries, MAX_CHUNK_SECS};
+use super::{energy_frame_len, frame_rms, SPEECH_FRAME_RMS};
+use crate::audio::AudioBuffer;
+use std::time::Duration;
+
+/// Shortest segment cut at a pause; earlier pauses are left alone so short dictations stay
+/// one request.
+pub const MIN_SEGMENT_SECS: f32 = 20.0;
+/// Quiet needed for a cut, so breaths and gaps between words don't end a segment.
+pub const MIN_PAUSE_MS: usize = 700;
+/// How often a running recording is checked for a cut.
+pub const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The sample offset (interleaved, exclusive) where the segment recorded so far in `audio`
+/// should end, or `None` to keep recording. A pause is cut in its middle so neither side
+/// loses the tail or onset of a word.
+pub fn next_cut(audio: &AudioBuffer) -> Option<usize> {
+    let channels = audio.channels.max(1) as usize;
+    let samples_per_sec = audio.sample_rate.max(1) as usize * channels;
+    let frame = energy_frame_len(audio);
+    let total = audio.samples.len();
+    let pause_frames = (MIN_PAUSE_MS * samples_per_sec / 1000).div_ceil(frame);
+    let min_len = (MIN_SEGMENT_SECS * samples_per_sec as f32) as usize;
+
+    let mut quiet = 0usize;
+    let mut frame_start = min_len / frame * frame;
+    while frame_start + frame <= total {
+        if frame_rms(&audio.samples[frame_start..frame_start + frame]) < SPEECH_FRAME_RMS {
+            quiet += 1;
+            if quiet == pause_frames {
+                let pause_start = frame_start + frame - quiet * frame;
+                return Some(pause_start + quiet / 2 * frame);
+            }
+        } else {
+            quiet = 0;
+        }
+        frame_start += frame;
+    }
+
+    let max_len = (MAX_CHUNK_SECS * samples_per_sec as f32) as usize;
+    (total > max_len).then(|| chunk_boundaries(audio, MAX_CHUNK_SECS)[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    /// A steady tone with silent gaps, each given as (start, length) in seconds.
+    fn speech_with_gaps(secs: f32, gaps: &[(f32, f32)]) -> AudioBuffer {
+        let total = (secs * RATE as f32) as usize;
+        let samples = (0..total)
+            .map(|idx| {
+                let at = idx as f32 / RATE as f32;
+                if gaps
+                    .iter()
+                    .any(|&(start, len)| at >= start && at < start + len)
+                {
+                    0
+                } else {
+                    ((idx as f32 * 0.05).sin() * 8_000.0) as i16
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut audio = AudioBuffer::new(RATE, 1);
+        audio.append(&samples);
+        audio
+    }
+
+    #[test]
+    fn test_cuts_in_the_middle_of_the_first_long_pause_after_the_minimum() {
+        let audio = speech_with_gaps(40.0, &[(10.0, 1.0), (24.0, 0.3), (30.0, 1.0)]);
+        let cut = next_cut(&audio).expect("a pause after 20 s");
+        let at = cut as f32 / RATE as f32;
+        assert!((at - 30.35).abs() < 0.03, "cut at {at}s");
+        assert_eq!(audio.samples[cut], 0);
+    }
+
+    #[test]
+    fn test_waits_until_the_pause_is_long_enough() {
+        assert_eq!(next_cut(&speech_with_gaps(15.0, &[(5.0, 2.0)])), None);
+        assert_eq!(next_cut(&speech_with_gaps(30.5, &[(30.0, 1.0)])), None);
+        assert!(next_cut(&speech_with_gaps(30.8, &[(30.0, 1.0)])).is_some());
+    }
+
+    #[test]
+    fn test_cuts_before_the_limit_when_nobody_pauses() {
+        assert_eq!(next_cut(&speech_with_gaps(54.0, &[])), None);
+
+        let audio = speech_with_gaps(56.0, &[(50.0, 0.1)]);
+        let cut = next_cut(&audio).expect("a hard cut past the limit");
+        assert!(cut as f32 / RATE as f32 <= MAX_CHUNK_SECS);
+        assert_eq!(audio.samples[cut], 0, "the hard cut should land in the gap");
+    }
+}
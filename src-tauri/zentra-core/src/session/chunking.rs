@@ -0,0 +1,460 @@
+//! Split recordings that exceed the provider limit into chunks cut at quiet points, then
+//! transcribe them in order and merge the result like session segments. A
+//! [`ChunkProgress`] file keeps finished chunks so a long file resumes after a restart.
+
+use super::{derive_duration_secs, energy_frame_len, frame_rms, AudioSegment, Stitcher};
+use crate::audio::AudioBuffer;
+use crate::orchestrator::{FailoverOrchestrator, OrchestratorError};
+use crate::stt::Transcript;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Longest recording sent as a single request (Groq rejects anything above 59 s).
+pub const CHUNK_THRESHOLD_SECS: f32 = 59.0;
+/// Target chunk length, leaving headroom below the provider limit.
+pub const MAX_CHUNK_SECS: f32 = 55.0;
+/// How far back from the chunk limit to look for a quiet point.
+const BOUNDARY_SEARCH_SECS: f32 = 15.0;
+
+/// Sample offsets (interleaved, exclusive) where each chunk ends; the last one is
+/// `audio.samples.len()`. Each cut lands on the quietest ~20 ms frame within the last
+/// [`BOUNDARY_SEARCH_SECS`] of the allowed window so words are not cut in half.
+pub fn chunk_boundaries(audio: &AudioBuffer, max_chunk_secs: f32) -> Vec<usize> {
+    let channels = audio.channels.max(1) as usize;
+    let samples_per_sec = audio.sample_rate.max(1) as usize * channels;
+    let frame = energy_frame_len(audio);
+    let max_len = ((max_chunk_secs * samples_per_sec as f32) as usize / channels) * channels;
+    let search_len = (BOUNDARY_SEARCH_SECS * samples_per_sec as f32) as usize;
+    let total = audio.samples.len();
+
+    let mut boundaries = Vec::new();
+    let mut start = 0usize;
+
+    while total - start > max_len {
+        let window_end = start + max_len;
+        let search_start = window_end.saturating_sub(search_len).max(start + frame);
+
+        let mut best_cut = window_end;
+        let mut best_rms = f32::MAX;
+        let mut frame_start = window_end.saturating_sub(frame);
+        while frame_start >= search_start {
+            let rms = frame_rms(&audio.samples[frame_start..frame_start + frame]);
+            if rms < best_rms {
+                best_rms = rms;
+                best_cut = frame_start;
+            }
+            frame_start -= frame;
+        }
+
+        boundaries.push(best_cut);
+        start = best_cut;
+    }
+
+    boundaries.push(total);
+    boundaries
+}
+
+pub fn split_on_silence(audio: &AudioBuffer, max_chunk_secs: f32) -> Vec<AudioBuffer> {
+    let mut start = 0usize;
+    chunk_boundaries(audio, max_chunk_secs)
+        .into_iter()
+        .map(|end| {
+            let mut chunk = AudioBuffer::new(audio.sample_rate, audio.channels);
+            chunk.append(&audio.samples[start..end]);
+            start = end;
+            chunk
+        })
+        .collect()
+}
+
+/// One finished chunk, a line of a progress file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChunkRecord {
+    index: usize,
+    /// End sample of the chunk; a record whose chunk no longer ends there is stale
+    end: usize,
+    transcript: Transcript,
+}
+
+/// The chunks of one long transcription that are done, appended to a JSON-lines file as
+/// each one finishes. Resuming with the same file skips them instead of paying for them
+/// again. The owner deletes the file once the transcript is stored.
+pub struct ChunkProgress {
+    path: PathBuf,
+}
+
+impl ChunkProgress {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Finished chunks by index. Lines that do not parse, such as one cut short by a
+    /// crash, and chunks cut elsewhere than `boundaries` are skipped.
+    fn load(&self, boundaries: &[usize]) -> HashMap<usize, Transcript> {
+        let Ok(raw) = fs::read(&self.path) else {
+            return HashMap::new();
+        };
+        String::from_utf8_lossy(&raw)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<ChunkRecord>(line).ok())
+            .filter(|record| boundaries.get(record.index) == Some(&record.end))
+            .map(|record| (record.index, record.transcript))
+            .collect()
+    }
+
+    fn append(&self, record: &ChunkRecord) {
+        let written = serde_json::to_string(record)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push('\n');
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = written {
+            tracing::warn!(
+                "Failed to save progress of chunk {} to {}: {}",
+                record.index + 1,
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    pub fn remove(&self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!("Failed to remove {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Transcribe `audio` of any length. Short recordings go straight to the orchestrator;
+/// longer ones are chunked, transcribed sequentially and stitched.
+pub async fn transcribe_long(
+    orchestrator: &FailoverOrchestrator,
+    audio: &AudioBuffer,
+) -> Result<Transcript, OrchestratorError> {
+    transcribe_chunks(orchestrator, audio, None).await
+}
+
+/// [`transcribe_long`] that records each finished chunk in `progress` and skips the chunks
+/// it already holds.
+pub async fn transcribe_long_resumable(
+    orchestrator: &FailoverOrchestrator,
+    audio: &AudioBuffer,
+    progress: &ChunkProgress,
+) -> Result<Transcript, OrchestratorError> {
+    transcribe_chunks(orchestrator, audio, Some(progress)).await
+}
+
+async fn transcribe_chunks(
+    orchestrator: &FailoverOrchestrator,
+    audio: &AudioBuffer,
+    progress: Option<&ChunkProgress>,
+) -> Result<Transcript, OrchestratorError> {
+    if derive_duration_secs(audio) <= CHUNK_THRESHOLD_SECS {
+        return orchestrator.transcribe(audio).await;
+    }
+
+    let boundaries = chunk_boundaries(audio, MAX_CHUNK_SECS);
+    let mut done = progress.map_or_else(HashMap::new, |progress| progress.load(&boundaries));
+    tracing::info!(
+        "Audio is {:.1}s, transcribing in {} chunks ({} already done)",
+        derive_duration_secs(audio),
+        boundaries.len(),
+        done.len()
+    );
+
+    let samples_per_sec = (audio.sample_rate.max(1) * audio.channels.max(1) as u32) as f32;
+    let mut segments = Vec::with_capacity(boundaries.len());
+    let mut start = 0usize;
+    for (idx, &end) in boundaries.iter().enumerate() {
+        let transcript = match done.remove(&idx) {
+            Some(transcript) => transcript,
+            None => {
+                let mut chunk = AudioBuffer::new(audio.sample_rate, audio.channels);
+                chunk.append(&audio.samples[start..end]);
+                let transcript = orchestrator.transcribe(&chunk).await?;
+                if let Some(progress) = progress {
+                    progress.append(&ChunkRecord {
+                        index: idx,
+                        end,
+                        transcript: transcript.clone(),
+                    });
+                }
+                transcript
+            }
+        };
+        let duration_secs = (end - start) as f32 / samples_per_sec;
+        let mut segment = AudioSegment::new(duration_secs, idx as u32 + 1);
+        segment.set_transcript(transcript);
+        segments.push(segment);
+        start = end;
+    }
+
+    Ok(merge_chunks(&segments))
+}
+
+fn merge_chunks(segments: &[AudioSegment]) -> Transcript {
+    let transcripts: Vec<&Transcript> = segments
+        .iter()
+        .filter_map(|segment| segment.transcript.as_ref())
+        .collect();
+    let duration_secs: f32 = transcripts.iter().map(|t| t.duration_secs).sum();
+    let confidence = if duration_secs > 0.0 {
+        transcripts
+            .iter()
+            .map(|t| t.confidence * t.duration_secs)
+            .sum::<f32>()
+            / duration_secs
+    } else {
+        0.0
+    };
+
+    // Chunks are contiguous, so each one's timings start where the previous chunk ended.
+    let mut words = Vec::new();
+    let mut timings = Vec::new();
+    let mut offset_secs = 0.0;
+    for transcript in &transcripts {
+        let mut shifted = (*transcript).clone();
+        shifted.shift_timings(offset_secs);
+        words.extend(shifted.words);
+        timings.extend(shifted.segments);
+        offset_secs += transcript.duration_secs;
+    }
+
+    let mut providers: Vec<&str> = Vec::new();
+    for transcript in &transcripts {
+        if !providers.contains(&transcript.provider.as_str()) {
+            providers.push(&transcript.provider);
+        }
+    }
+
+    Transcript {
+        // Every segment carries a transcript, so stitching cannot fail here.
+        text: Stitcher::stitch_transcripts(segments).unwrap_or_default(),
+        confidence,
+        language: transcripts.iter().find_map(|t| t.language.clone()),
+        duration_secs,
+        provider: providers.join("+"),
+        words,
+        segments: timings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{ProviderConfig, DEFAULT_MAX_CONCURRENT};
+    use crate::stt::{STTAdapter, STTError};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const RATE: u32 = 16_000;
+
+    /// Tone with a 0.4 s silent gap centred on every multiple of 15 s.
+    fn three_minute_recording() -> AudioBuffer {
+        let total = RATE as usize * 180;
+        let samples = (0..total)
+            .map(|idx| {
+                let secs = idx as f32 / RATE as f32;
+                let to_gap = (secs - (secs / 15.0).round() * 15.0).abs();
+                if secs > 1.0 && to_gap < 0.2 {
+                    0
+                } else {
+                    ((idx as f32 * 0.05).sin() * 8_000.0) as i16
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut audio = AudioBuffer::new(RATE, 1);
+        audio.append(&samples);
+        audio
+    }
+
+    #[test]
+    fn test_chunks_respect_limit_and_cut_in_silence() {
+        let audio = three_minute_recording();
+        let boundaries = chunk_boundaries(&audio, MAX_CHUNK_SECS);
+        assert_eq!(boundaries.len(), 4);
+        assert_eq!(*boundaries.last().unwrap(), audio.samples.len());
+
+        let mut start = 0;
+        for &end in &boundaries {
+            assert!((end - start) as f32 / RATE as f32 <= MAX_CHUNK_SECS);
+            start = end;
+        }
+        for &cut in &boundaries[..boundaries.len() - 1] {
+            assert_eq!(
+                audio.samples[cut],
+                0,
+                "cut at {}s is not silent",
+                cut / RATE as usize
+            );
+        }
+
+        let chunks = split_on_silence(&audio, MAX_CHUNK_SECS);
+        let total: usize = chunks.iter().map(|c| c.samples.len()).sum();
+        assert_eq!(total, audio.samples.len());
+    }
+
+    struct ChunkAdapter {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for ChunkAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if audio.duration_secs > CHUNK_THRESHOLD_SECS {
+                return Err(STTError::AudioTooLong);
+            }
+            // Each chunk repeats the last word of the previous one, as providers often do.
+            let text = if call == 0 {
+                "parte um".to_string()
+            } else {
+                format!("{} parte {}", call, call + 1)
+            };
+            Ok(Transcript {
+                text,
+                confidence: 0.9,
+                language: Some("pt".to_string()),
+                duration_secs: audio.duration_secs,
+                provider: "mock".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_long_audio_is_chunked_and_merged() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let orchestrator = FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "mock".to_string(),
+            priority: 1,
+            adapter: Box::new(ChunkAdapter {
+                calls: calls.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.5,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }]);
+
+        let transcript = transcribe_long(&orchestrator, &three_minute_recording())
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!((transcript.duration_secs - 180.0).abs() < 0.01);
+        assert_eq!(transcript.provider, "mock");
+        assert_eq!(transcript.language.as_deref(), Some("pt"));
+        assert!(transcript.text.starts_with("Parte um"));
+        assert!(transcript.text.contains("parte 4"));
+    }
+
+    /// Answers every chunk once `online`, and records which chunks it transcribed.
+    struct CheckpointAdapter {
+        online: Arc<std::sync::atomic::AtomicBool>,
+        transcribed: Arc<std::sync::Mutex<Vec<(usize, i64)>>>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for CheckpointAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            let mut transcribed = self.transcribed.lock().unwrap();
+            if !self.online.load(Ordering::SeqCst) && transcribed.len() == 2 {
+                return Err(STTError::NetworkError("connection reset".to_string()));
+            }
+            let fingerprint = (
+                audio.samples.len(),
+                audio.samples.iter().map(|&s| s as i64).sum(),
+            );
+            transcribed.push(fingerprint);
+            Ok(Transcript {
+                text: format!("parte {}", transcribed.len()),
+                confidence: 0.9,
+                duration_secs: audio.duration_secs,
+                provider: "mock".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_transcription_resumes_after_the_last_chunk() {
+        let path = std::env::temp_dir().join(format!(
+            "zentra-chunks-{}.progress.jsonl",
+            uuid::Uuid::new_v4()
+        ));
+        let progress = ChunkProgress::new(path.clone());
+        let audio = three_minute_recording();
+        let online = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let transcribed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let orchestrator = || {
+            FailoverOrchestrator::new(vec![ProviderConfig {
+                id: "mock".to_string(),
+                priority: 1,
+                adapter: Box::new(CheckpointAdapter {
+                    online: online.clone(),
+                    transcribed: transcribed.clone(),
+                }),
+                max_retries: 0,
+                timeout_secs: 10,
+                confidence_threshold: 0.5,
+                local: false,
+                max_concurrent: DEFAULT_MAX_CONCURRENT,
+            }])
+        };
+
+        assert!(
+            transcribe_long_resumable(&orchestrator(), &audio, &progress)
+                .await
+                .is_err()
+        );
+        // A write cut short by the crash, and a record for a chunk cut elsewhere.
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        let stale = r#"{"index":3,"end":12,"transcript":{"text":"velho","confidence":1.0,"durationSecs":1.0,"provider":"mock"}}"#;
+        writeln!(file, "{}", stale).unwrap();
+        file.write_all(br#"{"index":2,"#).unwrap();
+        drop(file);
+
+        online.store(true, Ordering::SeqCst);
+        let transcript = transcribe_long_resumable(&orchestrator(), &audio, &progress)
+            .await
+            .unwrap();
+        let transcribed = transcribed.lock().unwrap();
+        assert_eq!(transcribed.len(), 4);
+        let mut unique = transcribed.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 4, "a chunk was transcribed twice");
+        assert_eq!(transcript.text, "Parte 1 parte 2 parte 3 parte 4");
+        assert!((transcript.duration_secs - 180.0).abs() < 0.01);
+
+        progress.remove();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,113 @@
+//! Per-segment energy data for tuning the silence gate. Each segment gets a heat strip of
+//! one byte per 100 ms (600 bytes per minute) that the dashboard can draw directly.
+
+use super::{frame_rms, AudioSegment};
+use crate::audio::AudioBuffer;
+use serde::Serialize;
+
+/// Width of one heat-map bucket.
+pub const HEAT_FRAME_MS: u32 = 100;
+/// Energy at or below this level maps to 0; 0 dBFS maps to 255.
+const HEAT_FLOOR_DB: f32 = -80.0;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentDiagnostics {
+    pub segment_id: String,
+    pub sequence_number: u32,
+    pub duration_secs: f32,
+    /// The silence gate dropped this segment instead of sending it to a provider.
+    pub gated: bool,
+    pub frame_ms: u32,
+    /// Log-scaled RMS per frame: 0 = silence (≤ -80 dBFS), 255 = full scale.
+    pub energy: Vec<u8>,
+}
+
+impl SegmentDiagnostics {
+    pub fn from_segment(segment: &AudioSegment) -> Option<Self> {
+        let energy = segment.energy.clone()?;
+        Some(Self {
+            segment_id: segment.id.clone(),
+            sequence_number: segment.sequence_number,
+            duration_secs: segment.duration_secs,
+            gated: segment.is_gated(),
+            frame_ms: HEAT_FRAME_MS,
+            energy,
+        })
+    }
+}
+
+/// One byte per [`HEAT_FRAME_MS`] of audio; a trailing partial frame gets its own byte.
+pub fn energy_heat_map(audio: &AudioBuffer) -> Vec<u8> {
+    let frame_len = (audio.sample_rate as usize * HEAT_FRAME_MS as usize / 1000).max(1)
+        * audio.channels.max(1) as usize;
+    audio
+        .samples
+        .chunks(frame_len)
+        .map(|frame| heat_level(frame_rms(frame)))
+        .collect()
+}
+
+fn heat_level(rms: f32) -> u8 {
+    if rms <= 0.0 {
+        return 0;
+    }
+    let db = 20.0 * rms.log10();
+    ((db - HEAT_FLOOR_DB) / -HEAT_FLOOR_DB * 255.0)
+        .round()
+        .clamp(0.0, 255.0) as u8
+}
+
+/// `ZENTRA_SEGMENT_DIAGNOSTICS=1` keeps heat maps for every segment and past finalize.
+pub fn diagnostics_capture_enabled() -> bool {
+    std::env::var("ZENTRA_SEGMENT_DIAGNOSTICS")
+        .map(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes"
+            )
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    /// 2 s of silence with speech-like bursts at 0.5-0.8 s and 1.5-2.0 s.
+    fn bursts() -> AudioBuffer {
+        let samples = (0..RATE as usize * 2)
+            .map(|idx| {
+                let secs = idx as f32 / RATE as f32;
+                if (0.5..0.8).contains(&secs) || secs >= 1.5 {
+                    ((idx as f32 * 0.07).sin() * 6_000.0) as i16
+                } else {
+                    0
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut audio = AudioBuffer::new(RATE, 1);
+        audio.append(&samples);
+        audio
+    }
+
+    #[test]
+    fn test_heat_map_buckets_follow_bursts() {
+        let heat = energy_heat_map(&bursts());
+        assert_eq!(heat.len(), 20);
+
+        let loud: Vec<usize> = (0..heat.len()).filter(|&idx| heat[idx] > 0).collect();
+        assert_eq!(loud, vec![5, 6, 7, 15, 16, 17, 18, 19]);
+        // ~-17 dBFS tone lands in the upper part of the scale.
+        assert!(heat[5] > 150 && heat[5] < 255);
+    }
+
+    #[test]
+    fn test_heat_map_size_is_bounded() {
+        let mut stereo = AudioBuffer::new(48_000, 2);
+        stereo.append(&vec![100i16; 48_000 * 2 * 60]);
+        assert_eq!(energy_heat_map(&stereo).len(), 600);
+    }
+}
@@ -0,0 +1,1844 @@
+﻿use crate::audio::AudioBuffer;
+use crate::error_codes::{describe_orchestrator_error, ErrorCode};
+use crate::orchestrator::FailoverOrchestrator;
+use crate::stt::{LanguagePin, Transcript};
+use crate::text::subtitles::SegmentTiming;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{RwLock as TokioRwLock, Semaphore};
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::Instant;
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub mod autosplit;
+pub mod chunking;
+pub mod diagnostics;
+pub mod ordering;
+pub mod preview;
+pub mod progress;
+pub mod recovery;
+pub mod segment;
+pub mod silence;
+pub mod stats;
+pub mod stitcher;
+pub mod streamed;
+pub mod warmup;
+
+pub use diagnostics::SegmentDiagnostics;
+pub use ordering::ReorderBuffer;
+pub use preview::ClarityPreview;
+pub use progress::{ProgressSink, SegmentProgress, SegmentStatus, SessionProgress, SessionState};
+pub use recovery::{JournalEntry, RecoverableSession, RecoveryKind, RecoveryStore};
+pub use segment::AudioSegment;
+pub use silence::{SharedSilenceGate, SilenceGate};
+pub use stats::{ProviderBreakdown, SessionStats};
+pub use stitcher::{StitchError, Stitcher};
+pub use streamed::SegmentStream;
+
+pub struct SessionStitcher {
+    max_segment_duration_secs: f32,
+    segments: Vec<AudioSegment>,
+    orchestrator: Arc<TokioRwLock<FailoverOrchestrator>>,
+    silence_gate: SharedSilenceGate,
+    current_session_id: Option<String>,
+    max_segments: usize,
+    recovery: Option<RecoveryStore>,
+    next_sequence: u32,
+    reorder: ReorderBuffer<AudioSegment>,
+    paused_at: Option<Instant>,
+    paused_total: Duration,
+    last_activity: Instant,
+    capture_diagnostics: bool,
+    /// Diagnostics of the last finalized session, kept only when capture is on.
+    last_diagnostics: Vec<SegmentDiagnostics>,
+    retained_audio_limit_bytes: usize,
+    progress_sink: Option<Arc<dyn ProgressSink>>,
+    preview: Mutex<ClarityPreview>,
+    /// Segments of this session that failed and were dropped without a transcript
+    lost_segments: u32,
+    /// Language the session's warm-up heard; cleared with the session
+    language_pin: LanguagePin,
+    /// Transcriptions still running; dropped (and so aborted) with the session
+    tasks: JoinSet<SegmentOutcome>,
+    in_flight: Vec<InFlightSegment>,
+    transcription_slots: Arc<Semaphore>,
+}
+
+type SegmentOutcome = (AudioSegment, Result<Transcript, SessionError>);
+
+struct InFlightSegment {
+    task_id: tokio::task::Id,
+    sequence_number: u32,
+    segment_id: String,
+}
+
+/// Provider name on transcripts the silence gate produced without calling a provider.
+pub const SILENCE_GATE_PROVIDER: &str = "SilenceGate";
+/// Provider name on the empty transcript for a recording below the minimum duration.
+pub const TOO_SHORT_PROVIDER: &str = "TooShort";
+/// Segments per session unless a session length is configured (~98 minutes of audio).
+pub const DEFAULT_MAX_SEGMENTS: usize = 100;
+/// Budget for segment audio kept for retries (~17 minutes of 16 kHz mono).
+pub const DEFAULT_RETAINED_AUDIO_MB: usize = 32;
+/// Segments of a session transcribed at once; later ones queue for a slot.
+pub const MAX_PARALLEL_SEGMENTS: usize = 3;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StitchedResult {
+    pub full_text: String,
+    pub total_duration_secs: f32,
+    pub segment_count: u32,
+    pub confidence_avg: f32,
+    pub providers_used: Vec<String>,
+    /// Per-segment timing, in order; what subtitle exports time their cues with
+    #[serde(default)]
+    pub segments: Vec<SegmentTiming>,
+    #[serde(flatten)]
+    pub stats: SessionStats,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentResult {
+    pub segment_id: String,
+    pub status: SegmentStatus,
+    /// `None` while the segment is still being transcribed
+    pub transcript: Option<Transcript>,
+    pub is_final: bool,
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    NoActiveSession,
+    SessionPaused,
+    EmptySession,
+    SegmentTooLong { duration: f32, max: f32 },
+    SegmentLimitReached { max: usize },
+    StitchError(String),
+    TranscriptionFailed(String),
+    SegmentNotFound(String),
+    /// The segment's audio was dropped to stay within the retention budget.
+    AudioNotRetained(String),
+    /// Finalize refused: these segments failed and still have audio to retry with.
+    SegmentsNotTranscribed { segment_ids: Vec<String> },
+    /// A resume asked for a session with no saved segments.
+    NothingToRecover(String),
+}
+
+/// The message commands return, tagged with the error's code.
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            SessionError::NoActiveSession => "No recording session is active".to_string(),
+            SessionError::SessionPaused => "The session is paused".to_string(),
+            SessionError::EmptySession => "The session has no segments".to_string(),
+            SessionError::SegmentTooLong { duration, max } => {
+                format!("Segment is {:.1}s; the limit is {:.0}s", duration, max)
+            }
+            SessionError::SegmentLimitReached { max } => {
+                format!("The session reached its limit of {} segments", max)
+            }
+            SessionError::StitchError(message) => message.clone(),
+            // Usually already tagged by `describe_orchestrator_error`.
+            SessionError::TranscriptionFailed(message) if ErrorCode::untag(message).is_some() => {
+                return f.write_str(message)
+            }
+            SessionError::TranscriptionFailed(message) => message.clone(),
+            SessionError::SegmentNotFound(id) => format!("Segment not found: {}", id),
+            SessionError::AudioNotRetained(id) => {
+                format!("The audio of segment {} is no longer kept", id)
+            }
+            SessionError::SegmentsNotTranscribed { segment_ids } => {
+                format!("Segments still need a retry: {}", segment_ids.join(", "))
+            }
+            SessionError::NothingToRecover(id) => {
+                format!("No saved segments to resume for session {}", id)
+            }
+        };
+        f.write_str(&ErrorCode::from(self).tag(message))
+    }
+}
+
+impl SessionStitcher {
+    pub fn new(orchestrator: Arc<TokioRwLock<FailoverOrchestrator>>) -> Self {
+        Self {
+            max_segment_duration_secs: 59.0,
+            segments: Vec::new(),
+            orchestrator,
+            silence_gate: SharedSilenceGate::default(),
+            current_session_id: None,
+            max_segments: DEFAULT_MAX_SEGMENTS,
+            recovery: None,
+            next_sequence: 1,
+            reorder: ReorderBuffer::new(1),
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            last_activity: Instant::now(),
+            capture_diagnostics: diagnostics::diagnostics_capture_enabled(),
+            last_diagnostics: Vec::new(),
+            retained_audio_limit_bytes: retained_audio_limit_mb() * 1024 * 1024,
+            progress_sink: None,
+            preview: Mutex::new(ClarityPreview::default()),
+            lost_segments: 0,
+            language_pin: LanguagePin::default(),
+            tasks: JoinSet::new(),
+            in_flight: Vec::new(),
+            transcription_slots: Arc::new(Semaphore::new(MAX_PARALLEL_SEGMENTS)),
+        }
+    }
+
+    /// Read gate settings from `gate`, which the app updates when settings change.
+    pub fn with_silence_gate(mut self, gate: SharedSilenceGate) -> Self {
+        self.silence_gate = gate;
+        self
+    }
+
+    /// Refuse new segments once a session reaches `minutes` of audio. `None` restores
+    /// [`DEFAULT_MAX_SEGMENTS`].
+    /// Share the pin the orchestrator sends languages from; each session starts unpinned.
+    pub fn with_language_pin(mut self, language_pin: LanguagePin) -> Self {
+        self.language_pin = language_pin;
+        self
+    }
+
+    pub fn set_max_session_minutes(&mut self, minutes: Option<u32>) {
+        self.max_segments = match minutes {
+            Some(minutes) => {
+                ((minutes as f32 * 60.0) / self.max_segment_duration_secs).ceil() as usize
+            }
+            None => DEFAULT_MAX_SEGMENTS,
+        }
+        .max(1);
+    }
+
+    /// Cap the audio kept for retries across the session. `0` disables retention.
+    pub fn set_retained_audio_limit_mb(&mut self, megabytes: usize) {
+        self.retained_audio_limit_bytes = megabytes * 1024 * 1024;
+        self.enforce_audio_budget();
+    }
+
+    /// Keep energy heat maps for every segment, and past finalize, instead of only for
+    /// segments the silence gate dropped.
+    pub fn set_diagnostics_capture(&mut self, enabled: bool) {
+        self.capture_diagnostics = enabled;
+    }
+
+    pub fn segment_diagnostics(&self, segment_id: &str) -> Option<SegmentDiagnostics> {
+        self.segments
+            .iter()
+            .find(|segment| segment.id == segment_id)
+            .and_then(SegmentDiagnostics::from_segment)
+            .or_else(|| {
+                self.last_diagnostics
+                    .iter()
+                    .find(|diagnostics| diagnostics.segment_id == segment_id)
+                    .cloned()
+            })
+    }
+
+    /// Handle for background segment tasks; they run `transcribe_segment` without the stitcher lock.
+    pub fn orchestrator(&self) -> Arc<TokioRwLock<FailoverOrchestrator>> {
+        self.orchestrator.clone()
+    }
+
+    pub fn silence_gate(&self) -> SharedSilenceGate {
+        self.silence_gate.clone()
+    }
+
+    pub fn set_recovery(&mut self, store: RecoveryStore) {
+        self.recovery = Some(store);
+    }
+
+    /// Report segment status changes to `sink` as they happen.
+    pub fn set_progress_sink(&mut self, sink: Arc<dyn ProgressSink>) {
+        self.progress_sink = Some(sink);
+    }
+
+    /// Start a new session, or with `recovery_id` resume an unfinalized one from its
+    /// journal: its transcribed segments are loaded and new segments continue after them.
+    pub async fn start_session(
+        &mut self,
+        recovery_id: Option<&str>,
+    ) -> Result<String, SessionError> {
+        let Some(session_id) = recovery_id else {
+            let session_id = Uuid::new_v4().to_string();
+            self.current_session_id = Some(session_id.clone());
+            self.reset_segments();
+            tracing::info!("Started new session: {}", session_id);
+            return Ok(session_id);
+        };
+
+        let entries = self
+            .recovery
+            .as_ref()
+            .map(|recovery| recovery.load_segments(session_id))
+            .unwrap_or_default();
+        let Some(last) = entries.last().map(|entry| entry.sequence_number) else {
+            return Err(SessionError::NothingToRecover(session_id.to_string()));
+        };
+        self.current_session_id = Some(session_id.to_string());
+        self.reset_segments();
+        self.segments = entries
+            .into_iter()
+            .map(|entry| {
+                let mut segment = AudioSegment::new(entry.duration_secs, entry.sequence_number);
+                segment.set_transcript(entry.transcript);
+                segment
+            })
+            .collect();
+        self.next_sequence = last + 1;
+        self.reorder.reset(self.next_sequence);
+        self.persist_preview();
+
+        tracing::info!(
+            "Resumed session {} with {} segments",
+            session_id,
+            self.segments.len()
+        );
+        Ok(session_id.to_string())
+    }
+
+    /// Queue the next segment for transcription and return at once with its id. At most
+    /// [`MAX_PARALLEL_SEGMENTS`] run together; each outcome reaches the progress sink when
+    /// it lands and shows in [`get_progress`](Self::get_progress) from then on.
+    ///
+    /// When the session's first segment is below the gate's minimum duration the whole
+    /// recording was a tap: the session ends without a provider call and the result carries
+    /// a [`TOO_SHORT_PROVIDER`] transcript and no segment id.
+    pub fn add_segment(&mut self, audio: AudioBuffer) -> Result<SegmentResult, SessionError> {
+        self.queue_segment(audio, None)
+    }
+
+    /// [`add_segment`](Self::add_segment) for a segment whose upload started while it was
+    /// recorded. Its transcript comes from `stream`; if the stream fails, `audio` is sent
+    /// the buffered way.
+    pub fn add_streamed_segment(
+        &mut self,
+        audio: AudioBuffer,
+        stream: SegmentStream,
+    ) -> Result<SegmentResult, SessionError> {
+        self.queue_segment(audio, Some(stream))
+    }
+
+    fn queue_segment(
+        &mut self,
+        audio: AudioBuffer,
+        stream: Option<SegmentStream>,
+    ) -> Result<SegmentResult, SessionError> {
+        self.collect_finished();
+        let gate = self.silence_gate.get();
+        if self.current_session_id.is_some() && self.next_sequence == 1 {
+            if let Some(transcript) = gate.check_duration(&audio) {
+                self.discard_session()?;
+                return Ok(SegmentResult {
+                    segment_id: String::new(),
+                    status: SegmentStatus::Completed,
+                    transcript: Some(transcript),
+                    is_final: true,
+                });
+            }
+        }
+
+        let segment = self.begin_segment(&audio)?;
+        let sequence_number = segment.sequence_number;
+        let segment_id = segment.id.clone();
+        self.report_segment(
+            &segment_id,
+            sequence_number,
+            SegmentStatus::Transcribing,
+            None,
+        );
+
+        let orchestrator = self.orchestrator.clone();
+        let slots = self.transcription_slots.clone();
+        let sink = self.progress_sink.clone();
+        let task = async move {
+            // The semaphore is never closed, so this only waits for a free slot.
+            let _slot = slots.acquire_owned().await.ok();
+            let result =
+                transcribe_segment(&orchestrator, &gate, sequence_number, &audio, stream).await;
+            report_outcome(sink.as_deref(), &segment.id, sequence_number, &result);
+            (segment, result)
+        };
+        let handle = self.tasks.spawn(task.in_current_span());
+        self.in_flight.push(InFlightSegment {
+            task_id: handle.id(),
+            sequence_number,
+            segment_id: segment_id.clone(),
+        });
+
+        Ok(SegmentResult {
+            segment_id,
+            status: SegmentStatus::Transcribing,
+            transcript: None,
+            is_final: false,
+        })
+    }
+
+    /// Wait for every queued transcription and record its outcome.
+    pub async fn wait_for_segments(&mut self) {
+        while let Some(joined) = self.tasks.join_next_with_id().await {
+            self.finish_task(joined);
+        }
+    }
+
+    /// Record the transcriptions that already finished, without waiting for the rest.
+    fn collect_finished(&mut self) {
+        while let Some(joined) = self.tasks.try_join_next_with_id() {
+            self.finish_task(joined);
+        }
+    }
+
+    fn finish_task(&mut self, joined: Result<(tokio::task::Id, SegmentOutcome), JoinError>) {
+        let task_id = match &joined {
+            Ok((task_id, _)) => *task_id,
+            Err(e) => e.id(),
+        };
+        let Some(position) = self
+            .in_flight
+            .iter()
+            .position(|entry| entry.task_id == task_id)
+        else {
+            return;
+        };
+        let entry = self.in_flight.remove(position);
+
+        match joined.map(|(_, outcome)| outcome) {
+            Ok((mut segment, Ok(transcript))) => {
+                segment.set_transcript(transcript);
+                self.complete_segment(entry.sequence_number, Some(segment));
+            }
+            Ok((segment, Err(_))) => {
+                // Keep the failed segment, with its audio, so it can be retried.
+                let failed = segment.audio.is_some().then_some(segment);
+                self.complete_segment(entry.sequence_number, failed);
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Transcription task for segment {} ended abnormally: {}",
+                    entry.sequence_number,
+                    e
+                );
+                self.complete_segment(entry.sequence_number, None);
+            }
+        }
+    }
+
+    /// Transcribe a segment again from its retained audio. Works for failed segments and
+    /// for replacing a poor transcript.
+    pub async fn retry_segment(&mut self, segment_id: &str) -> Result<SegmentResult, SessionError> {
+        if self.current_session_id.is_none() {
+            return Err(SessionError::NoActiveSession);
+        }
+        self.collect_finished();
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| segment.id == segment_id)
+            .ok_or_else(|| SessionError::SegmentNotFound(segment_id.to_string()))?;
+        let sequence_number = segment.sequence_number;
+        let audio = segment
+            .audio
+            .clone()
+            .ok_or_else(|| SessionError::AudioNotRetained(segment_id.to_string()))?;
+
+        tracing::info!("Retrying segment {}", sequence_number);
+        if let Some(segment) = self
+            .segments
+            .iter_mut()
+            .find(|segment| segment.id == segment_id)
+        {
+            segment.retries += 1;
+        }
+        self.report_segment(
+            segment_id,
+            sequence_number,
+            SegmentStatus::Transcribing,
+            None,
+        );
+        let gate = self.silence_gate.get();
+        let result =
+            transcribe_segment(&self.orchestrator, &gate, sequence_number, &audio, None).await;
+        report_outcome(
+            self.progress_sink.as_deref(),
+            segment_id,
+            sequence_number,
+            &result,
+        );
+        let transcript = result?;
+
+        if let Some(segment) = self
+            .segments
+            .iter_mut()
+            .find(|segment| segment.id == segment_id)
+        {
+            segment.set_transcript(transcript.clone());
+        }
+        if let Some(segment) = self
+            .segments
+            .iter()
+            .find(|segment| segment.id == segment_id)
+        {
+            self.journal(segment);
+        }
+        self.last_activity = Instant::now();
+        self.persist_preview();
+
+        Ok(SegmentResult {
+            segment_id: segment_id.to_string(),
+            status: SegmentStatus::Completed,
+            transcript: Some(transcript),
+            is_final: false,
+        })
+    }
+
+    fn report_segment(
+        &self,
+        segment_id: &str,
+        sequence_number: u32,
+        status: SegmentStatus,
+        detail: Option<String>,
+    ) {
+        report_status(
+            self.progress_sink.as_deref(),
+            segment_id,
+            sequence_number,
+            status,
+            detail,
+        );
+    }
+
+    /// Segments that failed and still have audio for a retry, in sequence order.
+    pub fn failed_segment_ids(&self) -> Vec<String> {
+        self.segments
+            .iter()
+            .filter(|segment| !segment.is_transcribed())
+            .map(|segment| segment.id.clone())
+            .collect()
+    }
+
+    /// Drop retained audio, oldest transcribed segments first, until the session fits the
+    /// budget. A failed segment that loses its audio can no longer be retried and is removed.
+    fn enforce_audio_budget(&mut self) {
+        let mut total: usize = self
+            .segments
+            .iter()
+            .map(AudioSegment::retained_audio_bytes)
+            .sum();
+
+        while total > self.retained_audio_limit_bytes {
+            let victim = self
+                .segments
+                .iter()
+                .position(|segment| segment.audio.is_some() && segment.is_transcribed())
+                .or_else(|| {
+                    self.segments
+                        .iter()
+                        .position(|segment| segment.audio.is_some())
+                });
+            let Some(idx) = victim else {
+                break;
+            };
+
+            total -= self.segments[idx].retained_audio_bytes();
+            if self.segments[idx].is_transcribed() {
+                self.segments[idx].audio = None;
+            } else {
+                let dropped = self.segments.remove(idx);
+                self.lost_segments += 1;
+                tracing::warn!(
+                    "Dropped failed segment {}: retained audio budget exceeded",
+                    dropped.sequence_number
+                );
+            }
+        }
+    }
+
+    /// Validate `audio` and reserve the next sequence number for it.
+    pub fn begin_segment(&mut self, audio: &AudioBuffer) -> Result<AudioSegment, SessionError> {
+        if self.current_session_id.is_none() {
+            return Err(SessionError::NoActiveSession);
+        }
+
+        if self.paused_at.is_some() {
+            return Err(SessionError::SessionPaused);
+        }
+
+        if self.next_sequence as usize > self.max_segments {
+            return Err(SessionError::SegmentLimitReached {
+                max: self.max_segments,
+            });
+        }
+
+        let effective_duration_secs = derive_duration_secs(audio);
+
+        if effective_duration_secs > self.max_segment_duration_secs {
+            return Err(SessionError::SegmentTooLong {
+                duration: effective_duration_secs,
+                max: self.max_segment_duration_secs,
+            });
+        }
+
+        let sequence_number = self.next_sequence;
+        self.next_sequence += 1;
+        let mut segment = AudioSegment::new(effective_duration_secs, sequence_number);
+        segment.energy = Some(diagnostics::energy_heat_map(audio));
+        if self.retained_audio_limit_bytes > 0 {
+            segment.retain_audio(audio);
+        }
+
+        tracing::info!(
+            "Processing segment {} ({:.1}s)",
+            sequence_number,
+            segment.duration_secs
+        );
+        self.report_segment(&segment.id, sequence_number, SegmentStatus::Recording, None);
+        Ok(segment)
+    }
+
+    /// Record a finished (or failed, with `None`) segment. Completions are buffered until
+    /// their predecessors resolve; the returned results are in sequence order.
+    pub fn complete_segment(
+        &mut self,
+        sequence_number: u32,
+        segment: Option<AudioSegment>,
+    ) -> Vec<SegmentResult> {
+        if segment.is_none() {
+            self.lost_segments += 1;
+        }
+        let released = self.reorder.resolve(sequence_number, segment);
+        self.take_released(released)
+    }
+
+    /// Add segments the reorder buffer released, in order, to the session.
+    fn take_released(&mut self, mut released: Vec<AudioSegment>) -> Vec<SegmentResult> {
+        if released.is_empty() {
+            return Vec::new();
+        }
+        if !self.capture_diagnostics {
+            for segment in released.iter_mut().filter(|segment| !segment.is_gated()) {
+                segment.energy = None;
+            }
+        }
+
+        let results = released
+            .iter()
+            .filter_map(|segment| {
+                segment.transcript.clone().map(|transcript| SegmentResult {
+                    segment_id: segment.id.clone(),
+                    status: SegmentStatus::Completed,
+                    transcript: Some(transcript),
+                    is_final: false,
+                })
+            })
+            .collect();
+        for segment in released.iter().filter(|segment| segment.is_transcribed()) {
+            self.journal(segment);
+        }
+        self.segments.extend(released);
+        self.enforce_audio_budget();
+        self.last_activity = Instant::now();
+        self.persist_preview();
+        results
+    }
+
+    /// Pause the active session. Callers stop capture first and pass what was recorded
+    /// since the last segment as `tail`, which is queued before pausing so the words right
+    /// before a pause aren't lost. Nothing is recorded while paused.
+    ///
+    /// A tail that would be the session's first segment and is below the gate's minimum
+    /// duration is dropped rather than ending the session like a tap would.
+    pub fn pause_session(
+        &mut self,
+        tail: Option<AudioBuffer>,
+    ) -> Result<Option<SegmentResult>, SessionError> {
+        if self.current_session_id.is_none() {
+            return Err(SessionError::NoActiveSession);
+        }
+        let queued = match tail.filter(|audio| !audio.samples.is_empty()) {
+            Some(audio) if self.paused_at.is_some() => {
+                tracing::warn!("Dropped {:.1}s recorded while paused", audio.duration_secs);
+                None
+            }
+            Some(audio)
+                if self.next_sequence == 1
+                    && self.silence_gate.get().check_duration(&audio).is_some() =>
+            {
+                tracing::debug!(
+                    "Dropped a {:.1}s tail before the first pause",
+                    audio.duration_secs
+                );
+                None
+            }
+            Some(audio) => Some(self.add_segment(audio)?),
+            None => None,
+        };
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+            tracing::info!("Session paused");
+        }
+        // A paused session can sit for a long time; don't leave its preview held back by
+        // the write throttle meanwhile.
+        self.flush_recovery();
+        Ok(queued)
+    }
+
+    pub fn resume_session(&mut self) -> Result<(), SessionError> {
+        if self.current_session_id.is_none() {
+            return Err(SessionError::NoActiveSession);
+        }
+        if let Some(paused_at) = self.paused_at.take() {
+            let paused_for = paused_at.elapsed();
+            self.paused_total += paused_for;
+            self.last_activity = Instant::now();
+            tracing::info!("Session resumed after {:.1}s", paused_for.as_secs_f32());
+        }
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Whether an idle reaper should auto-finalize. Paused sessions never count as idle.
+    pub fn is_idle(&self, idle_timeout: Duration) -> bool {
+        self.current_session_id.is_some()
+            && self.paused_at.is_none()
+            && self.last_activity.elapsed() >= idle_timeout
+    }
+
+    fn paused_duration(&self) -> Duration {
+        self.paused_total + self.paused_at.map(|at| at.elapsed()).unwrap_or_default()
+    }
+
+    /// Wait for the queued transcriptions, then stitch the session's segments in sequence
+    /// order, however their transcriptions finished.
+    pub async fn finalize_session(&mut self) -> Result<StitchedResult, SessionError> {
+        if self.current_session_id.is_none() {
+            return Err(SessionError::NoActiveSession);
+        }
+        self.wait_for_segments().await;
+
+        // With every task done, only a segment begun and never completed can still hold
+        // its successors back; it is left out as a gap, like a failed segment.
+        let (orphans, missing) = self.reorder.drain();
+        if missing > 0 {
+            tracing::warn!(
+                "{} segment(s) never completed; finalizing without them",
+                missing
+            );
+            self.lost_segments += missing;
+            self.take_released(orphans);
+        }
+
+        if self.segments.is_empty() {
+            return Err(SessionError::EmptySession);
+        }
+
+        let failed = self.failed_segment_ids();
+        if !failed.is_empty() {
+            return Err(SessionError::SegmentsNotTranscribed {
+                segment_ids: failed,
+            });
+        }
+
+        tracing::info!("Finalizing session: {} segments", self.segments.len());
+
+        let full_text = Stitcher::stitch_transcripts(&self.segments)
+            .map_err(|e| SessionError::StitchError(format_stitch_error(e)))?;
+
+        let total_duration_secs: f32 = self.segments.iter().map(|s| s.duration_secs).sum();
+
+        let mut confidence_sum = 0.0f32;
+        let mut confidence_count = 0u32;
+        let mut providers_used: Vec<String> = Vec::new();
+
+        for segment in &self.segments {
+            if let Some(transcript) = segment.transcript.as_ref() {
+                confidence_sum += transcript.confidence;
+                confidence_count += 1;
+
+                if !providers_used.contains(&transcript.provider) {
+                    providers_used.push(transcript.provider.clone());
+                }
+            }
+        }
+
+        let confidence_avg = if confidence_count == 0 {
+            0.0
+        } else {
+            confidence_sum / confidence_count as f32
+        };
+
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| SegmentTiming {
+                duration_secs: segment.duration_secs,
+                word_count: segment.transcript.as_ref().map_or(0, |transcript| {
+                    transcript.text.split_whitespace().count() as u32
+                }),
+            })
+            .collect();
+
+        let stats = SessionStats::from_segments(&self.segments, self.lost_segments);
+        tracing::info!(
+            "Session usage: {} segments, {:.1}s; {}",
+            self.segments.len(),
+            total_duration_secs,
+            stats.summary()
+        );
+
+        let result = StitchedResult {
+            full_text,
+            total_duration_secs,
+            segment_count: self.segments.len() as u32,
+            confidence_avg,
+            providers_used,
+            segments,
+            stats,
+        };
+
+        self.last_diagnostics = if self.capture_diagnostics {
+            self.segments
+                .iter()
+                .filter_map(SegmentDiagnostics::from_segment)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let session_id = self.current_session_id.take();
+        self.reset_segments();
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.clear();
+            if let Some(session_id) = session_id {
+                recovery.clear_segments(&session_id);
+            }
+        }
+
+        tracing::info!(
+            "Session finalized: {} chars, {:.1}s total",
+            result.full_text.len(),
+            result.total_duration_secs
+        );
+
+        Ok(result)
+    }
+
+    /// Drop the active session without producing a result.
+    pub fn discard_session(&mut self) -> Result<(), SessionError> {
+        let Some(session_id) = self.current_session_id.take() else {
+            return Err(SessionError::NoActiveSession);
+        };
+        self.reset_segments();
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.clear();
+            recovery.clear_segments(&session_id);
+        }
+        tracing::info!("Session discarded: {}", session_id);
+        Ok(())
+    }
+
+    fn reset_segments(&mut self) {
+        self.segments.clear();
+        self.lost_segments = 0;
+        self.next_sequence = 1;
+        self.reorder.reset(1);
+        self.paused_at = None;
+        self.paused_total = Duration::ZERO;
+        self.last_activity = Instant::now();
+        self.language_pin.clear();
+        self.tasks = JoinSet::new();
+        self.in_flight.clear();
+    }
+
+    /// Append a transcribed segment to the session's journal.
+    fn journal(&self, segment: &AudioSegment) {
+        let (Some(recovery), Some(session_id), Some(transcript)) = (
+            self.recovery.as_ref(),
+            self.current_session_id.as_deref(),
+            segment.transcript.as_ref(),
+        ) else {
+            return;
+        };
+        let entry = JournalEntry {
+            sequence_number: segment.sequence_number,
+            duration_secs: segment.duration_secs,
+            transcript: transcript.clone(),
+        };
+        if let Err(e) = recovery.append_segment(session_id, &entry) {
+            tracing::warn!(
+                "Failed to journal segment {}: {}",
+                segment.sequence_number,
+                e
+            );
+        }
+    }
+
+    fn persist_preview(&mut self) {
+        let (Some(recovery), Some(session_id)) =
+            (self.recovery.as_mut(), self.current_session_id.as_deref())
+        else {
+            return;
+        };
+        let text = Stitcher::stitch_available(&self.segments);
+        let entry = RecoverableSession::new(
+            RecoveryKind::Session,
+            session_id,
+            text,
+            self.segments.len() as u32,
+        );
+        if let Err(e) = recovery.update(entry) {
+            tracing::warn!("Failed to persist session preview: {}", e);
+        }
+        // Nothing else will come along to write it while paused.
+        if self.paused_at.is_some() {
+            self.flush_recovery();
+        }
+    }
+
+    fn flush_recovery(&mut self) {
+        if let Some(recovery) = self.recovery.as_mut() {
+            if let Err(e) = recovery.flush() {
+                tracing::warn!("Failed to persist session preview: {}", e);
+            }
+        }
+    }
+
+    pub fn get_progress(&mut self) -> SessionProgress {
+        self.collect_finished();
+        let total_duration_secs: f32 = self.segments.iter().map(|s| s.duration_secs).sum();
+
+        let stitched = Stitcher::stitch_available(&self.segments);
+        let current_text = match self.preview.lock() {
+            Ok(mut preview) => preview.update(self.segments.len(), &stitched),
+            Err(_) => crate::prompt_engine::clarity::preview(&stitched),
+        };
+
+        SessionProgress {
+            segment_count: self.segments.len() as u32,
+            total_duration_secs,
+            current_text,
+            state: if self.is_paused() {
+                SessionState::Paused
+            } else {
+                SessionState::Active
+            },
+            paused_duration_secs: self.paused_duration().as_secs_f32(),
+            failed_segment_ids: self.failed_segment_ids(),
+            transcribing_segment_ids: self.transcribing_segment_ids(),
+        }
+    }
+
+    /// Segments still queued or being transcribed, in sequence order.
+    pub fn transcribing_segment_ids(&self) -> Vec<String> {
+        let mut in_flight = self.in_flight.iter().collect::<Vec<_>>();
+        in_flight.sort_by_key(|entry| entry.sequence_number);
+        in_flight
+            .into_iter()
+            .map(|entry| entry.segment_id.clone())
+            .collect()
+    }
+}
+
+fn report_status(
+    sink: Option<&dyn ProgressSink>,
+    segment_id: &str,
+    sequence_number: u32,
+    status: SegmentStatus,
+    detail: Option<String>,
+) {
+    let Some(sink) = sink else {
+        return;
+    };
+    let (provider, error) = match status {
+        SegmentStatus::Completed => (detail, None),
+        SegmentStatus::Failed => (None, detail),
+        SegmentStatus::Recording | SegmentStatus::Transcribing => (None, None),
+    };
+    sink.segment_status(SegmentProgress {
+        segment_id: segment_id.to_string(),
+        sequence_number,
+        status,
+        provider,
+        error,
+    });
+}
+
+fn report_outcome(
+    sink: Option<&dyn ProgressSink>,
+    segment_id: &str,
+    sequence_number: u32,
+    result: &Result<Transcript, SessionError>,
+) {
+    match result {
+        Ok(transcript) => report_status(
+            sink,
+            segment_id,
+            sequence_number,
+            SegmentStatus::Completed,
+            Some(transcript.provider.clone()),
+        ),
+        Err(e) => {
+            let message = match e {
+                SessionError::TranscriptionFailed(message) => message.clone(),
+                other => format!("{:?}", other),
+            };
+            report_status(
+                sink,
+                segment_id,
+                sequence_number,
+                SegmentStatus::Failed,
+                Some(message),
+            )
+        }
+    }
+}
+
+/// Transcribe one segment, from its `stream` when its upload already started. Only takes a
+/// read lock, so background tasks can run in parallel; the orchestrator's per-provider
+/// limiter bounds what is actually sent.
+#[tracing::instrument(name = "segment", skip_all, fields(segment = sequence_number))]
+pub async fn transcribe_segment(
+    orchestrator: &TokioRwLock<FailoverOrchestrator>,
+    gate: &SilenceGate,
+    sequence_number: u32,
+    audio: &AudioBuffer,
+    stream: Option<SegmentStream>,
+) -> Result<Transcript, SessionError> {
+    let metrics = audio_energy_metrics(audio);
+    tracing::info!(
+        "Segment {} energy: rms={:.5}, peak={:.5}, speech_ratio={:.3}",
+        sequence_number,
+        metrics.rms,
+        metrics.peak,
+        metrics.speech_ratio
+    );
+
+    if gate.is_probable_silence(&metrics) {
+        tracing::warn!(
+            "Segment {} skipped: probable silence (rms={:.5}, peak={:.5}, speech_ratio={:.3})",
+            sequence_number,
+            metrics.rms,
+            metrics.peak,
+            metrics.speech_ratio
+        );
+        tracing::debug!(
+            "Segment {} energy per {}ms: {:?}",
+            sequence_number,
+            diagnostics::HEAT_FRAME_MS,
+            metrics.heat_map
+        );
+
+        return Ok(silence::gated_transcript(audio));
+    }
+
+    let sent_at = Instant::now();
+    let streamed = match stream {
+        Some(stream) => stream
+            .finish()
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    "Segment {} streaming upload failed, sending it buffered: {}",
+                    sequence_number,
+                    e
+                )
+            })
+            .ok(),
+        None => None,
+    };
+    let upload = if streamed.is_some() {
+        "streaming"
+    } else {
+        "buffered"
+    };
+    let transcript_result = match streamed {
+        Some(transcript) => Ok(transcript),
+        None => orchestrator.read().await.transcribe(audio).await,
+    };
+    tracing::info!(
+        target: "perf",
+        "Segment {} came back in {} ms ({} upload)",
+        sequence_number,
+        sent_at.elapsed().as_millis(),
+        upload
+    );
+
+    match transcript_result {
+        Ok(transcript) => {
+            tracing::info!(
+                "Segment {} transcribed: provider={}, confidence={:.2}, text_len={}",
+                sequence_number,
+                transcript.provider,
+                transcript.confidence,
+                transcript.text.len()
+            );
+            Ok(transcript)
+        }
+        Err(e) => {
+            tracing::error!("Segment {} failed: {:?}", sequence_number, e);
+            Err(SessionError::TranscriptionFailed(describe_orchestrator_error(&e)))
+        }
+    }
+}
+
+fn derive_duration_secs(audio: &AudioBuffer) -> f32 {
+    if audio.duration_secs > 0.05 {
+        return audio.duration_secs;
+    }
+
+    let sample_rate = audio.sample_rate as f32;
+    let channels = audio.channels.max(1) as f32;
+    if sample_rate <= 0.0 {
+        return 0.0;
+    }
+
+    audio.samples.len() as f32 / (sample_rate * channels)
+}
+
+fn format_stitch_error(err: StitchError) -> String {
+    match err {
+        StitchError::SegmentNotTranscribed(id) => format!("Segment not transcribed: {}", id),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AudioEnergyMetrics {
+    rms: f32,
+    peak: f32,
+    speech_ratio: f32,
+    heat_map: Vec<u8>,
+}
+
+fn audio_energy_metrics(audio: &AudioBuffer) -> AudioEnergyMetrics {
+    if audio.samples.is_empty() {
+        return AudioEnergyMetrics {
+            rms: 0.0,
+            peak: 0.0,
+            speech_ratio: 0.0,
+            heat_map: Vec::new(),
+        };
+    }
+
+    let mut sum_squares = 0.0f32;
+    let mut peak = 0.0f32;
+
+    for &sample in &audio.samples {
+        let normalized = sample as f32 / i16::MAX as f32;
+        let abs = normalized.abs();
+        sum_squares += normalized * normalized;
+        if abs > peak {
+            peak = abs;
+        }
+    }
+
+    let rms = (sum_squares / audio.samples.len() as f32).sqrt();
+
+    let frame_size = energy_frame_len(audio);
+    let mut total_frames = 0usize;
+    let mut speech_frames = 0usize;
+
+    let mut idx = 0usize;
+    while idx < audio.samples.len() {
+        let end = usize::min(idx + frame_size, audio.samples.len());
+        let frame = &audio.samples[idx..end];
+        if !frame.is_empty() {
+            total_frames += 1;
+            if frame_rms(frame) >= SPEECH_FRAME_RMS {
+                speech_frames += 1;
+            }
+        }
+        idx = end;
+    }
+
+    let speech_ratio = if total_frames == 0 {
+        0.0
+    } else {
+        speech_frames as f32 / total_frames as f32
+    };
+
+    AudioEnergyMetrics {
+        rms,
+        peak,
+        speech_ratio,
+        heat_map: diagnostics::energy_heat_map(audio),
+    }
+}
+
+/// Frame RMS from which a frame counts as speech.
+pub(crate) const SPEECH_FRAME_RMS: f32 = 0.003;
+
+/// ~20 ms of interleaved samples.
+pub(crate) fn energy_frame_len(audio: &AudioBuffer) -> usize {
+    (audio.sample_rate as usize / 50).max(160) * audio.channels.max(1) as usize
+}
+
+pub(crate) fn frame_rms(frame: &[i16]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    (frame
+        .iter()
+        .map(|sample| {
+            let normalized = *sample as f32 / i16::MAX as f32;
+            normalized * normalized
+        })
+        .sum::<f32>()
+        / frame.len() as f32)
+        .sqrt()
+}
+
+/// `ZENTRA_RETAINED_AUDIO_MB` overrides [`DEFAULT_RETAINED_AUDIO_MB`].
+fn retained_audio_limit_mb() -> usize {
+    std::env::var("ZENTRA_RETAINED_AUDIO_MB")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_RETAINED_AUDIO_MB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcribed(mut segment: AudioSegment, text: &str) -> AudioSegment {
+        segment.set_transcript(Transcript {
+            text: text.to_string(),
+            confidence: 0.9,
+            language: None,
+            duration_secs: segment.duration_secs,
+            provider: "mock".to_string(),
+            ..Default::default()
+        });
+        segment
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_completions_keep_progress_in_sequence() {
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.start_session(None).await.unwrap();
+
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+        let first = stitcher.begin_segment(&audio).unwrap();
+        let second = stitcher.begin_segment(&audio).unwrap();
+        let third = stitcher.begin_segment(&audio).unwrap();
+
+        let released = stitcher.complete_segment(3, Some(transcribed(third, "terceiro")));
+        assert!(released.is_empty());
+        let released = stitcher.complete_segment(2, Some(transcribed(second, "segundo")));
+        assert!(released.is_empty());
+        assert_eq!(stitcher.get_progress().current_text, "");
+
+        let released = stitcher.complete_segment(1, Some(transcribed(first, "primeiro")));
+        let texts = released
+            .iter()
+            .filter_map(|result| result.transcript.as_ref())
+            .map(|transcript| transcript.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["primeiro", "segundo", "terceiro"]);
+        assert_eq!(
+            stitcher.get_progress().current_text,
+            "Primeiro segundo terceiro"
+        );
+    }
+
+    /// Takes a tenth of the first sample's value in milliseconds and reports the most
+    /// transcriptions it saw running at once.
+    struct DelayedAdapter {
+        running: std::sync::atomic::AtomicUsize,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::stt::STTAdapter for DelayedAdapter {
+        async fn transcribe(
+            &self,
+            audio: &AudioBuffer,
+        ) -> Result<Transcript, crate::stt::STTError> {
+            use std::sync::atomic::Ordering;
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            let value = audio.samples[0];
+            tokio::time::sleep(Duration::from_millis(value as u64 / 10)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(Transcript {
+                text: format!("parte {}", value / 1_000),
+                confidence: 0.9,
+                provider: "delayed".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "delayed"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_parallel_segments_are_stitched_in_sequence() {
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let provider = crate::orchestrator::ProviderConfig {
+            id: "delayed".to_string(),
+            priority: 1,
+            adapter: Box::new(DelayedAdapter {
+                running: Default::default(),
+                peak: peak.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.5,
+            local: false,
+            max_concurrent: 8,
+        };
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(vec![provider])));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.start_session(None).await.unwrap();
+
+        // Later segments finish first: segment 2 is the quickest, segment 1 the slowest.
+        for value in [5_000i16, 1_000, 4_000, 2_000, 3_000] {
+            let mut audio = AudioBuffer::new(16_000, 1);
+            audio.append(&vec![value; 16_000]);
+            let queued = stitcher.add_segment(audio).unwrap();
+            assert_eq!(queued.status, SegmentStatus::Transcribing);
+            assert!(queued.transcript.is_none());
+        }
+        assert_eq!(stitcher.get_progress().transcribing_segment_ids.len(), 5);
+
+        let result = stitcher.finalize_session().await.unwrap();
+        assert_eq!(result.segment_count, 5);
+        assert_eq!(result.full_text, "Parte 5 parte 1 parte 4 parte 2 parte 3");
+        assert_eq!(
+            peak.load(std::sync::atomic::Ordering::SeqCst),
+            MAX_PARALLEL_SEGMENTS
+        );
+    }
+
+    /// Answers streamed uploads with "pelo fluxo", or fails them, and buffered requests
+    /// with "gravado".
+    struct StreamingAdapter {
+        fail_streams: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::stt::STTAdapter for StreamingAdapter {
+        async fn transcribe(
+            &self,
+            _audio: &AudioBuffer,
+        ) -> Result<Transcript, crate::stt::STTError> {
+            Ok(Transcript {
+                text: "gravado".to_string(),
+                confidence: 0.9,
+                ..Default::default()
+            })
+        }
+
+        fn streams(&self) -> bool {
+            true
+        }
+
+        async fn transcribe_streaming(
+            &self,
+            upload: crate::stt::StreamingWav,
+            _language: Option<String>,
+        ) -> Result<Transcript, crate::stt::STTError> {
+            // Keeps the body, and so the feed, open for a while like a real request
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(upload);
+            if self.fail_streams {
+                return Err(crate::stt::STTError::TimeoutError);
+            }
+            Ok(Transcript {
+                text: "pelo fluxo".to_string(),
+                confidence: 0.9,
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "streaming"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_streamed_segments_fall_back_to_a_buffered_upload() {
+        let mut speech = AudioBuffer::new(16_000, 1);
+        speech.append(
+            &(0..16_000)
+                .map(|idx| ((idx as f32 * 0.07).sin() * 6_000.0) as i16)
+                .collect::<Vec<_>>(),
+        );
+
+        for (fail_streams, expected) in [(false, "Pelo fluxo"), (true, "Gravado")] {
+            let provider = crate::orchestrator::ProviderConfig {
+                id: "streaming".to_string(),
+                priority: 1,
+                adapter: Box::new(StreamingAdapter { fail_streams }),
+                max_retries: 0,
+                timeout_secs: 10,
+                confidence_threshold: 0.5,
+                local: false,
+                max_concurrent: 2,
+            };
+            let orchestrator =
+                Arc::new(TokioRwLock::new(FailoverOrchestrator::new(vec![provider])));
+            let mut stitcher = SessionStitcher::new(orchestrator.clone());
+            stitcher.start_session(None).await.unwrap();
+
+            let live = std::sync::Mutex::new(speech.clone());
+            let mut stream = SegmentStream::start(orchestrator, speech.samples.len(), 16_000, 1);
+            assert!(stream.feed_from(&live).await.unwrap());
+            stitcher
+                .add_streamed_segment(speech.clone(), stream)
+                .unwrap();
+
+            let result = stitcher.finalize_session().await.unwrap();
+            assert_eq!(result.full_text, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crashed_session_resumes_from_its_journal() {
+        let dir = std::env::temp_dir().join(format!("zentra-resume-{}", Uuid::new_v4()));
+        let stitcher_at = |dir: &std::path::Path| {
+            let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+            let mut stitcher = SessionStitcher::new(orchestrator);
+            stitcher.set_recovery(RecoveryStore::new(
+                dir.to_path_buf(),
+                crate::privacy::PrivacyGuard::default(),
+            ));
+            stitcher
+        };
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+
+        let mut stitcher = stitcher_at(&dir);
+        let session_id = stitcher.start_session(None).await.unwrap();
+        let first = stitcher.begin_segment(&audio).unwrap();
+        let second = stitcher.begin_segment(&audio).unwrap();
+        stitcher.complete_segment(1, Some(transcribed(first, "primeiro")));
+        stitcher.complete_segment(2, Some(transcribed(second, "segundo")));
+        drop(stitcher);
+
+        let recoverable = recovery::load_recoverable_session(&dir).unwrap();
+        assert_eq!(recoverable.session_id, session_id);
+        assert_eq!(recoverable.segment_count, 2);
+
+        let mut stitcher = stitcher_at(&dir);
+        assert!(matches!(
+            stitcher
+                .start_session(Some(&Uuid::new_v4().to_string()))
+                .await,
+            Err(SessionError::NothingToRecover(_))
+        ));
+        let resumed = stitcher.start_session(Some(&session_id)).await.unwrap();
+        assert_eq!(resumed, session_id);
+        assert_eq!(stitcher.get_progress().segment_count, 2);
+        let third = stitcher.begin_segment(&audio).unwrap();
+        assert_eq!(third.sequence_number, 3);
+        stitcher.complete_segment(3, Some(transcribed(third, "terceiro")));
+
+        let result = stitcher.finalize_session().await.unwrap();
+        assert_eq!(result.segment_count, 3);
+        assert_eq!(result.full_text, "Primeiro segundo terceiro");
+        assert!(recovery::load_recoverable_session(&dir).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_pause_writes_the_preview_the_throttle_held_back() {
+        let dir = std::env::temp_dir().join(format!("zentra-pause-{}", Uuid::new_v4()));
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.set_recovery(RecoveryStore::new(
+            dir.clone(),
+            crate::privacy::PrivacyGuard::default(),
+        ));
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+
+        stitcher.start_session(None).await.unwrap();
+        let first = stitcher.begin_segment(&audio).unwrap();
+        let second = stitcher.begin_segment(&audio).unwrap();
+        stitcher.complete_segment(1, Some(transcribed(first, "primeiro")));
+        stitcher.complete_segment(2, Some(transcribed(second, "segundo")));
+        assert_eq!(recovery::load_recoverable(&dir)[0].segment_count, 1);
+
+        stitcher.pause_session(None).unwrap();
+        let recovered = recovery::load_recoverable(&dir);
+        assert_eq!(recovered[0].segment_count, 2);
+        assert!(recovered[0].text.ends_with("segundo"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_paused_session_rejects_segments_and_is_never_idle() {
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.start_session(None).await.unwrap();
+
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+        let first = stitcher.begin_segment(&audio).unwrap();
+        stitcher.complete_segment(1, Some(transcribed(first, "antes")));
+
+        assert!(stitcher.pause_session(None).unwrap().is_none());
+        assert!(matches!(
+            stitcher.begin_segment(&audio),
+            Err(SessionError::SessionPaused)
+        ));
+
+        let idle_timeout = Duration::from_secs(30);
+        tokio::time::advance(Duration::from_secs(120)).await;
+        assert!(!stitcher.is_idle(idle_timeout));
+
+        let progress = stitcher.get_progress();
+        assert_eq!(progress.state, SessionState::Paused);
+        assert!((progress.paused_duration_secs - 120.0).abs() < 0.01);
+        assert!((progress.total_duration_secs - 1.0).abs() < 0.01);
+
+        stitcher.resume_session().unwrap();
+        assert!(!stitcher.is_idle(idle_timeout));
+        assert!(stitcher.begin_segment(&audio).is_ok());
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert!(stitcher.is_idle(idle_timeout));
+        assert_eq!(stitcher.get_progress().state, SessionState::Active);
+        assert!((stitcher.get_progress().paused_duration_secs - 120.0).abs() < 0.01);
+    }
+
+    #[tokio::test]
+    async fn test_failed_segments_keep_audio_and_block_finalize() {
+        // No providers, so every transcription attempt fails.
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.set_retained_audio_limit_mb(1);
+        stitcher.start_session(None).await.unwrap();
+
+        let mut speech = AudioBuffer::new(16_000, 1);
+        let tone = (0..16_000)
+            .map(|idx| ((idx as f32 * 0.07).sin() * 6_000.0) as i16)
+            .collect::<Vec<_>>();
+        speech.append(&tone);
+
+        let first = stitcher.begin_segment(&speech).unwrap();
+        stitcher.complete_segment(1, Some(transcribed(first, "primeiro")));
+        let queued = stitcher.add_segment(speech.clone()).unwrap();
+        assert_eq!(queued.status, SegmentStatus::Transcribing);
+        stitcher.wait_for_segments().await;
+
+        let failed = stitcher.failed_segment_ids();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(stitcher.get_progress().failed_segment_ids, failed);
+        assert_eq!(stitcher.get_progress().current_text, "Primeiro");
+        assert!(stitcher.retry_segment(&failed[0]).await.is_err());
+        assert!(matches!(
+            stitcher.retry_segment("missing").await,
+            Err(SessionError::SegmentNotFound(_))
+        ));
+        match stitcher.finalize_session().await {
+            Err(SessionError::SegmentsNotTranscribed { segment_ids }) => {
+                assert_eq!(segment_ids, failed)
+            }
+            other => panic!("finalize should refuse, got {:?}", other.map(|_| ())),
+        }
+
+        // Shrinking the budget evicts transcribed audio first, then drops failed segments.
+        stitcher.set_retained_audio_limit_mb(0);
+        assert!(stitcher.failed_segment_ids().is_empty());
+        assert!(stitcher.segments.iter().all(|segment| segment.audio.is_none()));
+        assert!(stitcher.finalize_session().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_segment_leaves_gap_without_blocking() {
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.start_session(None).await.unwrap();
+
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+        let _first = stitcher.begin_segment(&audio).unwrap();
+        let second = stitcher.begin_segment(&audio).unwrap();
+
+        assert!(stitcher
+            .complete_segment(2, Some(transcribed(second, "depois")))
+            .is_empty());
+        let released = stitcher.complete_segment(1, None);
+        assert_eq!(released.len(), 1);
+        assert_eq!(stitcher.get_progress().segment_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_keeps_segments_after_one_that_never_completed() {
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.start_session(None).await.unwrap();
+
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+        let first = stitcher.begin_segment(&audio).unwrap();
+        let _abandoned = stitcher.begin_segment(&audio).unwrap();
+        let third = stitcher.begin_segment(&audio).unwrap();
+        stitcher.complete_segment(1, Some(transcribed(first, "antes")));
+        assert!(stitcher
+            .complete_segment(3, Some(transcribed(third, "depois")))
+            .is_empty());
+
+        let result = stitcher.finalize_session().await.unwrap();
+        assert_eq!(result.full_text, "Antes depois");
+        assert_eq!(result.segment_count, 2);
+        assert_eq!(result.stats.failed_segments, 1);
+    }
+
+    #[tokio::test]
+    async fn test_energy_kept_for_gated_segments_and_dropped_on_finalize() {
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        stitcher.set_diagnostics_capture(false);
+        stitcher.start_session(None).await.unwrap();
+
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![100i16; 16_000]);
+        let mut gated = stitcher.begin_segment(&audio).unwrap();
+        gated.set_transcript(Transcript {
+            provider: SILENCE_GATE_PROVIDER.to_string(),
+            ..Default::default()
+        });
+        let gated_id = gated.id.clone();
+        let spoken = stitcher.begin_segment(&audio).unwrap();
+        let spoken_id = spoken.id.clone();
+        stitcher.complete_segment(1, Some(gated));
+        stitcher.complete_segment(2, Some(transcribed(spoken, "fala")));
+
+        let diagnostics = stitcher.segment_diagnostics(&gated_id).unwrap();
+        assert!(diagnostics.gated);
+        assert_eq!(diagnostics.energy.len(), 10);
+        assert!(stitcher.segment_diagnostics(&spoken_id).is_none());
+
+        stitcher.finalize_session().await.unwrap();
+        assert!(stitcher.segment_diagnostics(&gated_id).is_none());
+
+        stitcher.set_diagnostics_capture(true);
+        stitcher.start_session(None).await.unwrap();
+        let spoken = stitcher.begin_segment(&audio).unwrap();
+        let spoken_id = spoken.id.clone();
+        stitcher.complete_segment(1, Some(transcribed(spoken, "fala")));
+        stitcher.finalize_session().await.unwrap();
+        assert!(!stitcher.segment_diagnostics(&spoken_id).unwrap().gated);
+    }
+
+    #[tokio::test]
+    async fn test_too_short_first_segment_ends_the_session() {
+        // No providers: anything that reaches the orchestrator fails.
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        let samples = |count: usize| {
+            let mut audio = AudioBuffer::new(16_000, 1);
+            audio.append(&vec![3_000i16; count]);
+            audio
+        };
+
+        stitcher.start_session(None).await.unwrap();
+        let result = stitcher.add_segment(samples(3_200)).unwrap();
+        assert_eq!(result.transcript.unwrap().provider, TOO_SHORT_PROVIDER);
+        assert!(result.segment_id.is_empty());
+        assert!(matches!(
+            stitcher.finalize_session().await,
+            Err(SessionError::NoActiveSession)
+        ));
+
+        // Exactly at the threshold goes on to the provider.
+        stitcher.start_session(None).await.unwrap();
+        let first = stitcher.add_segment(samples(11_200)).unwrap();
+        assert_eq!(first.status, SegmentStatus::Transcribing);
+
+        // A short tail after the first segment is part of a longer recording.
+        let tail = stitcher.add_segment(samples(1_600)).unwrap();
+        assert_eq!(tail.status, SegmentStatus::Transcribing);
+        stitcher.wait_for_segments().await;
+        assert_eq!(stitcher.failed_segment_ids().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pause_queues_the_tail_before_pausing() {
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let mut stitcher = SessionStitcher::new(orchestrator);
+        let samples = |count: usize| {
+            let mut audio = AudioBuffer::new(16_000, 1);
+            audio.append(&vec![3_000i16; count]);
+            audio
+        };
+
+        // A tap before the first pause is dropped; the session goes on.
+        stitcher.start_session(None).await.unwrap();
+        assert!(stitcher.pause_session(Some(samples(1_600))).unwrap().is_none());
+        assert!(stitcher.get_progress().transcribing_segment_ids.is_empty());
+        stitcher.resume_session().unwrap();
+
+        stitcher.add_segment(samples(16_000)).unwrap();
+        let tail = stitcher
+            .pause_session(Some(samples(8_000)))
+            .unwrap()
+            .expect("tail queued");
+        assert_eq!(tail.status, SegmentStatus::Transcribing);
+        assert_eq!(stitcher.get_progress().state, SessionState::Paused);
+
+        // Nothing recorded while paused makes it in.
+        assert!(stitcher.pause_session(Some(samples(8_000))).unwrap().is_none());
+        stitcher.wait_for_segments().await;
+        assert_eq!(stitcher.failed_segment_ids().len(), 2);
+        assert!((stitcher.get_progress().total_duration_secs - 1.5).abs() < 0.01);
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(std::sync::Mutex<Vec<SegmentProgress>>);
+
+    impl ProgressSink for RecordingSink {
+        fn segment_status(&self, progress: SegmentProgress) {
+            self.0.lock().unwrap().push(progress);
+        }
+    }
+
+    impl RecordingSink {
+        fn take(&self) -> Vec<(u32, SegmentStatus, Option<String>)> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+                .into_iter()
+                .map(|p| (p.sequence_number, p.status, p.provider.or(p.error)))
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_segment_transitions_reach_the_progress_sink() {
+        // No providers: only the silence gate can complete a segment.
+        let orchestrator = Arc::new(TokioRwLock::new(FailoverOrchestrator::new(Vec::new())));
+        let gate = SharedSilenceGate::default();
+        gate.set(SilenceGate {
+            enabled: true,
+            ..SilenceGate::default()
+        });
+        let mut stitcher = SessionStitcher::new(orchestrator).with_silence_gate(gate);
+        let sink = Arc::new(RecordingSink::default());
+        stitcher.set_progress_sink(sink.clone());
+        stitcher.start_session(None).await.unwrap();
+
+        let mut silence = AudioBuffer::new(16_000, 1);
+        silence.append(&vec![0i16; 16_000]);
+        stitcher.add_segment(silence).unwrap();
+        stitcher.wait_for_segments().await;
+        assert_eq!(
+            sink.take(),
+            vec![
+                (1, SegmentStatus::Recording, None),
+                (1, SegmentStatus::Transcribing, None),
+                (
+                    1,
+                    SegmentStatus::Completed,
+                    Some(SILENCE_GATE_PROVIDER.to_string())
+                ),
+            ]
+        );
+
+        let mut speech = AudioBuffer::new(16_000, 1);
+        speech.append(&vec![3_000i16; 16_000]);
+        stitcher.add_segment(speech).unwrap();
+        stitcher.wait_for_segments().await;
+        let events = sink.take();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].0, 2);
+        assert_eq!(events[2].1, SegmentStatus::Failed);
+        assert!(events[2].2.is_some());
+
+        let failed = stitcher.failed_segment_ids();
+        assert!(stitcher.retry_segment(&failed[0]).await.is_err());
+        let statuses: Vec<_> = sink
+            .take()
+            .into_iter()
+            .map(|(_, status, _)| status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![SegmentStatus::Transcribing, SegmentStatus::Failed]
+        );
+    }
+
+    #[test]
+    fn test_ipc_results_serialize_camel_case() {
+        let progress = SessionProgress {
+            segment_count: 2,
+            total_duration_secs: 4.5,
+            current_text: "Olá".to_string(),
+            state: SessionState::Paused,
+            paused_duration_secs: 1.0,
+            failed_segment_ids: vec!["s2".to_string()],
+            transcribing_segment_ids: vec!["s3".to_string()],
+        };
+        assert_eq!(
+            serde_json::to_value(&progress).unwrap(),
+            serde_json::json!({
+                "segmentCount": 2,
+                "totalDurationSecs": 4.5,
+                "currentText": "Olá",
+                "state": "paused",
+                "pausedDurationSecs": 1.0,
+                "failedSegmentIds": ["s2"],
+                "transcribingSegmentIds": ["s3"]
+            })
+        );
+
+        let stitched = StitchedResult {
+            full_text: "Olá".to_string(),
+            total_duration_secs: 4.5,
+            segment_count: 2,
+            confidence_avg: 0.5,
+            providers_used: vec!["Groq".to_string()],
+            segments: vec![SegmentTiming {
+                duration_secs: 4.5,
+                word_count: 1,
+            }],
+            stats: SessionStats {
+                provider_breakdown: vec![ProviderBreakdown {
+                    provider: "Groq".to_string(),
+                    segments: 2,
+                    avg_confidence: 0.5,
+                }],
+                retried_segments: 1,
+                gated_segments: 0,
+                failed_segments: 0,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&stitched).unwrap(),
+            serde_json::json!({
+                "fullText": "Olá",
+                "totalDurationSecs": 4.5,
+                "segmentCount": 2,
+                "confidenceAvg": 0.5,
+                "providersUsed": ["Groq"],
+                "segments": [{ "durationSecs": 4.5, "wordCount": 1 }],
+                "providerBreakdown": [{ "provider": "Groq", "segments": 2, "avgConfidence": 0.5 }],
+                "retriedSegments": 1,
+                "gatedSegments": 0,
+                "failedSegments": 0
+            })
+        );
+
+        let segment = SegmentResult {
+            segment_id: "s1".to_string(),
+            status: SegmentStatus::Completed,
+            transcript: Some(Transcript::default()),
+            is_final: true,
+        };
+        let value = serde_json::to_value(&segment).unwrap();
+        assert_eq!(value["segmentId"], "s1");
+        assert_eq!(value["status"], "completed");
+        assert_eq!(value["isFinal"], true);
+        assert_eq!(value["transcript"]["durationSecs"], 0.0);
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+/// Releases completions in sequence order even when they resolve out of order.
+///
+/// A sequence resolved with `None` (failed segment) is a gap: it unblocks its successors
+/// without producing an item.
+pub struct ReorderBuffer<T> {
+    next_sequence: u32,
+    pending: BTreeMap<u32, Option<T>>,
+}
+
+impl<T> ReorderBuffer<T> {
+    pub fn new(first_sequence: u32) -> Self {
+        Self {
+            next_sequence: first_sequence,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Record the outcome for `sequence` and return every item that is now in order.
+    pub fn resolve(&mut self, sequence: u32, item: Option<T>) -> Vec<T> {
+        if sequence < self.next_sequence {
+            tracing::warn!("Ignoring late completion for sequence {}", sequence);
+            return Vec::new();
+        }
+        self.pending.insert(sequence, item);
+
+        let mut released = Vec::new();
+        while let Some(entry) = self.pending.remove(&self.next_sequence) {
+            released.extend(entry);
+            self.next_sequence += 1;
+        }
+        released
+    }
+
+    pub fn waiting(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Release everything still held back, in sequence order, as if every missing
+    /// predecessor had failed. Returns the items and how many sequences were missing.
+    pub fn drain(&mut self) -> (Vec<T>, u32) {
+        let Some(&last) = self.pending.keys().next_back() else {
+            return (Vec::new(), 0);
+        };
+        let missing = last + 1 - self.next_sequence - self.pending.len() as u32;
+        let released = std::mem::take(&mut self.pending)
+            .into_values()
+            .flatten()
+            .collect();
+        self.next_sequence = last + 1;
+        (released, missing)
+    }
+
+    pub fn reset(&mut self, first_sequence: u32) {
+        self.next_sequence = first_sequence;
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_of_order_completions_are_held_back() {
+        let mut buffer = ReorderBuffer::new(1);
+
+        assert!(buffer.resolve(3, Some("c")).is_empty());
+        assert!(buffer.resolve(2, Some("b")).is_empty());
+        assert_eq!(buffer.waiting(), 2);
+
+        assert_eq!(buffer.resolve(1, Some("a")), vec!["a", "b", "c"]);
+        assert_eq!(buffer.waiting(), 0);
+    }
+
+    #[test]
+    fn test_failed_sequence_does_not_block_successors() {
+        let mut buffer = ReorderBuffer::new(1);
+
+        assert!(buffer.resolve(2, Some("b")).is_empty());
+        assert_eq!(buffer.resolve(1, None), vec!["b"]);
+        assert_eq!(buffer.resolve(3, Some("c")), vec!["c"]);
+    }
+
+    #[test]
+    fn test_drain_releases_past_missing_predecessors() {
+        let mut buffer = ReorderBuffer::new(1);
+        assert!(buffer.resolve(3, Some("c")).is_empty());
+        assert!(buffer.resolve(5, None).is_empty());
+        assert!(buffer.resolve(6, Some("f")).is_empty());
+
+        assert_eq!(buffer.drain(), (vec!["c", "f"], 3));
+        assert_eq!(buffer.waiting(), 0);
+        assert_eq!(buffer.resolve(7, Some("g")), vec!["g"]);
+        assert_eq!(buffer.drain(), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn test_late_duplicate_is_ignored() {
+        let mut buffer = ReorderBuffer::new(1);
+        assert_eq!(buffer.resolve(1, Some("a")), vec!["a"]);
+        assert!(buffer.resolve(1, Some("again")).is_empty());
+    }
+}
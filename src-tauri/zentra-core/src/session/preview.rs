@@ -0,0 +1,107 @@
+//! Live preview of a session: the stitched transcript with the light clarity pass, so it
+//! reads like the finalized text instead of changing shape at finalize. The cleaned prefix
+//! is cached, and each progress update only cleans the text the new segments added.
+
+use crate::prompt_engine::clarity::PreviewCleaner;
+
+#[derive(Debug, Default)]
+pub struct ClarityPreview {
+    segment_count: usize,
+    /// Stitched text the cleaner has consumed
+    raw: String,
+    cleaner: PreviewCleaner,
+}
+
+impl ClarityPreview {
+    /// Preview of `stitched`, the text of the first `segment_count` segments. Starts over when
+    /// the earlier text changed (a new session, or a retried segment filled a gap).
+    pub fn update(&mut self, segment_count: usize, stitched: &str) -> String {
+        let extends_cache = segment_count >= self.segment_count && stitched.starts_with(&self.raw);
+        if !extends_cache {
+            *self = Self::default();
+        }
+
+        let tail = &stitched[self.raw.len()..];
+        self.cleaner.push(tail);
+        self.raw.push_str(tail);
+        self.segment_count = segment_count;
+        self.cleaner.text().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_engine::clarity::preview;
+    use crate::session::{AudioSegment, Stitcher};
+    use crate::stt::Transcript;
+
+    fn segment(text: &str, sequence_number: u32) -> AudioSegment {
+        let mut segment = AudioSegment::new(1.0, sequence_number);
+        segment.set_transcript(Transcript {
+            text: text.to_string(),
+            provider: "test".to_string(),
+            ..Default::default()
+        });
+        segment
+    }
+
+    #[test]
+    fn test_cleaner_matches_preview_however_the_text_is_split() {
+        let text = "então  ,vamos ver:o\tteste ;ok ?sim . ótimo!e agora ,  fim";
+        let expected = preview(text);
+        assert_eq!(
+            expected,
+            "Então, vamos ver: o teste; ok? Sim. Ótimo! E agora, fim"
+        );
+
+        let chars: Vec<char> = text.chars().collect();
+        for chunk in 1..=chars.len() {
+            let mut cleaner = PreviewCleaner::default();
+            for piece in chars.chunks(chunk) {
+                cleaner.push(&piece.iter().collect::<String>());
+            }
+            assert_eq!(cleaner.text(), expected, "chunks of {}", chunk);
+        }
+    }
+
+    #[test]
+    fn test_incremental_preview_equals_reprocessing_the_session() {
+        let texts = [
+            "bom dia a todos,hoje vamos",
+            "vamos revisar o orçamento .depois",
+            ", com calma ,a proposta:",
+            "primeiro item ;segundo item",
+            "fechamos   amanhã ?talvez",
+            "sim!",
+        ];
+        let segments: Vec<AudioSegment> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| segment(text, i as u32 + 1))
+            .collect();
+
+        let mut incremental = ClarityPreview::default();
+        for count in 1..=segments.len() {
+            let stitched = Stitcher::stitch_available(&segments[..count]);
+            let updated = incremental.update(count, &stitched);
+            assert_eq!(updated, preview(&stitched), "after {} segments", count);
+            // A repeated update with nothing new changes nothing.
+            assert_eq!(incremental.update(count, &stitched), updated);
+        }
+    }
+
+    #[test]
+    fn test_changed_history_starts_over() {
+        let mut incremental = ClarityPreview::default();
+        incremental.update(2, "primeiro segundo");
+
+        // A failed segment was retried and its text now sits in the middle.
+        assert_eq!(
+            incremental.update(3, "primeiro meio. segundo"),
+            "Primeiro meio. Segundo"
+        );
+        // A new session.
+        assert_eq!(incremental.update(1, "outra coisa"), "Outra coisa");
+    }
+}
@@ -0,0 +1,51 @@
+﻿use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionState {
+    Active,
+    Paused,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionProgress {
+    pub segment_count: u32,
+    /// Recorded audio only; paused spans are tracked separately
+    pub total_duration_secs: f32,
+    pub current_text: String,
+    pub state: SessionState,
+    pub paused_duration_secs: f32,
+    /// Segments whose transcription failed and that can still be retried
+    pub failed_segment_ids: Vec<String>,
+    /// Segments queued or being transcribed
+    pub transcribing_segment_ids: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentProgress {
+    pub segment_id: String,
+    pub sequence_number: u32,
+    pub status: SegmentStatus,
+    /// Provider that produced the transcript, once completed
+    pub provider: Option<String>,
+    /// Why transcription failed
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SegmentStatus {
+    Recording,
+    Transcribing,
+    Completed,
+    Failed,
+}
+
+/// Receives each segment status change as it happens. Called with the session locked and
+/// from its transcription tasks, so implementations should hand the update off rather than
+/// block.
+pub trait ProgressSink: Send + Sync {
+    fn segment_status(&self, progress: SegmentProgress);
+}
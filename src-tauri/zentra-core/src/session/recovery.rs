@@ -0,0 +1,497 @@
+use crate::privacy::PrivacyGuard;
+use crate::stt::Transcript;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+
+pub const RECOVERY_DIR: &str = "recovery";
+const ACTIVE_FILE: &str = "active.json";
+/// Segment journals, one `<session id>.jsonl` per unfinalized session
+const SESSIONS_DIR: &str = "sessions";
+const JOURNAL_EXTENSION: &str = "jsonl";
+const MIN_WRITE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryKind {
+    Session,
+}
+
+/// Text-only snapshot of in-progress work, written so a crash never loses transcribed segments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableSession {
+    pub kind: RecoveryKind,
+    pub session_id: String,
+    pub text: String,
+    pub segment_count: u32,
+    pub updated_at: String,
+}
+
+impl RecoverableSession {
+    pub fn new(kind: RecoveryKind, session_id: &str, text: String, segment_count: u32) -> Self {
+        Self {
+            kind,
+            session_id: session_id.to_string(),
+            text,
+            segment_count,
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// One transcribed segment, a line of a session's journal. A retried segment is appended
+/// again; the last line of a sequence number wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub sequence_number: u32,
+    pub duration_secs: f32,
+    pub transcript: Transcript,
+}
+
+/// Persists the active session's preview to `recovery/active.json`, at most once per
+/// second, and every transcribed segment to `recovery/sessions/<session id>.jsonl` as it
+/// completes, so a session can be resumed segment by segment after a crash.
+pub struct RecoveryStore {
+    dir: PathBuf,
+    privacy: PrivacyGuard,
+    snapshot: Arc<Mutex<Snapshot>>,
+}
+
+/// The preview waiting for its write, shared with the timer that writes it late.
+#[derive(Default)]
+struct Snapshot {
+    last_write: Option<Instant>,
+    pending: Option<RecoverableSession>,
+    flush_scheduled: bool,
+}
+
+impl RecoveryStore {
+    pub fn new(dir: PathBuf, privacy: PrivacyGuard) -> Self {
+        Self {
+            dir,
+            privacy,
+            snapshot: Arc::default(),
+        }
+    }
+
+    /// Append `entry` to the journal of `session_id`. A line is written in one call, so a
+    /// crash leaves at worst a truncated last line, which loading skips.
+    pub fn append_segment(&self, session_id: &str, entry: &JournalEntry) -> Result<(), String> {
+        if !self.privacy.allows_persistence() {
+            return Ok(());
+        }
+        let Some(path) = journal_path(&self.dir, session_id) else {
+            return Err(format!("Invalid session id: {}", session_id));
+        };
+        let mut line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize segment: {}", e))?;
+        line.push('\n');
+        fs::create_dir_all(self.dir.join(SESSIONS_DIR))
+            .map_err(|e| format!("Failed to create session journal dir: {}", e))?;
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to append to session journal: {}", e))
+    }
+
+    /// Transcribed segments of `session_id`, in sequence order.
+    pub fn load_segments(&self, session_id: &str) -> Vec<JournalEntry> {
+        journal_path(&self.dir, session_id).map_or_else(Vec::new, |path| read_journal(&path))
+    }
+
+    /// Remove the journal of `session_id` after a finalize or discard.
+    pub fn clear_segments(&self, session_id: &str) {
+        let Some(path) = journal_path(&self.dir, session_id) else {
+            return;
+        };
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!("Failed to remove session journal: {}", e),
+        }
+    }
+
+    pub fn update(&mut self, entry: RecoverableSession) -> Result<bool, String> {
+        self.update_at(entry, Instant::now())
+    }
+
+    /// Returns true when the snapshot hit the disk; otherwise it stays pending and, inside
+    /// a Tokio runtime, a timer writes it once the rate-limit window closes, unless a later
+    /// update or an explicit `flush` gets there first.
+    fn update_at(&mut self, entry: RecoverableSession, now: Instant) -> Result<bool, String> {
+        let mut snapshot = lock(&self.snapshot);
+        snapshot.pending = Some(entry);
+        if let Some(last) = snapshot.last_write {
+            if now.duration_since(last) < MIN_WRITE_INTERVAL {
+                let deadline = last + MIN_WRITE_INTERVAL;
+                self.schedule_flush(&mut snapshot, deadline);
+                return Ok(false);
+            }
+        }
+        snapshot.last_write = Some(now);
+        write_pending(&self.dir, &self.privacy, &mut snapshot)
+    }
+
+    pub fn flush(&mut self) -> Result<bool, String> {
+        write_pending(&self.dir, &self.privacy, &mut lock(&self.snapshot))
+    }
+
+    /// Write the pending snapshot at `deadline`, so the last update before the session goes
+    /// quiet isn't held back until the next one.
+    fn schedule_flush(&self, snapshot: &mut Snapshot, deadline: Instant) {
+        if snapshot.flush_scheduled {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        snapshot.flush_scheduled = true;
+        let dir = self.dir.clone();
+        let privacy = self.privacy.clone();
+        let shared = Arc::clone(&self.snapshot);
+        runtime.spawn(async move {
+            tokio::time::sleep_until(deadline).await;
+            let mut snapshot = lock(&shared);
+            snapshot.flush_scheduled = false;
+            if snapshot.pending.is_none() {
+                return;
+            }
+            snapshot.last_write = Some(Instant::now());
+            if let Err(e) = write_pending(&dir, &privacy, &mut snapshot) {
+                tracing::warn!("Failed to persist session preview: {}", e);
+            }
+        });
+    }
+
+    /// Remove the snapshot after a clean finalize or discard.
+    pub fn clear(&mut self) {
+        let mut snapshot = lock(&self.snapshot);
+        snapshot.pending = None;
+        snapshot.last_write = None;
+        let target = self.dir.join(ACTIVE_FILE);
+        if target.exists() {
+            if let Err(e) = fs::remove_file(&target) {
+                tracing::warn!("Failed to remove recovery snapshot: {}", e);
+            }
+        }
+    }
+}
+
+fn lock(snapshot: &Mutex<Snapshot>) -> MutexGuard<'_, Snapshot> {
+    snapshot
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Write the pending snapshot through a temporary file, so a crash never leaves half of it.
+fn write_pending(
+    dir: &Path,
+    privacy: &PrivacyGuard,
+    snapshot: &mut Snapshot,
+) -> Result<bool, String> {
+    let Some(entry) = snapshot.pending.take() else {
+        return Ok(false);
+    };
+    if !privacy.allows_persistence() {
+        return Ok(false);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create recovery dir: {}", e))?;
+    let raw = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize recovery snapshot: {}", e))?;
+    let target = dir.join(ACTIVE_FILE);
+    let tmp = dir.join(format!("{}.tmp", ACTIVE_FILE));
+    fs::write(&tmp, raw).map_err(|e| format!("Failed to write recovery snapshot: {}", e))?;
+    fs::rename(&tmp, &target).map_err(|e| format!("Failed to commit recovery snapshot: {}", e))?;
+    Ok(true)
+}
+
+/// Snapshots left behind by a previous run that did not shut down cleanly.
+pub fn load_recoverable(dir: &Path) -> Vec<RecoverableSession> {
+    let target = dir.join(ACTIVE_FILE);
+    let Ok(raw) = fs::read_to_string(&target) else {
+        return Vec::new();
+    };
+    match serde_json::from_str::<RecoverableSession>(&raw) {
+        Ok(entry) if !entry.text.trim().is_empty() => vec![entry],
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable recovery snapshot: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// The most recently written session journal with at least one readable segment: a
+/// session that was never finalized. Resume it with its `session_id`.
+pub fn load_recoverable_session(dir: &Path) -> Option<RecoverableSession> {
+    let entries = fs::read_dir(dir.join(SESSIONS_DIR)).ok()?;
+    let mut journals: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == JOURNAL_EXTENSION))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path))
+        })
+        .collect();
+    journals.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    journals.into_iter().find_map(|(modified, path)| {
+        let session_id = path.file_stem()?.to_str()?.to_string();
+        let segments = read_journal(&path);
+        if segments.is_empty() {
+            return None;
+        }
+        let text = segments
+            .iter()
+            .map(|entry| entry.transcript.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some(RecoverableSession {
+            kind: RecoveryKind::Session,
+            session_id,
+            text,
+            segment_count: segments.len() as u32,
+            updated_at: DateTime::<Utc>::from(modified).to_rfc3339(),
+        })
+    })
+}
+
+pub fn discard_recoverable(dir: &Path) -> Result<(), String> {
+    let target = dir.join(ACTIVE_FILE);
+    if target.exists() {
+        fs::remove_file(&target).map_err(|e| format!("Failed to discard recovery: {}", e))?;
+    }
+    let sessions = dir.join(SESSIONS_DIR);
+    if sessions.exists() {
+        fs::remove_dir_all(&sessions)
+            .map_err(|e| format!("Failed to discard session journals: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Only ids the stitcher hands out (UUIDs) name a journal; anything else from IPC could
+/// point outside the recovery folder.
+fn journal_path(dir: &Path, session_id: &str) -> Option<PathBuf> {
+    uuid::Uuid::parse_str(session_id).ok()?;
+    Some(
+        dir.join(SESSIONS_DIR)
+            .join(format!("{}.{}", session_id, JOURNAL_EXTENSION)),
+    )
+}
+
+/// Entries of a journal in sequence order. Lines that do not parse, such as one cut short
+/// by a crash, are skipped.
+fn read_journal(path: &Path) -> Vec<JournalEntry> {
+    let Ok(raw) = fs::read(path) else {
+        return Vec::new();
+    };
+    let mut skipped = 0;
+    let mut by_sequence = BTreeMap::new();
+    for line in String::from_utf8_lossy(&raw).lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JournalEntry>(line) {
+            Ok(entry) => {
+                by_sequence.insert(entry.sequence_number, entry);
+            }
+            Err(_) => skipped += 1,
+        }
+    }
+    if skipped > 0 {
+        tracing::warn!(
+            "Skipped {} unreadable line(s) in session journal {}",
+            skipped,
+            path.display()
+        );
+    }
+    by_sequence.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("zentra-recovery-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn snapshot(kind: RecoveryKind, text: &str, segments: u32) -> RecoverableSession {
+        RecoverableSession::new(kind, "session-1", text.to_string(), segments)
+    }
+
+    #[test]
+    fn test_abrupt_drop_recovers_latest_persisted_text() {
+        let dir = temp_dir();
+        let start = Instant::now();
+        let mut store = RecoveryStore::new(dir.clone(), PrivacyGuard::default());
+
+        assert!(store
+            .update_at(snapshot(RecoveryKind::Session, "primeiro", 1), start)
+            .unwrap());
+        assert!(store
+            .update_at(
+                snapshot(RecoveryKind::Session, "primeiro segundo", 2),
+                start + Duration::from_millis(1500),
+            )
+            .unwrap());
+        drop(store);
+
+        let recovered = load_recoverable(&dir);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].kind, RecoveryKind::Session);
+        assert_eq!(recovered[0].text, "primeiro segundo");
+        assert_eq!(recovered[0].segment_count, 2);
+    }
+
+    #[test]
+    fn test_writes_are_rate_limited_to_one_per_second() {
+        let dir = temp_dir();
+        let start = Instant::now();
+        let mut store = RecoveryStore::new(dir.clone(), PrivacyGuard::default());
+
+        assert!(store
+            .update_at(snapshot(RecoveryKind::Session, "um", 1), start)
+            .unwrap());
+        assert!(!store
+            .update_at(
+                snapshot(RecoveryKind::Session, "um dois", 2),
+                start + Duration::from_millis(200),
+            )
+            .unwrap());
+        assert_eq!(load_recoverable(&dir)[0].text, "um");
+
+        assert!(store.flush().unwrap());
+        let recovered = load_recoverable(&dir);
+        assert_eq!(recovered[0].kind, RecoveryKind::Session);
+        assert_eq!(recovered[0].text, "um dois");
+        assert!(!dir.join(format!("{}.tmp", ACTIVE_FILE)).exists());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_last_throttled_update_reaches_disk_once_the_window_closes() {
+        let dir = temp_dir();
+        let start = Instant::now();
+        let mut store = RecoveryStore::new(dir.clone(), PrivacyGuard::default());
+
+        assert!(store
+            .update_at(snapshot(RecoveryKind::Session, "um", 1), start)
+            .unwrap());
+        for (i, text) in ["um dois", "um dois tres"].into_iter().enumerate() {
+            let at = start + Duration::from_millis(200 * (i as u64 + 1));
+            assert!(!store
+                .update_at(snapshot(RecoveryKind::Session, text, 2 + i as u32), at)
+                .unwrap());
+        }
+        assert_eq!(load_recoverable(&dir)[0].text, "um");
+
+        tokio::time::sleep_until(start + MIN_WRITE_INTERVAL).await;
+        tokio::task::yield_now().await;
+        let recovered = load_recoverable(&dir);
+        assert_eq!(recovered[0].text, "um dois tres");
+        assert_eq!(recovered[0].segment_count, 3);
+        assert!(!store.flush().unwrap());
+    }
+
+    #[test]
+    fn test_clear_removes_snapshot_after_clean_finalize() {
+        let dir = temp_dir();
+        let mut store = RecoveryStore::new(dir.clone(), PrivacyGuard::default());
+        store
+            .update(snapshot(RecoveryKind::Session, "texto", 1))
+            .unwrap();
+
+        store.clear();
+        assert!(load_recoverable(&dir).is_empty());
+    }
+
+    fn entry(sequence_number: u32, text: &str) -> JournalEntry {
+        JournalEntry {
+            sequence_number,
+            duration_secs: 10.0,
+            transcript: Transcript {
+                text: text.to_string(),
+                provider: "Groq".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_journal_survives_corrupt_and_truncated_lines() {
+        let dir = temp_dir();
+        let store = RecoveryStore::new(dir.clone(), PrivacyGuard::default());
+        let session_id = uuid::Uuid::new_v4().to_string();
+        store
+            .append_segment(&session_id, &entry(2, "dois"))
+            .unwrap();
+        store.append_segment(&session_id, &entry(1, "um")).unwrap();
+        store
+            .append_segment(&session_id, &entry(2, "dois, de novo"))
+            .unwrap();
+
+        // A stray line and a write cut short by a crash.
+        let path = journal_path(&dir, &session_id).unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"not json\n{\"sequenceNumber\": 3, \"durat")
+            .unwrap();
+        drop(file);
+
+        let segments = store.load_segments(&session_id);
+        let texts: Vec<&str> = segments
+            .iter()
+            .map(|entry| entry.transcript.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["um", "dois, de novo"]);
+
+        let recoverable = load_recoverable_session(&dir).unwrap();
+        assert_eq!(recoverable.session_id, session_id);
+        assert_eq!(recoverable.kind, RecoveryKind::Session);
+        assert_eq!(recoverable.text, "um dois, de novo");
+        assert_eq!(recoverable.segment_count, 2);
+
+        store.clear_segments(&session_id);
+        assert!(load_recoverable_session(&dir).is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_journal_ids_must_be_session_ids() {
+        let dir = temp_dir();
+        let store = RecoveryStore::new(dir.clone(), PrivacyGuard::default());
+        assert!(store.append_segment("../config", &entry(1, "x")).is_err());
+        assert!(store.load_segments("../config").is_empty());
+        assert!(load_recoverable_session(&dir).is_none());
+
+        // Privacy mode keeps segments off disk too.
+        let private = RecoveryStore::new(dir.clone(), PrivacyGuard::new(true));
+        let session_id = uuid::Uuid::new_v4().to_string();
+        private
+            .append_segment(&session_id, &entry(1, "segredo"))
+            .unwrap();
+        assert!(private.load_segments(&session_id).is_empty());
+    }
+
+    #[test]
+    fn test_privacy_mode_skips_snapshot() {
+        let dir = temp_dir();
+        let mut store = RecoveryStore::new(dir.clone(), PrivacyGuard::new(true));
+
+        assert!(!store
+            .update(snapshot(RecoveryKind::Session, "segredo", 1))
+            .unwrap());
+        assert!(load_recoverable(&dir).is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+﻿use super::SILENCE_GATE_PROVIDER;
+use crate::audio::AudioBuffer;
+use crate::stt::Transcript;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Retained audio is down-mixed to this rate (about 1.9 MB per minute).
+pub const RETAINED_SAMPLE_RATE: u32 = 16_000;
+
+#[derive(Clone)]
+pub struct AudioSegment {
+    pub id: String,
+    pub transcript: Option<Transcript>,
+    pub sequence_number: u32,
+    pub timestamp: Instant,
+    pub duration_secs: f32,
+    /// Energy heat strip (see `diagnostics`); kept only for gated segments unless
+    /// diagnostics capture is on
+    pub energy: Option<Vec<u8>>,
+    /// 16 kHz mono copy of the segment's audio so it can be retried; dropped when the
+    /// session's retention budget runs out
+    pub audio: Option<AudioBuffer>,
+    /// Times the segment was transcribed again with `retry_segment`
+    pub retries: u32,
+}
+
+impl AudioSegment {
+    pub fn new(duration_secs: f32, sequence_number: u32) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            duration_secs,
+            transcript: None,
+            sequence_number,
+            timestamp: Instant::now(),
+            energy: None,
+            audio: None,
+            retries: 0,
+        }
+    }
+
+    /// Keep a 16 kHz mono copy of `audio` for retries.
+    pub fn retain_audio(&mut self, audio: &AudioBuffer) {
+        let mut retained = AudioBuffer::new(RETAINED_SAMPLE_RATE, 1);
+        retained.append(&audio.to_mono(RETAINED_SAMPLE_RATE));
+        self.audio = Some(retained);
+    }
+
+    pub fn retained_audio_bytes(&self) -> usize {
+        self.audio
+            .as_ref()
+            .map_or(0, |audio| audio.samples.len() * std::mem::size_of::<i16>())
+    }
+
+    pub fn set_transcript(&mut self, transcript: Transcript) {
+        self.transcript = Some(transcript);
+    }
+
+    pub fn is_transcribed(&self) -> bool {
+        self.transcript.is_some()
+    }
+
+    /// Skipped by the silence gate rather than transcribed by a provider.
+    pub fn is_gated(&self) -> bool {
+        self.transcript
+            .as_ref()
+            .is_some_and(|transcript| transcript.provider == SILENCE_GATE_PROVIDER)
+    }
+}
@@ -0,0 +1,243 @@
+//! Energy gate that drops segments which are almost certainly silence before they reach a
+//! provider. Off by default; thresholds come from the app settings. Recordings shorter than
+//! the minimum duration are dropped even with the gate off: a sub-second tap has too few
+//! frames for the energy math to mean anything, and providers answer it with invented words.
+
+use super::{
+    audio_energy_metrics, derive_duration_secs, AudioEnergyMetrics, SILENCE_GATE_PROVIDER,
+    TOO_SHORT_PROVIDER,
+};
+use crate::audio::AudioBuffer;
+use crate::stt::Transcript;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+pub const DEFAULT_RMS_THRESHOLD: f32 = 0.0015;
+pub const DEFAULT_PEAK_THRESHOLD: f32 = 0.010;
+pub const DEFAULT_SPEECH_RATIO_THRESHOLD: f32 = 0.015;
+pub const DEFAULT_MIN_DURATION_SECS: f32 = 0.7;
+/// Upper bound for the minimum duration, so a typo can't drop every recording.
+pub const MAX_MIN_DURATION_SECS: f32 = 5.0;
+
+/// A segment is dropped only when rms, peak and speech ratio are all below their thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SilenceGate {
+    pub enabled: bool,
+    /// Overall RMS level, 0-1 of full scale
+    pub rms_threshold: f32,
+    /// Loudest sample, 0-1 of full scale
+    pub peak_threshold: f32,
+    /// Share of 20 ms frames loud enough to be speech
+    pub speech_ratio_threshold: f32,
+    /// Recordings shorter than this are dropped whether or not the gate is enabled; 0
+    /// turns the check off. A recording of exactly this length is kept.
+    pub min_duration_secs: f32,
+}
+
+impl Default for SilenceGate {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rms_threshold: DEFAULT_RMS_THRESHOLD,
+            peak_threshold: DEFAULT_PEAK_THRESHOLD,
+            speech_ratio_threshold: DEFAULT_SPEECH_RATIO_THRESHOLD,
+            min_duration_secs: DEFAULT_MIN_DURATION_SECS,
+        }
+    }
+}
+
+impl SilenceGate {
+    /// Thresholds clamped to 0-1; anything outside that range would gate everything or nothing.
+    pub fn clamped(self) -> Self {
+        Self {
+            rms_threshold: self.rms_threshold.clamp(0.0, 1.0),
+            peak_threshold: self.peak_threshold.clamp(0.0, 1.0),
+            speech_ratio_threshold: self.speech_ratio_threshold.clamp(0.0, 1.0),
+            min_duration_secs: self.min_duration_secs.clamp(0.0, MAX_MIN_DURATION_SECS),
+            ..self
+        }
+    }
+
+    pub(super) fn is_probable_silence(&self, metrics: &AudioEnergyMetrics) -> bool {
+        self.enabled
+            && metrics.rms < self.rms_threshold
+            && metrics.peak < self.peak_threshold
+            && metrics.speech_ratio < self.speech_ratio_threshold
+    }
+
+    /// True when a whole recording is shorter than [`Self::min_duration_secs`].
+    pub fn is_too_short(&self, audio: &AudioBuffer) -> bool {
+        derive_duration_secs(audio) < self.min_duration_secs
+    }
+
+    /// The empty transcript, tagged with [`TOO_SHORT_PROVIDER`], for a recording below the
+    /// minimum duration; `None` when it is long enough.
+    pub fn check_duration(&self, audio: &AudioBuffer) -> Option<Transcript> {
+        if !self.is_too_short(audio) {
+            return None;
+        }
+        tracing::info!(
+            "Recording skipped: {:.2}s is shorter than the {:.2}s minimum",
+            derive_duration_secs(audio),
+            self.min_duration_secs
+        );
+        Some(Transcript {
+            provider: TOO_SHORT_PROVIDER.to_string(),
+            ..gated_transcript(audio)
+        })
+    }
+
+    /// For single-shot transcription: the empty transcript to return instead of calling a
+    /// provider, or `None` when the audio should be sent. Too-short recordings are
+    /// checked first.
+    pub fn check(&self, audio: &AudioBuffer) -> Option<Transcript> {
+        if let Some(too_short) = self.check_duration(audio) {
+            return Some(too_short);
+        }
+        if !self.enabled {
+            return None;
+        }
+        let metrics = audio_energy_metrics(audio);
+        if !self.is_probable_silence(&metrics) {
+            return None;
+        }
+        tracing::info!(
+            "Recording skipped: probable silence (rms={:.5}, peak={:.5}, speech_ratio={:.3})",
+            metrics.rms,
+            metrics.peak,
+            metrics.speech_ratio
+        );
+        Some(gated_transcript(audio))
+    }
+}
+
+/// Transcript for audio the gate dropped; empty text, tagged with [`SILENCE_GATE_PROVIDER`].
+pub(super) fn gated_transcript(audio: &AudioBuffer) -> Transcript {
+    Transcript {
+        text: String::new(),
+        confidence: 0.0,
+        language: None,
+        duration_secs: derive_duration_secs(audio),
+        provider: SILENCE_GATE_PROVIDER.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Gate settings shared between the app and the stitcher; read once per segment, so a
+/// settings change applies from the next segment on.
+#[derive(Debug, Clone, Default)]
+pub struct SharedSilenceGate {
+    gate: Arc<RwLock<SilenceGate>>,
+}
+
+impl SharedSilenceGate {
+    pub fn new(gate: SilenceGate) -> Self {
+        Self {
+            gate: Arc::new(RwLock::new(gate)),
+        }
+    }
+
+    pub fn get(&self) -> SilenceGate {
+        *self
+            .gate
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn set(&self, gate: SilenceGate) {
+        *self
+            .gate
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = gate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(level: i16) -> AudioBuffer {
+        samples(level, 16_000)
+    }
+
+    fn samples(level: i16, count: usize) -> AudioBuffer {
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![level; count]);
+        audio
+    }
+
+    #[test]
+    fn test_gate_only_drops_quiet_audio_when_enabled() {
+        let hiss = buffer(10);
+        let speech = buffer(3_000);
+
+        assert!(SilenceGate::default().check(&hiss).is_none());
+
+        let gate = SilenceGate {
+            enabled: true,
+            ..SilenceGate::default()
+        };
+        let gated = gate.check(&hiss).unwrap();
+        assert_eq!(gated.provider, SILENCE_GATE_PROVIDER);
+        assert!(gated.text.is_empty());
+        assert!((gated.duration_secs - 1.0).abs() < 1e-3);
+        assert!(gate.check(&speech).is_none());
+
+        // A stricter peak threshold lets the same hiss through.
+        let strict = SilenceGate {
+            peak_threshold: 0.0001,
+            ..gate
+        };
+        assert!(strict.check(&hiss).is_none());
+    }
+
+    #[test]
+    fn test_recordings_below_the_minimum_duration_are_dropped() {
+        let gate = SilenceGate::default();
+        assert!(!gate.enabled);
+
+        // 200 ms of speech: too short even with the energy gate off.
+        let tap = gate.check(&samples(3_000, 3_200)).unwrap();
+        assert_eq!(tap.provider, TOO_SHORT_PROVIDER);
+        assert!(tap.text.is_empty());
+
+        // Exactly at the threshold (0.7 s at 16 kHz) is long enough.
+        let at_threshold = samples(3_000, 11_200);
+        assert!((at_threshold.duration_secs - DEFAULT_MIN_DURATION_SECS).abs() < 1e-6);
+        assert!(!gate.is_too_short(&at_threshold));
+        assert!(gate.check(&at_threshold).is_none());
+        assert!(gate.is_too_short(&samples(3_000, 11_199)));
+
+        let off = SilenceGate {
+            min_duration_secs: 0.0,
+            ..gate
+        };
+        assert!(off.check(&samples(3_000, 160)).is_none());
+        assert_eq!(
+            SilenceGate {
+                min_duration_secs: 60.0,
+                ..gate
+            }
+            .clamped()
+            .min_duration_secs,
+            MAX_MIN_DURATION_SECS
+        );
+    }
+
+    #[test]
+    fn test_shared_gate_updates_every_clone() {
+        let shared = SharedSilenceGate::default();
+        let reader = shared.clone();
+        shared.set(
+            SilenceGate {
+                enabled: true,
+                rms_threshold: 2.0,
+                ..SilenceGate::default()
+            }
+            .clamped(),
+        );
+        assert!(reader.get().enabled);
+        assert_eq!(reader.get().rms_threshold, 1.0);
+    }
+}
@@ -0,0 +1,164 @@
+//! Where a session's segments came from: how many each provider transcribed, how many
+//! needed a retry, how many the silence gate skipped and how many were lost to failures.
+
+use super::AudioSegment;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderBreakdown {
+    pub provider: String,
+    pub segments: u32,
+    pub avg_confidence: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SessionStats {
+    /// Providers in the order they first transcribed a segment; gated segments excluded
+    pub provider_breakdown: Vec<ProviderBreakdown>,
+    /// Segments that were transcribed again at least once
+    pub retried_segments: u32,
+    /// Segments the silence gate skipped without a provider call
+    pub gated_segments: u32,
+    /// Segments that failed and were dropped; their speech is missing from the text
+    pub failed_segments: u32,
+}
+
+impl SessionStats {
+    pub fn from_segments(segments: &[AudioSegment], failed_segments: u32) -> Self {
+        let mut confidence: Vec<(String, u32, f32)> = Vec::new();
+        let mut stats = Self {
+            failed_segments,
+            ..Self::default()
+        };
+
+        for segment in segments {
+            if segment.retries > 0 {
+                stats.retried_segments += 1;
+            }
+            if segment.is_gated() {
+                stats.gated_segments += 1;
+                continue;
+            }
+            let Some(transcript) = segment.transcript.as_ref() else {
+                continue;
+            };
+            match confidence
+                .iter_mut()
+                .find(|(provider, _, _)| *provider == transcript.provider)
+            {
+                Some((_, count, sum)) => {
+                    *count += 1;
+                    *sum += transcript.confidence;
+                }
+                None => confidence.push((transcript.provider.clone(), 1, transcript.confidence)),
+            }
+        }
+
+        stats.provider_breakdown = confidence
+            .into_iter()
+            .map(|(provider, segments, sum)| ProviderBreakdown {
+                provider,
+                segments,
+                avg_confidence: sum / segments as f32,
+            })
+            .collect();
+        stats
+    }
+
+    /// One line for the log: `Groq 18 (0.91), ElevenLabs 3 (0.84); 2 retried, 1 gated, 0 failed`.
+    pub fn summary(&self) -> String {
+        let providers: Vec<String> = self
+            .provider_breakdown
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} ({:.2})",
+                    entry.provider, entry.segments, entry.avg_confidence
+                )
+            })
+            .collect();
+        format!(
+            "{}; {} retried, {} gated, {} failed",
+            if providers.is_empty() {
+                "no provider".to_string()
+            } else {
+                providers.join(", ")
+            },
+            self.retried_segments,
+            self.gated_segments,
+            self.failed_segments
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SILENCE_GATE_PROVIDER;
+    use crate::stt::Transcript;
+
+    fn segment(provider: &str, confidence: f32, retries: u32) -> AudioSegment {
+        let mut segment = AudioSegment::new(1.0, 1);
+        segment.set_transcript(Transcript {
+            text: "texto".to_string(),
+            provider: provider.to_string(),
+            confidence,
+            ..Default::default()
+        });
+        segment.retries = retries;
+        segment
+    }
+
+    #[test]
+    fn test_flaky_session_breakdown() {
+        let segments = [
+            segment("Groq", 0.9, 0),
+            segment("ElevenLabs", 0.8, 1),
+            segment("Groq", 0.7, 0),
+            segment(SILENCE_GATE_PROVIDER, 0.0, 0),
+            segment("Groq", 0.8, 2),
+        ];
+        let stats = SessionStats::from_segments(&segments, 1);
+
+        assert_eq!(stats.provider_breakdown.len(), 2);
+        assert_eq!(stats.provider_breakdown[0].provider, "Groq");
+        assert_eq!(stats.provider_breakdown[0].segments, 3);
+        assert!((stats.provider_breakdown[0].avg_confidence - 0.8).abs() < 1e-6);
+        assert_eq!(stats.provider_breakdown[1].provider, "ElevenLabs");
+        assert_eq!(stats.retried_segments, 2);
+        assert_eq!(stats.gated_segments, 1);
+        assert_eq!(stats.failed_segments, 1);
+        assert_eq!(
+            stats.summary(),
+            "Groq 3 (0.80), ElevenLabs 1 (0.80); 2 retried, 1 gated, 1 failed"
+        );
+    }
+
+    #[test]
+    fn test_stats_serialize_camel_case() {
+        let stats = SessionStats {
+            provider_breakdown: vec![ProviderBreakdown {
+                provider: "Groq".to_string(),
+                segments: 18,
+                avg_confidence: 0.5,
+            }],
+            retried_segments: 2,
+            gated_segments: 1,
+            failed_segments: 0,
+        };
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            serde_json::json!({
+                "providerBreakdown": [{ "provider": "Groq", "segments": 18, "avgConfidence": 0.5 }],
+                "retriedSegments": 2,
+                "gatedSegments": 1,
+                "failedSegments": 0
+            })
+        );
+        // Results saved before the breakdown existed still load.
+        let old: SessionStats = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(old, SessionStats::default());
+    }
+}
@@ -1,21 +1,26 @@
 ﻿use super::AudioSegment;
+use crate::text::normalize::token_equal;
 
 pub struct Stitcher;
 
 impl Stitcher {
     pub fn stitch_transcripts(segments: &[AudioSegment]) -> Result<String, StitchError> {
-        if segments.is_empty() {
-            return Ok(String::new());
+        if let Some(missing) = segments.iter().find(|segment| !segment.is_transcribed()) {
+            return Err(StitchError::SegmentNotTranscribed(missing.id.clone()));
         }
+        Ok(Self::stitch_available(segments))
+    }
 
+    /// Like [`Self::stitch_transcripts`], but skips segments without a transcript (failed
+    /// ones waiting for a retry).
+    pub fn stitch_available(segments: &[AudioSegment]) -> String {
         let mut full_text = String::new();
         let mut previous_words: Vec<String> = Vec::new();
 
         for segment in segments {
-            let transcript = segment
-                .transcript
-                .as_ref()
-                .ok_or_else(|| StitchError::SegmentNotTranscribed(segment.id.clone()))?;
+            let Some(transcript) = segment.transcript.as_ref() else {
+                continue;
+            };
 
             let mut words: Vec<String> = transcript
                 .text
@@ -53,29 +58,21 @@ impl Stitcher {
             }
         }
 
-        let normalized = Self::normalize_text(&full_text);
-        Ok(normalized)
+        Self::normalize_text(&full_text)
     }
 
     fn detect_overlap(previous: &[String], current: &[String]) -> usize {
         let max_check = std::cmp::min(3, std::cmp::min(previous.len(), current.len()));
 
         for n in (1..=max_check).rev() {
-            let prev_tail: Vec<_> = previous
-                .iter()
-                .rev()
-                .take(n)
-                .rev()
-                .map(|s| s.to_lowercase())
-                .collect();
+            let prev_tail = &previous[previous.len() - n..];
+            let curr_head = &current[..n];
 
-            let curr_head: Vec<_> = current
+            if prev_tail
                 .iter()
-                .take(n)
-                .map(|s| s.to_lowercase())
-                .collect();
-
-            if prev_tail == curr_head {
+                .zip(curr_head)
+                .all(|(prev, curr)| token_equal(prev, curr))
+            {
                 return n;
             }
         }
@@ -181,3 +178,33 @@ fn capitalize_sentences(text: &str) -> String {
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stt::Transcript;
+
+    fn segment(text: &str, sequence_number: u32) -> AudioSegment {
+        let mut segment = AudioSegment::new(1.0, sequence_number);
+        segment.set_transcript(Transcript {
+            text: text.to_string(),
+            confidence: 0.9,
+            language: None,
+            duration_secs: 1.0,
+            provider: "test".to_string(),
+            ..Default::default()
+        });
+        segment
+    }
+
+    #[test]
+    fn test_overlap_ignores_case_accents_and_punctuation() {
+        let segments = vec![
+            segment("vamos falar sobre a informação.", 1),
+            segment("Informacao que chegou hoje", 2),
+        ];
+
+        let text = Stitcher::stitch_transcripts(&segments).unwrap();
+        assert_eq!(text, "Vamos falar sobre a informação. Que chegou hoje");
+    }
+}
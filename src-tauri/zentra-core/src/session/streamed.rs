@@ -0,0 +1,112 @@
+//! Progressive upload of session segments (`experimental_streaming_upload`). In this mode a
+//! running recording is cut every [`STREAMED_SEGMENT_SECS`] instead of at pauses, so each
+//! segment's length is known while it is recorded: its upload starts once
+//! [`STREAM_START_SHARE`] of it is in and the rest is fed as it arrives, leaving only the
+//! tail to wait on the network. The upload goes through
+//! [`FailoverOrchestrator::transcribe_streamed`]; when it fails, the segment is sent again
+//! the buffered way, with failover.
+
+use crate::audio::AudioBuffer;
+use crate::orchestrator::FailoverOrchestrator;
+use crate::stt::{streaming_wav, STTError, SampleFeed, Transcript};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::RwLock as TokioRwLock;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// Length of every segment cut while streaming.
+pub const STREAMED_SEGMENT_SECS: f32 = 20.0;
+/// Share of a segment recorded before its upload starts.
+pub const STREAM_START_SHARE: f32 = 0.8;
+
+/// Interleaved samples in a streamed segment.
+pub fn segment_len(sample_rate: u32, channels: u16) -> usize {
+    (STREAMED_SEGMENT_SECS * sample_rate as f32) as usize * channels.max(1) as usize
+}
+
+/// Samples of a segment of `len` recorded before its upload starts.
+pub fn stream_start(len: usize) -> usize {
+    (len as f32 * STREAM_START_SHARE) as usize
+}
+
+/// The upload of the segment at the front of a capture buffer. Dropping it cancels the
+/// request, which then counts neither as a success nor as a failure of the provider.
+pub struct SegmentStream {
+    feed: Option<SampleFeed>,
+    len: usize,
+    sent: usize,
+    upload: Option<JoinHandle<Result<Transcript, STTError>>>,
+}
+
+impl SegmentStream {
+    /// Start uploading a segment of `len` interleaved samples. The request runs in a task
+    /// of its own, so it is under way while the segment is still being recorded.
+    pub fn start(
+        orchestrator: Arc<TokioRwLock<FailoverOrchestrator>>,
+        len: usize,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Self {
+        let (feed, upload) = streaming_wav(len, sample_rate, channels);
+        let task = async move { orchestrator.read().await.transcribe_streamed(upload).await };
+        Self {
+            feed: Some(feed),
+            len,
+            sent: 0,
+            upload: Some(tokio::spawn(task.in_current_span())),
+        }
+    }
+
+    /// Send the samples of the segment that reached `buffer` since the last call. True once
+    /// the whole segment went out.
+    pub async fn feed_from(&mut self, buffer: &Mutex<AudioBuffer>) -> Result<bool, STTError> {
+        let chunk = {
+            let guard = buffer.lock().unwrap_or_else(PoisonError::into_inner);
+            let upto = guard.samples.len().min(self.len);
+            guard
+                .samples
+                .get(self.sent..upto)
+                .map(<[i16]>::to_vec)
+                .ok_or_else(|| {
+                    STTError::ProviderError("Capture buffer restarted mid-upload".to_string())
+                })?
+        };
+        if let Some(feed) = self.feed.as_mut() {
+            feed.send(&chunk).await?;
+        }
+        self.sent += chunk.len();
+        Ok(self.sent == self.len)
+    }
+
+    /// The transcript. A segment that wasn't fed in full ends its upload short, which
+    /// fails the request.
+    pub async fn finish(mut self) -> Result<Transcript, STTError> {
+        self.feed = None;
+        let upload = self.upload.take().expect("upload taken only here");
+        upload
+            .await
+            .map_err(|e| STTError::ProviderError(format!("Streaming upload task failed: {}", e)))?
+    }
+}
+
+impl Drop for SegmentStream {
+    fn drop(&mut self) {
+        // Before the feed goes, so the request is cancelled instead of failing on a short body
+        if let Some(upload) = self.upload.take() {
+            upload.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streaming_starts_at_eighty_percent_of_a_fixed_length() {
+        let len = segment_len(48_000, 2);
+        assert_eq!(len, 1_920_000);
+        assert_eq!(stream_start(len), 1_536_000);
+        assert_eq!(segment_len(16_000, 0), 320_000);
+    }
+}
@@ -0,0 +1,195 @@
+//! Session warm-up. The first segment pays every cold-start cost at once: the TLS
+//! handshake, the provider guessing the language, a cold model. As soon as the live capture
+//! holds a couple of seconds of speech, that slice goes out as a throwaway request; the
+//! connection it opens is reused by the real segments, and the language it hears is
+//! pinned for the rest of the session. Its text is dropped and never reaches the stitcher.
+
+use super::{energy_frame_len, frame_rms, SPEECH_FRAME_RMS};
+use crate::audio::AudioBuffer;
+use crate::orchestrator::FailoverOrchestrator;
+use crate::stt::LanguagePin;
+use tokio::sync::RwLock as TokioRwLock;
+
+/// Speech the warm-up slice needs before it is sent.
+pub const WARMUP_SPEECH_SECS: f32 = 2.0;
+/// Stop looking for speech to warm up with once the recording is this long; the first
+/// segment is about to go out anyway.
+pub const WARMUP_DEADLINE_SECS: f32 = 12.0;
+
+/// The recording from its first speech frame up to the frame that completes
+/// [`WARMUP_SPEECH_SECS`] of speech; `None` until there is that much.
+pub fn warmup_slice(audio: &AudioBuffer) -> Option<AudioBuffer> {
+    let frame_len = energy_frame_len(audio);
+    let frames_per_sec =
+        audio.sample_rate.max(1) as f32 * audio.channels.max(1) as f32 / frame_len as f32;
+    let needed = (WARMUP_SPEECH_SECS * frames_per_sec).round() as usize;
+    let mut first_speech = None;
+    let mut speech_frames = 0;
+    for (index, frame) in audio.samples.chunks(frame_len).enumerate() {
+        if frame.len() < frame_len || frame_rms(frame) < SPEECH_FRAME_RMS {
+            continue;
+        }
+        let start = *first_speech.get_or_insert(index * frame_len);
+        speech_frames += 1;
+        if speech_frames >= needed {
+            let mut slice = AudioBuffer::new(audio.sample_rate, audio.channels);
+            slice.append(&audio.samples[start..(index + 1) * frame_len]);
+            return Some(slice);
+        }
+    }
+    None
+}
+
+/// Warm up with `slice` and pin the language the provider heard, unless one is pinned
+/// already. Returns the pinned language; the transcript itself is dropped.
+pub async fn warm_up(
+    orchestrator: &TokioRwLock<FailoverOrchestrator>,
+    pin: &LanguagePin,
+    slice: &AudioBuffer,
+) -> Option<String> {
+    let transcript = orchestrator.read().await.warm_up(slice).await?;
+    let language = transcript.language?;
+    if pin.pin(&language) {
+        tracing::info!(
+            "Session language pinned to {}",
+            pin.get().unwrap_or_default()
+        );
+    }
+    pin.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::OfflineMode;
+    use crate::orchestrator::{ProviderConfig, UsageTracker, DEFAULT_MAX_CONCURRENT};
+    use crate::privacy::PrivacyGuard;
+    use crate::session::SessionStitcher;
+    use crate::stt::{PreparedAudio, STTAdapter, STTError, Transcript};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers with the audio length, in Portuguese, and notes the language it was asked for.
+    struct EchoAdapter {
+        requested: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    #[async_trait]
+    impl STTAdapter for EchoAdapter {
+        async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+            self.transcribe_prepared(audio, &self.prepare(audio)?).await
+        }
+
+        async fn transcribe_prepared(
+            &self,
+            audio: &AudioBuffer,
+            prepared: &PreparedAudio,
+        ) -> Result<Transcript, STTError> {
+            self.requested
+                .lock()
+                .unwrap()
+                .push(prepared.language.clone());
+            Ok(Transcript {
+                text: format!("Amostras {}", audio.samples.len()),
+                confidence: 0.9,
+                language: Some("portuguese".to_string()),
+                provider: "echo".to_string(),
+                ..Default::default()
+            })
+        }
+
+        fn name(&self) -> &str {
+            "echo"
+        }
+    }
+
+    fn echo_provider(requested: &Arc<Mutex<Vec<Option<String>>>>) -> ProviderConfig {
+        ProviderConfig {
+            id: "echo".to_string(),
+            priority: 1,
+            adapter: Box::new(EchoAdapter {
+                requested: requested.clone(),
+            }),
+            max_retries: 0,
+            timeout_secs: 10,
+            confidence_threshold: 0.7,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }
+    }
+
+    fn recording(silence_secs: usize, speech_secs: usize) -> AudioBuffer {
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![0i16; 16_000 * silence_secs]);
+        audio.append(&vec![2_000i16; 16_000 * speech_secs]);
+        audio
+    }
+
+    #[test]
+    fn test_slice_starts_at_speech_and_waits_for_enough_of_it() {
+        assert!(warmup_slice(&recording(3, 1)).is_none());
+        let slice = warmup_slice(&recording(3, 5)).unwrap();
+        assert_eq!(slice.samples.len(), 32_000);
+        assert!(slice.samples.iter().all(|&sample| sample == 2_000));
+        assert!(warmup_slice(&AudioBuffer::new(16_000, 1)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_pins_the_language_and_never_reaches_the_stitcher() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let pin = LanguagePin::default();
+        let usage = UsageTracker::default();
+        let orchestrator = Arc::new(TokioRwLock::new(
+            FailoverOrchestrator::new(vec![echo_provider(&requested)])
+                .with_language_pin(pin.clone())
+                .with_usage(usage.clone()),
+        ));
+        let mut stitcher =
+            SessionStitcher::new(orchestrator.clone()).with_language_pin(pin.clone());
+        stitcher.start_session(None).await.unwrap();
+
+        let slice = warmup_slice(&recording(0, 3)).unwrap();
+        let pinned = warm_up(&orchestrator, &pin, &slice).await;
+        assert_eq!(pinned.as_deref(), Some("pt"));
+
+        stitcher.add_segment(recording(0, 3)).unwrap();
+        let result = stitcher.finalize_session().await.unwrap();
+        assert_eq!(result.segment_count, 1);
+        assert_eq!(result.full_text, "Amostras 48000");
+
+        // The warm-up went out without a language; the segment with the pinned one.
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec![None, Some("pt".to_string())]
+        );
+        let metrics = orchestrator.read().await.get_metrics();
+        assert_eq!(metrics.get_success_count("echo"), 1);
+        assert_eq!(metrics.get_warmup_count("echo"), 1);
+        // Both requests were sent, so both count as usage.
+        let stats = usage.stats(&Default::default());
+        assert_eq!(stats.providers[0].requests, 2);
+        // The session is over, and so is its language.
+        assert_eq!(pin.get(), None);
+    }
+
+    #[tokio::test]
+    async fn test_no_warm_up_in_privacy_or_offline_mode() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let pin = LanguagePin::default();
+        let slice = warmup_slice(&recording(0, 3)).unwrap();
+
+        let private = TokioRwLock::new(
+            FailoverOrchestrator::new(vec![echo_provider(&requested)])
+                .with_privacy(PrivacyGuard::new(true)),
+        );
+        assert_eq!(warm_up(&private, &pin, &slice).await, None);
+        let offline = TokioRwLock::new(
+            FailoverOrchestrator::new(vec![echo_provider(&requested)])
+                .with_offline(OfflineMode::new(true)),
+        );
+        assert_eq!(warm_up(&offline, &pin, &slice).await, None);
+
+        assert!(requested.lock().unwrap().is_empty());
+        assert_eq!(pin.get(), None);
+    }
+}
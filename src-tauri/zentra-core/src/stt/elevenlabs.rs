@@ -1,8 +1,10 @@
-﻿// src-tauri/src/stt/elevenlabs.rs
+﻿// src-tauri/zentra-core/src/stt/elevenlabs.rs
 // ElevenLabs Scribe STT Adapter (Fallback)
 
-use super::{STTAdapter, STTError, Transcript};
+use super::upload::{encode_upload, UploadFormat};
+use super::{PreparedAudio, STTAdapter, STTError, Transcript};
 use crate::audio::AudioBuffer;
+use crate::provider_error::ProviderApiError;
 use async_trait::async_trait;
 use reqwest::multipart;
 use serde::Deserialize;
@@ -21,6 +23,7 @@ struct ElevenLabsResponse {
 pub struct ElevenLabsAdapter {
     api_key: String,
     client: reqwest::Client,
+    upload_format: UploadFormat,
 }
 
 impl ElevenLabsAdapter {
@@ -32,70 +35,59 @@ impl ElevenLabsAdapter {
 
         tracing::info!("ElevenLabs adapter initialized");
 
-        Self { api_key, client }
-    }
-
-    /// Convert AudioBuffer to WAV bytes
-    fn to_wav_bytes(audio: &AudioBuffer) -> Result<Vec<u8>, STTError> {
-        let sample_rate = audio.sample_rate;
-        let channels = audio.channels;
-        let samples = &audio.samples;
-
-        if samples.is_empty() {
-            return Err(STTError::InvalidAudio);
-        }
-
-        let mut wav = Vec::new();
-
-        // RIFF header
-        wav.extend_from_slice(b"RIFF");
-        let file_size = (36 + samples.len() * 2) as u32;
-        wav.extend_from_slice(&file_size.to_le_bytes());
-        wav.extend_from_slice(b"WAVE");
-
-        // fmt chunk
-        wav.extend_from_slice(b"fmt ");
-        wav.extend_from_slice(&16u32.to_le_bytes());
-        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
-        wav.extend_from_slice(&channels.to_le_bytes());
-        wav.extend_from_slice(&sample_rate.to_le_bytes());
-        let byte_rate = sample_rate * channels as u32 * 2;
-        wav.extend_from_slice(&byte_rate.to_le_bytes());
-        wav.extend_from_slice(&(channels * 2).to_le_bytes());
-        wav.extend_from_slice(&16u16.to_le_bytes());
-
-        // data chunk
-        wav.extend_from_slice(b"data");
-        let data_size = (samples.len() * 2) as u32;
-        wav.extend_from_slice(&data_size.to_le_bytes());
-
-        for &sample in samples {
-            wav.extend_from_slice(&sample.to_le_bytes());
+        Self {
+            api_key,
+            client,
+            upload_format: UploadFormat::default(),
         }
+    }
 
-        Ok(wav)
+    /// Upload WAV instead of FLAC, or back.
+    pub fn with_upload_format(mut self, format: UploadFormat) -> Self {
+        self.upload_format = format;
+        self
     }
 }
 
 #[async_trait]
 impl STTAdapter for ElevenLabsAdapter {
     async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+        let prepared = self.prepare(audio)?;
+        self.transcribe_prepared(audio, &prepared).await
+    }
+
+    fn prepare(&self, audio: &AudioBuffer) -> Result<PreparedAudio, STTError> {
+        Ok(PreparedAudio {
+            bytes: encode_upload(audio, self.upload_format)?,
+            duration_secs: audio.duration_secs,
+            idempotency_key: None,
+            language: None,
+        })
+    }
+
+    async fn transcribe_prepared(
+        &self,
+        audio: &AudioBuffer,
+        prepared: &PreparedAudio,
+    ) -> Result<Transcript, STTError> {
         tracing::info!(
             "ElevenLabs STT: transcribing {:.1}s audio...",
-            audio.duration_secs
+            prepared.duration_secs
         );
 
-        let wav_bytes = Self::to_wav_bytes(audio)?;
-
         // Create form
-        let file_part = multipart::Part::bytes(wav_bytes)
-            .file_name("audio.wav")
-            .mime_str("audio/wav")
+        let file_part = multipart::Part::bytes(prepared.bytes.clone())
+            .file_name(self.upload_format.file_name())
+            .mime_str(self.upload_format.mime_type())
             .map_err(|e| STTError::ProviderError(e.to_string()))?;
 
         let form = multipart::Form::new()
             .text("model_id", "scribe_v1")
             .part("audio", file_part);
+        let form = match prepared.language.as_deref() {
+            Some(language) => form.text("language_code", language.to_string()),
+            None => form,
+        };
 
         let response = self
             .client
@@ -121,16 +113,17 @@ impl STTAdapter for ElevenLabsAdapter {
                         language: eleven_resp.language_code,
                         duration_secs: audio.duration_secs,
                         provider: "ElevenLabs".to_string(),
+                        ..Default::default()
                     })
                 } else if status.as_u16() == 401 {
                     Err(STTError::AuthenticationError)
                 } else if status.as_u16() == 429 {
-                    Err(STTError::RateLimitError)
+                    Err(STTError::rate_limited(resp.headers()))
                 } else {
-                    let error_text = resp.text().await.unwrap_or_default();
-                    Err(STTError::ProviderError(format!(
-                        "HTTP {}: {}",
-                        status, error_text
+                    let body = resp.text().await.unwrap_or_default();
+                    Err(STTError::ProviderApi(ProviderApiError::parse(
+                        status.as_u16(),
+                        &body,
                     )))
                 }
             }
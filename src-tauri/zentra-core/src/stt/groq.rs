@@ -0,0 +1,958 @@
+﻿// src-tauri/zentra-core/src/stt/groq.rs
+// Groq Whisper STT Adapter (Primary)
+
+use super::options::{strip_prompt_echo, SttOptions, PROMPT_ECHO_CONFIDENCE_FACTOR};
+use super::upload::{encode_upload, UploadFormat};
+use super::{
+    normalize_language, PreparedAudio, STTAdapter, STTError, SegmentTiming, StreamingWav,
+    Transcript, WordTiming,
+};
+use crate::audio::AudioBuffer;
+use crate::provider_error::ProviderApiError;
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::multipart;
+use serde::Deserialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const GROQ_API_BASE: &str = "https://api.groq.com/openai/v1";
+const TRANSCRIPTIONS_PATH: &str = "/audio/transcriptions";
+const MAX_DURATION_SECS: f32 = 59.0;
+const TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MODEL: &str = "whisper-large-v3";
+const DEFAULT_LANGUAGE: &str = "pt";
+const TRANSCRIPTION_PROMPT: &str =
+    "Transcreva exatamente a fala em português brasileiro. Não invente texto quando houver silêncio.";
+/// Used when the server omits `avg_logprob` (some OpenAI-compatible servers do).
+const FALLBACK_CONFIDENCE: f32 = 0.95;
+
+#[derive(Deserialize)]
+struct VerboseResponse {
+    text: String,
+    /// Detected language, as an English name (`portuguese`)
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    words: Vec<VerboseWord>,
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
+
+#[derive(Deserialize)]
+struct VerboseWord {
+    word: String,
+    start: f32,
+    end: f32,
+}
+
+#[derive(Deserialize)]
+struct VerboseSegment {
+    text: String,
+    start: f32,
+    end: f32,
+    avg_logprob: Option<f32>,
+    no_speech_prob: Option<f32>,
+}
+
+/// Everything the adapter keeps from a `verbose_json` body.
+struct VerboseResult {
+    text: String,
+    confidence: f32,
+    language: Option<String>,
+    words: Vec<WordTiming>,
+    segments: Vec<SegmentTiming>,
+}
+
+pub struct GroqAdapter {
+    api_key: String,
+    client: reqwest::Client,
+    endpoint: String,
+    model: String,
+    language: Option<String>,
+    timestamps: bool,
+    options: SttOptions,
+    upload_format: UploadFormat,
+}
+
+impl GroqAdapter {
+    /// Default threshold for [`Self::confidence`]. Clear speech usually averages a logprob
+    /// of -0.2 to -0.6, i.e. 0.55-0.8; a take Whisper thinks is silence stays under 0.1.
+    pub const CONFIDENCE_THRESHOLD: f32 = 0.35;
+
+    pub fn new(api_key: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(TIMEOUT_SECS))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        tracing::info!("Groq adapter initialized");
+
+        Self {
+            api_key,
+            client,
+            endpoint: Self::endpoint_for(GROQ_API_BASE),
+            model: DEFAULT_MODEL.to_string(),
+            language: Some(DEFAULT_LANGUAGE.to_string()),
+            timestamps: false,
+            options: SttOptions::new()
+                .with_prompt(TRANSCRIPTION_PROMPT)
+                .expect("built-in prompt is valid"),
+            upload_format: UploadFormat::default(),
+        }
+    }
+
+    /// Replace the request options, e.g. with a user prompt. They are sanitized when built.
+    pub fn with_options(mut self, options: SttOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Use `model` instead of `whisper-large-v3`.
+    pub fn with_model(mut self, model: &str) -> Self {
+        let model = model.trim();
+        if !model.is_empty() {
+            self.model = model.to_string();
+        }
+        self
+    }
+
+    /// Ask for `language` (e.g. `en`) instead of Portuguese; `auto` lets the server detect it.
+    pub fn with_language(mut self, language: &str) -> Self {
+        let language = language.trim();
+        if language.eq_ignore_ascii_case("auto") {
+            self.language = None;
+        } else if !language.is_empty() {
+            self.language = Some(language.to_string());
+        }
+        self
+    }
+
+    /// Return word and segment timings with the transcript. Requests always use
+    /// `verbose_json` (confidence comes from its segments); this only controls the timings.
+    pub fn with_timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    /// Upload WAV instead of FLAC, or back.
+    pub fn with_upload_format(mut self, format: UploadFormat) -> Self {
+        self.upload_format = format;
+        self
+    }
+
+    /// Send requests to `base_url` (e.g. `http://localhost:8000/v1`) instead of Groq.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.endpoint = Self::endpoint_for(base_url);
+        self
+    }
+
+    fn endpoint_for(base_url: &str) -> String {
+        format!("{}{}", base_url.trim_end_matches('/'), TRANSCRIPTIONS_PATH)
+    }
+
+    fn effective_duration_secs(audio: &AudioBuffer) -> f32 {
+        if audio.duration_secs > 0.0 {
+            return audio.duration_secs;
+        }
+        if audio.sample_rate == 0 {
+            return 0.0;
+        }
+        let channels = audio.channels.max(1) as f32;
+        audio.samples.len() as f32 / (audio.sample_rate as f32 * channels)
+    }
+
+    fn clean_transcript(text: &str) -> String {
+        static TS_RE: OnceLock<Regex> = OnceLock::new();
+        let re = TS_RE.get_or_init(|| {
+            Regex::new(r"\[\d{2}:\d{2}.*?\]|\(\d{2}:\d{2}\)").expect("valid timestamp regex")
+        });
+        let stripped = re.replace_all(text, "");
+        stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Split a `verbose_json` body into the plain text, its confidence and its timings. Word
+    /// and segment text is only trimmed; the timestamp cleanup applies to the full text alone.
+    fn parse_verbose(body: &str) -> Result<VerboseResult, STTError> {
+        let parsed: VerboseResponse = serde_json::from_str(body)
+            .map_err(|e| STTError::ProviderError(format!("Invalid verbose_json: {}", e)))?;
+
+        let confidence = Self::confidence(&parsed.segments);
+        let words = parsed
+            .words
+            .into_iter()
+            .map(|word| WordTiming {
+                word: word.word.trim().to_string(),
+                start_secs: word.start,
+                end_secs: word.end,
+            })
+            .collect();
+        let segments = parsed
+            .segments
+            .into_iter()
+            .map(|segment| SegmentTiming {
+                text: segment.text.trim().to_string(),
+                start_secs: segment.start,
+                end_secs: segment.end,
+            })
+            .collect();
+
+        Ok(VerboseResult {
+            text: parsed.text,
+            confidence,
+            language: parsed.language.as_deref().and_then(normalize_language),
+            words,
+            segments,
+        })
+    }
+
+    /// Duration-weighted mean of `exp(avg_logprob) * (1 - no_speech_prob)` over the segments.
+    /// A segment Whisper thinks is silence scores near zero however sure it is of the words,
+    /// which is what lets a hallucinated "Obrigado." fail the orchestrator's threshold.
+    fn confidence(segments: &[VerboseSegment]) -> f32 {
+        let mut weighted = 0.0f32;
+        let mut total = 0.0f32;
+        for segment in segments {
+            let Some(avg_logprob) = segment.avg_logprob else {
+                continue;
+            };
+            let speech_prob = 1.0 - segment.no_speech_prob.unwrap_or(0.0).clamp(0.0, 1.0);
+            let score = avg_logprob.min(0.0).exp() * speech_prob;
+            // Zero-length segments still count, just barely.
+            let weight = (segment.end - segment.start).max(0.01);
+            weighted += score * weight;
+            total += weight;
+        }
+
+        if total > 0.0 {
+            (weighted / total).clamp(0.0, 1.0)
+        } else {
+            FALLBACK_CONFIDENCE
+        }
+    }
+
+    /// `language` is sent over the configured one.
+    fn form(&self, file_part: multipart::Part, language: Option<&str>) -> multipart::Form {
+        let form = multipart::Form::new()
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .text("temperature", "0")
+            .part("file", file_part);
+
+        let form = if let Some(prompt) = self.options.prompt() {
+            form.text("prompt", prompt.to_string())
+        } else {
+            form
+        };
+
+        let form = if self.timestamps {
+            form.text("timestamp_granularities[]", "word")
+                .text("timestamp_granularities[]", "segment")
+        } else {
+            form
+        };
+
+        if let Some(language) = language.or(self.language.as_deref()) {
+            form.text("language", language.to_string())
+        } else {
+            form
+        }
+    }
+
+    async fn read_response(
+        &self,
+        response: reqwest::Result<reqwest::Response>,
+        duration_secs: f32,
+    ) -> Result<Transcript, STTError> {
+        match response {
+            Ok(resp) => {
+                let status = resp.status();
+
+                if status.is_success() {
+                    let body = resp
+                        .text()
+                        .await
+                        .map_err(|e| STTError::ProviderError(e.to_string()))?;
+                    let parsed = Self::parse_verbose(&body)?;
+                    let mut cleaned = Self::clean_transcript(&parsed.text);
+                    let mut confidence = parsed.confidence;
+
+                    // Whisper sometimes repeats the prompt before the speech, or instead of it.
+                    if let Some(rest) = self
+                        .options
+                        .prompt()
+                        .and_then(|prompt| strip_prompt_echo(&cleaned, prompt))
+                    {
+                        tracing::warn!("Groq STT: stripped an echo of the prompt from the output");
+                        cleaned = rest;
+                        confidence *= PROMPT_ECHO_CONFIDENCE_FACTOR;
+                    }
+
+                    if cleaned.is_empty() {
+                        return Err(STTError::ProviderError("Empty transcript".to_string()));
+                    }
+
+                    let (words, segments) = if self.timestamps {
+                        (parsed.words, parsed.segments)
+                    } else {
+                        (Vec::new(), Vec::new())
+                    };
+                    Ok(Transcript {
+                        text: cleaned,
+                        confidence,
+                        language: parsed.language.or_else(|| self.language.clone()),
+                        duration_secs,
+                        provider: "Groq".to_string(),
+                        words,
+                        segments,
+                    })
+                } else if status.as_u16() == 401 {
+                    Err(STTError::AuthenticationError)
+                } else if status.as_u16() == 429 {
+                    Err(STTError::rate_limited(resp.headers()))
+                } else {
+                    let body = resp.text().await.unwrap_or_default();
+                    Err(STTError::ProviderApi(ProviderApiError::parse(
+                        status.as_u16(),
+                        &body,
+                    )))
+                }
+            }
+            Err(e) => {
+                if e.is_timeout() {
+                    Err(STTError::TimeoutError)
+                } else {
+                    Err(STTError::NetworkError(e.to_string()))
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl STTAdapter for GroqAdapter {
+    async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+        let prepared = self.prepare(audio)?;
+        self.transcribe_prepared(audio, &prepared).await
+    }
+
+    fn prepare(&self, audio: &AudioBuffer) -> Result<PreparedAudio, STTError> {
+        let duration_secs = Self::effective_duration_secs(audio);
+
+        // Validate duration (Groq hard limit: 59s)
+        if duration_secs > MAX_DURATION_SECS {
+            tracing::warn!(
+                "Audio too long: {:.1}s > {:.1}s",
+                duration_secs,
+                MAX_DURATION_SECS
+            );
+            return Err(STTError::AudioTooLong);
+        }
+
+        // Encode once; retries reuse these bytes
+        Ok(PreparedAudio {
+            bytes: encode_upload(audio, self.upload_format)?,
+            duration_secs,
+            idempotency_key: None,
+            language: None,
+        })
+    }
+
+    async fn transcribe_prepared(
+        &self,
+        _audio: &AudioBuffer,
+        prepared: &PreparedAudio,
+    ) -> Result<Transcript, STTError> {
+        let duration_secs = prepared.duration_secs;
+
+        tracing::info!(
+            "Groq STT: transcribing {:.1}s audio with model {}",
+            duration_secs,
+            self.model
+        );
+
+        // Create multipart form
+        let file_part = multipart::Part::bytes(prepared.bytes.clone())
+            .file_name(self.upload_format.file_name())
+            .mime_str(self.upload_format.mime_type())
+            .map_err(|e| STTError::ProviderError(e.to_string()))?;
+
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .multipart(self.form(file_part, prepared.language.as_deref()));
+        if let Some(key) = prepared.idempotency_key.as_deref() {
+            request = request.header("Idempotency-Key", key);
+        }
+        let response = request.send().await;
+        self.read_response(response, duration_secs).await
+    }
+
+    fn streams(&self) -> bool {
+        true
+    }
+
+    /// The file goes up as WAV at its own rate whatever the upload format, since FLAC and
+    /// resampling need the whole recording.
+    async fn transcribe_streaming(
+        &self,
+        upload: StreamingWav,
+        language: Option<String>,
+    ) -> Result<Transcript, STTError> {
+        tracing::info!(
+            "Groq STT: streaming {:.1}s audio with model {}",
+            upload.duration_secs,
+            self.model
+        );
+        let file_part = multipart::Part::stream_with_length(upload.body, upload.len)
+            .file_name(UploadFormat::Wav.file_name())
+            .mime_str(UploadFormat::Wav.mime_type())
+            .map_err(|e| STTError::ProviderError(e.to_string()))?;
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .multipart(self.form(file_part, language.as_deref()))
+            .send()
+            .await;
+        self.read_response(response, upload.duration_secs).await
+    }
+
+    fn name(&self) -> &str {
+        "Groq Whisper"
+    }
+
+    fn request_context(&self) -> String {
+        format!(
+            "groq:{}:{}:{}",
+            self.model,
+            self.language.as_deref().unwrap_or("auto"),
+            if self.timestamps {
+                "timestamps"
+            } else {
+                "text"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{
+        FailoverOrchestrator, OrchestratorError, ProviderConfig, DEFAULT_MAX_CONCURRENT,
+    };
+    use std::collections::HashMap;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    const ENDPOINT: &str = "/openai/v1/audio/transcriptions";
+
+    fn adapter(server: &MockServer) -> GroqAdapter {
+        GroqAdapter::new("gsk_test".to_string())
+            .with_base_url(&format!("{}/openai/v1", server.uri()))
+    }
+
+    fn orchestrator(server: &MockServer, max_retries: u8) -> FailoverOrchestrator {
+        FailoverOrchestrator::new(vec![ProviderConfig {
+            id: "groq".to_string(),
+            priority: 1,
+            adapter: Box::new(adapter(server)),
+            max_retries,
+            timeout_secs: 10,
+            confidence_threshold: GroqAdapter::CONFIDENCE_THRESHOLD,
+            local: false,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+        }])
+    }
+
+    fn speech() -> AudioBuffer {
+        let samples = (0..48_000)
+            .map(|idx| ((idx as f32 * 0.05).sin() * 8_000.0) as i16)
+            .collect::<Vec<_>>();
+        let mut audio = AudioBuffer::new(48_000, 1);
+        audio.append(&samples);
+        audio
+    }
+
+    /// `verbose_json` body with one segment per `(text, avg_logprob, no_speech_prob)`, each
+    /// two seconds long.
+    fn verbose_body(segments: &[(&str, f32, f32)]) -> serde_json::Value {
+        let text = segments
+            .iter()
+            .map(|(text, _, _)| *text)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(|(idx, (text, avg_logprob, no_speech_prob))| {
+                serde_json::json!({
+                    "id": idx,
+                    "start": idx as f32 * 2.0,
+                    "end": (idx + 1) as f32 * 2.0,
+                    "text": text,
+                    "avg_logprob": avg_logprob,
+                    "no_speech_prob": no_speech_prob
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "text": text, "segments": segments })
+    }
+
+    fn confidence_of(body: serde_json::Value) -> f32 {
+        GroqAdapter::parse_verbose(&body.to_string())
+            .unwrap()
+            .confidence
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    /// Minimal multipart/form-data parser: field name -> raw value.
+    fn form_parts(request: &Request) -> HashMap<String, Vec<u8>> {
+        let content_type = request.headers["content-type"].to_str().unwrap();
+        let boundary = format!("--{}", content_type.split("boundary=").nth(1).unwrap());
+
+        let mut parts = HashMap::new();
+        let mut rest = request.body.as_slice();
+        while let Some(start) = find(rest, boundary.as_bytes()) {
+            rest = &rest[start + boundary.len()..];
+            let chunk = &rest[..find(rest, boundary.as_bytes()).unwrap_or(rest.len())];
+            let Some(header_end) = find(chunk, b"\r\n\r\n") else {
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&chunk[..header_end]);
+            let Some(name) = headers
+                .split("name=\"")
+                .nth(1)
+                .and_then(|tail| tail.split('"').next())
+            else {
+                continue;
+            };
+            let value = &chunk[header_end + 4..];
+            let value = value.strip_suffix(b"\r\n").unwrap_or(value);
+            parts.insert(name.to_string(), value.to_vec());
+        }
+        parts
+    }
+
+    fn text_field(parts: &HashMap<String, Vec<u8>>, name: &str) -> Option<String> {
+        parts
+            .get(name)
+            .map(|value| String::from_utf8_lossy(value).into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_request_form_carries_settings_and_flac() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .and(header("authorization", "Bearer gsk_test"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "text": "  olá   mundo \n" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let groq = adapter(&server);
+        let transcript = groq.transcribe(&speech()).await.unwrap();
+        assert_eq!(transcript.text, "olá mundo");
+        // No segment scores in the body, so the fallback applies.
+        assert_eq!(transcript.confidence, FALLBACK_CONFIDENCE);
+        assert!(transcript.segments.is_empty());
+
+        let requests = server.received_requests().await.unwrap();
+        let parts = form_parts(&requests[0]);
+        assert_eq!(text_field(&parts, "model"), Some(groq.model.clone()));
+        assert_eq!(text_field(&parts, "language"), groq.language.clone());
+        let prompt = text_field(&parts, "prompt").unwrap();
+        assert_eq!(Some(prompt.as_str()), groq.options.prompt());
+        // Sent without the final period that invites Whisper to continue it.
+        assert_eq!(format!("{}.", prompt), TRANSCRIPTION_PROMPT);
+        assert_eq!(text_field(&parts, "temperature").as_deref(), Some("0"));
+        assert_eq!(
+            text_field(&parts, "response_format").as_deref(),
+            Some("verbose_json")
+        );
+        assert!(!parts.contains_key("timestamp_granularities[]"));
+
+        let body = &requests[0].body;
+        assert!(find(body, b"filename=\"audio.flac\"").is_some());
+        assert!(find(body, b"Content-Type: audio/flac").is_some());
+        let flac = &parts["file"];
+        assert_eq!(&flac[0..4], b"fLaC");
+        // One second resampled to 16 kHz mono, well under the 32 KB of WAV.
+        assert!(flac.len() < 16_000 * 2);
+    }
+
+    #[tokio::test]
+    async fn test_wav_upload_can_be_forced() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "text": "olá" })),
+            )
+            .mount(&server)
+            .await;
+
+        adapter(&server)
+            .with_upload_format(UploadFormat::Wav)
+            .transcribe(&speech())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(find(&requests[0].body, b"filename=\"audio.wav\"").is_some());
+        let wav = &form_parts(&requests[0])["file"];
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 1);
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            crate::stt::UPLOAD_SAMPLE_RATE
+        );
+        assert_eq!(wav.len(), 44 + 16_000 * 2);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_upload_sends_the_fed_samples_as_wav() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "text": "olá" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let audio = speech();
+        let (mut feed, upload) =
+            crate::stt::streaming_wav(audio.samples.len(), audio.sample_rate, audio.channels);
+        let samples = audio.samples.clone();
+        let recording = tokio::spawn(async move {
+            for chunk in samples.chunks(4_800) {
+                feed.send(chunk).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+        let transcript = adapter(&server)
+            .transcribe_streaming(upload, Some("pt".to_string()))
+            .await
+            .unwrap();
+        recording.await.unwrap();
+        assert_eq!(transcript.text, "olá");
+        assert!((transcript.duration_secs - 1.0).abs() < 1e-6);
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(find(&requests[0].body, b"filename=\"audio.wav\"").is_some());
+        let parts = form_parts(&requests[0]);
+        assert_eq!(text_field(&parts, "model"), Some(adapter(&server).model));
+        assert_eq!(text_field(&parts, "language"), Some("pt".to_string()));
+        let sent = crate::audio::wav::decode_wav(&parts["file"]).unwrap();
+        assert_eq!(sent.samples, audio.samples);
+        assert_eq!(sent.sample_rate, 48_000);
+    }
+
+    #[tokio::test]
+    async fn test_verbose_json_returns_timings() {
+        let server = MockServer::start().await;
+        let body = serde_json::json!({
+            "text": " [00:00] Olá  mundo ",
+            "language": "portuguese",
+            "segments": [{ "id": 0, "start": 0.0, "end": 0.9, "text": " Olá mundo" }],
+            "words": [
+                { "word": " Olá", "start": 0.0, "end": 0.4 },
+                { "word": "mundo", "start": 0.45, "end": 0.9 }
+            ]
+        });
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&server)
+            .await;
+
+        let transcript = adapter(&server)
+            .with_timestamps(true)
+            .transcribe(&speech())
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "Olá mundo");
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[0].word, "Olá");
+        assert_eq!(transcript.words[1].start_secs, 0.45);
+        assert_eq!(transcript.segments[0].text, "Olá mundo");
+
+        let requests = server.received_requests().await.unwrap();
+        let parts = form_parts(&requests[0]);
+        assert!(parts.contains_key("timestamp_granularities[]"));
+    }
+
+    #[tokio::test]
+    async fn test_detected_language_is_reported_and_a_request_language_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(
+                    serde_json::json!({ "text": "Hello there", "language": "english" }),
+                ),
+            )
+            .mount(&server)
+            .await;
+
+        let mut groq = adapter(&server);
+        groq.language = None;
+        let audio = speech();
+        let transcript = groq.transcribe(&audio).await.unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+
+        let prepared = PreparedAudio {
+            language: Some("pt".to_string()),
+            ..groq.prepare(&audio).unwrap()
+        };
+        groq.transcribe_prepared(&audio, &prepared).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(text_field(&form_parts(&requests[0]), "language"), None);
+        assert_eq!(
+            text_field(&form_parts(&requests[1]), "language").as_deref(),
+            Some("pt")
+        );
+    }
+
+    #[test]
+    fn test_confidence_follows_segment_scores() {
+        let clean = confidence_of(verbose_body(&[
+            ("Bom dia a todos,", -0.12, 0.01),
+            ("vamos começar a reunião.", -0.18, 0.02),
+        ]));
+        assert!(clean > 0.8, "clean speech scored {}", clean);
+
+        // Ordinary speech, a little unsure of itself, still passes.
+        let ordinary = confidence_of(verbose_body(&[
+            ("Então, o relatório", -0.45, 0.05),
+            ("ficou pronto ontem à tarde.", -0.6, 0.08),
+        ]));
+        assert!(
+            ordinary > GroqAdapter::CONFIDENCE_THRESHOLD,
+            "ordinary speech scored {}",
+            ordinary
+        );
+
+        // Garbled segments pull a take down without making it look like silence.
+        let noisy = confidence_of(verbose_body(&[
+            ("O relatório", -0.35, 0.1),
+            ("ficou pronto ontem", -0.9, 0.35),
+            ("à tarde", -0.7, 0.3),
+        ]));
+        assert!(noisy > 0.2 && noisy < 0.7, "noisy speech scored {}", noisy);
+
+        // Whisper is sure of the words it hallucinates, but also sure nobody spoke.
+        let silence = confidence_of(verbose_body(&[("Obrigado.", -0.05, 0.97)]));
+        assert!(silence < 0.1, "silence scored {}", silence);
+
+        let no_scores = serde_json::json!({
+            "text": "olá",
+            "segments": [{ "start": 0.0, "end": 1.0, "text": "olá" }]
+        });
+        assert_eq!(confidence_of(no_scores), FALLBACK_CONFIDENCE);
+    }
+
+    #[tokio::test]
+    async fn test_hallucinated_silence_fails_confidence_check() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(verbose_body(&[(
+                "Obrigado por assistir.",
+                -0.2,
+                0.92,
+            )])))
+            .mount(&server)
+            .await;
+
+        let err = orchestrator(&server, 0)
+            .transcribe(&speech())
+            .await
+            .unwrap_err();
+        let OrchestratorError::AllProvidersFailed(errors) = err else {
+            panic!("expected provider failure");
+        };
+        assert!(
+            matches!(&errors[0].1, STTError::ProviderError(msg) if msg == "Low confidence"),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prompt_echo_is_stripped_and_lowers_confidence() {
+        let server = MockServer::start().await;
+        let echoed = format!(
+            "{}. Bom dia a todos.",
+            TRANSCRIPTION_PROMPT.trim_end_matches('.')
+        );
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "text": echoed })),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // Nothing but the prompt's second sentence, over silence.
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "text": "Não invente texto quando houver silêncio." }),
+            ))
+            .mount(&server)
+            .await;
+
+        let groq = adapter(&server);
+        let transcript = groq.transcribe(&speech()).await.unwrap();
+        assert_eq!(transcript.text, "Bom dia a todos.");
+        assert!(transcript.confidence < FALLBACK_CONFIDENCE);
+
+        let err = groq.transcribe(&speech()).await.unwrap_err();
+        assert!(
+            matches!(&err, STTError::ProviderError(msg) if msg == "Empty transcript"),
+            "{:?}",
+            err
+        );
+
+        // Without a prompt the same words are taken as speech.
+        let transcript = adapter(&server)
+            .with_options(SttOptions::new())
+            .transcribe(&speech())
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "Não invente texto quando houver silêncio.");
+        assert_eq!(transcript.confidence, FALLBACK_CONFIDENCE);
+        let requests = server.received_requests().await.unwrap();
+        assert!(!form_parts(&requests[2]).contains_key("prompt"));
+    }
+
+    #[tokio::test]
+    async fn test_error_statuses_map_to_stt_errors() {
+        for status in [401, 429, 500] {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path(ENDPOINT))
+                .respond_with(ResponseTemplate::new(status).set_body_string("nope"))
+                .mount(&server)
+                .await;
+
+            let err = adapter(&server).transcribe(&speech()).await.unwrap_err();
+            match status {
+                401 => assert!(matches!(err, STTError::AuthenticationError)),
+                429 => assert!(matches!(
+                    err,
+                    STTError::RateLimitError {
+                        retry_after_secs: None
+                    }
+                )),
+                _ => assert!(
+                    matches!(&err, STTError::ProviderApi(api) if api.status == 500 && api.message == "nope"),
+                    "{:?}",
+                    err
+                ),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_structured_error_bodies_reach_the_error_code() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                r#"{"error":{"message":"The model `whisper-large-v4` does not exist or you do not have access to it.","type":"invalid_request_error","code":"model_not_found"}}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let err = adapter(&server).transcribe(&speech()).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The model `whisper-large-v4` does not exist or you do not have access to it. (HTTP 404, model_not_found)"
+        );
+        assert_eq!(
+            crate::error_codes::ErrorCode::from(&err),
+            crate::error_codes::ErrorCode::SttModelNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_orchestrator_retries_after_rate_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(verbose_body(&[(
+                "tudo certo",
+                -0.1,
+                0.01,
+            )])))
+            .mount(&server)
+            .await;
+
+        let started = std::time::Instant::now();
+        let transcript = orchestrator(&server, 2)
+            .transcribe(&speech())
+            .await
+            .unwrap();
+        assert_eq!(transcript.text, "tudo certo");
+        // The header's 1 s with jitter, not the 2 s of the exponential schedule
+        let waited = started.elapsed();
+        assert!(waited >= Duration::from_millis(800), "{:?}", waited);
+        assert!(waited < Duration::from_secs(2), "{:?}", waited);
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        // Retries reuse the same idempotency key so the server can deduplicate them.
+        assert_eq!(
+            requests[0].headers.get("idempotency-key"),
+            requests[1].headers.get("idempotency-key")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeated_server_errors_trip_the_breaker() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ENDPOINT))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let orchestrator = orchestrator(&server, 0);
+        for _ in 0..3 {
+            assert!(orchestrator.transcribe(&speech()).await.is_err());
+        }
+
+        let err = orchestrator.transcribe(&speech()).await.unwrap_err();
+        let OrchestratorError::AllProvidersFailed(errors) = err else {
+            panic!("expected provider failure");
+        };
+        assert!(
+            matches!(&errors[0].1, STTError::ProviderError(msg) if msg.contains("Circuit breaker open"))
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+}
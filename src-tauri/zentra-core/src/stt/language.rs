@@ -0,0 +1,87 @@
+//! The language a session settled on. With the language on auto, each request makes the
+//! provider guess again, and short or mixed segments sometimes come back in the wrong one;
+//! once a session's language is known it is pinned and sent with every request.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Shared language pin, read by the orchestrator on every request.
+#[derive(Debug, Clone, Default)]
+pub struct LanguagePin {
+    inner: Arc<Mutex<Option<String>>>,
+}
+
+impl LanguagePin {
+    pub fn get(&self) -> Option<String> {
+        self.lock().clone()
+    }
+
+    /// Pin `language` unless one already is; returns whether it was pinned. Anything
+    /// [`normalize_language`] does not know is left unpinned.
+    pub fn pin(&self, language: &str) -> bool {
+        let Some(code) = normalize_language(language) else {
+            return false;
+        };
+        let mut pinned = self.lock();
+        if pinned.is_some() {
+            return false;
+        }
+        *pinned = Some(code);
+        true
+    }
+
+    pub fn clear(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<String>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// ISO 639-1 code for a language as providers report it: Whisper gives English names
+/// (`portuguese`), Scribe ISO 639-3 codes (`por`).
+pub fn normalize_language(language: &str) -> Option<String> {
+    const KNOWN: [(&str, &str, &str); 12] = [
+        ("pt", "portuguese", "por"),
+        ("en", "english", "eng"),
+        ("es", "spanish", "spa"),
+        ("fr", "french", "fra"),
+        ("de", "german", "deu"),
+        ("it", "italian", "ita"),
+        ("nl", "dutch", "nld"),
+        ("ru", "russian", "rus"),
+        ("ja", "japanese", "jpn"),
+        ("zh", "chinese", "zho"),
+        ("ko", "korean", "kor"),
+        ("pl", "polish", "pol"),
+    ];
+    let language = language.trim().to_ascii_lowercase();
+    KNOWN
+        .iter()
+        .find(|(code, name, iso3)| [*code, *name, *iso3].contains(&language.as_str()))
+        .map(|(code, _, _)| code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_the_first_known_language_is_pinned() {
+        assert_eq!(normalize_language("Portuguese").as_deref(), Some("pt"));
+        assert_eq!(normalize_language("eng").as_deref(), Some("en"));
+        assert_eq!(normalize_language(" pt ").as_deref(), Some("pt"));
+        assert_eq!(normalize_language("klingon"), None);
+
+        let pin = LanguagePin::default();
+        assert!(!pin.pin("klingon"));
+        assert_eq!(pin.get(), None);
+        assert!(pin.pin("portuguese"));
+        assert!(!pin.clone().pin("english"));
+        assert_eq!(pin.get().as_deref(), Some("pt"));
+        pin.clear();
+        assert_eq!(pin.get(), None);
+    }
+}
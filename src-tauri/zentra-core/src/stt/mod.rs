@@ -1,4 +1,4 @@
-﻿// src-tauri/src/stt/mod.rs
+﻿// src-tauri/zentra-core/src/stt/mod.rs
 // STT Module - Speech-to-Text Adapters
 
 mod types;
@@ -7,16 +7,29 @@ mod elevenlabs;
 #[cfg(feature = "vosk-stt")]
 mod vosk;
 mod whisper;
-
-pub use types::{Transcript, STTError};
+mod language;
+mod options;
+mod upload;
+mod streaming;
+
+pub use types::{
+    PreparedAudio, SegmentTiming, Transcript, TranscriptionProgress, TranscriptionProgressSink,
+    STTError, WordTiming,
+};
 pub use groq::GroqAdapter;
 pub use elevenlabs::ElevenLabsAdapter;
 #[cfg(feature = "vosk-stt")]
 pub use vosk::VoskAdapter;
-pub use whisper::WhisperAdapter;
+pub use whisper::{WhisperAdapter, WhisperOptions};
+pub use language::{normalize_language, LanguagePin};
+pub use options::{sanitize_prompt, strip_prompt_echo, PromptError, SttOptions};
+pub use upload::{encode_upload, UploadFormat, UPLOAD_SAMPLE_RATE};
+pub use streaming::{streaming_wav, wav_len, SampleFeed, StreamingWav};
 
 use crate::audio::AudioBuffer;
+use crate::orchestrator::ProviderSetup;
 use async_trait::async_trait;
+use std::sync::Arc;
 
 /// Unified STT Adapter trait
 #[async_trait]
@@ -24,8 +37,59 @@ pub trait STTAdapter: Send + Sync {
     /// Transcribe audio buffer to text
     async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError>;
 
+    /// Encode the request payload once so retry attempts can reuse it
+    fn prepare(&self, audio: &AudioBuffer) -> Result<PreparedAudio, STTError> {
+        Ok(PreparedAudio {
+            bytes: Vec::new(),
+            duration_secs: audio.duration_secs,
+            idempotency_key: None,
+            language: None,
+        })
+    }
+
+    /// Send a payload built by `prepare`; adapters without a payload just transcribe
+    async fn transcribe_prepared(
+        &self,
+        audio: &AudioBuffer,
+        prepared: &PreparedAudio,
+    ) -> Result<Transcript, STTError> {
+        let _ = prepared;
+        self.transcribe(audio).await
+    }
+
+    /// Whether [`STTAdapter::transcribe_streaming`] is supported
+    fn streams(&self) -> bool {
+        false
+    }
+
+    /// Transcribe a WAV that is still being recorded; see [`streaming_wav`]. `language` is
+    /// sent over the configured one. No retries: a failed stream is gone, and the caller
+    /// falls back to a buffered request.
+    async fn transcribe_streaming(
+        &self,
+        upload: StreamingWav,
+        language: Option<String>,
+    ) -> Result<Transcript, STTError> {
+        let _ = (upload, language);
+        Err(STTError::ProviderError(format!(
+            "{} does not take streaming uploads",
+            self.name()
+        )))
+    }
+
     /// Get provider name
     fn name(&self) -> &str;
+
+    /// Where to report progress; only adapters that run for a while locally report any
+    fn set_progress_sink(&mut self, sink: Arc<dyn TranscriptionProgressSink>) {
+        let _ = sink;
+    }
+
+    /// Settings besides the audio that change the result (model, language);
+    /// part of the idempotency key
+    fn request_context(&self) -> String {
+        self.name().to_string()
+    }
 }
 
 /// STT Manager with failover support
@@ -38,25 +102,43 @@ pub struct STTManager {
 }
 
 impl STTManager {
-    /// Create new STT Manager from environment variables
-    pub fn new() -> Self {
-        let groq_key = std::env::var("GROQ_API_KEY").ok();
-        let eleven_key = std::env::var("ELEVENLABS_API_KEY").ok();
-
-        let groq = groq_key
+    /// Create new STT Manager from the keys and engines in `setup`
+    pub fn new(setup: &ProviderSetup) -> Self {
+        let groq = setup
+            .groq_key
+            .clone()
             .filter(|k| k.starts_with("gsk_"))
-            .map(|k| GroqAdapter::new(k));
+            .map(|key| GroqAdapter::new(key).with_upload_format(setup.transcription.upload_format));
 
-        let elevenlabs = eleven_key
+        let elevenlabs = setup
+            .elevenlabs_key
+            .clone()
             .filter(|k| k.starts_with("sk_"))
-            .map(|k| ElevenLabsAdapter::new(k));
+            .map(|key| {
+                ElevenLabsAdapter::new(key).with_upload_format(setup.transcription.upload_format)
+            });
 
         #[cfg(feature = "vosk-stt")]
         let vosk = {
-            let model_pt = std::env::var("VOSK_MODEL_PT")
-                .unwrap_or_else(|_| "models/vosk-model-small-pt-0.3".to_string());
-            let model_en = std::env::var("VOSK_MODEL_EN")
-                .unwrap_or_else(|_| "models/vosk-model-small-en-us-0.15".to_string());
+            // In the models dir when there, else next to the app
+            let bundled = |name: &str| {
+                setup
+                    .models_dir
+                    .as_deref()
+                    .map(|dir| dir.join(name))
+                    .filter(|path| path.exists())
+                    .unwrap_or_else(|| std::path::Path::new("models").join(name))
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            let model_pt = setup
+                .vosk_model_pt
+                .clone()
+                .unwrap_or_else(|| bundled("vosk-model-small-pt-0.3"));
+            let model_en = setup
+                .vosk_model_en
+                .clone()
+                .unwrap_or_else(|| bundled("vosk-model-small-en-us-0.15"));
 
             match VoskAdapter::new(&model_pt, &model_en) {
                 Ok(v) => Some(v),
@@ -72,7 +154,10 @@ impl STTManager {
         #[cfg(not(feature = "vosk-stt"))]
         let vosk_available = false;
 
-        let whisper = WhisperAdapter::from_env();
+        let whisper = setup
+            .whisper
+            .as_ref()
+            .and_then(|whisper| setup.whisper_adapter(whisper, whisper.options.clone()));
 
         tracing::info!(
             "STT Manager initialized: Groq={}, VOSK={}, ElevenLabs={}, Whisper={}",
@@ -152,9 +237,3 @@ impl STTManager {
         ))
     }
 }
-
-impl Default for STTManager {
-    fn default() -> Self {
-        Self::new()
-    }
-}
@@ -0,0 +1,208 @@
+// src-tauri/zentra-core/src/stt/options.rs
+// Request options shared by the Whisper-style adapters
+
+use thiserror::Error;
+
+/// Whisper reads at most 224 prompt tokens; at roughly 4 tokens per 3 words this keeps a
+/// prompt near 200 and leaves the model room for its own context.
+pub const MAX_PROMPT_WORDS: usize = 150;
+/// Output that opens with this many prompt words in a row is an echo, not speech.
+pub const PROMPT_ECHO_MIN_WORDS: usize = 5;
+/// Confidence multiplier for a transcript that had a prompt echo stripped from it.
+pub const PROMPT_ECHO_CONFIDENCE_FACTOR: f32 = 0.8;
+
+/// Phrases Whisper invents over silence. A prompt that repeats one primes the model to
+/// produce it, so such prompts are refused. Compared after [`normalize_words`].
+const HALLUCINATION_PATTERNS: &[&str] = &[
+    "obrigado por assistir",
+    "legendas pela comunidade amara org",
+    "inscreva se no canal",
+    "thanks for watching",
+    "thank you for watching",
+    "subtitles by the amara org community",
+    "please subscribe",
+];
+
+/// Trailing characters that invite the model to continue the prompt into the transcript.
+const CONTINUATION_CHARS: &[char] = &[
+    '.', '!', '?', '…', ':', ';', ',', '-', '–', '—', '"', '\'', '“', '”', '«', '»',
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PromptError {
+    #[error("Prompt repeats a known Whisper hallucination: \"{0}\"")]
+    HallucinationPattern(String),
+}
+
+/// Options sent with every request. The prompt is sanitized on the way in, so adapters can
+/// pass it through as is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SttOptions {
+    prompt: Option<String>,
+}
+
+impl SttOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the prompt. An empty prompt (after sanitizing) clears it.
+    pub fn with_prompt(mut self, prompt: &str) -> Result<Self, PromptError> {
+        self.prompt = sanitize_prompt(prompt)?;
+        Ok(self)
+    }
+
+    /// Append vocabulary terms to the prompt as a comma-separated list; Whisper spells
+    /// names it has seen in the prompt the same way.
+    pub fn with_vocabulary(self, terms: &[String]) -> Result<Self, PromptError> {
+        let vocabulary = terms
+            .iter()
+            .map(|term| term.trim())
+            .filter(|term| !term.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if vocabulary.is_empty() {
+            return Ok(self);
+        }
+        let combined = match self.prompt.as_deref() {
+            Some(prompt) => format!("{}, {}", prompt, vocabulary),
+            None => vocabulary,
+        };
+        self.with_prompt(&combined)
+    }
+
+    pub fn prompt(&self) -> Option<&str> {
+        self.prompt.as_deref()
+    }
+}
+
+/// Lowercased words with punctuation removed, for comparing text regardless of formatting.
+fn normalize_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Collapse the prompt onto one line, bound it to [`MAX_PROMPT_WORDS`] and drop the
+/// trailing punctuation. `Ok(None)` when nothing is left.
+pub fn sanitize_prompt(raw: &str) -> Result<Option<String>, PromptError> {
+    let words: Vec<&str> = raw.split_whitespace().take(MAX_PROMPT_WORDS).collect();
+    let joined = words.join(" ");
+    let prompt =
+        joined.trim_end_matches(|c: char| c.is_whitespace() || CONTINUATION_CHARS.contains(&c));
+    if prompt.is_empty() {
+        return Ok(None);
+    }
+
+    let normalized = format!(" {} ", normalize_words(prompt).join(" "));
+    if let Some(pattern) = HALLUCINATION_PATTERNS
+        .iter()
+        .find(|pattern| normalized.contains(&format!(" {} ", pattern)))
+    {
+        return Err(PromptError::HallucinationPattern(pattern.to_string()));
+    }
+    Ok(Some(prompt.to_string()))
+}
+
+/// If `text` opens with at least [`PROMPT_ECHO_MIN_WORDS`] consecutive words of `prompt`,
+/// return what follows them (possibly empty). `None` when there is no echo.
+pub fn strip_prompt_echo(text: &str, prompt: &str) -> Option<String> {
+    let prompt_words = normalize_words(prompt);
+    let text_words: Vec<&str> = text.split_whitespace().collect();
+    let normalized_text: Vec<String> = text_words
+        .iter()
+        .map(|word| normalize_words(word).join(""))
+        .collect();
+
+    // Longest run of prompt words, starting anywhere in the prompt, that the text opens with.
+    let echoed = (0..prompt_words.len())
+        .map(|start| {
+            prompt_words[start..]
+                .iter()
+                .zip(&normalized_text)
+                .take_while(|(prompt_word, text_word)| prompt_word == text_word)
+                .count()
+        })
+        .max()
+        .unwrap_or(0);
+
+    (echoed >= PROMPT_ECHO_MIN_WORDS).then(|| text_words[echoed..].join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_is_flattened_bounded_and_left_open() {
+        let options = SttOptions::new()
+            .with_prompt("Reunião de produto.\nParticipantes: Ana e Rui.\n\n")
+            .unwrap();
+        assert_eq!(
+            options.prompt(),
+            Some("Reunião de produto. Participantes: Ana e Rui")
+        );
+
+        let continuation = sanitize_prompt("and then the user said: \"").unwrap();
+        assert_eq!(continuation.as_deref(), Some("and then the user said"));
+
+        let long = "palavra ".repeat(MAX_PROMPT_WORDS * 2);
+        let bounded = sanitize_prompt(&long).unwrap().unwrap();
+        assert_eq!(bounded.split_whitespace().count(), MAX_PROMPT_WORDS);
+
+        assert_eq!(sanitize_prompt(" ...\n ").unwrap(), None);
+        assert_eq!(SttOptions::new().with_prompt("").unwrap().prompt(), None);
+    }
+
+    #[test]
+    fn test_prompt_repeating_a_hallucination_is_rejected() {
+        let err = sanitize_prompt("Olá pessoal! Obrigado por assistir.").unwrap_err();
+        assert_eq!(
+            err,
+            PromptError::HallucinationPattern("obrigado por assistir".to_string())
+        );
+        assert!(sanitize_prompt("Subtitles by the Amara.org community").is_err());
+        // Only whole words match.
+        assert!(sanitize_prompt("Agradeço a todos por assistirem à reunião").is_ok());
+    }
+
+    #[test]
+    fn test_vocabulary_is_appended_to_the_prompt() {
+        let terms = vec!["Zentra".to_string(), " ".to_string(), "Groq".to_string()];
+        let options = SttOptions::new()
+            .with_prompt("Ditado técnico.")
+            .unwrap()
+            .with_vocabulary(&terms)
+            .unwrap();
+        assert_eq!(options.prompt(), Some("Ditado técnico, Zentra, Groq"));
+
+        let only_terms = SttOptions::new().with_vocabulary(&terms).unwrap();
+        assert_eq!(only_terms.prompt(), Some("Zentra, Groq"));
+    }
+
+    #[test]
+    fn test_prompt_echo_is_stripped_from_the_start() {
+        let prompt = "Transcreva exatamente a fala em português brasileiro";
+        assert_eq!(
+            strip_prompt_echo(
+                "Transcreva exatamente a fala em português brasileiro. Bom dia a todos",
+                prompt
+            )
+            .as_deref(),
+            Some("Bom dia a todos")
+        );
+        // A copy of the prompt's tail counts too.
+        assert_eq!(
+            strip_prompt_echo("a fala em português brasileiro", prompt).as_deref(),
+            Some("")
+        );
+        // A few shared words are ordinary speech.
+        assert_eq!(strip_prompt_echo("a fala em inglês", prompt), None);
+        assert_eq!(
+            strip_prompt_echo("Bom dia, transcreva exatamente a fala em português", prompt),
+            None
+        );
+        assert_eq!(strip_prompt_echo("qualquer coisa", ""), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! Progressive upload: a WAV request body that is sent while the audio is still being
+//! recorded. The header is written up front for a length fixed when the upload starts,
+//! and samples are appended through a channel as they arrive. A body that ends short
+//! fails the request instead of sending a WAV whose header lies about its length.
+
+use super::STTError;
+use crate::audio::wav;
+use futures::stream::{self, StreamExt};
+use tokio::sync::mpsc;
+
+/// Chunks of samples buffered between the recorder and the request.
+const CHANNEL_CAPACITY: usize = 64;
+
+/// The request side: a body of exactly [`StreamingWav::len`] bytes.
+pub struct StreamingWav {
+    pub body: reqwest::Body,
+    pub len: u64,
+    pub duration_secs: f32,
+}
+
+/// The recording side: feeds samples into the body until the declared count is reached.
+pub struct SampleFeed {
+    tx: mpsc::Sender<Vec<i16>>,
+    remaining: usize,
+}
+
+impl SampleFeed {
+    /// Interleaved samples still owed to the body.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Append `samples`. More than [`SampleFeed::remaining`] is refused, and so is any
+    /// sample once the request is gone.
+    pub async fn send(&mut self, samples: &[i16]) -> Result<(), STTError> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        if samples.len() > self.remaining {
+            return Err(STTError::ProviderError(format!(
+                "Streaming upload overrun: {} samples for {} remaining",
+                samples.len(),
+                self.remaining
+            )));
+        }
+        self.tx
+            .send(samples.to_vec())
+            .await
+            .map_err(|_| STTError::NetworkError("Streaming upload closed".to_string()))?;
+        self.remaining -= samples.len();
+        Ok(())
+    }
+}
+
+/// Byte length of a 16-bit WAV of `samples` interleaved samples.
+pub fn wav_len(samples: usize) -> u64 {
+    (wav::HEADER_LEN + samples * 2) as u64
+}
+
+/// A WAV body of `samples` interleaved samples and the feed that fills it.
+pub fn streaming_wav(
+    samples: usize,
+    sample_rate: u32,
+    channels: u16,
+) -> (SampleFeed, StreamingWav) {
+    let (tx, rx) = mpsc::channel::<Vec<i16>>(CHANNEL_CAPACITY);
+    let header = wav::header(samples, sample_rate, channels).to_vec();
+    let data = stream::unfold((rx, samples), |(mut rx, remaining)| async move {
+        match rx.recv().await {
+            Some(chunk) => {
+                let bytes: Vec<u8> = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+                Some((Ok(bytes), (rx, remaining.saturating_sub(chunk.len()))))
+            }
+            None if remaining == 0 => None,
+            // The recorder stopped early; end the body with an error so the request fails.
+            None => Some((
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("Streaming upload ended {} samples short", remaining),
+                )),
+                (rx, 0),
+            )),
+        }
+    });
+    let body = stream::once(async move { Ok::<_, std::io::Error>(header) }).chain(data);
+
+    let frames = samples / channels.max(1) as usize;
+    let duration_secs = if sample_rate == 0 {
+        0.0
+    } else {
+        frames as f32 / sample_rate as f32
+    };
+    (
+        SampleFeed {
+            tx,
+            remaining: samples,
+        },
+        StreamingWav {
+            body: reqwest::Body::wrap_stream(body),
+            len: wav_len(samples),
+            duration_secs,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn post(
+        server: &MockServer,
+        upload: StreamingWav,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        reqwest::Client::new()
+            .post(server.uri())
+            .header("content-length", upload.len)
+            .body(upload.body)
+            .send()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_samples_fed_while_sending_arrive_as_one_wav() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let (mut feed, upload) = streaming_wav(6, 16_000, 2);
+        assert_eq!(upload.len, 44 + 12);
+        assert!((upload.duration_secs - 3.0 / 16_000.0).abs() < 1e-9);
+        let sending = tokio::spawn(async move {
+            feed.send(&[1, -1]).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            feed.send(&[2, -2, 3]).await.unwrap();
+            assert!(feed.send(&[4, -4]).await.is_err(), "one more than declared");
+            feed.send(&[4]).await.unwrap();
+            assert_eq!(feed.remaining(), 0);
+        });
+        let response = post(&server, upload).await.unwrap();
+        assert!(response.status().is_success());
+        sending.await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let audio = wav::decode_wav(&requests[0].body).unwrap();
+        assert_eq!(audio.samples, vec![1, -1, 2, -2, 3, 4]);
+        assert_eq!((audio.sample_rate, audio.channels), (16_000, 2));
+    }
+
+    #[tokio::test]
+    async fn test_a_feed_dropped_short_fails_the_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let (mut feed, upload) = streaming_wav(16_000, 16_000, 1);
+        let sending = tokio::spawn(async move {
+            feed.send(&[0; 8_000]).await.unwrap();
+        });
+        assert!(post(&server, upload).await.is_err());
+        sending.await.unwrap();
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+}
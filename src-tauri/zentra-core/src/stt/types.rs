@@ -0,0 +1,237 @@
+// src-tauri/zentra-core/src/stt/types.rs
+// STT Types and Error Definitions
+
+use crate::audio::BufferError;
+use crate::provider_error::ProviderApiError;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Timing of a single recognized word, relative to the start of the audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordTiming {
+    pub word: String,
+    pub start_secs: f32,
+    pub end_secs: f32,
+}
+
+/// Timing of a provider-defined phrase segment, relative to the start of the audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentTiming {
+    pub text: String,
+    pub start_secs: f32,
+    pub end_secs: f32,
+}
+
+/// How far a long local transcription has got
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionProgress {
+    pub provider: String,
+    pub percent: u8,
+    /// End of the last segment the engine printed, in seconds into the audio
+    pub position_secs: Option<f32>,
+}
+
+/// Receives progress from adapters that run long enough to report it. Called from the
+/// adapter's output reader, so implementations should hand the update off rather than block.
+pub trait TranscriptionProgressSink: Send + Sync {
+    fn progress(&self, progress: TranscriptionProgress);
+}
+
+/// Transcription result from any STT provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    /// Transcribed text
+    pub text: String,
+    /// Confidence score (0.0 - 1.0)
+    pub confidence: f32,
+    /// Detected language (e.g., "pt-BR", "en")
+    pub language: Option<String>,
+    /// Audio duration in seconds
+    pub duration_secs: f32,
+    /// Provider name (e.g., "Groq", "VOSK", "ElevenLabs")
+    pub provider: String,
+    /// Word timings; empty unless the provider was asked for timestamps
+    #[serde(default)]
+    pub words: Vec<WordTiming>,
+    /// Segment timings; empty unless the provider was asked for timestamps
+    #[serde(default)]
+    pub segments: Vec<SegmentTiming>,
+}
+
+impl Transcript {
+    /// Move every timing later by `offset_secs`, for audio that starts mid-recording.
+    pub fn shift_timings(&mut self, offset_secs: f32) {
+        for word in &mut self.words {
+            word.start_secs += offset_secs;
+            word.end_secs += offset_secs;
+        }
+        for segment in &mut self.segments {
+            segment.start_secs += offset_secs;
+            segment.end_secs += offset_secs;
+        }
+    }
+}
+
+/// Provider-ready payload, encoded once per transcription and reused across retries
+#[derive(Debug, Clone, Default)]
+pub struct PreparedAudio {
+    /// Encoded request body (e.g. WAV bytes); empty for adapters that don't upload
+    pub bytes: Vec<u8>,
+    /// Effective audio duration in seconds
+    pub duration_secs: f32,
+    /// Stable request fingerprint, sent to providers that deduplicate retries
+    pub idempotency_key: Option<String>,
+    /// ISO 639-1 language for this request over the adapter's own, e.g. a session's
+    /// pinned language
+    pub language: Option<String>,
+}
+
+/// STT Error types with retry classification
+#[derive(Debug, Clone, Error)]
+pub enum STTError {
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    #[error("Request timeout")]
+    TimeoutError,
+
+    #[error("Audio too long (max 59s for Groq)")]
+    AudioTooLong,
+
+    #[error("Invalid audio format")]
+    InvalidAudio,
+
+    #[error("Invalid audio buffer: {0}")]
+    MalformedAudio(#[from] BufferError),
+
+    #[error("Authentication failed")]
+    AuthenticationError,
+
+    #[error("Rate limit exceeded")]
+    RateLimitError {
+        /// The wait the provider asked for in `retry-after`
+        retry_after_secs: Option<u64>,
+    },
+
+    #[error("Provider error: {0}")]
+    ProviderError(String),
+
+    /// A non-2xx answer whose body said what went wrong
+    #[error("{0}")]
+    ProviderApi(ProviderApiError),
+
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
+}
+
+impl STTError {
+    /// Returns true if this error is retryable
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            STTError::NetworkError(_) | STTError::TimeoutError | STTError::RateLimitError { .. }
+        )
+    }
+
+    /// A 429, with the wait from its `retry-after` header when the provider sent one.
+    pub fn rate_limited(headers: &HeaderMap) -> Self {
+        let retry_after_secs = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, Utc::now()));
+        STTError::RateLimitError { retry_after_secs }
+    }
+
+    /// How long a rate-limited provider asked to be left alone.
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            STTError::RateLimitError { retry_after_secs } => *retry_after_secs,
+            _ => None,
+        }
+    }
+}
+
+/// `retry-after` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return (secs.is_finite() && secs >= 0.0).then(|| secs.ceil() as u64);
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).num_seconds().max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_serializes_camel_case() {
+        let transcript = Transcript {
+            text: "olá mundo".to_string(),
+            confidence: 0.5,
+            language: Some("pt".to_string()),
+            duration_secs: 1.5,
+            provider: "Groq".to_string(),
+            words: vec![WordTiming {
+                word: "olá".to_string(),
+                start_secs: 0.0,
+                end_secs: 0.5,
+            }],
+            segments: vec![SegmentTiming {
+                text: "olá mundo".to_string(),
+                start_secs: 0.0,
+                end_secs: 1.5,
+            }],
+        };
+        let value = serde_json::to_value(&transcript).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "text": "olá mundo",
+                "confidence": 0.5,
+                "language": "pt",
+                "durationSecs": 1.5,
+                "provider": "Groq",
+                "words": [{ "word": "olá", "startSecs": 0.0, "endSecs": 0.5 }],
+                "segments": [{ "text": "olá mundo", "startSecs": 0.0, "endSecs": 1.5 }]
+            })
+        );
+
+        let parsed: Transcript = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.words, transcript.words);
+    }
+
+    #[test]
+    fn test_retry_after_takes_seconds_or_an_http_date() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:48Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("12", now), Some(12));
+        assert_eq!(parse_retry_after(" 2.5 ", now), Some(3));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(12)
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("-3", now), None);
+        assert_eq!(parse_retry_after("soon", now), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(STTError::rate_limited(&headers).retry_after_secs(), Some(7));
+        assert_eq!(
+            STTError::rate_limited(&HeaderMap::new()).retry_after_secs(),
+            None
+        );
+    }
+}
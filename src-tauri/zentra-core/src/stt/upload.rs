@@ -0,0 +1,91 @@
+//! Request body shared by the adapters that upload audio (Groq, ElevenLabs): the recording
+//! downmixed to 16 kHz mono and compressed to FLAC, or plain WAV for servers that reject
+//! FLAC.
+
+use super::STTError;
+use crate::audio::{flac::encode_flac, wav::encode_wav, AudioBuffer};
+use serde::{Deserialize, Serialize};
+
+pub const UPLOAD_SAMPLE_RATE: u32 = 16_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadFormat {
+    /// Lossless, about half the size of WAV for speech
+    #[default]
+    Flac,
+    Wav,
+}
+
+impl UploadFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Flac => "audio.flac",
+            Self::Wav => "audio.wav",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Flac => "audio/flac",
+            Self::Wav => "audio/wav",
+        }
+    }
+}
+
+/// Downmix, resample and encode `audio` for upload.
+pub fn encode_upload(audio: &AudioBuffer, format: UploadFormat) -> Result<Vec<u8>, STTError> {
+    audio.validate()?;
+    if audio.samples.is_empty() {
+        return Err(STTError::InvalidAudio);
+    }
+
+    // Whisper works at 16 kHz mono; sending that avoids device-specific channel layouts
+    // and keeps the upload small.
+    let mut mono = AudioBuffer::new(UPLOAD_SAMPLE_RATE, 1);
+    mono.append(&audio.to_mono(UPLOAD_SAMPLE_RATE));
+    let wav = encode_wav(&mono);
+    if format == UploadFormat::Wav {
+        return Ok(wav);
+    }
+
+    let flac = encode_flac(&mono).map_err(|e| {
+        tracing::warn!("FLAC encoding failed: {}", e);
+        STTError::InvalidAudio
+    })?;
+    tracing::info!(
+        "Upload encoded as FLAC: {} bytes vs {} bytes WAV ({:.0}%)",
+        flac.len(),
+        wav.len(),
+        flac.len() as f32 * 100.0 / wav.len() as f32
+    );
+    Ok(flac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_format_names_its_file() {
+        assert_eq!(
+            serde_json::from_str::<UploadFormat>(r#""wav""#).unwrap(),
+            UploadFormat::Wav
+        );
+        assert_eq!(UploadFormat::Flac.file_name(), "audio.flac");
+        assert_eq!(UploadFormat::Flac.mime_type(), "audio/flac");
+        assert_eq!(UploadFormat::Wav.file_name(), "audio.wav");
+        assert_eq!(UploadFormat::Wav.mime_type(), "audio/wav");
+
+        assert!(matches!(
+            encode_upload(&AudioBuffer::new(16_000, 1), UploadFormat::Flac),
+            Err(STTError::InvalidAudio)
+        ));
+        let mut no_channels = AudioBuffer::new(16_000, 0);
+        no_channels.append(&[1, 2, 3]);
+        assert!(matches!(
+            encode_upload(&no_channels, UploadFormat::Wav),
+            Err(STTError::MalformedAudio(_))
+        ));
+    }
+}
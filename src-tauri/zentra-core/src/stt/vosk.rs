@@ -1,4 +1,4 @@
-﻿// src-tauri/src/stt/vosk.rs
+﻿// src-tauri/zentra-core/src/stt/vosk.rs
 // VOSK Local STT Adapter (Fallback 1)
 
 use super::{STTAdapter, STTError, Transcript};
@@ -57,6 +57,7 @@ impl VoskAdapter {
     ) -> Result<Transcript, STTError> {
         // VOSK expects PCM 16kHz mono i16
         // Model must be created outside, recognizer created per request
+        audio.validate()?;
         if audio.samples.is_empty() {
             return Err(STTError::InvalidAudio);
         }
@@ -94,6 +95,7 @@ impl VoskAdapter {
             language: Some(language.to_string()),
             duration_secs: audio.duration_secs,
             provider: "VOSK".to_string(),
+            ..Default::default()
         })
     }
 }
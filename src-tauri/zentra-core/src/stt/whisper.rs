@@ -0,0 +1,618 @@
+﻿// src-tauri/zentra-core/src/stt/whisper.rs
+// Whisper.cpp Local STT Adapter (Fallback 3)
+
+use super::{STTAdapter, STTError, Transcript, TranscriptionProgress, TranscriptionProgressSink};
+use crate::audio::AudioBuffer;
+use async_trait::async_trait;
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+const TARGET_SAMPLE_RATE: u32 = 16000;
+const PROVIDER_NAME: &str = "Whisper.cpp";
+/// Longest a run may take before it is killed; whisper.cpp on a slow CPU with a large
+/// model can take minutes for a long recording.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+/// stderr lines kept for the error message of a failed run
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Run options besides the binary and model, from config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhisperOptions {
+    /// `-t`; whisper.cpp picks its own default when unset
+    pub threads: Option<u32>,
+    /// Translate into English instead of transcribing
+    pub translate: bool,
+    pub timeout: Duration,
+}
+
+impl Default for WhisperOptions {
+    fn default() -> Self {
+        Self {
+            threads: None,
+            translate: false,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+pub struct WhisperAdapter {
+    bin_path: PathBuf,
+    model_path: PathBuf,
+    language: String,
+    options: WhisperOptions,
+    /// Where each run's input and output files go
+    temp_dir: PathBuf,
+    progress: Option<Arc<dyn TranscriptionProgressSink>>,
+}
+
+impl WhisperAdapter {
+    /// The binary and model at these paths. Unset, they are looked up next to the app; the
+    /// model is looked up in `models_dir` first. `None` when either is missing.
+    /// `language` is whisper.cpp's `-l`, e.g. `auto`.
+    pub fn new(
+        bin_path: Option<PathBuf>,
+        model_path: Option<PathBuf>,
+        models_dir: Option<&Path>,
+        language: &str,
+    ) -> Option<Self> {
+        let bin_path = match bin_path.or_else(default_whisper_bin) {
+            Some(p) if p.exists() => p,
+            Some(p) => {
+                tracing::warn!("Whisper bin not found at {}", p.display());
+                return None;
+            }
+            None => {
+                tracing::warn!("Whisper bin not configured.");
+                return None;
+            }
+        };
+
+        let model_path = match model_path.or_else(|| default_whisper_model(models_dir)) {
+            Some(p) if p.exists() => p,
+            Some(p) => {
+                tracing::warn!("Whisper model not found at {}", p.display());
+                return None;
+            }
+            None => {
+                tracing::warn!("Whisper model not configured.");
+                return None;
+            }
+        };
+
+        tracing::info!(
+            "Whisper adapter initialized: bin={}, model={}",
+            bin_path.display(),
+            model_path.display()
+        );
+
+        Some(Self {
+            bin_path,
+            model_path,
+            language: language.to_string(),
+            options: WhisperOptions::default(),
+            temp_dir: env::temp_dir(),
+            progress: None,
+        })
+    }
+
+    /// Write the files of each run to `dir` instead of the system temp directory; it is
+    /// created when missing.
+    pub fn with_temp_dir(mut self, dir: PathBuf) -> Self {
+        self.temp_dir = dir;
+        self
+    }
+
+    pub fn with_options(mut self, options: WhisperOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &WhisperOptions {
+        &self.options
+    }
+
+    fn to_wav_16k_mono(audio: &AudioBuffer) -> Result<Vec<u8>, STTError> {
+        audio.validate()?;
+        if audio.samples.is_empty() {
+            return Err(STTError::InvalidAudio);
+        }
+
+        let channels = audio.channels.max(1) as usize;
+        let frames = audio.samples.len() / channels;
+        if frames == 0 {
+            return Err(STTError::InvalidAudio);
+        }
+
+        let mut mono = Vec::with_capacity(frames);
+        for i in 0..frames {
+            let mut sum: i32 = 0;
+            for c in 0..channels {
+                sum += audio.samples[i * channels + c] as i32;
+            }
+            let avg = sum as f32 / channels as f32;
+            mono.push(avg / i16::MAX as f32);
+        }
+
+        let src_rate = audio.sample_rate.max(1) as f32;
+        let dst_rate = TARGET_SAMPLE_RATE as f32;
+
+        let out_len = ((mono.len() as f32) * dst_rate / src_rate).ceil() as usize;
+        let mut resampled = Vec::with_capacity(out_len.max(1));
+
+        if out_len == 0 {
+            return Err(STTError::InvalidAudio);
+        }
+
+        let ratio = src_rate / dst_rate;
+        for i in 0..out_len {
+            let src_pos = i as f32 * ratio;
+            let idx = src_pos.floor() as usize;
+            let frac = src_pos - idx as f32;
+            let s0 = *mono.get(idx).unwrap_or(&0.0);
+            let s1 = *mono.get(idx + 1).unwrap_or(&s0);
+            let sample = s0 + (s1 - s0) * frac;
+            let clamped = sample.clamp(-1.0, 1.0);
+            resampled.push((clamped * i16::MAX as f32) as i16);
+        }
+
+        encode_wav_i16(&resampled, TARGET_SAMPLE_RATE, 1)
+    }
+
+    fn command(&self, files: &TempFiles) -> Command {
+        let mut command = Command::new(&self.bin_path);
+        command
+            .arg("--model")
+            .arg(&self.model_path)
+            .arg("--file")
+            .arg(&files.input)
+            .arg("--output-txt")
+            .arg("--output-file")
+            .arg(&files.output_base)
+            .arg("--language")
+            .arg(&self.language)
+            .arg("--print-progress");
+        if let Some(threads) = self.options.threads {
+            command.arg("--threads").arg(threads.to_string());
+        }
+        if self.options.translate {
+            command.arg("--translate");
+        }
+        // Dropping the future (timeout, cancelled dictation) must not leave whisper running.
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        command
+    }
+
+    /// Run whisper.cpp on the files without blocking the runtime, reporting the progress
+    /// and segment timestamps it prints as they come.
+    async fn run_whisper(&self, files: &TempFiles, duration_secs: f32) -> Result<String, STTError> {
+        let mut child = self
+            .command(files)
+            .spawn()
+            .map_err(|e| STTError::ProviderError(format!("Whisper failed to start: {}", e)))?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let run = async {
+            let (status, stdout, stderr) = tokio::join!(
+                child.wait(),
+                self.read_output(stdout, duration_secs),
+                self.read_output(stderr, duration_secs),
+            );
+            (status, stdout, stderr)
+        };
+        let (status, stdout, stderr) = tokio::time::timeout(self.options.timeout, run)
+            .await
+            .map_err(|_| {
+                tracing::warn!(
+                    "Whisper ran longer than {}s; killed",
+                    self.options.timeout.as_secs()
+                );
+                STTError::TimeoutError
+            })?;
+        let status = status.map_err(|e| STTError::ProviderError(e.to_string()))?;
+
+        if !status.success() {
+            let tail = stderr
+                .lines()
+                .rev()
+                .take(STDERR_TAIL_LINES)
+                .collect::<Vec<_>>();
+            let tail = tail.into_iter().rev().collect::<Vec<_>>().join("\n");
+            return Err(STTError::ProviderError(format!(
+                "Whisper failed: {}",
+                tail.trim()
+            )));
+        }
+
+        if let Ok(text) = tokio::fs::read_to_string(files.output_base.with_extension("txt")).await {
+            return Ok(text);
+        }
+
+        let text = stdout
+            .lines()
+            .map(|line| strip_timestamps(line).trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !text.is_empty() {
+            return Ok(text);
+        }
+
+        Err(STTError::ProviderError(
+            "Whisper produced no output".to_string(),
+        ))
+    }
+
+    /// Everything `stream` printed, line by line; progress lines are reported on the way.
+    async fn read_output(
+        &self,
+        stream: Option<impl AsyncRead + Unpin>,
+        duration_secs: f32,
+    ) -> String {
+        let Some(stream) = stream else {
+            return String::new();
+        };
+        let mut lines = BufReader::new(stream).lines();
+        let mut output = String::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let (Some(sink), Some(progress)) =
+                (self.progress.as_ref(), parse_progress(&line, duration_secs))
+            {
+                sink.progress(progress);
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// The input WAV and output base of one run, unique per call and deleted when dropped,
+/// including when the transcription is cancelled midway.
+struct TempFiles {
+    input: PathBuf,
+    output_base: PathBuf,
+}
+
+impl TempFiles {
+    fn new(tmp_dir: &Path) -> Self {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            input: tmp_dir.join(format!("whisper_input_{}.wav", id)),
+            output_base: tmp_dir.join(format!("whisper_out_{}", id)),
+        }
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.input);
+        for extension in ["txt", "vtt", "srt"] {
+            let _ = fs::remove_file(self.output_base.with_extension(extension));
+        }
+    }
+}
+
+#[async_trait]
+impl STTAdapter for WhisperAdapter {
+    async fn transcribe(&self, audio: &AudioBuffer) -> Result<Transcript, STTError> {
+        let wav_bytes = Self::to_wav_16k_mono(audio)?;
+
+        tokio::fs::create_dir_all(&self.temp_dir)
+            .await
+            .map_err(|e| STTError::ProviderError(e.to_string()))?;
+        let files = TempFiles::new(&self.temp_dir);
+        tokio::fs::write(&files.input, wav_bytes)
+            .await
+            .map_err(|e| STTError::ProviderError(e.to_string()))?;
+
+        let text = self.run_whisper(&files, audio.duration_secs).await?;
+
+        Ok(Transcript {
+            text: text.trim().to_string(),
+            confidence: 0.85,
+            language: Some(self.language.clone()),
+            duration_secs: audio.duration_secs,
+            provider: PROVIDER_NAME.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn name(&self) -> &str {
+        PROVIDER_NAME
+    }
+
+    fn set_progress_sink(&mut self, sink: Arc<dyn TranscriptionProgressSink>) {
+        self.progress = Some(sink);
+    }
+}
+
+/// whisper.cpp prints `progress = 40%` with `--print-progress`, and a
+/// `[00:00:01.000 --> 00:00:04.200]` line per segment; the latter is turned into a percentage
+/// of `duration_secs`.
+fn parse_progress(line: &str, duration_secs: f32) -> Option<TranscriptionProgress> {
+    if let Some((_, rest)) = line.split_once("progress =") {
+        let percent = rest
+            .trim()
+            .trim_end_matches('%')
+            .trim()
+            .parse::<u8>()
+            .ok()?;
+        return Some(TranscriptionProgress {
+            provider: PROVIDER_NAME.to_string(),
+            percent: percent.min(100),
+            position_secs: None,
+        });
+    }
+    let (_, end) = segment_span(line)?;
+    let percent = if duration_secs > 0.0 {
+        (end / duration_secs * 100.0).clamp(0.0, 100.0) as u8
+    } else {
+        0
+    };
+    Some(TranscriptionProgress {
+        provider: PROVIDER_NAME.to_string(),
+        percent,
+        position_secs: Some(end),
+    })
+}
+
+/// Start and end of a `[hh:mm:ss.mmm --> hh:mm:ss.mmm]` segment line.
+fn segment_span(line: &str) -> Option<(f32, f32)> {
+    let inner = line.trim_start().strip_prefix('[')?;
+    let (span, _) = inner.split_once(']')?;
+    let (start, end) = span.split_once("-->")?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end.trim())?))
+}
+
+fn parse_timestamp(value: &str) -> Option<f32> {
+    let mut secs = 0.0f32;
+    for part in value.split(':') {
+        secs = secs * 60.0 + part.parse::<f32>().ok()?;
+    }
+    Some(secs)
+}
+
+/// A segment line's text without its timestamps.
+fn strip_timestamps(line: &str) -> &str {
+    match segment_span(line) {
+        Some(_) => line.split_once(']').map_or(line, |(_, text)| text),
+        None => line,
+    }
+}
+
+fn default_whisper_bin() -> Option<PathBuf> {
+    let candidates = [
+        "bin/whisper-cli.exe",
+        "bin/whisper-cli",
+        "bin/main.exe",
+        "bin/main",
+    ];
+
+    for c in candidates {
+        let path = PathBuf::from(c);
+        if path.exists() {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+/// A known model in `models_dir`, then in `models/` next to the app.
+fn default_whisper_model(models_dir: Option<&Path>) -> Option<PathBuf> {
+    let candidates = [
+        "ggml-base.bin",
+        "ggml-base.en.bin",
+        "ggml-small.bin",
+        "ggml-small.en.bin",
+    ];
+
+    for dir in models_dir.into_iter().chain([Path::new("models")]) {
+        for c in candidates {
+            let path = dir.join(c);
+            if path.exists() {
+                return Some(path);
+            }
+        }
+
+        // Fallback: any ggml-*.bin in the directory
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension() == Some(OsStr::new("bin")) {
+                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                        if name.starts_with("ggml-") {
+                            return Some(path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn encode_wav_i16(samples: &[i16], sample_rate: u32, channels: u16) -> Result<Vec<u8>, STTError> {
+    let mut wav = Vec::new();
+
+    // RIFF header
+    wav.extend_from_slice(b"RIFF");
+    let file_size = (36 + samples.len() * 2) as u32;
+    wav.extend_from_slice(&file_size.to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    // fmt chunk
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes()); // chunk size
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM format
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    let byte_rate = sample_rate * channels as u32 * 2;
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&(channels * 2).to_le_bytes()); // block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+    // data chunk
+    wav.extend_from_slice(b"data");
+    let data_size = (samples.len() * 2) as u32;
+    wav.extend_from_slice(&data_size.to_le_bytes());
+
+    for &sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    Ok(wav)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Collected(Mutex<Vec<TranscriptionProgress>>);
+
+    impl TranscriptionProgressSink for Collected {
+        fn progress(&self, progress: TranscriptionProgress) {
+            self.0.lock().unwrap().push(progress);
+        }
+    }
+
+    #[test]
+    fn test_progress_comes_from_percentages_and_segment_timestamps() {
+        let percent = parse_progress("whisper_print_progress_callback: progress =  40%", 10.0);
+        assert_eq!(
+            percent.map(|p| (p.percent, p.position_secs)),
+            Some((40, None))
+        );
+
+        let segment = parse_progress("[00:00:02.000 --> 00:00:05.000]   Olá, tudo bem?", 10.0)
+            .expect("a segment line");
+        assert_eq!((segment.percent, segment.position_secs), (50, Some(5.0)));
+        assert_eq!(
+            parse_progress("[00:01:00.000 --> 00:01:30.500]  text", 60.0).map(|p| p.percent),
+            Some(100)
+        );
+
+        assert_eq!(
+            parse_progress("whisper_init_from_file: loading model", 10.0),
+            None
+        );
+        assert_eq!(
+            strip_timestamps("[00:00:02.000 --> 00:00:05.000]   Olá").trim(),
+            "Olá"
+        );
+        assert_eq!(strip_timestamps("no timestamps"), "no timestamps");
+    }
+
+    #[test]
+    fn test_temp_files_are_unique_and_removed_when_dropped() {
+        let first = TempFiles::new(&env::temp_dir());
+        let second = TempFiles::new(&env::temp_dir());
+        assert_ne!(first.input, second.input);
+
+        fs::write(&first.input, b"wav").unwrap();
+        fs::write(first.output_base.with_extension("txt"), b"text").unwrap();
+        let (input, txt) = (first.input.clone(), first.output_base.with_extension("txt"));
+        drop(first);
+        assert!(!input.exists() && !txt.exists());
+    }
+
+    #[cfg(unix)]
+    fn fake_whisper(script: &str) -> (WhisperAdapter, PathBuf) {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = env::temp_dir().join(format!("zentra-whisper-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let bin = dir.join("whisper-cli");
+        fs::write(&bin, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        let adapter = WhisperAdapter {
+            bin_path: bin,
+            model_path: dir.join("ggml-base.bin"),
+            language: "pt".to_string(),
+            options: WhisperOptions::default(),
+            temp_dir: dir.join("tmp"),
+            progress: None,
+        };
+        (adapter, dir)
+    }
+
+    fn one_second() -> AudioBuffer {
+        let mut audio = AudioBuffer::new(16_000, 1);
+        audio.append(&vec![1_000i16; 16_000]);
+        audio
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_runs_without_blocking_and_reports_progress() {
+        let (mut adapter, dir) = fake_whisper(
+            "echo 'progress = 50%' >&2\n\
+             sleep 0.2\n\
+             echo '[00:00:00.000 --> 00:00:01.000]   Bom dia'",
+        );
+        adapter.options.threads = Some(2);
+        let collected = Arc::new(Collected::default());
+        adapter.set_progress_sink(collected.clone());
+
+        // The runtime keeps serving other tasks while whisper runs.
+        let ticker = tokio::spawn(async {
+            let mut ticks = 0;
+            while ticks < 5 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                ticks += 1;
+            }
+            ticks
+        });
+        let transcript = adapter.transcribe(&one_second()).await.unwrap();
+        assert_eq!(transcript.text, "Bom dia");
+        assert_eq!(ticker.await.unwrap(), 5);
+        // The run's files went to the configured temp dir and were removed afterwards.
+        assert_eq!(fs::read_dir(dir.join("tmp")).unwrap().count(), 0);
+
+        let percents: Vec<u8> = collected
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|p| p.percent)
+            .collect();
+        assert_eq!(percents, [50, 100]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_a_run_past_the_timeout_is_killed() {
+        let (mut adapter, dir) = fake_whisper("sleep 5");
+        adapter.options.timeout = Duration::from_millis(200);
+        let started = std::time::Instant::now();
+        assert!(matches!(
+            adapter.transcribe(&one_second()).await,
+            Err(STTError::TimeoutError)
+        ));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let (adapter, _) = fake_whisper("echo 'model not found' >&2; exit 3");
+        match adapter.transcribe(&one_second()).await {
+            Err(STTError::ProviderError(message)) => assert!(message.contains("model not found")),
+            other => panic!("expected a provider error, got {:?}", other),
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
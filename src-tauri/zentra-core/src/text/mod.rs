@@ -0,0 +1,4 @@
+pub mod normalize;
+pub mod postprocess;
+pub mod repetition;
+pub mod subtitles;
@@ -0,0 +1,211 @@
+//! Shared text comparison primitives for PT/EN: stitcher overlap detection, history search,
+//! duplicate detection and dictation-command matching must all agree on what "equal" means.
+
+/// Replace accented Latin letters with their ASCII base letters.
+///
+/// Folding is 1:1 in chars except for ligatures and sharp s, which expand to two letters
+/// (`ß` -> `ss`, `æ` -> `ae`, `œ` -> `oe`, `ĳ` -> `ij`). Characters outside the table pass through.
+pub fn fold_diacritics(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match fold_char(ch) {
+            Folded::One(c) => out.push(c),
+            Folded::Two(a, b) => {
+                out.push(a);
+                out.push(b);
+            }
+        }
+    }
+    out
+}
+
+/// Lowercase, fold diacritics, turn punctuation into spaces and collapse whitespace.
+pub fn normalize_for_match(text: &str) -> String {
+    let folded = fold_diacritics(&text.to_lowercase());
+    let mut out = String::with_capacity(folded.len());
+    let mut pending_space = false;
+
+    for ch in folded.chars() {
+        if ch.is_alphanumeric() {
+            if pending_space && !out.is_empty() {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.push(ch);
+        } else {
+            pending_space = true;
+        }
+    }
+
+    out
+}
+
+/// Whether two words/phrases are the same once case, accents and punctuation are ignored.
+/// Tokens with nothing left after that (`-`, `...`) never match, not even each other.
+pub fn token_equal(a: &str, b: &str) -> bool {
+    let a = normalize_for_match(a);
+    !a.is_empty() && a == normalize_for_match(b)
+}
+
+/// Levenshtein-based similarity in `[0.0, 1.0]` over the normalized forms.
+pub fn similarity_ratio(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = normalize_for_match(a).chars().collect();
+    let b: Vec<char> = normalize_for_match(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0usize; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
+enum Folded {
+    One(char),
+    Two(char, char),
+}
+
+fn fold_char(ch: char) -> Folded {
+    let base = match ch {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => 'A',
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' | 'ª' => 'a',
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => 'C',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'Ð' | 'Ď' | 'Đ' => 'D',
+        'ð' | 'ď' | 'đ' => 'd',
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => 'E',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => 'G',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'Ĥ' | 'Ħ' => 'H',
+        'ĥ' | 'ħ' => 'h',
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => 'I',
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'Ĵ' => 'J',
+        'ĵ' => 'j',
+        'Ķ' => 'K',
+        'ķ' => 'k',
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => 'L',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => 'N',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => 'O',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' | 'º' => 'o',
+        'Ŕ' | 'Ŗ' | 'Ř' => 'R',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => 'S',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'Ţ' | 'Ť' | 'Ŧ' => 'T',
+        'ţ' | 'ť' | 'ŧ' => 't',
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => 'U',
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'Ŵ' => 'W',
+        'ŵ' => 'w',
+        'Ý' | 'Ŷ' | 'Ÿ' => 'Y',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'Ź' | 'Ż' | 'Ž' => 'Z',
+        'ź' | 'ż' | 'ž' => 'z',
+        'ß' => return Folded::Two('s', 's'),
+        'Æ' => return Folded::Two('A', 'E'),
+        'æ' => return Folded::Two('a', 'e'),
+        'Œ' => return Folded::Two('O', 'E'),
+        'œ' => return Folded::Two('o', 'e'),
+        'Ĳ' => return Folded::Two('I', 'J'),
+        'ĳ' => return Folded::Two('i', 'j'),
+        other => other,
+    };
+    Folded::One(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: &[&str] = &[
+        "",
+        "Olá, mundo!",
+        "AÇÃO  rápida... não é?",
+        "coração; informação: pão",
+        "Straße und Œuvre",
+        "   espaços\tmisturados\n",
+        "emoji 🙂 e números 123",
+        "d'água",
+    ];
+
+    #[test]
+    fn test_fold_diacritics_portuguese() {
+        assert_eq!(
+            fold_diacritics("ação coração pão à é ü"),
+            "acao coracao pao a e u"
+        );
+        assert_eq!(fold_diacritics("ÁÉÍÓÚ ÂÊÔ ÃÕ Ç"), "AEIOU AEO AO C");
+    }
+
+    #[test]
+    fn test_fold_keeps_char_count_except_documented_expansions() {
+        for sample in SAMPLES {
+            let expansions = sample
+                .chars()
+                .filter(|c| matches!(c, 'ß' | 'Æ' | 'æ' | 'Œ' | 'œ' | 'Ĳ' | 'ĳ'))
+                .count();
+            assert_eq!(
+                fold_diacritics(sample).chars().count(),
+                sample.chars().count() + expansions,
+                "{:?}",
+                sample
+            );
+        }
+        assert_eq!(fold_diacritics("Straße"), "Strasse");
+    }
+
+    #[test]
+    fn test_normalization_is_idempotent() {
+        for sample in SAMPLES {
+            let folded = fold_diacritics(sample);
+            assert_eq!(fold_diacritics(&folded), folded, "{:?}", sample);
+
+            let normalized = normalize_for_match(sample);
+            assert_eq!(normalize_for_match(&normalized), normalized, "{:?}", sample);
+        }
+    }
+
+    #[test]
+    fn test_normalize_for_match_strips_case_accents_and_punctuation() {
+        assert_eq!(
+            normalize_for_match("  AÇÃO  rápida... não é? "),
+            "acao rapida nao e"
+        );
+        assert_eq!(normalize_for_match("d'água"), "d agua");
+        assert_eq!(normalize_for_match("!!!"), "");
+    }
+
+    #[test]
+    fn test_token_equal() {
+        assert!(token_equal("Não", "nao"));
+        assert!(token_equal("mundo.", "Mundo"));
+        assert!(!token_equal("pão", "pau"));
+        assert!(!token_equal("-", "..."));
+        assert!(!token_equal("", ""));
+        assert!(!token_equal("—", "mundo"));
+    }
+
+    #[test]
+    fn test_similarity_ratio() {
+        assert_eq!(similarity_ratio("", ""), 1.0);
+        assert_eq!(similarity_ratio("Ação", "acao"), 1.0);
+        assert_eq!(similarity_ratio("abc", ""), 0.0);
+        let ratio = similarity_ratio("nova linha", "nova linhas");
+        assert!(ratio > 0.9 && ratio < 1.0, "{}", ratio);
+        assert!(similarity_ratio("ponto final", "abre aspas") < 0.5);
+    }
+}
@@ -0,0 +1,340 @@
+//! Post-processing applied to a finished transcript before it is pasted. The chain is an
+//! ordered list of named stages so the settings preview can show each intermediate result
+//! and the live pipeline runs exactly the same code.
+
+use super::normalize::{normalize_for_match, token_equal};
+use crate::prompt_engine::{clarity, dictation};
+use serde::{Deserialize, Serialize};
+
+const FILLERS_PT: &[&str] = &["hã", "hum", "humm", "ahn", "éh", "uhm"];
+const FILLERS_EN: &[&str] = &["um", "uh", "uhm", "erm", "hmm"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacementRule {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PostProcessingOptions {
+    pub language: String,
+    pub clarity: bool,
+    pub remove_fillers: bool,
+    pub replacements: Vec<ReplacementRule>,
+    /// Turn spoken "new line", "vírgula"... into formatting
+    pub dictation_commands: bool,
+}
+
+impl PostProcessingOptions {
+    /// Use the language the provider detected when the settings leave it on `auto`.
+    pub fn with_detected_language(mut self, detected: Option<&str>) -> Self {
+        if let Some(language) = detected.and_then(clarity::Language::from_code) {
+            if clarity::Language::from_code(&self.language).is_none() {
+                self.language = language.code().to_string();
+            }
+        }
+        self
+    }
+}
+
+impl Default for PostProcessingOptions {
+    fn default() -> Self {
+        Self {
+            language: "pt".to_string(),
+            clarity: false,
+            remove_fillers: false,
+            replacements: Vec::new(),
+            dictation_commands: false,
+        }
+    }
+}
+
+/// Per-field overrides used by the preview panel to try rules before saving them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OverrideOptions {
+    pub language: Option<String>,
+    pub clarity: Option<bool>,
+    pub remove_fillers: Option<bool>,
+    pub replacements: Option<Vec<ReplacementRule>>,
+    pub dictation_commands: Option<bool>,
+}
+
+impl OverrideOptions {
+    pub fn apply(self, mut options: PostProcessingOptions) -> PostProcessingOptions {
+        if let Some(language) = self.language {
+            options.language = language;
+        }
+        if let Some(clarity) = self.clarity {
+            options.clarity = clarity;
+        }
+        if let Some(remove_fillers) = self.remove_fillers {
+            options.remove_fillers = remove_fillers;
+        }
+        if let Some(replacements) = self.replacements {
+            options.replacements = replacements;
+        }
+        if let Some(dictation_commands) = self.dictation_commands {
+            options.dictation_commands = dictation_commands;
+        }
+        options
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Stage {
+    Clarity,
+    Replacements,
+    FillerRemoval,
+    /// Last, since the other stages would collapse the line breaks it inserts
+    DictationCommands,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageOutput {
+    pub stage: Stage,
+    pub enabled: bool,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostProcessingPreview {
+    pub input: String,
+    pub stages: Vec<StageOutput>,
+    pub output: String,
+}
+
+pub struct PostProcessor {
+    options: PostProcessingOptions,
+}
+
+impl PostProcessor {
+    /// Production order. Disabled stages still appear in the preview, unchanged.
+    pub const STAGES: &'static [Stage] = &[
+        Stage::Clarity,
+        Stage::Replacements,
+        Stage::FillerRemoval,
+        Stage::DictationCommands,
+    ];
+
+    pub fn new(options: PostProcessingOptions) -> Self {
+        Self { options }
+    }
+
+    /// Run every stage in order. The live pipeline uses `output`; the preview shows all of it.
+    pub fn run(&self, text: &str) -> PostProcessingPreview {
+        let mut current = text.to_string();
+        let mut stages = Vec::with_capacity(Self::STAGES.len());
+
+        for &stage in Self::STAGES {
+            let enabled = self.is_enabled(stage);
+            if enabled {
+                current = self.apply(stage, &current);
+            }
+            stages.push(StageOutput {
+                stage,
+                enabled,
+                text: current.clone(),
+            });
+        }
+
+        PostProcessingPreview {
+            input: text.to_string(),
+            stages,
+            output: current,
+        }
+    }
+
+    fn is_enabled(&self, stage: Stage) -> bool {
+        match stage {
+            Stage::Clarity => self.options.clarity,
+            Stage::Replacements => !self.options.replacements.is_empty(),
+            Stage::FillerRemoval => self.options.remove_fillers,
+            Stage::DictationCommands => self.options.dictation_commands,
+        }
+    }
+
+    fn apply(&self, stage: Stage, text: &str) -> String {
+        match stage {
+            Stage::Clarity => clarity::transform(text, &self.options.language),
+            Stage::Replacements => apply_replacements(text, &self.options.replacements),
+            Stage::FillerRemoval => remove_fillers(text, &self.options.language),
+            Stage::DictationCommands => {
+                dictation::apply_dictation_commands(text, &self.options.language)
+            }
+        }
+    }
+}
+
+/// Whole-word, case/accent-insensitive replacement. Punctuation attached to the last
+/// matched word is kept.
+fn apply_replacements(text: &str, rules: &[ReplacementRule]) -> String {
+    let mut tokens: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+
+    for rule in rules {
+        let pattern: Vec<&str> = rule.from.split_whitespace().collect();
+        if pattern.is_empty() || normalize_for_match(&rule.from).is_empty() {
+            continue;
+        }
+
+        let mut out = Vec::with_capacity(tokens.len());
+        let mut idx = 0;
+        while idx < tokens.len() {
+            let end = idx + pattern.len();
+            let matches = end <= tokens.len()
+                && tokens[idx..end]
+                    .iter()
+                    .zip(&pattern)
+                    .all(|(token, word)| token_equal(token, word));
+            if matches {
+                let trailing = trailing_punctuation(&tokens[end - 1]);
+                let replacement = format!("{}{}", rule.to.trim(), trailing);
+                if !replacement.is_empty() {
+                    out.push(replacement);
+                }
+                idx = end;
+            } else {
+                out.push(tokens[idx].clone());
+                idx += 1;
+            }
+        }
+        tokens = out;
+    }
+
+    tokens.join(" ")
+}
+
+/// Drop hesitation sounds. A sentence-ending mark on a removed filler moves to the previous
+/// word, and a filler that opened a sentence passes its capital letter on.
+fn remove_fillers(text: &str, language: &str) -> String {
+    let fillers: Vec<&str> = match language {
+        "en" => FILLERS_EN.to_vec(),
+        "pt" => FILLERS_PT.to_vec(),
+        _ => FILLERS_PT.iter().chain(FILLERS_EN).copied().collect(),
+    };
+
+    let mut out: Vec<String> = Vec::new();
+    let mut capitalize_next = false;
+
+    for token in text.split_whitespace() {
+        if fillers.iter().any(|filler| token_equal(token, filler)) {
+            let trailing = trailing_punctuation(token);
+            if trailing.contains(['.', '!', '?']) {
+                if let Some(previous) = out.last_mut() {
+                    previous.truncate(previous.trim_end_matches([',', ';', ':']).len());
+                    previous.push_str(trailing.trim_start_matches([',', ';', ':']));
+                }
+            }
+            capitalize_next |= token.chars().next().is_some_and(char::is_uppercase);
+            continue;
+        }
+
+        if capitalize_next {
+            let mut chars = token.chars();
+            let first = chars.next().map(|c| c.to_uppercase().collect::<String>());
+            out.push(format!("{}{}", first.unwrap_or_default(), chars.as_str()));
+            capitalize_next = false;
+        } else {
+            out.push(token.to_string());
+        }
+    }
+
+    out.join(" ")
+}
+
+fn trailing_punctuation(token: &str) -> &str {
+    let word_end = token
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_alphanumeric())
+        .map(|(idx, c)| idx + c.len_utf8())
+        .unwrap_or(0);
+    &token[word_end..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> PostProcessingOptions {
+        PostProcessingOptions {
+            language: "pt".to_string(),
+            clarity: true,
+            remove_fillers: true,
+            replacements: vec![ReplacementRule {
+                from: "zentra app".to_string(),
+                to: "Zentra".to_string(),
+            }],
+            dictation_commands: true,
+        }
+    }
+
+    #[test]
+    fn test_stages_run_in_production_order() {
+        let preview = PostProcessor::new(options()).run("hum  eu uso o zentra app, hã.");
+        let order: Vec<Stage> = preview.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(order, PostProcessor::STAGES);
+        assert_eq!(preview.stages[0].text, "Hum eu uso o zentra app, hã.");
+        assert_eq!(preview.stages[1].text, "Hum eu uso o Zentra, hã.");
+        assert_eq!(preview.stages[2].text, "Eu uso o Zentra.");
+        assert_eq!(preview.output, preview.stages[3].text);
+    }
+
+    #[test]
+    fn test_dictation_commands_keep_their_line_breaks() {
+        let preview =
+            PostProcessor::new(options()).run("hum lista nova linha pão vírgula leite hã");
+        assert_eq!(
+            preview.stages[0].text,
+            "Hum lista nova linha pão vírgula leite hã."
+        );
+        assert_eq!(preview.output, "Lista\nPão, leite.");
+    }
+
+    #[test]
+    fn test_disabled_stages_pass_text_through() {
+        let preview = PostProcessor::new(PostProcessingOptions::default()).run("um  texto");
+        assert!(preview.stages.iter().all(|stage| !stage.enabled));
+        assert_eq!(preview.output, "um  texto");
+    }
+
+    #[test]
+    fn test_overrides_replace_persisted_options() {
+        let overrides = OverrideOptions {
+            language: Some("en".to_string()),
+            clarity: Some(false),
+            ..OverrideOptions::default()
+        };
+        let options = overrides.apply(options());
+        assert_eq!(options.language, "en");
+        assert!(!options.clarity);
+        assert!(options.remove_fillers);
+        assert_eq!(
+            PostProcessor::new(options)
+                .run("Um so, uh, the Zentra app")
+                .output,
+            "So, the Zentra"
+        );
+    }
+
+    #[test]
+    fn test_detected_language_only_fills_in_auto() {
+        let auto = PostProcessingOptions {
+            language: "auto".to_string(),
+            ..options()
+        };
+        let detected = auto.clone().with_detected_language(Some("en-US"));
+        assert_eq!(detected.language, "en");
+        assert_eq!(
+            PostProcessor::new(detected).run("thx u").output,
+            "Thanks you."
+        );
+        assert_eq!(auto.clone().with_detected_language(None).language, "auto");
+        assert_eq!(options().with_detected_language(Some("en")).language, "pt");
+    }
+}
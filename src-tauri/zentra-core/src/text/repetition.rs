@@ -0,0 +1,154 @@
+//! Collapse Whisper repetition loops: the same sentence emitted several times in a row
+//! within one transcript ("Obrigado por assistir. Obrigado por assistir. ..."). The
+//! stitcher only removes overlap between segments, so loops inside one segment get here.
+
+use super::normalize::{normalize_for_match, similarity_ratio};
+
+/// Consecutive sentences at least this similar are one sentence repeated. Low enough for a
+/// loop that drifts a little ("Thanks for watching." / "Thank you for watching.").
+pub const REPEAT_SIMILARITY: f32 = 0.8;
+/// Shorter sentences are left alone; people do say "Sim. Sim." on purpose.
+pub const MIN_LOOP_WORDS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collapsed {
+    pub text: String,
+    /// Share of the normalized text that was dropped, 0-1
+    pub removed_ratio: f32,
+}
+
+/// Sentences of `text`, each with its closing punctuation. Text after the last sentence
+/// mark counts as a final sentence.
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, ch)) = chars.next() {
+        if !matches!(ch, '.' | '!' | '?' | '…') {
+            continue;
+        }
+        let at_boundary = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if at_boundary {
+            let end = idx + ch.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+/// Keep one sentence of every run of near-identical consecutive sentences. `None` when
+/// nothing repeats.
+pub fn collapse_repetitions(text: &str) -> Option<Collapsed> {
+    let sentences = split_sentences(text);
+    if sentences.len() < 2 {
+        return None;
+    }
+
+    let mut kept: Vec<&str> = Vec::with_capacity(sentences.len());
+    let mut total_chars = 0usize;
+    let mut removed_chars = 0usize;
+    for sentence in sentences {
+        let normalized = normalize_for_match(sentence);
+        let chars = normalized.chars().count();
+        total_chars += chars;
+
+        let repeats_previous = kept.last().is_some_and(|previous| {
+            normalized.split(' ').count() >= MIN_LOOP_WORDS
+                && similarity_ratio(previous, sentence) >= REPEAT_SIMILARITY
+        });
+        if repeats_previous {
+            removed_chars += chars;
+        } else {
+            kept.push(sentence);
+        }
+    }
+
+    if removed_chars == 0 || total_chars == 0 {
+        return None;
+    }
+    Some(Collapsed {
+        text: kept.join(" "),
+        removed_ratio: removed_chars as f32 / total_chars as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences_keep_their_punctuation() {
+        assert_eq!(
+            split_sentences("Olá! Tudo bem? Sim… R$ 3.50 cada. sem ponto"),
+            vec!["Olá!", "Tudo bem?", "Sim…", "R$ 3.50 cada.", "sem ponto"]
+        );
+        assert!(split_sentences("  ").is_empty());
+    }
+
+    #[test]
+    fn test_portuguese_loops_collapse_to_one_sentence() {
+        let collapsed = collapse_repetitions(
+            "Obrigado por assistir. Obrigado por assistir. Obrigado por assistir. \
+             Obrigado por assistir.",
+        )
+        .unwrap();
+        assert_eq!(collapsed.text, "Obrigado por assistir.");
+        assert!((collapsed.removed_ratio - 0.75).abs() < 1e-6);
+
+        // The loop drifts: gender, punctuation, accents.
+        let collapsed = collapse_repetitions(
+            "Vamos revisar o orçamento amanhã. Vamos revisar o orcamento amanhã! \
+             Vamos revisar o orçamento amanha. E depois fechamos a proposta.",
+        )
+        .unwrap();
+        assert_eq!(
+            collapsed.text,
+            "Vamos revisar o orçamento amanhã. E depois fechamos a proposta."
+        );
+        assert!(collapsed.removed_ratio > 0.5 && collapsed.removed_ratio < 0.7);
+    }
+
+    #[test]
+    fn test_english_loops_collapse_with_slight_variations() {
+        let collapsed = collapse_repetitions(
+            "Let me check the logs. Thanks for watching. Thank you for watching. \
+             Thanks for watching!",
+        )
+        .unwrap();
+        assert_eq!(
+            collapsed.text,
+            "Let me check the logs. Thanks for watching."
+        );
+
+        let collapsed = collapse_repetitions(
+            "I'll send the report on Friday. I will send the report on Friday.",
+        )
+        .unwrap();
+        assert_eq!(collapsed.text, "I'll send the report on Friday.");
+    }
+
+    #[test]
+    fn test_ordinary_speech_is_left_alone() {
+        assert_eq!(collapse_repetitions("Sim. Sim. Pode mandar."), None);
+        // The same sentence, but not back to back.
+        assert_eq!(
+            collapse_repetitions(
+                "Abre o arquivo. Salva uma cópia. Abre o arquivo. Fecha a janela."
+            ),
+            None
+        );
+        assert_eq!(
+            collapse_repetitions("Send it today. Send it tomorrow morning instead."),
+            None
+        );
+        assert_eq!(collapse_repetitions("Uma frase só."), None);
+    }
+}
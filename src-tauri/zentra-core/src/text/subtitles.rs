@@ -0,0 +1,275 @@
+//! Transcript export as subtitles (SRT, WebVTT), plain text or Markdown. There are no word
+//! timestamps, so cue times come from the segment durations: the final text's words are
+//! spread over the segments in proportion to what each segment transcribed, and each
+//! segment's time over its cues in proportion to their length.
+
+use serde::{Deserialize, Serialize};
+
+/// Subtitle lines wrap at this many characters; two lines make a cue.
+pub const MAX_LINE_CHARS: usize = 42;
+const LINES_PER_CUE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Srt,
+    Vtt,
+    Txt,
+    Md,
+}
+
+impl TranscriptFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::Vtt),
+            "txt" => Some(Self::Txt),
+            "md" => Some(Self::Md),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+            Self::Txt => "txt",
+            Self::Md => "md",
+        }
+    }
+}
+
+/// Length of one recorded segment and how many words it transcribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentTiming {
+    pub duration_secs: f32,
+    pub word_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start_secs: f32,
+    pub end_secs: f32,
+    pub lines: Vec<String>,
+}
+
+/// Cues for `text` over `segments`, in order and never overlapping.
+pub fn build_cues(text: &str, segments: &[SegmentTiming]) -> Vec<Cue> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let total_weight: u64 = segments.iter().map(|s| u64::from(s.word_count)).sum();
+    // Without word counts, time is the best guess for where the words went.
+    let weight = |segment: &SegmentTiming| {
+        if total_weight == 0 {
+            segment.duration_secs.max(0.0) as f64
+        } else {
+            f64::from(segment.word_count)
+        }
+    };
+    let total: f64 = segments.iter().map(weight).sum();
+
+    let mut cues = Vec::new();
+    let mut offset = 0.0f32;
+    let mut cumulative = 0.0f64;
+    let mut first_word = 0;
+    for segment in segments {
+        cumulative += weight(segment);
+        let last_word = if total > 0.0 {
+            ((words.len() as f64 * cumulative / total).round() as usize).min(words.len())
+        } else {
+            words.len()
+        };
+        let duration = segment.duration_secs.max(0.0);
+        push_segment_cues(&mut cues, &words[first_word..last_word], offset, duration);
+        first_word = last_word;
+        offset += duration;
+    }
+    cues
+}
+
+fn push_segment_cues(cues: &mut Vec<Cue>, words: &[&str], start: f32, duration: f32) {
+    let lines = wrap_lines(words);
+    let groups: Vec<&[String]> = lines.chunks(LINES_PER_CUE).collect();
+    let chars = |group: &[String]| group.iter().map(|l| l.chars().count()).sum::<usize>();
+    let total_chars: usize = groups.iter().map(|group| chars(group)).sum();
+    if total_chars == 0 {
+        return;
+    }
+
+    let mut done = 0usize;
+    for group in groups {
+        let begin = start + duration * done as f32 / total_chars as f32;
+        done += chars(group);
+        let end = start + duration * done as f32 / total_chars as f32;
+        cues.push(Cue {
+            start_secs: begin,
+            end_secs: end,
+            lines: group.to_vec(),
+        });
+    }
+}
+
+fn wrap_lines(words: &[&str]) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in words {
+        let fits = current.chars().count() + 1 + word.chars().count() <= MAX_LINE_CHARS;
+        if !current.is_empty() && !fits {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// File content for `text` in `format`. `title` heads the Markdown export.
+pub fn render(
+    format: TranscriptFormat,
+    text: &str,
+    segments: &[SegmentTiming],
+    title: &str,
+) -> String {
+    match format {
+        TranscriptFormat::Srt => {
+            let mut out = String::new();
+            for (index, cue) in build_cues(text, segments).iter().enumerate() {
+                out.push_str(&format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    index + 1,
+                    timestamp(cue.start_secs, ','),
+                    timestamp(cue.end_secs, ','),
+                    cue.lines.join("\n")
+                ));
+            }
+            out
+        }
+        TranscriptFormat::Vtt => {
+            let mut out = String::from("WEBVTT\n\n");
+            for cue in build_cues(text, segments) {
+                out.push_str(&format!(
+                    "{} --> {}\n{}\n\n",
+                    timestamp(cue.start_secs, '.'),
+                    timestamp(cue.end_secs, '.'),
+                    cue.lines.join("\n")
+                ));
+            }
+            out
+        }
+        TranscriptFormat::Txt => format!("{}\n", text.trim()),
+        TranscriptFormat::Md => format!("# {}\n\n{}\n", title, text.trim()),
+    }
+}
+
+/// `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT).
+fn timestamp(secs: f32, separator: char) -> String {
+    let millis = (f64::from(secs.max(0.0)) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(duration_secs: f32, word_count: u32) -> SegmentTiming {
+        SegmentTiming {
+            duration_secs,
+            word_count,
+        }
+    }
+
+    const SESSION: &str = "Bom dia a todos, hoje vamos revisar o orçamento do trimestre e as \
+        metas de vendas. Depois passamos para a proposta nova do cliente, com calma, item por \
+        item. Fechamos amanhã.";
+
+    #[test]
+    fn test_three_segment_session_cues_move_forward_without_overlap() {
+        let segments = [segment(20.0, 16), segment(15.5, 13), segment(4.0, 2)];
+        let cues = build_cues(SESSION, &segments);
+        assert!(cues.len() >= 3);
+
+        let mut previous_end = 0.0f32;
+        for cue in &cues {
+            assert!(cue.start_secs >= previous_end - 1e-4, "{:?}", cue);
+            assert!(cue.end_secs > cue.start_secs, "{:?}", cue);
+            assert!(cue.lines.len() <= LINES_PER_CUE);
+            assert!(cue
+                .lines
+                .iter()
+                .all(|line| line.chars().count() <= MAX_LINE_CHARS));
+            previous_end = cue.end_secs;
+        }
+        assert!((previous_end - 39.5).abs() < 1e-3);
+
+        // The last segment's two words get the last four seconds.
+        let last = cues.last().unwrap();
+        assert_eq!(last.lines, vec!["Fechamos amanhã."]);
+        assert!((last.start_secs - 35.5).abs() < 1e-3);
+
+        let words: Vec<String> = cues
+            .iter()
+            .flat_map(|cue| cue.lines.iter())
+            .flat_map(|line| line.split(' ').map(str::to_string))
+            .collect();
+        assert_eq!(
+            words.join(" "),
+            SESSION.split_whitespace().collect::<Vec<_>>().join(" ")
+        );
+    }
+
+    #[test]
+    fn test_srt_and_vtt_formats() {
+        let segments = [segment(3.0, 3), segment(62.25, 2)];
+        let srt = render(
+            TranscriptFormat::Srt,
+            "Olá a todos. Até logo.",
+            &segments,
+            "",
+        );
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:03,000\nOlá a todos.\n\n\
+             2\n00:00:03,000 --> 00:01:05,250\nAté logo.\n\n"
+        );
+
+        let vtt = render(
+            TranscriptFormat::Vtt,
+            "Olá a todos. Até logo.",
+            &segments,
+            "",
+        );
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:03.000\nOlá a todos.\n\n"));
+
+        assert_eq!(
+            render(TranscriptFormat::Md, " Texto ", &segments, "Reunião"),
+            "# Reunião\n\nTexto\n"
+        );
+        assert_eq!(
+            TranscriptFormat::parse(" SRT "),
+            Some(TranscriptFormat::Srt)
+        );
+        assert_eq!(TranscriptFormat::parse("docx"), None);
+    }
+
+    #[test]
+    fn test_segments_without_word_counts_split_by_time() {
+        let cues = build_cues("um dois três quatro", &[segment(1.0, 0), segment(3.0, 0)]);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].lines, vec!["um"]);
+        assert_eq!(cues[1].lines, vec!["dois três quatro"]);
+        assert!(build_cues("", &[segment(1.0, 1)]).is_empty());
+        assert!(build_cues("texto", &[]).is_empty());
+    }
+}
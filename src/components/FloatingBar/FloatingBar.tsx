@@ -34,16 +34,30 @@ const CancelButton: React.FC<{ onClick: () => void }> = React.memo(({ onClick })
   </button>
 ));
 
-const ProcessingContent: React.FC = React.memo(() => (
+const ProcessingContent: React.FC<{ percent: number | null }> = React.memo(({ percent }) => (
   <div className="processing-content">
     <div className="spinner" />
-    <span className="processing-text">Transcrevendo...</span>
+    <span className="processing-text">
+      {percent === null ? 'Transcrevendo...' : `Transcrevendo... ${percent}%`}
+    </span>
   </div>
 ));
 
 const FloatingBar: React.FC<{ onToast?: (toast: ToastPayload) => void }> = ({ onToast }) => {
   const [hovered, setHovered] = useState(false);
-  const { state, mode, setMode, startRecording, stopRecording, cancel, closeApp } = useRecording({ onToast });
+  const {
+    state,
+    mode,
+    setMode,
+    partialText,
+    transcribePercent,
+    startRecording,
+    stopRecording,
+    cancel,
+    closeApp,
+  } = useRecording({
+    onToast,
+  });
   const audioLevel = useAudioLevel(state === 'recording');
   const barRef = useRef<HTMLDivElement>(null);
   const isInteractiveState = state === 'idle' || state === 'recording';
@@ -67,11 +81,14 @@ const FloatingBar: React.FC<{ onToast?: (toast: ToastPayload) => void }> = ({ on
           <span className="bar-brand">zentra</span>
         </div>
 
-        <div className="bar-center">
+        <div className="bar-center" title={partialText || undefined}>
           {isInteractiveState && (
             <Waveform audioLevel={audioLevel} isRecording={state === 'recording'} compact />
           )}
-          {state === 'processing' && <ProcessingContent />}
+          {state === 'recording' && partialText && (
+            <span className="bar-partial">{partialText.split(/\s+/).slice(-4).join(' ')}</span>
+          )}
+          {state === 'processing' && <ProcessingContent percent={transcribePercent} />}
         </div>
 
         {isInteractiveState && (
@@ -2,20 +2,83 @@ import React, { useCallback, useEffect, useMemo, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { openUrl } from '@tauri-apps/plugin-opener';
+import { writeText } from '@tauri-apps/plugin-clipboard-manager';
 import Sidebar from './Sidebar';
 import StatsBar from './sections/StatsBar';
+import UsagePanel from './sections/UsagePanel';
+import TypingCalibration from './sections/TypingCalibration';
 import History from './sections/History';
-import type { DashboardData } from './types';
+import type {
+  ApiKeyInvalid,
+  ApiKeySlot,
+  DashboardData,
+  DestructiveActionToken,
+  EnvImported,
+  HistoryItem,
+  HistorySearchResult,
+  HistoryUpdated,
+  KeyUpdate,
+  LanguageMismatchSuggestion,
+  PendingTranscriptionCompleted,
+  SettingsChanged,
+  StartupHealth,
+} from './types';
 
 type Section = 'dashboard' | 'history' | 'settings' | 'community';
 
+const LANGUAGE_NAMES: Record<string, string> = {
+  pt: 'Portuguese',
+  en: 'English',
+  auto: 'automatic detection',
+};
+
+const keyUpdate = (draft: string): KeyUpdate => (draft.trim() ? { set: draft.trim() } : 'unchanged');
+
 interface SettingsDraft {
   userName: string;
   apiKey: string;
+  elevenlabsApiKey: string;
+  openrouterApiKey: string;
   hotkey: string;
+  stopHotkey: string;
+  pasteLastHotkey: string;
   language: 'pt' | 'en' | 'auto';
+  liveTranscription: boolean;
+  noiseSuppression: boolean;
+  a11yAnnouncements: boolean;
+  dictationCommands: boolean;
+  typingWpm: number | null;
+  sessionWarmup: boolean;
+  inputGain: number;
+  preRollMs: number;
+  restoreClipboard: boolean;
+  maxRecordingSecs: number;
+  historyLimit: number;
 }
 
+const HISTORY_LIMIT_OPTIONS = [
+  { id: 50, label: '50' },
+  { id: 500, label: '500' },
+  { id: 0, label: 'Keep everything' },
+];
+
+const MAX_RECORDING_OPTIONS = [
+  { id: 300, label: '5 min' },
+  { id: 900, label: '15 min' },
+  { id: 0, label: 'No limit' },
+];
+
+const INPUT_GAIN_OPTIONS = [
+  { id: 1.5, label: 'Hot microphone' },
+  { id: 2.5, label: 'Normal' },
+  { id: 5, label: 'Quiet microphone' },
+];
+
+const PRE_ROLL_OPTIONS = [
+  { id: 0, label: 'Open the mic on the shortcut' },
+  { id: 500, label: 'Keep the mic open, catch the first word' },
+];
+
 const INSPIRATION_MESSAGES = [
   'Ready to shape the future',
   'Your voice is faster than typing',
@@ -30,10 +93,31 @@ const Dashboard: React.FC = () => {
   const [settingsDraft, setSettingsDraft] = useState<SettingsDraft>({
     userName: '',
     apiKey: '',
+    elevenlabsApiKey: '',
+    openrouterApiKey: '',
     hotkey: 'CommandOrControl+Shift+Space',
+    stopHotkey: '',
+    pasteLastHotkey: '',
     language: 'pt',
+    liveTranscription: true,
+    noiseSuppression: false,
+    a11yAnnouncements: false,
+    dictationCommands: false,
+    typingWpm: null,
+    sessionWarmup: false,
+    inputGain: 2.5,
+    preRollMs: 0,
+    restoreClipboard: false,
+    maxRecordingSecs: 300,
+    historyLimit: 50,
   });
+  const [historyQuery, setHistoryQuery] = useState('');
+  const [historyResults, setHistoryResults] = useState<HistoryItem[]>([]);
+  const [historyMatches, setHistoryMatches] = useState(0);
+  const [historyPage, setHistoryPage] = useState(0);
   const [notice, setNotice] = useState<string | null>(null);
+  const [crashMessage, setCrashMessage] = useState<string | null>(null);
+  const [languageSuggestion, setLanguageSuggestion] = useState<LanguageMismatchSuggestion | null>(null);
   const [saving, setSaving] = useState(false);
   const [messageIndex, setMessageIndex] = useState(0);
   const [isDashboardMaximized, setIsDashboardMaximized] = useState(false);
@@ -46,8 +130,23 @@ const Dashboard: React.FC = () => {
       setSettingsDraft({
         userName: result.userName || '',
         apiKey: '',
+        elevenlabsApiKey: '',
+        openrouterApiKey: '',
         hotkey: result.hotkey || 'CommandOrControl+Shift+Space',
+        stopHotkey: result.hotkeys?.stopRecording ?? '',
+        pasteLastHotkey: result.hotkeys?.pasteLast ?? '',
         language: result.language || 'pt',
+        liveTranscription: result.liveTranscription ?? true,
+        noiseSuppression: result.noiseSuppression ?? false,
+        a11yAnnouncements: result.a11yAnnouncements ?? false,
+        dictationCommands: result.dictationCommands ?? false,
+        typingWpm: result.typingWpm ?? null,
+        sessionWarmup: result.sessionWarmup ?? false,
+        inputGain: result.inputGain ?? 2.5,
+        preRollMs: result.preRollMs ?? 0,
+        restoreClipboard: result.restoreClipboard ?? false,
+        maxRecordingSecs: result.maxRecordingSecs ?? 300,
+        historyLimit: result.historyLimit ?? 50,
       });
     } finally {
       setLoading(false);
@@ -62,9 +161,10 @@ const Dashboard: React.FC = () => {
     let unlistenNavigate: (() => void) | null = null;
     let unlistenRefresh: (() => void) | null = null;
     let unlistenHistory: (() => void) | null = null;
+    let unlistenSettings: (() => void) | null = null;
 
-    void listen<string>('dashboard:navigate', (event) => {
-      if (event.payload === 'settings') {
+    void listen<{ section: string }>('dashboard:navigate', (event) => {
+      if (event.payload.section === 'settings') {
         setActiveSection('settings');
       } else {
         setActiveSection('dashboard');
@@ -80,16 +180,26 @@ const Dashboard: React.FC = () => {
       unlistenRefresh = fn;
     });
 
-    void listen('dashboard:history-updated', () => {
-      void loadDashboard();
+    void listen<HistoryUpdated>('dashboard:history-updated', (event) => {
+      const { added, removed, updated } = event.payload.changeSummary;
+      if (added + removed + updated > 0) {
+        void loadDashboard();
+      }
     }).then((fn) => {
       unlistenHistory = fn;
     });
 
+    void listen<SettingsChanged>('settings-changed', () => {
+      void loadDashboard();
+    }).then((fn) => {
+      unlistenSettings = fn;
+    });
+
     return () => {
       unlistenNavigate?.();
       unlistenRefresh?.();
       unlistenHistory?.();
+      unlistenSettings?.();
     };
   }, [loadDashboard]);
 
@@ -123,7 +233,95 @@ const Dashboard: React.FC = () => {
     }, 1800);
   }, []);
 
-  const totalItems = data?.history.length ?? 0;
+  useEffect(() => {
+    let unlistenEnvImported: (() => void) | null = null;
+    void listen<EnvImported>('env-imported', (event) => {
+      showNotice(`Imported ${event.payload.keys.join(', ')} from .env. You can delete the .env file.`);
+      void loadDashboard();
+    }).then((fn) => {
+      unlistenEnvImported = fn;
+    });
+    return () => unlistenEnvImported?.();
+  }, [loadDashboard, showNotice]);
+
+  useEffect(() => {
+    // The event can fire before this window listens, so ask as well.
+    void invoke<StartupHealth>('get_startup_health').then((health) => {
+      setCrashMessage(health.crashMessage ?? null);
+    });
+    let unlistenHealth: (() => void) | null = null;
+    void listen<StartupHealth>('startup-health', (event) => {
+      setCrashMessage(event.payload.crashMessage ?? null);
+    }).then((fn) => {
+      unlistenHealth = fn;
+    });
+    return () => unlistenHealth?.();
+  }, []);
+
+  useEffect(() => {
+    let unlistenKeyInvalid: (() => void) | null = null;
+    void listen<ApiKeyInvalid>('api-key-invalid', (event) => {
+      setActiveSection('settings');
+      showNotice(`${event.payload.message} Paste a new key below.`);
+      void loadDashboard();
+    }).then((fn) => {
+      unlistenKeyInvalid = fn;
+    });
+    return () => unlistenKeyInvalid?.();
+  }, [loadDashboard, showNotice]);
+
+  useEffect(() => {
+    let unlistenSuggestion: (() => void) | null = null;
+    void listen<LanguageMismatchSuggestion>('language-mismatch-suggestion', (event) => {
+      setLanguageSuggestion(event.payload);
+    }).then((fn) => {
+      unlistenSuggestion = fn;
+    });
+    return () => unlistenSuggestion?.();
+  }, []);
+
+  const answerLanguageSuggestion = useCallback(
+    async (accept: boolean) => {
+      setLanguageSuggestion(null);
+      try {
+        if (accept) {
+          const language = await invoke<string>('accept_language_suggestion');
+          showNotice(`Dictation language set to ${LANGUAGE_NAMES[language] ?? language}`);
+          void loadDashboard();
+        } else {
+          await invoke('dismiss_language_suggestion');
+        }
+      } catch (err) {
+        showNotice(String(err));
+      }
+    },
+    [loadDashboard, showNotice],
+  );
+
+  useEffect(() => {
+    let unlistenPending: (() => void) | null = null;
+    void listen<PendingTranscriptionCompleted>('pending-transcription-completed', () => {
+      showNotice('Offline recording transcribed');
+    }).then((fn) => {
+      unlistenPending = fn;
+    });
+    return () => unlistenPending?.();
+  }, [showNotice]);
+
+  const searchHistory = useCallback(async (query: string, page: number) => {
+    const result = await invoke<HistorySearchResult>('search_history', { search: { query, page } });
+    setHistoryResults((current) => (page === 0 ? result.items : [...current, ...result.items]));
+    setHistoryMatches(result.total);
+    setHistoryPage(result.page);
+  }, []);
+
+  useEffect(() => {
+    if (activeSection !== 'history') return;
+    const timer = window.setTimeout(() => void searchHistory(historyQuery, 0), 200);
+    return () => window.clearTimeout(timer);
+  }, [activeSection, data, historyQuery, searchHistory]);
+
+  const totalItems = data?.historyTotal ?? 0;
   const displayName = data?.userName?.trim() || 'Creator';
 
   const pageTitle = useMemo(() => {
@@ -141,17 +339,55 @@ const Dashboard: React.FC = () => {
 
   const handleDeleteHistory = useCallback(
     async (id: string) => {
+      // The history-updated event that follows reloads the dashboard.
       await invoke('delete_history_item', { id });
-      await loadDashboard();
       showNotice('History item deleted');
     },
-    [loadDashboard, showNotice],
+    [showNotice],
   );
 
+  const handleCancelPending = useCallback(
+    async (id: string) => {
+      await invoke<boolean>('cancel_pending_transcription', { id });
+      showNotice('Offline recording discarded');
+    },
+    [showNotice],
+  );
+
+  const handleRetranscribe = useCallback(
+    async (id: string) => {
+      try {
+        await invoke('retranscribe_history_item', { id });
+        showNotice('Transcribed again from the saved recording');
+      } catch (error) {
+        showNotice(`Could not re-transcribe: ${String(error)}`);
+      }
+    },
+    [showNotice],
+  );
+
+  const handleToggleOffline = useCallback(async () => {
+    if (!data) return;
+    await invoke('set_offline_mode', { enabled: !data.offlineMode });
+    await loadDashboard();
+  }, [data, loadDashboard]);
+
   const handleClearHistory = useCallback(async () => {
-    await invoke('clear_history');
+    const confirmation = await invoke<DestructiveActionToken>('request_destructive_action', {
+      kind: 'clearHistory',
+    });
+    const { historyItems, oldest, newest } = confirmation.scope;
+    if (historyItems === 0) return;
+    const range = oldest && newest
+      ? ` from ${new Date(oldest).toLocaleDateString()} to ${new Date(newest).toLocaleDateString()}`
+      : '';
+    const confirmed = window.confirm(
+      `Clear ${historyItems} history item${historyItems === 1 ? '' : 's'}${range}? You can restore them for 24 hours.`,
+    );
+    if (!confirmed) return;
+    await invoke('clear_history', { token: confirmation.token });
     await loadDashboard();
-    showNotice('History cleared');
+    showNotice('History moved to trash');
   }, [loadDashboard, showNotice]);
 
   const handleSaveSettings = useCallback(async () => {
@@ -162,18 +398,64 @@ const Dashboard: React.FC = () => {
         payload: {
           userName: settingsDraft.userName,
           hotkey: settingsDraft.hotkey,
+          hotkeys: {
+            stopRecording: settingsDraft.stopHotkey,
+            pasteLast: settingsDraft.pasteLastHotkey,
+          },
           language: settingsDraft.language,
-          apiKey: settingsDraft.apiKey.trim() ? settingsDraft.apiKey : undefined,
+          liveTranscription: settingsDraft.liveTranscription,
+          noiseSuppression: settingsDraft.noiseSuppression,
+          a11yAnnouncements: settingsDraft.a11yAnnouncements,
+          dictationCommands: settingsDraft.dictationCommands,
+          typingWpm: settingsDraft.typingWpm ?? 0,
+          sessionWarmup: settingsDraft.sessionWarmup,
+          inputGain: settingsDraft.inputGain,
+          preRollMs: settingsDraft.preRollMs,
+          restoreClipboard: settingsDraft.restoreClipboard,
+          maxRecordingSecs: settingsDraft.maxRecordingSecs,
+          historyLimit: settingsDraft.historyLimit,
+          apiKey: keyUpdate(settingsDraft.apiKey),
+          elevenlabsApiKey: keyUpdate(settingsDraft.elevenlabsApiKey),
+          openrouterApiKey: keyUpdate(settingsDraft.openrouterApiKey),
         },
       });
       await loadDashboard();
-      setSettingsDraft((current) => ({ ...current, apiKey: '' }));
+      setSettingsDraft((current) => ({ ...current, apiKey: '', elevenlabsApiKey: '', openrouterApiKey: '' }));
       showNotice('Settings updated');
+    } catch (error) {
+      showNotice(String(error));
     } finally {
       setSaving(false);
     }
   }, [data, loadDashboard, settingsDraft, showNotice]);
 
+  const handleCopyStoredKey = useCallback(
+    async (slot: ApiKeySlot) => {
+      const confirmation = await invoke<DestructiveActionToken>('request_destructive_action', {
+        kind: 'revealApiKey',
+      });
+      if (!window.confirm('Copy the stored API key to the clipboard?')) return;
+      try {
+        const key = await invoke<string>('reveal_api_key', { slot, token: confirmation.token });
+        await writeText(key);
+        showNotice('API key copied');
+      } catch (error) {
+        showNotice(String(error));
+      }
+    },
+    [showNotice],
+  );
+
+  const handleRemoveStoredKey = useCallback(
+    async (field: 'apiKey' | 'elevenlabsApiKey' | 'openrouterApiKey') => {
+      if (!window.confirm('Remove the stored API key?')) return;
+      await invoke('update_settings', { payload: { [field]: 'clear' } });
+      await loadDashboard();
+      showNotice('API key removed');
+    },
+    [loadDashboard, showNotice],
+  );
+
   if (loading) {
     return (
       <div className="dashboard-window">
@@ -252,13 +534,22 @@ const Dashboard: React.FC = () => {
                 <span className="dashboard-badge">
                   {data.hasApiKey ? `API: ${data.apiKeyMasked ?? 'configured'}` : 'API key missing'}
                 </span>
+                {data.offlineMode && <span className="dashboard-badge">Offline mode</span>}
+                {data.pendingTranscriptions > 0 && (
+                  <span className="dashboard-badge">
+                    {`${data.pendingTranscriptions} waiting for connection`}
+                  </span>
+                )}
               </div>
               <StatsBar stats={data.stats} />
+              <UsagePanel />
               <div className="dashboard-history-panel">
                 <h2 className="dashboard-section-title">Recent history</h2>
                 <History
                   items={data.history.slice(0, 6)}
                   onDelete={handleDeleteHistory}
+                  onCancelPending={handleCancelPending}
+                  onRetranscribe={handleRetranscribe}
                   onCopied={() => showNotice('Copied to clipboard')}
                 />
               </div>
@@ -268,11 +559,29 @@ const Dashboard: React.FC = () => {
           {activeSection === 'history' && (
             <>
               <h2 className="dashboard-section-title">All transcriptions</h2>
+              <input
+                className="setup-input"
+                type="search"
+                placeholder="Search transcriptions"
+                value={historyQuery}
+                onChange={(event) => setHistoryQuery(event.target.value)}
+              />
               <History
-                items={data.history}
+                items={historyResults}
                 onDelete={handleDeleteHistory}
+                onCancelPending={handleCancelPending}
+                onRetranscribe={handleRetranscribe}
                 onCopied={() => showNotice('Copied to clipboard')}
               />
+              {historyResults.length < historyMatches && (
+                <button
+                  type="button"
+                  className="setup-primary-outline-btn"
+                  onClick={() => void searchHistory(historyQuery, historyPage + 1)}
+                >
+                  Load more
+                </button>
+              )}
             </>
           )}
 
@@ -300,6 +609,90 @@ const Dashboard: React.FC = () => {
                   }
                   autoComplete="off"
                 />
+                {data.hasApiKey && (
+                  <div className="dashboard-key-actions">
+                    <button type="button" className="dashboard-link-btn" onClick={() => void handleCopyStoredKey('groq')}>
+                      Copy stored key
+                    </button>
+                    <button
+                      type="button"
+                      className="dashboard-link-btn"
+                      onClick={() => void handleRemoveStoredKey('apiKey')}
+                    >
+                      Remove
+                    </button>
+                  </div>
+                )}
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">ElevenLabs API key (fallback)</label>
+                <input
+                  className="setup-input setup-input-mono"
+                  value={settingsDraft.elevenlabsApiKey}
+                  onChange={(event) =>
+                    setSettingsDraft((current) => ({ ...current, elevenlabsApiKey: event.target.value }))
+                  }
+                  placeholder={
+                    data.elevenlabsApiKeyMasked
+                      ? `Stored key: ${data.elevenlabsApiKeyMasked} (leave blank to keep)`
+                      : 'sk_...'
+                  }
+                  autoComplete="off"
+                />
+                {data.elevenlabsApiKeyMasked && (
+                  <div className="dashboard-key-actions">
+                    <button
+                      type="button"
+                      className="dashboard-link-btn"
+                      onClick={() => void handleCopyStoredKey('elevenlabs')}
+                    >
+                      Copy stored key
+                    </button>
+                    <button
+                      type="button"
+                      className="dashboard-link-btn"
+                      onClick={() => void handleRemoveStoredKey('elevenlabsApiKey')}
+                    >
+                      Remove
+                    </button>
+                  </div>
+                )}
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">OpenRouter API key</label>
+                <input
+                  className="setup-input setup-input-mono"
+                  value={settingsDraft.openrouterApiKey}
+                  onChange={(event) =>
+                    setSettingsDraft((current) => ({ ...current, openrouterApiKey: event.target.value }))
+                  }
+                  placeholder={
+                    data.openrouterApiKeyMasked
+                      ? `Stored key: ${data.openrouterApiKeyMasked} (leave blank to keep)`
+                      : 'sk-or-...'
+                  }
+                  autoComplete="off"
+                />
+                {data.openrouterApiKeyMasked && (
+                  <div className="dashboard-key-actions">
+                    <button
+                      type="button"
+                      className="dashboard-link-btn"
+                      onClick={() => void handleCopyStoredKey('openrouter')}
+                    >
+                      Copy stored key
+                    </button>
+                    <button
+                      type="button"
+                      className="dashboard-link-btn"
+                      onClick={() => void handleRemoveStoredKey('openrouterApiKey')}
+                    >
+                      Remove
+                    </button>
+                  </div>
+                )}
               </div>
 
               <div className="setup-field">
@@ -311,6 +704,28 @@ const Dashboard: React.FC = () => {
                 />
               </div>
 
+              <div className="setup-field">
+                <label className="setup-label">Stop hotkey (optional)</label>
+                <input
+                  className="setup-input setup-input-mono"
+                  value={settingsDraft.stopHotkey}
+                  onChange={(event) => setSettingsDraft((current) => ({ ...current, stopHotkey: event.target.value }))}
+                  placeholder="Only stops a recording; leave blank for none"
+                />
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Paste last transcript hotkey (optional)</label>
+                <input
+                  className="setup-input setup-input-mono"
+                  value={settingsDraft.pasteLastHotkey}
+                  onChange={(event) =>
+                    setSettingsDraft((current) => ({ ...current, pasteLastHotkey: event.target.value }))
+                  }
+                  placeholder="Pastes the last dictation again; leave blank for none"
+                />
+              </div>
+
               <div className="setup-field">
                 <label className="setup-label">Recognition language</label>
                 <div className="setup-usecase-grid">
@@ -336,6 +751,222 @@ const Dashboard: React.FC = () => {
                 </div>
               </div>
 
+              <div className="setup-field">
+                <label className="setup-label">Live preview while recording</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: true, label: 'On' },
+                    { id: false, label: 'Off (fewer API requests)' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.liveTranscription === option.id ? 'active' : ''}`}
+                      onClick={() =>
+                        setSettingsDraft((current) => ({ ...current, liveTranscription: option.id }))
+                      }
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Background noise</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: false, label: 'Send the recording as captured' },
+                    { id: true, label: 'Reduce fan and hum noise' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.noiseSuppression === option.id ? 'active' : ''}`}
+                      onClick={() =>
+                        setSettingsDraft((current) => ({ ...current, noiseSuppression: option.id }))
+                      }
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Level meter sensitivity</label>
+                <div className="setup-usecase-grid">
+                  {INPUT_GAIN_OPTIONS.map((option) => (
+                    <button
+                      key={option.id}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.inputGain === option.id ? 'active' : ''}`}
+                      onClick={() => setSettingsDraft((current) => ({ ...current, inputGain: option.id }))}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Recording start</label>
+                <div className="setup-usecase-grid">
+                  {PRE_ROLL_OPTIONS.map((option) => (
+                    <button
+                      key={option.id}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.preRollMs === option.id ? 'active' : ''}`}
+                      onClick={() => setSettingsDraft((current) => ({ ...current, preRollMs: option.id }))}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Screen reader</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: false, label: 'Stay quiet' },
+                    { id: true, label: 'Announce recording and paste events' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.a11yAnnouncements === option.id ? 'active' : ''}`}
+                      onClick={() =>
+                        setSettingsDraft((current) => ({ ...current, a11yAnnouncements: option.id }))
+                      }
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Spoken commands</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: false, label: 'Type every word' },
+                    { id: true, label: 'Turn "new line", "comma", "vírgula" into formatting' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.dictationCommands === option.id ? 'active' : ''}`}
+                      onClick={() =>
+                        setSettingsDraft((current) => ({ ...current, dictationCommands: option.id }))
+                      }
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <TypingCalibration
+                wpm={settingsDraft.typingWpm}
+                onChange={(typingWpm) => setSettingsDraft((current) => ({ ...current, typingWpm }))}
+              />
+
+              <div className="setup-field">
+                <label className="setup-label">First segment</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: false, label: 'Send as recorded' },
+                    { id: true, label: 'Warm up early and pin the language' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.sessionWarmup === option.id ? 'active' : ''}`}
+                      onClick={() =>
+                        setSettingsDraft((current) => ({ ...current, sessionWarmup: option.id }))
+                      }
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">After pasting</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: false, label: 'Leave the transcript on the clipboard' },
+                    { id: true, label: 'Restore what I had copied' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.restoreClipboard === option.id ? 'active' : ''}`}
+                      onClick={() =>
+                        setSettingsDraft((current) => ({ ...current, restoreClipboard: option.id }))
+                      }
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Work offline</label>
+                <div className="setup-usecase-grid">
+                  {[
+                    { id: false, label: 'Off' },
+                    { id: true, label: 'On (keep recordings, send when back online)' },
+                  ].map((option) => (
+                    <button
+                      key={option.label}
+                      type="button"
+                      className={`setup-usecase-pill ${data.offlineMode === option.id ? 'active' : ''}`}
+                      onClick={() => {
+                        if (data.offlineMode !== option.id) void handleToggleOffline();
+                      }}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Stop recordings automatically after</label>
+                <div className="setup-usecase-grid">
+                  {MAX_RECORDING_OPTIONS.map((option) => (
+                    <button
+                      key={option.id}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.maxRecordingSecs === option.id ? 'active' : ''}`}
+                      onClick={() => setSettingsDraft((current) => ({ ...current, maxRecordingSecs: option.id }))}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
+              <div className="setup-field">
+                <label className="setup-label">Transcriptions kept in history</label>
+                <div className="setup-usecase-grid">
+                  {HISTORY_LIMIT_OPTIONS.map((option) => (
+                    <button
+                      key={option.id}
+                      type="button"
+                      className={`setup-usecase-pill ${settingsDraft.historyLimit === option.id ? 'active' : ''}`}
+                      onClick={() => setSettingsDraft((current) => ({ ...current, historyLimit: option.id }))}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+
               <div className="dashboard-settings-actions">
                 <button type="button" className="setup-primary-outline-btn" onClick={handleSaveSettings} disabled={saving}>
                   {saving ? 'Saving...' : 'Save settings'}
@@ -435,6 +1066,35 @@ const Dashboard: React.FC = () => {
       </div>
 
       {notice && <div className="dashboard-notice">{notice}</div>}
+      {crashMessage && (
+        <div className="dashboard-crash-notice">
+          <span>{crashMessage}</span>
+          <button
+            type="button"
+            className="dashboard-link-btn"
+            onClick={() => void invoke('open_crash_folder').catch((err) => showNotice(String(err)))}
+          >
+            Open crash folder
+          </button>
+          <button type="button" className="dashboard-link-btn" onClick={() => setCrashMessage(null)}>
+            Dismiss
+          </button>
+        </div>
+      )}
+      {languageSuggestion && (
+        <div className="dashboard-crash-notice">
+          <span>
+            {languageSuggestion.mismatches} of your last {languageSuggestion.window} dictations didn't sound like{' '}
+            {LANGUAGE_NAMES[languageSuggestion.configured] ?? languageSuggestion.configured}.
+          </span>
+          <button type="button" className="dashboard-link-btn" onClick={() => void answerLanguageSuggestion(true)}>
+            Switch to {LANGUAGE_NAMES[languageSuggestion.suggested] ?? languageSuggestion.suggested}
+          </button>
+          <button type="button" className="dashboard-link-btn" onClick={() => void answerLanguageSuggestion(false)}>
+            Keep it
+          </button>
+        </div>
+      )}
     </div>
   );
 };
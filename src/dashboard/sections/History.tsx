@@ -5,10 +5,12 @@ import HistoryItem from './HistoryItem';
 interface HistoryProps {
   items: HistoryEntry[];
   onDelete: (id: string) => void;
+  onCancelPending: (id: string) => void;
+  onRetranscribe: (id: string) => void;
   onCopied: () => void;
 }
 
-const History: React.FC<HistoryProps> = ({ items, onDelete, onCopied }) => {
+const History: React.FC<HistoryProps> = ({ items, onDelete, onCancelPending, onRetranscribe, onCopied }) => {
   if (items.length === 0) {
     return (
       <div className="dashboard-empty-state">
@@ -22,7 +24,14 @@ const History: React.FC<HistoryProps> = ({ items, onDelete, onCopied }) => {
   return (
     <div className="dashboard-history-list">
       {items.map((item) => (
-        <HistoryItem key={item.id} item={item} onDelete={onDelete} onCopied={onCopied} />
+        <HistoryItem
+          key={item.id}
+          item={item}
+          onDelete={onDelete}
+          onCancelPending={onCancelPending}
+          onRetranscribe={onRetranscribe}
+          onCopied={onCopied}
+        />
       ))}
     </div>
   );
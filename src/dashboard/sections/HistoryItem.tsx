@@ -5,13 +5,16 @@ import type { HistoryItem as HistoryEntry } from '../types';
 interface HistoryItemProps {
   item: HistoryEntry;
   onDelete: (id: string) => void;
+  onCancelPending: (id: string) => void;
+  onRetranscribe: (id: string) => void;
   onCopied: () => void;
 }
 
 const PREVIEW_LIMIT = 120;
 
-const HistoryItem: React.FC<HistoryItemProps> = ({ item, onDelete, onCopied }) => {
+const HistoryItem: React.FC<HistoryItemProps> = ({ item, onDelete, onCancelPending, onRetranscribe, onCopied }) => {
   const [expanded, setExpanded] = useState(false);
+  const pending = item.status === 'pendingTranscription';
 
   const previewText = useMemo(() => {
     if (item.text.length <= PREVIEW_LIMIT) return item.text;
@@ -25,6 +28,17 @@ const HistoryItem: React.FC<HistoryItemProps> = ({ item, onDelete, onCopied }) =
     return parsed.toLocaleString();
   }, [item.timestamp]);
 
+  // e.g. "Groq 18 · ElevenLabs 3 · 2 retried · 1 gated"
+  const breakdown = useMemo(() => {
+    const stats = item.sessionStats;
+    if (!stats) return null;
+    const parts = stats.providerBreakdown.map((entry) => `${entry.provider} ${entry.segments}`);
+    if (stats.retriedSegments > 0) parts.push(`${stats.retriedSegments} retried`);
+    if (stats.gatedSegments > 0) parts.push(`${stats.gatedSegments} gated`);
+    if (stats.failedSegments > 0) parts.push(`${stats.failedSegments} failed`);
+    return parts.length > 0 ? parts.join(' · ') : null;
+  }, [item.sessionStats]);
+
   return (
     <div className="dashboard-history-item">
       <div className="dashboard-history-icon">≋</div>
@@ -35,23 +49,40 @@ const HistoryItem: React.FC<HistoryItemProps> = ({ item, onDelete, onCopied }) =
             {expanded ? 'Show less' : 'Show more'}
           </button>
         )}
+        {breakdown && <span className="dashboard-history-breakdown">{breakdown}</span>}
       </div>
       <div className="dashboard-history-meta">
         <span>{timestamp}</span>
-        <span>{item.wordCount} words</span>
+        <span>{pending ? 'Waiting for connection' : `${item.wordCount} words`}</span>
+        {!pending && item.minutesSaved !== undefined && item.minutesSaved >= 0.1 && (
+          <span>saved ~{item.minutesSaved.toFixed(1)} min</span>
+        )}
         <div className="dashboard-history-actions">
-          <button
-            type="button"
-            onClick={async () => {
-              await writeText(item.text);
-              onCopied();
-            }}
-          >
-            Copy
-          </button>
-          <button type="button" onClick={() => onDelete(item.id)}>
-            Delete
-          </button>
+          {pending ? (
+            <button type="button" onClick={() => onCancelPending(item.id)}>
+              Cancel
+            </button>
+          ) : (
+            <>
+              <button
+                type="button"
+                onClick={async () => {
+                  await writeText(item.text);
+                  onCopied();
+                }}
+              >
+                Copy
+              </button>
+              {item.audioPath && (
+                <button type="button" onClick={() => onRetranscribe(item.id)}>
+                  Re-transcribe
+                </button>
+              )}
+              <button type="button" onClick={() => onDelete(item.id)}>
+                Delete
+              </button>
+            </>
+          )}
         </div>
       </div>
     </div>
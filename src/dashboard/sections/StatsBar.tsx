@@ -16,14 +16,18 @@ function formatDecimal(value: number): string {
 const StatsBar: React.FC<StatsBarProps> = ({ stats }) => {
   const cards = [
     { label: 'Words dictated', value: formatNumber(stats.totalWords) },
-    { label: 'Minutes saved', value: formatDecimal(stats.minutesSaved) },
+    {
+      label: 'Minutes saved',
+      value: formatDecimal(stats.minutesSaved),
+      hint: `Typing time at ${formatNumber(stats.typingWpm)} WPM`,
+    },
     { label: 'Voice WPM', value: formatDecimal(stats.wpm) },
   ];
 
   return (
     <div className="dashboard-stats-grid">
       {cards.map((card) => (
-        <div key={card.label} className="dashboard-stat-card">
+        <div key={card.label} className="dashboard-stat-card" title={card.hint}>
           <div className="dashboard-stat-label">{card.label}</div>
           <div className="dashboard-stat-value">{card.value}</div>
         </div>
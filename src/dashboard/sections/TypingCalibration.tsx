@@ -0,0 +1,77 @@
+import React, { useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+/** Time saved assumes this until the user measures their own speed. */
+const DEFAULT_TYPING_WPM = 130;
+
+const SAMPLE =
+  'The quick brown fox jumps over the lazy dog while the team reviews the notes from this morning.';
+
+interface TypingCalibrationProps {
+  /** Calibrated speed in the settings draft; null uses the default */
+  wpm: number | null;
+  onChange: (wpm: number | null) => void;
+}
+
+/** Measures typing speed from a typed sample; the result goes into the settings draft. */
+const TypingCalibration: React.FC<TypingCalibrationProps> = ({ wpm, onChange }) => {
+  const [typed, setTyped] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const keystrokes = useRef<number[]>([]);
+
+  const reset = () => {
+    keystrokes.current = [];
+    setTyped('');
+  };
+
+  const measure = async () => {
+    try {
+      const measured = await invoke<number>('measure_typing_wpm', { keystrokesMs: keystrokes.current });
+      onChange(measured);
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+    reset();
+  };
+
+  return (
+    <div className="setup-field">
+      <label className="setup-label">Typing speed</label>
+      <p className="setup-muted">
+        {wpm === null
+          ? `Minutes saved assume ${DEFAULT_TYPING_WPM} WPM. Type the sentence below to use your own speed.`
+          : `Minutes saved use your measured ${Math.round(wpm)} WPM.`}
+      </p>
+      <p className="setup-muted">{SAMPLE}</p>
+      <input
+        className="setup-input"
+        value={typed}
+        placeholder="Type the sentence above"
+        onKeyDown={(event) => {
+          if (event.key.length === 1) keystrokes.current.push(event.timeStamp);
+        }}
+        onChange={(event) => setTyped(event.target.value)}
+        onPaste={(event) => event.preventDefault()}
+      />
+      {error && <p className="setup-muted">{error}</p>}
+      <div className="setup-usecase-grid">
+        <button
+          type="button"
+          className="setup-usecase-pill"
+          disabled={typed.trim().length === 0}
+          onClick={() => void measure()}
+        >
+          Measure
+        </button>
+        {wpm !== null && (
+          <button type="button" className="setup-usecase-pill" onClick={() => onChange(null)}>
+            Use {DEFAULT_TYPING_WPM} WPM
+          </button>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default TypingCalibration;
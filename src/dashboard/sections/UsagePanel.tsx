@@ -0,0 +1,71 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { MonthUsage, UsageStats } from '../types';
+
+function currentMonth(): string {
+  return new Date().toISOString().slice(0, 7);
+}
+
+function formatMinutes(secs: number): string {
+  return `${new Intl.NumberFormat(undefined, { maximumFractionDigits: 1 }).format(secs / 60)} min`;
+}
+
+function formatCost(usd: number): string {
+  return new Intl.NumberFormat(undefined, {
+    style: 'currency',
+    currency: 'USD',
+    minimumFractionDigits: 2,
+    maximumFractionDigits: usd < 1 ? 3 : 2,
+  }).format(usd);
+}
+
+const UsagePanel: React.FC = () => {
+  const [month, setMonth] = useState<MonthUsage | null>(null);
+
+  const load = useCallback(async () => {
+    const stats = await invoke<UsageStats>('get_usage_stats');
+    setMonth(stats.months.find((entry) => entry.month === currentMonth()) ?? null);
+  }, []);
+
+  useEffect(() => {
+    void load();
+  }, [load]);
+
+  const reset = async () => {
+    if (!window.confirm('Reset the usage counters for every provider?')) return;
+    await invoke('reset_usage_stats');
+    await load();
+  };
+
+  const cards = [
+    { label: 'Audio sent this month', value: formatMinutes(month?.audioSecs ?? 0) },
+    { label: 'Requests', value: String(month?.requests ?? 0) },
+    { label: 'Estimated cost', value: formatCost(month?.estimatedCost ?? 0) },
+  ];
+
+  return (
+    <div className="dashboard-history-panel">
+      <h2 className="dashboard-section-title">Provider usage</h2>
+      <div className="dashboard-stats-grid">
+        {cards.map((card) => (
+          <div key={card.label} className="dashboard-stat-card">
+            <div className="dashboard-stat-label">{card.label}</div>
+            <div className="dashboard-stat-value">{card.value}</div>
+          </div>
+        ))}
+      </div>
+      {month && month.providers.length > 1 && (
+        <p className="setup-muted">
+          {month.providers
+            .map((p) => `${p.provider}: ${formatMinutes(p.audioSecs)}, ${formatCost(p.estimatedCost)}`)
+            .join(' · ')}
+        </p>
+      )}
+      <button type="button" className="setup-primary-outline-btn" onClick={() => void reset()}>
+        Reset usage
+      </button>
+    </div>
+  );
+};
+
+export default UsagePanel;
@@ -4,6 +4,41 @@ export interface HistoryItem {
   timestamp: string;
   durationSeconds: number;
   wordCount: number;
+  /** Provider of the current text; missing on items saved before it was recorded */
+  provider?: string | null;
+  /** Archived recording, when the audio archive kept one; enables re-transcribing */
+  audioPath?: string | null;
+  /** `pendingTranscription` while the audio waits in the offline queue */
+  status: HistoryStatus;
+  /** Dictation that produced the item, for matching it against the logs */
+  requestId?: string | null;
+  /** Where the session's segments came from; missing on items saved before it was recorded */
+  sessionStats?: SessionStats | null;
+  /** Typing time the dictation saved at the current typing speed */
+  minutesSaved?: number;
+}
+
+export interface ProviderBreakdown {
+  provider: string;
+  segments: number;
+  avgConfidence: number;
+}
+
+export interface SessionStats {
+  providerBreakdown: ProviderBreakdown[];
+  retriedSegments: number;
+  gatedSegments: number;
+  failedSegments: number;
+}
+
+export type HistoryStatus = 'transcribed' | 'pendingTranscription';
+
+/** One page of `search_history`; `total` counts matches across all pages. */
+export interface HistorySearchResult {
+  items: HistoryItem[];
+  total: number;
+  page: number;
+  limit: number;
 }
 
 export interface DashboardStats {
@@ -11,17 +46,337 @@ export interface DashboardStats {
   totalWords: number;
   minutesSaved: number;
   wpm: number;
+  /** Typing speed `minutesSaved` assumes: the calibrated one, else 130 */
+  typingWpm: number;
+}
+
+export interface ProviderUsage {
+  provider: string;
+  audioSecs: number;
+  requests: number;
+  /** USD, from per-minute rates; 0 for providers without one */
+  estimatedCost: number;
+}
+
+export interface MonthUsage {
+  /** UTC month, `YYYY-MM` */
+  month: string;
+  audioSecs: number;
+  requests: number;
+  estimatedCost: number;
+  providers: ProviderUsage[];
+}
+
+/** `get_usage_stats` */
+export interface UsageStats {
+  providers: ProviderUsage[];
+  /** Newest first */
+  months: MonthUsage[];
+}
+
+export interface NotesSinkSettings {
+  enabled: boolean;
+  folder: string;
+  filenameTemplate: string;
+  entryTemplate: string;
+}
+
+export interface WebhookSettings {
+  enabled: boolean;
+  url: string;
+  payloadTemplate: string;
+}
+
+export interface IntegrationSettings {
+  notesSink: NotesSinkSettings;
+  webhook: WebhookSettings;
+}
+
+export type IntegrationKind = 'notesSink' | 'webhook';
+
+export interface IntegrationIssue {
+  kind: IntegrationKind;
+  field: string;
+  message: string;
+}
+
+export interface IntegrationPreview {
+  kind: IntegrationKind;
+  target: string;
+  body: string;
+}
+
+export interface ReplacementRule {
+  from: string;
+  to: string;
+}
+
+export interface PostProcessingSettings {
+  clarity: boolean;
+  removeFillers: boolean;
+  replacements: ReplacementRule[];
+}
+
+/** A recording is dropped only when all three levels are below their thresholds. */
+export interface SilenceGateSettings {
+  enabled: boolean;
+  rmsThreshold: number;
+  peakThreshold: number;
+  speechRatioThreshold: number;
+  /** Shorter recordings are dropped even with the gate off; 0 turns this off */
+  minDurationSecs: number;
+}
+
+export interface OfflineQueueSettings {
+  maxPendingItems: number;
+  maxPendingMb: number;
 }
 
+export interface AudioArchiveSettings {
+  /** Turning it off deletes the recordings already kept */
+  enabled: boolean;
+  keepLast: number;
+  maxMb: number;
+}
+
+export type PostProcessingStage = 'clarity' | 'replacements' | 'fillerRemoval' | 'dictationCommands';
+
+export interface PostProcessingPreview {
+  input: string;
+  stages: { stage: PostProcessingStage; enabled: boolean; text: string }[];
+  output: string;
+}
+
+/** `provider-circuit-opened`: the provider is skipped until the cooldown ends or `force_close_circuit` */
+export interface ProviderCircuitOpened {
+  provider: string;
+  cooldownSecs: number;
+  recentErrors: string[];
+}
+
+/** `provider-circuit-closed` */
+export interface ProviderCircuitClosed {
+  provider: string;
+}
+
+export type OptimizationMode = 'ClarityOnly' | 'AIOptimize';
+
+export interface PromptProfile {
+  id: string;
+  name: string;
+  goal: string;
+  returnFormat: string;
+  warnings: string[];
+  contextTemplate: string;
+  preferredProvider: string | null;
+  preferredModel: string | null;
+}
+
+export interface OptimizedPrompt {
+  text: string;
+  profileUsed: string;
+  /** `ClarityOnly` when the LLM providers all failed and the clarity pass was used instead */
+  mode: OptimizationMode;
+  provider?: string | null;
+  confidence: number;
+}
+
+/** Run in order after each dictation; a preset's list replaces this one while active */
+export type PostAction =
+  | { kind: 'clarity' }
+  | { kind: 'optimize'; profile: string }
+  | { kind: 'paste' }
+  | { kind: 'copyOnly' }
+  | { kind: 'saveToNotes' }
+  | { kind: 'webhook' }
+  | { kind: 'recordHistory' };
+
+export type HotkeyAction = 'stopRecording' | 'pasteLast';
+
 export interface DashboardData {
   userName: string;
   hasApiKey: boolean;
   apiKeyMasked?: string | null;
+  elevenlabsApiKeyMasked?: string | null;
+  openrouterApiKeyMasked?: string | null;
+  keyStatus: KeyStatus;
   inputDeviceName?: string | null;
   hotkey: string;
+  /** Extra shortcuts; an action without an entry is unbound */
+  hotkeys: Partial<Record<HotkeyAction, string>>;
   language: 'pt' | 'en' | 'auto';
+  /** The manual toggle; offline mode can also be detected from network failures */
+  offlineMode: boolean;
+  offlineQueue: OfflineQueueSettings;
+  audioArchive: AudioArchiveSettings;
+  pendingTranscriptions: number;
+  pillFocusable: boolean;
+  /** The pill moves to the monitor under the cursor when a recording starts */
+  pillFollowsActiveMonitor: boolean;
+  restoreClipboard: boolean;
+  clipboardRestoreDelayMs: number;
+  liveTranscription: boolean;
+  /** Steady background noise is subtracted before upload */
+  noiseSuppression: boolean;
+  /** Recording state changes are read out by the screen reader */
+  a11yAnnouncements: boolean;
+  /** The first seconds of speech go out early to open the connection and pin the language */
+  sessionWarmup: boolean;
+  /** Scale of the level meter; the recording is not amplified */
+  inputGain: number;
+  /** Audio from before the shortcut each recording starts with; 0 keeps the mic closed */
+  preRollMs: number;
+  /** Recordings stop and transcribe after this many seconds; 0 disables */
+  maxRecordingSecs: number;
+  silenceGate: SilenceGateSettings;
+  integrations: IntegrationSettings;
+  postActions: PostAction[];
+  postProcessing: PostProcessingSettings;
+  /** Spoken "new line", "comma", "vírgula"... become formatting in the final text */
+  dictationCommands: boolean;
+  /** Profile `optimize_transcript` uses when none is passed */
+  promptProfileId: string;
+  optimizationMode: OptimizationMode;
   stats: DashboardStats;
+  /** Most recent page only; use `search_history` for the rest. */
   history: HistoryItem[];
+  historyTotal: number;
+  /** 0 keeps everything */
+  historyLimit: number;
+  /** Folder that gets a JSON copy of every history item; set to '' to stop mirroring */
+  mirrorHistoryToFolder?: string | null;
+  /** Measured with `measure_typing_wpm`; null assumes 130 WPM */
+  typingWpm?: number | null;
+  historyTrashCount: number;
   githubUrl: string;
   appVersion: string;
 }
+
+export type ApiKeySlot = 'groq' | 'elevenlabs' | 'openrouter';
+
+/** What a settings save does to a stored key; keys are never sent back unmasked. */
+export type KeyUpdate = 'unchanged' | 'clear' | { set: string };
+
+export interface DestructiveScope {
+  historyItems: number;
+  oldest?: string | null;
+  newest?: string | null;
+}
+
+export interface DestructiveActionToken {
+  token: string;
+  kind: 'clearHistory' | 'revealApiKey';
+  expiresInSecs: number;
+  scope: DestructiveScope;
+}
+
+export interface WordTiming {
+  word: string;
+  startSecs: number;
+  endSecs: number;
+}
+
+export interface SegmentTiming {
+  text: string;
+  startSecs: number;
+  endSecs: number;
+}
+
+/** Result of `transcribe_audio`; timings are empty unless `providers.groq.timestamps` is on. */
+export interface Transcript {
+  text: string;
+  confidence: number;
+  language: string | null;
+  durationSecs: number;
+  provider: string;
+  words: WordTiming[];
+  segments: SegmentTiming[];
+}
+
+/** Per-segment energy strip from `get_segment_diagnostics`; one byte per `frameMs`. */
+export interface SegmentDiagnostics {
+  segmentId: string;
+  sequenceNumber: number;
+  durationSecs: number;
+  gated: boolean;
+  frameMs: number;
+  energy: number[];
+}
+
+/** Result of the background Groq key check; `unknown` until a probe gets an answer. */
+export type KeyStatus = 'valid' | 'invalid' | 'unknown';
+
+/** Payload of `api-key-invalid`: the saved key was rejected (revoked or expired). */
+export interface ApiKeyInvalid {
+  provider: string;
+  message: string;
+}
+
+/** Payload of `language-mismatch-suggestion`: most recent dictations were detected in another language. */
+export interface LanguageMismatchSuggestion {
+  configured: 'pt' | 'en';
+  /** `auto` when the detected language is neither Portuguese nor English */
+  suggested: 'pt' | 'en' | 'auto';
+  mismatches: number;
+  window: number;
+}
+
+/** Payload of `settings-changed`: settings were saved from the tray or another window. */
+export interface SettingsChanged {
+  language: string;
+}
+
+/** History items added, removed and changed since the previous `dashboard:history-updated`. */
+export interface ChangeSummary {
+  added: number;
+  removed: number;
+  updated: number;
+}
+
+/** Payload of `dashboard:history-updated`; bursts of writes arrive as one event, at most every 500 ms. */
+export interface HistoryUpdated {
+  changeSummary: ChangeSummary;
+}
+
+/** Payload of `pending-transcription-completed`: a recording queued offline was transcribed. */
+export interface PendingTranscriptionCompleted {
+  id: string;
+  text: string;
+  provider: string;
+}
+
+/** Payload of `env-imported`: settings picked up from a legacy .env on first run. */
+export interface EnvImported {
+  keys: string[];
+}
+
+/** Payload of `startup-health` and `get_startup_health`. */
+export interface StartupHealth {
+  storageAvailable: boolean;
+  storageMessage?: string | null;
+  hotkeyMessage?: string | null;
+  /** Set when the previous run panicked; `open_crash_folder` shows the reports */
+  crashMessage?: string | null;
+}
+
+/** A history item trimmed for the tray's recent dictations popover (`get_mini_history`). */
+export interface MiniHistoryItem {
+  id: string;
+  snippet: string;
+  /** "just now", "5 min ago", "yesterday"... */
+  relativeTime: string;
+  wordCount: number;
+  /** Privacy mode is on; `snippet` is a placeholder. */
+  hidden: boolean;
+}
+
+/** Payload of `device-changed`: a new input device selection took effect; a selection made during a recording waits for it to stop. */
+export interface DeviceChanged {
+  /** `null` is the system default. */
+  selected: string | null;
+  /** The device capture will open, after any fallback. */
+  deviceName: string;
+  sampleRate: number;
+  channels: number;
+  wasQueued: boolean;
+}
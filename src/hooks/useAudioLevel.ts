@@ -1,6 +1,20 @@
 import { useState, useEffect, useRef } from 'react';
 import { listen } from '@tauri-apps/api/event';
 
+/** Payload of 'audio-level': smoothed RMS, peak since the last event, sustained clipping. */
+export interface AudioLevelPayload {
+  rms: number;
+  peak: number;
+  clipping: boolean;
+}
+
+export type MicGuidance = 'loud' | 'quiet' | 'good';
+
+/** Peak below this for the whole window reads as a microphone that is too quiet. */
+const QUIET_PEAK = 0.08;
+/** How long a verdict looks back, and how long "too loud" stays up after clipping. */
+const GUIDANCE_WINDOW_MS = 2000;
+
 /**
  * Listens for 'audio-level' events from Tauri backend.
  * Returns a smoothed audio level (0.0 - 1.0).
@@ -24,9 +38,9 @@ export function useAudioLevel(active: boolean): number {
     // Listen for real audio level events from backend
     const setupListener = async () => {
       try {
-        const unlistenFn = await listen<number>('audio-level', (event) => {
+        const unlistenFn = await listen<AudioLevelPayload>('audio-level', (event) => {
           hasRealData = true;
-          const raw = Math.max(0, Math.min(1, event.payload));
+          const raw = Math.max(0, Math.min(1, event.payload.rms));
           // Exponential smoothing
           smoothedRef.current += (raw - smoothedRef.current) * 0.35;
           setLevel(smoothedRef.current);
@@ -65,3 +79,59 @@ export function useAudioLevel(active: boolean): number {
 
   return level;
 }
+
+/**
+ * Mic check verdict over the last couple of seconds of 'audio-level' events: 'loud' while
+ * the input clips, 'quiet' when even the peaks stay low, otherwise 'good'. Null until
+ * there is a window's worth of data.
+ */
+export function useMicGuidance(active: boolean): MicGuidance | null {
+  const [guidance, setGuidance] = useState<MicGuidance | null>(null);
+
+  useEffect(() => {
+    setGuidance(null);
+    if (!active) {
+      return;
+    }
+
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+    const startedAt = Date.now();
+    let lastClipAt = -Infinity;
+    let peaks: { at: number; peak: number }[] = [];
+
+    listen<AudioLevelPayload>('audio-level', (event) => {
+      const now = Date.now();
+      if (event.payload.clipping) {
+        lastClipAt = now;
+      }
+      peaks.push({ at: now, peak: event.payload.peak });
+      peaks = peaks.filter((sample) => now - sample.at <= GUIDANCE_WINDOW_MS);
+
+      if (now - lastClipAt <= GUIDANCE_WINDOW_MS) {
+        setGuidance('loud');
+      } else if (now - startedAt < GUIDANCE_WINDOW_MS) {
+        setGuidance(null);
+      } else if (Math.max(...peaks.map((sample) => sample.peak)) < QUIET_PEAK) {
+        setGuidance('quiet');
+      } else {
+        setGuidance('good');
+      }
+    })
+      .then((unlistenFn) => {
+        if (cancelled) {
+          unlistenFn();
+        } else {
+          unlisten = unlistenFn;
+        }
+      })
+      .catch((err) => console.warn('audio-level listener failed:', err));
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [active]);
+
+  return guidance;
+}
@@ -1,11 +1,12 @@
 ﻿import { useState, useEffect, useCallback, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { writeText } from '@tauri-apps/plugin-clipboard-manager';
 import type { ToastPayload } from '../types/toast';
 
 type BarState = 'idle' | 'recording' | 'processing';
 const MAX_SEGMENT_SECONDS = 59;
+/** Provider on the transcript of a recording below the minimum duration. */
+const TOO_SHORT_PROVIDER = 'TooShort';
 
 interface UseRecordingOptions {
   onToast?: (toast: ToastPayload) => void;
@@ -13,28 +14,130 @@ interface UseRecordingOptions {
 
 interface AudioPayload {
   samples: number[];
-  sample_rate: number;
+  sampleRate: number;
   channels: number;
 }
 
+interface SegmentTiming {
+  durationSecs: number;
+  wordCount: number;
+}
+
+interface ProviderBreakdown {
+  provider: string;
+  segments: number;
+  avgConfidence: number;
+}
+
 interface StitchedResult {
-  full_text: string;
-  total_duration_secs?: number;
+  fullText: string;
+  totalDurationSecs?: number;
+  segments?: SegmentTiming[];
+  providerBreakdown?: ProviderBreakdown[];
+  retriedSegments?: number;
+  gatedSegments?: number;
+  failedSegments?: number;
 }
 
 interface SegmentResult {
-  transcript: {
+  status: 'recording' | 'transcribing' | 'completed' | 'failed';
+  /** Absent while the segment is still being transcribed */
+  transcript?: {
     provider?: string;
-  };
+  } | null;
+}
+
+interface SessionProgress {
+  failedSegmentIds: string[];
+  transcribingSegmentIds: string[];
+}
+
+interface SegmentProgress {
+  segmentId: string;
+  sequenceNumber: number;
+  status: 'recording' | 'transcribing' | 'completed' | 'failed';
+  provider?: string | null;
+  error?: string | null;
+}
+
+interface StartRecordingError {
+  code: string;
+  /** Catalogue code, e.g. `REC_DEVICE_BUSY` */
+  errorCode?: string;
+  message: string;
+  device?: string | null;
+}
+
+/** Progress of a local engine (whisper.cpp) on the recording being transcribed */
+interface WhisperProgress {
+  provider: string;
+  percent: number;
+  positionSecs?: number | null;
+}
+
+interface TranscriptPartial {
+  text: string;
+  windows: number;
+  requestId: string;
+}
+
+interface RecordingTooShort {
+  durationSecs: number;
+  minDurationSecs: number;
+  requestId?: string | null;
+}
+
+interface RecordingAutoStopped {
+  durationSecs: number;
+  maxRecordingSecs: number;
+  requestId?: string | null;
+}
+
+interface MicPossiblyExclusive {
+  device: string;
+}
+
+interface RecordingError {
+  code: string;
+  message: string;
+  device?: string | null;
 }
 
 interface PasteAttempt {
+  /** The text reached the target, pasted or typed */
   pasted: boolean;
+  method: 'clipboard_paste' | 'typed' | 'none';
   reason?: string | null;
+  errorCode?: string | null;
+  requestId?: string | null;
+}
+
+type PostAction =
+  | { kind: 'clarity' }
+  | { kind: 'optimize'; profile: string }
+  | { kind: 'paste' }
+  | { kind: 'copyOnly' }
+  | { kind: 'saveToNotes' }
+  | { kind: 'webhook' }
+  | { kind: 'recordHistory' };
+
+interface ActionResult {
+  action: PostAction;
+  ok: boolean;
+  detail?: string | null;
+  error?: string | null;
+  /** Why the action didn't run, e.g. privacy mode */
+  skipped?: string | null;
+}
+
+interface ProcessedResult {
+  text: string;
+  actions: ActionResult[];
+  paste?: PasteAttempt | null;
 }
 
 function splitAudioIntoChunks(audio: AudioPayload): AudioPayload[] {
-  const sampleRate = Math.max(1, audio.sample_rate || 16000);
+  const sampleRate = Math.max(1, audio.sampleRate || 16000);
   const channels = Math.max(1, audio.channels || 1);
   const samplesPerSecond = sampleRate * channels;
   const maxSamplesPerChunk = samplesPerSecond * MAX_SEGMENT_SECONDS;
@@ -48,7 +151,7 @@ function splitAudioIntoChunks(audio: AudioPayload): AudioPayload[] {
     const end = Math.min(start + maxSamplesPerChunk, audio.samples.length);
     chunks.push({
       samples: audio.samples.slice(start, end),
-      sample_rate: sampleRate,
+      sampleRate,
       channels,
     });
   }
@@ -57,15 +160,15 @@ function splitAudioIntoChunks(audio: AudioPayload): AudioPayload[] {
 }
 
 function estimateDurationSeconds(audio: AudioPayload): number {
-  if (!audio.samples?.length || !audio.sample_rate || !audio.channels) {
+  if (!audio.samples?.length || !audio.sampleRate || !audio.channels) {
     return 0;
   }
-  const samplesPerSecond = Math.max(1, audio.sample_rate * audio.channels);
+  const samplesPerSecond = Math.max(1, audio.sampleRate * audio.channels);
   return audio.samples.length / samplesPerSecond;
 }
 
-function countWords(text: string): number {
-  return text.trim().split(/\s+/).filter(Boolean).length;
+function isStartRecordingError(error: unknown): error is StartRecordingError {
+  return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
 }
 
 function formatInvokeError(error: unknown): string {
@@ -75,6 +178,9 @@ function formatInvokeError(error: unknown): string {
   if (typeof error === 'string') {
     return error;
   }
+  if (isStartRecordingError(error)) {
+    return error.errorCode ? `[${error.errorCode}] ${error.message}` : error.message;
+  }
   try {
     return JSON.stringify(error);
   } catch {
@@ -82,9 +188,31 @@ function formatInvokeError(error: unknown): string {
   }
 }
 
+/** Hand the recording to the offline queue; the backend refuses when providers are reachable. */
+async function queueForLater(audio: AudioPayload): Promise<boolean> {
+  try {
+    await invoke('queue_recording_for_later', { audio });
+    return true;
+  } catch (error) {
+    console.debug('Recording not queued:', error);
+    return false;
+  }
+}
+
+/** Catalogue code of a `[CODE] message` error string from the backend. */
+function errorCodeOf(message: string): string | null {
+  return /^\[([A-Z_]+)\] /.exec(message)?.[1] ?? null;
+}
+
 function mapRecordingErrorToToast(message: string): ToastPayload {
+  const code = errorCodeOf(message);
   const normalized = message.toLowerCase();
-  if (normalized.includes('groq api key missing') || normalized.includes('authentication failed')) {
+  if (
+    code === 'STT_NO_KEY' ||
+    code === 'STT_AUTH' ||
+    normalized.includes('groq api key missing') ||
+    normalized.includes('authentication failed')
+  ) {
     return {
       type: 'error',
       title: 'Invalid Groq API key',
@@ -92,7 +220,7 @@ function mapRecordingErrorToToast(message: string): ToastPayload {
       durationMs: 2800,
     };
   }
-  if (normalized.includes('rate limit')) {
+  if (code === 'STT_RATE_LIMIT' || normalized.includes('rate limit')) {
     return {
       type: 'error',
       title: 'Groq rate limit reached',
@@ -100,7 +228,23 @@ function mapRecordingErrorToToast(message: string): ToastPayload {
       durationMs: 2600,
     };
   }
-  if (normalized.includes('timeout')) {
+  if (code === 'STT_MODEL_NOT_FOUND') {
+    return {
+      type: 'error',
+      title: 'Transcription model unavailable',
+      subtitle: 'Choose another model in Settings',
+      durationMs: 2800,
+    };
+  }
+  if (code === 'STT_AUDIO_TOO_LARGE') {
+    return {
+      type: 'error',
+      title: 'Recording too large to upload',
+      subtitle: 'Record in shorter parts',
+      durationMs: 2600,
+    };
+  }
+  if (code === 'STT_TIMEOUT' || normalized.includes('timeout')) {
     return {
       type: 'error',
       title: 'Groq request timed out',
@@ -118,6 +262,8 @@ function mapRecordingErrorToToast(message: string): ToastPayload {
 export function useRecording({ onToast }: UseRecordingOptions = {}) {
   const [state, setState] = useState<BarState>('idle');
   const [mode, setMode] = useState<'ai' | 'clarity'>('ai');
+  const [partialText, setPartialText] = useState('');
+  const [transcribePercent, setTranscribePercent] = useState<number | null>(null);
   const stateRef = useRef<BarState>('idle');
   const transitionLockRef = useRef(false);
   const listenerBoundRef = useRef(false);
@@ -125,6 +271,12 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
   useEffect(() => {
     // keep latest state in ref so global shortcut callbacks never use stale values
     stateRef.current = state;
+    if (state === 'idle') {
+      setPartialText('');
+    }
+    if (state !== 'processing') {
+      setTranscribePercent(null);
+    }
   }, [state]);
 
   const startRecording = useCallback(async () => {
@@ -136,14 +288,30 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
       setState('recording');
     } catch (err) {
       console.error('Start recording failed:', err);
-      const message = formatInvokeError(err);
-      if (message.toLowerCase().includes('groq api key missing')) {
+      const code = isStartRecordingError(err) ? err.code : null;
+      if (code === 'already_transitioning') {
+        // The shortcut or another window is starting or stopping a recording right now.
+      } else if (code === 'groq_key_missing') {
         onToast?.({
           type: 'error',
           title: 'Invalid Groq API key',
           subtitle: 'Open Setup/Settings and configure a valid key',
           durationMs: 2800,
         });
+      } else if (code === 'key_invalid') {
+        onToast?.({
+          type: 'error',
+          title: 'Groq API key was rejected',
+          subtitle: 'It may have been revoked or expired. Update it in Settings',
+          durationMs: 3600,
+        });
+      } else if (isStartRecordingError(err) && err.code === 'device_busy') {
+        onToast?.({
+          type: 'error',
+          title: `${err.device ?? 'Microphone'} is in use`,
+          subtitle: 'Close apps like Discord or OBS, or pick another input device',
+          durationMs: 3600,
+        });
       } else {
         onToast?.({
           type: 'error',
@@ -162,8 +330,10 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
     if (stateRef.current !== 'recording' || transitionLockRef.current) return;
     transitionLockRef.current = true;
     setState('processing');
+    let recorded: AudioPayload | null = null;
     try {
       const audio = await invoke<AudioPayload>('stop_recording');
+      recorded = audio;
       if (!audio?.samples?.length) {
         onToast?.({
           type: 'error',
@@ -173,15 +343,29 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
         return;
       }
 
+      // Segments are queued and transcribed in parallel; failures show up once they settle.
       const chunks = splitAudioIntoChunks(audio);
       for (const [index, chunk] of chunks.entries()) {
-        const segment = await invoke<SegmentResult>('add_audio_segment', { audio: chunk });
-        const provider = segment.transcript?.provider ?? 'unknown';
-        console.debug(`Segment ${index + 1} provider:`, provider);
+        try {
+          const segment = await invoke<SegmentResult>('add_audio_segment', { audio: chunk });
+          console.debug(`Segment ${index + 1}:`, segment.status);
+          if (segment.transcript?.provider === TOO_SHORT_PROVIDER) {
+            // The backend ended the session and reports it through recording-too-short.
+            return;
+          }
+        } catch (error) {
+          console.warn(`Segment ${index + 1} was not queued:`, error);
+        }
+      }
+
+      // The session keeps a failed segment's audio; retry it once the rest are done.
+      const progress = await invoke<SessionProgress>('wait_for_session_segments');
+      for (const segmentId of progress.failedSegmentIds) {
+        await invoke<SegmentResult>('retry_segment', { segmentId });
       }
 
       const result = await invoke<StitchedResult>('finalize_recording_session');
-      const finalText = result.full_text?.trim() ?? '';
+      const finalText = result.fullText?.trim() ?? '';
       console.log('TRANSCRIPT:', finalText);
       if (!finalText) {
         onToast?.({
@@ -193,35 +377,43 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
       }
 
       const durationSeconds =
-        result.total_duration_secs && result.total_duration_secs > 0.05
-          ? result.total_duration_secs
+        result.totalDurationSecs && result.totalDurationSecs > 0.05
+          ? result.totalDurationSecs
           : estimateDurationSeconds(audio);
-      const wordCount = countWords(finalText);
-      try {
-        await invoke('record_transcription_history', {
-          payload: {
-            text: finalText,
-            durationSeconds,
-            wordCount,
-            timestamp: new Date().toISOString(),
-          },
-        });
-      } catch (historyError) {
-        console.warn('History record failed:', historyError);
+      const processed = await invoke<ProcessedResult>('run_post_actions', {
+        text: finalText,
+        durationSeconds,
+        sessionStats: {
+          providerBreakdown: result.providerBreakdown ?? [],
+          retriedSegments: result.retriedSegments ?? 0,
+          gatedSegments: result.gatedSegments ?? 0,
+          failedSegments: result.failedSegments ?? 0,
+        },
+      });
+      for (const outcome of processed.actions) {
+        if (!outcome.ok) {
+          console.warn(`Post action ${outcome.action.kind} failed:`, outcome.error);
+        }
       }
 
-      await writeText(finalText);
-      const pasteResult = await invoke<PasteAttempt>('paste_text');
-
-      if (pasteResult.pasted) {
+      const pasteResult = processed.paste;
+      const copied = processed.actions.some(
+        (outcome) => outcome.action.kind === 'copyOnly' && outcome.ok,
+      );
+      if (pasteResult?.pasted) {
         onToast?.({
           type: 'pasted',
-          title: 'Pasted',
+          title: pasteResult.method === 'typed' ? 'Typed' : 'Pasted',
           durationMs: 1800,
         });
-      } else {
-        if (pasteResult.reason) {
-          console.debug('Auto-paste fallback:', pasteResult.reason);
+      } else if (pasteResult || copied) {
+        if (pasteResult?.reason) {
+          console.debug(
+            'Auto-paste fallback:',
+            pasteResult.errorCode,
+            pasteResult.reason,
+            pasteResult.requestId,
+          );
         }
         onToast?.({
           type: 'copied',
@@ -231,6 +423,15 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
       }
     } catch (err) {
       console.error('Stop/transcribe failed:', err);
+      if (recorded && (await queueForLater(recorded))) {
+        onToast?.({
+          type: 'copied',
+          title: 'Saved for later',
+          subtitle: 'It will be transcribed when you are back online',
+          durationMs: 2800,
+        });
+        return;
+      }
       const message = formatInvokeError(err);
       onToast?.(mapRecordingErrorToToast(message));
     } finally {
@@ -242,13 +443,221 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
   const cancel = useCallback(async () => {
     try {
       if (stateRef.current === 'recording') {
-        await invoke('stop_recording').catch(() => {});
+        await invoke('cancel_recording').catch(() => {});
       }
     } catch (_) {}
     transitionLockRef.current = false;
     setState('idle');
   }, []);
 
+  useEffect(() => {
+    const onKeyDown = (event: KeyboardEvent) => {
+      if (event.key === 'Escape' && stateRef.current === 'recording' && !transitionLockRef.current) {
+        event.preventDefault();
+        void cancel();
+      }
+    };
+    window.addEventListener('keydown', onKeyDown);
+    return () => window.removeEventListener('keydown', onKeyDown);
+  }, [cancel]);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<MicPossiblyExclusive>('mic-possibly-exclusive', (event) => {
+      onToast?.({
+        type: 'error',
+        title: `No sound from ${event.payload.device}`,
+        subtitle: 'Another app may hold the mic exclusively. Close it or pick another device',
+        durationMs: 4000,
+      });
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('mic-possibly-exclusive listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, [onToast]);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<RecordingError>('recording-error', (event) => {
+      console.warn('Recording stopped by the backend:', event.payload.code, event.payload.message);
+      onToast?.({
+        type: 'error',
+        title: `${event.payload.device ?? 'Microphone'} disconnected`,
+        subtitle: 'Recording stopped. Reconnect it or pick another input device',
+        durationMs: 3600,
+      });
+      // Capture is already stopped; this drops a session with nothing transcribed yet.
+      void cancel();
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('recording-error listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, [onToast, cancel]);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<RecordingAutoStopped>('recording-auto-stopped', (event) => {
+      const limitSecs = event.payload.maxRecordingSecs;
+      const limit = limitSecs % 60 === 0 ? `${limitSecs / 60} min` : `${limitSecs}s`;
+      onToast?.({
+        type: 'copied',
+        title: `Recording stopped after ${limit}`,
+        subtitle: 'Transcribing what was captured',
+        durationMs: 3000,
+      });
+      // Capture is already stopped; stop_recording hands back the kept audio.
+      void stopRecording();
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('recording-auto-stopped listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, [onToast, stopRecording]);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<RecordingTooShort>('recording-too-short', (event) => {
+      onToast?.({
+        type: 'copied',
+        title: 'Too short to transcribe',
+        subtitle: `Hold for at least ${event.payload.minDurationSecs.toFixed(1)}s`,
+        durationMs: 1600,
+      });
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('recording-too-short listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, [onToast]);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<SegmentProgress>('session:segment-status', (event) => {
+      const { sequenceNumber, status, provider, error } = event.payload;
+      if (status === 'failed') {
+        console.warn(`Segment ${sequenceNumber} failed:`, error);
+      } else {
+        console.debug(`Segment ${sequenceNumber} ${status}`, provider ?? '');
+      }
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('session:segment-status listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, []);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<WhisperProgress>('whisper-progress', (event) => {
+      if (stateRef.current === 'processing') {
+        setTranscribePercent(event.payload.percent);
+      }
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('whisper-progress listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, []);
+
+  useEffect(() => {
+    let disposed = false;
+    let unlistenFn: (() => void) | null = null;
+    void listen<TranscriptPartial>('transcript-partial', (event) => {
+      if (stateRef.current === 'recording') {
+        setPartialText(event.payload.text);
+      }
+    })
+      .then((unlisten) => {
+        if (disposed) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      })
+      .catch((err) => {
+        console.warn('transcript-partial listener failed:', err);
+      });
+
+    return () => {
+      disposed = true;
+      unlistenFn?.();
+    };
+  }, []);
+
   const handleToggleFromHotkey = useCallback(() => {
     if (transitionLockRef.current) return;
 
@@ -262,7 +671,7 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
   const closeApp = useCallback(async () => {
     try {
       if (stateRef.current === 'recording') {
-        await invoke('stop_recording').catch(() => {});
+        await invoke('cancel_recording').catch(() => {});
       }
     } catch (_) {}
     transitionLockRef.current = false;
@@ -275,31 +684,46 @@ export function useRecording({ onToast }: UseRecordingOptions = {}) {
     listenerBoundRef.current = true;
 
     let disposed = false;
-    let unlistenFn: (() => void) | null = null;
-    void listen('toggle-recording', () => {
-      handleToggleFromHotkey();
-    })
-      .then((unlisten) => {
-        if (disposed) {
-          unlisten();
-          return;
-        }
-        unlistenFn = unlisten;
-      })
-      .catch((err) => {
-        console.warn('toggle-recording listener failed:', err);
-      });
+    const unlistenFns: Array<() => void> = [];
+    const bind = (event: string, handler: () => void) => {
+      void listen(event, handler)
+        .then((unlisten) => {
+          if (disposed) {
+            unlisten();
+            return;
+          }
+          unlistenFns.push(unlisten);
+        })
+        .catch((err) => {
+          console.warn(`${event} listener failed:`, err);
+        });
+    };
+    bind('toggle-recording', handleToggleFromHotkey);
+    // The dedicated stop shortcut never starts a recording.
+    bind('stop-recording', () => {
+      if (!transitionLockRef.current && stateRef.current === 'recording') {
+        void stopRecording();
+      }
+    });
 
     return () => {
       disposed = true;
       listenerBoundRef.current = false;
-      if (unlistenFn) {
-        unlistenFn();
-      }
+      unlistenFns.forEach((unlisten) => unlisten());
     };
-  }, [handleToggleFromHotkey]);
+  }, [handleToggleFromHotkey, stopRecording]);
 
-  return { state, mode, setMode, startRecording, stopRecording, cancel, closeApp };
+  return {
+    state,
+    mode,
+    setMode,
+    partialText,
+    transcribePercent,
+    startRecording,
+    stopRecording,
+    cancel,
+    closeApp,
+  };
 }
 
 
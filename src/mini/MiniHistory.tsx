@@ -0,0 +1,61 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import type { MiniHistoryItem } from '../dashboard/types';
+
+const ITEM_LIMIT = 5;
+
+const MiniHistory: React.FC = () => {
+  const [items, setItems] = useState<MiniHistoryItem[]>([]);
+  const [copied, setCopied] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    setItems(await invoke<MiniHistoryItem[]>('get_mini_history', { limit: ITEM_LIMIT }));
+  }, []);
+
+  // The window is hidden, not closed, between uses; refresh whenever it comes back.
+  useEffect(() => {
+    void load();
+    const unlisten = getCurrentWebviewWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) {
+        setCopied(null);
+        void load();
+      }
+    });
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, [load]);
+
+  const copy = async (id: string) => {
+    await invoke('copy_history_item', { id });
+    setCopied(id);
+  };
+
+  return (
+    <div className="mini-history">
+      <header className="mini-history-header">Recent dictations</header>
+      {items.length === 0 ? (
+        <p className="mini-history-empty">Nothing dictated yet.</p>
+      ) : (
+        <ul className="mini-history-list">
+          {items.map((item) => (
+            <li key={item.id} className="mini-history-item">
+              <p className={item.hidden ? 'mini-history-snippet hidden' : 'mini-history-snippet'}>{item.snippet}</p>
+              <div className="mini-history-meta">
+                <span>
+                  {item.relativeTime} · {item.wordCount} words
+                </span>
+                <button type="button" onClick={() => void copy(item.id)}>
+                  {copied === item.id ? 'Copied' : 'Copy'}
+                </button>
+              </div>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+};
+
+export default MiniHistory;
@@ -1,5 +1,6 @@
 import React, { useCallback, useEffect, useMemo, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import SetupComplete from './SetupComplete';
@@ -8,7 +9,12 @@ import Step2ApiKey from './steps/Step2ApiKey';
 import Step3Hotkey from './steps/Step3Hotkey';
 import Step4MicTest from './steps/Step4MicTest';
 import type {
+  AccessibilityPermission,
+  CaptureInfo,
   CompleteSetupPayload,
+  HotkeyCaptured,
+  InputDevicesChanged,
+  RecordingError,
   SaveSetupPartialPayload,
   SetupState,
   UseCase,
@@ -46,9 +52,11 @@ const SetupWizard: React.FC = () => {
   const [language, setLanguage] = useState<'pt' | 'en' | 'auto'>('pt');
   const [capturing, setCapturing] = useState(false);
   const [hotkeyWarning, setHotkeyWarning] = useState<string | null>(null);
+  const [accessibilityMissing, setAccessibilityMissing] = useState(false);
   const [micAvailable, setMicAvailable] = useState(false);
   const [micName, setMicName] = useState('');
   const [micMonitoring, setMicMonitoring] = useState(false);
+  const [captureInfo, setCaptureInfo] = useState<CaptureInfo | null>(null);
   const [inputDevices, setInputDevices] = useState<string[]>([]);
   const [selectedInputDevice, setSelectedInputDevice] = useState<string>('');
   const [refreshingDevices, setRefreshingDevices] = useState(false);
@@ -122,6 +130,86 @@ const SetupWizard: React.FC = () => {
     }
   }, []);
 
+  const applyHotkey = useCallback((nextHotkey: string) => {
+    setHotkey(nextHotkey);
+    setCapturing(false);
+    if (nextHotkey === 'CommandOrControl+Space') {
+      setHotkeyWarning('Ctrl+Space can conflict with IME on some systems.');
+    } else {
+      setHotkeyWarning(null);
+    }
+  }, []);
+
+  useEffect(() => {
+    let disposed = false;
+    const unlisteners: Array<() => void> = [];
+    const track = (pending: Promise<() => void>) => {
+      void pending
+        .then((unlisten) => {
+          if (disposed) {
+            unlisten();
+            return;
+          }
+          unlisteners.push(unlisten);
+        })
+        .catch((err) => {
+          console.warn('input device listener failed:', err);
+        });
+    };
+
+    track(
+      listen<InputDevicesChanged>('input-devices-changed', (event) => {
+        setInputDevices(event.payload.devices);
+        if (event.payload.selected && !event.payload.selectedMissing) {
+          setSelectedInputDevice(event.payload.selected);
+        }
+      }),
+    );
+    track(
+      listen<RecordingError>('recording-error', (event) => {
+        setMicMonitoring(false);
+        setErrorMessage(`Microphone test stopped: ${event.payload.message}`);
+      }),
+    );
+    track(
+      listen<HotkeyCaptured>('hotkey-captured', (event) => {
+        if (event.payload.accelerator) {
+          applyHotkey(event.payload.accelerator);
+        } else {
+          setCapturing(false);
+        }
+      }),
+    );
+
+    return () => {
+      disposed = true;
+      unlisteners.forEach((unlisten) => unlisten());
+      void invoke('cancel_hotkey_capture');
+    };
+  }, [applyHotkey]);
+
+  // Pasting and shortcut capture need Accessibility on macOS; ask before the first recording.
+  useEffect(() => {
+    if (step !== 3) return;
+    invoke<AccessibilityPermission>('check_accessibility_permission')
+      .then((permission) => setAccessibilityMissing(permission.required && !permission.granted))
+      .catch((error) => console.warn('Accessibility check failed:', error));
+  }, [step]);
+
+  const grantAccessibility = useCallback(async () => {
+    try {
+      const permission = await invoke<AccessibilityPermission>('check_accessibility_permission', {
+        prompt: true,
+      });
+      setAccessibilityMissing(permission.required && !permission.granted);
+      if (permission.required && !permission.granted) {
+        await invoke('open_accessibility_settings');
+      }
+    } catch (error) {
+      setErrorMessage(`Failed to open Accessibility settings: ${String(error)}`);
+    }
+  }, []);
+
   useEffect(() => {
     if (step !== 4) {
       void invoke('stop_mic_monitor');
@@ -147,8 +235,9 @@ const SetupWizard: React.FC = () => {
       if (!micInfo.available || cancelled) return;
 
       try {
-        await invoke('start_mic_monitor');
+        const info = await invoke<CaptureInfo>('start_mic_monitor');
         if (!cancelled) {
+          setCaptureInfo(info);
           setMicMonitoring(true);
         }
       } catch (error) {
@@ -211,14 +300,21 @@ const SetupWizard: React.FC = () => {
       return;
     }
 
-    const nextHotkey = parts.join('+');
-    setHotkey(nextHotkey);
+    void invoke('cancel_hotkey_capture');
+    applyHotkey(parts.join('+'));
+  }, [applyHotkey]);
+
+  const startHotkeyCapture = useCallback(() => {
+    setCapturing(true);
+    // Keys pressed in this window are still captured when the backend listener is unavailable.
+    invoke('begin_hotkey_capture').catch((error) => {
+      console.warn('Backend hotkey capture unavailable:', error);
+    });
+  }, []);
+
+  const stopHotkeyCapture = useCallback(() => {
     setCapturing(false);
-    if (nextHotkey === 'CommandOrControl+Space') {
-      setHotkeyWarning('Ctrl+Space can conflict with IME on some systems.');
-    } else {
-      setHotkeyWarning(null);
-    }
+    void invoke('cancel_hotkey_capture');
   }, []);
 
   const goNext = useCallback(async () => {
@@ -370,8 +466,10 @@ const SetupWizard: React.FC = () => {
             hotkey={hotkey}
             capturing={capturing}
             warning={hotkeyWarning}
-            onStartCapture={() => setCapturing(true)}
-            onStopCapture={() => setCapturing(false)}
+            accessibilityMissing={accessibilityMissing}
+            onGrantAccessibility={grantAccessibility}
+            onStartCapture={startHotkeyCapture}
+            onStopCapture={stopHotkeyCapture}
             onResetDefault={() => {
               setHotkey(DEFAULT_HOTKEY);
               setHotkeyWarning(null);
@@ -385,6 +483,7 @@ const SetupWizard: React.FC = () => {
             micAvailable={micAvailable}
             micName={micName}
             monitoring={micMonitoring}
+            captureInfo={captureInfo}
             inputDevices={inputDevices}
             selectedInputDevice={selectedInputDevice}
             refreshingDevices={refreshingDevices}
@@ -402,7 +501,8 @@ const SetupWizard: React.FC = () => {
                     setMicMonitoring(false);
                     const micInfo = await detectMic();
                     if (micInfo.available) {
-                      await invoke('start_mic_monitor');
+                      const info = await invoke<CaptureInfo>('start_mic_monitor');
+                      setCaptureInfo(info);
                       setMicMonitoring(true);
                     }
                   }
@@ -4,6 +4,9 @@ interface Step3HotkeyProps {
   hotkey: string;
   capturing: boolean;
   warning?: string | null;
+  /** macOS has not granted Accessibility, which pasting and shortcut capture need */
+  accessibilityMissing?: boolean;
+  onGrantAccessibility?: () => void;
   onStartCapture: () => void;
   onStopCapture: () => void;
   onResetDefault: () => void;
@@ -22,6 +25,8 @@ const Step3Hotkey: React.FC<Step3HotkeyProps> = ({
   hotkey,
   capturing,
   warning,
+  accessibilityMissing,
+  onGrantAccessibility,
   onStartCapture,
   onStopCapture,
   onResetDefault,
@@ -58,6 +63,15 @@ const Step3Hotkey: React.FC<Step3HotkeyProps> = ({
 
       {warning ? <div className="setup-warning">{warning}</div> : null}
 
+      {accessibilityMissing ? (
+        <div className="setup-warning">
+          Zentra needs Accessibility access to paste your dictation.{' '}
+          <button type="button" className="setup-link" onClick={onGrantAccessibility}>
+            Grant access
+          </button>
+        </div>
+      ) : null}
+
       <button type="button" className="setup-link" onClick={onResetDefault}>
         Use default: Ctrl+Shift+Space
       </button>
@@ -1,10 +1,12 @@
 import React, { useMemo } from 'react';
-import { useAudioLevel } from '../../hooks/useAudioLevel';
+import { useAudioLevel, useMicGuidance, type MicGuidance } from '../../hooks/useAudioLevel';
+import type { CaptureInfo } from '../types';
 
 interface Step4MicTestProps {
   micAvailable: boolean;
   micName: string;
   monitoring: boolean;
+  captureInfo: CaptureInfo | null;
   inputDevices: string[];
   selectedInputDevice: string;
   refreshingDevices: boolean;
@@ -14,10 +16,17 @@ interface Step4MicTestProps {
 
 const BAR_COUNT = 24;
 
+const GUIDANCE: Record<MicGuidance, { tone: 'success' | 'error'; text: string }> = {
+  loud: { tone: 'error', text: 'Too loud: the input is clipping. Move back or lower the input volume.' },
+  quiet: { tone: 'error', text: 'Too quiet: move closer or raise the input volume.' },
+  good: { tone: 'success', text: 'Sounds good.' },
+};
+
 const Step4MicTest: React.FC<Step4MicTestProps> = ({
   micAvailable,
   micName,
   monitoring,
+  captureInfo,
   inputDevices,
   selectedInputDevice,
   refreshingDevices,
@@ -25,6 +34,7 @@ const Step4MicTest: React.FC<Step4MicTestProps> = ({
   onSelectInputDevice,
 }) => {
   const level = useAudioLevel(monitoring);
+  const guidance = useMicGuidance(monitoring && micAvailable);
 
   const bars = useMemo(() => {
     const now = Date.now() * 0.01;
@@ -38,6 +48,10 @@ const Step4MicTest: React.FC<Step4MicTestProps> = ({
     });
   }, [level, micAvailable]);
 
+  const captureFormat = captureInfo
+    ? ` • ${(captureInfo.sampleRate / 1000).toFixed(1)} kHz ${captureInfo.channels === 1 ? 'mono' : `${captureInfo.channels} ch`}`
+    : '';
+
   const volumePct = Math.round(level * 100);
   const meterClass = volumePct < 45 ? 'low' : volumePct < 75 ? 'mid' : 'high';
 
@@ -74,7 +88,10 @@ const Step4MicTest: React.FC<Step4MicTestProps> = ({
       </div>
 
       {micAvailable ? (
-        <div className="setup-mic-status success">Microphone detected • {micName || 'Default input'}</div>
+        <div className="setup-mic-status success">
+          Microphone detected • {micName || 'Default input'}
+          {captureFormat}
+        </div>
       ) : (
         <div className="setup-mic-status error">
           No microphone found. Check system permissions and try again.
@@ -89,6 +106,12 @@ const Step4MicTest: React.FC<Step4MicTestProps> = ({
         <span>{volumePct}%</span>
       </div>
 
+      {guidance && (
+        <div className={`setup-mic-status ${GUIDANCE[guidance].tone}`} role="status">
+          {GUIDANCE[guidance].text}
+        </div>
+      )}
+
       {!micAvailable && (
         <button type="button" className="setup-primary-outline-btn" onClick={onRetryDetect}>
           Retry detection
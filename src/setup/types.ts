@@ -28,3 +28,39 @@ export interface SaveSetupPartialPayload {
   hotkey?: string;
   language?: 'pt' | 'en' | 'auto';
 }
+
+export interface CaptureInfo {
+  deviceName: string;
+  /** Audio host the device was opened on (WASAPI, ALSA, CoreAudio, ...) */
+  host: string;
+  sampleRate: number;
+  channels: number;
+  sampleFormat: string;
+  preRollMs: number;
+}
+
+/** Payload of `input-devices-changed`, sent when a device is plugged in or removed. */
+export interface InputDevicesChanged {
+  devices: string[];
+  selected?: string | null;
+  selectedMissing: boolean;
+}
+
+/** Result of `check_accessibility_permission`; only macOS requires the grant. */
+export interface AccessibilityPermission {
+  required: boolean;
+  granted: boolean;
+}
+
+/** Payload of `hotkey-captured`: the chord pressed after `begin_hotkey_capture`, or a timeout. */
+export interface HotkeyCaptured {
+  accelerator?: string | null;
+  timedOut: boolean;
+}
+
+/** Payload of `recording-error`: capture stopped on its own, e.g. the mic was unplugged. */
+export interface RecordingError {
+  code: string;
+  message: string;
+  device?: string | null;
+}
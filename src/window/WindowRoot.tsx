@@ -3,6 +3,7 @@ import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import App from '../App';
 import SetupWizard from '../setup/SetupWizard';
 import Dashboard from '../dashboard/Dashboard';
+import MiniHistory from '../mini/MiniHistory';
 
 function detectWindowLabel(): string {
   try {
@@ -24,6 +25,10 @@ const WindowRoot: React.FC = () => {
     return <Dashboard />;
   }
 
+  if (label === 'mini-history') {
+    return <MiniHistory />;
+  }
+
   return <App />;
 };
 